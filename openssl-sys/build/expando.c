@@ -51,6 +51,10 @@ RUST_CONF_OPENSSL_NO_CMS
 RUST_CONF_OPENSSL_NO_COMP
 #endif
 
+#ifdef OPENSSL_NO_CT
+RUST_CONF_OPENSSL_NO_CT
+#endif
+
 #ifdef OPENSSL_NO_EC
 RUST_CONF_OPENSSL_NO_EC
 #endif
@@ -134,3 +138,7 @@ RUST_CONF_OPENSSL_NO_DEPRECATED_3_0
 #ifdef OPENSSL_NO_SEED
 RUST_CONF_OPENSSL_NO_SEED
 #endif
+
+#if !defined(OPENSSL_THREADS) && !defined(LIBRESSL_VERSION_NUMBER)
+RUST_CONF_OPENSSL_NO_THREADS
+#endif
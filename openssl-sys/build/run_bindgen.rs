@@ -52,8 +52,18 @@ const INCLUDES: &str = "
 #include <openssl/kdf.h>
 #endif
 
+#if !defined(LIBRESSL_VERSION_NUMBER) && !defined(OPENSSL_IS_BORINGSSL) && OPENSSL_VERSION_NUMBER >= 0x10100000 && !defined(OPENSSL_NO_CT)
+#include <openssl/ct.h>
+#endif
+
 #if OPENSSL_VERSION_NUMBER >= 0x30000000
 #include <openssl/provider.h>
+#include <openssl/store.h>
+#include <openssl/ui.h>
+#endif
+
+#if !defined(LIBRESSL_VERSION_NUMBER) && OPENSSL_VERSION_NUMBER >= 0x30200000
+#include <openssl/hpke.h>
 #endif
 
 #if defined(LIBRESSL_VERSION_NUMBER) || defined(OPENSSL_IS_BORINGSSL)
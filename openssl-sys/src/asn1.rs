@@ -2,6 +2,17 @@ use libc::*;
 
 use super::*;
 
+pub const V_ASN1_UNIVERSAL: c_int = 0x00;
+pub const V_ASN1_APPLICATION: c_int = 0x40;
+pub const V_ASN1_CONTEXT_SPECIFIC: c_int = 0x80;
+pub const V_ASN1_PRIVATE: c_int = 0xc0;
+
+pub const V_ASN1_CONSTRUCTED: c_int = 0x20;
+
+pub const ASN1_R_WRONG_TAG: c_int = 168;
+
+pub const V_ASN1_UNDEF: c_int = -1;
+
 // ASN.1 tag values
 pub const V_ASN1_EOC: c_int = 0;
 pub const V_ASN1_BOOLEAN: c_int = 1;
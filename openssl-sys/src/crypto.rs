@@ -109,6 +109,8 @@ cfg_if! {
     if #[cfg(ossl110)] {
         pub const CRYPTO_EX_INDEX_SSL: c_int = 0;
         pub const CRYPTO_EX_INDEX_SSL_CTX: c_int = 1;
+        pub const CRYPTO_EX_INDEX_EC_KEY: c_int = 8;
+        pub const CRYPTO_EX_INDEX_RSA: c_int = 9;
     } else if #[cfg(libressl)] {
         pub const CRYPTO_EX_INDEX_SSL: c_int = 1;
         pub const CRYPTO_EX_INDEX_SSL_CTX: c_int = 2;
@@ -0,0 +1,13 @@
+use super::*;
+
+pub const CT_LOG_ENTRY_TYPE_NOT_SET: ct_log_entry_type_t = -1;
+pub const CT_LOG_ENTRY_TYPE_X509: ct_log_entry_type_t = 0;
+pub const CT_LOG_ENTRY_TYPE_PRECERT: ct_log_entry_type_t = 1;
+
+pub const SCT_VERSION_NOT_SET: sct_version_t = -1;
+pub const SCT_VERSION_V1: sct_version_t = 0;
+
+pub const SCT_SOURCE_UNKNOWN: sct_source_t = 0;
+pub const SCT_SOURCE_TLS_EXTENSION: sct_source_t = 1;
+pub const SCT_SOURCE_X509V3_EXTENSION: sct_source_t = 2;
+pub const SCT_SOURCE_OCSP_STAPLED_RESPONSE: sct_source_t = 3;
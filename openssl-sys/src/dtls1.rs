@@ -1,4 +1,7 @@
 use libc::*;
+use std::ptr;
+
+use super::*;
 
 cfg_if! {
     if #[cfg(ossl300)] {
@@ -7,3 +10,28 @@ cfg_if! {
         pub const DTLS1_COOKIE_LENGTH: c_uint = 256;
     }
 }
+
+pub const DTLS_CTRL_GET_TIMEOUT: c_int = 73;
+pub const DTLS_CTRL_HANDLE_TIMEOUT: c_int = 74;
+#[cfg(ossl102)]
+pub const DTLS_CTRL_SET_LINK_MTU: c_int = 120;
+#[cfg(ossl102)]
+pub const DTLS_CTRL_GET_LINK_MIN_MTU: c_int = 121;
+
+pub unsafe fn DTLSv1_get_timeout(ssl: *mut SSL, timeval: *mut timeval) -> c_long {
+    SSL_ctrl(ssl, DTLS_CTRL_GET_TIMEOUT, 0, timeval as *mut c_void)
+}
+
+pub unsafe fn DTLSv1_handle_timeout(ssl: *mut SSL) -> c_long {
+    SSL_ctrl(ssl, DTLS_CTRL_HANDLE_TIMEOUT, 0, ptr::null_mut())
+}
+
+#[cfg(ossl102)]
+pub unsafe fn DTLS_set_link_mtu(ssl: *mut SSL, mtu: c_long) -> c_long {
+    SSL_ctrl(ssl, DTLS_CTRL_SET_LINK_MTU, mtu, ptr::null_mut())
+}
+
+#[cfg(ossl102)]
+pub unsafe fn DTLS_get_link_min_mtu(ssl: *mut SSL) -> c_long {
+    SSL_ctrl(ssl, DTLS_CTRL_GET_LINK_MIN_MTU, 0, ptr::null_mut())
+}
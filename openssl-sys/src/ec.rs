@@ -14,3 +14,82 @@ pub unsafe fn EVP_EC_gen(curve: *const c_char) -> *mut EVP_PKEY {
         curve,
     )
 }
+
+#[cfg(ossl110)]
+pub const EVP_PKEY_CTRL_EC_KDF_TYPE: c_int = EVP_PKEY_ALG_CTRL + 4;
+#[cfg(ossl110)]
+pub const EVP_PKEY_CTRL_EC_KDF_MD: c_int = EVP_PKEY_ALG_CTRL + 5;
+#[cfg(ossl110)]
+pub const EVP_PKEY_CTRL_EC_KDF_OUTLEN: c_int = EVP_PKEY_ALG_CTRL + 7;
+#[cfg(ossl110)]
+pub const EVP_PKEY_CTRL_EC_KDF_UKM: c_int = EVP_PKEY_ALG_CTRL + 9;
+
+#[cfg(ossl110)]
+pub const EVP_PKEY_ECDH_KDF_NONE: c_int = 1;
+#[cfg(ossl110)]
+pub const EVP_PKEY_ECDH_KDF_X9_62: c_int = 2;
+#[cfg(ossl110)]
+pub const EVP_PKEY_ECDH_KDF_X9_63: c_int = EVP_PKEY_ECDH_KDF_X9_62;
+
+cfg_if! {
+    if #[cfg(all(ossl110, not(ossl300)))] {
+        pub unsafe fn EVP_PKEY_CTX_set_ecdh_kdf_type(ctx: *mut EVP_PKEY_CTX, kdf: c_int) -> c_int {
+            EVP_PKEY_CTX_ctrl(
+                ctx,
+                EVP_PKEY_EC,
+                EVP_PKEY_OP_DERIVE,
+                EVP_PKEY_CTRL_EC_KDF_TYPE,
+                kdf,
+                ptr::null_mut(),
+            )
+        }
+
+        pub unsafe fn EVP_PKEY_CTX_set_ecdh_kdf_md(ctx: *mut EVP_PKEY_CTX, md: *const EVP_MD) -> c_int {
+            EVP_PKEY_CTX_ctrl(
+                ctx,
+                EVP_PKEY_EC,
+                EVP_PKEY_OP_DERIVE,
+                EVP_PKEY_CTRL_EC_KDF_MD,
+                0,
+                md as *mut c_void,
+            )
+        }
+
+        pub unsafe fn EVP_PKEY_CTX_set_ecdh_kdf_outlen(ctx: *mut EVP_PKEY_CTX, len: c_int) -> c_int {
+            EVP_PKEY_CTX_ctrl(
+                ctx,
+                EVP_PKEY_EC,
+                EVP_PKEY_OP_DERIVE,
+                EVP_PKEY_CTRL_EC_KDF_OUTLEN,
+                len,
+                ptr::null_mut(),
+            )
+        }
+
+        pub unsafe fn EVP_PKEY_CTX_set0_ecdh_kdf_ukm(
+            ctx: *mut EVP_PKEY_CTX,
+            ukm: *mut c_uchar,
+            len: c_int,
+        ) -> c_int {
+            EVP_PKEY_CTX_ctrl(
+                ctx,
+                EVP_PKEY_EC,
+                EVP_PKEY_OP_DERIVE,
+                EVP_PKEY_CTRL_EC_KDF_UKM,
+                len,
+                ukm as *mut c_void,
+            )
+        }
+    }
+}
+
+#[cfg(ossl110)]
+pub unsafe fn EC_KEY_get_ex_new_index(
+    l: c_long,
+    p: *mut c_void,
+    newf: Option<CRYPTO_EX_new>,
+    dupf: Option<CRYPTO_EX_dup>,
+    freef: Option<CRYPTO_EX_free>,
+) -> c_int {
+    CRYPTO_get_ex_new_index(CRYPTO_EX_INDEX_EC_KEY, l, p, newf, dupf, freef)
+}
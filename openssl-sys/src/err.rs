@@ -3,9 +3,38 @@ use libc::*;
 pub const ERR_TXT_MALLOCED: c_int = 0x01;
 pub const ERR_TXT_STRING: c_int = 0x02;
 
+pub const ERR_LIB_NONE: c_int = 1;
 pub const ERR_LIB_SYS: c_int = 2;
+pub const ERR_LIB_BN: c_int = 3;
+pub const ERR_LIB_RSA: c_int = 4;
+pub const ERR_LIB_DH: c_int = 5;
+pub const ERR_LIB_EVP: c_int = 6;
+pub const ERR_LIB_BUF: c_int = 7;
+pub const ERR_LIB_OBJ: c_int = 8;
 pub const ERR_LIB_PEM: c_int = 9;
+pub const ERR_LIB_DSA: c_int = 10;
+pub const ERR_LIB_X509: c_int = 11;
 pub const ERR_LIB_ASN1: c_int = 13;
+pub const ERR_LIB_CONF: c_int = 14;
+pub const ERR_LIB_CRYPTO: c_int = 15;
+pub const ERR_LIB_EC: c_int = 16;
+pub const ERR_LIB_SSL: c_int = 20;
+pub const ERR_LIB_BIO: c_int = 32;
+pub const ERR_LIB_PKCS7: c_int = 33;
+pub const ERR_LIB_X509V3: c_int = 34;
+pub const ERR_LIB_PKCS12: c_int = 35;
+pub const ERR_LIB_RAND: c_int = 36;
+pub const ERR_LIB_ENGINE: c_int = 38;
+pub const ERR_LIB_OCSP: c_int = 39;
+pub const ERR_LIB_CMS: c_int = 46;
+pub const ERR_LIB_KDF: c_int = 52;
+#[cfg(ossl300)]
+pub const ERR_LIB_PROV: c_int = 57;
+#[cfg(ossl300)]
+pub const ERR_LIB_OSSL_ENCODER: c_int = 59;
+#[cfg(ossl300)]
+pub const ERR_LIB_OSSL_DECODER: c_int = 60;
+pub const ERR_LIB_USER: c_int = 128;
 
 cfg_if! {
     if #[cfg(ossl300)] {
@@ -19,6 +48,7 @@ cfg_if! {
         pub const ERR_REASON_MASK: c_ulong = 0x7FFFFF;
 
         pub const ERR_RFLAG_FATAL: c_ulong = 0x1 << ERR_RFLAGS_OFFSET;
+        pub const ERR_RFLAG_COMMON: c_ulong = 0x2 << ERR_RFLAGS_OFFSET;
 
         pub const fn ERR_SYSTEM_ERROR(errcode: c_ulong) -> bool {
             errcode & ERR_SYSTEM_FLAG != 0
@@ -64,3 +94,46 @@ cfg_if! {
         }
     }
 }
+
+cfg_if! {
+    if #[cfg(ossl300)] {
+        const ERR_R_COMMON: c_int = ERR_RFLAG_COMMON as c_int;
+        pub const ERR_R_FATAL: c_int = ERR_RFLAG_FATAL as c_int | ERR_R_COMMON;
+
+        pub const ERR_R_MALLOC_FAILURE: c_int = 256 | ERR_R_FATAL;
+        pub const ERR_R_SHOULD_NOT_HAVE_BEEN_CALLED: c_int = 257 | ERR_R_FATAL;
+        pub const ERR_R_PASSED_NULL_PARAMETER: c_int = 258 | ERR_R_FATAL;
+        pub const ERR_R_INTERNAL_ERROR: c_int = 259 | ERR_R_FATAL;
+        pub const ERR_R_DISABLED: c_int = 260 | ERR_R_FATAL;
+        pub const ERR_R_PASSED_INVALID_ARGUMENT: c_int = 262 | ERR_R_COMMON;
+        pub const ERR_R_NESTED_ASN1_ERROR: c_int = 266 | ERR_R_COMMON;
+        pub const ERR_R_MISSING_ASN1_EOS: c_int = 267 | ERR_R_COMMON;
+        pub const ERR_R_UNSUPPORTED: c_int = 268 | ERR_R_COMMON;
+    } else {
+        const ERR_R_COMMON: c_int = 0;
+        pub const ERR_R_FATAL: c_int = 64;
+
+        pub const ERR_R_MALLOC_FAILURE: c_int = 1 | ERR_R_FATAL;
+        pub const ERR_R_SHOULD_NOT_HAVE_BEEN_CALLED: c_int = 2 | ERR_R_FATAL;
+        pub const ERR_R_PASSED_NULL_PARAMETER: c_int = 3 | ERR_R_FATAL;
+        pub const ERR_R_INTERNAL_ERROR: c_int = 4 | ERR_R_FATAL;
+        pub const ERR_R_DISABLED: c_int = 5 | ERR_R_FATAL;
+        #[cfg(ossl111)]
+        pub const ERR_R_PASSED_INVALID_ARGUMENT: c_int = 7;
+        pub const ERR_R_NESTED_ASN1_ERROR: c_int = 58;
+        pub const ERR_R_MISSING_ASN1_EOS: c_int = 63;
+    }
+}
+
+pub const ERR_R_BN_LIB: c_int = ERR_LIB_BN | ERR_R_COMMON;
+pub const ERR_R_RSA_LIB: c_int = ERR_LIB_RSA | ERR_R_COMMON;
+pub const ERR_R_EVP_LIB: c_int = ERR_LIB_EVP | ERR_R_COMMON;
+pub const ERR_R_BUF_LIB: c_int = ERR_LIB_BUF | ERR_R_COMMON;
+pub const ERR_R_OBJ_LIB: c_int = ERR_LIB_OBJ | ERR_R_COMMON;
+pub const ERR_R_PEM_LIB: c_int = ERR_LIB_PEM | ERR_R_COMMON;
+pub const ERR_R_X509_LIB: c_int = ERR_LIB_X509 | ERR_R_COMMON;
+pub const ERR_R_ASN1_LIB: c_int = ERR_LIB_ASN1 | ERR_R_COMMON;
+pub const ERR_R_EC_LIB: c_int = ERR_LIB_EC | ERR_R_COMMON;
+pub const ERR_R_SSL_LIB: c_int = ERR_LIB_SSL | ERR_R_COMMON;
+pub const ERR_R_BIO_LIB: c_int = ERR_LIB_BIO | ERR_R_COMMON;
+pub const ERR_R_X509V3_LIB: c_int = ERR_LIB_X509V3 | ERR_R_COMMON;
@@ -6,6 +6,14 @@ pub const EVP_MAX_MD_SIZE: c_uint = 64;
 pub const PKCS5_SALT_LEN: c_int = 8;
 pub const PKCS12_DEFAULT_ITER: c_int = 2048;
 
+pub const EVP_R_BAD_DECRYPT: c_int = 100;
+pub const EVP_R_UNSUPPORTED_CIPHER: c_int = 107;
+pub const EVP_R_WRONG_FINAL_BLOCK_LENGTH: c_int = 109;
+pub const EVP_R_DATA_NOT_MULTIPLE_OF_BLOCK_LENGTH: c_int = 138;
+pub const EVP_R_OPERATION_NOT_SUPPORTED_FOR_THIS_KEYTYPE: c_int = 150;
+#[cfg(ossl300)]
+pub const EVP_R_UNSUPPORTED_ALGORITHM: c_int = 156;
+
 pub const EVP_PKEY_RSA: c_int = NID_rsaEncryption;
 pub const EVP_PKEY_DSA: c_int = NID_dsa;
 pub const EVP_PKEY_DH: c_int = NID_dhKeyAgreement;
@@ -30,6 +38,19 @@ pub const EVP_PKEY_HKDF: c_int = NID_hkdf;
 #[cfg(ossl102)]
 pub const EVP_CIPHER_CTX_FLAG_WRAP_ALLOW: c_int = 0x1;
 
+#[cfg(ossl110)]
+pub const EVP_CIPH_FLAG_PIPELINE: c_ulong = 0x800000;
+pub const EVP_CIPH_MODE: c_ulong = 0xF0007;
+pub const EVP_CIPH_CFB_MODE: c_ulong = 0x3;
+pub const EVP_CIPH_OFB_MODE: c_ulong = 0x4;
+
+#[cfg(ossl110)]
+pub const EVP_CTRL_SET_PIPELINE_OUTPUT_BUFS: c_int = 0x22;
+#[cfg(ossl110)]
+pub const EVP_CTRL_SET_PIPELINE_INPUT_BUFS: c_int = 0x23;
+#[cfg(ossl110)]
+pub const EVP_CTRL_SET_PIPELINE_INPUT_LENS: c_int = 0x24;
+
 pub const EVP_CTRL_GCM_SET_IVLEN: c_int = 0x9;
 pub const EVP_CTRL_GCM_GET_TAG: c_int = 0x10;
 pub const EVP_CTRL_GCM_SET_TAG: c_int = 0x11;
@@ -45,6 +66,11 @@ cfg_if! {
             EVP_MD_CTX_get0_md(ctx)
         }
 
+        #[inline]
+        pub unsafe fn EVP_MD_CTX_pkey_ctx(ctx: *const EVP_MD_CTX) -> *mut EVP_PKEY_CTX {
+            EVP_MD_CTX_get_pkey_ctx(ctx)
+        }
+
         #[inline]
         pub unsafe fn EVP_MD_CTX_get_size(ctx: *const EVP_MD_CTX) -> c_int {
             EVP_MD_get_size(EVP_MD_CTX_get0_md(ctx))
@@ -90,6 +116,11 @@ cfg_if! {
             EVP_CIPHER_get_nid(cipher)
         }
 
+        #[inline]
+        pub unsafe fn EVP_CIPHER_flags(cipher: *const EVP_CIPHER) -> c_ulong {
+            EVP_CIPHER_get_flags(cipher)
+        }
+
         #[inline]
         pub unsafe fn EVP_CIPHER_CTX_block_size(ctx: *const EVP_CIPHER_CTX) -> c_int {
             EVP_CIPHER_CTX_get_block_size(ctx)
@@ -56,6 +56,8 @@ extern "C" {
     pub fn ASN1_STRING_free(x: *mut ASN1_STRING);
     pub fn ASN1_STRING_length(x: *const ASN1_STRING) -> c_int;
     pub fn ASN1_STRING_set(x: *mut ASN1_STRING, data: *const c_void, len_in: c_int) -> c_int;
+    pub fn ASN1_STRING_copy(dst: *mut ASN1_STRING, str: *const ASN1_STRING) -> c_int;
+    pub fn ASN1_STRING_dup(a: *const ASN1_STRING) -> *mut ASN1_STRING;
     pub fn ASN1_OCTET_STRING_set(
         x: *mut ASN1_OCTET_STRING,
         data: *const c_uchar,
@@ -63,6 +65,11 @@ extern "C" {
     ) -> c_int;
 
     pub fn ASN1_BIT_STRING_free(x: *mut ASN1_BIT_STRING);
+    pub fn d2i_ASN1_BIT_STRING(
+        a: *mut *mut ASN1_BIT_STRING,
+        pp: *mut *const c_uchar,
+        length: c_long,
+    ) -> *mut ASN1_BIT_STRING;
     pub fn ASN1_OCTET_STRING_free(x: *mut ASN1_OCTET_STRING);
 
     pub fn ASN1_GENERALIZEDTIME_free(tm: *mut ASN1_GENERALIZEDTIME);
@@ -103,6 +110,28 @@ extern "C" {
         buf: *mut *const u8,
         len: c_long,
     ) -> *mut ASN1_TYPE;
+
+    pub fn d2i_ASN1_OBJECT(
+        a: *mut *mut ASN1_OBJECT,
+        pp: *mut *const c_uchar,
+        length: c_long,
+    ) -> *mut ASN1_OBJECT;
+
+    pub fn ASN1_get_object(
+        pp: *mut *const c_uchar,
+        plength: *mut c_long,
+        ptag: *mut c_int,
+        pclass: *mut c_int,
+        omax: c_long,
+    ) -> c_int;
+    pub fn ASN1_put_object(
+        pp: *mut *mut c_uchar,
+        constructed: c_int,
+        length: c_int,
+        tag: c_int,
+        xclass: c_int,
+    );
+    pub fn ASN1_object_size(constructed: c_int, length: c_int, tag: c_int) -> c_int;
 }
 
 const_ptr_api! {
@@ -111,5 +140,6 @@ const_ptr_api! {
         pub fn ASN1_STRING_type(x: #[const_ptr_if(any(ossl110, libressl280))]  ASN1_STRING) -> c_int;
         pub fn ASN1_generate_v3(str: #[const_ptr_if(any(ossl110, libressl280))] c_char, cnf: *mut X509V3_CTX) -> *mut ASN1_TYPE;
         pub fn i2d_ASN1_TYPE(a: #[const_ptr_if(ossl300)] ASN1_TYPE, pp: *mut *mut c_uchar) -> c_int;
+        pub fn i2d_ASN1_OBJECT(a: #[const_ptr_if(any(ossl110, libressl280))] ASN1_OBJECT, pp: *mut *mut c_uchar) -> c_int;
     }
 }
@@ -2,6 +2,9 @@ use super::super::*;
 use libc::*;
 
 pub enum CMS_ContentInfo {}
+pub enum CMS_SignerInfo {}
+
+stack!(stack_st_CMS_SignerInfo);
 
 extern "C" {
     #[cfg(ossl101)]
@@ -26,6 +29,22 @@ extern "C" {
     #[cfg(ossl101)]
     pub fn SMIME_read_CMS(bio: *mut BIO, bcont: *mut *mut BIO) -> *mut CMS_ContentInfo;
 
+    #[cfg(ossl101)]
+    pub fn SMIME_write_CMS(
+        out: *mut BIO,
+        cms: *mut CMS_ContentInfo,
+        data: *mut BIO,
+        flags: c_int,
+    ) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn i2d_CMS_bio_stream(
+        out: *mut BIO,
+        cms: *mut CMS_ContentInfo,
+        data: *mut BIO,
+        flags: c_int,
+    ) -> c_int;
+
     #[cfg(ossl101)]
     pub fn CMS_sign(
         signcert: *mut X509,
@@ -62,4 +81,85 @@ extern "C" {
         out: *mut BIO,
         flags: c_uint,
     ) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_compress(in_: *mut BIO, comp_nid: c_int, flags: c_uint) -> *mut CMS_ContentInfo;
+
+    #[cfg(ossl101)]
+    pub fn CMS_uncompress(
+        cms: *mut CMS_ContentInfo,
+        dcont: *mut BIO,
+        out: *mut BIO,
+        flags: c_uint,
+    ) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_add1_signer(
+        cms: *mut CMS_ContentInfo,
+        signer: *mut X509,
+        pk: *mut EVP_PKEY,
+        md: *const EVP_MD,
+        flags: c_uint,
+    ) -> *mut CMS_SignerInfo;
+
+    #[cfg(ossl101)]
+    pub fn CMS_SignerInfo_sign(si: *mut CMS_SignerInfo) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_final(
+        cms: *mut CMS_ContentInfo,
+        data: *mut BIO,
+        dcont: *mut BIO,
+        flags: c_uint,
+    ) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_signed_add1_attr_by_NID(
+        si: *mut CMS_SignerInfo,
+        nid: c_int,
+        type_: c_int,
+        bytes: *const c_void,
+        len: c_int,
+    ) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_get0_SignerInfos(cms: *mut CMS_ContentInfo) -> *mut stack_st_CMS_SignerInfo;
+
+    #[cfg(ossl110)]
+    pub fn CMS_SignerInfo_get0_signature(si: *mut CMS_SignerInfo) -> *mut ASN1_OCTET_STRING;
+
+    #[cfg(ossl101)]
+    pub fn CMS_SignerInfo_get0_algs(
+        si: *mut CMS_SignerInfo,
+        pk: *mut *mut EVP_PKEY,
+        signer: *mut *mut X509,
+        pdig: *mut *mut X509_ALGOR,
+        psig: *mut *mut X509_ALGOR,
+    );
+
+    #[cfg(ossl101)]
+    pub fn CMS_signed_get_attr_by_NID(
+        si: *const CMS_SignerInfo,
+        nid: c_int,
+        lastpos: c_int,
+    ) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_unsigned_get_attr_by_NID(
+        si: *const CMS_SignerInfo,
+        nid: c_int,
+        lastpos: c_int,
+    ) -> c_int;
+
+    #[cfg(ossl101)]
+    pub fn CMS_unsigned_get_attr(si: *const CMS_SignerInfo, loc: c_int) -> *mut X509_ATTRIBUTE;
+
+    #[cfg(ossl101)]
+    pub fn CMS_unsigned_add1_attr_by_NID(
+        si: *mut CMS_SignerInfo,
+        nid: c_int,
+        type_: c_int,
+        bytes: *const c_void,
+        len: c_int,
+    ) -> c_int;
 }
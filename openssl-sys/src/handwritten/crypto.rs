@@ -18,6 +18,8 @@ cfg_if! {
 }
 
 extern "C" {
+    pub fn OPENSSL_cleanse(ptr: *mut c_void, len: size_t);
+
     #[cfg(any(ossl110, libressl))]
     pub fn CRYPTO_get_ex_new_index(
         class_index: c_int,
@@ -61,6 +63,22 @@ cfg_if! {
         extern "C" {
             pub fn CRYPTO_malloc(num: size_t, file: *const c_char, line: c_int) -> *mut c_void;
             pub fn CRYPTO_free(buf: *mut c_void, file: *const c_char, line: c_int);
+            pub fn CRYPTO_set_mem_functions(
+                malloc_fn: Option<
+                    unsafe extern "C" fn(num: size_t, file: *const c_char, line: c_int) -> *mut c_void,
+                >,
+                realloc_fn: Option<
+                    unsafe extern "C" fn(
+                        addr: *mut c_void,
+                        num: size_t,
+                        file: *const c_char,
+                        line: c_int,
+                    ) -> *mut c_void,
+                >,
+                free_fn: Option<
+                    unsafe extern "C" fn(addr: *mut c_void, file: *const c_char, line: c_int),
+                >,
+            ) -> c_int;
         }
     } else {
         extern "C" {
@@ -0,0 +1,33 @@
+use super::super::*;
+use libc::*;
+
+pub enum SCT {}
+
+stack!(stack_st_SCT);
+
+pub type ct_log_entry_type_t = c_int;
+pub type sct_version_t = c_int;
+pub type sct_source_t = c_uint;
+
+extern "C" {
+    pub fn SCT_free(sct: *mut SCT);
+    pub fn SCT_LIST_free(a: *mut stack_st_SCT);
+
+    pub fn SCT_get_version(sct: *const SCT) -> sct_version_t;
+    pub fn SCT_get_log_entry_type(sct: *const SCT) -> ct_log_entry_type_t;
+    pub fn SCT_get0_log_id(sct: *const SCT, log_id: *mut *mut c_uchar) -> size_t;
+    pub fn SCT_get_timestamp(sct: *const SCT) -> u64;
+    pub fn SCT_get_signature_nid(sct: *const SCT) -> c_int;
+    pub fn SCT_get0_extensions(sct: *const SCT, ext: *mut *mut c_uchar) -> size_t;
+    pub fn SCT_get0_signature(sct: *const SCT, sig: *mut *mut c_uchar) -> size_t;
+    pub fn SCT_get_source(sct: *const SCT) -> sct_source_t;
+
+    pub fn i2o_SCT_LIST(a: *const stack_st_SCT, pp: *mut *mut c_uchar) -> c_int;
+    pub fn o2i_SCT_LIST(
+        a: *mut *mut stack_st_SCT,
+        pp: *mut *const c_uchar,
+        len: size_t,
+    ) -> *mut stack_st_SCT;
+    pub fn i2o_SCT(sct: *const SCT, out: *mut *mut c_uchar) -> c_int;
+    pub fn o2i_SCT(psct: *mut *mut SCT, in_: *mut *const c_uchar, len: size_t) -> *mut SCT;
+}
@@ -268,4 +268,64 @@ extern "C" {
     ) -> *mut ECDSA_SIG;
 
     pub fn i2d_ECDSA_SIG(sig: *const ECDSA_SIG, out: *mut *mut c_uchar) -> c_int;
+
+    pub fn ECDSA_size(eckey: *const EC_KEY) -> c_int;
+}
+
+cfg_if! {
+    if #[cfg(ossl300)] {
+        extern "C" {
+            pub fn EVP_PKEY_CTX_set_ecdh_kdf_type(ctx: *mut EVP_PKEY_CTX, kdf: c_int) -> c_int;
+            pub fn EVP_PKEY_CTX_set_ecdh_kdf_md(ctx: *mut EVP_PKEY_CTX, md: *const EVP_MD) -> c_int;
+            pub fn EVP_PKEY_CTX_set_ecdh_kdf_outlen(ctx: *mut EVP_PKEY_CTX, len: c_int) -> c_int;
+            pub fn EVP_PKEY_CTX_set0_ecdh_kdf_ukm(
+                ctx: *mut EVP_PKEY_CTX,
+                ukm: *mut c_uchar,
+                len: c_int,
+            ) -> c_int;
+        }
+    }
+}
+
+#[cfg(ossl110)]
+extern "C" {
+    pub fn EC_KEY_OpenSSL() -> *const EC_KEY_METHOD;
+    pub fn EC_KEY_set_method(key: *mut EC_KEY, meth: *const EC_KEY_METHOD) -> c_int;
+    pub fn EC_KEY_set_ex_data(key: *mut EC_KEY, idx: c_int, arg: *mut c_void) -> c_int;
+    pub fn EC_KEY_get_ex_data(key: *const EC_KEY, idx: c_int) -> *mut c_void;
+
+    pub fn EC_KEY_METHOD_new(meth: *const EC_KEY_METHOD) -> *mut EC_KEY_METHOD;
+    pub fn EC_KEY_METHOD_free(meth: *mut EC_KEY_METHOD);
+    pub fn EC_KEY_METHOD_set_sign(
+        meth: *mut EC_KEY_METHOD,
+        sign: Option<
+            unsafe extern "C" fn(
+                type_: c_int,
+                dgst: *const c_uchar,
+                dlen: c_int,
+                sig: *mut c_uchar,
+                siglen: *mut c_uint,
+                kinv: *const BIGNUM,
+                r: *const BIGNUM,
+                eckey: *mut EC_KEY,
+            ) -> c_int,
+        >,
+        sign_setup: Option<
+            unsafe extern "C" fn(
+                eckey: *mut EC_KEY,
+                ctx_in: *mut BN_CTX,
+                kinvp: *mut *mut BIGNUM,
+                rp: *mut *mut BIGNUM,
+            ) -> c_int,
+        >,
+        sign_sig: Option<
+            unsafe extern "C" fn(
+                dgst: *const c_uchar,
+                dgst_len: c_int,
+                in_kinv: *const BIGNUM,
+                in_r: *const BIGNUM,
+                eckey: *mut EC_KEY,
+            ) -> *mut ECDSA_SIG,
+        >,
+    );
 }
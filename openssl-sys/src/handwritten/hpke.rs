@@ -0,0 +1,100 @@
+use super::super::*;
+use libc::*;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct OSSL_HPKE_SUITE {
+    pub kem_id: u16,
+    pub kdf_id: u16,
+    pub aead_id: u16,
+}
+
+extern "C" {
+    pub fn OSSL_HPKE_CTX_new(
+        mode: c_int,
+        suite: OSSL_HPKE_SUITE,
+        role: c_int,
+        libctx: *mut OSSL_LIB_CTX,
+        propq: *const c_char,
+    ) -> *mut OSSL_HPKE_CTX;
+    pub fn OSSL_HPKE_CTX_free(ctx: *mut OSSL_HPKE_CTX);
+
+    pub fn OSSL_HPKE_encap(
+        ctx: *mut OSSL_HPKE_CTX,
+        enc: *mut c_uchar,
+        enclen: *mut size_t,
+        pub_: *const c_uchar,
+        publen: size_t,
+        info: *const c_uchar,
+        infolen: size_t,
+    ) -> c_int;
+    pub fn OSSL_HPKE_seal(
+        ctx: *mut OSSL_HPKE_CTX,
+        ct: *mut c_uchar,
+        ctlen: *mut size_t,
+        aad: *const c_uchar,
+        aadlen: size_t,
+        pt: *const c_uchar,
+        ptlen: size_t,
+    ) -> c_int;
+    pub fn OSSL_HPKE_keygen(
+        suite: OSSL_HPKE_SUITE,
+        pub_: *mut c_uchar,
+        publen: *mut size_t,
+        priv_: *mut *mut EVP_PKEY,
+        ikm: *const c_uchar,
+        ikmlen: size_t,
+        libctx: *mut OSSL_LIB_CTX,
+        propq: *const c_char,
+    ) -> c_int;
+    pub fn OSSL_HPKE_decap(
+        ctx: *mut OSSL_HPKE_CTX,
+        enc: *const c_uchar,
+        enclen: size_t,
+        recippriv: *mut EVP_PKEY,
+        info: *const c_uchar,
+        infolen: size_t,
+    ) -> c_int;
+    pub fn OSSL_HPKE_open(
+        ctx: *mut OSSL_HPKE_CTX,
+        pt: *mut c_uchar,
+        ptlen: *mut size_t,
+        aad: *const c_uchar,
+        aadlen: size_t,
+        ct: *const c_uchar,
+        ctlen: size_t,
+    ) -> c_int;
+    pub fn OSSL_HPKE_export(
+        ctx: *mut OSSL_HPKE_CTX,
+        secret: *mut c_uchar,
+        secretlen: size_t,
+        label: *const c_uchar,
+        labellen: size_t,
+    ) -> c_int;
+
+    pub fn OSSL_HPKE_CTX_set1_authpriv(ctx: *mut OSSL_HPKE_CTX, priv_: *mut EVP_PKEY) -> c_int;
+    pub fn OSSL_HPKE_CTX_set1_authpub(
+        ctx: *mut OSSL_HPKE_CTX,
+        pub_: *const c_uchar,
+        publen: size_t,
+    ) -> c_int;
+    pub fn OSSL_HPKE_CTX_set1_psk(
+        ctx: *mut OSSL_HPKE_CTX,
+        pskid: *const c_char,
+        psk: *const c_uchar,
+        psklen: size_t,
+    ) -> c_int;
+    pub fn OSSL_HPKE_CTX_set1_ikme(
+        ctx: *mut OSSL_HPKE_CTX,
+        ikme: *const c_uchar,
+        ikmelen: size_t,
+    ) -> c_int;
+    pub fn OSSL_HPKE_CTX_set_seq(ctx: *mut OSSL_HPKE_CTX, seq: u64) -> c_int;
+    pub fn OSSL_HPKE_CTX_get_seq(ctx: *mut OSSL_HPKE_CTX, seq: *mut u64) -> c_int;
+
+    pub fn OSSL_HPKE_suite_check(suite: OSSL_HPKE_SUITE) -> c_int;
+    pub fn OSSL_HPKE_str2suite(str_: *const c_char, suite: *mut OSSL_HPKE_SUITE) -> c_int;
+    pub fn OSSL_HPKE_get_ciphertext_size(suite: OSSL_HPKE_SUITE, clearlen: size_t) -> size_t;
+    pub fn OSSL_HPKE_get_public_encap_size(suite: OSSL_HPKE_SUITE) -> size_t;
+    pub fn OSSL_HPKE_get_recommended_ikmelen(suite: OSSL_HPKE_SUITE) -> size_t;
+}
@@ -21,6 +21,21 @@ cfg_if! {
                 info: *const u8,
                 infolen: c_int,
             ) -> c_int;
+
+            pub fn EVP_KDF_fetch(
+                ctx: *mut OSSL_LIB_CTX,
+                algorithm: *const c_char,
+                properties: *const c_char,
+            ) -> *mut EVP_KDF;
+            pub fn EVP_KDF_free(kdf: *mut EVP_KDF);
+            pub fn EVP_KDF_CTX_new(kdf: *mut EVP_KDF) -> *mut EVP_KDF_CTX;
+            pub fn EVP_KDF_CTX_free(ctx: *mut EVP_KDF_CTX);
+            pub fn EVP_KDF_derive(
+                ctx: *mut EVP_KDF_CTX,
+                key: *mut u8,
+                keylen: size_t,
+                params: *const OSSL_PARAM,
+            ) -> c_int;
         }
     }
 }
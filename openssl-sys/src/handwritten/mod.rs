@@ -6,15 +6,21 @@ pub use self::cmac::*;
 pub use self::cms::*;
 pub use self::conf::*;
 pub use self::crypto::*;
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+pub use self::ct::*;
 pub use self::dh::*;
 pub use self::dsa::*;
 pub use self::ec::*;
 pub use self::err::*;
 pub use self::evp::*;
 pub use self::hmac::*;
+#[cfg(ossl320)]
+pub use self::hpke::*;
 pub use self::kdf::*;
 pub use self::object::*;
 pub use self::ocsp::*;
+#[cfg(ossl300)]
+pub use self::params::*;
 pub use self::pem::*;
 pub use self::pkcs12::*;
 pub use self::pkcs7::*;
@@ -28,8 +34,12 @@ pub use self::sha::*;
 pub use self::srtp::*;
 pub use self::ssl::*;
 pub use self::stack::*;
+#[cfg(ossl300)]
+pub use self::store::*;
 pub use self::tls1::*;
 pub use self::types::*;
+#[cfg(ossl300)]
+pub use self::ui::*;
 pub use self::x509::*;
 pub use self::x509_vfy::*;
 pub use self::x509v3::*;
@@ -42,15 +52,21 @@ mod cmac;
 mod cms;
 mod conf;
 mod crypto;
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+mod ct;
 mod dh;
 mod dsa;
 mod ec;
 mod err;
 mod evp;
 mod hmac;
+#[cfg(ossl320)]
+mod hpke;
 mod kdf;
 mod object;
 mod ocsp;
+#[cfg(ossl300)]
+mod params;
 mod pem;
 mod pkcs12;
 mod pkcs7;
@@ -64,8 +80,12 @@ mod sha;
 mod srtp;
 mod ssl;
 mod stack;
+#[cfg(ossl300)]
+mod store;
 mod tls1;
 mod types;
+#[cfg(ossl300)]
+mod ui;
 mod x509;
 mod x509_vfy;
 mod x509v3;
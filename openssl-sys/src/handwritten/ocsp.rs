@@ -86,4 +86,23 @@ extern "C" {
         st: *mut X509_STORE,
         flags: c_ulong,
     ) -> c_int;
+
+    pub fn OCSP_request_add1_nonce(req: *mut OCSP_REQUEST, val: *mut c_uchar, len: c_int) -> c_int;
+    pub fn OCSP_check_nonce(req: *mut OCSP_REQUEST, bs: *mut OCSP_BASICRESP) -> c_int;
+
+    pub fn OCSP_request_sign(
+        req: *mut OCSP_REQUEST,
+        signer: *mut X509,
+        key: *mut EVP_PKEY,
+        dgst: *const EVP_MD,
+        certs: *mut stack_st_X509,
+        flags: c_ulong,
+    ) -> c_int;
+    pub fn OCSP_request_is_signed(req: *mut OCSP_REQUEST) -> c_int;
+    pub fn OCSP_request_verify(
+        req: *mut OCSP_REQUEST,
+        certs: *mut stack_st_X509,
+        store: *mut X509_STORE,
+        flags: c_ulong,
+    ) -> c_int;
 }
@@ -51,3 +51,18 @@ const_ptr_api! {
         pub fn i2d_PKCS12_bio(b: *mut BIO, a: #[const_ptr_if(ossl300)] PKCS12) -> c_int;
     }
 }
+const_ptr_api! {
+    extern "C" {
+        #[cfg(not(boringssl))]
+        pub fn PKCS12_pbe_crypt(
+            algor: #[const_ptr_if(any(ossl110, libressl280))] X509_ALGOR,
+            pass: *const c_char,
+            passlen: c_int,
+            in_: #[const_ptr_if(any(ossl110, libressl280))] c_uchar,
+            inlen: c_int,
+            data: *mut *mut c_uchar,
+            datalen: *mut c_int,
+            en_de: c_int,
+        ) -> *mut c_uchar;
+    }
+}
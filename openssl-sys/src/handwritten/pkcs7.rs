@@ -206,6 +206,13 @@ extern "C" {
 
     pub fn SMIME_read_PKCS7(bio: *mut BIO, bcont: *mut *mut BIO) -> *mut PKCS7;
 
+    pub fn i2d_PKCS7_bio_stream(
+        out: *mut BIO,
+        pkcs7: *mut PKCS7,
+        data: *mut BIO,
+        flags: c_int,
+    ) -> c_int;
+
     pub fn PKCS7_new() -> *mut PKCS7;
 
     pub fn PKCS7_set_type(p7: *mut PKCS7, nid_pkcs7: c_int) -> c_int;
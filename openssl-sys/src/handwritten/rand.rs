@@ -1,3 +1,4 @@
+use super::super::*;
 use libc::*;
 
 extern "C" {
@@ -10,3 +11,32 @@ extern "C" {
 
     pub fn RAND_add(buf: *const c_void, num: c_int, randomness: c_double);
 }
+
+cfg_if! {
+    if #[cfg(ossl300)] {
+        extern "C" {
+            pub fn RAND_bytes_ex(
+                ctx: *mut OSSL_LIB_CTX,
+                buf: *mut c_uchar,
+                num: size_t,
+                strength: c_uint,
+            ) -> c_int;
+            pub fn RAND_priv_bytes_ex(
+                ctx: *mut OSSL_LIB_CTX,
+                buf: *mut c_uchar,
+                num: size_t,
+                strength: c_uint,
+            ) -> c_int;
+            pub fn RAND_get0_public(ctx: *mut OSSL_LIB_CTX) -> *mut EVP_RAND_CTX;
+            pub fn RAND_get0_private(ctx: *mut OSSL_LIB_CTX) -> *mut EVP_RAND_CTX;
+            pub fn RAND_set_DRBG_type(
+                ctx: *mut OSSL_LIB_CTX,
+                drbg: *const c_char,
+                propq: *const c_char,
+                cipher: *const c_char,
+                digest: *const c_char,
+            ) -> c_int;
+            pub fn EVP_RAND_CTX_set_params(ctx: *mut EVP_RAND_CTX, params: *const OSSL_PARAM) -> c_int;
+        }
+    }
+}
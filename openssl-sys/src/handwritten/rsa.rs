@@ -8,7 +8,9 @@ cfg_if! {
             pub fn EVP_PKEY_CTX_get_rsa_padding(ctx: *mut EVP_PKEY_CTX, pad_mode: *mut c_int) -> c_int;
 
             pub fn EVP_PKEY_CTX_set_rsa_pss_saltlen(ctx: *mut EVP_PKEY_CTX, len: c_int) -> c_int;
+            pub fn EVP_PKEY_CTX_get_rsa_pss_saltlen(ctx: *mut EVP_PKEY_CTX, len: *mut c_int) -> c_int;
             pub fn EVP_PKEY_CTX_set_rsa_mgf1_md(ctx: *mut EVP_PKEY_CTX, md: *const EVP_MD) -> c_int;
+            pub fn EVP_PKEY_CTX_get_rsa_mgf1_md(ctx: *mut EVP_PKEY_CTX, md: *mut *const EVP_MD) -> c_int;
         }
     }
 }
@@ -89,6 +91,14 @@ extern "C" {
         pad: c_int,
     ) -> c_int;
     pub fn RSA_check_key(r: *const RSA) -> c_int;
+    pub fn RSA_blinding_on(rsa: *mut RSA, ctx: *mut BN_CTX) -> c_int;
+    pub fn RSA_blinding_off(rsa: *mut RSA);
+    #[cfg(any(ossl110, libressl273))]
+    pub fn RSA_set_flags(r: *mut RSA, flags: c_int);
+    #[cfg(any(ossl110, libressl273))]
+    pub fn RSA_clear_flags(r: *mut RSA, flags: c_int);
+    #[cfg(any(ossl110, libressl273))]
+    pub fn RSA_test_flags(r: *const RSA, flags: c_int) -> c_int;
     pub fn RSA_free(rsa: *mut RSA);
     pub fn RSA_up_ref(rsa: *mut RSA) -> c_int;
 
@@ -122,3 +132,52 @@ extern "C" {
         rsa_len: c_int,
     ) -> c_int;
 }
+
+#[cfg(ossl110)]
+extern "C" {
+    pub fn RSA_PKCS1_OpenSSL() -> *const RSA_METHOD;
+    pub fn RSA_set_method(rsa: *mut RSA, meth: *const RSA_METHOD) -> c_int;
+    pub fn RSA_set_ex_data(r: *mut RSA, idx: c_int, arg: *mut c_void) -> c_int;
+    pub fn RSA_get_ex_data(r: *const RSA, idx: c_int) -> *mut c_void;
+
+    pub fn RSA_meth_dup(meth: *const RSA_METHOD) -> *mut RSA_METHOD;
+    pub fn RSA_meth_free(meth: *mut RSA_METHOD);
+    pub fn RSA_meth_set1_name(meth: *mut RSA_METHOD, name: *const c_char) -> c_int;
+    pub fn RSA_meth_set_priv_enc(
+        meth: *mut RSA_METHOD,
+        priv_enc: Option<
+            unsafe extern "C" fn(
+                flen: c_int,
+                from: *const c_uchar,
+                to: *mut c_uchar,
+                rsa: *mut RSA,
+                padding: c_int,
+            ) -> c_int,
+        >,
+    ) -> c_int;
+    pub fn RSA_meth_set_priv_dec(
+        meth: *mut RSA_METHOD,
+        priv_dec: Option<
+            unsafe extern "C" fn(
+                flen: c_int,
+                from: *const c_uchar,
+                to: *mut c_uchar,
+                rsa: *mut RSA,
+                padding: c_int,
+            ) -> c_int,
+        >,
+    ) -> c_int;
+    pub fn RSA_meth_set_sign(
+        meth: *mut RSA_METHOD,
+        sign: Option<
+            unsafe extern "C" fn(
+                type_: c_int,
+                m: *const c_uchar,
+                m_length: c_uint,
+                sigret: *mut c_uchar,
+                siglen: *mut c_uint,
+                rsa: *const RSA,
+            ) -> c_int,
+        >,
+    ) -> c_int;
+}
@@ -210,6 +210,22 @@ cfg_if! {
     }
 }
 
+cfg_if! {
+    if #[cfg(ossl300)] {
+        extern "C" {
+            pub fn SSL_get_options(s: *const SSL) -> u64;
+            pub fn SSL_set_options(s: *mut SSL, op: u64) -> u64;
+            pub fn SSL_clear_options(s: *mut SSL, op: u64) -> u64;
+        }
+    } else if #[cfg(ossl110)] {
+        extern "C" {
+            pub fn SSL_get_options(s: *const SSL) -> c_ulong;
+            pub fn SSL_set_options(s: *mut SSL, op: c_ulong) -> c_ulong;
+            pub fn SSL_clear_options(s: *mut SSL, op: c_ulong) -> c_ulong;
+        }
+    }
+}
+
 pub type GEN_SESSION_CB =
     Option<unsafe extern "C" fn(*const SSL, *mut c_uchar, *mut c_uint) -> c_int>;
 
@@ -405,6 +421,9 @@ extern "C" {
     pub fn SSL_extension_supported(ext_type: c_uint) -> c_int;
 }
 
+// FIXME should be unsafe
+pub type SSL_verify_cb = Option<extern "C" fn(c_int, *mut X509_STORE_CTX) -> c_int>;
+
 #[cfg(ossl111)]
 pub type SSL_CTX_keylog_cb_func =
     Option<unsafe extern "C" fn(ssl: *const SSL, line: *const c_char)>;
@@ -422,17 +441,34 @@ extern "C" {
     #[cfg(any(ossl111, libressl340))]
     pub fn SSL_get_max_early_data(ctx: *const SSL) -> u32;
 
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_set_recv_max_early_data(ctx: *mut SSL_CTX, recv_max_early_data: u32) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_get_recv_max_early_data(ctx: *const SSL_CTX) -> u32;
+    #[cfg(ossl111)]
+    pub fn SSL_set_recv_max_early_data(s: *mut SSL, recv_max_early_data: u32) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_get_recv_max_early_data(s: *const SSL) -> u32;
+
+    #[cfg(ossl110)]
+    pub fn SSL_CTX_set_default_read_buffer_len(ctx: *mut SSL_CTX, len: size_t);
+    #[cfg(ossl110)]
+    pub fn SSL_set_default_read_buffer_len(s: *mut SSL, len: size_t);
+
     pub fn SSL_get_finished(s: *const SSL, buf: *mut c_void, count: size_t) -> size_t;
     pub fn SSL_get_peer_finished(s: *const SSL, buf: *mut c_void, count: size_t) -> size_t;
 
     pub fn SSL_CTX_get_verify_mode(ctx: *const SSL_CTX) -> c_int;
     pub fn SSL_get_verify_mode(s: *const SSL) -> c_int;
+    pub fn SSL_get_verify_callback(s: *const SSL) -> SSL_verify_cb;
 }
 
 const_ptr_api! {
     extern "C" {
         #[cfg(ossl110)]
         pub fn SSL_is_init_finished(s: #[const_ptr_if(ossl111)] SSL) -> c_int;
+        #[cfg(ossl110)]
+        pub fn SSL_in_before(s: #[const_ptr_if(ossl111)] SSL) -> c_int;
     }
 }
 
@@ -461,6 +497,12 @@ cfg_if! {
 extern "C" {
     pub fn SSL_CTX_set_cipher_list(ssl: *mut SSL_CTX, s: *const c_char) -> c_int;
     pub fn SSL_CTX_new(method: *const SSL_METHOD) -> *mut SSL_CTX;
+    #[cfg(ossl300)]
+    pub fn SSL_CTX_new_ex(
+        libctx: *mut OSSL_LIB_CTX,
+        propq: *const c_char,
+        meth: *const SSL_METHOD,
+    ) -> *mut SSL_CTX;
     pub fn SSL_CTX_free(ctx: *mut SSL_CTX);
     #[cfg(any(ossl110, libressl273))]
     pub fn SSL_CTX_up_ref(x: *mut SSL_CTX) -> c_int;
@@ -517,6 +559,12 @@ extern "C" {
         ctx: *mut SSL_CTX,
         cert_chain_file: *const c_char,
     ) -> c_int;
+    #[cfg(libressl)]
+    pub fn SSL_CTX_use_certificate_chain_mem(
+        ctx: *mut SSL_CTX,
+        buf: *mut c_void,
+        len: c_int,
+    ) -> c_int;
     pub fn SSL_use_PrivateKey_file(ssl: *mut SSL, file: *const c_char, type_: c_int) -> c_int;
     pub fn SSL_use_PrivateKey(ssl: *mut SSL, pkey: *mut EVP_PKEY) -> c_int;
     pub fn SSL_use_certificate(ssl: *mut SSL, x: *mut X509) -> c_int;
@@ -525,6 +573,11 @@ extern "C" {
     pub fn SSL_set_client_CA_list(s: *mut SSL, name_list: *mut stack_st_X509_NAME);
     pub fn SSL_add_client_CA(ssl: *mut SSL, x: *mut X509) -> c_int;
     pub fn SSL_load_client_CA_file(file: *const c_char) -> *mut stack_st_X509_NAME;
+    pub fn SSL_add_dir_cert_subjects_to_stack(
+        stack: *mut stack_st_X509_NAME,
+        dir: *const c_char,
+    ) -> c_int;
+    pub fn SSL_get_client_CA_list(s: *const SSL) -> *mut stack_st_X509_NAME;
 
     #[cfg(not(ossl110))]
     pub fn SSL_load_error_strings();
@@ -542,6 +595,46 @@ extern "C" {
     pub fn SSL_SESSION_get_max_early_data(ctx: *const SSL_SESSION) -> u32;
 
     pub fn SSL_SESSION_get_id(s: *const SSL_SESSION, len: *mut c_uint) -> *const c_uchar;
+    #[cfg(ossl110)]
+    pub fn SSL_SESSION_set1_id(s: *mut SSL_SESSION, sid: *const c_uchar, sid_len: c_uint) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_SESSION_set1_master_key(
+        sess: *mut SSL_SESSION,
+        in_: *const c_uchar,
+        len: size_t,
+    ) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_SESSION_get0_cipher(s: *const SSL_SESSION) -> *const SSL_CIPHER;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_set_cipher(s: *mut SSL_SESSION, cipher: *const SSL_CIPHER) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_set_protocol_version(s: *mut SSL_SESSION, version: c_int) -> c_int;
+    pub fn SSL_SESSION_get0_peer(s: *mut SSL_SESSION) -> *mut X509;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_get0_alpn_selected(
+        s: *const SSL_SESSION,
+        alpn: *mut *const c_uchar,
+        len: *mut size_t,
+    );
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_set1_alpn_selected(
+        s: *mut SSL_SESSION,
+        alpn: *const c_uchar,
+        len: size_t,
+    ) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_SESSION_get0_ticket(
+        s: *const SSL_SESSION,
+        tick: *mut *const c_uchar,
+        len: *mut size_t,
+    );
+    #[cfg(ossl110)]
+    pub fn SSL_SESSION_get_ticket_lifetime_hint(s: *const SSL_SESSION) -> c_ulong;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_is_resumable(s: *const SSL_SESSION) -> c_int;
+    pub fn SSL_SESSION_new() -> *mut SSL_SESSION;
+    #[cfg(ossl111)]
+    pub fn SSL_SESSION_dup(src: *const SSL_SESSION) -> *mut SSL_SESSION;
     #[cfg(any(ossl110, libressl273))]
     pub fn SSL_SESSION_up_ref(ses: *mut SSL_SESSION) -> c_int;
     pub fn SSL_SESSION_free(s: *mut SSL_SESSION);
@@ -579,6 +672,7 @@ extern "C" {
     pub fn SSL_CTX_set_post_handshake_auth(ctx: *mut SSL_CTX, val: c_int);
 
     pub fn SSL_CTX_check_private_key(ctx: *const SSL_CTX) -> c_int;
+    pub fn SSL_check_private_key(ssl: *const SSL) -> c_int;
 
     pub fn SSL_CTX_set_session_id_context(
         ssl: *mut SSL_CTX,
@@ -587,6 +681,7 @@ extern "C" {
     ) -> c_int;
 
     pub fn SSL_new(ctx: *mut SSL_CTX) -> *mut SSL;
+    pub fn SSL_dup(ssl: *mut SSL) -> *mut SSL;
 
     #[cfg(any(ossl102, libressl261))]
     pub fn SSL_CTX_get0_param(ctx: *mut SSL_CTX) -> *mut X509_VERIFY_PARAM;
@@ -595,6 +690,49 @@ extern "C" {
     pub fn SSL_get0_param(ssl: *mut SSL) -> *mut X509_VERIFY_PARAM;
 }
 
+extern "C" {
+    pub fn SSL_CTX_set_msg_callback(
+        ctx: *mut SSL_CTX,
+        cb: Option<
+            unsafe extern "C" fn(
+                write_p: c_int,
+                version: c_int,
+                content_type: c_int,
+                buf: *const c_void,
+                len: size_t,
+                ssl: *mut SSL,
+                arg: *mut c_void,
+            ),
+        >,
+    );
+    pub fn SSL_set_msg_callback(
+        ssl: *mut SSL,
+        cb: Option<
+            unsafe extern "C" fn(
+                write_p: c_int,
+                version: c_int,
+                content_type: c_int,
+                buf: *const c_void,
+                len: size_t,
+                ssl: *mut SSL,
+                arg: *mut c_void,
+            ),
+        >,
+    );
+}
+
+#[cfg(ossl111)]
+pub type SSL_allow_early_data_cb_fn =
+    Option<unsafe extern "C" fn(s: *mut SSL, arg: *mut c_void) -> c_int>;
+extern "C" {
+    #[cfg(ossl111)]
+    pub fn SSL_CTX_set_allow_early_data_cb(
+        ctx: *mut SSL_CTX,
+        cb: SSL_allow_early_data_cb_fn,
+        arg: *mut c_void,
+    );
+}
+
 #[cfg(ossl111)]
 pub type SSL_client_hello_cb_fn =
     Option<unsafe extern "C" fn(s: *mut SSL, al: *mut c_int, arg: *mut c_void) -> c_int>;
@@ -725,6 +863,8 @@ extern "C" {
 
     pub fn SSL_CTX_add_client_CA(ctx: *mut SSL_CTX, cacert: *mut X509) -> c_int;
 
+    pub fn SSL_CTX_get_client_CA_list(ctx: *const SSL_CTX) -> *mut stack_st_X509_NAME;
+
     pub fn SSL_CTX_set_default_verify_paths(ctx: *mut SSL_CTX) -> c_int;
     pub fn SSL_CTX_load_verify_locations(
         ctx: *mut SSL_CTX,
@@ -768,6 +908,11 @@ extern "C" {
 
     pub fn SSL_set_shutdown(ss: *mut SSL, mode: c_int);
     pub fn SSL_get_shutdown(ssl: *const SSL) -> c_int;
+    pub fn SSL_want(ssl: *const SSL) -> c_int;
+    pub fn SSL_CTX_set_quiet_shutdown(ctx: *mut SSL_CTX, mode: c_int);
+    pub fn SSL_CTX_get_quiet_shutdown(ctx: *const SSL_CTX) -> c_int;
+    pub fn SSL_set_quiet_shutdown(ssl: *mut SSL, mode: c_int);
+    pub fn SSL_get_quiet_shutdown(ssl: *const SSL) -> c_int;
     pub fn SSL_version(ssl: *const SSL) -> c_int;
     pub fn SSL_get_session(s: *const SSL) -> *mut SSL_SESSION;
     pub fn SSL_get_SSL_CTX(ssl: *const SSL) -> *mut SSL_CTX;
@@ -886,6 +1031,17 @@ extern "C" {
     pub fn SSL_CIPHER_get_cipher_nid(c: *const SSL_CIPHER) -> c_int;
     #[cfg(any(ossl110, libressl270))]
     pub fn SSL_CIPHER_get_digest_nid(c: *const SSL_CIPHER) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_CIPHER_get_kx_nid(c: *const SSL_CIPHER) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_CIPHER_get_auth_nid(c: *const SSL_CIPHER) -> c_int;
+    #[cfg(ossl110)]
+    pub fn SSL_CIPHER_is_aead(c: *const SSL_CIPHER) -> c_int;
+    #[cfg(ossl111)]
+    pub fn SSL_CIPHER_get_protocol_id(c: *const SSL_CIPHER) -> u16;
+
+    #[cfg(ossl110)]
+    pub fn SSL_CTX_get_ciphers(ctx: *const SSL_CTX) -> *mut stack_st_SSL_CIPHER;
 }
 
 const_ptr_api! {
@@ -923,4 +1079,18 @@ extern "C" {
     pub fn SSL_get_num_tickets(s: *const SSL) -> size_t;
     #[cfg(all(ossl111, not(ossl111b)))]
     pub fn SSL_get_num_tickets(s: *mut SSL) -> size_t;
+
+    #[cfg(ossl300)]
+    pub fn SSL_new_session_ticket(s: *mut SSL) -> c_int;
+}
+
+#[cfg(ossl111)]
+pub type DTLS_timer_cb = Option<unsafe extern "C" fn(s: *mut SSL, timer_us: c_uint) -> c_uint>;
+
+extern "C" {
+    #[cfg(ossl111)]
+    pub fn DTLS_get_data_mtu(s: *const SSL) -> size_t;
+
+    #[cfg(ossl111)]
+    pub fn DTLS_set_timer_cb(s: *mut SSL, cb: DTLS_timer_cb);
 }
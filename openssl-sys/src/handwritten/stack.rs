@@ -29,6 +29,7 @@ cfg_if! {
             );
             pub fn OPENSSL_sk_push(st: *mut OPENSSL_STACK, data: *const c_void) -> c_int;
             pub fn OPENSSL_sk_pop(st: *mut OPENSSL_STACK) -> *mut c_void;
+            pub fn OPENSSL_sk_sort(st: *mut OPENSSL_STACK);
         }
     } else {
         extern "C" {
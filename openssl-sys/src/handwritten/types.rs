@@ -265,7 +265,11 @@ cfg_if! {
 }
 pub enum RSA_METHOD {}
 
+pub enum UI_METHOD {}
+
 pub enum EC_KEY {}
+#[cfg(ossl110)]
+pub enum EC_KEY_METHOD {}
 
 cfg_if! {
     if #[cfg(any(ossl110, libressl280))] {
@@ -1093,3 +1097,31 @@ pub enum OSSL_PROVIDER {}
 
 #[cfg(ossl300)]
 pub enum OSSL_LIB_CTX {}
+
+#[cfg(ossl300)]
+pub enum OSSL_STORE_CTX {}
+
+#[cfg(ossl300)]
+pub enum OSSL_STORE_INFO {}
+
+#[cfg(ossl320)]
+pub enum OSSL_HPKE_CTX {}
+
+#[cfg(ossl300)]
+pub enum EVP_KDF {}
+
+#[cfg(ossl300)]
+pub enum EVP_KDF_CTX {}
+
+#[cfg(ossl300)]
+pub enum EVP_RAND_CTX {}
+
+#[cfg(ossl300)]
+#[repr(C)]
+pub struct OSSL_PARAM {
+    pub key: *const c_char,
+    pub data_type: c_uint,
+    pub data: *mut c_void,
+    pub data_size: size_t,
+    pub return_size: size_t,
+}
@@ -0,0 +1,7 @@
+use super::super::*;
+use libc::*;
+
+extern "C" {
+    pub fn UI_UTIL_wrap_read_pem_callback(cb: pem_password_cb, rwflag: c_int) -> *mut UI_METHOD;
+    pub fn UI_destroy_method(ui_method: *mut UI_METHOD);
+}
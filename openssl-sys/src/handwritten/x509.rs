@@ -143,6 +143,8 @@ stack!(stack_st_X509_LOOKUP);
 extern "C" {
     pub fn X509_verify_cert_error_string(n: c_long) -> *const c_char;
 
+    pub fn X509_cmp_current_time(s: *const ASN1_TIME) -> c_int;
+
     pub fn X509_sign(x: *mut X509, pkey: *mut EVP_PKEY, md: *const EVP_MD) -> c_int;
 
     pub fn X509_digest(
@@ -205,6 +207,37 @@ extern "C" {
     pub fn X509_to_X509_REQ(x: *mut X509, pkey: *mut EVP_PKEY, md: *const EVP_MD) -> *mut X509_REQ;
 
     pub fn X509_ALGOR_free(x: *mut X509_ALGOR);
+    pub fn X509_ALGOR_set0(
+        alg: *mut X509_ALGOR,
+        aobj: *mut ASN1_OBJECT,
+        ptype: c_int,
+        pval: *mut c_void,
+    ) -> c_int;
+    pub fn d2i_X509_ALGOR(
+        a: *mut *mut X509_ALGOR,
+        pp: *mut *const c_uchar,
+        length: c_long,
+    ) -> *mut X509_ALGOR;
+
+    #[cfg(not(boringssl))]
+    pub fn PKCS5_pbe2_set_iv(
+        cipher: *const EVP_CIPHER,
+        iter: c_int,
+        salt: *mut c_uchar,
+        saltlen: c_int,
+        aiv: *mut c_uchar,
+        prf_nid: c_int,
+    ) -> *mut X509_ALGOR;
+    #[cfg(ossl110)]
+    pub fn PKCS5_pbe2_set_scrypt(
+        cipher: *const EVP_CIPHER,
+        salt: *const c_uchar,
+        saltlen: c_int,
+        aiv: *mut c_uchar,
+        N: u64,
+        r: u64,
+        p: u64,
+    ) -> *mut X509_ALGOR;
 
     pub fn X509_REVOKED_new() -> *mut X509_REVOKED;
     pub fn X509_REVOKED_free(x: *mut X509_REVOKED);
@@ -267,8 +300,24 @@ const_ptr_api! {
 
         #[cfg(any(ossl110, libressl270))]
         pub fn X509_REQ_dup(x: #[const_ptr_if(ossl300)] X509_REQ) -> *mut X509_REQ;
+
+        pub fn i2d_X509_ALGOR(x: #[const_ptr_if(ossl300)] X509_ALGOR, buf: *mut *mut u8) -> c_int;
     }
 }
+extern "C" {
+    #[cfg(ossl110)]
+    pub fn X509_get0_tbs_sigalg(x: *const X509) -> *const X509_ALGOR;
+    #[cfg(ossl110)]
+    pub fn X509_REQ_get0_signature(
+        req: *const X509_REQ,
+        psig: *mut *const ASN1_BIT_STRING,
+        palg: *mut *const X509_ALGOR,
+    );
+    #[cfg(ossl110)]
+    pub fn i2d_re_X509_tbs(x: *mut X509, pp: *mut *mut c_uchar) -> c_int;
+    #[cfg(ossl110)]
+    pub fn i2d_re_X509_REQ_tbs(req: *mut X509_REQ, pp: *mut *mut c_uchar) -> c_int;
+}
 extern "C" {
     #[cfg(ossl102)]
     pub fn X509_get_signature_nid(x: *const X509) -> c_int;
@@ -284,6 +333,23 @@ extern "C" {
     pub fn X509_new() -> *mut X509;
     pub fn X509_free(x: *mut X509);
 }
+
+cfg_if! {
+    if #[cfg(ossl300)] {
+        extern "C" {
+            pub fn X509_NAME_hash_ex(
+                x: *const X509_NAME,
+                libctx: *mut OSSL_LIB_CTX,
+                propq: *const c_char,
+                ok: *mut c_int,
+            ) -> c_ulong;
+        }
+    } else {
+        extern "C" {
+            pub fn X509_NAME_hash(x: *mut X509_NAME) -> c_ulong;
+        }
+    }
+}
 const_ptr_api! {
     extern "C" {
         pub fn i2d_X509(x: #[const_ptr_if(ossl300)] X509, buf: *mut *mut u8) -> c_int;
@@ -305,7 +371,16 @@ extern "C" {
     pub fn d2i_X509_bio(b: *mut BIO, a: *mut *mut X509) -> *mut X509;
 
     pub fn X509_get_pubkey(x: *mut X509) -> *mut EVP_PKEY;
-
+}
+const_ptr_api! {
+    extern "C" {
+        pub fn X509_check_private_key(
+            x: #[const_ptr_if(any(ossl110, libressl280))] X509,
+            pkey: #[const_ptr_if(any(ossl110, libressl280))] EVP_PKEY,
+        ) -> c_int;
+    }
+}
+extern "C" {
     pub fn X509_set_version(x: *mut X509, version: c_long) -> c_int;
     #[cfg(ossl110)]
     pub fn X509_get_version(x: *const X509) -> c_long;
@@ -444,6 +519,12 @@ extern "C" {
     pub fn X509_CRL_get0_lastUpdate(x: *const X509_CRL) -> *const ASN1_TIME;
     #[cfg(any(ossl110, libressl281))]
     pub fn X509_CRL_get_issuer(x: *const X509_CRL) -> *mut X509_NAME;
+    #[cfg(ossl110)]
+    pub fn X509_CRL_get0_signature(
+        crl: *const X509_CRL,
+        psig: *mut *const ASN1_BIT_STRING,
+        palg: *mut *const X509_ALGOR,
+    );
 
     #[cfg(ossl110)]
     pub fn X509_get0_extensions(req: *const X509) -> *const stack_st_X509_EXTENSION;
@@ -650,6 +731,7 @@ const_ptr_api! {
 #[cfg(any(ossl110, libressl270))]
 extern "C" {
     pub fn X509_OBJECT_get0_X509(x: *const X509_OBJECT) -> *mut X509;
+    pub fn X509_OBJECT_get0_X509_CRL(x: *const X509_OBJECT) -> *mut X509_CRL;
 }
 
 cfg_if! {
@@ -53,6 +53,20 @@ extern "C" {
 
 }
 
+#[cfg(ossl110)]
+extern "C" {
+    pub fn X509_STORE_up_ref(store: *mut X509_STORE) -> c_int;
+    pub fn X509_STORE_lock(store: *mut X509_STORE) -> c_int;
+    pub fn X509_STORE_unlock(store: *mut X509_STORE) -> c_int;
+}
+
+const_ptr_api! {
+    extern "C" {
+        #[cfg(ossl110)]
+        pub fn X509_STORE_get0_param(store: #[const_ptr_if(ossl300)] X509_STORE) -> *mut X509_VERIFY_PARAM;
+    }
+}
+
 const_ptr_api! {
     extern "C" {
         pub fn X509_STORE_set1_param(store: *mut X509_STORE, pm: #[const_ptr_if(ossl300)] X509_VERIFY_PARAM) -> c_int;
@@ -69,6 +83,12 @@ const_ptr_api! {
 }
 extern "C" {
     pub fn X509_STORE_CTX_set_error(ctx: *mut X509_STORE_CTX, error: c_int);
+    pub fn X509_STORE_CTX_set_time(ctx: *mut X509_STORE_CTX, flags: c_ulong, t: time_t);
+}
+const_ptr_api! {
+    extern "C" {
+        pub fn X509_STORE_CTX_get0_param(ctx: #[const_ptr_if(ossl300)] X509_STORE_CTX) -> *mut X509_VERIFY_PARAM;
+    }
 }
 cfg_if! {
     if #[cfg(any(ossl110, libressl350))] {
@@ -13,6 +13,16 @@ extern "C" {
     ) -> c_int;
 }
 
+const_ptr_api! {
+    extern "C" {
+        pub fn GENERAL_NAME_get0_otherName(
+            gen: #[const_ptr_if(any(ossl110, libressl280))] GENERAL_NAME,
+            poid: *mut *mut ASN1_OBJECT,
+            pvalue: *mut *mut ASN1_TYPE,
+        ) -> c_int;
+    }
+}
+
 #[repr(C)]
 pub struct ACCESS_DESCRIPTION {
     pub method: *mut ASN1_OBJECT,
@@ -117,6 +127,17 @@ extern "C" {
     pub fn X509_get0_authority_issuer(x: *mut X509) -> *const stack_st_GENERAL_NAME;
     #[cfg(ossl111d)]
     pub fn X509_get0_authority_serial(x: *mut X509) -> *const ASN1_INTEGER;
+
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_check_host(
+        x: *mut X509,
+        chk: *const c_char,
+        chklen: size_t,
+        flags: c_uint,
+        peername: *mut *mut c_char,
+    ) -> c_int;
+    #[cfg(any(ossl102, libressl261))]
+    pub fn X509_check_ip_asc(x: *mut X509, ipasc: *const c_char, flags: c_uint) -> c_int;
 }
 
 #[repr(C)]
@@ -0,0 +1,31 @@
+use libc::*;
+
+pub const OSSL_HPKE_MODE_BASE: c_int = 0;
+pub const OSSL_HPKE_MODE_PSK: c_int = 1;
+pub const OSSL_HPKE_MODE_AUTH: c_int = 2;
+pub const OSSL_HPKE_MODE_PSKAUTH: c_int = 3;
+
+pub const OSSL_HPKE_ROLE_SENDER: c_int = 0;
+pub const OSSL_HPKE_ROLE_RECEIVER: c_int = 1;
+
+pub const OSSL_HPKE_MAX_PARMLEN: size_t = 66;
+pub const OSSL_HPKE_MIN_PSKLEN: size_t = 32;
+pub const OSSL_HPKE_MAX_INFOLEN: size_t = 1024;
+
+pub const OSSL_HPKE_KEM_ID_RESERVED: u16 = 0x0000;
+pub const OSSL_HPKE_KEM_ID_P256: u16 = 0x0010;
+pub const OSSL_HPKE_KEM_ID_P384: u16 = 0x0011;
+pub const OSSL_HPKE_KEM_ID_P521: u16 = 0x0012;
+pub const OSSL_HPKE_KEM_ID_X25519: u16 = 0x0020;
+pub const OSSL_HPKE_KEM_ID_X448: u16 = 0x0021;
+
+pub const OSSL_HPKE_KDF_ID_RESERVED: u16 = 0x0000;
+pub const OSSL_HPKE_KDF_ID_HKDF_SHA256: u16 = 0x0001;
+pub const OSSL_HPKE_KDF_ID_HKDF_SHA384: u16 = 0x0002;
+pub const OSSL_HPKE_KDF_ID_HKDF_SHA512: u16 = 0x0003;
+
+pub const OSSL_HPKE_AEAD_ID_RESERVED: u16 = 0x0000;
+pub const OSSL_HPKE_AEAD_ID_AES_GCM_128: u16 = 0x0001;
+pub const OSSL_HPKE_AEAD_ID_AES_GCM_256: u16 = 0x0002;
+pub const OSSL_HPKE_AEAD_ID_CHACHA_POLY1305: u16 = 0x0003;
+pub const OSSL_HPKE_AEAD_ID_EXPORTONLY: u16 = 0xFFFF;
@@ -46,12 +46,16 @@ mod openssl {
     pub use self::bn::*;
     pub use self::cms::*;
     pub use self::crypto::*;
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+    pub use self::ct::*;
     pub use self::dtls1::*;
     pub use self::ec::*;
     pub use self::err::*;
     pub use self::evp::*;
     #[cfg(not(feature = "bindgen"))]
     pub use self::handwritten::*;
+    #[cfg(ossl320)]
+    pub use self::hpke::*;
     pub use self::obj_mac::*;
     pub use self::ocsp::*;
     pub use self::pem::*;
@@ -61,6 +65,8 @@ mod openssl {
     pub use self::srtp::*;
     pub use self::ssl::*;
     pub use self::ssl3::*;
+    #[cfg(ossl300)]
+    pub use self::store::*;
     pub use self::tls1::*;
     pub use self::types::*;
     pub use self::x509::*;
@@ -76,12 +82,16 @@ mod openssl {
     mod bn;
     mod cms;
     mod crypto;
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+    mod ct;
     mod dtls1;
     mod ec;
     mod err;
     mod evp;
     #[cfg(not(feature = "bindgen"))]
     mod handwritten;
+    #[cfg(ossl320)]
+    mod hpke;
     mod obj_mac;
     mod ocsp;
     mod pem;
@@ -91,6 +101,8 @@ mod openssl {
     mod srtp;
     mod ssl;
     mod ssl3;
+    #[cfg(ossl300)]
+    mod store;
     mod tls1;
     mod types;
     mod x509;
@@ -100,6 +100,14 @@ pub const NID_brainpoolP256r1: c_int = 927;
 pub const NID_brainpoolP384r1: c_int = 931;
 #[cfg(ossl110)]
 pub const NID_brainpoolP512r1: c_int = 933;
+#[cfg(ossl110)]
+pub const NID_ct_precert_scts: c_int = 951;
+#[cfg(ossl110)]
+pub const NID_ct_precert_poison: c_int = 952;
+#[cfg(ossl110)]
+pub const NID_ct_precert_signer: c_int = 953;
+#[cfg(ossl110)]
+pub const NID_ct_cert_scts: c_int = 954;
 pub const NID_wap_wsg_idm_ecid_wtls1: c_int = 735;
 pub const NID_wap_wsg_idm_ecid_wtls3: c_int = 736;
 pub const NID_wap_wsg_idm_ecid_wtls4: c_int = 737;
@@ -202,6 +210,8 @@ pub const NID_id_smime_aa_equivalentLabels: c_int = 220;
 pub const NID_id_smime_aa_contentReference: c_int = 221;
 pub const NID_id_smime_aa_encrypKeyPref: c_int = 222;
 pub const NID_id_smime_aa_signingCertificate: c_int = 223;
+#[cfg(ossl111)]
+pub const NID_id_smime_aa_signingCertificateV2: c_int = 1086;
 pub const NID_id_smime_aa_smimeEncryptCerts: c_int = 224;
 pub const NID_id_smime_aa_timeStampToken: c_int = 225;
 pub const NID_id_smime_aa_ets_sigPolicyId: c_int = 226;
@@ -1,3 +1,5 @@
 use libc::*;
 
+pub const PEM_R_BAD_DECRYPT: c_int = 101;
+pub const PEM_R_BAD_PASSWORD_READ: c_int = 104;
 pub const PEM_R_NO_START_LINE: c_int = 108;
@@ -5,6 +5,26 @@ use super::super::*;
 
 pub const RSA_F4: c_long = 0x10001;
 
+pub const RSA_FLAG_CACHE_PUBLIC: c_int = 0x0002;
+pub const RSA_FLAG_CACHE_PRIVATE: c_int = 0x0004;
+pub const RSA_FLAG_BLINDING: c_int = 0x0008;
+pub const RSA_FLAG_THREAD_SAFE: c_int = 0x0010;
+pub const RSA_FLAG_EXT_PKEY: c_int = 0x0020;
+pub const RSA_FLAG_NO_BLINDING: c_int = 0x0080;
+#[cfg(ossl110)]
+pub const RSA_FLAG_NO_CONSTTIME: c_int = 0x0000;
+#[cfg(not(ossl110))]
+pub const RSA_FLAG_NO_CONSTTIME: c_int = 0x0100;
+
+pub const RSA_R_D_E_NOT_CONGRUENT_TO_1: c_int = 123;
+pub const RSA_R_DMP1_NOT_CONGRUENT_TO_D: c_int = 124;
+pub const RSA_R_DMQ1_NOT_CONGRUENT_TO_D: c_int = 125;
+pub const RSA_R_IQMP_NOT_INVERSE_OF_Q: c_int = 126;
+pub const RSA_R_N_DOES_NOT_EQUAL_P_Q: c_int = 127;
+pub const RSA_R_P_NOT_PRIME: c_int = 128;
+pub const RSA_R_Q_NOT_PRIME: c_int = 129;
+pub const RSA_R_VALUE_MISSING: c_int = 147;
+
 cfg_if! {
     if #[cfg(not(ossl300))] {
         pub unsafe fn EVP_PKEY_CTX_set_rsa_padding(ctx: *mut EVP_PKEY_CTX, pad: c_int) -> c_int {
@@ -39,6 +59,17 @@ cfg_if! {
             )
         }
 
+        pub unsafe fn EVP_PKEY_CTX_get_rsa_pss_saltlen(ctx: *mut EVP_PKEY_CTX, len: *mut c_int) -> c_int {
+            EVP_PKEY_CTX_ctrl(
+                ctx,
+                EVP_PKEY_RSA,
+                EVP_PKEY_OP_SIGN | EVP_PKEY_OP_VERIFY,
+                EVP_PKEY_CTRL_GET_RSA_PSS_SALTLEN,
+                0,
+                len as *mut c_void,
+            )
+        }
+
         pub unsafe fn EVP_PKEY_CTX_set_rsa_mgf1_md(ctx: *mut EVP_PKEY_CTX, md: *mut EVP_MD) -> c_int {
             EVP_PKEY_CTX_ctrl(
                 ctx,
@@ -49,6 +80,17 @@ cfg_if! {
                 md as *mut c_void,
             )
         }
+
+        pub unsafe fn EVP_PKEY_CTX_get_rsa_mgf1_md(ctx: *mut EVP_PKEY_CTX, md: *mut *const EVP_MD) -> c_int {
+            EVP_PKEY_CTX_ctrl(
+                ctx,
+                EVP_PKEY_RSA,
+                EVP_PKEY_OP_TYPE_SIG | EVP_PKEY_OP_TYPE_CRYPT,
+                EVP_PKEY_CTRL_GET_RSA_MGF1_MD,
+                0,
+                md as *mut c_void,
+            )
+        }
     }
 }
 
@@ -86,6 +128,8 @@ pub const EVP_PKEY_CTRL_RSA_PSS_SALTLEN: c_int = EVP_PKEY_ALG_CTRL + 2;
 pub const EVP_PKEY_CTRL_RSA_MGF1_MD: c_int = EVP_PKEY_ALG_CTRL + 5;
 
 pub const EVP_PKEY_CTRL_GET_RSA_PADDING: c_int = EVP_PKEY_ALG_CTRL + 6;
+pub const EVP_PKEY_CTRL_GET_RSA_PSS_SALTLEN: c_int = EVP_PKEY_ALG_CTRL + 7;
+pub const EVP_PKEY_CTRL_GET_RSA_MGF1_MD: c_int = EVP_PKEY_ALG_CTRL + 8;
 
 #[cfg(any(ossl102, libressl310))]
 pub const EVP_PKEY_CTRL_RSA_OAEP_MD: c_int = EVP_PKEY_ALG_CTRL + 9;
@@ -99,3 +143,14 @@ pub const RSA_NO_PADDING: c_int = 3;
 pub const RSA_PKCS1_OAEP_PADDING: c_int = 4;
 pub const RSA_X931_PADDING: c_int = 5;
 pub const RSA_PKCS1_PSS_PADDING: c_int = 6;
+
+#[cfg(ossl110)]
+pub unsafe fn RSA_get_ex_new_index(
+    l: c_long,
+    p: *mut c_void,
+    newf: Option<CRYPTO_EX_new>,
+    dupf: Option<CRYPTO_EX_dup>,
+    freef: Option<CRYPTO_EX_free>,
+) -> c_int {
+    CRYPTO_get_ex_new_index(CRYPTO_EX_INDEX_RSA, l, p, newf, dupf, freef)
+}
@@ -22,6 +22,16 @@ pub const SSL_RECEIVED_SHUTDOWN: c_int = 2;
 pub const SSL_FILETYPE_PEM: c_int = X509_FILETYPE_PEM;
 pub const SSL_FILETYPE_ASN1: c_int = X509_FILETYPE_ASN1;
 
+pub const SSL_R_CERTIFICATE_VERIFY_FAILED: c_int = 134;
+pub const SSL_R_NO_SHARED_CIPHER: c_int = 193;
+pub const SSL_R_WRONG_VERSION_NUMBER: c_int = 267;
+#[cfg(ossl300)]
+pub const SSL_R_UNEXPECTED_EOF_WHILE_READING: c_int = 294;
+
+pub const SSL_NOTHING: c_int = 1;
+pub const SSL_WRITING: c_int = 2;
+pub const SSL_READING: c_int = 3;
+
 #[cfg(ossl111)]
 pub const SSL_EXT_TLS_ONLY: c_uint = 0x0001;
 /* This extension is only allowed in DTLS */
@@ -72,6 +82,8 @@ cfg_if! {
     }
 }
 
+#[cfg(ossl300)]
+pub const SSL_OP_NO_EXTENDED_MASTER_SECRET: ssl_op_type!() = 0x00000001;
 pub const SSL_OP_LEGACY_SERVER_CONNECT: ssl_op_type!() = 0x00000004;
 cfg_if! {
     if #[cfg(libressl261)] {
@@ -111,6 +123,8 @@ cfg_if! {
 pub const SSL_OP_ENABLE_MIDDLEBOX_COMPAT: ssl_op_type!() = 0x00100000;
 #[cfg(ossl111)]
 pub const SSL_OP_PRIORITIZE_CHACHA: ssl_op_type!() = 0x00200000;
+#[cfg(ossl111)]
+pub const SSL_OP_NO_ANTI_REPLAY: ssl_op_type!() = 0x01000000;
 
 pub const SSL_OP_CIPHER_SERVER_PREFERENCE: ssl_op_type!() = 0x00400000;
 cfg_if! {
@@ -272,6 +286,23 @@ cfg_if! {
                 ptr::null_mut(),
             ) as c_ulong
         }
+
+        pub unsafe fn SSL_get_options(s: *const SSL) -> c_ulong {
+            SSL_ctrl(s as *mut _, SSL_CTRL_OPTIONS, 0, ptr::null_mut()) as c_ulong
+        }
+
+        pub unsafe fn SSL_set_options(s: *const SSL, op: c_ulong) -> c_ulong {
+            SSL_ctrl(s as *mut _, SSL_CTRL_OPTIONS, op as c_long, ptr::null_mut()) as c_ulong
+        }
+
+        pub unsafe fn SSL_clear_options(s: *const SSL, op: c_ulong) -> c_ulong {
+            SSL_ctrl(
+                s as *mut _,
+                SSL_CTRL_CLEAR_OPTIONS,
+                op as c_long,
+                ptr::null_mut(),
+            ) as c_ulong
+        }
     }
 }
 
@@ -284,6 +315,10 @@ pub unsafe fn SSL_get_extms_support(ssl: *mut SSL) -> c_long {
     SSL_ctrl(ssl, SSL_CTRL_GET_EXTMS_SUPPORT, 0, ptr::null_mut())
 }
 
+pub unsafe fn SSL_get_secure_renegotiation_support(ssl: *mut SSL) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_GET_RI_SUPPORT, 0, ptr::null_mut())
+}
+
 pub const SSL_SESS_CACHE_OFF: c_long = 0x0;
 pub const SSL_SESS_CACHE_CLIENT: c_long = 0x1;
 pub const SSL_SESS_CACHE_SERVER: c_long = 0x2;
@@ -300,6 +335,7 @@ pub const OPENSSL_NPN_NO_OVERLAP: c_int = 2;
 
 pub const SSL_AD_ILLEGAL_PARAMETER: c_int = SSL3_AD_ILLEGAL_PARAMETER;
 pub const SSL_AD_DECODE_ERROR: c_int = TLS1_AD_DECODE_ERROR;
+pub const SSL_AD_INTERNAL_ERROR: c_int = TLS1_AD_INTERNAL_ERROR;
 pub const SSL_AD_UNRECOGNIZED_NAME: c_int = TLS1_AD_UNRECOGNIZED_NAME;
 pub const SSL_ERROR_NONE: c_int = 0;
 pub const SSL_ERROR_SSL: c_int = 1;
@@ -321,6 +357,18 @@ pub const SSL_CTRL_SET_TMP_ECDH: c_int = 4;
 pub const SSL_CTRL_GET_SESSION_REUSED: c_int = 8;
 pub const SSL_CTRL_EXTRA_CHAIN_CERT: c_int = 14;
 pub const SSL_CTRL_SET_MTU: c_int = 17;
+pub const SSL_CTRL_SESS_NUMBER: c_int = 20;
+pub const SSL_CTRL_SESS_CONNECT: c_int = 21;
+pub const SSL_CTRL_SESS_CONNECT_GOOD: c_int = 22;
+pub const SSL_CTRL_SESS_CONNECT_RENEGOTIATE: c_int = 23;
+pub const SSL_CTRL_SESS_ACCEPT: c_int = 24;
+pub const SSL_CTRL_SESS_ACCEPT_GOOD: c_int = 25;
+pub const SSL_CTRL_SESS_ACCEPT_RENEGOTIATE: c_int = 26;
+pub const SSL_CTRL_SESS_HIT: c_int = 27;
+pub const SSL_CTRL_SESS_CB_HIT: c_int = 28;
+pub const SSL_CTRL_SESS_MISSES: c_int = 29;
+pub const SSL_CTRL_SESS_TIMEOUTS: c_int = 30;
+pub const SSL_CTRL_SESS_CACHE_FULL: c_int = 31;
 #[cfg(any(libressl, all(ossl101, not(ossl110))))]
 pub const SSL_CTRL_OPTIONS: c_int = 32;
 pub const SSL_CTRL_MODE: c_int = 33;
@@ -336,10 +384,13 @@ pub const SSL_CTRL_SET_TLSEXT_STATUS_REQ_CB_ARG: c_int = 64;
 pub const SSL_CTRL_SET_TLSEXT_STATUS_REQ_TYPE: c_int = 65;
 pub const SSL_CTRL_GET_TLSEXT_STATUS_REQ_OCSP_RESP: c_int = 70;
 pub const SSL_CTRL_SET_TLSEXT_STATUS_REQ_OCSP_RESP: c_int = 71;
+pub const SSL_CTRL_GET_RI_SUPPORT: c_int = 76;
 #[cfg(any(libressl, all(ossl101, not(ossl110))))]
 pub const SSL_CTRL_CLEAR_OPTIONS: c_int = 77;
 pub const SSL_CTRL_GET_EXTRA_CHAIN_CERTS: c_int = 82;
 #[cfg(ossl102)]
+pub const SSL_CTRL_CHAIN: c_int = 88;
+#[cfg(ossl102)]
 pub const SSL_CTRL_CHAIN_CERT: c_int = 89;
 #[cfg(any(ossl111, libressl252))]
 pub const SSL_CTRL_SET_GROUPS_LIST: c_int = 92;
@@ -349,6 +400,12 @@ pub const SSL_CTRL_SET_ECDH_AUTO: c_int = 94;
 pub const SSL_CTRL_SET_SIGALGS_LIST: c_int = 98;
 #[cfg(ossl102)]
 pub const SSL_CTRL_SET_VERIFY_CERT_STORE: c_int = 106;
+#[cfg(ossl102)]
+pub const SSL_CTRL_GET_CHAIN_CERTS: c_int = 115;
+#[cfg(ossl102)]
+pub const SSL_CTRL_SELECT_CURRENT_CERT: c_int = 116;
+#[cfg(ossl102)]
+pub const SSL_CTRL_SET_CURRENT_CERT: c_int = 117;
 #[cfg(ossl110)]
 pub const SSL_CTRL_GET_EXTMS_SUPPORT: c_int = 122;
 #[cfg(any(ossl110, libressl261))]
@@ -359,6 +416,19 @@ pub const SSL_CTRL_SET_MAX_PROTO_VERSION: c_int = 124;
 pub const SSL_CTRL_GET_MIN_PROTO_VERSION: c_int = 130;
 #[cfg(any(ossl110g, libressl270))]
 pub const SSL_CTRL_GET_MAX_PROTO_VERSION: c_int = 131;
+#[cfg(ossl110)]
+pub const SSL_CTRL_SET_MAX_SEND_FRAGMENT: c_int = 52;
+#[cfg(ossl110)]
+pub const SSL_CTRL_SET_SPLIT_SEND_FRAGMENT: c_int = 125;
+#[cfg(ossl110)]
+pub const SSL_CTRL_SET_MAX_PIPELINES: c_int = 126;
+
+#[cfg(ossl102)]
+pub const SSL_CERT_SET_FIRST: c_long = 1;
+#[cfg(ossl102)]
+pub const SSL_CERT_SET_NEXT: c_long = 2;
+#[cfg(ossl102)]
+pub const SSL_CERT_SET_SERVER: c_long = 3;
 
 pub unsafe fn SSL_CTX_set_tmp_dh(ctx: *mut SSL_CTX, dh: *mut DH) -> c_long {
     SSL_CTX_ctrl(ctx, SSL_CTRL_SET_TMP_DH, 0, dh as *mut c_void)
@@ -414,11 +484,56 @@ cfg_if! {
     }
 }
 
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_set0_chain(ctx: *mut SSL_CTX, sk: *mut stack_st_X509) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_CHAIN, 0, sk as *mut c_void)
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_clear_chain_certs(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_set0_chain(ctx, ptr::null_mut())
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_add0_chain_cert(ctx: *mut SSL_CTX, ptr: *mut X509) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_CHAIN_CERT, 0, ptr as *mut c_void)
+}
+
 #[cfg(ossl102)]
 pub unsafe fn SSL_add0_chain_cert(ssl: *mut SSL, ptr: *mut X509) -> c_long {
     SSL_ctrl(ssl, SSL_CTRL_CHAIN_CERT, 0, ptr as *mut c_void)
 }
 
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_get0_chain_certs(ctx: *mut SSL_CTX, sk: *mut *mut stack_st_X509) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_GET_CHAIN_CERTS, 0, sk as *mut c_void)
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_get0_chain_certs(ssl: *mut SSL, sk: *mut *mut stack_st_X509) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_GET_CHAIN_CERTS, 0, sk as *mut c_void)
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_select_current_cert(ctx: *mut SSL_CTX, x509: *mut X509) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SELECT_CURRENT_CERT, 0, x509 as *mut c_void)
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_select_current_cert(ssl: *mut SSL, x509: *mut X509) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_SELECT_CURRENT_CERT, 0, x509 as *mut c_void)
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_CTX_set_current_cert(ctx: *mut SSL_CTX, op: c_long) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SET_CURRENT_CERT, op, ptr::null_mut())
+}
+
+#[cfg(ossl102)]
+pub unsafe fn SSL_set_current_cert(ssl: *mut SSL, op: c_long) -> c_long {
+    SSL_ctrl(ssl, SSL_CTRL_SET_CURRENT_CERT, op, ptr::null_mut())
+}
+
 #[cfg(ossl102)]
 pub unsafe fn SSL_CTX_set1_sigalgs_list(ctx: *mut SSL_CTX, s: *const c_char) -> c_long {
     SSL_CTX_ctrl(
@@ -507,6 +622,34 @@ cfg_if! {
     }
 }
 
+cfg_if! {
+    if #[cfg(ossl110)] {
+        pub unsafe fn SSL_CTX_set_max_send_fragment(ctx: *mut SSL_CTX, m: c_long) -> c_long {
+            SSL_CTX_ctrl(ctx, SSL_CTRL_SET_MAX_SEND_FRAGMENT, m, ptr::null_mut())
+        }
+
+        pub unsafe fn SSL_set_max_send_fragment(s: *mut SSL, m: c_long) -> c_long {
+            SSL_ctrl(s, SSL_CTRL_SET_MAX_SEND_FRAGMENT, m, ptr::null_mut())
+        }
+
+        pub unsafe fn SSL_CTX_set_split_send_fragment(ctx: *mut SSL_CTX, m: c_long) -> c_long {
+            SSL_CTX_ctrl(ctx, SSL_CTRL_SET_SPLIT_SEND_FRAGMENT, m, ptr::null_mut())
+        }
+
+        pub unsafe fn SSL_set_split_send_fragment(s: *mut SSL, m: c_long) -> c_long {
+            SSL_ctrl(s, SSL_CTRL_SET_SPLIT_SEND_FRAGMENT, m, ptr::null_mut())
+        }
+
+        pub unsafe fn SSL_CTX_set_max_pipelines(ctx: *mut SSL_CTX, m: c_long) -> c_long {
+            SSL_CTX_ctrl(ctx, SSL_CTRL_SET_MAX_PIPELINES, m, ptr::null_mut())
+        }
+
+        pub unsafe fn SSL_set_max_pipelines(s: *mut SSL, m: c_long) -> c_long {
+            SSL_ctrl(s, SSL_CTRL_SET_MAX_PIPELINES, m, ptr::null_mut())
+        }
+    }
+}
+
 #[cfg(ossl111)]
 pub const SSL_CLIENT_HELLO_SUCCESS: c_int = 1;
 #[cfg(ossl111)]
@@ -556,6 +699,54 @@ pub unsafe fn SSL_CTX_sess_get_cache_size(ctx: *mut SSL_CTX) -> c_long {
     SSL_CTX_ctrl(ctx, SSL_CTRL_GET_SESS_CACHE_SIZE, 0, ptr::null_mut())
 }
 
+pub unsafe fn SSL_CTX_sess_number(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_NUMBER, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_connect(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CONNECT, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_connect_good(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CONNECT_GOOD, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_connect_renegotiate(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CONNECT_RENEGOTIATE, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_accept(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_ACCEPT, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_accept_good(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_ACCEPT_GOOD, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_accept_renegotiate(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_ACCEPT_RENEGOTIATE, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_hits(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_HIT, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_cb_hits(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CB_HIT, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_misses(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_MISSES, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_timeouts(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_TIMEOUTS, 0, ptr::null_mut())
+}
+
+pub unsafe fn SSL_CTX_sess_cache_full(ctx: *mut SSL_CTX) -> c_long {
+    SSL_CTX_ctrl(ctx, SSL_CTRL_SESS_CACHE_FULL, 0, ptr::null_mut())
+}
+
 pub unsafe fn SSL_CTX_set_session_cache_mode(ctx: *mut SSL_CTX, m: c_long) -> c_long {
     SSL_CTX_ctrl(ctx, SSL_CTRL_SET_SESS_CACHE_MODE, m, ptr::null_mut())
 }
@@ -3,3 +3,12 @@ use libc::*;
 pub const SSL3_VERSION: c_int = 0x300;
 
 pub const SSL3_AD_ILLEGAL_PARAMETER: c_int = 47;
+
+pub const SSL3_RT_HANDSHAKE: c_int = 22;
+
+pub const SSL3_MT_CLIENT_HELLO: c_int = 1;
+pub const SSL3_MT_SERVER_HELLO: c_int = 2;
+pub const SSL3_MT_CERTIFICATE: c_int = 11;
+pub const SSL3_MT_CERTIFICATE_REQUEST: c_int = 13;
+pub const SSL3_MT_CERTIFICATE_VERIFY: c_int = 15;
+pub const SSL3_MT_FINISHED: c_int = 20;
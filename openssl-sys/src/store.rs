@@ -0,0 +1,8 @@
+use libc::*;
+
+pub const OSSL_STORE_INFO_NAME: c_int = 1;
+pub const OSSL_STORE_INFO_PARAMS: c_int = 2;
+pub const OSSL_STORE_INFO_PUBKEY: c_int = 3;
+pub const OSSL_STORE_INFO_PKEY: c_int = 4;
+pub const OSSL_STORE_INFO_CERT: c_int = 5;
+pub const OSSL_STORE_INFO_CRL: c_int = 6;
@@ -15,6 +15,7 @@ pub const DTLS1_VERSION: c_int = 0xFEFF;
 pub const DTLS1_2_VERSION: c_int = 0xFEFD;
 
 pub const TLS1_AD_DECODE_ERROR: c_int = 50;
+pub const TLS1_AD_INTERNAL_ERROR: c_int = 80;
 pub const TLS1_AD_UNRECOGNIZED_NAME: c_int = 112;
 
 pub const TLSEXT_NAMETYPE_host_name: c_int = 0;
@@ -1,10 +1,16 @@
 use libc::*;
+use std::ptr;
+
+use super::*;
 
 pub const X509_FILETYPE_PEM: c_int = 1;
 pub const X509_FILETYPE_ASN1: c_int = 2;
 pub const X509_FILETYPE_DEFAULT: c_int = 3;
 
 pub const ASN1_R_HEADER_TOO_LONG: c_int = 123;
+pub const X509_R_CERT_ALREADY_IN_HASH_TABLE: c_int = 101;
+pub const X509_R_KEY_TYPE_MISMATCH: c_int = 115;
+pub const X509_R_KEY_VALUES_MISMATCH: c_int = 116;
 
 cfg_if! {
     if #[cfg(not(any(ossl110, libressl350)))] {
@@ -13,3 +19,8 @@ cfg_if! {
         pub const X509_LU_CRL: c_int = 2;
     }
 }
+
+#[cfg(ossl300)]
+pub unsafe fn X509_NAME_hash(x: *mut X509_NAME) -> c_ulong {
+    X509_NAME_hash_ex(x, ptr::null_mut(), ptr::null(), ptr::null_mut())
+}
@@ -0,0 +1,64 @@
+//! Compares creating a new AES-256-GCM `Crypter` for every packet against reusing one with
+//! `Crypter::reset_with_iv`.
+//!
+//! Run with `cargo bench --bench crypter_reset`.
+
+use openssl::symm::{Cipher, Crypter, Mode};
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 100_000;
+const KEY: [u8; 32] = [0x42; 32];
+const PACKET: [u8; 64] = [0x17; 64];
+
+fn nonce(i: u32) -> [u8; 12] {
+    let mut nonce = [0; 12];
+    nonce[8..].copy_from_slice(&i.to_be_bytes());
+    nonce
+}
+
+fn seal(crypter: &mut Crypter, out: &mut [u8], tag: &mut [u8]) {
+    let len = crypter.update(&PACKET, out).unwrap();
+    crypter.finalize(&mut out[len..]).unwrap();
+    crypter.get_tag(tag).unwrap();
+}
+
+fn time<F>(name: &str, mut f: F) -> Duration
+where
+    F: FnMut(u32),
+{
+    // Warm up.
+    for i in 0..ITERATIONS / 10 {
+        f(i);
+    }
+
+    let start = Instant::now();
+    for i in 0..ITERATIONS {
+        f(i);
+    }
+    let per_iter = start.elapsed() / ITERATIONS;
+    println!("{:<24} {:>10?} per packet", name, per_iter);
+    per_iter
+}
+
+fn main() {
+    let cipher = Cipher::aes_256_gcm();
+    let mut out = [0; 80];
+    let mut tag = [0; 16];
+
+    println!("seal {} byte packet", PACKET.len());
+    let fresh = time("new Crypter", |i| {
+        let mut crypter = Crypter::new(cipher, Mode::Encrypt, &KEY, Some(&nonce(i))).unwrap();
+        seal(&mut crypter, &mut out, &mut tag);
+    });
+
+    let mut crypter = Crypter::new(cipher, Mode::Encrypt, &KEY, Some(&nonce(0))).unwrap();
+    let reused = time("Crypter::reset_with_iv", |i| {
+        crypter.reset_with_iv(&nonce(i)).unwrap();
+        seal(&mut crypter, &mut out, &mut tag);
+    });
+    println!(
+        "{:<24} {:>10.1}x",
+        "speedup",
+        fresh.as_secs_f64() / reused.as_secs_f64()
+    );
+}
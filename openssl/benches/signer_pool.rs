@@ -0,0 +1,90 @@
+//! Compares creating a new RSA-PSS `Signer` for every signature against reusing contexts from a
+//! `SignerPool`.
+//!
+//! Run with `cargo bench --bench signer_pool`.
+
+#[cfg(ossl110)]
+mod bench {
+    use openssl::hash::MessageDigest;
+    use openssl::pkey::{PKey, Private};
+    use openssl::rsa::{Padding, Rsa};
+    use openssl::sign::{RsaPssSaltlen, Signer, SignerPool};
+    use std::time::{Duration, Instant};
+
+    const ITERATIONS: u32 = 1000;
+    const DATA: &[u8] = b"eyJhbGciOiJQUzI1NiJ9.eyJzdWIiOiIxMjM0NTY3ODkwIn0";
+
+    fn pss_signer(key: &PKey<Private>) -> Signer<'static> {
+        let mut signer = Signer::new(MessageDigest::sha256(), key).unwrap();
+        signer.set_rsa_padding(Padding::PKCS1_PSS).unwrap();
+        signer
+            .set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)
+            .unwrap();
+        signer
+    }
+
+    fn time<F>(name: &str, mut f: F)
+    where
+        F: FnMut(),
+    {
+        // Warm up.
+        for _ in 0..ITERATIONS / 10 {
+            f();
+        }
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            f();
+        }
+        let per_iter = start.elapsed() / ITERATIONS;
+        println!("{:<24} {:>10?} per signature", name, per_iter);
+    }
+
+    fn setup_only<F>(name: &str, mut f: F) -> Duration
+    where
+        F: FnMut(),
+    {
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            f();
+        }
+        let per_iter = start.elapsed() / ITERATIONS;
+        println!("{:<24} {:>10?} per context", name, per_iter);
+        per_iter
+    }
+
+    pub fn run() {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let pool = SignerPool::new(pss_signer(&key), 1).unwrap();
+
+        println!("context setup");
+        let fresh = setup_only("new Signer", || {
+            pss_signer(&key);
+        });
+        let pooled = setup_only("SignerPool::get", || {
+            pool.get().unwrap();
+        });
+        println!(
+            "{:<24} {:>10.1}x",
+            "speedup",
+            fresh.as_secs_f64() / pooled.as_secs_f64()
+        );
+
+        println!("\nsign {} byte message", DATA.len());
+        time("new Signer", || {
+            let mut signer = pss_signer(&key);
+            signer.update(DATA).unwrap();
+            signer.sign_to_vec().unwrap();
+        });
+        time("SignerPool::get", || {
+            let mut signer = pool.get().unwrap();
+            signer.update(DATA).unwrap();
+            signer.sign_to_vec().unwrap();
+        });
+    }
+}
+
+fn main() {
+    #[cfg(ossl110)]
+    bench::run();
+}
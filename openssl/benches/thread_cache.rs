@@ -0,0 +1,64 @@
+//! Compares the one-shot hashing, encryption, and signing functions on short messages with and
+//! without the per-thread context cache.
+//!
+//! Run with `cargo bench --bench thread_cache`.
+
+use openssl::hash::{hash, MessageDigest};
+use openssl::pkey::PKey;
+use openssl::sign::{hmac, sign_oneshot};
+use openssl::symm::{encrypt, Cipher};
+use openssl::thread_cache;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 100_000;
+const DATA: [u8; 64] = [0x17; 64];
+
+fn time<F>(name: &str, mut f: F) -> Duration
+where
+    F: FnMut(),
+{
+    // Warm up.
+    for _ in 0..ITERATIONS / 10 {
+        f();
+    }
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let per_iter = start.elapsed() / ITERATIONS;
+    println!("{:<24} {:>10?} per call", name, per_iter);
+    per_iter
+}
+
+fn compare<F>(name: &str, mut f: F)
+where
+    F: FnMut(),
+{
+    println!("{}", name);
+    thread_cache::set_enabled(false);
+    let uncached = time("uncached", &mut f);
+    thread_cache::set_enabled(true);
+    let cached = time("cached", &mut f);
+    println!(
+        "{:<24} {:>10.1}x\n",
+        "speedup",
+        uncached.as_secs_f64() / cached.as_secs_f64()
+    );
+}
+
+fn main() {
+    compare("hash::hash sha256, 64 bytes", || {
+        hash(MessageDigest::sha256(), &DATA).unwrap();
+    });
+    compare("symm::encrypt aes-128-cbc, 64 bytes", || {
+        encrypt(Cipher::aes_128_cbc(), &[0x42; 16], Some(&[0x24; 16]), &DATA).unwrap();
+    });
+    compare("sign::hmac sha256, 64 bytes", || {
+        hmac(MessageDigest::sha256(), &[0x42; 32], &DATA).unwrap();
+    });
+    let key = PKey::generate_ed25519().unwrap();
+    compare("sign::sign_oneshot ed25519, 64 bytes", || {
+        sign_oneshot(None, &key, &DATA).unwrap();
+    });
+}
@@ -61,8 +61,9 @@ let mut iv = *b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0A\x0B\x0C\x0D\x0E\x0F
 //! ```
 //!
 use cfg_if::cfg_if;
-use libc::{c_int, c_uint};
-use std::mem::MaybeUninit;
+use libc::{c_int, c_uint, c_void};
+use std::fmt;
+use std::mem::{self, MaybeUninit};
 use std::ptr;
 
 #[cfg(not(boringssl))]
@@ -74,6 +75,9 @@ use openssl_macros::corresponds;
 pub struct KeyError(());
 
 /// The key used to encrypt or decrypt cipher blocks.
+///
+/// The expanded key schedule is erased when the key is dropped, and is never included in the
+/// `Debug` representation.
 pub struct AesKey(ffi::AES_KEY);
 
 cfg_if! {
@@ -137,6 +141,39 @@ impl AesKey {
     }
 }
 
+impl AesKey {
+    fn cleanse(&mut self) {
+        unsafe {
+            ffi::OPENSSL_cleanse(
+                &mut self.0 as *mut ffi::AES_KEY as *mut c_void,
+                mem::size_of::<ffi::AES_KEY>(),
+            );
+        }
+    }
+}
+
+impl Drop for AesKey {
+    fn drop(&mut self) {
+        self.cleanse();
+    }
+}
+
+impl fmt::Debug for AesKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AesKey")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for AesKey {
+    fn zeroize(&mut self) {
+        self.cleanse();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for AesKey {}
+
 /// Performs AES IGE encryption or decryption
 ///
 /// AES IGE (Infinite Garble Extension) is a form of AES block cipher utilized in
@@ -316,4 +353,26 @@ mod test {
         );
         assert_eq!(&unwrapped[..], &key_data[..]);
     }
+
+    #[test]
+    fn debug_redacted() {
+        let key = AesKey::new_encrypt(&[0x42; 16]).unwrap();
+        assert_eq!(format!("{:?}", key), "AesKey");
+    }
+
+    #[test]
+    #[cfg(feature = "zeroize")]
+    fn zeroize() {
+        use zeroize::Zeroize;
+
+        let mut key = AesKey::new_encrypt(&[0x42; 16]).unwrap();
+        key.zeroize();
+        let raw = unsafe {
+            std::slice::from_raw_parts(
+                &key.0 as *const ffi::AES_KEY as *const u8,
+                mem::size_of::<ffi::AES_KEY>(),
+            )
+        };
+        assert!(raw.iter().all(|&b| b == 0));
+    }
 }
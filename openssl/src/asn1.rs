@@ -34,9 +34,11 @@ use std::fmt;
 use std::ptr;
 use std::slice;
 use std::str;
+#[cfg(ossl102)]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::bio::MemBio;
-use crate::bn::{BigNum, BigNumRef};
+use crate::bn::{BigNum, BigNumRef, MsbOption};
 use crate::error::ErrorStack;
 use crate::nid::Nid;
 use crate::stack::Stackable;
@@ -66,6 +68,14 @@ foreign_type_and_impl_send_sync! {
     pub struct Asn1GeneralizedTimeRef;
 }
 
+impl Asn1GeneralizedTimeRef {
+    /// Returns the time as an [`Asn1TimeRef`], which supports comparisons and conversions.
+    pub fn as_time(&self) -> &Asn1TimeRef {
+        // an ASN1_TIME is either a UTCTime or a GeneralizedTime
+        unsafe { Asn1TimeRef::from_ptr(self.as_ptr() as *mut ffi::ASN1_TIME) }
+    }
+}
+
 impl fmt::Display for Asn1GeneralizedTimeRef {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         unsafe {
@@ -208,7 +218,7 @@ impl Asn1TimeRef {
         let err = unsafe { ffi::ASN1_TIME_diff(&mut days, &mut secs, self.as_ptr(), other) };
 
         match err {
-            0 => Err(ErrorStack::get()),
+            0 => Err(ErrorStack::report()),
             _ => Ok(TimeDiff { days, secs }),
         }
     }
@@ -227,6 +237,19 @@ impl Asn1TimeRef {
 
         Ok(Ordering::Equal)
     }
+
+    /// Converts the time to a `SystemTime`.
+    #[cfg(ossl102)]
+    pub fn to_system_time(&self) -> Result<SystemTime, ErrorStack> {
+        let epoch = Asn1Time::from_unix(0)?;
+        let diff = epoch.diff(self)?;
+        let secs = i64::from(diff.days) * 60 * 60 * 24 + i64::from(diff.secs);
+        if secs >= 0 {
+            Ok(UNIX_EPOCH + Duration::from_secs(secs as u64))
+        } else {
+            Ok(UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs()))
+        }
+    }
 }
 
 #[cfg(ossl102)]
@@ -442,7 +465,7 @@ impl Asn1StringRef {
             let mut ptr = ptr::null_mut();
             let len = ffi::ASN1_STRING_to_UTF8(&mut ptr, self.as_ptr());
             if len < 0 {
-                return Err(ErrorStack::get());
+                return Err(ErrorStack::report());
             }
 
             Ok(OpensslString::from_ptr(ptr as *mut c_char))
@@ -510,6 +533,23 @@ impl Asn1Integer {
     pub fn from_bn(bn: &BigNumRef) -> Result<Self, ErrorStack> {
         bn.to_asn1_integer()
     }
+
+    /// Generates a random certificate serial number.
+    ///
+    /// The serial number is a positive integer of up to 159 random bits, so that it fits in the 20
+    /// octets permitted by RFC 5280 and comfortably exceeds the 64 bits of entropy required by the
+    /// CA/Browser Forum Baseline Requirements.
+    #[corresponds(BN_rand)]
+    pub fn rand_serial() -> Result<Self, ErrorStack> {
+        let mut bn = BigNum::new()?;
+        loop {
+            bn.rand(159, MsbOption::MAYBE_ZERO, false)?;
+            // zero is not a valid serial number
+            if bn.num_bits() > 0 {
+                return bn.to_asn1_integer();
+            }
+        }
+    }
 }
 
 impl Ord for Asn1Integer {
@@ -739,10 +779,10 @@ impl fmt::Debug for Asn1ObjectRef {
 
 cfg_if! {
     if #[cfg(any(ossl110, libressl273, boringssl))] {
-        use ffi::ASN1_STRING_get0_data;
+        pub(crate) use ffi::ASN1_STRING_get0_data;
     } else {
         #[allow(bad_style)]
-        unsafe fn ASN1_STRING_get0_data(s: *mut ffi::ASN1_STRING) -> *const ::libc::c_uchar {
+        pub(crate) unsafe fn ASN1_STRING_get0_data(s: *mut ffi::ASN1_STRING) -> *const ::libc::c_uchar {
             ffi::ASN1_STRING_data(s)
         }
     }
@@ -795,6 +835,18 @@ mod tests {
         roundtrip(-BigNum::from_u32(1234).unwrap());
     }
 
+    #[test]
+    fn rand_serial() {
+        let a = Asn1Integer::rand_serial().unwrap().to_bn().unwrap();
+        let b = Asn1Integer::rand_serial().unwrap().to_bn().unwrap();
+        assert_ne!(a, b);
+        for serial in [a, b] {
+            assert!(!serial.is_negative());
+            assert!(serial.num_bits() > 64);
+            assert!(serial.num_bits() <= 159);
+        }
+    }
+
     #[test]
     fn time_from_str() {
         Asn1Time::from_str("99991231235959Z").unwrap();
@@ -857,6 +909,21 @@ mod tests {
         assert!(c_ref < a_ref);
     }
 
+    #[test]
+    #[cfg(ossl102)]
+    fn time_to_system_time() {
+        let t = Asn1Time::from_unix(1_700_000_000).unwrap();
+        assert_eq!(
+            t.to_system_time().unwrap(),
+            UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+        );
+        let t = Asn1Time::from_str("19491231235959Z").unwrap();
+        assert_eq!(
+            t.to_system_time().unwrap(),
+            UNIX_EPOCH - Duration::from_secs(631_152_001)
+        );
+    }
+
     #[test]
     fn integer_to_owned() {
         let a = Asn1Integer::from_bn(&BigNum::from_dec_str("42").unwrap()).unwrap();
@@ -1,11 +1,13 @@
 use cfg_if::cfg_if;
 use libc::c_int;
+use std::io::{self, Read, Write};
 use std::marker::PhantomData;
 use std::ptr;
 use std::slice;
 
 use crate::cvt_p;
 use crate::error::ErrorStack;
+use crate::ssl::{self, bio::BioMethod};
 
 pub struct MemBioSlice<'a>(*mut ffi::BIO, PhantomData<&'a [u8]>);
 
@@ -73,6 +75,128 @@ impl MemBio {
     }
 }
 
+/// A BIO backed by a Rust stream.
+pub struct StreamBio<S> {
+    bio: *mut ffi::BIO,
+    _method: BioMethod,
+    _p: PhantomData<S>,
+}
+
+impl<S> Drop for StreamBio<S> {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::BIO_free_all(self.bio);
+        }
+    }
+}
+
+impl<S: Read + Write> StreamBio<S> {
+    pub fn new(stream: S) -> Result<StreamBio<S>, ErrorStack> {
+        ffi::init();
+
+        let (bio, method) = ssl::bio::new(stream)?;
+        Ok(StreamBio {
+            bio,
+            _method: method,
+            _p: PhantomData,
+        })
+    }
+
+    pub fn as_ptr(&self) -> *mut ffi::BIO {
+        self.bio
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        unsafe { ssl::bio::get_mut(self.bio) }
+    }
+
+    /// Returns the error reported by the stream.
+    ///
+    /// If the stream panicked, the panic is resumed or reported as an error depending on the
+    /// [`PanicPolicy`].
+    ///
+    /// [`PanicPolicy`]: crate::panic::PanicPolicy
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        unsafe {
+            if let Some(panic) = ssl::bio::take_panic::<S>(self.bio) {
+                let msg = crate::panic::message(&*panic);
+                crate::panic::resume(panic);
+                return Some(io::Error::new(io::ErrorKind::Other, msg));
+            }
+            ssl::bio::take_error::<S>(self.bio)
+        }
+    }
+}
+
+/// Adapts a reader to the `Read + Write` bound of `StreamBio`.
+pub struct ReadOnly<R>(pub R);
+
+impl<R: Read> Read for ReadOnly<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl<R> Write for ReadOnly<R> {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "stream is not writable",
+        ))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Adapts a writer to the `Read + Write` bound of `StreamBio`.
+pub struct WriteOnly<W>(pub W);
+
+impl<W> Read for WriteOnly<W> {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "stream is not readable",
+        ))
+    }
+}
+
+impl<W: Write> Write for WriteOnly<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Calls `f` with BIOs reading from `input` and writing to `output`, and flushes `output` if it
+/// succeeds.
+///
+/// `f` should return the result of the OpenSSL function, where a value of 0 or less indicates an
+/// error. Errors reported by the streams take precedence over the OpenSSL error stack.
+pub fn stream<R, W, F>(input: R, output: W, f: F) -> io::Result<()>
+where
+    R: Read,
+    W: Write,
+    F: FnOnce(*mut ffi::BIO, *mut ffi::BIO) -> c_int,
+{
+    let mut input = StreamBio::new(ReadOnly(input))?;
+    let mut output = StreamBio::new(WriteOnly(output))?;
+
+    let r = f(input.as_ptr(), output.as_ptr());
+    let input_error = input.take_error();
+    let output_error = output.take_error();
+    if r <= 0 {
+        let error = ErrorStack::report();
+        return Err(input_error.or(output_error).unwrap_or_else(|| error.into()));
+    }
+
+    output.get_mut().flush()
+}
+
 cfg_if! {
     if #[cfg(any(ossl102, boringssl))] {
         use ffi::BIO_new_mem_buf;
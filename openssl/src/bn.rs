@@ -188,7 +188,7 @@ impl BigNumRef {
         unsafe {
             let r = ffi::BN_div_word(self.as_ptr(), w.into());
             if r == ffi::BN_ULONG::max_value() {
-                Err(ErrorStack::get())
+                Err(ErrorStack::report())
             } else {
                 Ok(r.into())
             }
@@ -202,7 +202,7 @@ impl BigNumRef {
         unsafe {
             let r = ffi::BN_mod_word(self.as_ptr(), w.into());
             if r == ffi::BN_ULONG::max_value() {
-                Err(ErrorStack::get())
+                Err(ErrorStack::report())
             } else {
                 Ok(r.into())
             }
@@ -1145,6 +1145,20 @@ impl BigNum {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for BigNumRef {
+    fn zeroize(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for BigNum {
+    fn zeroize(&mut self) {
+        self.clear();
+    }
+}
+
 impl fmt::Debug for BigNumRef {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.to_dec_str() {
@@ -7,6 +7,8 @@ use crate::error::ErrorStack;
 #[cfg(ossl300)]
 use crate::lib_ctx::LibCtxRef;
 use crate::nid::Nid;
+#[cfg(ossl300)]
+use crate::policy::{self, Operation};
 use cfg_if::cfg_if;
 use foreign_types::{ForeignTypeRef, Opaque};
 use openssl_macros::corresponds;
@@ -130,10 +132,16 @@ impl Cipher {
             let ptr = cvt_p(ffi::EVP_CIPHER_fetch(
                 ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
                 algorithm.as_ptr(),
-                properties.map_or(ptr::null_mut(), |s| s.as_ptr()),
+                properties.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
             ))?;
+            let cipher = Cipher::from_ptr(ptr);
+            policy::check(
+                ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+                cipher.nid(),
+                Operation::Cipher,
+            )?;
 
-            Ok(Cipher::from_ptr(ptr))
+            Ok(cipher)
         }
     }
 
@@ -517,4 +525,15 @@ impl CipherRef {
     pub fn block_size(&self) -> usize {
         unsafe { EVP_CIPHER_block_size(self.as_ptr()) as usize }
     }
+
+    /// Determines if the cipher can process multiple buffers in a single call.
+    ///
+    /// Pipelining is typically only supported by ciphers provided by hardware accelerator engines.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(EVP_CIPHER_flags)]
+    #[cfg(ossl110)]
+    pub fn supports_pipeline(&self) -> bool {
+        unsafe { ffi::EVP_CIPHER_flags(self.as_ptr()) & ffi::EVP_CIPH_FLAG_PIPELINE != 0 }
+    }
 }
@@ -54,6 +54,8 @@ use crate::cipher::CipherRef;
 use crate::error::ErrorStack;
 #[cfg(not(boringssl))]
 use crate::pkey::{HasPrivate, HasPublic, PKey, PKeyRef};
+#[cfg(ossl300)]
+use crate::policy::{self, Operation};
 use crate::{cvt, cvt_p};
 #[cfg(ossl102)]
 use bitflags::bitflags;
@@ -158,6 +160,11 @@ impl CipherCtxRef {
             *const c_uchar,
         ) -> c_int,
     ) -> Result<(), ErrorStack> {
+        #[cfg(ossl300)]
+        if let Some(type_) = type_ {
+            policy::check(ptr::null_mut(), type_.nid(), Operation::Cipher)?;
+        }
+
         if let Some(key) = key {
             let key_len = type_.map_or_else(|| self.key_length(), |c| c.key_length());
             assert!(key_len <= key.len());
@@ -390,6 +397,39 @@ impl CipherCtxRef {
         unsafe { ffi::EVP_CIPHER_CTX_num(self.as_ptr()) as usize }
     }
 
+    /// Copies the current IV of the cipher into `buf`, returning the number of bytes written.
+    ///
+    /// In modes such as OFB and CFB, the IV is updated as data is processed and holds the state
+    /// carried into the next block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the context has not been initialized with a cipher, or if `buf` is smaller than
+    /// the IV length.
+    #[corresponds(EVP_CIPHER_CTX_get_updated_iv)]
+    #[cfg(ossl110)]
+    pub fn updated_iv(&self, buf: &mut [u8]) -> Result<usize, ErrorStack> {
+        let len = self.iv_length();
+        assert!(buf.len() >= len);
+
+        unsafe {
+            cfg_if! {
+                if #[cfg(ossl300)] {
+                    cvt(ffi::EVP_CIPHER_CTX_get_updated_iv(
+                        self.as_ptr(),
+                        buf.as_mut_ptr() as *mut _,
+                        len,
+                    ))?;
+                } else {
+                    let iv = ffi::EVP_CIPHER_CTX_iv(self.as_ptr());
+                    ptr::copy_nonoverlapping(iv, buf.as_mut_ptr(), len);
+                }
+            }
+        }
+
+        Ok(len)
+    }
+
     /// Sets the length of the IV expected by this context.
     ///
     /// Only some ciphers support configurable IV lengths.
@@ -500,6 +540,21 @@ impl CipherCtxRef {
         }
     }
 
+    /// Resets the context, clearing the cipher, key, and IV.
+    ///
+    /// The context can then be used for a new operation as if it were newly created.
+    ///
+    /// Requires OpenSSL 1.1.0 or LibreSSL 2.7.0 or newer.
+    #[corresponds(EVP_CIPHER_CTX_reset)]
+    #[cfg(any(ossl110, libressl270, boringssl))]
+    pub fn reset(&mut self) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EVP_CIPHER_CTX_reset(self.as_ptr()))?;
+        }
+
+        Ok(())
+    }
+
     /// Sets the total length of plaintext data.
     ///
     /// This is required for ciphers operating in CCM mode.
@@ -657,6 +712,74 @@ impl CipherCtxRef {
         Ok(outlen as usize)
     }
 
+    /// Encrypts or decrypts several independent buffers in a single call.
+    ///
+    /// Each buffer in `inputs` is processed into the buffer at the same index in `outputs`. This
+    /// allows a pipelining-capable implementation, such as a hardware accelerator engine, to work
+    /// on all of the buffers in parallel.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the context's cipher does not support pipelining (see
+    /// [`CipherRef::supports_pipeline`]), if `inputs` and `outputs` have different lengths or are
+    /// empty, or if any output buffer is smaller than its input buffer.
+    #[corresponds(EVP_Cipher)]
+    #[cfg(ossl110)]
+    pub fn cipher_update_pipeline(
+        &mut self,
+        inputs: &[&[u8]],
+        outputs: &mut [&mut [u8]],
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            let cipher = EVP_CIPHER_CTX_get0_cipher(self.as_ptr());
+            assert!(!cipher.is_null());
+            assert!(
+                CipherRef::from_ptr(cipher as *mut _).supports_pipeline(),
+                "Cipher does not support pipelining"
+            );
+        }
+        assert!(!inputs.is_empty());
+        assert_eq!(inputs.len(), outputs.len());
+        for (input, output) in inputs.iter().zip(outputs.iter()) {
+            assert!(output.len() >= input.len());
+        }
+
+        let n = c_int::try_from(inputs.len()).unwrap();
+        let mut ins = inputs.iter().map(|i| i.as_ptr()).collect::<Vec<_>>();
+        let mut outs = outputs
+            .iter_mut()
+            .map(|o| o.as_mut_ptr())
+            .collect::<Vec<_>>();
+        let mut lens = inputs.iter().map(|i| i.len()).collect::<Vec<_>>();
+        let first_len = u32::try_from(lens[0]).unwrap();
+
+        unsafe {
+            cvt(ffi::EVP_CIPHER_CTX_ctrl(
+                self.as_ptr(),
+                ffi::EVP_CTRL_SET_PIPELINE_OUTPUT_BUFS,
+                n,
+                outs.as_mut_ptr() as *mut _,
+            ))?;
+            cvt(ffi::EVP_CIPHER_CTX_ctrl(
+                self.as_ptr(),
+                ffi::EVP_CTRL_SET_PIPELINE_INPUT_BUFS,
+                n,
+                ins.as_mut_ptr() as *mut _,
+            ))?;
+            cvt(ffi::EVP_CIPHER_CTX_ctrl(
+                self.as_ptr(),
+                ffi::EVP_CTRL_SET_PIPELINE_INPUT_LENS,
+                n,
+                lens.as_mut_ptr() as *mut _,
+            ))?;
+            cvt(ffi::EVP_Cipher(self.as_ptr(), outs[0], ins[0], first_len))?;
+        }
+
+        Ok(())
+    }
+
     /// Finalizes the encryption or decryption process.
     ///
     /// Any remaining data will be written to the output buffer.
@@ -1095,4 +1218,15 @@ mod test {
 
         cipher_wrap_test(Cipher::aes_256_wrap_pad(), pt, ct, key, None);
     }
+
+    #[test]
+    #[cfg(ossl110)]
+    #[should_panic(expected = "Cipher does not support pipelining")]
+    fn pipeline_unsupported() {
+        let mut ctx = CipherCtx::new().unwrap();
+        ctx.encrypt_init(Some(Cipher::aes_128_cbc()), Some(&[0; 16]), Some(&[0; 16]))
+            .unwrap();
+        let mut out = [0; 16];
+        let _ = ctx.cipher_update_pipeline(&[&[0; 16]], &mut [&mut out]);
+    }
 }
@@ -7,15 +7,24 @@
 
 use bitflags::bitflags;
 use foreign_types::{ForeignType, ForeignTypeRef};
-use libc::c_uint;
+use libc::{c_int, c_uint};
+use std::convert::TryInto;
+use std::io::{self, Read, Write};
 use std::ptr;
 
-use crate::bio::{MemBio, MemBioSlice};
+#[cfg(ossl110)]
+use crate::asn1::Asn1OctetStringRef;
+use crate::asn1::Asn1StringRef;
+use crate::bio::{self, MemBio, MemBioSlice};
 use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::nid::Nid;
+use crate::pkcs7::Pkcs7;
 use crate::pkey::{HasPrivate, PKeyRef};
-use crate::stack::StackRef;
+use crate::stack::{StackRef, Stackable};
 use crate::symm::Cipher;
-use crate::x509::{store::X509StoreRef, X509Ref, X509};
+use crate::util::ForeignTypeRefExt;
+use crate::x509::{store::X509StoreRef, X509AlgorithmRef, X509Ref, X509};
 use crate::{cvt, cvt_p};
 use openssl_macros::corresponds;
 
@@ -45,6 +54,12 @@ bitflags! {
         const KEY_PARAM = ffi::CMS_KEY_PARAM;
         #[cfg(all(not(libressl), not(ossl101), not(ossl102)))]
         const ASCIICRLF = ffi::CMS_ASCIICRLF;
+        /// Adds an ESS signing-certificate-v2 attribute when signing, producing a CAdES-BES signature, and
+        /// requires it when verifying.
+        ///
+        /// Requires OpenSSL 3.0.0 or newer.
+        #[cfg(ossl300)]
+        const CADES = ffi::CMS_CADES;
     }
 }
 
@@ -67,7 +82,141 @@ foreign_type_and_impl_send_sync! {
     pub struct CmsContentInfoRef;
 }
 
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::CMS_SignerInfo;
+    fn drop = cms_signer_info_free;
+
+    /// Information about a single signer of a `CmsContentInfo`.
+    pub struct CmsSignerInfo;
+    /// Reference to [`CmsSignerInfo`].
+    pub struct CmsSignerInfoRef;
+}
+
+// signer infos are only ever borrowed from their content info, and OpenSSL does not export a free function
+unsafe fn cms_signer_info_free(_si: *mut ffi::CMS_SignerInfo) {}
+
+impl Stackable for CmsSignerInfo {
+    type StackType = ffi::stack_st_CMS_SignerInfo;
+}
+
+impl CmsSignerInfoRef {
+    /// Returns the signature value.
+    ///
+    /// An RFC 3161 timestamp over this value upgrades a CAdES-BES signature to CAdES-T.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(CMS_SignerInfo_get0_signature)]
+    #[cfg(ossl110)]
+    pub fn signature(&self) -> &Asn1OctetStringRef {
+        unsafe { Asn1OctetStringRef::from_ptr(ffi::CMS_SignerInfo_get0_signature(self.as_ptr())) }
+    }
+
+    /// Returns the digest algorithm used to hash the content and signed attributes.
+    #[corresponds(CMS_SignerInfo_get0_algs)]
+    pub fn digest_algorithm(&self) -> &X509AlgorithmRef {
+        unsafe {
+            let mut algor = ptr::null_mut();
+            ffi::CMS_SignerInfo_get0_algs(
+                self.as_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut algor,
+                ptr::null_mut(),
+            );
+            X509AlgorithmRef::from_const_ptr_opt(algor).expect("digest algorithm must not be null")
+        }
+    }
+
+    /// Returns the signature algorithm.
+    #[corresponds(CMS_SignerInfo_get0_algs)]
+    pub fn signature_algorithm(&self) -> &X509AlgorithmRef {
+        unsafe {
+            let mut algor = ptr::null_mut();
+            ffi::CMS_SignerInfo_get0_algs(
+                self.as_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut algor,
+            );
+            X509AlgorithmRef::from_const_ptr_opt(algor)
+                .expect("signature algorithm must not be null")
+        }
+    }
+
+    /// Determines if the signed attributes contain an attribute of the given type.
+    #[corresponds(CMS_signed_get_attr_by_NID)]
+    pub fn has_signed_attribute(&self, nid: Nid) -> bool {
+        unsafe { ffi::CMS_signed_get_attr_by_NID(self.as_ptr(), nid.as_raw(), -1) >= 0 }
+    }
+
+    /// Adds a DER encoded RFC 3161 `TimeStampToken` as an unsigned signature-time-stamp attribute.
+    ///
+    /// The token's message imprint should be a hash of [`signature`]. The token itself is not verified.
+    ///
+    /// [`signature`]: CmsSignerInfoRef::signature
+    #[corresponds(CMS_unsigned_add1_attr_by_NID)]
+    pub fn add_timestamp_token(&mut self, token: &[u8]) -> Result<(), ErrorStack> {
+        // a TimeStampToken is a SignedData ContentInfo
+        Pkcs7::from_der(token)?;
+        unsafe {
+            cvt(ffi::CMS_unsigned_add1_attr_by_NID(
+                self.as_ptr(),
+                ffi::NID_id_smime_aa_timeStampToken,
+                ffi::V_ASN1_SEQUENCE,
+                token.as_ptr() as *const _,
+                token.len().try_into().unwrap(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Returns the DER encoded RFC 3161 `TimeStampToken` in the unsigned signature-time-stamp attribute, if any.
+    #[corresponds(CMS_unsigned_get_attr_by_NID)]
+    pub fn timestamp_token(&self) -> Option<&[u8]> {
+        unsafe {
+            let loc = ffi::CMS_unsigned_get_attr_by_NID(
+                self.as_ptr(),
+                ffi::NID_id_smime_aa_timeStampToken,
+                -1,
+            );
+            if loc < 0 {
+                return None;
+            }
+            let attr = ffi::CMS_unsigned_get_attr(self.as_ptr(), loc);
+            let value = ffi::X509_ATTRIBUTE_get0_type(attr, 0);
+            if value.is_null() || (*value).type_ != ffi::V_ASN1_SEQUENCE {
+                return None;
+            }
+            Some(Asn1StringRef::from_ptr((*value).value.sequence).as_slice())
+        }
+    }
+}
+
 impl CmsContentInfoRef {
+    /// Returns the signer infos of signed data.
+    ///
+    /// Returns `None` if the content is not signed data.
+    #[corresponds(CMS_get0_SignerInfos)]
+    pub fn signer_infos(&self) -> Option<&StackRef<CmsSignerInfo>> {
+        unsafe { StackRef::from_const_ptr_opt(ffi::CMS_get0_SignerInfos(self.as_ptr())) }
+    }
+
+    /// Returns a mutable reference to the signer infos of signed data.
+    ///
+    /// Returns `None` if the content is not signed data.
+    #[corresponds(CMS_get0_SignerInfos)]
+    pub fn signer_infos_mut(&mut self) -> Option<&mut StackRef<CmsSignerInfo>> {
+        unsafe {
+            let ptr = ffi::CMS_get0_SignerInfos(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(StackRef::from_ptr_mut(ptr))
+            }
+        }
+    }
+
     /// Given the sender's private key, `pkey` and the recipient's certificate, `cert`,
     /// decrypt the data in `self`.
     #[corresponds(CMS_decrypt)]
@@ -120,6 +269,25 @@ impl CmsContentInfoRef {
         }
     }
 
+    /// Decompresses the content of a compressed-data CmsContentInfo.
+    ///
+    /// OpenSSL only supports zlib compression, and fails if it was built without zlib.
+    #[corresponds(CMS_uncompress)]
+    pub fn uncompress(&self, flags: CMSOptions) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let out = MemBio::new()?;
+
+            cvt(ffi::CMS_uncompress(
+                self.as_ptr(),
+                ptr::null_mut(),
+                out.as_ptr(),
+                flags.bits(),
+            ))?;
+
+            Ok(out.get_buf().to_owned())
+        }
+    }
+
     to_der! {
         /// Serializes this CmsContentInfo using DER.
         #[corresponds(i2d_CMS_ContentInfo)]
@@ -133,6 +301,56 @@ impl CmsContentInfoRef {
         to_pem,
         ffi::PEM_write_bio_CMS
     }
+
+    /// Serializes this CmsContentInfo using BER, reading the content from `input` as it is
+    /// written to `output`.
+    ///
+    /// The CmsContentInfo must have been created with `CMSOptions::STREAM`, in which case
+    /// `CmsContentInfo::sign` and `CmsContentInfo::encrypt` do not read their data argument, and
+    /// the content is signed or encrypted here instead. The content is never held in memory in
+    /// full, so this is suitable for payloads too large to buffer. The output uses indefinite
+    /// length encodings, and the content is omitted from it if `CMSOptions::DETACHED` was set.
+    ///
+    /// `flags` should be the flags the CmsContentInfo was created with.
+    #[corresponds(i2d_CMS_bio_stream)]
+    pub fn write_der_stream<R, W>(
+        &mut self,
+        input: R,
+        output: W,
+        flags: CMSOptions,
+    ) -> io::Result<()>
+    where
+        R: Read,
+        W: Write,
+    {
+        bio::stream(input, output, |input, output| unsafe {
+            ffi::i2d_CMS_bio_stream(output, self.as_ptr(), input, flags.bits() as c_int)
+        })
+    }
+
+    /// Converts this CmsContentInfo to S/MIME format, reading the content from `input` as it is
+    /// written to `output`.
+    ///
+    /// As with [`write_der_stream`], the CmsContentInfo must have been created with
+    /// `CMSOptions::STREAM`. A detached signature is written as a `multipart/signed` message
+    /// containing the content.
+    ///
+    /// [`write_der_stream`]: CmsContentInfoRef::write_der_stream
+    #[corresponds(SMIME_write_CMS)]
+    pub fn write_smime_stream<R, W>(
+        &mut self,
+        input: R,
+        output: W,
+        flags: CMSOptions,
+    ) -> io::Result<()>
+    where
+        R: Read,
+        W: Write,
+    {
+        bio::stream(input, output, |input, output| unsafe {
+            ffi::SMIME_write_CMS(output, self.as_ptr(), input, flags.bits() as c_int)
+        })
+    }
 }
 
 impl CmsContentInfo {
@@ -168,6 +386,10 @@ impl CmsContentInfo {
     /// data `data` and flags `flags`, create a CmsContentInfo struct.
     ///
     /// All arguments are optional.
+    ///
+    /// The content is hashed with the default digest of `pkey`. Keys which sign messages directly,
+    /// such as Ed25519 and Ed448 keys, have no default digest and must be used with
+    /// [`CmsContentInfo::sign_with_digest`] instead.
     #[corresponds(CMS_sign)]
     pub fn sign<T>(
         signcert: Option<&X509Ref>,
@@ -201,6 +423,117 @@ impl CmsContentInfo {
         }
     }
 
+    /// Like [`CmsContentInfo::sign`], but hashes the content with `md` rather than the default
+    /// digest of `pkey`.
+    ///
+    /// This is required for Ed25519 and Ed448 keys, which RFC 8419 specifies are used with
+    /// SHA-512 and SHAKE256 respectively. Signing with them requires OpenSSL 3.2.0 or newer, as
+    /// older versions pass the digest on to the signature algorithm, which rejects it.
+    #[corresponds(CMS_add1_signer)]
+    pub fn sign_with_digest<T>(
+        signcert: &X509Ref,
+        pkey: &PKeyRef<T>,
+        certs: Option<&StackRef<X509>>,
+        data: &[u8],
+        md: MessageDigest,
+        flags: CMSOptions,
+    ) -> Result<CmsContentInfo, ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe {
+            let certs = certs.map_or(ptr::null_mut(), |p| p.as_ptr());
+            let cms = CmsContentInfo::from_ptr(cvt_p(ffi::CMS_sign(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                certs,
+                ptr::null_mut(),
+                (flags | CMSOptions::PARTIAL).bits(),
+            ))?);
+
+            cvt_p(ffi::CMS_add1_signer(
+                cms.as_ptr(),
+                signcert.as_ptr(),
+                pkey.as_ptr(),
+                md.as_ptr(),
+                flags.bits(),
+            ))?;
+
+            let data_bio = MemBioSlice::new(data)?;
+            cvt(ffi::CMS_final(
+                cms.as_ptr(),
+                data_bio.as_ptr(),
+                ptr::null_mut(),
+                flags.bits(),
+            ))?;
+
+            Ok(cms)
+        }
+    }
+
+    /// Creates a detached signature over content which has already been hashed with `md`.
+    ///
+    /// This is used when the content is not available as a single buffer, such as the signed byte ranges of a
+    /// PDF document. The signed attributes contain `digest` as the message digest, along with the content type
+    /// and signing time, and any others requested by `flags`.
+    ///
+    /// `CMSOptions::NOATTR` is not supported, as the digest is only included in the signed attributes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `digest` does not match the output size of `md`.
+    #[corresponds(CMS_add1_signer)]
+    pub fn sign_digest<T>(
+        signcert: &X509Ref,
+        pkey: &PKeyRef<T>,
+        certs: Option<&StackRef<X509>>,
+        md: MessageDigest,
+        digest: &[u8],
+        flags: CMSOptions,
+    ) -> Result<CmsContentInfo, ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        assert_eq!(digest.len(), md.size(), "digest length does not match");
+        let flags = (flags - CMSOptions::NOATTR) | CMSOptions::DETACHED | CMSOptions::PARTIAL;
+
+        unsafe {
+            let certs = certs.map_or(ptr::null_mut(), |p| p.as_ptr());
+            let cms = CmsContentInfo::from_ptr(cvt_p(ffi::CMS_sign(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                certs,
+                ptr::null_mut(),
+                flags.bits(),
+            ))?);
+
+            let si = cvt_p(ffi::CMS_add1_signer(
+                cms.as_ptr(),
+                signcert.as_ptr(),
+                pkey.as_ptr(),
+                md.as_ptr(),
+                flags.bits(),
+            ))?;
+            cvt(ffi::CMS_signed_add1_attr_by_NID(
+                si,
+                ffi::NID_pkcs9_messageDigest,
+                ffi::V_ASN1_OCTET_STRING,
+                digest.as_ptr() as *const _,
+                digest.len() as c_int,
+            ))?;
+            cvt(ffi::CMS_signed_add1_attr_by_NID(
+                si,
+                ffi::NID_pkcs9_contentType,
+                ffi::V_ASN1_OBJECT,
+                ffi::OBJ_nid2obj(ffi::NID_pkcs7_data) as *const _,
+                -1,
+            ))?;
+            cvt(ffi::CMS_SignerInfo_sign(si))?;
+
+            Ok(cms)
+        }
+    }
+
     /// Given a certificate stack `certs`, data `data`, cipher `cipher` and flags `flags`,
     /// create a CmsContentInfo struct.
     ///
@@ -228,6 +561,28 @@ impl CmsContentInfo {
         }
     }
 
+    /// Given `data`, create a compressed-data CmsContentInfo struct using zlib compression.
+    ///
+    /// The result can itself be signed or encrypted by passing its DER or S/MIME encoding as the
+    /// data of [`sign`] or [`encrypt`]. OpenSSL fails if it was built without zlib.
+    ///
+    /// [`sign`]: CmsContentInfo::sign
+    /// [`encrypt`]: CmsContentInfo::encrypt
+    #[corresponds(CMS_compress)]
+    pub fn compress(data: &[u8], flags: CMSOptions) -> Result<CmsContentInfo, ErrorStack> {
+        unsafe {
+            let data_bio = MemBioSlice::new(data)?;
+
+            let cms = cvt_p(ffi::CMS_compress(
+                data_bio.as_ptr(),
+                ffi::NID_zlib_compression,
+                flags.bits(),
+            ))?;
+
+            Ok(CmsContentInfo::from_ptr(cms))
+        }
+    }
+
     /// Verify this CmsContentInfo's signature,
     /// This will search the 'certs' list for the signing certificate.      
     /// Additional certificates, needed for building the certificate chain, may be
@@ -483,4 +838,246 @@ mod test {
             _ => panic!("expected CMS verification error, got Ok()"),
         }
     }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn cms_sign_cades() {
+        let _provider = crate::provider::Provider::try_load(None, "legacy", true).unwrap();
+
+        let priv_cert_bytes = include_bytes!("../test/cms.p12");
+        let priv_cert = Pkcs12::from_der(priv_cert_bytes).expect("failed to load priv cert");
+        let priv_cert = priv_cert
+            .parse2("mypass")
+            .expect("failed to parse priv cert");
+        let cert = priv_cert.cert.unwrap();
+        let pkey = priv_cert.pkey.unwrap();
+
+        let data = b"Hello world!";
+        let mut cms = CmsContentInfo::sign(
+            Some(&cert),
+            Some(&pkey),
+            None,
+            Some(data),
+            CMSOptions::BINARY | CMSOptions::CADES,
+        )
+        .expect("failed to CMS sign a message");
+
+        // a stand-in for a TSA response, which is also a SignedData ContentInfo
+        let token = CmsContentInfo::sign(
+            Some(&cert),
+            Some(&pkey),
+            None,
+            Some(b"timestamp"),
+            CMSOptions::BINARY,
+        )
+        .unwrap()
+        .to_der()
+        .unwrap();
+
+        let signer = cms.signer_infos_mut().unwrap().get_mut(0).unwrap();
+        for nid in [
+            Nid::PKCS9_CONTENTTYPE,
+            Nid::PKCS9_MESSAGEDIGEST,
+            Nid::PKCS9_SIGNINGTIME,
+            Nid::ID_SMIME_AA_SIGNINGCERTIFICATEV2,
+        ] {
+            assert!(signer.has_signed_attribute(nid));
+        }
+        assert!(!signer.signature().as_slice().is_empty());
+        assert!(signer.timestamp_token().is_none());
+        assert!(signer.add_timestamp_token(b"not a token").is_err());
+        signer.add_timestamp_token(&token).unwrap();
+
+        let mut cms = CmsContentInfo::from_der(&cms.to_der().unwrap()).unwrap();
+        let signer = cms.signer_infos().unwrap().get(0).unwrap();
+        assert_eq!(signer.timestamp_token().unwrap(), &token[..]);
+
+        // the signing certificate is checked against the verified chain, so it cannot be skipped
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(cert).unwrap();
+        let store = store.build();
+        cms.verify(None, Some(&store), None, None, CMSOptions::CADES)
+            .expect("failed to verify CAdES signature");
+    }
+
+    #[test]
+    fn cms_sign_with_digest() {
+        #[cfg(ossl300)]
+        let _provider = crate::provider::Provider::try_load(None, "legacy", true).unwrap();
+
+        let priv_cert_bytes = include_bytes!("../test/cms.p12");
+        let priv_cert = Pkcs12::from_der(priv_cert_bytes).expect("failed to load priv cert");
+        let priv_cert = priv_cert
+            .parse2("mypass")
+            .expect("failed to parse priv cert");
+        let cert = priv_cert.cert.unwrap();
+        let pkey = priv_cert.pkey.unwrap();
+
+        let cms = CmsContentInfo::sign_with_digest(
+            &cert,
+            &pkey,
+            None,
+            b"Hello world!",
+            MessageDigest::sha384(),
+            CMSOptions::empty(),
+        )
+        .expect("failed to CMS sign with a digest");
+
+        let mut cms = CmsContentInfo::from_der(&cms.to_der().unwrap()).unwrap();
+        let signer = cms.signer_infos().unwrap().get(0).unwrap();
+        assert_eq!(signer.digest_algorithm().object().nid(), Nid::SHA384);
+
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(cert).unwrap();
+        let store = store.build();
+
+        let mut content = vec![];
+        cms.verify(
+            None,
+            Some(&store),
+            None,
+            Some(&mut content),
+            CMSOptions::empty(),
+        )
+        .expect("failed to verify signature");
+        assert_eq!(content, b"Hello world!");
+    }
+
+    #[test]
+    fn cms_sign_digest() {
+        #[cfg(ossl300)]
+        let _provider = crate::provider::Provider::try_load(None, "legacy", true).unwrap();
+
+        let priv_cert_bytes = include_bytes!("../test/cms.p12");
+        let priv_cert = Pkcs12::from_der(priv_cert_bytes).expect("failed to load priv cert");
+        let priv_cert = priv_cert
+            .parse2("mypass")
+            .expect("failed to parse priv cert");
+        let cert = priv_cert.cert.unwrap();
+        let pkey = priv_cert.pkey.unwrap();
+
+        let data = b"Hello world!";
+        let digest = crate::hash::hash(MessageDigest::sha256(), data).unwrap();
+        let cms = CmsContentInfo::sign_digest(
+            &cert,
+            &pkey,
+            None,
+            MessageDigest::sha256(),
+            &digest,
+            CMSOptions::empty(),
+        )
+        .expect("failed to CMS sign a digest");
+
+        let mut cms = CmsContentInfo::from_der(&cms.to_der().unwrap()).unwrap();
+        let signer = cms.signer_infos().unwrap().get(0).unwrap();
+        assert_eq!(signer.digest_algorithm().object().nid(), Nid::SHA256);
+        assert_eq!(
+            signer.signature_algorithm().object().nid(),
+            Nid::RSAENCRYPTION
+        );
+
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(cert).unwrap();
+        let store = store.build();
+
+        cms.verify(None, Some(&store), Some(data), None, CMSOptions::BINARY)
+            .expect("failed to verify signature over digest");
+        assert!(cms
+            .verify(
+                None,
+                Some(&store),
+                Some(b"Goodbye world!"),
+                None,
+                CMSOptions::BINARY
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn cms_stream_encrypt() {
+        #[cfg(ossl300)]
+        let _provider = crate::provider::Provider::try_load(None, "legacy", true).unwrap();
+
+        let pub_cert = X509::from_der(include_bytes!("../test/cms_pubkey.der")).unwrap();
+        let priv_cert = Pkcs12::from_der(include_bytes!("../test/cms.p12"))
+            .unwrap()
+            .parse2("mypass")
+            .unwrap();
+        let mut certs = Stack::new().unwrap();
+        certs.push(pub_cert).unwrap();
+
+        let input = vec![b'x'; 1 << 20];
+        let flags = CMSOptions::STREAM | CMSOptions::BINARY;
+        let mut cms = CmsContentInfo::encrypt(&certs, &[], Cipher::aes_256_cbc(), flags).unwrap();
+        let mut encrypted = vec![];
+        cms.write_der_stream(&input[..], &mut encrypted, flags)
+            .unwrap();
+
+        let decrypted = CmsContentInfo::from_der(&encrypted)
+            .unwrap()
+            .decrypt_without_cert_check(priv_cert.pkey.as_ref().unwrap())
+            .unwrap();
+        assert_eq!(decrypted, input);
+    }
+
+    #[test]
+    fn cms_stream_sign() {
+        let cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        let key = PKey::private_key_from_pem(include_bytes!("../test/key.pem")).unwrap();
+
+        let input = vec![b'x'; 1 << 20];
+        let flags = CMSOptions::STREAM | CMSOptions::BINARY;
+        let mut cms = CmsContentInfo::sign(Some(&cert), Some(&key), None, None, flags).unwrap();
+        let mut signed = vec![];
+        cms.write_der_stream(&input[..], &mut signed, flags)
+            .unwrap();
+
+        let mut cms = CmsContentInfo::from_der(&signed).unwrap();
+        let mut content = vec![];
+        cms.verify(
+            None,
+            None,
+            None,
+            Some(&mut content),
+            CMSOptions::NOVERIFY | CMSOptions::BINARY,
+        )
+        .unwrap();
+        assert_eq!(content, input);
+
+        let mut cms = CmsContentInfo::sign(Some(&cert), Some(&key), None, None, flags).unwrap();
+        let mut smime = vec![];
+        cms.write_smime_stream(&b"hello"[..], &mut smime, flags)
+            .unwrap();
+        assert!(smime.starts_with(b"MIME-Version: 1.0"));
+    }
+
+    #[test]
+    fn cms_compress_uncompress() {
+        let input = vec![b'x'; 4096];
+        let cms = match CmsContentInfo::compress(&input, CMSOptions::BINARY) {
+            Ok(cms) => cms,
+            // OpenSSL was built without zlib
+            Err(_) => return,
+        };
+        let der = cms.to_der().unwrap();
+        assert!(der.len() < input.len());
+
+        let cms = CmsContentInfo::from_der(&der).unwrap();
+        assert_eq!(cms.uncompress(CMSOptions::BINARY).unwrap(), input);
+    }
+
+    #[test]
+    fn cms_uncompress_wrong_type() {
+        let cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        let key = PKey::private_key_from_pem(include_bytes!("../test/key.pem")).unwrap();
+        let cms = CmsContentInfo::sign(
+            Some(&cert),
+            Some(&key),
+            None,
+            Some(b"hello"),
+            CMSOptions::BINARY,
+        )
+        .unwrap();
+        assert!(cms.uncompress(CMSOptions::empty()).is_err());
+    }
 }
@@ -0,0 +1,219 @@
+//! A minimal CBOR encoder and decoder covering the subset of RFC 8949 used by COSE.
+//!
+//! Only definite length items are supported, and floating point values are rejected.
+use std::convert::TryFrom;
+
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Unsigned(u64),
+    /// The negative integer `-1 - n`.
+    Negative(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+    Tag(u64, Box<Value>),
+    Bool(bool),
+    Null,
+}
+
+impl Value {
+    pub fn int(value: i64) -> Value {
+        if value < 0 {
+            Value::Negative(-(value + 1) as u64)
+        } else {
+            Value::Unsigned(value as u64)
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match *self {
+            Value::Unsigned(n) => i64::try_from(n).ok(),
+            Value::Negative(n) => i64::try_from(n).ok().map(|n| -1 - n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match *self {
+            Value::Bytes(ref b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&[(Value, Value)]> {
+        match *self {
+            Value::Map(ref m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Looks up an integer label in a map.
+    pub fn get(&self, label: i64) -> Option<&Value> {
+        self.as_map()?
+            .iter()
+            .find(|(k, _)| k.as_int() == Some(label))
+            .map(|(_, v)| v)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        self.encode_to(&mut buf);
+        buf
+    }
+
+    fn encode_to(&self, buf: &mut Vec<u8>) {
+        match *self {
+            Value::Unsigned(n) => write_head(buf, 0, n),
+            Value::Negative(n) => write_head(buf, 1, n),
+            Value::Bytes(ref b) => {
+                write_head(buf, 2, b.len() as u64);
+                buf.extend_from_slice(b);
+            }
+            Value::Text(ref s) => {
+                write_head(buf, 3, s.len() as u64);
+                buf.extend_from_slice(s.as_bytes());
+            }
+            Value::Array(ref a) => {
+                write_head(buf, 4, a.len() as u64);
+                for v in a {
+                    v.encode_to(buf);
+                }
+            }
+            Value::Map(ref m) => {
+                write_head(buf, 5, m.len() as u64);
+                for (k, v) in m {
+                    k.encode_to(buf);
+                    v.encode_to(buf);
+                }
+            }
+            Value::Tag(tag, ref v) => {
+                write_head(buf, 6, tag);
+                v.encode_to(buf);
+            }
+            Value::Bool(false) => buf.push(0xf4),
+            Value::Bool(true) => buf.push(0xf5),
+            Value::Null => buf.push(0xf6),
+        }
+    }
+
+    /// Decodes a single item, rejecting trailing data.
+    pub fn decode(mut buf: &[u8]) -> Result<Value, &'static str> {
+        let value = decode_item(&mut buf, 0)?;
+        if !buf.is_empty() {
+            return Err("trailing data after CBOR item");
+        }
+        Ok(value)
+    }
+}
+
+fn write_head(buf: &mut Vec<u8>, major: u8, n: u64) {
+    let major = major << 5;
+    if n < 24 {
+        buf.push(major | n as u8);
+    } else if n <= u64::from(u8::MAX) {
+        buf.push(major | 24);
+        buf.push(n as u8);
+    } else if n <= u64::from(u16::MAX) {
+        buf.push(major | 25);
+        buf.extend_from_slice(&(n as u16).to_be_bytes());
+    } else if n <= u64::from(u32::MAX) {
+        buf.push(major | 26);
+        buf.extend_from_slice(&(n as u32).to_be_bytes());
+    } else {
+        buf.push(major | 27);
+        buf.extend_from_slice(&n.to_be_bytes());
+    }
+}
+
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8], &'static str> {
+    if buf.len() < len {
+        return Err("truncated CBOR item");
+    }
+    let (head, tail) = buf.split_at(len);
+    *buf = tail;
+    Ok(head)
+}
+
+fn read_argument(buf: &mut &[u8], info: u8) -> Result<u64, &'static str> {
+    let len = match info {
+        0..=23 => return Ok(u64::from(info)),
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        _ => return Err("unsupported CBOR length encoding"),
+    };
+    Ok(take(buf, len)?
+        .iter()
+        .fold(0, |acc, &b| (acc << 8) | u64::from(b)))
+}
+
+fn read_len(buf: &mut &[u8], info: u8) -> Result<usize, &'static str> {
+    let len = usize::try_from(read_argument(buf, info)?).map_err(|_| "CBOR item too large")?;
+    // every item is at least one byte long, which bounds allocations by the input size
+    if len > buf.len() {
+        return Err("truncated CBOR item");
+    }
+    Ok(len)
+}
+
+fn decode_item(buf: &mut &[u8], depth: usize) -> Result<Value, &'static str> {
+    if depth > MAX_DEPTH {
+        return Err("CBOR item nested too deeply");
+    }
+
+    let initial = take(buf, 1)?[0];
+    let major = initial >> 5;
+    let info = initial & 0x1f;
+
+    let value = match major {
+        0 => Value::Unsigned(read_argument(buf, info)?),
+        1 => Value::Negative(read_argument(buf, info)?),
+        2 => {
+            let len = read_len(buf, info)?;
+            Value::Bytes(take(buf, len)?.to_vec())
+        }
+        3 => {
+            let len = read_len(buf, info)?;
+            let text = String::from_utf8(take(buf, len)?.to_vec())
+                .map_err(|_| "invalid UTF-8 in CBOR text string")?;
+            Value::Text(text)
+        }
+        4 => {
+            let len = read_len(buf, info)?;
+            let mut items = Vec::with_capacity(len);
+            for _ in 0..len {
+                items.push(decode_item(buf, depth + 1)?);
+            }
+            Value::Array(items)
+        }
+        5 => {
+            let len = read_len(buf, info)?;
+            let mut entries = Vec::with_capacity(len);
+            for _ in 0..len {
+                let key = decode_item(buf, depth + 1)?;
+                let value = decode_item(buf, depth + 1)?;
+                if entries.iter().any(|(k, _)| *k == key) {
+                    return Err("duplicate CBOR map key");
+                }
+                entries.push((key, value));
+            }
+            Value::Map(entries)
+        }
+        6 => {
+            let tag = read_argument(buf, info)?;
+            Value::Tag(tag, Box::new(decode_item(buf, depth + 1)?))
+        }
+        _ => match info {
+            20 => Value::Bool(false),
+            21 => Value::Bool(true),
+            22 => Value::Null,
+            _ => return Err("unsupported CBOR simple value"),
+        },
+    };
+
+    Ok(value)
+}
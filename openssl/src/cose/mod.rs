@@ -0,0 +1,816 @@
+//! COSE key and signature structures.
+//!
+//! This module implements the subset of [RFC 9052] needed to verify WebAuthn and CBOR-based attestation data with
+//! OpenSSL: conversion between [`PKey`]s and `COSE_Key` structures, and signing and verification of `COSE_Sign1`
+//! messages.
+//!
+//! EC2 keys on the P-256, P-384, P-521 and secp256k1 curves, OKP keys (Ed25519, Ed448, X25519 and X448), and RSA
+//! keys ([RFC 8230]) are supported.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::cose::{Algorithm, Sign1};
+//! use openssl::ec::{EcGroup, EcKey};
+//! use openssl::nid::Nid;
+//! use openssl::pkey::{PKey, Public};
+//!
+//! let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+//! let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+//!
+//! let message = Sign1::sign(&key, Algorithm::ES256, b"hello", b"").unwrap().to_vec();
+//!
+//! let cose_key = key.public_key_to_cose_key().unwrap();
+//! let public = PKey::<Public>::public_key_from_cose_key(&cose_key).unwrap();
+//! let message = Sign1::from_slice(&message).unwrap();
+//! assert!(message.verify(&public, b"").unwrap());
+//! assert_eq!(message.payload(), Some(&b"hello"[..]));
+//! ```
+//!
+//! [RFC 9052]: https://datatracker.ietf.org/doc/html/rfc9052
+//! [RFC 8230]: https://datatracker.ietf.org/doc/html/rfc8230
+
+use self::cbor::Value;
+use crate::bn::{BigNum, BigNumRef};
+use crate::ec::{EcGroup, EcGroupRef, EcKey, EcPoint};
+use crate::ecdsa::EcdsaSig;
+use crate::hash::{hash, MessageDigest};
+use crate::nid::Nid;
+#[cfg(any(ossl111, boringssl, libressl370))]
+use crate::okp;
+use crate::pkey::{HasPrivate, HasPublic, Id, PKey, PKeyRef, Private, Public};
+use crate::rsa::{Padding, Rsa, RsaPrivateKeyBuilder};
+use crate::sign::{RsaPssSaltlen, Signer, Verifier};
+use crate::thread_cache;
+
+mod cbor;
+
+const SIGN1_TAG: u64 = 18;
+
+const HEADER_ALG: i64 = 1;
+const HEADER_KID: i64 = 4;
+
+const KEY_KTY: i64 = 1;
+#[cfg(any(ossl111, boringssl, libressl370))]
+const KTY_OKP: i64 = 1;
+const KTY_EC2: i64 = 2;
+const KTY_RSA: i64 = 3;
+
+const CRV_P256: i64 = 1;
+const CRV_P384: i64 = 2;
+const CRV_P521: i64 = 3;
+const CRV_SECP256K1: i64 = 8;
+
+invalid_input_error! {
+    /// An error converting or verifying a COSE structure.
+    pub struct CoseError => "invalid COSE structure: {}";
+}
+
+/// A COSE signature algorithm identifier.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Algorithm(i64);
+
+impl Algorithm {
+    /// ECDSA with SHA-256 on the P-256 curve.
+    pub const ES256: Algorithm = Algorithm(-7);
+    /// ECDSA with SHA-384 on the P-384 curve.
+    pub const ES384: Algorithm = Algorithm(-35);
+    /// ECDSA with SHA-512 on the P-521 curve.
+    pub const ES512: Algorithm = Algorithm(-36);
+    /// ECDSA with SHA-256 on the secp256k1 curve.
+    pub const ES256K: Algorithm = Algorithm(-47);
+    /// EdDSA with an Ed25519 or Ed448 key.
+    pub const EDDSA: Algorithm = Algorithm(-8);
+    /// RSASSA-PSS with SHA-256.
+    pub const PS256: Algorithm = Algorithm(-37);
+    /// RSASSA-PSS with SHA-384.
+    pub const PS384: Algorithm = Algorithm(-38);
+    /// RSASSA-PSS with SHA-512.
+    pub const PS512: Algorithm = Algorithm(-39);
+    /// RSASSA-PKCS1-v1_5 with SHA-256.
+    pub const RS256: Algorithm = Algorithm(-257);
+    /// RSASSA-PKCS1-v1_5 with SHA-384.
+    pub const RS384: Algorithm = Algorithm(-258);
+    /// RSASSA-PKCS1-v1_5 with SHA-512.
+    pub const RS512: Algorithm = Algorithm(-259);
+
+    /// Creates an `Algorithm` from its IANA registered value.
+    pub const fn from_raw(raw: i64) -> Algorithm {
+        Algorithm(raw)
+    }
+
+    /// Returns the IANA registered value of the algorithm.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub const fn as_raw(&self) -> i64 {
+        self.0
+    }
+
+    fn scheme(self) -> Result<Scheme, CoseError> {
+        let scheme = match self {
+            Algorithm::ES256 => Scheme::Ecdsa(MessageDigest::sha256(), Nid::X9_62_PRIME256V1),
+            Algorithm::ES384 => Scheme::Ecdsa(MessageDigest::sha384(), Nid::SECP384R1),
+            Algorithm::ES512 => Scheme::Ecdsa(MessageDigest::sha512(), Nid::SECP521R1),
+            Algorithm::ES256K => Scheme::Ecdsa(MessageDigest::sha256(), Nid::SECP256K1),
+            #[cfg(any(ossl111, boringssl, libressl370))]
+            Algorithm::EDDSA => Scheme::EdDsa,
+            Algorithm::PS256 => Scheme::Rsa(MessageDigest::sha256(), Padding::PKCS1_PSS),
+            Algorithm::PS384 => Scheme::Rsa(MessageDigest::sha384(), Padding::PKCS1_PSS),
+            Algorithm::PS512 => Scheme::Rsa(MessageDigest::sha512(), Padding::PKCS1_PSS),
+            Algorithm::RS256 => Scheme::Rsa(MessageDigest::sha256(), Padding::PKCS1),
+            Algorithm::RS384 => Scheme::Rsa(MessageDigest::sha384(), Padding::PKCS1),
+            Algorithm::RS512 => Scheme::Rsa(MessageDigest::sha512(), Padding::PKCS1),
+            Algorithm(alg) => {
+                return Err(CoseError::invalid(format!("unsupported algorithm {}", alg)))
+            }
+        };
+        Ok(scheme)
+    }
+}
+
+enum Scheme {
+    Ecdsa(MessageDigest, Nid),
+    #[cfg(any(ossl111, boringssl, libressl370))]
+    EdDsa,
+    Rsa(MessageDigest, Padding),
+}
+
+impl Scheme {
+    fn check_key<T>(&self, key: &PKeyRef<T>) -> Result<(), CoseError>
+    where
+        T: HasPublic,
+    {
+        let ok = match *self {
+            Scheme::Ecdsa(_, nid) => {
+                key.id() == Id::EC && key.ec_key()?.group().curve_name() == Some(nid)
+            }
+            #[cfg(any(ossl111, boringssl, libressl370))]
+            Scheme::EdDsa => match key.id() {
+                Id::ED25519 => true,
+                #[cfg(ossl111)]
+                Id::ED448 => true,
+                _ => false,
+            },
+            Scheme::Rsa(..) => key.id() == Id::RSA,
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(CoseError::invalid("key does not match the algorithm"))
+        }
+    }
+
+    fn sign<T>(&self, key: &PKeyRef<T>, data: &[u8]) -> Result<Vec<u8>, CoseError>
+    where
+        T: HasPrivate,
+    {
+        self.check_key(key)?;
+        match *self {
+            Scheme::Ecdsa(md, _) => {
+                let ec_key = key.ec_key()?;
+                let digest = hash(md, data)?;
+                let sig = EcdsaSig::sign(&digest, &ec_key)?;
+                let len = ec_key.group().field_len();
+                let mut out = pad(sig.r(), len)?;
+                out.extend_from_slice(&pad(sig.s(), len)?);
+                Ok(out)
+            }
+            #[cfg(any(ossl111, boringssl, libressl370))]
+            Scheme::EdDsa => Ok(Signer::new_without_digest(key)?.sign_oneshot_to_vec(data)?),
+            Scheme::Rsa(md, padding) => {
+                let mut signer = Signer::new(md, key)?;
+                signer.set_rsa_padding(padding)?;
+                if padding == Padding::PKCS1_PSS {
+                    signer.set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)?;
+                }
+                signer.update(data)?;
+                Ok(signer.sign_to_vec()?)
+            }
+        }
+    }
+
+    fn verify<T>(&self, key: &PKeyRef<T>, data: &[u8], signature: &[u8]) -> Result<bool, CoseError>
+    where
+        T: HasPublic,
+    {
+        self.check_key(key)?;
+        match *self {
+            Scheme::Ecdsa(md, _) => {
+                let ec_key = key.ec_key()?;
+                let len = ec_key.group().field_len();
+                if signature.len() != len * 2 {
+                    return Ok(false);
+                }
+                let r = BigNum::from_slice(&signature[..len])?;
+                let s = BigNum::from_slice(&signature[len..])?;
+                let sig = EcdsaSig::from_private_components(r, s)?;
+                let digest = hash(md, data)?;
+                Ok(sig.verify(&digest, &ec_key)?)
+            }
+            #[cfg(any(ossl111, boringssl, libressl370))]
+            Scheme::EdDsa => {
+                Ok(Verifier::new_without_digest(key)?.verify_oneshot(signature, data)?)
+            }
+            Scheme::Rsa(md, padding) => {
+                let mut verifier = Verifier::new(md, key)?;
+                verifier.set_rsa_padding(padding)?;
+                if padding == Padding::PKCS1_PSS {
+                    verifier.set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)?;
+                }
+                verifier.update(data)?;
+                Ok(verifier.verify(signature)?)
+            }
+        }
+    }
+}
+
+/// A `COSE_Sign1` message: a payload with a single signature.
+#[derive(Debug, Clone)]
+pub struct Sign1 {
+    protected: Vec<u8>,
+    alg: Algorithm,
+    key_id: Option<Vec<u8>>,
+    payload: Option<Vec<u8>>,
+    signature: Vec<u8>,
+}
+
+impl Sign1 {
+    /// Signs `payload` with `key`, embedding the payload in the message.
+    ///
+    /// The algorithm is placed in the protected header, and `external_aad` is covered by the signature without being
+    /// included in the message.
+    pub fn sign<T>(
+        key: &PKeyRef<T>,
+        alg: Algorithm,
+        payload: &[u8],
+        external_aad: &[u8],
+    ) -> Result<Sign1, CoseError>
+    where
+        T: HasPrivate,
+    {
+        let scheme = alg.scheme()?;
+        let protected = Value::Map(vec![(Value::int(HEADER_ALG), Value::int(alg.0))]).encode();
+        let data = sig_structure(&protected, external_aad, payload);
+        let signature = scheme.sign(key, &data)?;
+
+        Ok(Sign1 {
+            protected,
+            alg,
+            key_id: None,
+            payload: Some(payload.to_vec()),
+            signature,
+        })
+    }
+
+    /// Decodes a `COSE_Sign1` message, which may or may not carry its CBOR tag.
+    ///
+    /// The algorithm must be present in the protected header.
+    pub fn from_slice(buf: &[u8]) -> Result<Sign1, CoseError> {
+        let value = Value::decode(buf).map_err(CoseError::invalid)?;
+        let value = match value {
+            Value::Tag(SIGN1_TAG, value) => *value,
+            Value::Tag(..) => return Err(CoseError::invalid("unexpected CBOR tag")),
+            value => value,
+        };
+        let mut items = match value {
+            Value::Array(items) if items.len() == 4 => items.into_iter(),
+            _ => return Err(CoseError::invalid("expected an array of 4 items")),
+        };
+
+        let protected = match items.next() {
+            Some(Value::Bytes(protected)) => protected,
+            _ => return Err(CoseError::invalid("protected header must be a byte string")),
+        };
+        let protected_map = if protected.is_empty() {
+            Value::Map(vec![])
+        } else {
+            Value::decode(&protected).map_err(CoseError::invalid)?
+        };
+        if protected_map.as_map().is_none() {
+            return Err(CoseError::invalid("protected header must be a map"));
+        }
+        let unprotected = items.next().unwrap();
+        if unprotected.as_map().is_none() {
+            return Err(CoseError::invalid("unprotected header must be a map"));
+        }
+        let payload = match items.next() {
+            Some(Value::Bytes(payload)) => Some(payload),
+            Some(Value::Null) => None,
+            _ => return Err(CoseError::invalid("payload must be a byte string or nil")),
+        };
+        let signature = match items.next() {
+            Some(Value::Bytes(signature)) => signature,
+            _ => return Err(CoseError::invalid("signature must be a byte string")),
+        };
+
+        let alg = protected_map
+            .get(HEADER_ALG)
+            .and_then(Value::as_int)
+            .map(Algorithm)
+            .ok_or_else(|| CoseError::invalid("missing algorithm in protected header"))?;
+        let key_id = protected_map
+            .get(HEADER_KID)
+            .or_else(|| unprotected.get(HEADER_KID))
+            .and_then(Value::as_bytes)
+            .map(<[u8]>::to_vec);
+
+        Ok(Sign1 {
+            protected,
+            alg,
+            key_id,
+            payload,
+            signature,
+        })
+    }
+
+    /// Encodes the message as a tagged `COSE_Sign1` structure.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut unprotected = vec![];
+        if let Some(ref key_id) = self.key_id {
+            unprotected.push((Value::int(HEADER_KID), Value::Bytes(key_id.clone())));
+        }
+        let payload = match self.payload {
+            Some(ref payload) => Value::Bytes(payload.clone()),
+            None => Value::Null,
+        };
+        Value::Tag(
+            SIGN1_TAG,
+            Box::new(Value::Array(vec![
+                Value::Bytes(self.protected.clone()),
+                Value::Map(unprotected),
+                payload,
+                Value::Bytes(self.signature.clone()),
+            ])),
+        )
+        .encode()
+    }
+
+    /// Returns the signature algorithm from the protected header.
+    pub fn algorithm(&self) -> Algorithm {
+        self.alg
+    }
+
+    /// Returns the key identifier, if present in either header.
+    pub fn key_id(&self) -> Option<&[u8]> {
+        self.key_id.as_deref()
+    }
+
+    /// Sets the key identifier, which is placed in the unprotected header.
+    pub fn set_key_id(&mut self, key_id: &[u8]) {
+        self.key_id = Some(key_id.to_vec());
+    }
+
+    /// Returns the embedded payload, or `None` if the payload is detached.
+    pub fn payload(&self) -> Option<&[u8]> {
+        self.payload.as_deref()
+    }
+
+    /// Removes the payload from the message so that it can be transported separately.
+    pub fn detach_payload(&mut self) -> Option<Vec<u8>> {
+        self.payload.take()
+    }
+
+    /// Returns the serialized protected header.
+    pub fn protected_header(&self) -> &[u8] {
+        &self.protected
+    }
+
+    /// Returns the signature.
+    ///
+    /// ECDSA signatures are the fixed length concatenation of `r` and `s` rather than a DER structure.
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// Verifies the signature over the embedded payload.
+    ///
+    /// Returns an error if the payload is detached, or if `key` cannot be used with the message's algorithm.
+    pub fn verify<T>(&self, key: &PKeyRef<T>, external_aad: &[u8]) -> Result<bool, CoseError>
+    where
+        T: HasPublic,
+    {
+        match self.payload {
+            Some(ref payload) => self.verify_detached(key, payload, external_aad),
+            None => Err(CoseError::invalid("payload is detached")),
+        }
+    }
+
+    /// Verifies the signature over a separately transported payload.
+    pub fn verify_detached<T>(
+        &self,
+        key: &PKeyRef<T>,
+        payload: &[u8],
+        external_aad: &[u8],
+    ) -> Result<bool, CoseError>
+    where
+        T: HasPublic,
+    {
+        let scheme = self.alg.scheme()?;
+        let data = sig_structure(&self.protected, external_aad, payload);
+        scheme.verify(key, &data, &self.signature)
+    }
+}
+
+fn sig_structure(protected: &[u8], external_aad: &[u8], payload: &[u8]) -> Vec<u8> {
+    Value::Array(vec![
+        Value::Text("Signature1".to_string()),
+        Value::Bytes(protected.to_vec()),
+        Value::Bytes(external_aad.to_vec()),
+        Value::Bytes(payload.to_vec()),
+    ])
+    .encode()
+}
+
+impl PKey<Public> {
+    /// Deserializes a public key from a `COSE_Key` structure.
+    ///
+    /// Private key parameters are ignored.
+    pub fn public_key_from_cose_key(cose_key: &[u8]) -> Result<PKey<Public>, CoseError> {
+        let key = decode_key(cose_key)?;
+        match key_type(&key)? {
+            KTY_EC2 => {
+                let group = ec_group(&key)?;
+                Ok(PKey::from_ec_key(ec_public_key(&key, &group)?)?)
+            }
+            KTY_RSA => {
+                let rsa = Rsa::from_public_components(bn(&key, -1)?, bn(&key, -2)?)?;
+                Ok(PKey::from_rsa(rsa)?)
+            }
+            #[cfg(any(ossl111, boringssl, libressl370))]
+            KTY_OKP => Ok(PKey::public_key_from_raw_bytes(
+                bytes(&key, -2)?,
+                okp_id(&key)?,
+            )?),
+            kty => Err(CoseError::invalid(format!("unsupported key type {}", kty))),
+        }
+    }
+}
+
+impl PKey<Private> {
+    /// Deserializes a private key from a `COSE_Key` structure.
+    ///
+    /// RSA keys must contain the `p` and `q` parameters; the CRT parameters are computed if they are missing.
+    pub fn private_key_from_cose_key(cose_key: &[u8]) -> Result<PKey<Private>, CoseError> {
+        let key = decode_key(cose_key)?;
+        match key_type(&key)? {
+            KTY_EC2 => {
+                let group = ec_group(&key)?;
+                let public = ec_public_key(&key, &group)?;
+                let d = bytes(&key, -4)?;
+                if d.len() != group.field_len() {
+                    return Err(CoseError::invalid("private key has the wrong length"));
+                }
+                let d = BigNum::from_slice(d)?;
+                let ec_key = EcKey::from_private_components(&group, &d, public.public_key())?;
+                ec_key.check_key()?;
+                Ok(PKey::from_ec_key(ec_key)?)
+            }
+            KTY_RSA => {
+                let builder =
+                    RsaPrivateKeyBuilder::new(bn(&key, -1)?, bn(&key, -2)?, bn(&key, -3)?)?
+                        .set_factors(bn(&key, -4)?, bn(&key, -5)?)?;
+                let builder = if key.get(-6).is_some() {
+                    builder.set_crt_params(bn(&key, -6)?, bn(&key, -7)?, bn(&key, -8)?)?
+                } else {
+                    builder.compute_crt_params()?
+                };
+                let rsa = builder.build();
+                rsa.validate()?;
+                Ok(PKey::from_rsa(rsa)?)
+            }
+            #[cfg(any(ossl111, boringssl, libressl370))]
+            KTY_OKP => {
+                let pkey = PKey::private_key_from_raw_bytes(bytes(&key, -4)?, okp_id(&key)?)?;
+                if let Some(x) = key.get(-2) {
+                    if x.as_bytes() != Some(&pkey.raw_public_key()?[..]) {
+                        return Err(CoseError::invalid("public key does not match private key"));
+                    }
+                }
+                Ok(pkey)
+            }
+            kty => Err(CoseError::invalid(format!("unsupported key type {}", kty))),
+        }
+    }
+}
+
+impl<T> PKeyRef<T>
+where
+    T: HasPublic,
+{
+    /// Serializes the public key as a `COSE_Key` structure.
+    ///
+    /// The key is encoded deterministically and does not include an algorithm.
+    pub fn public_key_to_cose_key(&self) -> Result<Vec<u8>, CoseError> {
+        Ok(Value::Map(self.cose_key_params()?).encode())
+    }
+
+    fn cose_key_params(&self) -> Result<Vec<(Value, Value)>, CoseError> {
+        let mut params = vec![];
+        match self.id() {
+            Id::EC => {
+                let ec_key = self.ec_key()?;
+                let group = ec_key.group();
+                let mut x = BigNum::new()?;
+                let mut y = BigNum::new()?;
+                thread_cache::with_bn_ctx(|ctx| {
+                    ec_key
+                        .public_key()
+                        .affine_coordinates_gfp(group, &mut x, &mut y, ctx)
+                })?;
+                let len = group.field_len();
+                params.push(param(KEY_KTY, Value::int(KTY_EC2)));
+                params.push(param(-1, Value::int(ec_curve(group)?)));
+                params.push(param(-2, Value::Bytes(pad(&x, len)?)));
+                params.push(param(-3, Value::Bytes(pad(&y, len)?)));
+            }
+            Id::RSA => {
+                let rsa = self.rsa()?;
+                params.push(param(KEY_KTY, Value::int(KTY_RSA)));
+                params.push(param(-1, Value::Bytes(rsa.n().to_vec())));
+                params.push(param(-2, Value::Bytes(rsa.e().to_vec())));
+            }
+            #[cfg(any(ossl111, boringssl, libressl370))]
+            id => {
+                params.push(param(KEY_KTY, Value::int(KTY_OKP)));
+                params.push(param(-1, Value::int(okp_curve(id)?)));
+                params.push(param(-2, Value::Bytes(self.raw_public_key()?)));
+            }
+            #[cfg(not(any(ossl111, boringssl, libressl370)))]
+            _ => return Err(CoseError::invalid("unsupported key type")),
+        }
+        Ok(params)
+    }
+}
+
+impl<T> PKeyRef<T>
+where
+    T: HasPrivate,
+{
+    /// Serializes the private key as a `COSE_Key` structure, including the public key parameters.
+    pub fn private_key_to_cose_key(&self) -> Result<Vec<u8>, CoseError> {
+        let mut params = self.cose_key_params()?;
+        match self.id() {
+            Id::EC => {
+                let ec_key = self.ec_key()?;
+                let d = pad(ec_key.private_key(), ec_key.group().field_len())?;
+                params.push(param(-4, Value::Bytes(d)));
+            }
+            Id::RSA => {
+                let rsa = self.rsa()?;
+                let missing = || CoseError::invalid("RSA key is missing its factors");
+                params.push(param(-3, Value::Bytes(rsa.d().to_vec())));
+                let factors = [rsa.p(), rsa.q(), rsa.dmp1(), rsa.dmq1(), rsa.iqmp()];
+                for (label, factor) in (-8..=-4).rev().zip(&factors) {
+                    let factor = factor.ok_or_else(missing)?;
+                    params.push(param(label, Value::Bytes(factor.to_vec())));
+                }
+            }
+            #[cfg(any(ossl111, boringssl, libressl370))]
+            _ => params.push(param(-4, Value::Bytes(self.raw_private_key()?))),
+            #[cfg(not(any(ossl111, boringssl, libressl370)))]
+            _ => unreachable!(),
+        }
+        Ok(Value::Map(params).encode())
+    }
+}
+
+fn param(label: i64, value: Value) -> (Value, Value) {
+    (Value::int(label), value)
+}
+
+fn decode_key(buf: &[u8]) -> Result<Value, CoseError> {
+    let key = Value::decode(buf).map_err(CoseError::invalid)?;
+    if key.as_map().is_none() {
+        return Err(CoseError::invalid("COSE_Key must be a map"));
+    }
+    Ok(key)
+}
+
+fn key_type(key: &Value) -> Result<i64, CoseError> {
+    int(key, KEY_KTY)
+}
+
+fn int(key: &Value, label: i64) -> Result<i64, CoseError> {
+    key.get(label)
+        .and_then(Value::as_int)
+        .ok_or_else(|| CoseError::invalid(format!("missing integer parameter {}", label)))
+}
+
+fn bytes(key: &Value, label: i64) -> Result<&[u8], CoseError> {
+    key.get(label)
+        .and_then(Value::as_bytes)
+        .ok_or_else(|| CoseError::invalid(format!("missing byte string parameter {}", label)))
+}
+
+fn bn(key: &Value, label: i64) -> Result<BigNum, CoseError> {
+    Ok(BigNum::from_slice(bytes(key, label)?)?)
+}
+
+fn ec_group(key: &Value) -> Result<EcGroup, CoseError> {
+    let nid = match int(key, -1)? {
+        CRV_P256 => Nid::X9_62_PRIME256V1,
+        CRV_P384 => Nid::SECP384R1,
+        CRV_P521 => Nid::SECP521R1,
+        CRV_SECP256K1 => Nid::SECP256K1,
+        crv => return Err(CoseError::invalid(format!("unsupported curve {}", crv))),
+    };
+    Ok(EcGroup::from_curve_name(nid)?)
+}
+
+fn ec_curve(group: &EcGroupRef) -> Result<i64, CoseError> {
+    match group.curve_name() {
+        Some(Nid::X9_62_PRIME256V1) => Ok(CRV_P256),
+        Some(Nid::SECP384R1) => Ok(CRV_P384),
+        Some(Nid::SECP521R1) => Ok(CRV_P521),
+        Some(Nid::SECP256K1) => Ok(CRV_SECP256K1),
+        _ => Err(CoseError::invalid("unsupported curve")),
+    }
+}
+
+/// Builds an EC2 public key, where `y` is either a coordinate or the sign bit of a compressed point.
+fn ec_public_key(key: &Value, group: &EcGroupRef) -> Result<EcKey<Public>, CoseError> {
+    let len = group.field_len();
+    let x = bytes(key, -2)?;
+    if x.len() != len {
+        return Err(CoseError::invalid("x coordinate has the wrong length"));
+    }
+
+    match key.get(-3) {
+        Some(Value::Bytes(y)) => {
+            if y.len() != len {
+                return Err(CoseError::invalid("y coordinate has the wrong length"));
+            }
+            let x = BigNum::from_slice(x)?;
+            let y = BigNum::from_slice(y)?;
+            Ok(EcKey::from_public_key_affine_coordinates(group, &x, &y)?)
+        }
+        Some(Value::Bool(sign)) => {
+            let mut point = Vec::with_capacity(len + 1);
+            point.push(if *sign { 0x03 } else { 0x02 });
+            point.extend_from_slice(x);
+            let point = thread_cache::with_bn_ctx(|ctx| EcPoint::from_bytes(group, &point, ctx))?;
+            Ok(EcKey::from_public_key(group, &point)?)
+        }
+        _ => Err(CoseError::invalid("missing y coordinate")),
+    }
+}
+
+fn pad(bn: &BigNumRef, len: usize) -> Result<Vec<u8>, CoseError> {
+    let bytes = bn.to_vec();
+    if bytes.len() > len {
+        return Err(CoseError::invalid("value too large for the curve"));
+    }
+    let mut padded = vec![0; len - bytes.len()];
+    padded.extend_from_slice(&bytes);
+    Ok(padded)
+}
+
+#[cfg(any(ossl111, boringssl, libressl370))]
+fn okp_id(key: &Value) -> Result<Id, CoseError> {
+    let crv = int(key, -1)?;
+    okp::from_cose(crv)
+        .map(|c| c.id)
+        .ok_or_else(|| CoseError::invalid(format!("unsupported curve {}", crv)))
+}
+
+#[cfg(any(ossl111, boringssl, libressl370))]
+fn okp_curve(id: Id) -> Result<i64, CoseError> {
+    okp::from_id(id)
+        .map(|c| c.cose)
+        .ok_or_else(|| CoseError::invalid("unsupported key type"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bn::BigNumContext;
+
+    fn p256_key() -> PKey<Private> {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn ec2_key_encoding() {
+        let key = p256_key();
+        let cose_key = key.public_key_to_cose_key().unwrap();
+
+        let ec_key = key.ec_key().unwrap();
+        let mut x = BigNum::new().unwrap();
+        let mut y = BigNum::new().unwrap();
+        let mut ctx = BigNumContext::new().unwrap();
+        ec_key
+            .public_key()
+            .affine_coordinates_gfp(ec_key.group(), &mut x, &mut y, &mut ctx)
+            .unwrap();
+
+        // {1: 2, -1: 1, -2: h'..', -3: h'..'}
+        let mut expected = vec![0xa4, 0x01, 0x02, 0x20, 0x01, 0x21, 0x58, 0x20];
+        expected.extend_from_slice(&pad(&x, 32).unwrap());
+        expected.extend_from_slice(&[0x22, 0x58, 0x20]);
+        expected.extend_from_slice(&pad(&y, 32).unwrap());
+        assert_eq!(cose_key, expected);
+
+        let public = PKey::public_key_from_cose_key(&cose_key).unwrap();
+        assert!(public.public_eq(&key));
+
+        let mut compressed = cose_key[..cose_key.len() - 35].to_vec();
+        compressed.extend_from_slice(&[0x22, if y.is_bit_set(0) { 0xf5 } else { 0xf4 }]);
+        let public = PKey::public_key_from_cose_key(&compressed).unwrap();
+        assert!(public.public_eq(&key));
+
+        let private =
+            PKey::private_key_from_cose_key(&key.private_key_to_cose_key().unwrap()).unwrap();
+        assert_eq!(
+            private.ec_key().unwrap().private_key(),
+            ec_key.private_key()
+        );
+    }
+
+    #[test]
+    fn rsa_key_round_trip() {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+
+        let public =
+            PKey::public_key_from_cose_key(&key.public_key_to_cose_key().unwrap()).unwrap();
+        assert!(public.public_eq(&key));
+
+        let private =
+            PKey::private_key_from_cose_key(&key.private_key_to_cose_key().unwrap()).unwrap();
+        assert!(private.public_eq(&key));
+        assert_eq!(private.rsa().unwrap().d(), key.rsa().unwrap().d());
+    }
+
+    #[test]
+    #[cfg(any(ossl111, boringssl, libressl370))]
+    fn okp_key_round_trip() {
+        let key = PKey::generate_ed25519().unwrap();
+        let cose_key = key.private_key_to_cose_key().unwrap();
+        assert_eq!(&cose_key[..5], &[0xa4, 0x01, 0x01, 0x20, 0x06]);
+
+        let private = PKey::private_key_from_cose_key(&cose_key).unwrap();
+        assert_eq!(
+            private.raw_private_key().unwrap(),
+            key.raw_private_key().unwrap()
+        );
+        let public = PKey::public_key_from_cose_key(&cose_key).unwrap();
+        assert!(public.public_eq(&key));
+    }
+
+    #[test]
+    fn sign1() {
+        let key = p256_key();
+        let message = Sign1::sign(&key, Algorithm::ES256, b"payload", b"aad").unwrap();
+        assert_eq!(message.signature().len(), 64);
+
+        let mut message = Sign1::from_slice(&message.to_vec()).unwrap();
+        assert_eq!(message.algorithm(), Algorithm::ES256);
+        assert_eq!(message.payload(), Some(&b"payload"[..]));
+        assert!(message.verify(&key, b"aad").unwrap());
+        assert!(!message.verify(&key, b"other").unwrap());
+        assert!(!message.verify(&p256_key(), b"aad").unwrap());
+
+        message.set_key_id(b"key-1");
+        let payload = message.detach_payload().unwrap();
+        let message = Sign1::from_slice(&message.to_vec()).unwrap();
+        assert_eq!(message.key_id(), Some(&b"key-1"[..]));
+        message.verify(&key, b"aad").unwrap_err();
+        assert!(message.verify_detached(&key, &payload, b"aad").unwrap());
+    }
+
+    #[test]
+    fn sign1_algorithms() {
+        let rsa = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut keys = vec![(rsa.clone(), Algorithm::PS256), (rsa, Algorithm::RS256)];
+        #[cfg(any(ossl111, boringssl, libressl370))]
+        keys.push((PKey::generate_ed25519().unwrap(), Algorithm::EDDSA));
+
+        for (key, alg) in keys {
+            let message = Sign1::sign(&key, alg, b"payload", b"").unwrap().to_vec();
+            let message = Sign1::from_slice(&message).unwrap();
+            assert_eq!(message.algorithm(), alg);
+            assert!(message.verify(&key, b"").unwrap());
+        }
+    }
+
+    #[test]
+    fn sign1_wrong_key_type() {
+        let key = p256_key();
+        Sign1::sign(&key, Algorithm::ES384, b"payload", b"").unwrap_err();
+        Sign1::sign(&key, Algorithm::RS256, b"payload", b"").unwrap_err();
+        Sign1::sign(&key, Algorithm::from_raw(0), b"payload", b"").unwrap_err();
+    }
+
+    #[test]
+    fn invalid() {
+        Sign1::from_slice(&[]).unwrap_err();
+        Sign1::from_slice(&[0x84, 0x40, 0xa0, 0xf6]).unwrap_err();
+        // missing algorithm
+        Sign1::from_slice(&[0x84, 0x40, 0xa0, 0xf6, 0x40]).unwrap_err();
+        // trailing data
+        Sign1::from_slice(&[0x84, 0x43, 0xa1, 0x01, 0x26, 0xa0, 0xf6, 0x40, 0x00]).unwrap_err();
+        Sign1::from_slice(&[0x84, 0x43, 0xa1, 0x01, 0x26, 0xa0, 0xf6, 0x40]).unwrap();
+        PKey::public_key_from_cose_key(&[0xa1, 0x01, 0x04]).unwrap_err();
+        PKey::public_key_from_cose_key(&[0x5b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff])
+            .unwrap_err();
+    }
+}
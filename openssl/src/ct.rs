@@ -0,0 +1,280 @@
+//! Certificate Transparency signed certificate timestamps.
+//!
+//! A signed certificate timestamp (SCT) is a promise by a Certificate Transparency log to
+//! incorporate a certificate, as defined in [RFC 6962]. A CA taking part in Certificate
+//! Transparency submits a precertificate to one or more logs, and embeds the SCTs it receives
+//! in the final certificate.
+//!
+//! Embedded SCTs are read with [`X509Ref::scts`], and embedded with
+//! [`X509Extension::new_sct_list`].
+//!
+//! Requires OpenSSL 1.1.0 or newer.
+//!
+//! [RFC 6962]: https://tools.ietf.org/html/rfc6962#section-3.2
+//! [`X509Ref::scts`]: crate::x509::X509Ref::scts
+//! [`X509Extension::new_sct_list`]: crate::x509::X509Extension::new_sct_list
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::{c_int, c_uint};
+use std::ptr;
+use std::slice;
+
+use crate::error::ErrorStack;
+use crate::nid::Nid;
+use crate::stack::{Stack, StackRef, Stackable};
+use crate::{cvt, cvt_p};
+use openssl_macros::corresponds;
+
+/// The version of a signed certificate timestamp.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SctVersion(c_int);
+
+impl SctVersion {
+    /// The version defined in RFC 6962.
+    pub const V1: SctVersion = SctVersion(ffi::SCT_VERSION_V1);
+
+    /// Constructs an `SctVersion` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> SctVersion {
+        SctVersion(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+/// The type of log entry a signed certificate timestamp was issued for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LogEntryType(c_int);
+
+impl LogEntryType {
+    /// The log entry type is unknown.
+    pub const NOT_SET: LogEntryType = LogEntryType(ffi::CT_LOG_ENTRY_TYPE_NOT_SET);
+    /// The SCT was issued for a certificate.
+    pub const X509: LogEntryType = LogEntryType(ffi::CT_LOG_ENTRY_TYPE_X509);
+    /// The SCT was issued for a precertificate.
+    pub const PRECERT: LogEntryType = LogEntryType(ffi::CT_LOG_ENTRY_TYPE_PRECERT);
+
+    /// Constructs a `LogEntryType` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> LogEntryType {
+        LogEntryType(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+/// Where a signed certificate timestamp was obtained from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SctSource(c_uint);
+
+impl SctSource {
+    /// The source of the SCT is unknown, for example because it was parsed directly.
+    pub const UNKNOWN: SctSource = SctSource(ffi::SCT_SOURCE_UNKNOWN);
+    /// The SCT was received in the TLS `signed_certificate_timestamp` extension.
+    pub const TLS_EXTENSION: SctSource = SctSource(ffi::SCT_SOURCE_TLS_EXTENSION);
+    /// The SCT was embedded in the certificate.
+    pub const X509V3_EXTENSION: SctSource = SctSource(ffi::SCT_SOURCE_X509V3_EXTENSION);
+    /// The SCT was embedded in a stapled OCSP response.
+    pub const OCSP_STAPLED_RESPONSE: SctSource = SctSource(ffi::SCT_SOURCE_OCSP_STAPLED_RESPONSE);
+
+    /// Constructs an `SctSource` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_uint) -> SctSource {
+        SctSource(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_uint {
+        self.0
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::SCT;
+    fn drop = ffi::SCT_free;
+
+    /// A signed certificate timestamp.
+    pub struct Sct;
+    /// A reference to an [`Sct`].
+    pub struct SctRef;
+}
+
+impl Stackable for Sct {
+    type StackType = ffi::stack_st_SCT;
+}
+
+impl Sct {
+    /// Deserializes an SCT from the TLS encoding defined in RFC 6962.
+    #[corresponds(o2i_SCT)]
+    pub fn from_tls(tls: &[u8]) -> Result<Sct, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let sct = cvt_p(ffi::o2i_SCT(ptr::null_mut(), &mut tls.as_ptr(), tls.len()))?;
+            Ok(Sct::from_ptr(sct))
+        }
+    }
+
+    /// Deserializes a `SignedCertificateTimestampList` from the TLS encoding defined in RFC 6962.
+    ///
+    /// This is the contents of the TLS extension and of the certificate extension.
+    #[corresponds(o2i_SCT_LIST)]
+    pub fn list_from_tls(tls: &[u8]) -> Result<Stack<Sct>, ErrorStack> {
+        unsafe {
+            ffi::init();
+            let list = cvt_p(ffi::o2i_SCT_LIST(
+                ptr::null_mut(),
+                &mut tls.as_ptr(),
+                tls.len(),
+            ))?;
+            Ok(Stack::from_ptr(list))
+        }
+    }
+
+    /// Serializes a list of SCTs to the TLS encoding of a `SignedCertificateTimestampList`.
+    #[corresponds(i2o_SCT_LIST)]
+    pub fn list_to_tls(list: &StackRef<Sct>) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let len = cvt(ffi::i2o_SCT_LIST(list.as_ptr(), ptr::null_mut()))?;
+            let mut buf = vec![0; len as usize];
+            cvt(ffi::i2o_SCT_LIST(list.as_ptr(), &mut buf.as_mut_ptr()))?;
+            Ok(buf)
+        }
+    }
+}
+
+impl SctRef {
+    /// Serializes the SCT to the TLS encoding defined in RFC 6962.
+    #[corresponds(i2o_SCT)]
+    pub fn to_tls(&self) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let len = cvt(ffi::i2o_SCT(self.as_ptr(), ptr::null_mut()))?;
+            let mut buf = vec![0; len as usize];
+            cvt(ffi::i2o_SCT(self.as_ptr(), &mut buf.as_mut_ptr()))?;
+            Ok(buf)
+        }
+    }
+
+    /// Returns the version of the SCT.
+    #[corresponds(SCT_get_version)]
+    pub fn version(&self) -> SctVersion {
+        unsafe { SctVersion(ffi::SCT_get_version(self.as_ptr())) }
+    }
+
+    /// Returns the type of log entry the SCT was issued for.
+    #[corresponds(SCT_get_log_entry_type)]
+    pub fn log_entry_type(&self) -> LogEntryType {
+        unsafe { LogEntryType(ffi::SCT_get_log_entry_type(self.as_ptr())) }
+    }
+
+    /// Returns the ID of the log which issued the SCT, the SHA-256 hash of its public key.
+    #[corresponds(SCT_get0_log_id)]
+    pub fn log_id(&self) -> &[u8] {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+            let len = ffi::SCT_get0_log_id(self.as_ptr(), &mut ptr);
+            bytes(ptr, len)
+        }
+    }
+
+    /// Returns the time at which the SCT was issued, in milliseconds since the Unix epoch.
+    #[corresponds(SCT_get_timestamp)]
+    pub fn timestamp(&self) -> u64 {
+        unsafe { ffi::SCT_get_timestamp(self.as_ptr()) }
+    }
+
+    /// Returns the signature algorithm of the SCT, such as `Nid::ECDSA_WITH_SHA256`.
+    #[corresponds(SCT_get_signature_nid)]
+    pub fn signature_nid(&self) -> Nid {
+        unsafe { Nid::from_raw(ffi::SCT_get_signature_nid(self.as_ptr())) }
+    }
+
+    /// Returns the extensions of the SCT.
+    #[corresponds(SCT_get0_extensions)]
+    pub fn extensions(&self) -> &[u8] {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+            let len = ffi::SCT_get0_extensions(self.as_ptr(), &mut ptr);
+            bytes(ptr, len)
+        }
+    }
+
+    /// Returns the signature of the SCT.
+    #[corresponds(SCT_get0_signature)]
+    pub fn signature(&self) -> &[u8] {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+            let len = ffi::SCT_get0_signature(self.as_ptr(), &mut ptr);
+            bytes(ptr, len)
+        }
+    }
+
+    /// Returns where the SCT was obtained from.
+    #[corresponds(SCT_get_source)]
+    pub fn source(&self) -> SctSource {
+        unsafe { SctSource(ffi::SCT_get_source(self.as_ptr())) }
+    }
+}
+
+unsafe fn bytes<'a>(ptr: *const u8, len: usize) -> &'a [u8] {
+    if ptr.is_null() || len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(ptr, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex::FromHex;
+
+    // A version 1 SCT with an empty extensions field and a placeholder ECDSA-SHA256 signature.
+    const SCT: &str = "00\
+        a4b90990b418581487bb13a2cc67700a3c359804f91bdfb8e377cd0ec80ddc10\
+        0000017b2ce6a5a0\
+        0000\
+        040300083006020101020101";
+
+    #[test]
+    fn parse() {
+        let tls = Vec::from_hex(SCT).unwrap();
+        let sct = Sct::from_tls(&tls).unwrap();
+        assert_eq!(sct.version(), SctVersion::V1);
+        assert_eq!(
+            hex::encode(sct.log_id()),
+            "a4b90990b418581487bb13a2cc67700a3c359804f91bdfb8e377cd0ec80ddc10"
+        );
+        assert_eq!(sct.timestamp(), 1628545918368);
+        assert_eq!(sct.extensions(), b"");
+        assert_eq!(sct.signature_nid(), Nid::ECDSA_WITH_SHA256);
+        assert_eq!(sct.signature(), &tls[tls.len() - 8..]);
+        assert_eq!(sct.source(), SctSource::UNKNOWN);
+        assert_eq!(sct.to_tls().unwrap(), tls);
+    }
+
+    #[test]
+    fn list() {
+        let tls = Vec::from_hex(SCT).unwrap();
+        let mut list = Stack::new().unwrap();
+        list.push(Sct::from_tls(&tls).unwrap()).unwrap();
+        list.push(Sct::from_tls(&tls).unwrap()).unwrap();
+
+        let encoded = Sct::list_to_tls(&list).unwrap();
+        assert_eq!(encoded.len(), 2 + 2 * (2 + tls.len()));
+
+        let parsed = Sct::list_from_tls(&encoded).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1].to_tls().unwrap(), tls);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Sct::from_tls(b"\x00\x01").is_err());
+        assert!(Sct::list_from_tls(b"\x00\x05\x00").is_err());
+    }
+}
@@ -0,0 +1,165 @@
+//! Generic DER serialization.
+//!
+//! Most types in this crate which have a DER encoding provide inherent `to_der` and `from_der`
+//! methods. The [`ToDer`] and [`FromDer`] traits expose the same methods so that code such as
+//! caches can be written once for every such type.
+//!
+//! Both the owned and the borrowed form of a type implement [`ToDer`].
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::der::{FromDer, ToDer};
+//! use openssl::error::ErrorStack;
+//! use openssl::x509::X509;
+//!
+//! fn round_trip<T: ToDer + FromDer>(value: &T) -> Result<T, ErrorStack> {
+//!     T::from_der(&value.to_der()?)
+//! }
+//!
+//! let cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+//! let copy = round_trip(&cert).unwrap();
+//! assert_eq!(copy.to_der().unwrap(), cert.to_der().unwrap());
+//! ```
+use crate::error::ErrorStack;
+
+/// A type which can be serialized to DER.
+pub trait ToDer {
+    /// Serializes the value to DER.
+    fn to_der(&self) -> Result<Vec<u8>, ErrorStack>;
+}
+
+/// A type which can be deserialized from DER.
+pub trait FromDer: Sized {
+    /// Deserializes a DER-encoded value.
+    fn from_der(der: &[u8]) -> Result<Self, ErrorStack>;
+}
+
+impl<T: ToDer + ?Sized> ToDer for &T {
+    fn to_der(&self) -> Result<Vec<u8>, ErrorStack> {
+        (**self).to_der()
+    }
+}
+
+macro_rules! impl_to_der {
+    ($($(#[$attr:meta])* $owned:ty, $borrowed:ty;)*) => {
+        $(
+            $(#[$attr])*
+            impl ToDer for $owned {
+                fn to_der(&self) -> Result<Vec<u8>, ErrorStack> {
+                    <$borrowed>::to_der(self)
+                }
+            }
+
+            $(#[$attr])*
+            impl ToDer for $borrowed {
+                fn to_der(&self) -> Result<Vec<u8>, ErrorStack> {
+                    <$borrowed>::to_der(self)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_der {
+    ($($(#[$attr:meta])* $owned:ty, $borrowed:ty;)*) => {
+        $(
+            impl_to_der! {
+                $(#[$attr])*
+                $owned, $borrowed;
+            }
+
+            $(#[$attr])*
+            impl FromDer for $owned {
+                fn from_der(der: &[u8]) -> Result<Self, ErrorStack> {
+                    <$owned>::from_der(der)
+                }
+            }
+        )*
+    };
+}
+
+impl_der! {
+    crate::x509::X509, crate::x509::X509Ref;
+    crate::x509::X509Name, crate::x509::X509NameRef;
+    crate::x509::X509Req, crate::x509::X509ReqRef;
+    crate::x509::X509Crl, crate::x509::X509CrlRef;
+    crate::x509::X509Revoked, crate::x509::X509RevokedRef;
+    crate::x509::X509Algorithm, crate::x509::X509AlgorithmRef;
+    crate::pkcs12::Pkcs12, crate::pkcs12::Pkcs12Ref;
+    crate::ssl::SslSession, crate::ssl::SslSessionRef;
+    crate::dsa::DsaSig, crate::dsa::DsaSigRef;
+    crate::ecdsa::EcdsaSig, crate::ecdsa::EcdsaSigRef;
+    #[cfg(not(boringssl))]
+    crate::pkcs7::Pkcs7, crate::pkcs7::Pkcs7Ref;
+    #[cfg(all(not(boringssl), not(libressl), not(osslconf = "OPENSSL_NO_CMS")))]
+    crate::cms::CmsContentInfo, crate::cms::CmsContentInfoRef;
+    #[cfg(not(any(boringssl, osslconf = "OPENSSL_NO_OCSP")))]
+    crate::ocsp::OcspResponse, crate::ocsp::OcspResponseRef;
+    #[cfg(not(any(boringssl, osslconf = "OPENSSL_NO_OCSP")))]
+    crate::ocsp::OcspRequest, crate::ocsp::OcspRequestRef;
+}
+
+impl_to_der! {
+    crate::x509::X509Extension, crate::x509::X509ExtensionRef;
+}
+
+impl ToDer for crate::x509::other_name::HardwareModuleName {
+    fn to_der(&self) -> Result<Vec<u8>, ErrorStack> {
+        crate::x509::other_name::HardwareModuleName::to_der(self)
+    }
+}
+
+impl FromDer for crate::x509::other_name::HardwareModuleName {
+    fn from_der(der: &[u8]) -> Result<Self, ErrorStack> {
+        crate::x509::other_name::HardwareModuleName::from_der(der)
+    }
+}
+
+impl ToDer for crate::x509::other_name::PermanentIdentifier {
+    fn to_der(&self) -> Result<Vec<u8>, ErrorStack> {
+        crate::x509::other_name::PermanentIdentifier::to_der(self)
+    }
+}
+
+impl FromDer for crate::x509::other_name::PermanentIdentifier {
+    fn from_der(der: &[u8]) -> Result<Self, ErrorStack> {
+        crate::x509::other_name::PermanentIdentifier::from_der(der)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::x509::{X509Crl, X509Name, X509Req, X509};
+
+    fn round_trip<T: ToDer + FromDer>(der: &[u8]) {
+        let value = T::from_der(der).unwrap();
+        assert_eq!(value.to_der().unwrap(), der);
+    }
+
+    #[test]
+    fn round_trips() {
+        let cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        round_trip::<X509>(&cert.to_der().unwrap());
+        round_trip::<X509Name>(&cert.subject_name().to_der().unwrap());
+
+        let req = X509Req::from_pem(include_bytes!("../test/csr.pem")).unwrap();
+        round_trip::<X509Req>(&req.to_der().unwrap());
+
+        round_trip::<X509Crl>(include_bytes!("../test/test.crl"));
+    }
+
+    #[test]
+    fn borrowed() {
+        let cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        let expected = cert.to_der().unwrap();
+        let values: Vec<&dyn ToDer> = vec![&cert, &*cert, cert.subject_name()];
+        assert_eq!(values[0].to_der().unwrap(), expected);
+        assert_eq!(values[1].to_der().unwrap(), expected);
+        assert_eq!(
+            values[2].to_der().unwrap(),
+            cert.subject_name().to_der().unwrap()
+        );
+    }
+}
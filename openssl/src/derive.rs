@@ -50,14 +50,40 @@
 //! [ECDH]: https://wiki.openssl.org/index.php/Elliptic_Curve_Diffie_Hellman
 
 use foreign_types::ForeignTypeRef;
+#[cfg(ossl110)]
+use libc::c_int;
+#[cfg(ossl110)]
+use std::cmp;
+#[cfg(ossl110)]
+use std::convert::TryFrom;
 use std::marker::PhantomData;
 use std::ptr;
 
 use crate::error::ErrorStack;
+#[cfg(ossl110)]
+use crate::md::MdRef;
 use crate::pkey::{HasPrivate, HasPublic, PKeyRef};
 use crate::{cvt, cvt_p};
 use openssl_macros::corresponds;
 
+/// A key derivation function applied to the output of ECDH.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+#[cfg(ossl110)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EcdhKdf(c_int);
+
+#[cfg(ossl110)]
+impl EcdhKdf {
+    /// The raw shared secret is returned.
+    ///
+    /// This is the default.
+    pub const NONE: Self = EcdhKdf(ffi::EVP_PKEY_ECDH_KDF_NONE);
+
+    /// The shared secret is passed through the ANSI X9.63 KDF.
+    pub const X9_63: Self = EcdhKdf(ffi::EVP_PKEY_ECDH_KDF_X9_63);
+}
+
 /// A type used to derive a shared secret between two keys.
 pub struct Deriver<'a>(*mut ffi::EVP_PKEY_CTX, PhantomData<&'a ()>);
 
@@ -114,6 +140,74 @@ impl<'a> Deriver<'a> {
         }
     }
 
+    /// Sets the key derivation function applied to an ECDH shared secret.
+    ///
+    /// When a KDF is used, the digest and output length must also be set with
+    /// [`set_ecdh_kdf_md`](Self::set_ecdh_kdf_md) and
+    /// [`set_ecdh_kdf_outlen`](Self::set_ecdh_kdf_outlen).
+    ///
+    /// This is only useful for EC keys.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(EVP_PKEY_CTX_set_ecdh_kdf_type)]
+    #[cfg(ossl110)]
+    pub fn set_ecdh_kdf_type(&mut self, kdf: EcdhKdf) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::EVP_PKEY_CTX_set_ecdh_kdf_type(self.0, kdf.0)).map(|_| ()) }
+    }
+
+    /// Sets the digest used by the ECDH key derivation function.
+    ///
+    /// This is only useful for EC keys.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(EVP_PKEY_CTX_set_ecdh_kdf_md)]
+    #[cfg(ossl110)]
+    pub fn set_ecdh_kdf_md(&mut self, digest: &MdRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::EVP_PKEY_CTX_set_ecdh_kdf_md(self.0, digest.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets the length of the output of the ECDH key derivation function.
+    ///
+    /// This is only useful for EC keys.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(EVP_PKEY_CTX_set_ecdh_kdf_outlen)]
+    #[cfg(ossl110)]
+    pub fn set_ecdh_kdf_outlen(&mut self, len: usize) -> Result<(), ErrorStack> {
+        let len = c_int::try_from(len).unwrap();
+
+        unsafe { cvt(ffi::EVP_PKEY_CTX_set_ecdh_kdf_outlen(self.0, len)).map(|_| ()) }
+    }
+
+    /// Sets the user keying material, or shared info, passed to the ECDH key derivation function.
+    ///
+    /// This is only useful for EC keys.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(EVP_PKEY_CTX_set0_ecdh_kdf_ukm)]
+    #[cfg(ossl110)]
+    pub fn set_ecdh_kdf_ukm(&mut self, ukm: &[u8]) -> Result<(), ErrorStack> {
+        let len = c_int::try_from(ukm.len()).unwrap();
+
+        unsafe {
+            // OPENSSL_malloc may return null for 0 bytes, and OpenSSL 3 rejects a null pointer
+            // even with a length of 0, so at least one byte is allocated
+            let p = cvt_p(ffi::OPENSSL_malloc(cmp::max(ukm.len(), 1) as _))?;
+            ptr::copy_nonoverlapping(ukm.as_ptr(), p as *mut u8, ukm.len());
+
+            cvt(ffi::EVP_PKEY_CTX_set0_ecdh_kdf_ukm(
+                self.0,
+                p as *mut _,
+                len,
+            ))
+            .map(|_| ())
+            .map_err(|e| {
+                ffi::OPENSSL_free(p);
+                e
+            })
+        }
+    }
+
     /// Returns the size of the shared secret.
     ///
     /// It can be used to size the buffer passed to [`Deriver::derive`].
@@ -214,4 +308,40 @@ mod test {
         let shared = deriver.derive_to_vec().unwrap();
         assert!(!shared.is_empty());
     }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn test_ec_key_derive_x963() {
+        use crate::md::Md;
+        use crate::sha::Sha256;
+
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let pkey = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let pkey2 = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+        let mut deriver = Deriver::new(&pkey).unwrap();
+        deriver.set_peer(&pkey2).unwrap();
+        let z = deriver.derive_to_vec().unwrap();
+
+        for shared_info in &[&b"shared info"[..], b""] {
+            let mut expected = vec![];
+            for counter in 1u32..=2 {
+                let mut h = Sha256::new();
+                h.update(&z);
+                h.update(&counter.to_be_bytes());
+                h.update(shared_info);
+                expected.extend_from_slice(&h.finish());
+            }
+            expected.truncate(48);
+
+            let mut deriver = Deriver::new(&pkey).unwrap();
+            deriver.set_peer(&pkey2).unwrap();
+            deriver.set_ecdh_kdf_type(EcdhKdf::X9_63).unwrap();
+            deriver.set_ecdh_kdf_md(Md::sha256()).unwrap();
+            deriver.set_ecdh_kdf_outlen(48).unwrap();
+            deriver.set_ecdh_kdf_ukm(shared_info).unwrap();
+            assert_eq!(deriver.len().unwrap(), 48);
+            assert_eq!(deriver.derive_to_vec().unwrap(), expected);
+        }
+    }
 }
@@ -238,6 +238,11 @@ impl EcGroupRef {
         unsafe { ffi::EC_GROUP_get_degree(self.as_ptr()) as u32 }
     }
 
+    /// Returns the length in bytes of a field element, as used by fixed-width encodings.
+    pub(crate) fn field_len(&self) -> usize {
+        (self.degree() as usize + 7) / 8
+    }
+
     /// Returns the number of bits in the group order.
     #[corresponds(EC_GROUP_order_bits)]
     #[cfg(ossl110)]
@@ -452,7 +457,7 @@ impl EcPointRef {
                 ctx.as_ptr(),
             );
             if len == 0 {
-                return Err(ErrorStack::get());
+                return Err(ErrorStack::report());
             }
             let mut buf = vec![0; len];
             let len = ffi::EC_POINT_point2oct(
@@ -464,7 +469,7 @@ impl EcPointRef {
                 ctx.as_ptr(),
             );
             if len == 0 {
-                Err(ErrorStack::get())
+                Err(ErrorStack::report())
             } else {
                 Ok(buf)
             }
@@ -0,0 +1,397 @@
+//! Elliptic Curve Integrated Encryption Scheme.
+//!
+//! ECIES, specified in section 5.1 of [SEC 1], encrypts a message to an elliptic curve public key.
+//! The sender generates an ephemeral key on the recipient's curve, derives symmetric keys from
+//! the ECDH shared secret with the ANSI X9.63 KDF, and encrypts the message with them. The output
+//! is the ephemeral public key as an uncompressed point, followed by the ciphertext and its tag.
+//!
+//! Implementations differ in the symmetric scheme and in how the keys are derived, so those
+//! parameters are gathered into a [`Profile`]. [`Profile::apple_standard`] and
+//! [`Profile::apple_variable_iv`] interoperate with the ECIES algorithms of Apple's Security
+//! framework, which encrypt to keys held in the Secure Enclave.
+//!
+//! Requires OpenSSL 3.0.0 or newer.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::ec::{EcGroup, EcKey};
+//! use openssl::ecies::{self, Profile};
+//! use openssl::nid::Nid;
+//! use openssl::pkey::PKey;
+//!
+//! let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+//! let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+//!
+//! let profile = Profile::apple_variable_iv();
+//! let ciphertext = ecies::encrypt(&profile, &key, b"secret message").unwrap();
+//! let plaintext = ecies::decrypt(&profile, &key, &ciphertext).unwrap();
+//! assert_eq!(plaintext, b"secret message");
+//! ```
+//!
+//! [SEC 1]: https://www.secg.org/sec1-v2.pdf
+
+use crate::bn::BigNumContext;
+use crate::derive::Deriver;
+use crate::ec::{EcGroupRef, EcKey, EcPoint, PointConversionForm};
+use crate::error::ErrorStack;
+use crate::kdf::x963kdf;
+use crate::md::{Md, MdRef};
+use crate::md_ctx::MdCtx;
+use crate::memcmp;
+use crate::pkey::{HasPublic, PKey, PKeyRef, Private};
+use crate::symm::{self, Cipher};
+
+const IV_LEN: usize = 16;
+const GCM_TAG_LEN: usize = 16;
+
+invalid_input_error! {
+    /// An error decrypting an ECIES message.
+    pub struct EciesError => "invalid ECIES message: {}";
+}
+
+/// The symmetric scheme which encrypts and authenticates the message.
+#[derive(Copy, Clone)]
+pub enum Encryption {
+    /// AES in GCM mode, with a 16 byte tag.
+    AesGcm,
+
+    /// AES in CTR mode, followed by an HMAC of the ciphertext with the given digest.
+    ///
+    /// The HMAC key and the tag are as long as the digest's output.
+    AesCtrHmac(&'static MdRef),
+}
+
+/// The parameters of an ECIES implementation.
+#[derive(Copy, Clone)]
+pub struct Profile {
+    kdf_digest: &'static MdRef,
+    encryption: Encryption,
+    key_len: Option<usize>,
+    derived_iv: bool,
+    ephemeral_key_in_shared_info: bool,
+}
+
+impl Profile {
+    /// Creates a profile which derives keys with the X9.63 KDF over `kdf_digest`.
+    ///
+    /// By default, the AES key is 16 bytes long for curves of up to 256 bits and 32 bytes long
+    /// otherwise, the 16 byte IV is all zeros, and the KDF's `SharedInfo` is empty.
+    pub fn new(kdf_digest: &'static MdRef, encryption: Encryption) -> Profile {
+        Profile {
+            kdf_digest,
+            encryption,
+            key_len: None,
+            derived_iv: false,
+            ephemeral_key_in_shared_info: false,
+        }
+    }
+
+    /// Returns the profile of Apple's `eciesEncryptionStandardX963SHA256AESGCM` algorithm.
+    ///
+    /// This is also the profile of the corresponding cofactor algorithm, since the cofactor of
+    /// the NIST curves is 1.
+    pub fn apple_standard() -> Profile {
+        let mut profile = Profile::new(Md::sha256(), Encryption::AesGcm);
+        profile.set_ephemeral_key_in_shared_info(true);
+        profile
+    }
+
+    /// Returns the profile of Apple's `eciesEncryptionStandardVariableIVX963SHA256AESGCM`
+    /// algorithm.
+    ///
+    /// This is also the profile of the corresponding cofactor algorithm.
+    pub fn apple_variable_iv() -> Profile {
+        let mut profile = Profile::apple_standard();
+        profile.set_derived_iv(true);
+        profile
+    }
+
+    /// Sets the length of the AES key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is not 16, 24, or 32.
+    pub fn set_key_len(&mut self, len: usize) -> &mut Profile {
+        assert!(
+            len == 16 || len == 24 || len == 32,
+            "invalid AES key length {}",
+            len
+        );
+        self.key_len = Some(len);
+        self
+    }
+
+    /// Sets whether the IV is derived by the KDF, following the keys, rather than all zeros.
+    pub fn set_derived_iv(&mut self, derived: bool) -> &mut Profile {
+        self.derived_iv = derived;
+        self
+    }
+
+    /// Sets whether the ephemeral public key is used as the KDF's `SharedInfo`.
+    pub fn set_ephemeral_key_in_shared_info(&mut self, include: bool) -> &mut Profile {
+        self.ephemeral_key_in_shared_info = include;
+        self
+    }
+
+    fn key_len(&self, group: &EcGroupRef) -> usize {
+        match self.key_len {
+            Some(len) => len,
+            None if group.degree() <= 256 => 16,
+            None => 32,
+        }
+    }
+
+    fn tag_len(&self) -> usize {
+        match self.encryption {
+            Encryption::AesGcm => GCM_TAG_LEN,
+            Encryption::AesCtrHmac(md) => md.size(),
+        }
+    }
+
+    fn keys(
+        &self,
+        group: &EcGroupRef,
+        shared_secret: &[u8],
+        ephemeral_key: &[u8],
+    ) -> Result<Keys, ErrorStack> {
+        let key_len = self.key_len(group);
+        let mac_key_len = match self.encryption {
+            Encryption::AesGcm => 0,
+            Encryption::AesCtrHmac(md) => md.size(),
+        };
+        let iv_len = if self.derived_iv { IV_LEN } else { 0 };
+        let shared_info = if self.ephemeral_key_in_shared_info {
+            ephemeral_key
+        } else {
+            &[]
+        };
+
+        let mut keys = vec![0; key_len + mac_key_len + iv_len];
+        x963kdf(self.kdf_digest, shared_secret, shared_info, &mut keys)?;
+        let iv = if self.derived_iv {
+            keys.split_off(key_len + mac_key_len)
+        } else {
+            vec![0; IV_LEN]
+        };
+        let mac_key = keys.split_off(key_len);
+
+        let cipher = match (self.encryption, key_len) {
+            (Encryption::AesGcm, 16) => Cipher::aes_128_gcm(),
+            (Encryption::AesGcm, 24) => Cipher::aes_192_gcm(),
+            (Encryption::AesGcm, _) => Cipher::aes_256_gcm(),
+            (Encryption::AesCtrHmac(_), 16) => Cipher::aes_128_ctr(),
+            (Encryption::AesCtrHmac(_), 24) => Cipher::aes_192_ctr(),
+            (Encryption::AesCtrHmac(_), _) => Cipher::aes_256_ctr(),
+        };
+        Ok(Keys {
+            cipher,
+            key: keys,
+            mac_key,
+            iv,
+        })
+    }
+}
+
+struct Keys {
+    cipher: Cipher,
+    key: Vec<u8>,
+    mac_key: Vec<u8>,
+    iv: Vec<u8>,
+}
+
+/// Encrypts a message to an elliptic curve public key.
+pub fn encrypt<T>(
+    profile: &Profile,
+    public_key: &PKeyRef<T>,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, ErrorStack>
+where
+    T: HasPublic,
+{
+    let ec_key = public_key.ec_key()?;
+    let group = ec_key.group();
+    let ephemeral = EcKey::generate(group)?;
+    let mut ctx = BigNumContext::new()?;
+    let mut out =
+        ephemeral
+            .public_key()
+            .to_bytes(group, PointConversionForm::UNCOMPRESSED, &mut ctx)?;
+
+    let ephemeral = PKey::from_ec_key(ephemeral)?;
+    let mut deriver = Deriver::new(&ephemeral)?;
+    deriver.set_peer(public_key)?;
+    let shared_secret = deriver.derive_to_vec()?;
+    let keys = profile.keys(group, &shared_secret, &out)?;
+
+    match profile.encryption {
+        Encryption::AesGcm => {
+            let mut tag = [0; GCM_TAG_LEN];
+            let ciphertext = symm::encrypt_aead(
+                keys.cipher,
+                &keys.key,
+                Some(&keys.iv),
+                &[],
+                plaintext,
+                &mut tag,
+            )?;
+            out.extend_from_slice(&ciphertext);
+            out.extend_from_slice(&tag);
+        }
+        Encryption::AesCtrHmac(md) => {
+            let ciphertext = symm::encrypt(keys.cipher, &keys.key, Some(&keys.iv), plaintext)?;
+            let tag = hmac(md, &keys.mac_key, &ciphertext)?;
+            out.extend_from_slice(&ciphertext);
+            out.extend_from_slice(&tag);
+        }
+    }
+    Ok(out)
+}
+
+/// Decrypts a message encrypted to an elliptic curve private key.
+pub fn decrypt(
+    profile: &Profile,
+    private_key: &PKeyRef<Private>,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, EciesError> {
+    let ec_key = private_key.ec_key()?;
+    let group = ec_key.group();
+    let point_len = 1 + 2 * group.field_len();
+    let tag_len = profile.tag_len();
+    if ciphertext.len() < point_len + tag_len {
+        return Err(EciesError::invalid("message too short"));
+    }
+    let (ephemeral_key, ciphertext) = ciphertext.split_at(point_len);
+    let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - tag_len);
+
+    let mut ctx = BigNumContext::new()?;
+    let point = EcPoint::from_bytes(group, ephemeral_key, &mut ctx)?;
+    let ephemeral = PKey::from_ec_key(EcKey::from_public_key(group, &point)?)?;
+    let mut deriver = Deriver::new(private_key)?;
+    deriver.set_peer(&ephemeral)?;
+    let shared_secret = deriver.derive_to_vec()?;
+    let keys = profile.keys(group, &shared_secret, ephemeral_key)?;
+
+    let plaintext = match profile.encryption {
+        Encryption::AesGcm => {
+            symm::decrypt_aead(keys.cipher, &keys.key, Some(&keys.iv), &[], ciphertext, tag)?
+        }
+        Encryption::AesCtrHmac(md) => {
+            let expected = hmac(md, &keys.mac_key, ciphertext)?;
+            if !memcmp::eq(&expected, tag) {
+                return Err(EciesError::invalid("tag mismatch"));
+            }
+            symm::decrypt(keys.cipher, &keys.key, Some(&keys.iv), ciphertext)?
+        }
+    };
+    Ok(plaintext)
+}
+
+fn hmac(md: &MdRef, key: &[u8], data: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+    let key = PKey::hmac(key)?;
+    let mut ctx = MdCtx::new()?;
+    ctx.digest_sign_init(Some(md), &key)?;
+    ctx.digest_sign_update(data)?;
+    let mut tag = vec![];
+    ctx.digest_sign_final_to_vec(&mut tag)?;
+    Ok(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ec::EcGroup;
+    use crate::nid::Nid;
+
+    fn key(nid: Nid) -> PKey<Private> {
+        let group = EcGroup::from_curve_name(nid).unwrap();
+        PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut aes_192 = Profile::new(Md::sha384(), Encryption::AesGcm);
+        aes_192.set_key_len(24);
+        let mut hmac = Profile::new(Md::sha256(), Encryption::AesCtrHmac(Md::sha256()));
+        hmac.set_derived_iv(true);
+        let profiles = [
+            Profile::apple_standard(),
+            Profile::apple_variable_iv(),
+            aes_192,
+            Profile::new(Md::sha512(), Encryption::AesCtrHmac(Md::sha512())),
+            hmac,
+        ];
+
+        for &(nid, point_len) in &[
+            (Nid::X9_62_PRIME256V1, 65),
+            (Nid::SECP384R1, 97),
+            (Nid::SECP521R1, 133),
+        ] {
+            let key = key(nid);
+            for profile in &profiles {
+                let ciphertext = encrypt(profile, &key, b"message").unwrap();
+                assert_eq!(ciphertext[0], 4);
+                assert_eq!(ciphertext.len(), point_len + 7 + profile.tag_len());
+                assert_eq!(decrypt(profile, &key, &ciphertext).unwrap(), b"message");
+
+                let empty = encrypt(profile, &key, b"").unwrap();
+                assert_eq!(decrypt(profile, &key, &empty).unwrap(), b"");
+            }
+        }
+    }
+
+    #[test]
+    fn tampered() {
+        let key = key(Nid::X9_62_PRIME256V1);
+        let profiles = [
+            Profile::apple_standard(),
+            Profile::new(Md::sha256(), Encryption::AesCtrHmac(Md::sha256())),
+        ];
+        for profile in &profiles {
+            let mut ciphertext = encrypt(profile, &key, b"message").unwrap();
+            ciphertext[66] ^= 1;
+            assert!(decrypt(profile, &key, &ciphertext).is_err());
+            assert!(decrypt(profile, &key, &ciphertext[..70]).is_err());
+        }
+
+        let ciphertext = encrypt(&Profile::apple_standard(), &key, b"message").unwrap();
+        assert!(decrypt(&Profile::apple_variable_iv(), &key, &ciphertext).is_err());
+    }
+
+    // builds a message by hand from the description of Apple's algorithm
+    #[test]
+    fn apple_standard_layout() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = key(Nid::X9_62_PRIME256V1);
+        let ephemeral = EcKey::generate(&group).unwrap();
+        let mut ctx = BigNumContext::new().unwrap();
+        let ephemeral_key = ephemeral
+            .public_key()
+            .to_bytes(&group, PointConversionForm::UNCOMPRESSED, &mut ctx)
+            .unwrap();
+
+        let ephemeral = PKey::from_ec_key(ephemeral).unwrap();
+        let mut deriver = Deriver::new(&ephemeral).unwrap();
+        deriver.set_peer(&key).unwrap();
+        let shared_secret = deriver.derive_to_vec().unwrap();
+        let mut aes_key = [0; 16];
+        x963kdf(Md::sha256(), &shared_secret, &ephemeral_key, &mut aes_key).unwrap();
+
+        let mut tag = [0; 16];
+        let ciphertext = symm::encrypt_aead(
+            Cipher::aes_128_gcm(),
+            &aes_key,
+            Some(&[0; 16]),
+            &[],
+            b"message",
+            &mut tag,
+        )
+        .unwrap();
+        let mut message = ephemeral_key;
+        message.extend_from_slice(&ciphertext);
+        message.extend_from_slice(&tag);
+
+        let plaintext = decrypt(&Profile::apple_standard(), &key, &message).unwrap();
+        assert_eq!(plaintext, b"message");
+    }
+}
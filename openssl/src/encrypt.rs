@@ -84,7 +84,7 @@ impl<'a> Encrypter<'a> {
             let r = ffi::EVP_PKEY_encrypt_init(pctx);
             if r != 1 {
                 ffi::EVP_PKEY_CTX_free(pctx);
-                return Err(ErrorStack::get());
+                return Err(ErrorStack::report());
             }
 
             Ok(Encrypter {
@@ -288,7 +288,7 @@ impl<'a> Decrypter<'a> {
             let r = ffi::EVP_PKEY_decrypt_init(pctx);
             if r != 1 {
                 ffi::EVP_PKEY_CTX_free(pctx);
-                return Err(ErrorStack::get());
+                return Err(ErrorStack::report());
             }
 
             Ok(Decrypter {
@@ -15,15 +15,21 @@
 //!     Err(e) => println!("Parsing Error: {:?}", e),
 //! }
 //! ```
+use bitflags::bitflags;
 use cfg_if::cfg_if;
 use libc::{c_char, c_int};
+use once_cell::sync::OnceCell;
+use openssl_macros::corresponds;
 use std::borrow::Cow;
+use std::cell::Cell;
 #[cfg(boringssl)]
 use std::convert::TryInto;
 use std::error;
 use std::ffi::CStr;
 use std::fmt;
 use std::io;
+use std::marker::PhantomData;
+use std::mem;
 use std::ptr;
 use std::str;
 
@@ -48,6 +54,18 @@ impl ErrorStack {
         ErrorStack(vec)
     }
 
+    /// Returns the contents of the OpenSSL error stack, passing each error to the error hook.
+    ///
+    /// This is used where the errors are returned to the caller, so that errors which are only
+    /// probed for and discarded are not reported.
+    pub(crate) fn report() -> ErrorStack {
+        let stack = ErrorStack::get();
+        for error in stack.errors() {
+            call_error_hook(error);
+        }
+        stack
+    }
+
     /// Pushes the errors back onto the OpenSSL error stack.
     pub fn put(&self) {
         for error in self.errors() {
@@ -95,6 +113,377 @@ impl From<ErrorStack> for fmt::Error {
     }
 }
 
+type ErrorHook = Box<dyn Fn(&Error) + Sync + Send>;
+
+static ERROR_HOOK: OnceCell<ErrorHook> = OnceCell::new();
+
+thread_local! {
+    // const initializers in thread_local! require Rust 1.59
+    #[allow(clippy::missing_const_for_thread_local)]
+    static IN_ERROR_HOOK: Cell<bool> = Cell::new(false);
+}
+
+/// Installs a process-wide hook which is called with each error returned by this crate.
+///
+/// Every failing operation in this crate collects the errors queued by OpenSSL, including
+/// those raised inside callbacks, so the hook observes them in the order OpenSSL reported them,
+/// on the thread which encountered them. Errors which the crate only probes for and discards,
+/// those taken with [`ErrorStack::get`], and those discarded with `ERR_clear_error` or an
+/// [`ErrorMark`] are not seen. Errors raised by OpenSSL calls made from within the hook itself
+/// are not passed back to it.
+///
+/// Only one hook can be installed. Returns `false` if a hook was already present, in which case
+/// `hook` is dropped.
+pub fn set_error_hook<F>(hook: F) -> bool
+where
+    F: Fn(&Error) + Sync + Send + 'static,
+{
+    ERROR_HOOK.set(Box::new(hook)).is_ok()
+}
+
+/// Installs an error hook which forwards each error to the [`log`] crate.
+///
+/// Errors are logged at `level` with the `openssl::error` target. The message contains the file,
+/// line, and function reported by OpenSSL where available.
+///
+/// Requires the `log` feature.
+///
+/// [`log`]: https://docs.rs/log
+#[cfg(feature = "log")]
+pub fn set_log_error_hook(level: log::Level) -> bool {
+    set_error_hook(move |error| log::log!(target: "openssl::error", level, "{}", error))
+}
+
+/// Installs an error hook which forwards each error to the [`tracing`] crate.
+///
+/// Errors are recorded as events at `level` with the `openssl::error` target. The event's
+/// message is the error, and its fields contain the error code, library, reason, file, line,
+/// and function reported by OpenSSL where available.
+///
+/// Requires the `tracing` feature.
+///
+/// [`tracing`]: https://docs.rs/tracing
+#[cfg(feature = "tracing")]
+pub fn set_tracing_error_hook(level: tracing::Level) -> bool {
+    macro_rules! event {
+        ($level:expr, $error:expr) => {
+            tracing::event!(
+                target: "openssl::error",
+                $level,
+                code = $error.code() as u64,
+                library = $error.library(),
+                reason = $error.reason(),
+                file = $error.file(),
+                line = $error.line(),
+                function = $error.function(),
+                "{}",
+                $error
+            )
+        };
+    }
+
+    set_error_hook(move |error| match level {
+        tracing::Level::ERROR => event!(tracing::Level::ERROR, error),
+        tracing::Level::WARN => event!(tracing::Level::WARN, error),
+        tracing::Level::INFO => event!(tracing::Level::INFO, error),
+        tracing::Level::DEBUG => event!(tracing::Level::DEBUG, error),
+        _ => event!(tracing::Level::TRACE, error),
+    })
+}
+
+fn call_error_hook(error: &Error) {
+    let hook = match ERROR_HOOK.get() {
+        Some(hook) => hook,
+        None => return,
+    };
+
+    IN_ERROR_HOOK.with(|in_hook| {
+        if in_hook.replace(true) {
+            return;
+        }
+
+        struct Reset<'a>(&'a Cell<bool>);
+
+        impl Drop for Reset<'_> {
+            fn drop(&mut self) {
+                self.0.set(false);
+            }
+        }
+
+        let _reset = Reset(in_hook);
+        hook(error);
+    })
+}
+
+/// A mark on the OpenSSL error stack.
+///
+/// When the mark is dropped, any errors pushed onto the calling thread's error stack since it was
+/// set are discarded, and errors which were already present are left untouched. This allows an
+/// operation to be attempted without affecting the error state seen by the caller.
+///
+/// Functions in this crate collect the entire error stack when they fail, so a mark is mostly
+/// useful around direct calls into OpenSSL.
+///
+/// # Examples
+///
+/// ```
+/// use openssl::error::{ErrorMark, ErrorStack};
+/// use openssl::x509::X509;
+///
+/// let pending = X509::from_der(b"not a certificate").unwrap_err();
+/// let probe = X509::from_pem(b"not a certificate").unwrap_err();
+///
+/// pending.put();
+/// {
+///     let _mark = ErrorMark::new();
+///     probe.put();
+/// }
+///
+/// assert_eq!(ErrorStack::get().errors().len(), pending.errors().len());
+/// ```
+pub struct ErrorMark {
+    // the error stack is thread local
+    _p: PhantomData<*const ()>,
+}
+
+impl ErrorMark {
+    /// Sets a mark on the current thread's error stack.
+    #[corresponds(ERR_set_mark)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> ErrorMark {
+        unsafe {
+            ffi::init();
+            ffi::ERR_set_mark();
+        }
+        ErrorMark { _p: PhantomData }
+    }
+
+    /// Removes the mark without discarding the errors pushed since it was set.
+    #[corresponds(ERR_clear_last_mark)]
+    #[cfg(ossl111)]
+    pub fn keep(self) {
+        unsafe {
+            ffi::ERR_clear_last_mark();
+        }
+        mem::forget(self);
+    }
+}
+
+impl Drop for ErrorMark {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::ERR_pop_to_mark();
+        }
+    }
+}
+
+/// The library which reported an [`Error`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Library(c_int);
+
+#[allow(clippy::unnecessary_cast)]
+impl Library {
+    pub const NONE: Library = Library(ffi::ERR_LIB_NONE as c_int);
+    pub const SYS: Library = Library(ffi::ERR_LIB_SYS as c_int);
+    pub const BN: Library = Library(ffi::ERR_LIB_BN as c_int);
+    pub const RSA: Library = Library(ffi::ERR_LIB_RSA as c_int);
+    pub const DH: Library = Library(ffi::ERR_LIB_DH as c_int);
+    pub const EVP: Library = Library(ffi::ERR_LIB_EVP as c_int);
+    pub const BUF: Library = Library(ffi::ERR_LIB_BUF as c_int);
+    pub const OBJ: Library = Library(ffi::ERR_LIB_OBJ as c_int);
+    pub const PEM: Library = Library(ffi::ERR_LIB_PEM as c_int);
+    pub const DSA: Library = Library(ffi::ERR_LIB_DSA as c_int);
+    pub const X509: Library = Library(ffi::ERR_LIB_X509 as c_int);
+    pub const ASN1: Library = Library(ffi::ERR_LIB_ASN1 as c_int);
+    pub const CONF: Library = Library(ffi::ERR_LIB_CONF as c_int);
+    pub const CRYPTO: Library = Library(ffi::ERR_LIB_CRYPTO as c_int);
+    pub const EC: Library = Library(ffi::ERR_LIB_EC as c_int);
+    pub const SSL: Library = Library(ffi::ERR_LIB_SSL as c_int);
+    pub const BIO: Library = Library(ffi::ERR_LIB_BIO as c_int);
+    pub const PKCS7: Library = Library(ffi::ERR_LIB_PKCS7 as c_int);
+    pub const X509V3: Library = Library(ffi::ERR_LIB_X509V3 as c_int);
+    #[cfg(not(boringssl))]
+    pub const PKCS12: Library = Library(ffi::ERR_LIB_PKCS12 as c_int);
+    pub const RAND: Library = Library(ffi::ERR_LIB_RAND as c_int);
+    pub const ENGINE: Library = Library(ffi::ERR_LIB_ENGINE as c_int);
+    pub const OCSP: Library = Library(ffi::ERR_LIB_OCSP as c_int);
+    #[cfg(not(boringssl))]
+    pub const CMS: Library = Library(ffi::ERR_LIB_CMS as c_int);
+    #[cfg(not(boringssl))]
+    pub const KDF: Library = Library(ffi::ERR_LIB_KDF as c_int);
+    #[cfg(ossl300)]
+    pub const PROV: Library = Library(ffi::ERR_LIB_PROV as c_int);
+    #[cfg(ossl300)]
+    pub const ENCODER: Library = Library(ffi::ERR_LIB_OSSL_ENCODER as c_int);
+    #[cfg(ossl300)]
+    pub const DECODER: Library = Library(ffi::ERR_LIB_OSSL_DECODER as c_int);
+    pub const USER: Library = Library(ffi::ERR_LIB_USER as c_int);
+
+    /// Creates a `Library` from an OpenSSL library code.
+    pub const fn from_raw(raw: c_int) -> Library {
+        Library(raw)
+    }
+
+    /// Returns the OpenSSL library code.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub const fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+/// The reason for an [`Error`].
+///
+/// Reason codes are only meaningful in combination with the library which reported them, so a
+/// `Reason` carries both. The exception are the reasons common to all libraries, such as
+/// [`Reason::MALLOC_FAILURE`], which always use [`Library::NONE`] so that they compare equal to
+/// the constants here whichever library reported them. That library is still available from
+/// [`Error::library_id`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Reason {
+    library: Library,
+    code: c_int,
+}
+
+#[allow(clippy::unnecessary_cast)]
+impl Reason {
+    pub const MALLOC_FAILURE: Reason = Reason::common(ffi::ERR_R_MALLOC_FAILURE as c_int);
+    pub const SHOULD_NOT_HAVE_BEEN_CALLED: Reason =
+        Reason::common(ffi::ERR_R_SHOULD_NOT_HAVE_BEEN_CALLED as c_int);
+    pub const PASSED_NULL_PARAMETER: Reason =
+        Reason::common(ffi::ERR_R_PASSED_NULL_PARAMETER as c_int);
+    pub const INTERNAL_ERROR: Reason = Reason::common(ffi::ERR_R_INTERNAL_ERROR as c_int);
+    #[cfg(not(boringssl))]
+    pub const DISABLED: Reason = Reason::common(ffi::ERR_R_DISABLED);
+    #[cfg(ossl111)]
+    pub const PASSED_INVALID_ARGUMENT: Reason = Reason::common(ffi::ERR_R_PASSED_INVALID_ARGUMENT);
+    #[cfg(not(boringssl))]
+    pub const NESTED_ASN1_ERROR: Reason = Reason::common(ffi::ERR_R_NESTED_ASN1_ERROR);
+    #[cfg(not(boringssl))]
+    pub const MISSING_ASN1_EOS: Reason = Reason::common(ffi::ERR_R_MISSING_ASN1_EOS);
+    #[cfg(ossl300)]
+    pub const UNSUPPORTED: Reason = Reason::common(ffi::ERR_R_UNSUPPORTED);
+    pub const BN_LIB: Reason = Reason::common(ffi::ERR_R_BN_LIB as c_int);
+    pub const RSA_LIB: Reason = Reason::common(ffi::ERR_R_RSA_LIB as c_int);
+    pub const EVP_LIB: Reason = Reason::common(ffi::ERR_R_EVP_LIB as c_int);
+    pub const BUF_LIB: Reason = Reason::common(ffi::ERR_R_BUF_LIB as c_int);
+    pub const OBJ_LIB: Reason = Reason::common(ffi::ERR_R_OBJ_LIB as c_int);
+    pub const PEM_LIB: Reason = Reason::common(ffi::ERR_R_PEM_LIB as c_int);
+    pub const X509_LIB: Reason = Reason::common(ffi::ERR_R_X509_LIB as c_int);
+    pub const ASN1_LIB: Reason = Reason::common(ffi::ERR_R_ASN1_LIB as c_int);
+    pub const EC_LIB: Reason = Reason::common(ffi::ERR_R_EC_LIB as c_int);
+    pub const SSL_LIB: Reason = Reason::common(ffi::ERR_R_SSL_LIB as c_int);
+    pub const BIO_LIB: Reason = Reason::common(ffi::ERR_R_BIO_LIB as c_int);
+    pub const X509V3_LIB: Reason = Reason::common(ffi::ERR_R_X509V3_LIB as c_int);
+
+    pub const ASN1_HEADER_TOO_LONG: Reason =
+        Reason::from_raw(Library::ASN1, ffi::ASN1_R_HEADER_TOO_LONG as c_int);
+    #[cfg(not(boringssl))]
+    pub const ASN1_WRONG_TAG: Reason = Reason::from_raw(Library::ASN1, ffi::ASN1_R_WRONG_TAG);
+    pub const EVP_BAD_DECRYPT: Reason =
+        Reason::from_raw(Library::EVP, ffi::EVP_R_BAD_DECRYPT as c_int);
+    #[cfg(not(boringssl))]
+    pub const EVP_WRONG_FINAL_BLOCK_LENGTH: Reason =
+        Reason::from_raw(Library::EVP, ffi::EVP_R_WRONG_FINAL_BLOCK_LENGTH);
+    #[cfg(not(boringssl))]
+    pub const EVP_DATA_NOT_MULTIPLE_OF_BLOCK_LENGTH: Reason =
+        Reason::from_raw(Library::EVP, ffi::EVP_R_DATA_NOT_MULTIPLE_OF_BLOCK_LENGTH);
+    #[cfg(not(boringssl))]
+    pub const EVP_OPERATION_NOT_SUPPORTED_FOR_THIS_KEYTYPE: Reason = Reason::from_raw(
+        Library::EVP,
+        ffi::EVP_R_OPERATION_NOT_SUPPORTED_FOR_THIS_KEYTYPE,
+    );
+    #[cfg(not(boringssl))]
+    pub const PEM_BAD_DECRYPT: Reason = Reason::from_raw(Library::PEM, ffi::PEM_R_BAD_DECRYPT);
+    #[cfg(not(boringssl))]
+    pub const PEM_BAD_PASSWORD_READ: Reason =
+        Reason::from_raw(Library::PEM, ffi::PEM_R_BAD_PASSWORD_READ);
+    pub const PEM_NO_START_LINE: Reason =
+        Reason::from_raw(Library::PEM, ffi::PEM_R_NO_START_LINE as c_int);
+    #[cfg(not(boringssl))]
+    pub const RSA_D_E_NOT_CONGRUENT_TO_1: Reason =
+        Reason::from_raw(Library::RSA, ffi::RSA_R_D_E_NOT_CONGRUENT_TO_1);
+    #[cfg(not(boringssl))]
+    pub const RSA_DMP1_NOT_CONGRUENT_TO_D: Reason =
+        Reason::from_raw(Library::RSA, ffi::RSA_R_DMP1_NOT_CONGRUENT_TO_D);
+    #[cfg(not(boringssl))]
+    pub const RSA_DMQ1_NOT_CONGRUENT_TO_D: Reason =
+        Reason::from_raw(Library::RSA, ffi::RSA_R_DMQ1_NOT_CONGRUENT_TO_D);
+    #[cfg(not(boringssl))]
+    pub const RSA_IQMP_NOT_INVERSE_OF_Q: Reason =
+        Reason::from_raw(Library::RSA, ffi::RSA_R_IQMP_NOT_INVERSE_OF_Q);
+    #[cfg(not(boringssl))]
+    pub const RSA_N_DOES_NOT_EQUAL_P_Q: Reason =
+        Reason::from_raw(Library::RSA, ffi::RSA_R_N_DOES_NOT_EQUAL_P_Q);
+    #[cfg(not(boringssl))]
+    pub const RSA_P_NOT_PRIME: Reason = Reason::from_raw(Library::RSA, ffi::RSA_R_P_NOT_PRIME);
+    #[cfg(not(boringssl))]
+    pub const RSA_Q_NOT_PRIME: Reason = Reason::from_raw(Library::RSA, ffi::RSA_R_Q_NOT_PRIME);
+    #[cfg(not(boringssl))]
+    pub const RSA_VALUE_MISSING: Reason = Reason::from_raw(Library::RSA, ffi::RSA_R_VALUE_MISSING);
+    pub const SSL_CERTIFICATE_VERIFY_FAILED: Reason =
+        Reason::from_raw(Library::SSL, ffi::SSL_R_CERTIFICATE_VERIFY_FAILED as c_int);
+    #[cfg(not(boringssl))]
+    pub const SSL_NO_SHARED_CIPHER: Reason =
+        Reason::from_raw(Library::SSL, ffi::SSL_R_NO_SHARED_CIPHER);
+    #[cfg(not(boringssl))]
+    pub const SSL_WRONG_VERSION_NUMBER: Reason =
+        Reason::from_raw(Library::SSL, ffi::SSL_R_WRONG_VERSION_NUMBER);
+    #[cfg(ossl300)]
+    pub const SSL_UNEXPECTED_EOF_WHILE_READING: Reason =
+        Reason::from_raw(Library::SSL, ffi::SSL_R_UNEXPECTED_EOF_WHILE_READING);
+    pub const X509_CERT_ALREADY_IN_HASH_TABLE: Reason = Reason::from_raw(
+        Library::X509,
+        ffi::X509_R_CERT_ALREADY_IN_HASH_TABLE as c_int,
+    );
+
+    /// Creates a `Reason` from a library and an OpenSSL reason code.
+    pub const fn from_raw(library: Library, code: c_int) -> Reason {
+        Reason { library, code }
+    }
+
+    const fn common(code: c_int) -> Reason {
+        Reason::from_raw(Library::NONE, code)
+    }
+
+    fn is_common(library: Library, code: c_int) -> bool {
+        if library == Library::SYS {
+            // the reason is an errno value
+            return false;
+        }
+        cfg_if! {
+            if #[cfg(ossl300)] {
+                code as libc::c_ulong & ffi::ERR_RFLAG_COMMON != 0
+            } else {
+                // library specific reasons start at 100
+                code < 100
+            }
+        }
+    }
+
+    /// Returns the library the reason code belongs to.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub const fn library(&self) -> Library {
+        self.library
+    }
+
+    /// Returns the OpenSSL reason code.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub const fn as_raw(&self) -> c_int {
+        self.code
+    }
+}
+
+bitflags! {
+    /// Flags describing the additional data attached to an [`Error`].
+    pub struct ErrorDataFlags: c_int {
+        /// The data is a string.
+        const STRING = ffi::ERR_TXT_STRING;
+        /// The data was dynamically allocated by OpenSSL.
+        #[cfg(not(boringssl))]
+        const MALLOCED = ffi::ERR_TXT_MALLOCED;
+    }
+}
+
 /// An error reported from OpenSSL.
 #[derive(Clone)]
 pub struct Error {
@@ -103,6 +492,7 @@ pub struct Error {
     line: c_int,
     func: Option<ShimStr>,
     data: Option<Cow<'static, str>>,
+    data_flags: c_int,
 }
 
 unsafe impl Sync for Error {}
@@ -154,6 +544,7 @@ impl Error {
                         line,
                         func,
                         data,
+                        data_flags: flags,
                     })
                 }
             }
@@ -245,6 +636,11 @@ impl Error {
         unsafe { ffi::ERR_GET_LIB(self.code) }
     }
 
+    /// Returns the library reporting the error.
+    pub fn library_id(&self) -> Library {
+        Library(self.library_code())
+    }
+
     /// Returns the name of the function reporting the error.
     pub fn function(&self) -> Option<RetStr<'_>> {
         self.func.as_ref().map(|s| s.as_str())
@@ -270,6 +666,18 @@ impl Error {
         unsafe { ffi::ERR_GET_REASON(self.code) }
     }
 
+    /// Returns the reason for the error, along with the library which reported it.
+    ///
+    /// Reasons common to all libraries are returned with [`Library::NONE`].
+    pub fn reason_id(&self) -> Reason {
+        let code = self.reason_code();
+        if Reason::is_common(self.library_id(), code) {
+            Reason::common(code)
+        } else {
+            Reason::from_raw(self.library_id(), code)
+        }
+    }
+
     /// Returns the name of the source file which encountered the error.
     pub fn file(&self) -> RetStr<'_> {
         self.file.as_str()
@@ -285,6 +693,11 @@ impl Error {
     pub fn data(&self) -> Option<&str> {
         self.data.as_ref().map(|s| &**s)
     }
+
+    /// Returns the flags OpenSSL reported for the error's additional data.
+    pub fn data_flags(&self) -> ErrorDataFlags {
+        ErrorDataFlags::from_bits_truncate(self.data_flags)
+    }
 }
 
 impl fmt::Debug for Error {
@@ -401,8 +814,10 @@ cfg_if! {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     #[cfg(not(ossl310))]
     use crate::nid::Nid;
+    use crate::x509::X509;
 
     #[test]
     // Due to a bug in OpenSSL 3.1.0, this test can hang there. Skip for now.
@@ -415,4 +830,99 @@ mod tests {
         #[cfg(boringssl)]
         assert_eq!(errors[0].library_code(), ffi::ERR_LIB_OBJ as libc::c_int);
     }
+
+    #[test]
+    fn test_error_reason_id() {
+        let stack = X509::from_pem(b"not a certificate").unwrap_err();
+        let error = stack
+            .errors()
+            .iter()
+            .find(|e| e.library_id() == Library::PEM)
+            .unwrap();
+        assert_eq!(error.reason_id(), Reason::PEM_NO_START_LINE);
+        assert_eq!(error.reason_id().library(), Library::PEM);
+    }
+
+    #[test]
+    fn test_error_common_reason_id() {
+        let stack = X509::from_der(b"not a certificate").unwrap_err();
+        let error = stack
+            .errors()
+            .iter()
+            .find(|e| e.reason_id() == Reason::NESTED_ASN1_ERROR)
+            .unwrap();
+        assert_eq!(error.library_id(), Library::ASN1);
+        assert_eq!(error.reason_id().library(), Library::NONE);
+    }
+
+    #[test]
+    fn test_error_mark() {
+        let pending = X509::from_der(b"not a certificate").unwrap_err();
+        let probe = X509::from_pem(b"not a certificate").unwrap_err();
+
+        pending.put();
+        {
+            let _mark = ErrorMark::new();
+            probe.put();
+        }
+
+        let stack = ErrorStack::get();
+        assert_eq!(stack.errors().len(), pending.errors().len());
+        for (a, b) in stack.errors().iter().zip(pending.errors()) {
+            assert_eq!(a.code(), b.code());
+        }
+    }
+
+    #[test]
+    fn test_error_hook() {
+        use once_cell::sync::Lazy;
+        use std::sync::Mutex;
+        use std::thread::{self, ThreadId};
+
+        static SEEN: Lazy<Mutex<Vec<(ThreadId, ErrType)>>> = Lazy::new(|| Mutex::new(vec![]));
+
+        assert!(set_error_hook(|error| {
+            SEEN.lock()
+                .unwrap()
+                .push((thread::current().id(), error.code()))
+        }));
+        assert!(!set_error_hook(|_| {}));
+
+        let seen = || {
+            let id = thread::current().id();
+            SEEN.lock()
+                .unwrap()
+                .iter()
+                .filter(|(t, _)| *t == id)
+                .map(|(_, code)| *code)
+                .collect::<Vec<_>>()
+        };
+
+        let stack = X509::from_pem(b"not a certificate").unwrap_err();
+        let expected = stack.errors().iter().map(Error::code).collect::<Vec<_>>();
+        assert_eq!(seen(), expected);
+
+        // errors which are probed for and discarded are not reported
+        stack.put();
+        assert_eq!(ErrorStack::get().errors().len(), expected.len());
+        assert_eq!(seen(), expected);
+    }
+
+    #[test]
+    #[cfg(ossl111)]
+    fn test_error_mark_keep() {
+        let pending = X509::from_der(b"not a certificate").unwrap_err();
+        let probe = X509::from_pem(b"not a certificate").unwrap_err();
+
+        pending.put();
+        let mark = ErrorMark::new();
+        probe.put();
+        mark.keep();
+
+        let stack = ErrorStack::get();
+        assert_eq!(
+            stack.errors().len(),
+            pending.errors().len() + probe.errors().len()
+        );
+    }
 }
@@ -31,6 +31,7 @@
 //! # Ok(()) }
 //! ```
 use cfg_if::cfg_if;
+use foreign_types::ForeignTypeRef;
 use std::ffi::CString;
 use std::fmt;
 use std::io;
@@ -39,7 +40,11 @@ use std::ops::{Deref, DerefMut};
 use std::ptr;
 
 use crate::error::ErrorStack;
+use crate::md::MdRef;
 use crate::nid::Nid;
+#[cfg(ossl300)]
+use crate::policy::{self, Operation};
+use crate::thread_cache;
 use crate::{cvt, cvt_p};
 
 cfg_if! {
@@ -245,6 +250,8 @@ impl Hasher {
     /// Creates a new `Hasher` with the specified hash type.
     pub fn new(ty: MessageDigest) -> Result<Hasher, ErrorStack> {
         ffi::init();
+        #[cfg(ossl300)]
+        policy::check(ptr::null_mut(), ty.type_(), Operation::Digest)?;
 
         let ctx = unsafe { cvt_p(EVP_MD_CTX_new())? };
 
@@ -313,6 +320,31 @@ impl Hasher {
         }
     }
 
+    /// Writes the hash of the data into the supplied buf and resets the non-XOF hasher.
+    ///
+    /// Returns the number of bytes written, which is the size of the digest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` is smaller than the size of the digest.
+    pub fn finish_into(&mut self, buf: &mut [u8]) -> Result<usize, ErrorStack> {
+        assert!(buf.len() >= self.type_.size());
+
+        if self.state == Finalized {
+            self.init()?;
+        }
+        unsafe {
+            let mut len = 0;
+            cvt(ffi::EVP_DigestFinal_ex(
+                self.ctx,
+                buf.as_mut_ptr(),
+                &mut len,
+            ))?;
+            self.state = Finalized;
+            Ok(len as usize)
+        }
+    }
+
     /// Writes the hash of the data into the supplied buf and resets the XOF hasher.
     /// The hash will be as long as the buf.
     #[cfg(ossl111)]
@@ -434,9 +466,40 @@ impl fmt::Debug for DigestBytes {
 /// # Ok(()) }
 /// ```
 pub fn hash(t: MessageDigest, data: &[u8]) -> Result<DigestBytes, ErrorStack> {
-    let mut h = Hasher::new(t)?;
-    h.update(data)?;
-    h.finish()
+    let mut buf = [0; ffi::EVP_MAX_MD_SIZE as usize];
+    let len = hash_into(t, data, &mut buf)?;
+    Ok(DigestBytes { buf, len })
+}
+
+/// Computes the hash of the `data` with the non-XOF hasher `t` and stores it in `buf`.
+///
+/// Returns the number of bytes written, which is the size of the digest.
+///
+/// # Panics
+///
+/// Panics if `buf` is smaller than the size of the digest.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use openssl::hash::{hash_into, MessageDigest};
+///
+/// let data = b"\x42\xF4\x97\xE0";
+/// let spec = b"\x7c\x43\x0f\x17\x8a\xef\xdf\x14\x87\xfe\xe7\x14\x4e\x96\x41\xe2";
+/// let mut buf = [0; 16];
+/// let len = hash_into(MessageDigest::md5(), data, &mut buf)?;
+/// assert_eq!(&buf[..len], spec);
+/// # Ok(()) }
+/// ```
+pub fn hash_into(t: MessageDigest, data: &[u8], buf: &mut [u8]) -> Result<usize, ErrorStack> {
+    assert!(buf.len() >= t.size());
+
+    thread_cache::with_md_ctx(|ctx| {
+        ctx.digest_init(unsafe { MdRef::from_ptr(t.as_ptr() as *mut _) })?;
+        ctx.digest_update(data)?;
+        ctx.digest_final(buf)
+    })
 }
 
 /// Computes the hash of the `data` with the XOF hasher `t` and stores it in `buf`.
@@ -470,6 +533,10 @@ mod tests {
     fn hash_test(hashtype: MessageDigest, hashtest: &(&str, &str)) {
         let res = hash(hashtype, &Vec::from_hex(hashtest.0).unwrap()).unwrap();
         assert_eq!(hex::encode(res), hashtest.1);
+
+        let mut buf = [0; ffi::EVP_MAX_MD_SIZE as usize];
+        let len = hash_into(hashtype, &Vec::from_hex(hashtest.0).unwrap(), &mut buf).unwrap();
+        assert_eq!(hex::encode(&buf[..len]), hashtest.1);
     }
 
     #[cfg(ossl111)]
@@ -0,0 +1,760 @@
+//! Hybrid Public Key Encryption.
+//!
+//! HPKE, specified in [RFC 9180], encrypts messages to the holder of a private key by combining a
+//! key encapsulation mechanism (KEM), a key derivation function (KDF), and an AEAD into a
+//! [`Suite`]. It is the building block of protocols such as TLS Encrypted Client Hello, MLS, and
+//! Oblivious HTTP.
+//!
+//! A [`Sender`] encapsulates a shared secret to the recipient's public key, and can then seal
+//! any number of messages with it. A [`Recipient`] decapsulates the secret with its private key,
+//! and opens the messages in the order they were sealed. [`Sender::seal_once`] and
+//! [`Recipient::open_once`] handle a single message, and [`seal`] and [`open`] do the same in the
+//! base mode.
+//!
+//! The [`Mode`] determines how the sender is authenticated: the PSK modes mix a pre-shared key
+//! into the shared secret, and the auth modes prove that the sender holds a private key.
+//!
+//! Requires OpenSSL 3.2.0 or newer.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::hpke::{Aead, Kdf, Kem, Mode, Recipient, Sender, Suite};
+//!
+//! let suite = Suite::new(Kem::DHKEM_X25519_HKDF_SHA256, Kdf::HKDF_SHA256, Aead::AES_128_GCM);
+//! let (public_key, private_key) = suite.generate_key().unwrap();
+//!
+//! let mut sender = Sender::new(Mode::BASE, suite).unwrap();
+//! let enc = sender.encap(&public_key, b"info").unwrap();
+//! let first = sender.seal(b"aad", b"first message").unwrap();
+//! let second = sender.seal(b"aad", b"second message").unwrap();
+//!
+//! let mut recipient = Recipient::new(Mode::BASE, suite).unwrap();
+//! recipient.decap(&enc, &private_key, b"info").unwrap();
+//! assert_eq!(recipient.open(b"aad", &first).unwrap(), b"first message");
+//! assert_eq!(recipient.open(b"aad", &second).unwrap(), b"second message");
+//! ```
+//!
+//! [RFC 9180]: https://www.rfc-editor.org/rfc/rfc9180
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::c_int;
+use openssl_macros::corresponds;
+use std::ffi::CString;
+use std::ptr;
+
+use crate::error::ErrorStack;
+use crate::pkey::{PKey, PKeyRef, Private};
+use crate::{cvt, cvt_p};
+
+/// A key encapsulation mechanism.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Kem(u16);
+
+impl Kem {
+    pub const DHKEM_P256_HKDF_SHA256: Kem = Kem(ffi::OSSL_HPKE_KEM_ID_P256);
+    pub const DHKEM_P384_HKDF_SHA384: Kem = Kem(ffi::OSSL_HPKE_KEM_ID_P384);
+    pub const DHKEM_P521_HKDF_SHA512: Kem = Kem(ffi::OSSL_HPKE_KEM_ID_P521);
+    pub const DHKEM_X25519_HKDF_SHA256: Kem = Kem(ffi::OSSL_HPKE_KEM_ID_X25519);
+    pub const DHKEM_X448_HKDF_SHA512: Kem = Kem(ffi::OSSL_HPKE_KEM_ID_X448);
+
+    /// Constructs a `Kem` from its IANA registered identifier.
+    pub const fn from_raw(raw: u16) -> Kem {
+        Kem(raw)
+    }
+
+    /// Returns the IANA registered identifier of the `Kem`.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub const fn as_raw(&self) -> u16 {
+        self.0
+    }
+}
+
+/// A key derivation function.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Kdf(u16);
+
+impl Kdf {
+    pub const HKDF_SHA256: Kdf = Kdf(ffi::OSSL_HPKE_KDF_ID_HKDF_SHA256);
+    pub const HKDF_SHA384: Kdf = Kdf(ffi::OSSL_HPKE_KDF_ID_HKDF_SHA384);
+    pub const HKDF_SHA512: Kdf = Kdf(ffi::OSSL_HPKE_KDF_ID_HKDF_SHA512);
+
+    /// Constructs a `Kdf` from its IANA registered identifier.
+    pub const fn from_raw(raw: u16) -> Kdf {
+        Kdf(raw)
+    }
+
+    /// Returns the IANA registered identifier of the `Kdf`.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub const fn as_raw(&self) -> u16 {
+        self.0
+    }
+}
+
+/// An authenticated encryption algorithm.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Aead(u16);
+
+impl Aead {
+    pub const AES_128_GCM: Aead = Aead(ffi::OSSL_HPKE_AEAD_ID_AES_GCM_128);
+    pub const AES_256_GCM: Aead = Aead(ffi::OSSL_HPKE_AEAD_ID_AES_GCM_256);
+    pub const CHACHA20_POLY1305: Aead = Aead(ffi::OSSL_HPKE_AEAD_ID_CHACHA_POLY1305);
+    /// No encryption, for contexts which are only used to export secrets.
+    pub const EXPORT_ONLY: Aead = Aead(ffi::OSSL_HPKE_AEAD_ID_EXPORTONLY);
+
+    /// Constructs an `Aead` from its IANA registered identifier.
+    pub const fn from_raw(raw: u16) -> Aead {
+        Aead(raw)
+    }
+
+    /// Returns the IANA registered identifier of the `Aead`.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub const fn as_raw(&self) -> u16 {
+        self.0
+    }
+}
+
+/// The mode of an HPKE exchange.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Mode(c_int);
+
+impl Mode {
+    /// The sender is not authenticated.
+    pub const BASE: Mode = Mode(ffi::OSSL_HPKE_MODE_BASE);
+    /// The sender is authenticated by a pre-shared key.
+    pub const PSK: Mode = Mode(ffi::OSSL_HPKE_MODE_PSK);
+    /// The sender is authenticated by a private key.
+    pub const AUTH: Mode = Mode(ffi::OSSL_HPKE_MODE_AUTH);
+    /// The sender is authenticated by both a pre-shared key and a private key.
+    pub const AUTH_PSK: Mode = Mode(ffi::OSSL_HPKE_MODE_PSKAUTH);
+}
+
+/// The combination of algorithms used by an HPKE exchange.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Suite {
+    kem: Kem,
+    kdf: Kdf,
+    aead: Aead,
+}
+
+impl Suite {
+    /// Creates a suite from its algorithms.
+    pub const fn new(kem: Kem, kdf: Kdf, aead: Aead) -> Suite {
+        Suite { kem, kdf, aead }
+    }
+
+    /// Parses a suite from a string such as `x25519,hkdf-sha256,aes-128-gcm`.
+    ///
+    /// The algorithms are named as in OpenSSL's documentation, or by their IANA registered
+    /// identifiers.
+    #[corresponds(OSSL_HPKE_str2suite)]
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Result<Suite, ErrorStack> {
+        let s = CString::new(s).unwrap();
+        unsafe {
+            ffi::init();
+            let mut suite = Suite::new(Kem(0), Kdf(0), Aead(0)).as_raw();
+            cvt(ffi::OSSL_HPKE_str2suite(s.as_ptr(), &mut suite))?;
+            Ok(Suite::new(
+                Kem(suite.kem_id),
+                Kdf(suite.kdf_id),
+                Aead(suite.aead_id),
+            ))
+        }
+    }
+
+    /// Returns the key encapsulation mechanism.
+    pub fn kem(&self) -> Kem {
+        self.kem
+    }
+
+    /// Returns the key derivation function.
+    pub fn kdf(&self) -> Kdf {
+        self.kdf
+    }
+
+    /// Returns the authenticated encryption algorithm.
+    pub fn aead(&self) -> Aead {
+        self.aead
+    }
+
+    /// Checks that the linked OpenSSL supports the suite.
+    #[corresponds(OSSL_HPKE_suite_check)]
+    pub fn check(&self) -> Result<(), ErrorStack> {
+        unsafe {
+            ffi::init();
+            cvt(ffi::OSSL_HPKE_suite_check(self.as_raw())).map(|_| ())
+        }
+    }
+
+    /// Returns the length of the encapsulated key, which is also the length of public keys.
+    ///
+    /// Returns 0 if the suite is not supported.
+    #[corresponds(OSSL_HPKE_get_public_encap_size)]
+    pub fn enc_len(&self) -> usize {
+        unsafe { ffi::OSSL_HPKE_get_public_encap_size(self.as_raw()) }
+    }
+
+    /// Returns the length of the ciphertext of a message of `plaintext_len` bytes.
+    ///
+    /// Returns 0 if the suite is not supported.
+    #[corresponds(OSSL_HPKE_get_ciphertext_size)]
+    pub fn ciphertext_len(&self, plaintext_len: usize) -> usize {
+        unsafe { ffi::OSSL_HPKE_get_ciphertext_size(self.as_raw(), plaintext_len) }
+    }
+
+    /// Returns the recommended length of the input keying material passed to
+    /// [`Suite::derive_key`].
+    #[corresponds(OSSL_HPKE_get_recommended_ikmelen)]
+    pub fn recommended_ikm_len(&self) -> usize {
+        unsafe { ffi::OSSL_HPKE_get_recommended_ikmelen(self.as_raw()) }
+    }
+
+    /// Generates a random key pair for the suite's KEM.
+    ///
+    /// Returns the encoded public key, which is passed to [`Sender::encap`], and the private
+    /// key.
+    #[corresponds(OSSL_HPKE_keygen)]
+    pub fn generate_key(&self) -> Result<(Vec<u8>, PKey<Private>), ErrorStack> {
+        self.keygen(&[])
+    }
+
+    /// Deterministically derives a key pair for the suite's KEM from input keying material.
+    ///
+    /// Returns the encoded public key and the private key.
+    #[corresponds(OSSL_HPKE_keygen)]
+    pub fn derive_key(&self, ikm: &[u8]) -> Result<(Vec<u8>, PKey<Private>), ErrorStack> {
+        self.keygen(ikm)
+    }
+
+    fn keygen(&self, ikm: &[u8]) -> Result<(Vec<u8>, PKey<Private>), ErrorStack> {
+        ffi::init();
+        let mut public_key = vec![0; self.enc_len()];
+        let mut len = public_key.len();
+        let mut private_key = ptr::null_mut();
+        // OpenSSL rejects a non-null pointer to empty input keying material
+        let ikm_ptr = if ikm.is_empty() {
+            ptr::null()
+        } else {
+            ikm.as_ptr()
+        };
+        unsafe {
+            cvt(ffi::OSSL_HPKE_keygen(
+                self.as_raw(),
+                public_key.as_mut_ptr(),
+                &mut len,
+                &mut private_key,
+                ikm_ptr,
+                ikm.len(),
+                ptr::null_mut(),
+                ptr::null(),
+            ))?;
+            let private_key = PKey::from_ptr(private_key);
+            public_key.truncate(len);
+            Ok((public_key, private_key))
+        }
+    }
+
+    fn as_raw(&self) -> ffi::OSSL_HPKE_SUITE {
+        ffi::OSSL_HPKE_SUITE {
+            kem_id: self.kem.0,
+            kdf_id: self.kdf.0,
+            aead_id: self.aead.0,
+        }
+    }
+}
+
+/// Encrypts a single message to a public key in the base mode.
+///
+/// Returns the encapsulated key and the ciphertext, which are both sent to the recipient.
+pub fn seal(
+    suite: Suite,
+    public_key: &[u8],
+    info: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), ErrorStack> {
+    Sender::new(Mode::BASE, suite)?.seal_once(public_key, info, aad, plaintext)
+}
+
+/// Decrypts a single message sealed in the base mode.
+pub fn open(
+    suite: Suite,
+    enc: &[u8],
+    private_key: &PKeyRef<Private>,
+    info: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, ErrorStack> {
+    Recipient::new(Mode::BASE, suite)?.open_once(enc, private_key, info, aad, ciphertext)
+}
+
+/// The sender's side of an HPKE exchange.
+pub struct Sender {
+    ctx: *mut ffi::OSSL_HPKE_CTX,
+    suite: Suite,
+}
+
+unsafe impl Sync for Sender {}
+unsafe impl Send for Sender {}
+
+impl Drop for Sender {
+    fn drop(&mut self) {
+        unsafe { ffi::OSSL_HPKE_CTX_free(self.ctx) }
+    }
+}
+
+impl Sender {
+    /// Creates a sender.
+    ///
+    /// The sender must be configured with the keys required by the mode before [`encap`] is
+    /// called.
+    ///
+    /// [`encap`]: Sender::encap
+    #[corresponds(OSSL_HPKE_CTX_new)]
+    pub fn new(mode: Mode, suite: Suite) -> Result<Sender, ErrorStack> {
+        let ctx = new_ctx(mode, suite, ffi::OSSL_HPKE_ROLE_SENDER)?;
+        Ok(Sender { ctx, suite })
+    }
+
+    /// Returns the sender's suite.
+    pub fn suite(&self) -> Suite {
+        self.suite
+    }
+
+    /// Sets the pre-shared key and its identifier, for the PSK modes.
+    #[corresponds(OSSL_HPKE_CTX_set1_psk)]
+    pub fn set_psk(&mut self, id: &str, psk: &[u8]) -> Result<(), ErrorStack> {
+        unsafe { set_psk(self.ctx, id, psk) }
+    }
+
+    /// Sets the sender's private key, for the auth modes.
+    ///
+    /// The recipient must be configured with the corresponding public key.
+    #[corresponds(OSSL_HPKE_CTX_set1_authpriv)]
+    pub fn set_auth_key(&mut self, key: &PKeyRef<Private>) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::OSSL_HPKE_CTX_set1_authpriv(self.ctx, key.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets the input keying material from which the ephemeral key is derived.
+    ///
+    /// By default the ephemeral key is random. A fixed ephemeral key is only intended for testing
+    /// against known answers.
+    #[corresponds(OSSL_HPKE_CTX_set1_ikme)]
+    pub fn set_ephemeral_ikm(&mut self, ikm: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OSSL_HPKE_CTX_set1_ikme(
+                self.ctx,
+                ikm.as_ptr(),
+                ikm.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Encapsulates a shared secret to the recipient's public key, returning the encapsulated key.
+    ///
+    /// `info` binds the shared secret to the application's context, and must be the same for
+    /// the recipient. This must be called once, before any messages are sealed.
+    #[corresponds(OSSL_HPKE_encap)]
+    pub fn encap(&mut self, public_key: &[u8], info: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        let mut enc = vec![0; self.suite.enc_len()];
+        let mut len = enc.len();
+        unsafe {
+            cvt(ffi::OSSL_HPKE_encap(
+                self.ctx,
+                enc.as_mut_ptr(),
+                &mut len,
+                public_key.as_ptr(),
+                public_key.len(),
+                info.as_ptr(),
+                info.len(),
+            ))?;
+        }
+        enc.truncate(len);
+        Ok(enc)
+    }
+
+    /// Encrypts and authenticates the next message, returning its ciphertext.
+    ///
+    /// `aad` is authenticated but not encrypted, and must be the same when the message is
+    /// opened.
+    #[corresponds(OSSL_HPKE_seal)]
+    pub fn seal(&mut self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        let mut ciphertext = vec![0; self.suite.ciphertext_len(plaintext.len())];
+        let mut len = ciphertext.len();
+        unsafe {
+            cvt(ffi::OSSL_HPKE_seal(
+                self.ctx,
+                ciphertext.as_mut_ptr(),
+                &mut len,
+                aad.as_ptr(),
+                aad.len(),
+                plaintext.as_ptr(),
+                plaintext.len(),
+            ))?;
+        }
+        ciphertext.truncate(len);
+        Ok(ciphertext)
+    }
+
+    /// Encapsulates a shared secret and seals a single message with it.
+    ///
+    /// Returns the encapsulated key and the ciphertext.
+    pub fn seal_once(
+        mut self,
+        public_key: &[u8],
+        info: &[u8],
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), ErrorStack> {
+        let enc = self.encap(public_key, info)?;
+        let ciphertext = self.seal(aad, plaintext)?;
+        Ok((enc, ciphertext))
+    }
+
+    /// Derives a secret of `secret.len()` bytes from the shared secret and `label`.
+    ///
+    /// The recipient derives the same secret from the same label.
+    #[corresponds(OSSL_HPKE_export)]
+    pub fn export(&mut self, label: &[u8], secret: &mut [u8]) -> Result<(), ErrorStack> {
+        unsafe { export(self.ctx, label, secret) }
+    }
+
+    /// Returns the sequence number of the next message.
+    #[corresponds(OSSL_HPKE_CTX_get_seq)]
+    pub fn seq(&self) -> Result<u64, ErrorStack> {
+        unsafe { seq(self.ctx) }
+    }
+
+    /// Sets the sequence number of the next message.
+    ///
+    /// Messages are numbered in the order they are sealed, and must be opened with the same
+    /// numbers. Reusing a sequence number for a different message breaks the security of the
+    /// AEAD.
+    #[corresponds(OSSL_HPKE_CTX_set_seq)]
+    pub fn set_seq(&mut self, seq: u64) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::OSSL_HPKE_CTX_set_seq(self.ctx, seq)).map(|_| ()) }
+    }
+}
+
+/// The recipient's side of an HPKE exchange.
+pub struct Recipient {
+    ctx: *mut ffi::OSSL_HPKE_CTX,
+    suite: Suite,
+}
+
+unsafe impl Sync for Recipient {}
+unsafe impl Send for Recipient {}
+
+impl Drop for Recipient {
+    fn drop(&mut self) {
+        unsafe { ffi::OSSL_HPKE_CTX_free(self.ctx) }
+    }
+}
+
+impl Recipient {
+    /// Creates a recipient.
+    ///
+    /// The recipient must be configured with the keys required by the mode before [`decap`] is
+    /// called.
+    ///
+    /// [`decap`]: Recipient::decap
+    #[corresponds(OSSL_HPKE_CTX_new)]
+    pub fn new(mode: Mode, suite: Suite) -> Result<Recipient, ErrorStack> {
+        let ctx = new_ctx(mode, suite, ffi::OSSL_HPKE_ROLE_RECEIVER)?;
+        Ok(Recipient { ctx, suite })
+    }
+
+    /// Returns the recipient's suite.
+    pub fn suite(&self) -> Suite {
+        self.suite
+    }
+
+    /// Sets the pre-shared key and its identifier, for the PSK modes.
+    #[corresponds(OSSL_HPKE_CTX_set1_psk)]
+    pub fn set_psk(&mut self, id: &str, psk: &[u8]) -> Result<(), ErrorStack> {
+        unsafe { set_psk(self.ctx, id, psk) }
+    }
+
+    /// Sets the sender's encoded public key, for the auth modes.
+    #[corresponds(OSSL_HPKE_CTX_set1_authpub)]
+    pub fn set_auth_public_key(&mut self, public_key: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OSSL_HPKE_CTX_set1_authpub(
+                self.ctx,
+                public_key.as_ptr(),
+                public_key.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Decapsulates the shared secret from the sender's encapsulated key.
+    ///
+    /// `info` must be the same as the sender's. This must be called once, before any messages
+    /// are opened.
+    #[corresponds(OSSL_HPKE_decap)]
+    pub fn decap(
+        &mut self,
+        enc: &[u8],
+        private_key: &PKeyRef<Private>,
+        info: &[u8],
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OSSL_HPKE_decap(
+                self.ctx,
+                enc.as_ptr(),
+                enc.len(),
+                private_key.as_ptr(),
+                info.as_ptr(),
+                info.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Decrypts and verifies the next message, returning its plaintext.
+    #[corresponds(OSSL_HPKE_open)]
+    pub fn open(&mut self, aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        let mut plaintext = vec![0; ciphertext.len()];
+        let mut len = plaintext.len();
+        unsafe {
+            cvt(ffi::OSSL_HPKE_open(
+                self.ctx,
+                plaintext.as_mut_ptr(),
+                &mut len,
+                aad.as_ptr(),
+                aad.len(),
+                ciphertext.as_ptr(),
+                ciphertext.len(),
+            ))?;
+        }
+        plaintext.truncate(len);
+        Ok(plaintext)
+    }
+
+    /// Decapsulates the shared secret and opens a single message with it.
+    pub fn open_once(
+        mut self,
+        enc: &[u8],
+        private_key: &PKeyRef<Private>,
+        info: &[u8],
+        aad: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, ErrorStack> {
+        self.decap(enc, private_key, info)?;
+        self.open(aad, ciphertext)
+    }
+
+    /// Derives a secret of `secret.len()` bytes from the shared secret and `label`.
+    #[corresponds(OSSL_HPKE_export)]
+    pub fn export(&mut self, label: &[u8], secret: &mut [u8]) -> Result<(), ErrorStack> {
+        unsafe { export(self.ctx, label, secret) }
+    }
+
+    /// Returns the sequence number of the next message.
+    #[corresponds(OSSL_HPKE_CTX_get_seq)]
+    pub fn seq(&self) -> Result<u64, ErrorStack> {
+        unsafe { seq(self.ctx) }
+    }
+
+    /// Sets the sequence number of the next message.
+    ///
+    /// This allows messages to be opened out of order, or after one was lost.
+    #[corresponds(OSSL_HPKE_CTX_set_seq)]
+    pub fn set_seq(&mut self, seq: u64) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::OSSL_HPKE_CTX_set_seq(self.ctx, seq)).map(|_| ()) }
+    }
+}
+
+fn new_ctx(mode: Mode, suite: Suite, role: c_int) -> Result<*mut ffi::OSSL_HPKE_CTX, ErrorStack> {
+    ffi::init();
+    unsafe {
+        cvt_p(ffi::OSSL_HPKE_CTX_new(
+            mode.0,
+            suite.as_raw(),
+            role,
+            ptr::null_mut(),
+            ptr::null(),
+        ))
+    }
+}
+
+unsafe fn set_psk(ctx: *mut ffi::OSSL_HPKE_CTX, id: &str, psk: &[u8]) -> Result<(), ErrorStack> {
+    let id = CString::new(id).unwrap();
+    cvt(ffi::OSSL_HPKE_CTX_set1_psk(
+        ctx,
+        id.as_ptr(),
+        psk.as_ptr(),
+        psk.len(),
+    ))
+    .map(|_| ())
+}
+
+unsafe fn export(
+    ctx: *mut ffi::OSSL_HPKE_CTX,
+    label: &[u8],
+    secret: &mut [u8],
+) -> Result<(), ErrorStack> {
+    cvt(ffi::OSSL_HPKE_export(
+        ctx,
+        secret.as_mut_ptr(),
+        secret.len(),
+        label.as_ptr(),
+        label.len(),
+    ))
+    .map(|_| ())
+}
+
+unsafe fn seq(ctx: *mut ffi::OSSL_HPKE_CTX) -> Result<u64, ErrorStack> {
+    let mut seq = 0;
+    cvt(ffi::OSSL_HPKE_CTX_get_seq(ctx, &mut seq))?;
+    Ok(seq)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUITES: &[Suite] = &[
+        Suite::new(
+            Kem::DHKEM_P256_HKDF_SHA256,
+            Kdf::HKDF_SHA256,
+            Aead::AES_128_GCM,
+        ),
+        Suite::new(
+            Kem::DHKEM_P384_HKDF_SHA384,
+            Kdf::HKDF_SHA384,
+            Aead::AES_256_GCM,
+        ),
+        Suite::new(
+            Kem::DHKEM_P521_HKDF_SHA512,
+            Kdf::HKDF_SHA512,
+            Aead::AES_256_GCM,
+        ),
+        Suite::new(
+            Kem::DHKEM_X25519_HKDF_SHA256,
+            Kdf::HKDF_SHA256,
+            Aead::CHACHA20_POLY1305,
+        ),
+        Suite::new(
+            Kem::DHKEM_X448_HKDF_SHA512,
+            Kdf::HKDF_SHA512,
+            Aead::CHACHA20_POLY1305,
+        ),
+    ];
+
+    const PSK: &[u8] = b"a pre-shared key of at least 32 bytes";
+
+    fn exchange(mode: Mode, suite: Suite) {
+        let (public_key, private_key) = suite.generate_key().unwrap();
+        let (auth_public_key, auth_key) = suite.generate_key().unwrap();
+
+        let mut sender = Sender::new(mode, suite).unwrap();
+        let mut recipient = Recipient::new(mode, suite).unwrap();
+        if mode == Mode::PSK || mode == Mode::AUTH_PSK {
+            sender.set_psk("id", PSK).unwrap();
+            recipient.set_psk("id", PSK).unwrap();
+        }
+        if mode == Mode::AUTH || mode == Mode::AUTH_PSK {
+            sender.set_auth_key(&auth_key).unwrap();
+            recipient.set_auth_public_key(&auth_public_key).unwrap();
+        }
+
+        let enc = sender.encap(&public_key, b"info").unwrap();
+        assert_eq!(enc.len(), suite.enc_len());
+        let first = sender.seal(b"aad", b"first").unwrap();
+        assert_eq!(first.len(), suite.ciphertext_len(5));
+        let second = sender.seal(b"", b"second").unwrap();
+        assert_eq!(sender.seq().unwrap(), 2);
+
+        recipient.decap(&enc, &private_key, b"info").unwrap();
+        assert!(recipient.open(b"wrong", &first).is_err());
+        assert_eq!(recipient.open(b"aad", &first).unwrap(), b"first");
+        assert_eq!(recipient.open(b"", &second).unwrap(), b"second");
+
+        let mut sender_secret = [0; 32];
+        sender.export(b"label", &mut sender_secret).unwrap();
+        let mut recipient_secret = [1; 32];
+        recipient.export(b"label", &mut recipient_secret).unwrap();
+        assert_eq!(sender_secret, recipient_secret);
+    }
+
+    #[test]
+    fn modes() {
+        for suite in SUITES {
+            suite.check().unwrap();
+            for mode in &[Mode::BASE, Mode::PSK, Mode::AUTH, Mode::AUTH_PSK] {
+                exchange(*mode, *suite);
+            }
+        }
+    }
+
+    #[test]
+    fn single_shot() {
+        let suite = SUITES[0];
+        let (public_key, private_key) = suite.generate_key().unwrap();
+
+        let (enc, ciphertext) = seal(suite, &public_key, b"info", b"aad", b"message").unwrap();
+        let plaintext = open(suite, &enc, &private_key, b"info", b"aad", &ciphertext).unwrap();
+        assert_eq!(plaintext, b"message");
+
+        assert!(open(suite, &enc, &private_key, b"other", b"aad", &ciphertext).is_err());
+    }
+
+    #[test]
+    fn sequence() {
+        let suite = SUITES[3];
+        let (public_key, private_key) = suite.generate_key().unwrap();
+
+        let mut sender = Sender::new(Mode::BASE, suite).unwrap();
+        let enc = sender.encap(&public_key, b"").unwrap();
+        let first = sender.seal(b"", b"first").unwrap();
+        let second = sender.seal(b"", b"second").unwrap();
+
+        let mut recipient = Recipient::new(Mode::BASE, suite).unwrap();
+        recipient.decap(&enc, &private_key, b"").unwrap();
+        recipient.set_seq(1).unwrap();
+        assert_eq!(recipient.open(b"", &second).unwrap(), b"second");
+        recipient.set_seq(0).unwrap();
+        assert_eq!(recipient.open(b"", &first).unwrap(), b"first");
+        assert_eq!(recipient.seq().unwrap(), 1);
+    }
+
+    #[test]
+    fn derived_keys() {
+        let suite = SUITES[3];
+        let ikm = vec![7; suite.recommended_ikm_len()];
+        let (public_key, _) = suite.derive_key(&ikm).unwrap();
+        let (other, _) = suite.derive_key(&ikm).unwrap();
+        assert_eq!(public_key, other);
+
+        let mut first = Sender::new(Mode::BASE, suite).unwrap();
+        first.set_ephemeral_ikm(&ikm).unwrap();
+        let mut second = Sender::new(Mode::BASE, suite).unwrap();
+        second.set_ephemeral_ikm(&ikm).unwrap();
+        assert_eq!(
+            first.encap(&public_key, b"").unwrap(),
+            second.encap(&public_key, b"").unwrap()
+        );
+    }
+
+    #[test]
+    fn suites() {
+        let suite = Suite::from_str("x25519,hkdf-sha256,aes-128-gcm").unwrap();
+        assert_eq!(
+            suite,
+            Suite::new(
+                Kem::DHKEM_X25519_HKDF_SHA256,
+                Kdf::HKDF_SHA256,
+                Aead::AES_128_GCM
+            )
+        );
+        assert!(Suite::from_str("x25519,hkdf-sha256").is_err());
+        assert!(
+            Suite::new(Kem::from_raw(0x7f), Kdf::HKDF_SHA256, Aead::AES_128_GCM)
+                .check()
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,546 @@
+//! JSON Web Key import and export.
+//!
+//! This module is only available when the `jwk` Cargo feature is enabled.
+//!
+//! [`PKey`]s can be converted to and from JSON Web Keys as defined in [RFC 7517]. RSA keys, EC keys on the P-256,
+//! P-384, P-521 and secp256k1 curves ([RFC 7518]), and Ed25519, Ed448, X25519 and X448 keys ([RFC 8037]) are
+//! supported. Members other than the key material, such as `kid` or `use`, are ignored on import and not emitted on
+//! export.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::pkey::{PKey, Public};
+//!
+//! let jwk = r#"{
+//!     "kty": "EC",
+//!     "crv": "P-256",
+//!     "x": "MKBCTNIcKUSDii11ySs3526iDZ8AiTo7Tu6KPAqv7D4",
+//!     "y": "4Etl6SRW2YiLUrN5vfvVHuhp7x8PxltmWWlbbM4IFyM"
+//! }"#;
+//! let key = PKey::<Public>::public_key_from_jwk(jwk).unwrap();
+//! assert_eq!(key.bits(), 256);
+//!
+//! let exported = key.public_key_to_jwk().unwrap();
+//! assert_eq!(exported["kty"], "EC");
+//! ```
+//!
+//! [RFC 7517]: https://datatracker.ietf.org/doc/html/rfc7517
+//! [RFC 7518]: https://datatracker.ietf.org/doc/html/rfc7518#section-6
+//! [RFC 8037]: https://datatracker.ietf.org/doc/html/rfc8037
+use serde_json::{Map, Value};
+
+use crate::base64;
+use crate::bn::{BigNum, BigNumContext, BigNumRef};
+use crate::ec::{EcGroup, EcGroupRef, EcKey};
+use crate::nid::Nid;
+#[cfg(any(ossl111, boringssl, libressl370))]
+use crate::okp;
+use crate::pkey::{HasPrivate, HasPublic, Id, PKey, PKeyRef, Private, Public};
+use crate::rsa::{Rsa, RsaPrivateKeyBuilder};
+
+invalid_input_error! {
+    /// An error converting a key to or from a JSON Web Key.
+    pub struct JwkError => "invalid JWK: {}";
+    Json(serde_json::Error) => "invalid JSON: {}";
+}
+
+impl PKey<Private> {
+    /// Deserializes a private key from a JSON Web Key.
+    ///
+    /// RSA keys without the `p` and `q` members are supported, but are limited to the slower non-CRT private key
+    /// operations. If `p` and `q` are present but the CRT parameters are not, they are computed.
+    pub fn private_key_from_jwk(jwk: &str) -> Result<PKey<Private>, JwkError> {
+        let jwk = serde_json::from_str::<Value>(jwk)?;
+        PKey::private_key_from_jwk_value(&jwk)
+    }
+
+    /// Like [`private_key_from_jwk`], but takes an already parsed JSON value.
+    ///
+    /// [`private_key_from_jwk`]: PKey::private_key_from_jwk
+    pub fn private_key_from_jwk_value(jwk: &Value) -> Result<PKey<Private>, JwkError> {
+        let jwk = object(jwk)?;
+        match required(jwk, "kty")? {
+            "RSA" => rsa_private_key(jwk),
+            "EC" => ec_private_key(jwk),
+            "OKP" => okp_private_key(jwk),
+            kty => Err(JwkError::invalid(format!("unsupported key type `{}`", kty))),
+        }
+    }
+}
+
+impl PKey<Public> {
+    /// Deserializes a public key from a JSON Web Key.
+    ///
+    /// Private key members are ignored, so the public half of a private JSON Web Key can be loaded with this method.
+    pub fn public_key_from_jwk(jwk: &str) -> Result<PKey<Public>, JwkError> {
+        let jwk = serde_json::from_str::<Value>(jwk)?;
+        PKey::public_key_from_jwk_value(&jwk)
+    }
+
+    /// Like [`public_key_from_jwk`], but takes an already parsed JSON value.
+    ///
+    /// [`public_key_from_jwk`]: PKey::public_key_from_jwk
+    pub fn public_key_from_jwk_value(jwk: &Value) -> Result<PKey<Public>, JwkError> {
+        let jwk = object(jwk)?;
+        match required(jwk, "kty")? {
+            "RSA" => rsa_public_key(jwk),
+            "EC" => ec_public_key(jwk),
+            "OKP" => okp_public_key(jwk),
+            kty => Err(JwkError::invalid(format!("unsupported key type `{}`", kty))),
+        }
+    }
+}
+
+impl<T> PKeyRef<T>
+where
+    T: HasPublic,
+{
+    /// Serializes the public key as a JSON Web Key.
+    ///
+    /// Returns an error if the key type cannot be represented as a JSON Web Key.
+    pub fn public_key_to_jwk(&self) -> Result<Value, JwkError> {
+        let mut jwk = Map::new();
+        match self.id() {
+            Id::RSA => {
+                let rsa = self.rsa()?;
+                jwk.insert("kty".into(), "RSA".into());
+                jwk.insert("n".into(), encode_bn(rsa.n()).into());
+                jwk.insert("e".into(), encode_bn(rsa.e()).into());
+            }
+            Id::EC => {
+                let ec_key = self.ec_key()?;
+                let group = ec_key.group();
+                let mut x = BigNum::new()?;
+                let mut y = BigNum::new()?;
+                let mut ctx = BigNumContext::new()?;
+                ec_key
+                    .public_key()
+                    .affine_coordinates_gfp(group, &mut x, &mut y, &mut ctx)?;
+                let len = group.field_len();
+                jwk.insert("kty".into(), "EC".into());
+                jwk.insert("crv".into(), curve_name(group)?.into());
+                jwk.insert("x".into(), encode_bn_padded(&x, len)?.into());
+                jwk.insert("y".into(), encode_bn_padded(&y, len)?.into());
+            }
+            #[cfg(any(ossl111, boringssl, libressl370))]
+            id => {
+                let crv = okp_curve_name(id)?;
+                jwk.insert("kty".into(), "OKP".into());
+                jwk.insert("crv".into(), crv.into());
+                jwk.insert("x".into(), encode(&self.raw_public_key()?).into());
+            }
+            #[cfg(not(any(ossl111, boringssl, libressl370)))]
+            _ => return Err(JwkError::invalid("unsupported key type")),
+        }
+        Ok(Value::Object(jwk))
+    }
+}
+
+impl<T> PKeyRef<T>
+where
+    T: HasPrivate,
+{
+    /// Serializes the private key as a JSON Web Key.
+    ///
+    /// The result includes the public key members. RSA CRT parameters are only emitted if the key has them.
+    pub fn private_key_to_jwk(&self) -> Result<Value, JwkError> {
+        let mut jwk = match self.public_key_to_jwk()? {
+            Value::Object(jwk) => jwk,
+            _ => unreachable!(),
+        };
+        match self.id() {
+            Id::RSA => {
+                let rsa = self.rsa()?;
+                jwk.insert("d".into(), encode_bn(rsa.d()).into());
+                let params = [
+                    ("p", rsa.p()),
+                    ("q", rsa.q()),
+                    ("dp", rsa.dmp1()),
+                    ("dq", rsa.dmq1()),
+                    ("qi", rsa.iqmp()),
+                ];
+                for &(name, param) in &params {
+                    if let Some(param) = param {
+                        jwk.insert(name.into(), encode_bn(param).into());
+                    }
+                }
+            }
+            Id::EC => {
+                let ec_key = self.ec_key()?;
+                let len = ec_key.group().field_len();
+                jwk.insert(
+                    "d".into(),
+                    encode_bn_padded(ec_key.private_key(), len)?.into(),
+                );
+            }
+            #[cfg(any(ossl111, boringssl, libressl370))]
+            _ => {
+                jwk.insert("d".into(), encode(&self.raw_private_key()?).into());
+            }
+            #[cfg(not(any(ossl111, boringssl, libressl370)))]
+            _ => unreachable!(),
+        }
+        Ok(Value::Object(jwk))
+    }
+}
+
+fn rsa_public_key(jwk: &Map<String, Value>) -> Result<PKey<Public>, JwkError> {
+    let n = required_bn(jwk, "n")?;
+    let e = required_bn(jwk, "e")?;
+    let rsa = Rsa::from_public_components(n, e)?;
+    Ok(PKey::from_rsa(rsa)?)
+}
+
+fn rsa_private_key(jwk: &Map<String, Value>) -> Result<PKey<Private>, JwkError> {
+    if jwk.contains_key("oth") {
+        return Err(JwkError::invalid(
+            "RSA keys with more than two primes are not supported",
+        ));
+    }
+
+    let n = required_bn(jwk, "n")?;
+    let e = required_bn(jwk, "e")?;
+    let d = required_bn(jwk, "d")?;
+    let mut builder = RsaPrivateKeyBuilder::new(n, e, d)?;
+
+    let factors = (optional_bn(jwk, "p")?, optional_bn(jwk, "q")?);
+    let check = match factors {
+        (Some(p), Some(q)) => {
+            builder = builder.set_factors(p, q)?;
+            let crt_params = (
+                optional_bn(jwk, "dp")?,
+                optional_bn(jwk, "dq")?,
+                optional_bn(jwk, "qi")?,
+            );
+            builder = match crt_params {
+                (Some(dp), Some(dq), Some(qi)) => builder.set_crt_params(dp, dq, qi)?,
+                (None, None, None) => builder.compute_crt_params()?,
+                _ => {
+                    return Err(JwkError::invalid(
+                        "`dp`, `dq` and `qi` must be present together",
+                    ))
+                }
+            };
+            true
+        }
+        (None, None) => false,
+        _ => return Err(JwkError::invalid("`p` and `q` must be present together")),
+    };
+
+    let rsa = builder.build();
+    if check {
+        rsa.validate()?;
+    }
+    Ok(PKey::from_rsa(rsa)?)
+}
+
+fn ec_public_key(jwk: &Map<String, Value>) -> Result<PKey<Public>, JwkError> {
+    let group = ec_group(jwk)?;
+    let ec_key = ec_public_components(jwk, &group)?;
+    Ok(PKey::from_ec_key(ec_key)?)
+}
+
+fn ec_private_key(jwk: &Map<String, Value>) -> Result<PKey<Private>, JwkError> {
+    let group = ec_group(jwk)?;
+    let public = ec_public_components(jwk, &group)?;
+    let d = decode_padded(jwk, "d", group.field_len())?;
+    let d = BigNum::from_slice(&d)?;
+    let ec_key = EcKey::from_private_components(&group, &d, public.public_key())?;
+    ec_key.check_key()?;
+    Ok(PKey::from_ec_key(ec_key)?)
+}
+
+fn ec_group(jwk: &Map<String, Value>) -> Result<EcGroup, JwkError> {
+    let nid = match required(jwk, "crv")? {
+        "P-256" => Nid::X9_62_PRIME256V1,
+        "P-384" => Nid::SECP384R1,
+        "P-521" => Nid::SECP521R1,
+        "secp256k1" => Nid::SECP256K1,
+        crv => return Err(JwkError::invalid(format!("unsupported curve `{}`", crv))),
+    };
+    Ok(EcGroup::from_curve_name(nid)?)
+}
+
+fn ec_public_components(
+    jwk: &Map<String, Value>,
+    group: &EcGroupRef,
+) -> Result<EcKey<Public>, JwkError> {
+    let len = group.field_len();
+    let x = BigNum::from_slice(&decode_padded(jwk, "x", len)?)?;
+    let y = BigNum::from_slice(&decode_padded(jwk, "y", len)?)?;
+    Ok(EcKey::from_public_key_affine_coordinates(group, &x, &y)?)
+}
+
+fn curve_name(group: &EcGroupRef) -> Result<&'static str, JwkError> {
+    match group.curve_name() {
+        Some(Nid::X9_62_PRIME256V1) => Ok("P-256"),
+        Some(Nid::SECP384R1) => Ok("P-384"),
+        Some(Nid::SECP521R1) => Ok("P-521"),
+        Some(Nid::SECP256K1) => Ok("secp256k1"),
+        _ => Err(JwkError::invalid("unsupported curve")),
+    }
+}
+
+#[cfg(any(ossl111, boringssl, libressl370))]
+fn okp_id(jwk: &Map<String, Value>) -> Result<Id, JwkError> {
+    let crv = required(jwk, "crv")?;
+    okp::from_jwk(crv)
+        .map(|c| c.id)
+        .ok_or_else(|| JwkError::invalid(format!("unsupported curve `{}`", crv)))
+}
+
+#[cfg(any(ossl111, boringssl, libressl370))]
+fn okp_curve_name(id: Id) -> Result<&'static str, JwkError> {
+    okp::from_id(id)
+        .map(|c| c.jwk)
+        .ok_or_else(|| JwkError::invalid("unsupported key type"))
+}
+
+#[cfg(any(ossl111, boringssl, libressl370))]
+fn okp_public_key(jwk: &Map<String, Value>) -> Result<PKey<Public>, JwkError> {
+    let id = okp_id(jwk)?;
+    let x = decode(required(jwk, "x")?)?;
+    Ok(PKey::public_key_from_raw_bytes(&x, id)?)
+}
+
+#[cfg(any(ossl111, boringssl, libressl370))]
+fn okp_private_key(jwk: &Map<String, Value>) -> Result<PKey<Private>, JwkError> {
+    let id = okp_id(jwk)?;
+    let d = decode(required(jwk, "d")?)?;
+    let key = PKey::private_key_from_raw_bytes(&d, id)?;
+    if let Some(x) = optional(jwk, "x")? {
+        if decode(x)? != key.raw_public_key()? {
+            return Err(JwkError::invalid("`x` does not match `d`"));
+        }
+    }
+    Ok(key)
+}
+
+#[cfg(not(any(ossl111, boringssl, libressl370)))]
+fn okp_public_key(_: &Map<String, Value>) -> Result<PKey<Public>, JwkError> {
+    Err(JwkError::invalid("unsupported key type `OKP`"))
+}
+
+#[cfg(not(any(ossl111, boringssl, libressl370)))]
+fn okp_private_key(_: &Map<String, Value>) -> Result<PKey<Private>, JwkError> {
+    Err(JwkError::invalid("unsupported key type `OKP`"))
+}
+
+fn object(jwk: &Value) -> Result<&Map<String, Value>, JwkError> {
+    jwk.as_object()
+        .ok_or_else(|| JwkError::invalid("expected a JSON object"))
+}
+
+fn optional<'a>(jwk: &'a Map<String, Value>, name: &str) -> Result<Option<&'a str>, JwkError> {
+    match jwk.get(name) {
+        Some(Value::String(value)) => Ok(Some(value)),
+        Some(_) => Err(JwkError::invalid(format!("`{}` must be a string", name))),
+        None => Ok(None),
+    }
+}
+
+fn required<'a>(jwk: &'a Map<String, Value>, name: &str) -> Result<&'a str, JwkError> {
+    optional(jwk, name)?.ok_or_else(|| JwkError::invalid(format!("missing `{}`", name)))
+}
+
+fn optional_bn(jwk: &Map<String, Value>, name: &str) -> Result<Option<BigNum>, JwkError> {
+    match optional(jwk, name)? {
+        Some(value) => Ok(Some(BigNum::from_slice(&decode(value)?)?)),
+        None => Ok(None),
+    }
+}
+
+fn required_bn(jwk: &Map<String, Value>, name: &str) -> Result<BigNum, JwkError> {
+    Ok(BigNum::from_slice(&decode(required(jwk, name)?)?)?)
+}
+
+fn decode_padded(jwk: &Map<String, Value>, name: &str, len: usize) -> Result<Vec<u8>, JwkError> {
+    let value = decode(required(jwk, name)?)?;
+    if value.len() != len {
+        return Err(JwkError::invalid(format!(
+            "`{}` must be {} bytes long",
+            name, len
+        )));
+    }
+    Ok(value)
+}
+
+fn encode_bn(bn: &BigNumRef) -> String {
+    encode(&bn.to_vec())
+}
+
+fn encode_bn_padded(bn: &BigNumRef, len: usize) -> Result<String, JwkError> {
+    let bytes = bn.to_vec();
+    if bytes.len() > len {
+        return Err(JwkError::invalid("value too large for the curve"));
+    }
+    let mut padded = vec![0; len - bytes.len()];
+    padded.extend_from_slice(&bytes);
+    Ok(encode(&padded))
+}
+
+/// Encodes bytes as unpadded base64url.
+fn encode(bytes: &[u8]) -> String {
+    base64::encode_block(bytes)
+        .trim_end_matches('=')
+        .chars()
+        .map(|c| match c {
+            '+' => '-',
+            '/' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// Decodes unpadded base64url.
+fn decode(s: &str) -> Result<Vec<u8>, JwkError> {
+    if s.len() % 4 == 1
+        || !s
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+    {
+        return Err(JwkError::invalid("invalid base64url encoding"));
+    }
+
+    let mut translated = s
+        .chars()
+        .map(|c| match c {
+            '-' => '+',
+            '_' => '/',
+            c => c,
+        })
+        .collect::<String>();
+    while translated.len() % 4 != 0 {
+        translated.push('=');
+    }
+    Ok(base64::decode_block(&translated)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ec::EcGroup;
+
+    // RFC 7517 appendix A.2
+    const EC_JWK: &str = r#"{
+        "kty": "EC",
+        "crv": "P-256",
+        "x": "MKBCTNIcKUSDii11ySs3526iDZ8AiTo7Tu6KPAqv7D4",
+        "y": "4Etl6SRW2YiLUrN5vfvVHuhp7x8PxltmWWlbbM4IFyM",
+        "d": "870MB6gfuTJ4HtUnUvYMyJpr5eUZNP4Bk43bVdj3eAE",
+        "use": "enc",
+        "kid": "1"
+    }"#;
+
+    // RFC 8037 appendix A.1
+    #[cfg(any(ossl111, boringssl, libressl370))]
+    const ED25519_JWK: &str = r#"{
+        "kty": "OKP",
+        "crv": "Ed25519",
+        "d": "nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A",
+        "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"
+    }"#;
+
+    #[test]
+    fn ec_vector() {
+        let key = PKey::private_key_from_jwk(EC_JWK).unwrap();
+        assert_eq!(key.id(), Id::EC);
+
+        let expected = serde_json::from_str::<Value>(EC_JWK).unwrap();
+        let jwk = key.private_key_to_jwk().unwrap();
+        for &name in &["kty", "crv", "x", "y", "d"] {
+            assert_eq!(jwk[name], expected[name]);
+        }
+        assert!(jwk.get("kid").is_none());
+
+        let public = PKey::public_key_from_jwk(EC_JWK).unwrap();
+        assert!(public.public_eq(&key));
+        assert!(public.public_key_to_jwk().unwrap().get("d").is_none());
+    }
+
+    #[test]
+    fn ec_round_trip() {
+        for &nid in &[Nid::SECP384R1, Nid::SECP521R1] {
+            let group = EcGroup::from_curve_name(nid).unwrap();
+            let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+            let jwk = key.private_key_to_jwk().unwrap();
+            let decoded = PKey::private_key_from_jwk_value(&jwk).unwrap();
+            assert!(decoded.public_eq(&key));
+            assert_eq!(
+                decoded.ec_key().unwrap().private_key(),
+                key.ec_key().unwrap().private_key()
+            );
+        }
+    }
+
+    #[test]
+    fn ec_mismatched_private_key() {
+        let mut jwk = serde_json::from_str::<Value>(EC_JWK).unwrap();
+        jwk["d"] = "AAAAB6gfuTJ4HtUnUvYMyJpr5eUZNP4Bk43bVdj3eAE".into();
+        PKey::private_key_from_jwk_value(&jwk).unwrap_err();
+    }
+
+    #[test]
+    fn rsa_round_trip() {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+
+        let jwk = key.private_key_to_jwk().unwrap();
+        assert_eq!(jwk["kty"], "RSA");
+        assert_eq!(jwk["e"], "AQAB");
+        let decoded = PKey::private_key_from_jwk(&jwk.to_string()).unwrap();
+        assert!(decoded.public_eq(&key));
+        assert_eq!(decoded.rsa().unwrap().d(), key.rsa().unwrap().d());
+
+        let public = key.public_key_to_jwk().unwrap();
+        assert_eq!(public.as_object().unwrap().len(), 3);
+        let decoded = PKey::public_key_from_jwk_value(&public).unwrap();
+        assert!(decoded.public_eq(&key));
+    }
+
+    #[test]
+    fn rsa_missing_crt_params() {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut jwk = key.private_key_to_jwk().unwrap();
+        let members = jwk.as_object_mut().unwrap();
+        for name in &["dp", "dq", "qi"] {
+            members.remove(*name);
+        }
+
+        let decoded = PKey::private_key_from_jwk_value(&jwk).unwrap();
+        let rsa = decoded.rsa().unwrap();
+        assert_eq!(rsa.iqmp(), key.rsa().unwrap().iqmp());
+
+        for name in &["p", "q"] {
+            jwk.as_object_mut().unwrap().remove(*name);
+        }
+        let decoded = PKey::private_key_from_jwk_value(&jwk).unwrap();
+        assert!(decoded.rsa().unwrap().p().is_none());
+    }
+
+    #[test]
+    #[cfg(any(ossl111, boringssl, libressl370))]
+    fn okp_vector() {
+        let key = PKey::private_key_from_jwk(ED25519_JWK).unwrap();
+        assert_eq!(key.id(), Id::ED25519);
+
+        let expected = serde_json::from_str::<Value>(ED25519_JWK).unwrap();
+        assert_eq!(key.private_key_to_jwk().unwrap(), expected);
+
+        let public = PKey::public_key_from_jwk(ED25519_JWK).unwrap();
+        let jwk = public.public_key_to_jwk().unwrap();
+        assert_eq!(jwk["x"], expected["x"]);
+        assert!(jwk.get("d").is_none());
+    }
+
+    #[test]
+    fn invalid() {
+        PKey::public_key_from_jwk("[]").unwrap_err();
+        PKey::public_key_from_jwk(r#"{"kty": "oct", "k": "AAAA"}"#).unwrap_err();
+        PKey::public_key_from_jwk(r#"{"kty": "RSA", "n": "AQAB"}"#).unwrap_err();
+        PKey::public_key_from_jwk(r#"{"kty": "RSA", "n": "AQ+B", "e": "AQAB"}"#).unwrap_err();
+        PKey::public_key_from_jwk(r#"{"kty": "EC", "crv": "P-256", "x": "AQAB", "y": "AQAB"}"#)
+            .unwrap_err();
+    }
+}
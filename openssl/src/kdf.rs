@@ -0,0 +1,710 @@
+//! Key derivation functions.
+//!
+//! Most of these are built on OpenSSL's `EVP_KDF` API, and require OpenSSL 3.0.0 or newer. The
+//! HKDF functions are also available on OpenSSL 1.1.0 or newer, where they use an HKDF
+//! [`PkeyCtx`] before 3.0.0, and on BoringSSL, where they use its `HKDF` functions.
+//!
+//! # Examples
+//!
+//! Derive a key with HKDF:
+//!
+//! ```
+//! use openssl::kdf::hkdf;
+//! use openssl::md::Md;
+//!
+//! let mut key = [0; 32];
+//! hkdf(Md::sha256(), b"secret", b"salt", b"info", &mut key).unwrap();
+//! ```
+//!
+//! [`PkeyCtx`]: crate::pkey_ctx::PkeyCtx
+use cfg_if::cfg_if;
+#[cfg(ossl300)]
+use libc::{c_char, c_int, c_uint, c_void};
+#[cfg(ossl300)]
+use std::ffi::CStr;
+#[cfg(ossl300)]
+use std::marker::PhantomData;
+#[cfg(ossl300)]
+use std::ptr;
+
+#[cfg(boringssl)]
+use crate::cvt;
+use crate::error::ErrorStack;
+use crate::md::MdRef;
+#[cfg(not(any(ossl300, boringssl)))]
+use crate::pkey::Id;
+#[cfg(all(ossl111, not(ossl300)))]
+use crate::pkey_ctx::HkdfMode;
+#[cfg(not(any(ossl300, boringssl)))]
+use crate::pkey_ctx::PkeyCtx;
+#[cfg(ossl300)]
+use crate::{cvt, cvt_p};
+#[cfg(any(ossl300, boringssl))]
+use foreign_types::ForeignTypeRef;
+use openssl_macros::corresponds;
+
+#[cfg(ossl300)]
+struct EvpKdf(*mut ffi::EVP_KDF);
+
+#[cfg(ossl300)]
+impl Drop for EvpKdf {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::EVP_KDF_free(self.0);
+        }
+    }
+}
+
+#[cfg(ossl300)]
+struct EvpKdfCtx(*mut ffi::EVP_KDF_CTX);
+
+#[cfg(ossl300)]
+impl Drop for EvpKdfCtx {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::EVP_KDF_CTX_free(self.0);
+        }
+    }
+}
+
+#[cfg(ossl300)]
+/// A list of `OSSL_PARAM`s borrowing the values they point to.
+///
+/// Keys must be NUL-terminated.
+pub(crate) struct ParamBuilder<'a> {
+    params: Vec<ffi::OSSL_PARAM>,
+    _p: PhantomData<&'a ()>,
+}
+
+#[cfg(ossl300)]
+impl<'a> ParamBuilder<'a> {
+    pub(crate) fn new() -> ParamBuilder<'a> {
+        ParamBuilder {
+            params: vec![],
+            _p: PhantomData,
+        }
+    }
+
+    pub(crate) fn octet_string(
+        &mut self,
+        key: &'static [u8],
+        value: &'a [u8],
+    ) -> &mut ParamBuilder<'a> {
+        debug_assert_eq!(key.last(), Some(&0));
+        unsafe {
+            self.params.push(ffi::OSSL_PARAM_construct_octet_string(
+                key.as_ptr() as *const c_char,
+                value.as_ptr() as *mut c_void,
+                value.len(),
+            ));
+        }
+        self
+    }
+
+    fn utf8_string(&mut self, key: &'static [u8], value: &'a CStr) -> &mut ParamBuilder<'a> {
+        debug_assert_eq!(key.last(), Some(&0));
+        unsafe {
+            self.params.push(ffi::OSSL_PARAM_construct_utf8_string(
+                key.as_ptr() as *const c_char,
+                value.as_ptr() as *mut c_char,
+                0,
+            ));
+        }
+        self
+    }
+
+    fn int(&mut self, key: &'static [u8], value: &'a c_int) -> &mut ParamBuilder<'a> {
+        debug_assert_eq!(key.last(), Some(&0));
+        unsafe {
+            self.params.push(ffi::OSSL_PARAM_construct_int(
+                key.as_ptr() as *const c_char,
+                value as *const c_int as *mut c_int,
+            ));
+        }
+        self
+    }
+
+    pub(crate) fn uint(&mut self, key: &'static [u8], value: &'a c_uint) -> &mut ParamBuilder<'a> {
+        debug_assert_eq!(key.last(), Some(&0));
+        unsafe {
+            self.params.push(ffi::OSSL_PARAM_construct_uint(
+                key.as_ptr() as *const c_char,
+                value as *const c_uint as *mut c_uint,
+            ));
+        }
+        self
+    }
+
+    fn digest(&mut self, md: &'a MdRef) -> &mut ParamBuilder<'a> {
+        let name = unsafe { CStr::from_ptr(ffi::EVP_MD_get0_name(md.as_ptr())) };
+        self.utf8_string(b"digest\0", name)
+    }
+
+    pub(crate) fn build(mut self) -> Vec<ffi::OSSL_PARAM> {
+        unsafe {
+            self.params.push(ffi::OSSL_PARAM_construct_end());
+        }
+        self.params
+    }
+}
+
+#[cfg(ossl300)]
+/// Runs the named KDF with the provided parameters, filling `out`.
+fn derive(algorithm: &CStr, params: ParamBuilder<'_>, out: &mut [u8]) -> Result<(), ErrorStack> {
+    ffi::init();
+
+    unsafe {
+        let kdf = EvpKdf(cvt_p(ffi::EVP_KDF_fetch(
+            ptr::null_mut(),
+            algorithm.as_ptr(),
+            ptr::null(),
+        ))?);
+        let ctx = EvpKdfCtx(cvt_p(ffi::EVP_KDF_CTX_new(kdf.0))?);
+        let params = params.build();
+        cvt(ffi::EVP_KDF_derive(
+            ctx.0,
+            out.as_mut_ptr(),
+            out.len(),
+            params.as_ptr(),
+        ))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(ossl300)]
+fn cstr(s: &'static [u8]) -> &'static CStr {
+    CStr::from_bytes_with_nul(s).unwrap()
+}
+
+#[cfg(ossl300)]
+/// A builder for the `FixedInfo` input of the NIST SP 800-56C single-step KDF.
+///
+/// SP 800-56C defines `FixedInfo` as the concatenation of `AlgorithmID`, `PartyUInfo`,
+/// `PartyVInfo`, and optionally `SuppPubInfo` and `SuppPrivInfo`. Each field may be encoded
+/// either as fixed-length data or as variable-length data prefixed with its length as a 32-bit
+/// big-endian integer. Protocols define which encoding applies to each field, so this builder
+/// only provides the primitive encodings, to be appended in order.
+///
+/// # Examples
+///
+/// ```
+/// use openssl::kdf::FixedInfoBuilder;
+///
+/// let fixed_info = FixedInfoBuilder::new()
+///     .append_length_prefixed(b"A256GCM")
+///     .append_length_prefixed(b"Alice")
+///     .append_length_prefixed(b"Bob")
+///     .append_u32(256)
+///     .build();
+/// assert_eq!(&fixed_info[..4], &[0, 0, 0, 7]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FixedInfoBuilder {
+    buf: Vec<u8>,
+}
+
+#[cfg(ossl300)]
+impl FixedInfoBuilder {
+    /// Creates a new, empty builder.
+    pub fn new() -> FixedInfoBuilder {
+        FixedInfoBuilder::default()
+    }
+
+    /// Appends a fixed-length field as-is.
+    pub fn append(&mut self, data: &[u8]) -> &mut FixedInfoBuilder {
+        self.buf.extend_from_slice(data);
+        self
+    }
+
+    /// Appends a variable-length field prefixed by its length in bytes as a 32-bit big-endian
+    /// integer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is longer than `u32::MAX` bytes.
+    pub fn append_length_prefixed(&mut self, data: &[u8]) -> &mut FixedInfoBuilder {
+        assert!(data.len() <= u32::MAX as usize);
+        self.append_u32(data.len() as u32);
+        self.append(data)
+    }
+
+    /// Appends a 32-bit big-endian integer, such as the `keydatalen` commonly carried in
+    /// `SuppPubInfo`.
+    pub fn append_u32(&mut self, value: u32) -> &mut FixedInfoBuilder {
+        self.append(&value.to_be_bytes())
+    }
+
+    /// Returns the encoded `FixedInfo`.
+    pub fn build(&self) -> Vec<u8> {
+        self.buf.clone()
+    }
+}
+
+/// Derives key material using the hash-based single-step KDF from NIST SP 800-56C.
+///
+/// `secret` is the shared secret `Z`, and `fixed_info` is the context specific `FixedInfo`,
+/// which can be constructed with a [`FixedInfoBuilder`]. `out` is filled with the derived keying
+/// material.
+#[corresponds(EVP_KDF_derive)]
+#[cfg(ossl300)]
+pub fn sskdf_digest(
+    digest: &MdRef,
+    secret: &[u8],
+    fixed_info: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    let mut params = ParamBuilder::new();
+    params
+        .digest(digest)
+        .octet_string(b"secret\0", secret)
+        .octet_string(b"info\0", fixed_info);
+    derive(cstr(b"SSKDF\0"), params, out)
+}
+
+/// Derives key material using the HMAC-based single-step KDF from NIST SP 800-56C.
+///
+/// This is identical to [`sskdf_digest`], except that HMAC keyed with `salt` is used as the
+/// auxiliary function.
+#[corresponds(EVP_KDF_derive)]
+#[cfg(ossl300)]
+pub fn sskdf_hmac(
+    digest: &MdRef,
+    secret: &[u8],
+    salt: &[u8],
+    fixed_info: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    let mut params = ParamBuilder::new();
+    params
+        .utf8_string(b"mac\0", cstr(b"HMAC\0"))
+        .digest(digest)
+        .octet_string(b"secret\0", secret)
+        .octet_string(b"salt\0", salt)
+        .octet_string(b"info\0", fixed_info);
+    derive(cstr(b"SSKDF\0"), params, out)
+}
+
+/// Derives key material using the ANSI X9.63 KDF.
+///
+/// `secret` is the shared secret `Z`, and `shared_info` is the context specific `SharedInfo`.
+/// This is the KDF used by SEC 1 ECIES.
+#[corresponds(EVP_KDF_derive)]
+#[cfg(ossl300)]
+pub fn x963kdf(
+    digest: &MdRef,
+    secret: &[u8],
+    shared_info: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    let mut params = ParamBuilder::new();
+    params
+        .digest(digest)
+        .octet_string(b"key\0", secret)
+        .octet_string(b"info\0", shared_info);
+    derive(cstr(b"X963KDF\0"), params, out)
+}
+
+/// Derives key material using the NIST SP 800-108 KDF in counter mode with HMAC as the PRF.
+///
+/// The fixed input data is formed as `label || 0x00 || context || L`, where `L` is the length of
+/// `out` in bits encoded as a 32-bit big-endian integer, and the counter is a 32-bit big-endian
+/// integer preceding the fixed input data.
+#[corresponds(EVP_KDF_derive)]
+#[cfg(ossl300)]
+pub fn kbkdf_counter_hmac(
+    digest: &MdRef,
+    key: &[u8],
+    label: &[u8],
+    context: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    let use_l = 1;
+    let use_separator = 1;
+
+    let mut params = ParamBuilder::new();
+    params
+        .utf8_string(b"mode\0", cstr(b"counter\0"))
+        .utf8_string(b"mac\0", cstr(b"HMAC\0"))
+        .digest(digest)
+        .octet_string(b"key\0", key)
+        .octet_string(b"salt\0", label)
+        .octet_string(b"info\0", context)
+        .int(b"use-l\0", &use_l)
+        .int(b"use-separator\0", &use_separator);
+    derive(cstr(b"KBKDF\0"), params, out)
+}
+
+/// Derives key material using HKDF, performing HKDF-Extract followed by HKDF-Expand.
+///
+/// This is the HKDF function defined in [RFC 5869]. `out` is filled with the output keying
+/// material.
+///
+/// Requires OpenSSL 1.1.0 or newer, or BoringSSL.
+///
+/// [RFC 5869]: https://datatracker.ietf.org/doc/html/rfc5869
+#[corresponds(EVP_KDF_derive)]
+pub fn hkdf(
+    digest: &MdRef,
+    key: &[u8],
+    salt: &[u8],
+    info: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    cfg_if! {
+        if #[cfg(ossl300)] {
+            let mut params = ParamBuilder::new();
+            params
+                .utf8_string(b"mode\0", cstr(b"EXTRACT_AND_EXPAND\0"))
+                .digest(digest)
+                .octet_string(b"key\0", key)
+                .octet_string(b"salt\0", salt)
+                .octet_string(b"info\0", info);
+            derive(cstr(b"HKDF\0"), params, out)
+        } else if #[cfg(boringssl)] {
+            unsafe {
+                ffi::init();
+                cvt(ffi::HKDF(
+                    out.as_mut_ptr(),
+                    out.len(),
+                    digest.as_ptr(),
+                    key.as_ptr(),
+                    key.len(),
+                    salt.as_ptr(),
+                    salt.len(),
+                    info.as_ptr(),
+                    info.len(),
+                ))?;
+            }
+            Ok(())
+        } else {
+            let mut ctx = PkeyCtx::new_id(Id::HKDF)?;
+            ctx.derive_init()?;
+            ctx.set_hkdf_md(digest)?;
+            ctx.set_hkdf_key(key)?;
+            ctx.set_hkdf_salt(salt)?;
+            ctx.add_hkdf_info(info)?;
+            ctx.derive(Some(out))?;
+            Ok(())
+        }
+    }
+}
+
+/// Performs only the HKDF-Extract step of HKDF, returning the pseudorandom key.
+///
+/// The returned key is the size of the output of `digest`.
+///
+/// Requires OpenSSL 1.1.1 or newer, or BoringSSL.
+#[corresponds(EVP_KDF_derive)]
+#[cfg(any(ossl111, boringssl))]
+pub fn hkdf_extract(digest: &MdRef, key: &[u8], salt: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+    cfg_if! {
+        if #[cfg(ossl300)] {
+            let mut params = ParamBuilder::new();
+            params
+                .utf8_string(b"mode\0", cstr(b"EXTRACT_ONLY\0"))
+                .digest(digest)
+                .octet_string(b"key\0", key)
+                .octet_string(b"salt\0", salt);
+            let mut out = vec![0; digest.size()];
+            derive(cstr(b"HKDF\0"), params, &mut out)?;
+        } else if #[cfg(boringssl)] {
+            let mut out = vec![0; ffi::EVP_MAX_MD_SIZE as usize];
+            let mut len = 0;
+            unsafe {
+                ffi::init();
+                cvt(ffi::HKDF_extract(
+                    out.as_mut_ptr(),
+                    &mut len,
+                    digest.as_ptr(),
+                    key.as_ptr(),
+                    key.len(),
+                    salt.as_ptr(),
+                    salt.len(),
+                ))?;
+            }
+            out.truncate(len);
+        } else {
+            let mut ctx = PkeyCtx::new_id(Id::HKDF)?;
+            ctx.derive_init()?;
+            ctx.set_hkdf_mode(HkdfMode::EXTRACT_ONLY)?;
+            ctx.set_hkdf_md(digest)?;
+            ctx.set_hkdf_key(key)?;
+            ctx.set_hkdf_salt(salt)?;
+            let mut out = vec![];
+            ctx.derive_to_vec(&mut out)?;
+        }
+    }
+    Ok(out)
+}
+
+/// Performs only the HKDF-Expand step of HKDF.
+///
+/// `prk` must be a pseudorandom key such as one returned by [`hkdf_extract`], not raw input
+/// keying material. `out` is filled with the output keying material.
+///
+/// Requires OpenSSL 1.1.1 or newer, or BoringSSL.
+#[corresponds(EVP_KDF_derive)]
+#[cfg(any(ossl111, boringssl))]
+pub fn hkdf_expand(
+    digest: &MdRef,
+    prk: &[u8],
+    info: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    cfg_if! {
+        if #[cfg(ossl300)] {
+            let mut params = ParamBuilder::new();
+            params
+                .utf8_string(b"mode\0", cstr(b"EXPAND_ONLY\0"))
+                .digest(digest)
+                .octet_string(b"key\0", prk)
+                .octet_string(b"info\0", info);
+            derive(cstr(b"HKDF\0"), params, out)
+        } else if #[cfg(boringssl)] {
+            unsafe {
+                ffi::init();
+                cvt(ffi::HKDF_expand(
+                    out.as_mut_ptr(),
+                    out.len(),
+                    digest.as_ptr(),
+                    prk.as_ptr(),
+                    prk.len(),
+                    info.as_ptr(),
+                    info.len(),
+                ))?;
+            }
+            Ok(())
+        } else {
+            let mut ctx = PkeyCtx::new_id(Id::HKDF)?;
+            ctx.derive_init()?;
+            ctx.set_hkdf_mode(HkdfMode::EXPAND_ONLY)?;
+            ctx.set_hkdf_md(digest)?;
+            ctx.set_hkdf_key(prk)?;
+            ctx.add_hkdf_info(info)?;
+            ctx.derive(Some(out))?;
+            Ok(())
+        }
+    }
+}
+
+/// Performs the TLS 1.3 `HKDF-Expand-Label` function.
+///
+/// As defined in [RFC 8446 section 7.1], this calls [`hkdf_expand`] with an `HkdfLabel`
+/// structure encoding the length of `out`, the label prefixed with `tls13 `, and `context`.
+///
+/// # Panics
+///
+/// Panics if `out` is longer than 65535 bytes, the prefixed label is longer than 255 bytes, or
+/// `context` is longer than 255 bytes.
+///
+/// Requires OpenSSL 1.1.1 or newer, or BoringSSL.
+///
+/// [RFC 8446 section 7.1]: https://datatracker.ietf.org/doc/html/rfc8446#section-7.1
+#[cfg(any(ossl111, boringssl))]
+pub fn tls13_hkdf_expand_label(
+    digest: &MdRef,
+    secret: &[u8],
+    label: &[u8],
+    context: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    const PREFIX: &[u8] = b"tls13 ";
+
+    assert!(out.len() <= u16::MAX as usize);
+    assert!(PREFIX.len() + label.len() <= u8::MAX as usize);
+    assert!(context.len() <= u8::MAX as usize);
+
+    let mut info = Vec::with_capacity(4 + PREFIX.len() + label.len() + context.len());
+    info.extend_from_slice(&(out.len() as u16).to_be_bytes());
+    info.push((PREFIX.len() + label.len()) as u8);
+    info.extend_from_slice(PREFIX);
+    info.extend_from_slice(label);
+    info.push(context.len() as u8);
+    info.extend_from_slice(context);
+
+    hkdf_expand(digest, secret, &info, out)
+}
+
+/// Derives key material using the TLS 1.0-1.2 pseudorandom function.
+///
+/// This computes `PRF(secret, label, seed)` as defined in [RFC 5246 section 5]. TLS 1.2 uses
+/// the cipher suite's PRF hash, typically SHA-256, while TLS 1.0 and 1.1 use `MD5-SHA1`.
+///
+/// [RFC 5246 section 5]: https://datatracker.ietf.org/doc/html/rfc5246#section-5
+#[corresponds(EVP_KDF_derive)]
+#[cfg(ossl300)]
+pub fn tls1_prf(
+    digest: &MdRef,
+    secret: &[u8],
+    label: &[u8],
+    seed: &[u8],
+    out: &mut [u8],
+) -> Result<(), ErrorStack> {
+    // Multiple seed parameters are concatenated.
+    let mut params = ParamBuilder::new();
+    params
+        .digest(digest)
+        .octet_string(b"secret\0", secret)
+        .octet_string(b"seed\0", label)
+        .octet_string(b"seed\0", seed);
+    derive(cstr(b"TLS1-PRF\0"), params, out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::md::Md;
+
+    #[test]
+    #[cfg(ossl300)]
+    fn fixed_info() {
+        let info = FixedInfoBuilder::new()
+            .append(b"id")
+            .append_length_prefixed(b"abc")
+            .append_u32(128)
+            .build();
+        assert_eq!(info, b"id\x00\x00\x00\x03abc\x00\x00\x00\x80");
+    }
+
+    // Expected values were computed independently from the SP 800-56C and SP 800-108 definitions.
+    #[test]
+    #[cfg(ossl300)]
+    fn sskdf_digest_sha224() {
+        let secret = hex::decode(
+            "6dbdc23f045488e4062757b06b9ebae183fc5a5946d80db93fec6f62ec07e3727f0126aed12ce4b262f47d48d54287f81d474c7c3b1850e9",
+        )
+        .unwrap();
+        let info = hex::decode("a1b2c3d4e5").unwrap();
+        let mut out = [0; 14];
+        sskdf_digest(Md::sha224(), &secret, &info, &mut out).unwrap();
+        assert_eq!(hex::encode(out), "2289ddc375f9377684e1a0a7b36b");
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn sskdf_hmac_sha256() {
+        let secret = hex::decode("b74a149a161545").unwrap();
+        let salt = hex::decode("0000000000000000").unwrap();
+        let info = hex::decode("8e1eb6e7").unwrap();
+        let mut out = [0; 16];
+        sskdf_hmac(Md::sha256(), &secret, &salt, &info, &mut out).unwrap();
+        assert_eq!(hex::encode(out), "d634909c37129c4e4927c4c82075d7a8");
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn x963kdf_sha256() {
+        use crate::sha::Sha256;
+
+        let secret = b"shared secret";
+        let info = b"shared info";
+        let mut expected = vec![];
+        for counter in 1u32..=2 {
+            let mut h = Sha256::new();
+            h.update(secret);
+            h.update(&counter.to_be_bytes());
+            h.update(info);
+            expected.extend_from_slice(&h.finish());
+        }
+        expected.truncate(40);
+
+        let mut out = [0; 40];
+        x963kdf(Md::sha256(), secret, info, &mut out).unwrap();
+        assert_eq!(&out[..], &expected[..]);
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn kbkdf_counter_sha256() {
+        let key = hex::decode("00112233445566778899aabbccddeeff").unwrap();
+        let mut out = [0; 32];
+        kbkdf_counter_hmac(Md::sha256(), &key, b"label", b"context", &mut out).unwrap();
+        assert_eq!(
+            hex::encode(out),
+            "0221984163d8ce7453e80fe3d91b9f262bf7d316fd29aeb415d6ae903adae919"
+        );
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn kbkdf_rejects_empty_key() {
+        let mut out = [0; 16];
+        assert!(kbkdf_counter_hmac(Md::sha256(), b"", b"label", b"context", &mut out).is_err());
+    }
+
+    // RFC 5869 test case 1
+    #[test]
+    fn hkdf_rfc5869() {
+        let ikm = hex::decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap();
+        let salt = hex::decode("000102030405060708090a0b0c").unwrap();
+        let info = hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap();
+        let okm =
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865";
+
+        let mut out = [0; 42];
+        hkdf(Md::sha256(), &ikm, &salt, &info, &mut out).unwrap();
+        assert_eq!(hex::encode(out), okm);
+    }
+
+    #[test]
+    #[cfg(any(ossl111, boringssl))]
+    fn hkdf_extract_expand_rfc5869() {
+        let ikm = hex::decode("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b").unwrap();
+        let salt = hex::decode("000102030405060708090a0b0c").unwrap();
+        let info = hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap();
+        let okm =
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865";
+
+        let prk = hkdf_extract(Md::sha256(), &ikm, &salt).unwrap();
+        assert_eq!(
+            hex::encode(&prk),
+            "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"
+        );
+
+        let mut out = [0; 42];
+        hkdf_expand(Md::sha256(), &prk, &info, &mut out).unwrap();
+        assert_eq!(hex::encode(out), okm);
+    }
+
+    // The early secret and its "derived" secret from RFC 8448 section 3
+    #[test]
+    #[cfg(any(ossl111, boringssl))]
+    fn tls13_expand_label() {
+        let early_secret = hkdf_extract(Md::sha256(), &[0; 32], &[0]).unwrap();
+        assert_eq!(
+            hex::encode(&early_secret),
+            "33ad0a1c607ec03b09e6cd9893680ce210adf300aa1f2660e1b22e10f170f92a"
+        );
+
+        let empty_hash =
+            hex::decode("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+                .unwrap();
+        let mut derived = [0; 32];
+        tls13_hkdf_expand_label(
+            Md::sha256(),
+            &early_secret,
+            b"derived",
+            &empty_hash,
+            &mut derived,
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(derived),
+            "6f2615a108c702c5678f54fc9dbab69716c076189c48250cebeac3576c3611ba"
+        );
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn tls12_prf_sha256() {
+        let secret = hex::decode("9bbe436ba940f017b17652849a71db35").unwrap();
+        let seed = hex::decode("a0ba9f936cda311827a6f796ffd5198c").unwrap();
+        let mut out = [0; 100];
+        tls1_prf(Md::sha256(), &secret, b"test label", &seed, &mut out).unwrap();
+        assert_eq!(
+            hex::encode(&out[..]),
+            "e3f229ba727be17b8d122620557cd453c2aab21d07c3d495329b52d4e61edb5a6b301791e90d35c9c9a46b4e14baf9af0fa022f7077def17abfd3797c0564bab4fbc91666e9def9b97fce34f796789baa48082d122ee42c5a72e5a5110fff70187347b66"
+        );
+    }
+}
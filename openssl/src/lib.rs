@@ -67,6 +67,12 @@
 //! Additionally, these variables can be prefixed with the upper-cased target architecture (e.g.
 //!     `X86_64_UNKNOWN_LINUX_GNU_OPENSSL_DIR`), which can be useful when cross compiling.
 //!
+//! ## Minimal builds
+//!
+//! Every module is built regardless of the Cargo features enabled. Moving the `ssl` and `x509` modules behind
+//! features would break crates which already depend on `openssl` with `default-features = false`, so builds without
+//! them are not offered in 0.10.
+//!
 //! # Feature Detection
 //!
 //! APIs have been added to and removed from the various supported OpenSSL versions, and this library exposes the
@@ -132,6 +138,8 @@ use crate::error::ErrorStack;
 mod macros;
 
 mod bio;
+#[cfg(any(ossl111, boringssl, libressl370))]
+mod okp;
 #[macro_use]
 mod util;
 pub mod aes;
@@ -143,11 +151,17 @@ pub mod cipher_ctx;
 #[cfg(all(not(boringssl), not(libressl), not(osslconf = "OPENSSL_NO_CMS")))]
 pub mod cms;
 pub mod conf;
+pub mod cose;
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+pub mod ct;
+pub mod der;
 pub mod derive;
 pub mod dh;
 pub mod dsa;
 pub mod ec;
 pub mod ecdsa;
+#[cfg(ossl300)]
+pub mod ecies;
 pub mod encrypt;
 #[cfg(not(boringssl))]
 pub mod envelope;
@@ -156,14 +170,27 @@ pub mod ex_data;
 #[cfg(not(any(libressl, ossl300)))]
 pub mod fips;
 pub mod hash;
+#[cfg(ossl320)]
+pub mod hpke;
+#[cfg(feature = "jwk")]
+pub mod jwk;
+#[cfg(any(ossl110, boringssl))]
+pub mod kdf;
 #[cfg(ossl300)]
 pub mod lib_ctx;
 pub mod md;
 pub mod md_ctx;
+#[cfg(ossl110)]
+pub mod mem;
 pub mod memcmp;
 pub mod nid;
 #[cfg(not(any(boringssl, osslconf = "OPENSSL_NO_OCSP")))]
 pub mod ocsp;
+#[cfg(ossl320)]
+pub mod ohttp;
+pub mod ops;
+pub mod panic;
+pub mod pkcs11;
 pub mod pkcs12;
 pub mod pkcs5;
 #[cfg(not(boringssl))]
@@ -171,16 +198,33 @@ pub mod pkcs7;
 pub mod pkey;
 pub mod pkey_ctx;
 #[cfg(ossl300)]
+pub mod policy;
+#[cfg(ossl300)]
 pub mod provider;
 pub mod rand;
+#[cfg(ossl110)]
+pub mod remote_key;
 pub mod rsa;
+#[cfg(feature = "serde")]
+pub mod serde;
 pub mod sha;
 pub mod sign;
 pub mod srtp;
+pub mod ssh;
 pub mod ssl;
 pub mod stack;
+pub mod starttls;
+#[cfg(ossl300)]
+pub mod store;
 pub mod string;
 pub mod symm;
+pub mod test_support;
+pub mod thread_cache;
+pub mod thread_check;
+#[cfg(ossl300)]
+pub mod tls_record;
+#[cfg(boringssl)]
+pub mod trust_token;
 pub mod version;
 pub mod x509;
 
@@ -197,7 +241,7 @@ type SLenType = libc::c_int;
 #[inline]
 fn cvt_p<T>(r: *mut T) -> Result<*mut T, ErrorStack> {
     if r.is_null() {
-        Err(ErrorStack::get())
+        Err(ErrorStack::report())
     } else {
         Ok(r)
     }
@@ -206,7 +250,7 @@ fn cvt_p<T>(r: *mut T) -> Result<*mut T, ErrorStack> {
 #[inline]
 fn cvt(r: c_int) -> Result<c_int, ErrorStack> {
     if r <= 0 {
-        Err(ErrorStack::get())
+        Err(ErrorStack::report())
     } else {
         Ok(r)
     }
@@ -215,7 +259,7 @@ fn cvt(r: c_int) -> Result<c_int, ErrorStack> {
 #[inline]
 fn cvt_n(r: c_int) -> Result<c_int, ErrorStack> {
     if r < 0 {
-        Err(ErrorStack::get())
+        Err(ErrorStack::report())
     } else {
         Ok(r)
     }
@@ -1,16 +1,23 @@
-use crate::cvt_p;
 use crate::error::ErrorStack;
-use foreign_types::ForeignType;
+use crate::{cvt, cvt_p};
+use foreign_types::{ForeignType, ForeignTypeRef};
 use openssl_macros::corresponds;
+use std::ffi::CString;
+use std::ptr;
 
 foreign_type_and_impl_send_sync! {
     type CType = ffi::OSSL_LIB_CTX;
-    fn drop = ffi::OSSL_LIB_CTX_free;
+    fn drop = lib_ctx_free;
 
     pub struct LibCtx;
     pub struct LibCtxRef;
 }
 
+unsafe fn lib_ctx_free(ctx: *mut ffi::OSSL_LIB_CTX) {
+    crate::policy::forget(ctx as usize);
+    ffi::OSSL_LIB_CTX_free(ctx);
+}
+
 impl LibCtx {
     #[corresponds(OSSL_LIB_CTX_new)]
     pub fn new() -> Result<Self, ErrorStack> {
@@ -19,4 +26,41 @@ impl LibCtx {
             Ok(LibCtx::from_ptr(ptr))
         }
     }
+
+    /// Sets the default property query string of `ctx`, which is combined with the properties
+    /// passed to each fetch.
+    ///
+    /// For example, `"fips=yes"` directs every algorithm fetched from `ctx` to a FIPS provider
+    /// unless a fetch overrides it. If `ctx` is `None`, the default library context is modified.
+    #[corresponds(EVP_set_default_properties)]
+    pub fn set_default_properties(
+        ctx: Option<&LibCtxRef>,
+        properties: &str,
+    ) -> Result<(), ErrorStack> {
+        let properties = CString::new(properties).unwrap();
+        unsafe {
+            cvt(ffi::EVP_set_default_properties(
+                ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+                properties.as_ptr(),
+            ))
+            .map(|_| ())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md::Md;
+
+    #[test]
+    fn set_default_properties() {
+        let ctx = LibCtx::new().unwrap();
+        Md::fetch(Some(&ctx), "SHA256", None).unwrap();
+
+        LibCtx::set_default_properties(Some(&ctx), "provider=bogus").unwrap();
+        assert!(Md::fetch(Some(&ctx), "SHA256", None).is_err());
+        // a query passed to the fetch overrides the default
+        Md::fetch(Some(&ctx), "SHA256", Some("provider=default")).unwrap();
+    }
 }
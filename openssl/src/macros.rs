@@ -268,3 +268,85 @@ macro_rules! generic_foreign_type_and_impl_send_sync {
         unsafe impl<T> Sync for $borrowed<T>{}
     };
 }
+
+/// Defines an error type which is either a description of invalid input or an OpenSSL error stack.
+///
+/// The invalid input is displayed with the given format string. Additional wrapped error types can
+/// be listed after it, each converted with `From` and displayed with its own format string.
+macro_rules! invalid_input_error {
+    (
+        $(#[$m:meta])*
+        pub struct $n:ident => $invalid:literal;
+        $($variant:ident($t:ty) => $display:literal;)*
+    ) => {
+        $(#[$m])*
+        pub struct $n(Inner);
+
+        enum Inner {
+            Invalid(String),
+            $($variant($t),)*
+            Ssl(crate::error::ErrorStack),
+        }
+
+        impl $n {
+            fn invalid<S>(msg: S) -> $n
+            where
+                S: Into<String>,
+            {
+                $n(Inner::Invalid(msg.into()))
+            }
+
+            /// Returns the underlying OpenSSL error stack, if the error was reported by OpenSSL.
+            pub fn ssl_error(&self) -> Option<&crate::error::ErrorStack> {
+                match self.0 {
+                    Inner::Ssl(ref e) => Some(e),
+                    _ => None,
+                }
+            }
+        }
+
+        impl ::std::fmt::Debug for $n {
+            fn fmt(&self, fmt: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self.0 {
+                    Inner::Invalid(ref e) => fmt.debug_tuple("Invalid").field(e).finish(),
+                    $(Inner::$variant(ref e) => fmt.debug_tuple(stringify!($variant)).field(e).finish(),)*
+                    Inner::Ssl(ref e) => fmt.debug_tuple("Ssl").field(e).finish(),
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for $n {
+            fn fmt(&self, fmt: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self.0 {
+                    Inner::Invalid(ref e) => write!(fmt, $invalid, e),
+                    $(Inner::$variant(ref e) => write!(fmt, $display, e),)*
+                    Inner::Ssl(ref e) => ::std::fmt::Display::fmt(e, fmt),
+                }
+            }
+        }
+
+        impl ::std::error::Error for $n {
+            fn source(&self) -> Option<&(dyn ::std::error::Error + 'static)> {
+                match self.0 {
+                    Inner::Invalid(_) => None,
+                    $(Inner::$variant(ref e) => Some(e),)*
+                    Inner::Ssl(ref e) => Some(e),
+                }
+            }
+        }
+
+        impl From<crate::error::ErrorStack> for $n {
+            fn from(e: crate::error::ErrorStack) -> $n {
+                $n(Inner::Ssl(e))
+            }
+        }
+
+        $(
+            impl From<$t> for $n {
+                fn from(e: $t) -> $n {
+                    $n(Inner::$variant(e))
+                }
+            }
+        )*
+    };
+}
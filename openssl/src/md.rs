@@ -7,6 +7,8 @@ use crate::error::ErrorStack;
 #[cfg(ossl300)]
 use crate::lib_ctx::LibCtxRef;
 use crate::nid::Nid;
+#[cfg(ossl300)]
+use crate::policy::{self, Operation};
 use cfg_if::cfg_if;
 use foreign_types::{ForeignTypeRef, Opaque};
 use openssl_macros::corresponds;
@@ -107,10 +109,16 @@ impl Md {
             let ptr = cvt_p(ffi::EVP_MD_fetch(
                 ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
                 algorithm.as_ptr(),
-                properties.map_or(ptr::null_mut(), |s| s.as_ptr()),
+                properties.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
             ))?;
+            let md = Md::from_ptr(ptr);
+            policy::check(
+                ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+                md.type_(),
+                Operation::Digest,
+            )?;
 
-            Ok(Md::from_ptr(ptr))
+            Ok(md)
         }
     }
 
@@ -82,14 +82,20 @@ assert!(valid);
 )]
 
 use crate::error::ErrorStack;
+#[cfg(ossl300)]
+use crate::lib_ctx::LibCtxRef;
 use crate::md::MdRef;
 use crate::pkey::{HasPrivate, HasPublic, PKeyRef};
 use crate::pkey_ctx::PkeyCtxRef;
+#[cfg(ossl300)]
+use crate::policy::{self, Operation};
 use crate::{cvt, cvt_n, cvt_p};
 use cfg_if::cfg_if;
 use foreign_types::{ForeignType, ForeignTypeRef};
 use openssl_macros::corresponds;
 use std::convert::TryFrom;
+#[cfg(ossl300)]
+use std::ffi::CString;
 use std::ptr;
 
 cfg_if! {
@@ -128,6 +134,9 @@ impl MdCtxRef {
     #[corresponds(EVP_DigestInit_ex)]
     #[inline]
     pub fn digest_init(&mut self, digest: &MdRef) -> Result<(), ErrorStack> {
+        #[cfg(ossl300)]
+        policy::check(ptr::null_mut(), digest.type_(), Operation::Digest)?;
+
         unsafe {
             cvt(ffi::EVP_DigestInit_ex(
                 self.as_ptr(),
@@ -152,6 +161,11 @@ impl MdCtxRef {
     where
         T: HasPrivate,
     {
+        #[cfg(ossl300)]
+        if let Some(digest) = digest {
+            policy::check(ptr::null_mut(), digest.type_(), Operation::Sign)?;
+        }
+
         unsafe {
             let mut p = ptr::null_mut();
             cvt(ffi::EVP_DigestSignInit(
@@ -165,6 +179,52 @@ impl MdCtxRef {
         }
     }
 
+    /// Like [`digest_sign_init`], but fetches the digest named `digest` and the signature
+    /// algorithm from `ctx` according to the property query string `properties`.
+    ///
+    /// If `ctx` is `None`, the default library context is used.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// [`digest_sign_init`]: MdCtxRef::digest_sign_init
+    #[corresponds(EVP_DigestSignInit_ex)]
+    #[cfg(ossl300)]
+    pub fn digest_sign_init_ex<'a, T>(
+        &'a mut self,
+        digest: Option<&str>,
+        ctx: Option<&LibCtxRef>,
+        properties: Option<&str>,
+        pkey: &PKeyRef<T>,
+    ) -> Result<&'a mut PkeyCtxRef<T>, ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        let digest = digest.map(|s| CString::new(s).unwrap());
+        let properties = properties.map(|s| CString::new(s).unwrap());
+
+        unsafe {
+            let mut p = ptr::null_mut();
+            cvt(ffi::EVP_DigestSignInit_ex(
+                self.as_ptr(),
+                &mut p,
+                digest.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+                ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+                properties.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+                pkey.as_ptr(),
+                ptr::null(),
+            ))?;
+            let md = ffi::EVP_MD_CTX_get0_md(self.as_ptr());
+            if !md.is_null() {
+                policy::check(
+                    ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+                    MdRef::from_ptr(md as *mut _).type_(),
+                    Operation::Sign,
+                )?;
+            }
+            Ok(PkeyCtxRef::from_ptr_mut(p))
+        }
+    }
+
     /// Initializes the context to verify the signature of data.
     ///
     /// A reference to the context's inner `PkeyCtx` is returned, allowing signature settings to be configured.
@@ -178,6 +238,11 @@ impl MdCtxRef {
     where
         T: HasPublic,
     {
+        #[cfg(ossl300)]
+        if let Some(digest) = digest {
+            policy::check(ptr::null_mut(), digest.type_(), Operation::Verify)?;
+        }
+
         unsafe {
             let mut p = ptr::null_mut();
             cvt(ffi::EVP_DigestVerifyInit(
@@ -191,6 +256,52 @@ impl MdCtxRef {
         }
     }
 
+    /// Like [`digest_verify_init`], but fetches the digest named `digest` and the signature
+    /// algorithm from `ctx` according to the property query string `properties`.
+    ///
+    /// If `ctx` is `None`, the default library context is used.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// [`digest_verify_init`]: MdCtxRef::digest_verify_init
+    #[corresponds(EVP_DigestVerifyInit_ex)]
+    #[cfg(ossl300)]
+    pub fn digest_verify_init_ex<'a, T>(
+        &'a mut self,
+        digest: Option<&str>,
+        ctx: Option<&LibCtxRef>,
+        properties: Option<&str>,
+        pkey: &PKeyRef<T>,
+    ) -> Result<&'a mut PkeyCtxRef<T>, ErrorStack>
+    where
+        T: HasPublic,
+    {
+        let digest = digest.map(|s| CString::new(s).unwrap());
+        let properties = properties.map(|s| CString::new(s).unwrap());
+
+        unsafe {
+            let mut p = ptr::null_mut();
+            cvt(ffi::EVP_DigestVerifyInit_ex(
+                self.as_ptr(),
+                &mut p,
+                digest.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+                ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+                properties.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+                pkey.as_ptr(),
+                ptr::null(),
+            ))?;
+            let md = ffi::EVP_MD_CTX_get0_md(self.as_ptr());
+            if !md.is_null() {
+                policy::check(
+                    ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+                    MdRef::from_ptr(md as *mut _).type_(),
+                    Operation::Verify,
+                )?;
+            }
+            Ok(PkeyCtxRef::from_ptr_mut(p))
+        }
+    }
+
     /// Updates the context with more data.
     #[corresponds(EVP_DigestUpdate)]
     #[inline]
@@ -537,4 +648,34 @@ mod test {
         // Validate result of digest of "World"
         assert_eq!(reset_result, world_expected);
     }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn sign_verify_ex() {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let data = b"Some Crypto Text";
+
+        let mut ctx = MdCtx::new().unwrap();
+        ctx.digest_sign_init_ex(Some("SHA256"), None, Some("provider=default"), &key)
+            .unwrap();
+        ctx.digest_sign_update(data).unwrap();
+        let mut signature = vec![];
+        ctx.digest_sign_final_to_vec(&mut signature).unwrap();
+
+        let mut ctx = MdCtx::new().unwrap();
+        ctx.digest_verify_init(Some(Md::sha256()), &key).unwrap();
+        ctx.digest_verify_update(data).unwrap();
+        assert!(ctx.digest_verify_final(&signature).unwrap());
+
+        let mut ctx = MdCtx::new().unwrap();
+        ctx.digest_verify_init_ex(Some("SHA256"), None, Some("provider=default"), &key)
+            .unwrap();
+        ctx.digest_verify_update(data).unwrap();
+        assert!(ctx.digest_verify_final(&signature).unwrap());
+
+        let mut ctx = MdCtx::new().unwrap();
+        assert!(ctx
+            .digest_sign_init_ex(Some("SHA256"), None, Some("provider=bogus"), &key)
+            .is_err());
+    }
 }
@@ -0,0 +1,431 @@
+//! Instrumented memory allocation for OpenSSL.
+//!
+//! [`install`] replaces the allocator OpenSSL uses for its own memory with one which allocates
+//! through Rust's global allocator and counts the memory held by each part of OpenSSL. This makes
+//! OpenSSL's memory visible to Rust allocators and heap profilers, and [`usage`] and
+//! [`usage_by_module`] report it, which helps to track down memory growth in long-running
+//! processes such as TLS terminators.
+//!
+//! The allocator must be installed before OpenSSL allocates any memory, which it does on the first
+//! use of almost any function in this crate, including [`init`]. Call [`install`] at the start of
+//! `main`. Memory allocated from OpenSSL's secure heap is not counted.
+//!
+//! Requires OpenSSL 1.1.0 or newer.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::mem;
+//!
+//! assert!(mem::install());
+//!
+//! openssl::init();
+//! let usage = mem::usage();
+//! assert!(usage.bytes > 0);
+//!
+//! for (module, usage) in mem::usage_by_module() {
+//!     println!("{}: {} bytes in {} allocations", module, usage.bytes, usage.allocations);
+//! }
+//! ```
+//!
+//! [`init`]: crate::init
+use libc::{c_char, c_int, c_void, size_t};
+use once_cell::sync::Lazy;
+use openssl_macros::corresponds;
+use std::alloc::{self, Layout};
+use std::cmp;
+use std::ffi::CStr;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+// the alignment of malloc on common platforms, which OpenSSL relies on
+const ALIGN: usize = 16;
+// the size of `Header`, rounded up to `ALIGN`
+const HEADER_LEN: usize = 16;
+
+// the top level directories of OpenSSL's source tree, used to name modules
+const SOURCE_DIRS: &[&str] = &["crypto", "ssl", "providers", "engines"];
+
+// the number of source files whose module is cached, which is larger than the number of files in
+// OpenSSL which allocate memory
+const FILE_SLOTS: usize = 4096;
+
+/// Counters of the memory allocated by OpenSSL.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// The number of bytes currently allocated.
+    pub bytes: u64,
+
+    /// The largest number of bytes allocated at any one time.
+    pub peak_bytes: u64,
+
+    /// The number of allocations which have not been freed.
+    pub allocations: u64,
+
+    /// The total number of allocations made, including those which have been freed.
+    pub total_allocations: u64,
+}
+
+/// Installs the instrumented allocator.
+///
+/// Returns `false` if OpenSSL has already allocated memory, in which case its allocator cannot be
+/// replaced and nothing is counted.
+#[corresponds(CRYPTO_set_mem_functions)]
+pub fn install() -> bool {
+    unsafe {
+        ffi::CRYPTO_set_mem_functions(Some(malloc_hook), Some(realloc_hook), Some(free_hook)) == 1
+    }
+}
+
+/// Returns the memory currently allocated by OpenSSL.
+///
+/// All counters are 0 if the instrumented allocator is not installed.
+pub fn usage() -> MemoryUsage {
+    TOTAL.usage()
+}
+
+/// Returns the memory currently allocated by each module of OpenSSL, sorted by name.
+///
+/// Modules are named after the directory of OpenSSL's source tree which allocated the memory,
+/// such as `crypto/evp` or `ssl/statem`. Memory reallocated by a different module is counted
+/// against the module which reallocated it. Modules which have freed all of their memory are
+/// included with 0 bytes allocated.
+///
+/// The counters are read one at a time while OpenSSL may be allocating on other threads, so they
+/// are not a consistent snapshot.
+pub fn usage_by_module() -> Vec<(String, MemoryUsage)> {
+    let mut modules = modules()
+        .map(|module| (module.name.clone(), module.counters.usage()))
+        .collect::<Vec<_>>();
+    modules.sort_by(|a, b| a.0.cmp(&b.0));
+    modules
+}
+
+#[repr(C)]
+struct Header {
+    size: usize,
+    module: *const Module,
+}
+
+// The counters are updated with atomics rather than under a lock, since every allocation made by
+// OpenSSL on any thread goes through the hooks.
+struct Counters {
+    bytes: AtomicUsize,
+    peak_bytes: AtomicUsize,
+    allocations: AtomicUsize,
+    total_allocations: AtomicUsize,
+}
+
+impl Counters {
+    const fn new() -> Counters {
+        Counters {
+            bytes: AtomicUsize::new(0),
+            peak_bytes: AtomicUsize::new(0),
+            allocations: AtomicUsize::new(0),
+            total_allocations: AtomicUsize::new(0),
+        }
+    }
+
+    fn add(&self, size: usize, new: bool) {
+        let bytes = self
+            .bytes
+            .fetch_add(size, Ordering::Relaxed)
+            .wrapping_add(size);
+        self.peak_bytes.fetch_max(bytes, Ordering::Relaxed);
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        if new {
+            self.total_allocations.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn remove(&self, size: usize) {
+        saturating_sub(&self.bytes, size);
+        saturating_sub(&self.allocations, 1);
+    }
+
+    fn usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            bytes: self.bytes.load(Ordering::Relaxed) as u64,
+            peak_bytes: self.peak_bytes.load(Ordering::Relaxed) as u64,
+            allocations: self.allocations.load(Ordering::Relaxed) as u64,
+            total_allocations: self.total_allocations.load(Ordering::Relaxed) as u64,
+        }
+    }
+}
+
+fn saturating_sub(counter: &AtomicUsize, n: usize) {
+    let _ = counter.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| {
+        Some(v.saturating_sub(n))
+    });
+}
+
+struct Module {
+    name: String,
+    counters: Counters,
+    next: *const Module,
+}
+
+// a source file name passed by OpenSSL, and the module it belongs to
+struct FileSlot {
+    file: AtomicUsize,
+    module: AtomicPtr<Module>,
+}
+
+static TOTAL: Counters = Counters::new();
+
+// modules are never freed, so the hooks can refer to them without locking
+static MODULES: AtomicPtr<Module> = AtomicPtr::new(ptr::null_mut());
+static REGISTER: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+// source file names passed by OpenSSL are string literals, so their addresses can be cached
+static FILES: Lazy<Box<[FileSlot]>> = Lazy::new(|| {
+    (0..FILE_SLOTS)
+        .map(|_| FileSlot {
+            file: AtomicUsize::new(0),
+            module: AtomicPtr::new(ptr::null_mut()),
+        })
+        .collect()
+});
+
+fn modules() -> impl Iterator<Item = &'static Module> {
+    let mut next = MODULES.load(Ordering::Acquire) as *const Module;
+    std::iter::from_fn(move || unsafe {
+        let module = next.as_ref()?;
+        next = module.next;
+        Some(module)
+    })
+}
+
+fn file_slots(file: usize) -> impl Iterator<Item = &'static FileSlot> {
+    let start = (file >> 3) % FILE_SLOTS;
+    (0..FILE_SLOTS).map(move |i| &FILES[(start + i) % FILE_SLOTS])
+}
+
+unsafe fn module(file: *const c_char) -> *const Module {
+    let key = file as usize;
+    for slot in file_slots(key) {
+        match slot.file.load(Ordering::Acquire) {
+            0 => break,
+            f if f == key => {
+                let module = slot.module.load(Ordering::Acquire);
+                if !module.is_null() {
+                    return module;
+                }
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let name = if file.is_null() {
+        "unknown".to_string()
+    } else {
+        module_name(&CStr::from_ptr(file).to_string_lossy())
+    };
+    let module = register(name);
+
+    for slot in file_slots(key) {
+        match slot
+            .file
+            .compare_exchange(0, key, Ordering::AcqRel, Ordering::Acquire)
+        {
+            Ok(_) => {
+                slot.module.store(module as *mut _, Ordering::Release);
+                break;
+            }
+            Err(f) if f == key => break,
+            Err(_) => {}
+        }
+    }
+
+    module
+}
+
+fn register(name: String) -> *const Module {
+    // the module is allocated before taking the lock, so that allocating does not hold it
+    let new = Box::new(Module {
+        name,
+        counters: Counters::new(),
+        next: ptr::null(),
+    });
+
+    let _guard = REGISTER.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(module) = modules().find(|m| m.name == new.name) {
+        return module;
+    }
+    let new = Box::into_raw(new);
+    unsafe {
+        (*new).next = MODULES.load(Ordering::Acquire);
+    }
+    MODULES.store(new, Ordering::Release);
+    new
+}
+
+fn add(module: *const Module, size: usize, new: bool) {
+    TOTAL.add(size, new);
+    unsafe { (*module).counters.add(size, new) }
+}
+
+fn remove(module: *const Module, size: usize) {
+    TOTAL.remove(size);
+    unsafe { (*module).counters.remove(size) }
+}
+
+fn module_name(file: &str) -> String {
+    let mut dirs = file.split(&['/', '\\'][..]).collect::<Vec<_>>();
+    let file_name = dirs.pop().unwrap_or("");
+    match dirs.iter().position(|d| SOURCE_DIRS.contains(d)) {
+        Some(i) => dirs[i..cmp::min(i + 2, dirs.len())].join("/"),
+        None => dirs.last().unwrap_or(&file_name).to_string(),
+    }
+}
+
+fn layout(size: usize) -> Option<Layout> {
+    let size = size.checked_add(HEADER_LEN)?;
+    Layout::from_size_align(size, ALIGN).ok()
+}
+
+unsafe extern "C" fn malloc_hook(num: size_t, file: *const c_char, _: c_int) -> *mut c_void {
+    let layout = match layout(num) {
+        Some(layout) => layout,
+        None => return ptr::null_mut(),
+    };
+    let base = alloc::alloc(layout);
+    if base.is_null() {
+        return ptr::null_mut();
+    }
+
+    let module = module(file);
+    add(module, num, true);
+    (base as *mut Header).write(Header { size: num, module });
+
+    base.add(HEADER_LEN) as *mut c_void
+}
+
+unsafe extern "C" fn realloc_hook(
+    addr: *mut c_void,
+    num: size_t,
+    file: *const c_char,
+    line: c_int,
+) -> *mut c_void {
+    if addr.is_null() {
+        return malloc_hook(num, file, line);
+    }
+    if num == 0 {
+        free_hook(addr, file, line);
+        return ptr::null_mut();
+    }
+
+    let new_layout = match layout(num) {
+        Some(layout) => layout,
+        None => return ptr::null_mut(),
+    };
+    let base = (addr as *mut u8).sub(HEADER_LEN);
+    let header = (base as *const Header).read();
+    let old_layout = Layout::from_size_align_unchecked(header.size + HEADER_LEN, ALIGN);
+    let base = alloc::realloc(base, old_layout, new_layout.size());
+    if base.is_null() {
+        return ptr::null_mut();
+    }
+
+    remove(header.module, header.size);
+    let module = module(file);
+    add(module, num, false);
+    (base as *mut Header).write(Header { size: num, module });
+
+    base.add(HEADER_LEN) as *mut c_void
+}
+
+unsafe extern "C" fn free_hook(addr: *mut c_void, _: *const c_char, _: c_int) {
+    if addr.is_null() {
+        return;
+    }
+
+    let base = (addr as *mut u8).sub(HEADER_LEN);
+    let header = (base as *const Header).read();
+    remove(header.module, header.size);
+    alloc::dealloc(
+        base,
+        Layout::from_size_align_unchecked(header.size + HEADER_LEN, ALIGN),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn install_after_use() {
+        crate::init();
+        assert!(!install());
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(module_name("crypto/evp/evp_enc.c"), "crypto/evp");
+        assert_eq!(module_name("../ssl/statem/statem_clnt.c"), "ssl/statem");
+        assert_eq!(module_name("/build/openssl/ssl/s3_lib.c"), "ssl");
+        assert_eq!(
+            module_name("providers/implementations/ciphers/cipher_aes.c"),
+            "providers/implementations"
+        );
+        assert_eq!(module_name("src/lib.rs"), "src");
+        assert_eq!(module_name("mem.c"), "mem.c");
+    }
+
+    #[test]
+    fn hooks() {
+        // the hooks are called directly, since OpenSSL has already allocated memory in the tests
+        let file = b"../crypto/mem_test/mem_test.c\0".as_ptr() as *const c_char;
+        let other = b"../ssl/mem_test/mem_test.c\0".as_ptr() as *const c_char;
+        let module = |name: &str| {
+            usage_by_module()
+                .into_iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, usage)| usage)
+                .unwrap_or_default()
+        };
+
+        unsafe {
+            let a = malloc_hook(100, file, 0) as *mut u8;
+            assert_eq!(a as usize % ALIGN, 0);
+            ptr::write_bytes(a, 7, 100);
+            let b = malloc_hook(0, file, 0);
+            assert!(!b.is_null());
+            let usage = module("crypto/mem_test");
+            assert_eq!(usage.bytes, 100);
+            assert_eq!(usage.allocations, 2);
+
+            let a = realloc_hook(a as *mut c_void, 1000, other, 0) as *mut u8;
+            assert_eq!(*a.add(99), 7);
+            assert_eq!(module("crypto/mem_test").bytes, 0);
+            let usage = module("ssl/mem_test");
+            assert_eq!(usage.bytes, 1000);
+            assert_eq!(usage.allocations, 1);
+            assert_eq!(usage.total_allocations, 0);
+
+            free_hook(a as *mut c_void, file, 0);
+            assert!(realloc_hook(b, 0, file, 0).is_null());
+            free_hook(ptr::null_mut(), file, 0);
+
+            let usage = module("crypto/mem_test");
+            assert_eq!(usage.allocations, 0);
+            assert_eq!(usage.total_allocations, 2);
+            assert_eq!(usage.peak_bytes, 100);
+            assert_eq!(module("ssl/mem_test").peak_bytes, 1000);
+        }
+    }
+
+    #[test]
+    fn remove_saturates() {
+        let counters = Counters::new();
+        counters.add(10, true);
+        counters.remove(20);
+        counters.remove(20);
+        let usage = counters.usage();
+        assert_eq!(usage.bytes, 0);
+        assert_eq!(usage.allocations, 0);
+        assert_eq!(usage.peak_bytes, 10);
+    }
+}
@@ -71,7 +71,7 @@ impl Nid {
             let ln = CString::new(ln).unwrap();
             let raw = ffi::OBJ_create(oid.as_ptr(), sn.as_ptr(), ln.as_ptr());
             if raw == ffi::NID_undef {
-                Err(ErrorStack::get())
+                Err(ErrorStack::report())
             } else {
                 Ok(Nid(raw))
             }
@@ -221,6 +221,14 @@ impl Nid {
     pub const BRAINPOOL_P384R1: Nid = Nid(ffi::NID_brainpoolP384r1);
     #[cfg(ossl110)]
     pub const BRAINPOOL_P512R1: Nid = Nid(ffi::NID_brainpoolP512r1);
+    #[cfg(ossl110)]
+    pub const CT_PRECERT_SCTS: Nid = Nid(ffi::NID_ct_precert_scts);
+    #[cfg(ossl110)]
+    pub const CT_PRECERT_POISON: Nid = Nid(ffi::NID_ct_precert_poison);
+    #[cfg(ossl110)]
+    pub const CT_PRECERT_SIGNER: Nid = Nid(ffi::NID_ct_precert_signer);
+    #[cfg(ossl110)]
+    pub const CT_CERT_SCTS: Nid = Nid(ffi::NID_ct_cert_scts);
     pub const WAP_WSG_IDM_ECID_WTLS1: Nid = Nid(ffi::NID_wap_wsg_idm_ecid_wtls1);
     pub const WAP_WSG_IDM_ECID_WTLS3: Nid = Nid(ffi::NID_wap_wsg_idm_ecid_wtls3);
     pub const WAP_WSG_IDM_ECID_WTLS4: Nid = Nid(ffi::NID_wap_wsg_idm_ecid_wtls4);
@@ -323,6 +331,9 @@ impl Nid {
     pub const ID_SMIME_AA_CONTENTREFERENCE: Nid = Nid(ffi::NID_id_smime_aa_contentReference);
     pub const ID_SMIME_AA_ENCRYPKEYPREF: Nid = Nid(ffi::NID_id_smime_aa_encrypKeyPref);
     pub const ID_SMIME_AA_SIGNINGCERTIFICATE: Nid = Nid(ffi::NID_id_smime_aa_signingCertificate);
+    #[cfg(ossl111)]
+    pub const ID_SMIME_AA_SIGNINGCERTIFICATEV2: Nid =
+        Nid(ffi::NID_id_smime_aa_signingCertificateV2);
     pub const ID_SMIME_AA_SMIMEENCRYPTCERTS: Nid = Nid(ffi::NID_id_smime_aa_smimeEncryptCerts);
     pub const ID_SMIME_AA_TIMESTAMPTOKEN: Nid = Nid(ffi::NID_id_smime_aa_timeStampToken);
     pub const ID_SMIME_AA_ETS_SIGPOLICYID: Nid = Nid(ffi::NID_id_smime_aa_ets_sigPolicyId);
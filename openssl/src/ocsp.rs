@@ -3,10 +3,13 @@ use foreign_types::ForeignTypeRef;
 use libc::{c_int, c_long, c_ulong};
 use std::mem;
 use std::ptr;
+#[cfg(ossl102)]
+use std::time::{Duration, SystemTime};
 
 use crate::asn1::Asn1GeneralizedTimeRef;
 use crate::error::ErrorStack;
 use crate::hash::MessageDigest;
+use crate::pkey::{HasPrivate, PKeyRef};
 use crate::stack::StackRef;
 use crate::util::ForeignTypeRefExt;
 use crate::x509::store::X509StoreRef;
@@ -107,6 +110,35 @@ impl OcspRevokedStatus {
     }
 }
 
+/// The result of comparing the nonces of an OCSP request and response.
+///
+/// Only `EQUAL` shows that the response was generated for the request. Responders are not
+/// required to echo nonces, so whether the other values are acceptable depends on the responder.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OcspNonceStatus(c_int);
+
+impl OcspNonceStatus {
+    /// The request and response contain the same nonce.
+    pub const EQUAL: OcspNonceStatus = OcspNonceStatus(1);
+    /// Neither the request nor the response contain a nonce.
+    pub const ABSENT: OcspNonceStatus = OcspNonceStatus(2);
+    /// Only the response contains a nonce.
+    pub const RESPONSE_ONLY: OcspNonceStatus = OcspNonceStatus(3);
+    /// Only the request contains a nonce.
+    pub const REQUEST_ONLY: OcspNonceStatus = OcspNonceStatus(-1);
+    /// The request and response contain different nonces.
+    pub const MISMATCH: OcspNonceStatus = OcspNonceStatus(0);
+
+    pub fn from_raw(raw: c_int) -> OcspNonceStatus {
+        OcspNonceStatus(raw)
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
 pub struct OcspStatus<'a> {
     /// The overall status of the response.
     pub status: OcspCertStatus,
@@ -140,6 +172,51 @@ impl<'a> OcspStatus<'a> {
             .map(|_| ())
         }
     }
+
+    /// Returns the `this_update` field as a `SystemTime`.
+    #[cfg(ossl102)]
+    pub fn this_update_time(&self) -> Result<SystemTime, ErrorStack> {
+        self.this_update.as_time().to_system_time()
+    }
+
+    /// Returns the `next_update` field as a `SystemTime`.
+    ///
+    /// Returns `None` if the response does not specify when newer information will be available.
+    #[cfg(ossl102)]
+    pub fn next_update_time(&self) -> Result<Option<SystemTime>, ErrorStack> {
+        // nextUpdate is optional, in which case the reference is null
+        if self.next_update.as_ptr().is_null() {
+            return Ok(None);
+        }
+        self.next_update.as_time().to_system_time().map(Some)
+    }
+
+    /// Returns how much longer the response may be cached.
+    ///
+    /// Following [RFC 5019], responses may be cached until their `next_update` time, and
+    /// responses without one must not be cached, in which case `None` is returned. `max_age` is
+    /// the remaining lifetime of the response according to the HTTP caching headers it was
+    /// delivered with, if any, and further limits the result. A lifetime of zero means the response
+    /// is stale and must be refreshed.
+    ///
+    /// [RFC 5019]: https://datatracker.ietf.org/doc/html/rfc5019#section-6
+    #[cfg(ossl102)]
+    pub fn cache_lifetime(
+        &self,
+        max_age: Option<Duration>,
+    ) -> Result<Option<Duration>, ErrorStack> {
+        let next_update = match self.next_update_time()? {
+            Some(next_update) => next_update,
+            None => return Ok(None),
+        };
+        let lifetime = next_update
+            .duration_since(SystemTime::now())
+            .unwrap_or_else(|_| Duration::from_secs(0));
+        match max_age {
+            Some(max_age) => Ok(Some(lifetime.min(max_age))),
+            None => Ok(Some(lifetime)),
+        }
+    }
 }
 
 foreign_type_and_impl_send_sync! {
@@ -197,7 +274,7 @@ impl OcspBasicResponseRef {
 
                 Some(OcspStatus {
                     status: OcspCertStatus(status),
-                    reason: OcspRevokedStatus(status),
+                    reason: OcspRevokedStatus(reason),
                     revocation_time,
                     this_update: Asn1GeneralizedTimeRef::from_ptr(this_update),
                     next_update: Asn1GeneralizedTimeRef::from_ptr(next_update),
@@ -339,6 +416,104 @@ impl OcspRequestRef {
             Ok(OcspOneReqRef::from_ptr_mut(ptr))
         }
     }
+
+    /// Adds a random 16 byte nonce extension to the request.
+    ///
+    /// Responders which support nonces echo it in their response, which can then be matched
+    /// against the request with [`check_nonce`] to show that the response is fresh.
+    ///
+    /// [`check_nonce`]: OcspRequestRef::check_nonce
+    #[corresponds(OCSP_request_add1_nonce)]
+    pub fn add_nonce(&mut self) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OCSP_request_add1_nonce(
+                self.as_ptr(),
+                ptr::null_mut(),
+                -1,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Adds a nonce extension containing `nonce` to the request.
+    ///
+    /// [RFC 8954] requires nonces to be between 1 and 32 bytes long.
+    ///
+    /// [RFC 8954]: https://datatracker.ietf.org/doc/html/rfc8954
+    #[corresponds(OCSP_request_add1_nonce)]
+    pub fn add_nonce_bytes(&mut self, nonce: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(nonce.len() <= c_int::MAX as usize);
+            cvt(ffi::OCSP_request_add1_nonce(
+                self.as_ptr(),
+                nonce.as_ptr() as *mut _,
+                nonce.len() as c_int,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Compares the nonce of the request with that of `response`.
+    #[corresponds(OCSP_check_nonce)]
+    pub fn check_nonce(&self, response: &OcspBasicResponseRef) -> OcspNonceStatus {
+        unsafe { OcspNonceStatus(ffi::OCSP_check_nonce(self.as_ptr(), response.as_ptr())) }
+    }
+
+    /// Signs the request, setting the requestor name to the subject of `signer`.
+    ///
+    /// `signer` and `certs` are included in the request unless `flags` contains `NO_CERTS`.
+    #[corresponds(OCSP_request_sign)]
+    pub fn sign<T>(
+        &mut self,
+        signer: &X509Ref,
+        key: &PKeyRef<T>,
+        digest: MessageDigest,
+        certs: Option<&StackRef<X509>>,
+        flags: OcspFlag,
+    ) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe {
+            cvt(ffi::OCSP_request_sign(
+                self.as_ptr(),
+                signer.as_ptr(),
+                key.as_ptr(),
+                digest.as_ptr(),
+                certs.map_or(ptr::null_mut(), |certs| certs.as_ptr()),
+                flags.bits(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Determines if the request is signed.
+    #[corresponds(OCSP_request_is_signed)]
+    pub fn is_signed(&self) -> bool {
+        unsafe { ffi::OCSP_request_is_signed(self.as_ptr()) == 1 }
+    }
+
+    /// Verifies the signature of the request and the certificate of its signer.
+    ///
+    /// The `certs` parameter contains a set of certificates that will be searched when locating
+    /// the signer's certificate, in addition to those included in the request.
+    #[corresponds(OCSP_request_verify)]
+    pub fn verify(
+        &self,
+        certs: &StackRef<X509>,
+        store: &X509StoreRef,
+        flags: OcspFlag,
+    ) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::OCSP_request_verify(
+                self.as_ptr(),
+                certs.as_ptr(),
+                store.as_ptr(),
+                flags.bits(),
+            ))
+            .map(|_| ())
+        }
+    }
 }
 
 foreign_type_and_impl_send_sync! {
@@ -348,3 +523,122 @@ foreign_type_and_impl_send_sync! {
     pub struct OcspOneReq;
     pub struct OcspOneReqRef;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ec::{EcGroup, EcKey};
+    use crate::nid::Nid;
+    use crate::pkey::PKey;
+    use crate::stack::Stack;
+    use crate::x509::ca::{CertificateAuthority, IssuanceProfile};
+    use crate::x509::self_signed::SelfSignedOptions;
+    use crate::x509::store::X509StoreBuilder;
+    use crate::x509::X509Name;
+    #[cfg(ossl102)]
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn nonce() {
+        let request = OcspRequest::from_der(include_bytes!("../test/ocsp_request.der")).unwrap();
+        let response = OcspResponse::from_der(include_bytes!("../test/ocsp_response.der")).unwrap();
+        let response = response.basic().unwrap();
+        assert_eq!(request.check_nonce(&response), OcspNonceStatus::EQUAL);
+
+        let mut request = OcspRequest::new().unwrap();
+        assert_eq!(
+            request.check_nonce(&response),
+            OcspNonceStatus::RESPONSE_ONLY
+        );
+        request
+            .add_nonce_bytes(&hex::decode("13cb2ffcc282e7cf63fa92e3be87361d").unwrap())
+            .unwrap();
+        assert_eq!(request.check_nonce(&response), OcspNonceStatus::EQUAL);
+
+        let mut request = OcspRequest::new().unwrap();
+        request.add_nonce().unwrap();
+        assert_eq!(request.check_nonce(&response), OcspNonceStatus::MISMATCH);
+    }
+
+    #[test]
+    #[cfg(ossl102)]
+    fn status_validity() {
+        let cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        let issuer = X509::from_pem(include_bytes!("../test/root-ca.pem")).unwrap();
+        let id = OcspCertId::from_cert(MessageDigest::sha1(), &cert, &issuer).unwrap();
+
+        let response = OcspResponse::from_der(include_bytes!("../test/ocsp_response.der")).unwrap();
+        let response = response.basic().unwrap();
+        let status = response.find_status(&id).unwrap();
+        assert_eq!(status.status, OcspCertStatus::GOOD);
+        assert_eq!(status.reason, OcspRevokedStatus::NO_STATUS);
+        assert!(status.revocation_time.is_none());
+        let this_update = status.this_update_time().unwrap();
+        let next_update = status.next_update_time().unwrap().unwrap();
+        assert!(
+            this_update.duration_since(UNIX_EPOCH).unwrap() > Duration::from_secs(1_790_000_000)
+        );
+        assert!(
+            next_update.duration_since(this_update).unwrap() > Duration::from_secs(36_499 * 86_400)
+        );
+        let lifetime = status.cache_lifetime(None).unwrap().unwrap();
+        assert!(lifetime > Duration::from_secs(365 * 86_400));
+        let max_age = Duration::from_secs(3600);
+        assert_eq!(status.cache_lifetime(Some(max_age)).unwrap(), Some(max_age));
+
+        let response =
+            OcspResponse::from_der(include_bytes!("../test/ocsp_response_no_next_update.der"))
+                .unwrap();
+        let response = response.basic().unwrap();
+        let status = response.find_status(&id).unwrap();
+        assert!(status.next_update_time().unwrap().is_none());
+        assert_eq!(status.cache_lifetime(Some(max_age)).unwrap(), None);
+    }
+
+    #[test]
+    fn signed_request() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let ca_key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let mut name = X509Name::builder().unwrap();
+        name.append_entry_by_nid(Nid::COMMONNAME, "Test CA")
+            .unwrap();
+        let ca_cert =
+            X509::self_signed(&name.build(), &ca_key, SelfSignedOptions::new().ca()).unwrap();
+        let mut ca = CertificateAuthority::new(ca_cert.clone(), ca_key).unwrap();
+
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let mut name = X509Name::builder().unwrap();
+        name.append_entry_by_nid(Nid::COMMONNAME, "requestor")
+            .unwrap();
+        let cert = ca
+            .issue(&name.build(), &key, &IssuanceProfile::new())
+            .unwrap();
+
+        let mut request = OcspRequest::new().unwrap();
+        request
+            .add_id(OcspCertId::from_cert(MessageDigest::sha1(), &cert, &ca_cert).unwrap())
+            .unwrap();
+        request.add_nonce().unwrap();
+        assert!(!request.is_signed());
+        request
+            .sign(
+                &cert,
+                &key,
+                MessageDigest::sha256(),
+                None,
+                OcspFlag::empty(),
+            )
+            .unwrap();
+        assert!(request.is_signed());
+
+        let request = OcspRequest::from_der(&request.to_der().unwrap()).unwrap();
+        assert!(request.is_signed());
+        let certs = Stack::new().unwrap();
+        let store = X509StoreBuilder::new().unwrap().build();
+        assert!(request.verify(&certs, &store, OcspFlag::empty()).is_err());
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(ca_cert).unwrap();
+        let store = store.build();
+        request.verify(&certs, &store, OcspFlag::empty()).unwrap();
+    }
+}
@@ -0,0 +1,600 @@
+//! Oblivious HTTP.
+//!
+//! Oblivious HTTP, specified in [RFC 9458], lets a client send an HTTP request to a gateway
+//! through a relay, without the relay seeing the request or the gateway learning who sent it. The
+//! client encrypts the request to a public key published by the gateway in a [`KeyConfig`], and
+//! the gateway encrypts the response with a key derived from the request, using [HPKE].
+//!
+//! A [`ClientRequest`] encapsulates a request and returns a [`ClientResponse`] which decapsulates
+//! the matching response. A [`Server`] holds the gateway's private key, decapsulates requests,
+//! and returns a [`ServerResponse`] for each one which encapsulates the response. The requests
+//! and responses are opaque bytes, normally Binary HTTP messages as specified in [RFC 9292],
+//! which are encoded and decoded by the caller.
+//!
+//! Requires OpenSSL 3.2.0 or newer.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::hpke::{Aead, Kdf, Kem};
+//! use openssl::ohttp::{ClientRequest, KeyConfig, Server};
+//!
+//! // the gateway publishes its key configuration
+//! let (config, private_key) = KeyConfig::generate(
+//!     1,
+//!     Kem::DHKEM_X25519_HKDF_SHA256,
+//!     vec![(Kdf::HKDF_SHA256, Aead::AES_128_GCM)],
+//! )
+//! .unwrap();
+//! let keys = KeyConfig::list_to_bytes(&[config.clone()]);
+//! let server = Server::new(config, private_key);
+//!
+//! // the client encapsulates a request with it
+//! let config = KeyConfig::list_from_bytes(&keys).unwrap().remove(0);
+//! let (enc_request, client_response) = ClientRequest::new(&config)
+//!     .unwrap()
+//!     .encapsulate(b"request")
+//!     .unwrap();
+//!
+//! // the gateway decapsulates the request and encapsulates the response
+//! let (request, server_response) = server.decapsulate(&enc_request).unwrap();
+//! assert_eq!(request, b"request");
+//! let enc_response = server_response.encapsulate(b"response").unwrap();
+//!
+//! let response = client_response.decapsulate(&enc_response).unwrap();
+//! assert_eq!(response, b"response");
+//! ```
+//!
+//! [RFC 9458]: https://www.rfc-editor.org/rfc/rfc9458
+//! [RFC 9292]: https://www.rfc-editor.org/rfc/rfc9292
+//! [HPKE]: crate::hpke
+use std::cmp;
+
+use crate::error::ErrorStack;
+use crate::hpke::{Aead, Kdf, Kem, Mode, Recipient, Sender, Suite};
+use crate::kdf::{hkdf_expand, hkdf_extract};
+use crate::md::{Md, MdRef};
+use crate::pkey::{PKey, Private};
+use crate::rand::rand_bytes;
+use crate::symm::{self, Cipher};
+
+const REQUEST_LABEL: &[u8] = b"message/bhttp request";
+const RESPONSE_LABEL: &[u8] = b"message/bhttp response";
+
+// the length of the header of an encapsulated request
+const HEADER_LEN: usize = 7;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+invalid_input_error! {
+    /// An error encoding or decoding an Oblivious HTTP message.
+    ///
+    /// A message which fails to decrypt is reported as an OpenSSL error.
+    pub struct OhttpError => "invalid Oblivious HTTP message: {}";
+}
+
+/// A gateway's public key and the algorithms it accepts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyConfig {
+    key_id: u8,
+    kem: Kem,
+    public_key: Vec<u8>,
+    algorithms: Vec<(Kdf, Aead)>,
+}
+
+impl KeyConfig {
+    /// Creates a key configuration from an encoded HPKE public key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `algorithms` is empty.
+    pub fn new(key_id: u8, kem: Kem, public_key: Vec<u8>, algorithms: Vec<(Kdf, Aead)>) -> Self {
+        assert!(!algorithms.is_empty(), "no algorithms in key configuration");
+        KeyConfig {
+            key_id,
+            kem,
+            public_key,
+            algorithms,
+        }
+    }
+
+    /// Generates a key pair for `kem`, returning its key configuration and the private key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `algorithms` is empty.
+    pub fn generate(
+        key_id: u8,
+        kem: Kem,
+        algorithms: Vec<(Kdf, Aead)>,
+    ) -> Result<(KeyConfig, PKey<Private>), ErrorStack> {
+        let (public_key, private_key) = kem_suite(kem).generate_key()?;
+        Ok((
+            KeyConfig::new(key_id, kem, public_key, algorithms),
+            private_key,
+        ))
+    }
+
+    /// Decodes a single key configuration.
+    pub fn from_bytes(bytes: &[u8]) -> Result<KeyConfig, OhttpError> {
+        let mut reader = Reader(bytes);
+        let config = KeyConfig::read(&mut reader)?;
+        reader.finish()?;
+        Ok(config)
+    }
+
+    /// Decodes a list of key configurations in the `application/ohttp-keys` format.
+    ///
+    /// Configurations with a KEM which the linked OpenSSL does not support are skipped, as the
+    /// RFC requires.
+    pub fn list_from_bytes(bytes: &[u8]) -> Result<Vec<KeyConfig>, OhttpError> {
+        let mut reader = Reader(bytes);
+        let mut configs = vec![];
+        while !reader.0.is_empty() {
+            let len = reader.read_u16()?;
+            let mut config = Reader(reader.read(usize::from(len))?);
+            if kem_suite(Kem::from_raw(config.peek_kem()?)).enc_len() == 0 {
+                continue;
+            }
+            configs.push(KeyConfig::read(&mut config)?);
+            config.finish()?;
+        }
+        Ok(configs)
+    }
+
+    /// Encodes the key configuration.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.key_id];
+        bytes.extend_from_slice(&self.kem.as_raw().to_be_bytes());
+        bytes.extend_from_slice(&self.public_key);
+        let len = self.algorithms.len() * 4;
+        bytes.extend_from_slice(&(len as u16).to_be_bytes());
+        for (kdf, aead) in &self.algorithms {
+            bytes.extend_from_slice(&kdf.as_raw().to_be_bytes());
+            bytes.extend_from_slice(&aead.as_raw().to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Encodes a list of key configurations in the `application/ohttp-keys` format.
+    pub fn list_to_bytes(configs: &[KeyConfig]) -> Vec<u8> {
+        let mut bytes = vec![];
+        for config in configs {
+            let config = config.to_bytes();
+            bytes.extend_from_slice(&(config.len() as u16).to_be_bytes());
+            bytes.extend_from_slice(&config);
+        }
+        bytes
+    }
+
+    /// Returns the identifier of the key.
+    pub fn key_id(&self) -> u8 {
+        self.key_id
+    }
+
+    /// Returns the KEM of the key.
+    pub fn kem(&self) -> Kem {
+        self.kem
+    }
+
+    /// Returns the encoded public key.
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// Returns the KDFs and AEADs which the gateway accepts, in order of preference.
+    pub fn algorithms(&self) -> &[(Kdf, Aead)] {
+        &self.algorithms
+    }
+
+    fn read(reader: &mut Reader<'_>) -> Result<KeyConfig, OhttpError> {
+        let key_id = reader.read_u8()?;
+        let kem = Kem::from_raw(reader.read_u16()?);
+        let public_key_len = kem_suite(kem).enc_len();
+        if public_key_len == 0 {
+            return Err(OhttpError::invalid(format!(
+                "unsupported KEM {:#06x}",
+                kem.as_raw()
+            )));
+        }
+        let public_key = reader.read(public_key_len)?.to_vec();
+
+        let len = usize::from(reader.read_u16()?);
+        if len == 0 || len % 4 != 0 {
+            return Err(OhttpError::invalid("invalid algorithms length"));
+        }
+        let mut algorithms = Reader(reader.read(len)?);
+        let mut config = KeyConfig {
+            key_id,
+            kem,
+            public_key,
+            algorithms: vec![],
+        };
+        while !algorithms.0.is_empty() {
+            let kdf = Kdf::from_raw(algorithms.read_u16()?);
+            let aead = Aead::from_raw(algorithms.read_u16()?);
+            config.algorithms.push((kdf, aead));
+        }
+        Ok(config)
+    }
+
+    fn suite(&self, kdf: Kdf, aead: Aead) -> Suite {
+        Suite::new(self.kem, kdf, aead)
+    }
+}
+
+/// A request being encapsulated by a client.
+pub struct ClientRequest {
+    config: KeyConfig,
+    suite: Suite,
+}
+
+impl ClientRequest {
+    /// Creates a request to the gateway of `config`, using the first of its algorithms which is
+    /// supported.
+    pub fn new(config: &KeyConfig) -> Result<ClientRequest, OhttpError> {
+        for &(kdf, aead) in &config.algorithms {
+            if let Ok(request) = ClientRequest::with_algorithms(config, kdf, aead) {
+                return Ok(request);
+            }
+        }
+        Err(OhttpError::invalid("no supported algorithms"))
+    }
+
+    /// Creates a request to the gateway of `config`, using a specific KDF and AEAD.
+    pub fn with_algorithms(
+        config: &KeyConfig,
+        kdf: Kdf,
+        aead: Aead,
+    ) -> Result<ClientRequest, OhttpError> {
+        if !config.algorithms.contains(&(kdf, aead)) {
+            return Err(OhttpError::invalid(
+                "algorithms not accepted by the gateway",
+            ));
+        }
+        let suite = config.suite(kdf, aead);
+        response_params(suite)?;
+        suite.check()?;
+        Ok(ClientRequest {
+            config: config.clone(),
+            suite,
+        })
+    }
+
+    /// Encapsulates a request, returning the encapsulated request and the state needed to
+    /// decapsulate its response.
+    pub fn encapsulate(self, request: &[u8]) -> Result<(Vec<u8>, ClientResponse), OhttpError> {
+        let header = header(self.config.key_id, self.suite);
+        let mut sender = Sender::new(Mode::BASE, self.suite)?;
+        let enc = sender.encap(&self.config.public_key, &info(&header))?;
+        let ciphertext = sender.seal(&[], request)?;
+        let secret = export_secret(self.suite, |label, secret| sender.export(label, secret))?;
+
+        let mut enc_request = header;
+        enc_request.extend_from_slice(&enc);
+        enc_request.extend_from_slice(&ciphertext);
+        let response = ClientResponse {
+            suite: self.suite,
+            enc,
+            secret,
+        };
+        Ok((enc_request, response))
+    }
+}
+
+/// The state a client needs to decapsulate the response to a request.
+pub struct ClientResponse {
+    suite: Suite,
+    enc: Vec<u8>,
+    secret: Vec<u8>,
+}
+
+impl ClientResponse {
+    /// Decapsulates the response.
+    pub fn decapsulate(self, enc_response: &[u8]) -> Result<Vec<u8>, OhttpError> {
+        let (_, key_len, _) = response_params(self.suite)?;
+        let nonce_len = cmp::max(NONCE_LEN, key_len);
+        if enc_response.len() < nonce_len + TAG_LEN {
+            return Err(OhttpError::invalid("response too short"));
+        }
+        let (response_nonce, ciphertext) = enc_response.split_at(nonce_len);
+        let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - TAG_LEN);
+
+        let (cipher, key, nonce) =
+            response_key(self.suite, &self.enc, &self.secret, response_nonce)?;
+        let response = symm::decrypt_aead(cipher, &key, Some(&nonce), &[], ciphertext, tag)?;
+        Ok(response)
+    }
+}
+
+/// A gateway which decapsulates requests.
+pub struct Server {
+    config: KeyConfig,
+    private_key: PKey<Private>,
+}
+
+impl Server {
+    /// Creates a gateway from its key configuration and the corresponding private key.
+    pub fn new(config: KeyConfig, private_key: PKey<Private>) -> Server {
+        Server {
+            config,
+            private_key,
+        }
+    }
+
+    /// Returns the gateway's key configuration.
+    pub fn config(&self) -> &KeyConfig {
+        &self.config
+    }
+
+    /// Decapsulates a request, returning the request and the state needed to encapsulate its
+    /// response.
+    pub fn decapsulate(&self, enc_request: &[u8]) -> Result<(Vec<u8>, ServerResponse), OhttpError> {
+        let mut reader = Reader(enc_request);
+        let key_id = reader.read_u8()?;
+        let kem = Kem::from_raw(reader.read_u16()?);
+        let kdf = Kdf::from_raw(reader.read_u16()?);
+        let aead = Aead::from_raw(reader.read_u16()?);
+        if key_id != self.config.key_id || kem != self.config.kem {
+            return Err(OhttpError::invalid("unknown key"));
+        }
+        if !self.config.algorithms.contains(&(kdf, aead)) {
+            return Err(OhttpError::invalid("unsupported algorithms"));
+        }
+        let suite = self.config.suite(kdf, aead);
+        response_params(suite)?;
+        let enc = reader.read(suite.enc_len())?;
+        let ciphertext = reader.0;
+
+        let header = &enc_request[..HEADER_LEN];
+        let mut recipient = Recipient::new(Mode::BASE, suite)?;
+        recipient.decap(enc, &self.private_key, &info(header))?;
+        let request = recipient.open(&[], ciphertext)?;
+        let secret = export_secret(suite, |label, secret| recipient.export(label, secret))?;
+
+        let response = ServerResponse {
+            suite,
+            enc: enc.to_vec(),
+            secret,
+        };
+        Ok((request, response))
+    }
+}
+
+/// The state a gateway needs to encapsulate the response to a request.
+pub struct ServerResponse {
+    suite: Suite,
+    enc: Vec<u8>,
+    secret: Vec<u8>,
+}
+
+impl ServerResponse {
+    /// Encapsulates the response.
+    pub fn encapsulate(self, response: &[u8]) -> Result<Vec<u8>, OhttpError> {
+        let (_, key_len, _) = response_params(self.suite)?;
+        let mut enc_response = vec![0; cmp::max(NONCE_LEN, key_len)];
+        rand_bytes(&mut enc_response)?;
+
+        let (cipher, key, nonce) =
+            response_key(self.suite, &self.enc, &self.secret, &enc_response)?;
+        let mut tag = [0; TAG_LEN];
+        let ciphertext = symm::encrypt_aead(cipher, &key, Some(&nonce), &[], response, &mut tag)?;
+        enc_response.extend_from_slice(&ciphertext);
+        enc_response.extend_from_slice(&tag);
+        Ok(enc_response)
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn read(&mut self, len: usize) -> Result<&'a [u8], OhttpError> {
+        if self.0.len() < len {
+            return Err(OhttpError::invalid("unexpected end of message"));
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, OhttpError> {
+        Ok(self.read(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, OhttpError> {
+        let bytes = self.read(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn peek_kem(&self) -> Result<u16, OhttpError> {
+        let mut reader = Reader(self.0);
+        reader.read_u8()?;
+        reader.read_u16()
+    }
+
+    fn finish(&self) -> Result<(), OhttpError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(OhttpError::invalid("trailing data"))
+        }
+    }
+}
+
+// a suite whose only meaningful algorithm is the KEM
+fn kem_suite(kem: Kem) -> Suite {
+    Suite::new(kem, Kdf::HKDF_SHA256, Aead::AES_128_GCM)
+}
+
+fn header(key_id: u8, suite: Suite) -> Vec<u8> {
+    let mut header = vec![key_id];
+    header.extend_from_slice(&suite.kem().as_raw().to_be_bytes());
+    header.extend_from_slice(&suite.kdf().as_raw().to_be_bytes());
+    header.extend_from_slice(&suite.aead().as_raw().to_be_bytes());
+    header
+}
+
+fn info(header: &[u8]) -> Vec<u8> {
+    let mut info = REQUEST_LABEL.to_vec();
+    info.push(0);
+    info.extend_from_slice(header);
+    info
+}
+
+// the digest of the KDF, and the cipher and key length of the AEAD
+fn response_params(suite: Suite) -> Result<(&'static MdRef, usize, Cipher), OhttpError> {
+    let md = match suite.kdf() {
+        Kdf::HKDF_SHA256 => Md::sha256(),
+        Kdf::HKDF_SHA384 => Md::sha384(),
+        Kdf::HKDF_SHA512 => Md::sha512(),
+        _ => return Err(OhttpError::invalid("unsupported KDF")),
+    };
+    let (key_len, cipher) = match suite.aead() {
+        Aead::AES_128_GCM => (16, Cipher::aes_128_gcm()),
+        Aead::AES_256_GCM => (32, Cipher::aes_256_gcm()),
+        #[cfg(not(osslconf = "OPENSSL_NO_CHACHA"))]
+        Aead::CHACHA20_POLY1305 => (32, Cipher::chacha20_poly1305()),
+        _ => return Err(OhttpError::invalid("unsupported AEAD")),
+    };
+    Ok((md, key_len, cipher))
+}
+
+fn export_secret<F>(suite: Suite, export: F) -> Result<Vec<u8>, OhttpError>
+where
+    F: FnOnce(&[u8], &mut [u8]) -> Result<(), ErrorStack>,
+{
+    let (_, key_len, _) = response_params(suite)?;
+    let mut secret = vec![0; cmp::max(NONCE_LEN, key_len)];
+    export(RESPONSE_LABEL, &mut secret)?;
+    Ok(secret)
+}
+
+fn response_key(
+    suite: Suite,
+    enc: &[u8],
+    secret: &[u8],
+    response_nonce: &[u8],
+) -> Result<(Cipher, Vec<u8>, Vec<u8>), OhttpError> {
+    let (md, key_len, cipher) = response_params(suite)?;
+    let mut salt = enc.to_vec();
+    salt.extend_from_slice(response_nonce);
+    let prk = hkdf_extract(md, secret, &salt)?;
+
+    let mut key = vec![0; key_len];
+    hkdf_expand(md, &prk, b"key", &mut key)?;
+    let mut nonce = vec![0; NONCE_LEN];
+    hkdf_expand(md, &prk, b"nonce", &mut nonce)?;
+    Ok((cipher, key, nonce))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::slice;
+
+    fn server(kem: Kem, algorithms: Vec<(Kdf, Aead)>) -> Server {
+        let (config, private_key) = KeyConfig::generate(7, kem, algorithms).unwrap();
+        Server::new(config, private_key)
+    }
+
+    #[test]
+    fn round_trip() {
+        let algorithms = vec![
+            (Kdf::HKDF_SHA256, Aead::AES_128_GCM),
+            (Kdf::HKDF_SHA384, Aead::AES_256_GCM),
+            (Kdf::HKDF_SHA512, Aead::CHACHA20_POLY1305),
+        ];
+        let kems = [
+            Kem::DHKEM_P256_HKDF_SHA256,
+            Kem::DHKEM_P384_HKDF_SHA384,
+            Kem::DHKEM_P521_HKDF_SHA512,
+            Kem::DHKEM_X25519_HKDF_SHA256,
+            Kem::DHKEM_X448_HKDF_SHA512,
+        ];
+
+        for &kem in &kems {
+            let server = server(kem, algorithms.clone());
+            let config = server.config();
+            assert_eq!(config.public_key().len(), kem_suite(kem).enc_len());
+            assert_eq!(&KeyConfig::from_bytes(&config.to_bytes()).unwrap(), config);
+
+            for &(kdf, aead) in &algorithms {
+                let request = ClientRequest::with_algorithms(config, kdf, aead).unwrap();
+                let (enc_request, client_response) = request.encapsulate(b"request").unwrap();
+                assert_eq!(&enc_request[..3], &[7, 0, kem.as_raw() as u8]);
+
+                let (request, server_response) = server.decapsulate(&enc_request).unwrap();
+                assert_eq!(request, b"request");
+
+                let enc_response = server_response.encapsulate(b"response").unwrap();
+                let response = client_response.decapsulate(&enc_response).unwrap();
+                assert_eq!(response, b"response");
+            }
+        }
+    }
+
+    #[test]
+    fn tampered() {
+        let server = server(
+            Kem::DHKEM_X25519_HKDF_SHA256,
+            vec![(Kdf::HKDF_SHA256, Aead::AES_128_GCM)],
+        );
+        let (mut enc_request, client_response) = ClientRequest::new(server.config())
+            .unwrap()
+            .encapsulate(b"request")
+            .unwrap();
+
+        let last = enc_request.len() - 1;
+        enc_request[last] ^= 1;
+        assert!(server
+            .decapsulate(&enc_request)
+            .err()
+            .unwrap()
+            .ssl_error()
+            .is_some());
+        enc_request[last] ^= 1;
+        enc_request[0] = 8;
+        assert!(server
+            .decapsulate(&enc_request)
+            .err()
+            .unwrap()
+            .ssl_error()
+            .is_none());
+        enc_request[0] = 7;
+
+        let (_, server_response) = server.decapsulate(&enc_request).unwrap();
+        let mut enc_response = server_response.encapsulate(b"response").unwrap();
+        enc_response[0] ^= 1;
+        assert!(client_response.decapsulate(&enc_response).is_err());
+    }
+
+    #[test]
+    fn key_configs() {
+        let public_key = vec![0x42; 32];
+        let config = KeyConfig::new(
+            1,
+            Kem::DHKEM_X25519_HKDF_SHA256,
+            public_key.clone(),
+            vec![
+                (Kdf::HKDF_SHA256, Aead::AES_128_GCM),
+                (Kdf::HKDF_SHA256, Aead::CHACHA20_POLY1305),
+            ],
+        );
+
+        let mut expected = vec![1, 0x00, 0x20];
+        expected.extend_from_slice(&public_key);
+        expected.extend_from_slice(&[0, 8, 0, 1, 0, 1, 0, 1, 0, 3]);
+        assert_eq!(config.to_bytes(), expected);
+        assert_eq!(KeyConfig::from_bytes(&expected).unwrap(), config);
+
+        let mut list = KeyConfig::list_to_bytes(slice::from_ref(&config));
+        assert_eq!(&list[..2], &[0, expected.len() as u8]);
+        // a configuration with an unknown KEM is skipped
+        list.extend_from_slice(&[0, 9, 2, 0x7f, 0x7f, 0, 4, 0, 1, 0, 1]);
+        assert_eq!(KeyConfig::list_from_bytes(&list).unwrap(), vec![config]);
+
+        assert!(KeyConfig::from_bytes(&expected[..expected.len() - 1]).is_err());
+        expected.push(0);
+        assert!(KeyConfig::from_bytes(&expected).is_err());
+    }
+}
@@ -0,0 +1,52 @@
+//! Octet key pair curves, as named by JSON Web Keys and COSE keys.
+use crate::pkey::Id;
+
+pub(crate) struct OkpCurve {
+    pub(crate) id: Id,
+    /// The `crv` name registered for JOSE by RFC 8037.
+    #[cfg_attr(not(feature = "jwk"), allow(dead_code))]
+    pub(crate) jwk: &'static str,
+    /// The `crv` value registered for COSE by RFC 9053.
+    pub(crate) cose: i64,
+}
+
+const CURVES: &[OkpCurve] = &[
+    OkpCurve {
+        id: Id::X25519,
+        jwk: "X25519",
+        cose: 4,
+    },
+    #[cfg(ossl111)]
+    OkpCurve {
+        id: Id::X448,
+        jwk: "X448",
+        cose: 5,
+    },
+    OkpCurve {
+        id: Id::ED25519,
+        jwk: "Ed25519",
+        cose: 6,
+    },
+    #[cfg(ossl111)]
+    OkpCurve {
+        id: Id::ED448,
+        jwk: "Ed448",
+        cose: 7,
+    },
+];
+
+/// Returns the curve of an octet key pair of type `id`.
+pub(crate) fn from_id(id: Id) -> Option<&'static OkpCurve> {
+    CURVES.iter().find(|c| c.id == id)
+}
+
+/// Returns the curve with the JOSE name `crv`.
+#[cfg(feature = "jwk")]
+pub(crate) fn from_jwk(crv: &str) -> Option<&'static OkpCurve> {
+    CURVES.iter().find(|c| c.jwk == crv)
+}
+
+/// Returns the curve with the COSE identifier `crv`.
+pub(crate) fn from_cose(crv: i64) -> Option<&'static OkpCurve> {
+    CURVES.iter().find(|c| c.cose == crv)
+}
@@ -0,0 +1,178 @@
+//! Message digests, HMACs, and signatures, like `openssl dgst`.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::hash::MessageDigest;
+//! use openssl::ops::dgst::Dgst;
+//!
+//! // openssl dgst -sha256
+//! let digest = Dgst::new(MessageDigest::sha256()).digest(&b"hello"[..]).unwrap();
+//! assert_eq!(digest.len(), 32);
+//!
+//! // openssl dgst -sha256 -hmac secret
+//! let mac = Dgst::new(MessageDigest::sha256())
+//!     .hmac(b"secret")
+//!     .digest(&b"hello"[..])
+//!     .unwrap();
+//! assert_eq!(mac.len(), 32);
+//! ```
+use std::io::Read;
+
+use crate::hash::{Hasher, MessageDigest};
+use crate::ops::{for_each_chunk, OpsError};
+use crate::pkey::{HasPrivate, HasPublic, Id, PKey, PKeyRef};
+use crate::sign::{Signer, Verifier};
+
+/// The options of a digest operation.
+pub struct Dgst {
+    digest: MessageDigest,
+    hmac_key: Option<Vec<u8>>,
+}
+
+impl Dgst {
+    /// Creates a new `Dgst` using `digest`, like `-sha256`.
+    pub fn new(digest: MessageDigest) -> Dgst {
+        Dgst {
+            digest,
+            hmac_key: None,
+        }
+    }
+
+    /// Computes an HMAC with `key` instead of a plain digest, like `-hmac`.
+    pub fn hmac(&mut self, key: &[u8]) -> &mut Dgst {
+        self.hmac_key = Some(key.to_vec());
+        self
+    }
+
+    /// Returns the digest, or HMAC, of `input`.
+    pub fn digest<R>(&self, input: R) -> Result<Vec<u8>, OpsError>
+    where
+        R: Read,
+    {
+        match &self.hmac_key {
+            Some(key) => {
+                let key = PKey::hmac(key)?;
+                let mut signer = Signer::new(self.digest, &key)?;
+                for_each_chunk(input, |chunk| signer.update(chunk))?;
+                Ok(signer.sign_to_vec()?)
+            }
+            None => {
+                let mut hasher = Hasher::new(self.digest)?;
+                for_each_chunk(input, |chunk| hasher.update(chunk))?;
+                Ok(hasher.finish()?.to_vec())
+            }
+        }
+    }
+
+    /// Signs `input` with `key`, like `-sign`.
+    ///
+    /// Ed25519 and Ed448 keys sign the input directly, so the digest is ignored for them and the
+    /// input is read into memory.
+    pub fn sign<T, R>(&self, key: &PKeyRef<T>, input: R) -> Result<Vec<u8>, OpsError>
+    where
+        T: HasPrivate,
+        R: Read,
+    {
+        if is_raw(key.id()) {
+            let data = read_all(input)?;
+            let mut signer = Signer::new_without_digest(key)?;
+            return Ok(signer.sign_oneshot_to_vec(&data)?);
+        }
+
+        let mut signer = Signer::new(self.digest, key)?;
+        for_each_chunk(input, |chunk| signer.update(chunk))?;
+        Ok(signer.sign_to_vec()?)
+    }
+
+    /// Verifies the `signature` of `input` with `key`, like `-verify`.
+    ///
+    /// Returns `false` if the signature is invalid.
+    pub fn verify<T, R>(
+        &self,
+        key: &PKeyRef<T>,
+        input: R,
+        signature: &[u8],
+    ) -> Result<bool, OpsError>
+    where
+        T: HasPublic,
+        R: Read,
+    {
+        if is_raw(key.id()) {
+            let data = read_all(input)?;
+            let mut verifier = Verifier::new_without_digest(key)?;
+            return Ok(verifier.verify_oneshot(signature, &data)?);
+        }
+
+        let mut verifier = Verifier::new(self.digest, key)?;
+        for_each_chunk(input, |chunk| verifier.update(chunk))?;
+        Ok(verifier.verify(signature)?)
+    }
+}
+
+fn is_raw(id: Id) -> bool {
+    match id {
+        #[cfg(any(ossl111, boringssl, libressl370))]
+        Id::ED25519 => true,
+        #[cfg(ossl111)]
+        Id::ED448 => true,
+        _ => false,
+    }
+}
+
+fn read_all<R>(mut input: R) -> Result<Vec<u8>, OpsError>
+where
+    R: Read,
+{
+    let mut data = vec![];
+    input.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rsa::Rsa;
+
+    #[test]
+    fn digest() {
+        let digest = Dgst::new(MessageDigest::sha256())
+            .digest(&b"abc"[..])
+            .unwrap();
+        assert_eq!(
+            hex::encode(digest),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn hmac() {
+        // RFC 4231 test case 2
+        let mac = Dgst::new(MessageDigest::sha256())
+            .hmac(b"Jefe")
+            .digest(&b"what do ya want for nothing?"[..])
+            .unwrap();
+        assert_eq!(
+            hex::encode(mac),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn sign_verify() {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let dgst = Dgst::new(MessageDigest::sha256());
+        let signature = dgst.sign(&key, &b"hello"[..]).unwrap();
+        assert!(dgst.verify(&key, &b"hello"[..], &signature).unwrap());
+        assert!(!dgst.verify(&key, &b"goodbye"[..], &signature).unwrap());
+    }
+
+    #[test]
+    #[cfg(any(ossl111, boringssl, libressl370))]
+    fn sign_verify_ed25519() {
+        let key = PKey::generate_ed25519().unwrap();
+        let dgst = Dgst::new(MessageDigest::sha256());
+        let signature = dgst.sign(&key, &b"hello"[..]).unwrap();
+        assert!(dgst.verify(&key, &b"hello"[..], &signature).unwrap());
+    }
+}
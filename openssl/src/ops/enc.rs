@@ -0,0 +1,203 @@
+//! Password-based encryption in the format of `openssl enc`.
+//!
+//! Data encrypted with [`Enc`] can be decrypted with `openssl enc -d` given the same options, and
+//! vice versa. By default the key and IV are derived with PBKDF2, as with
+//! `openssl enc -pbkdf2`, and the output starts with the `Salted__` magic followed by the salt.
+//!
+//! The format does not authenticate the ciphertext, so AEAD ciphers are not supported, just as
+//! they are not by `openssl enc`.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::ops::enc::Enc;
+//! use openssl::symm::Cipher;
+//!
+//! // openssl enc -aes-256-cbc -pbkdf2 -pass pass:hunter2
+//! let enc = Enc::new(Cipher::aes_256_cbc());
+//! let ciphertext = enc.encrypt(b"hunter2", b"secret").unwrap();
+//! assert_eq!(enc.decrypt(b"hunter2", &ciphertext).unwrap(), b"secret");
+//! ```
+use crate::hash::MessageDigest;
+use crate::ops::OpsError;
+#[cfg(not(boringssl))]
+use crate::pkcs5::bytes_to_key;
+use crate::pkcs5::pbkdf2_hmac;
+use crate::rand::rand_bytes;
+use crate::symm::{self, Cipher};
+
+const MAGIC: &[u8] = b"Salted__";
+const SALT_LEN: usize = 8;
+const DEFAULT_ITER: u32 = 10000;
+
+#[derive(Copy, Clone)]
+enum Kdf {
+    Pbkdf2(u32),
+    #[cfg(not(boringssl))]
+    BytesToKey,
+}
+
+/// The options of a password-based encryption operation.
+pub struct Enc {
+    cipher: Cipher,
+    digest: MessageDigest,
+    kdf: Kdf,
+    salt: bool,
+}
+
+impl Enc {
+    /// Creates a new `Enc` using `cipher`, like `-aes-256-cbc`.
+    ///
+    /// The key and IV are derived with 10000 iterations of PBKDF2 using HMAC-SHA256, the defaults
+    /// of `openssl enc -pbkdf2`.
+    pub fn new(cipher: Cipher) -> Enc {
+        Enc {
+            cipher,
+            digest: MessageDigest::sha256(),
+            kdf: Kdf::Pbkdf2(DEFAULT_ITER),
+            salt: true,
+        }
+    }
+
+    /// Sets the number of PBKDF2 iterations, like `-iter`.
+    pub fn iter(&mut self, iter: u32) -> &mut Enc {
+        self.kdf = Kdf::Pbkdf2(iter);
+        self
+    }
+
+    /// Derives the key and IV with `EVP_BytesToKey` instead of PBKDF2, as `openssl enc` does when
+    /// neither `-pbkdf2` nor `-iter` is given.
+    ///
+    /// This derivation is weak, and should only be used to decrypt existing data.
+    #[cfg(not(boringssl))]
+    pub fn bytes_to_key(&mut self) -> &mut Enc {
+        self.kdf = Kdf::BytesToKey;
+        self
+    }
+
+    /// Sets the digest used to derive the key and IV, like `-md`.
+    ///
+    /// Defaults to SHA-256.
+    pub fn digest(&mut self, digest: MessageDigest) -> &mut Enc {
+        self.digest = digest;
+        self
+    }
+
+    /// Derives the key and IV without a salt, like `-nosalt`.
+    ///
+    /// The output then consists of the ciphertext alone.
+    pub fn no_salt(&mut self) -> &mut Enc {
+        self.salt = false;
+        self
+    }
+
+    /// Encrypts `data` with a key derived from `pass`.
+    pub fn encrypt(&self, pass: &[u8], data: &[u8]) -> Result<Vec<u8>, OpsError> {
+        let mut out = vec![];
+        let mut salt = [0; SALT_LEN];
+        if self.salt {
+            rand_bytes(&mut salt)?;
+            out.extend_from_slice(MAGIC);
+            out.extend_from_slice(&salt);
+        }
+
+        let (key, iv) = self.derive(pass, self.salt.then(|| &salt[..]))?;
+        out.extend_from_slice(&symm::encrypt(self.cipher, &key, iv.as_deref(), data)?);
+        Ok(out)
+    }
+
+    /// Decrypts `data` with a key derived from `pass`.
+    pub fn decrypt(&self, pass: &[u8], data: &[u8]) -> Result<Vec<u8>, OpsError> {
+        let (salt, data) = if self.salt {
+            if data.len() < MAGIC.len() + SALT_LEN || !data.starts_with(MAGIC) {
+                return Err(OpsError::invalid("bad magic number"));
+            }
+            let (salt, data) = data[MAGIC.len()..].split_at(SALT_LEN);
+            (Some(salt), data)
+        } else {
+            (None, data)
+        };
+
+        let (key, iv) = self.derive(pass, salt)?;
+        Ok(symm::decrypt(self.cipher, &key, iv.as_deref(), data)?)
+    }
+
+    fn derive(
+        &self,
+        pass: &[u8],
+        salt: Option<&[u8]>,
+    ) -> Result<(Vec<u8>, Option<Vec<u8>>), OpsError> {
+        match self.kdf {
+            Kdf::Pbkdf2(iter) => {
+                let key_len = self.cipher.key_len();
+                let iv_len = self.cipher.iv_len().unwrap_or(0);
+                let mut buf = vec![0; key_len + iv_len];
+                pbkdf2_hmac(
+                    pass,
+                    salt.unwrap_or(&[]),
+                    iter as usize,
+                    self.digest,
+                    &mut buf,
+                )?;
+                let iv = buf.split_off(key_len);
+                Ok((buf, self.cipher.iv_len().map(|_| iv)))
+            }
+            #[cfg(not(boringssl))]
+            Kdf::BytesToKey => {
+                let pair = bytes_to_key(self.cipher, self.digest, pass, salt, 1)?;
+                Ok((pair.key, pair.iv))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let enc = Enc::new(Cipher::aes_128_cbc());
+        let ciphertext = enc.encrypt(b"hunter2", b"secret").unwrap();
+        assert!(ciphertext.starts_with(b"Salted__"));
+        assert_eq!(enc.decrypt(b"hunter2", &ciphertext).unwrap(), b"secret");
+        assert!(enc.decrypt(b"hunter2", b"garbage").is_err());
+    }
+
+    // generated with `openssl enc -aes-256-cbc -pbkdf2 -iter 1000 -pass pass:hunter2`
+    #[test]
+    fn cli_pbkdf2() {
+        let ciphertext =
+            hex::decode("53616c7465645f5f0d640acaad933a70da97f390fd7f88e538b307f7d35b51c0")
+                .unwrap();
+        let plaintext = Enc::new(Cipher::aes_256_cbc())
+            .iter(1000)
+            .decrypt(b"hunter2", &ciphertext)
+            .unwrap();
+        assert_eq!(plaintext, b"secret message");
+    }
+
+    // generated with `openssl enc -aes-128-cbc -md sha256 -pass pass:hunter2`
+    #[test]
+    #[cfg(not(boringssl))]
+    fn cli_bytes_to_key() {
+        let ciphertext =
+            hex::decode("53616c7465645f5fe48194b407bbd623ce9cfbd1cca8a0a39bc9ad7f84ccdab2")
+                .unwrap();
+        let plaintext = Enc::new(Cipher::aes_128_cbc())
+            .bytes_to_key()
+            .decrypt(b"hunter2", &ciphertext)
+            .unwrap();
+        assert_eq!(plaintext, b"secret message");
+    }
+
+    // generated with `openssl enc -aes-128-ctr -pbkdf2 -nosalt -pass pass:hunter2`
+    #[test]
+    fn cli_no_salt() {
+        let ciphertext = Enc::new(Cipher::aes_128_ctr())
+            .no_salt()
+            .encrypt(b"hunter2", b"secret message")
+            .unwrap();
+        assert_eq!(hex::encode(ciphertext), "b8b8a6053d6d6b8bc57952714d0a");
+    }
+}
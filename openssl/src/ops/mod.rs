@@ -0,0 +1,82 @@
+//! Library versions of common `openssl` command line operations.
+//!
+//! Tools which shell out to the `openssl` binary to create requests, issue certificates, verify
+//! chains, hash files, encrypt data, or perform raw public key operations can use these modules
+//! instead. Each module corresponds to one subcommand, takes its options as a builder, and returns
+//! structured values rather than text:
+//!
+//! * [`req`] creates certificate requests and self-signed certificates, like `openssl req`.
+//! * [`x509`] issues certificates from requests and summarizes certificates, like `openssl x509`.
+//! * [`verify`] verifies certificate chains, like `openssl verify`.
+//! * [`dgst`] hashes, MACs, and signs data, like `openssl dgst`.
+//! * [`enc`] encrypts data with a password in the same format as `openssl enc`.
+//! * [`pkeyutl`] performs raw public key operations, like `openssl pkeyutl`.
+//!
+//! # Examples
+//!
+//! Create a self-signed certificate and verify it, as with
+//! `openssl req -x509 -new -key key.pem -subj /CN=example.com` followed by
+//! `openssl verify -CAfile cert.pem cert.pem`:
+//!
+//! ```
+//! use openssl::ec::{EcGroup, EcKey};
+//! use openssl::nid::Nid;
+//! use openssl::ops::req::Req;
+//! use openssl::ops::verify::Verify;
+//! use openssl::pkey::PKey;
+//!
+//! let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+//! let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+//!
+//! let cert = Req::new()
+//!     .subject("/CN=example.com")
+//!     .unwrap()
+//!     .self_signed(&key)
+//!     .unwrap();
+//!
+//! let outcome = Verify::new().trusted(cert.clone()).verify(&cert).unwrap();
+//! assert!(outcome.is_ok());
+//! ```
+use std::io::{self, Read};
+
+use crate::error::ErrorStack;
+
+pub mod dgst;
+pub mod enc;
+pub mod pkeyutl;
+pub mod req;
+pub mod verify;
+pub mod x509;
+
+invalid_input_error! {
+    /// An error performing an operation.
+    pub struct OpsError => "{}";
+    Io(io::Error) => "{}";
+}
+
+impl OpsError {
+    /// Returns the underlying I/O error, if the error occurred reading the input.
+    pub fn io_error(&self) -> Option<&io::Error> {
+        match self.0 {
+            Inner::Io(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Calls `f` with each chunk read from `input`.
+fn for_each_chunk<R, F>(mut input: R, mut f: F) -> Result<(), OpsError>
+where
+    R: Read,
+    F: FnMut(&[u8]) -> Result<(), ErrorStack>,
+{
+    let mut buf = [0; 8192];
+    loop {
+        match input.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => f(&buf[..n])?,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Raw public key operations, like `openssl pkeyutl`.
+//!
+//! Unlike [`dgst`], these operations do not hash their input. Signing takes a digest that has
+//! already been computed, and encryption is limited to inputs shorter than the key.
+//!
+//! [`dgst`]: crate::ops::dgst
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::ops::pkeyutl::PkeyUtl;
+//! use openssl::pkey::PKey;
+//! use openssl::rsa::{Padding, Rsa};
+//!
+//! let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+//!
+//! // openssl pkeyutl -encrypt -pkeyopt rsa_padding_mode:oaep
+//! let mut pkeyutl = PkeyUtl::new();
+//! pkeyutl.rsa_padding(Padding::PKCS1_OAEP);
+//! let ciphertext = pkeyutl.encrypt(&key, b"secret").unwrap();
+//! assert_eq!(pkeyutl.decrypt(&key, &ciphertext).unwrap(), b"secret");
+//! ```
+use crate::md::MdRef;
+use crate::ops::OpsError;
+use crate::pkey::{HasPrivate, HasPublic, PKeyRef};
+use crate::pkey_ctx::{PkeyCtx, PkeyCtxRef};
+use crate::rsa::Padding;
+
+/// The options of a raw public key operation.
+#[derive(Default)]
+pub struct PkeyUtl<'a> {
+    rsa_padding: Option<Padding>,
+    digest: Option<&'a MdRef>,
+}
+
+impl<'a> PkeyUtl<'a> {
+    /// Creates a new `PkeyUtl` with the default options of the key type.
+    pub fn new() -> PkeyUtl<'a> {
+        PkeyUtl::default()
+    }
+
+    /// Sets the RSA padding mode, like `-pkeyopt rsa_padding_mode:...`.
+    pub fn rsa_padding(&mut self, padding: Padding) -> &mut PkeyUtl<'a> {
+        self.rsa_padding = Some(padding);
+        self
+    }
+
+    /// Sets the digest algorithm of the input to sign or verify, like `-pkeyopt digest:...`.
+    ///
+    /// For RSA keys this wraps the digest in a `DigestInfo` structure, as required by PKCS#1
+    /// signatures.
+    pub fn digest(&mut self, digest: &'a MdRef) -> &mut PkeyUtl<'a> {
+        self.digest = Some(digest);
+        self
+    }
+
+    /// Signs the digest `data` with `key`, like `-sign`.
+    pub fn sign<T>(&self, key: &PKeyRef<T>, data: &[u8]) -> Result<Vec<u8>, OpsError>
+    where
+        T: HasPrivate,
+    {
+        let mut ctx = PkeyCtx::new(key)?;
+        ctx.sign_init()?;
+        self.configure(&mut ctx)?;
+        let mut signature = vec![];
+        ctx.sign_to_vec(data, &mut signature)?;
+        Ok(signature)
+    }
+
+    /// Verifies the `signature` of the digest `data` with `key`, like `-verify`.
+    ///
+    /// Returns `false` if the signature is invalid.
+    pub fn verify<T>(
+        &self,
+        key: &PKeyRef<T>,
+        data: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, OpsError>
+    where
+        T: HasPublic,
+    {
+        let mut ctx = PkeyCtx::new(key)?;
+        ctx.verify_init()?;
+        self.configure(&mut ctx)?;
+        Ok(ctx.verify(data, signature)?)
+    }
+
+    /// Encrypts `data` with `key`, like `-encrypt`.
+    pub fn encrypt<T>(&self, key: &PKeyRef<T>, data: &[u8]) -> Result<Vec<u8>, OpsError>
+    where
+        T: HasPublic,
+    {
+        let mut ctx = PkeyCtx::new(key)?;
+        ctx.encrypt_init()?;
+        self.configure(&mut ctx)?;
+        let mut out = vec![];
+        ctx.encrypt_to_vec(data, &mut out)?;
+        Ok(out)
+    }
+
+    /// Decrypts `data` with `key`, like `-decrypt`.
+    pub fn decrypt<T>(&self, key: &PKeyRef<T>, data: &[u8]) -> Result<Vec<u8>, OpsError>
+    where
+        T: HasPrivate,
+    {
+        let mut ctx = PkeyCtx::new(key)?;
+        ctx.decrypt_init()?;
+        self.configure(&mut ctx)?;
+        let mut out = vec![];
+        ctx.decrypt_to_vec(data, &mut out)?;
+        Ok(out)
+    }
+
+    /// Derives a shared secret from `key` and the public key `peer`, like `-derive`.
+    pub fn derive<T, U>(&self, key: &PKeyRef<T>, peer: &PKeyRef<U>) -> Result<Vec<u8>, OpsError>
+    where
+        T: HasPrivate,
+        U: HasPublic,
+    {
+        let mut ctx = PkeyCtx::new(key)?;
+        ctx.derive_init()?;
+        ctx.derive_set_peer(peer)?;
+        let mut out = vec![];
+        ctx.derive_to_vec(&mut out)?;
+        Ok(out)
+    }
+
+    fn configure<T>(&self, ctx: &mut PkeyCtxRef<T>) -> Result<(), OpsError> {
+        if let Some(padding) = self.rsa_padding {
+            ctx.set_rsa_padding(padding)?;
+        }
+        if let Some(digest) = self.digest {
+            ctx.set_signature_md(digest)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ec::{EcGroup, EcKey};
+    use crate::hash::{hash, MessageDigest};
+    use crate::md::Md;
+    use crate::nid::Nid;
+    use crate::pkey::PKey;
+    use crate::rsa::Rsa;
+    use crate::sign::Verifier;
+
+    #[test]
+    fn sign_verify() {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let digest = hash(MessageDigest::sha256(), b"hello").unwrap();
+
+        let mut pkeyutl = PkeyUtl::new();
+        pkeyutl.digest(Md::sha256());
+        let signature = pkeyutl.sign(&key, &digest).unwrap();
+        assert!(pkeyutl.verify(&key, &digest, &signature).unwrap());
+
+        // signing a digest is equivalent to signing the message it was computed from
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &key).unwrap();
+        assert!(verifier.verify_oneshot(&signature, b"hello").unwrap());
+    }
+
+    #[test]
+    fn encrypt_decrypt() {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let pkeyutl = PkeyUtl::new();
+        let ciphertext = pkeyutl.encrypt(&key, b"secret").unwrap();
+        assert_eq!(pkeyutl.decrypt(&key, &ciphertext).unwrap(), b"secret");
+    }
+
+    #[test]
+    fn derive() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let a = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let b = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+        let pkeyutl = PkeyUtl::new();
+        assert_eq!(
+            pkeyutl.derive(&a, &b).unwrap(),
+            pkeyutl.derive(&b, &a).unwrap()
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Certificate requests and self-signed certificates, like `openssl req`.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::ec::{EcGroup, EcKey};
+//! use openssl::nid::Nid;
+//! use openssl::ops::req::Req;
+//! use openssl::pkey::PKey;
+//! use openssl::x509::extension::SubjectAlternativeName;
+//!
+//! let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+//! let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+//!
+//! // openssl req -new -key key.pem -subj "/O=Example/CN=example.com" \
+//! //     -addext subjectAltName=DNS:example.com
+//! let mut san = SubjectAlternativeName::new();
+//! san.dns("example.com");
+//! let mut req = Req::new();
+//! req.subject("/O=Example/CN=example.com")
+//!     .unwrap()
+//!     .profile_mut()
+//!     .subject_alt_name(san);
+//! let csr = req.new_request(&key).unwrap();
+//! assert!(csr.verify(&key).unwrap());
+//! ```
+use crate::ops::OpsError;
+use crate::pkey::{HasPrivate, PKeyRef};
+use crate::x509::csr::CsrProfile;
+use crate::x509::self_signed::SelfSignedOptions;
+use crate::x509::{X509Name, X509Req, X509};
+
+const DEFAULT_DAYS: u32 = 30;
+
+/// The options of a certificate request operation.
+pub struct Req {
+    profile: CsrProfile,
+    days: u32,
+}
+
+impl Default for Req {
+    fn default() -> Req {
+        Req::new()
+    }
+}
+
+impl Req {
+    /// Creates a new `Req` with an empty subject.
+    pub fn new() -> Req {
+        Req {
+            profile: CsrProfile::new(),
+            days: DEFAULT_DAYS,
+        }
+    }
+
+    /// Sets the subject name from a string in the format of `-subj`.
+    ///
+    /// The string consists of `/type=value` entries, such as `/C=US/O=Example/CN=example.com`.
+    /// A `/` within a value is escaped as `\/`.
+    pub fn subject(&mut self, subject: &str) -> Result<&mut Req, OpsError> {
+        let name = parse_subject(subject)?;
+        self.profile.subject(name);
+        Ok(self)
+    }
+
+    /// Returns the underlying request profile, to set extensions and attributes of the request.
+    ///
+    /// Options corresponding to `-addext` are set here.
+    pub fn profile_mut(&mut self) -> &mut CsrProfile {
+        &mut self.profile
+    }
+
+    /// Sets the number of days a self-signed certificate is valid for, like `-days`.
+    ///
+    /// Defaults to 30.
+    pub fn days(&mut self, days: u32) -> &mut Req {
+        self.days = days;
+        self
+    }
+
+    /// Creates a certificate request signed by `key`, like `-new`.
+    pub fn new_request<T>(&self, key: &PKeyRef<T>) -> Result<X509Req, OpsError>
+    where
+        T: HasPrivate,
+    {
+        Ok(self.profile.build(key)?)
+    }
+
+    /// Creates a self-signed certificate authority certificate for `key`, like `-new -x509`.
+    ///
+    /// The certificate is created as by [`X509::self_signed`] with [`SelfSignedOptions::ca`],
+    /// and carries the extensions of the request in place of the generated ones.
+    pub fn self_signed<T>(&self, key: &PKeyRef<T>) -> Result<X509, OpsError>
+    where
+        T: HasPrivate,
+    {
+        let req = self.new_request(key)?;
+        let mut options = SelfSignedOptions::new();
+        options
+            .ca()
+            .days(self.days)
+            .extensions(req.extensions()?.into_iter().collect());
+        Ok(X509::self_signed(req.subject_name(), key, &options)?)
+    }
+}
+
+fn parse_subject(subject: &str) -> Result<X509Name, OpsError> {
+    let rest = match subject.strip_prefix('/') {
+        Some(rest) => rest,
+        None => return Err(OpsError::invalid("subject must start with '/'")),
+    };
+
+    let mut entries = vec![];
+    let mut entry = String::new();
+    let mut chars = rest.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(c) => entry.push(c),
+                None => return Err(OpsError::invalid("subject ends with an escape")),
+            },
+            '/' => entries.push(std::mem::take(&mut entry)),
+            c => entry.push(c),
+        }
+    }
+    if !rest.is_empty() {
+        entries.push(entry);
+    }
+
+    let mut builder = X509Name::builder()?;
+    for entry in &entries {
+        let (field, value) = match entry.find('=') {
+            Some(idx) if idx > 0 => (&entry[..idx], &entry[idx + 1..]),
+            _ => {
+                return Err(OpsError::invalid(format!(
+                    "subject entry `{}` is not of the form type=value",
+                    entry
+                )))
+            }
+        };
+        builder.append_entry_by_text(field, value)?;
+    }
+    Ok(builder.build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ec::{EcGroup, EcKey};
+    use crate::nid::Nid;
+    use crate::pkey::PKey;
+
+    #[test]
+    fn parse() {
+        let name = parse_subject("/C=US/O=Foo\\/Bar/CN=example.com").unwrap();
+        let entries = name
+            .entries()
+            .map(|e| (e.object().nid(), e.data().as_utf8().unwrap().to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                (Nid::COUNTRYNAME, "US".to_string()),
+                (Nid::ORGANIZATIONNAME, "Foo/Bar".to_string()),
+                (Nid::COMMONNAME, "example.com".to_string()),
+            ]
+        );
+
+        assert_eq!(parse_subject("/").unwrap().entries().count(), 0);
+        assert!(parse_subject("CN=example.com").is_err());
+        assert!(parse_subject("/CN").is_err());
+        assert!(parse_subject("/=example.com").is_err());
+        assert!(parse_subject("/bogus=example.com").is_err());
+    }
+
+    #[test]
+    fn self_signed() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let cert = Req::new()
+            .subject("/CN=example.com")
+            .unwrap()
+            .days(365)
+            .self_signed(&key)
+            .unwrap();
+        assert!(cert.verify(&key).unwrap());
+        assert_eq!(
+            cert.subject_name().to_der().unwrap(),
+            cert.issuer_name().to_der().unwrap()
+        );
+        assert!(cert.subject_key_id().is_some());
+    }
+}
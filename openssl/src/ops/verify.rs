@@ -0,0 +1,261 @@
+//! Certificate chain verification, like `openssl verify`.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use openssl::ops::verify::Verify;
+//! use openssl::x509::X509;
+//!
+//! let cert = X509::from_pem(&std::fs::read("cert.pem").unwrap()).unwrap();
+//! let intermediate = X509::from_pem(&std::fs::read("intermediate.pem").unwrap()).unwrap();
+//!
+//! // openssl verify -untrusted intermediate.pem cert.pem
+//! let outcome = Verify::new()
+//!     .default_paths()
+//!     .untrusted(intermediate)
+//!     .verify(&cert)
+//!     .unwrap();
+//! if !outcome.is_ok() {
+//!     println!(
+//!         "error {} at depth {}",
+//!         outcome.result().error_string(),
+//!         outcome.error_depth()
+//!     );
+//! }
+//! ```
+use libc::time_t;
+
+use crate::ops::OpsError;
+use crate::stack::Stack;
+use crate::x509::store::X509StoreBuilder;
+use crate::x509::{X509PurposeId, X509Ref, X509StoreContext, X509VerifyResult, X509};
+
+/// The options of a verification operation.
+#[derive(Default)]
+pub struct Verify {
+    trusted: Vec<X509>,
+    untrusted: Vec<X509>,
+    default_paths: bool,
+    purpose: Option<X509PurposeId>,
+    host: Option<String>,
+    time: Option<time_t>,
+}
+
+impl Verify {
+    /// Creates a new `Verify` with no trusted certificates.
+    pub fn new() -> Verify {
+        Verify::default()
+    }
+
+    /// Adds a trusted certificate, like `-CAfile`.
+    pub fn trusted(&mut self, cert: X509) -> &mut Verify {
+        self.trusted.push(cert);
+        self
+    }
+
+    /// Adds an untrusted intermediate certificate which may be used to build the chain, like
+    /// `-untrusted`.
+    pub fn untrusted(&mut self, cert: X509) -> &mut Verify {
+        self.untrusted.push(cert);
+        self
+    }
+
+    /// Trusts the certificates in the default locations, as `openssl verify` does unless
+    /// `-no-CAfile` and `-no-CApath` are given.
+    pub fn default_paths(&mut self) -> &mut Verify {
+        self.default_paths = true;
+        self
+    }
+
+    /// Requires the chain to be valid for `purpose`, like `-purpose`.
+    pub fn purpose(&mut self, purpose: X509PurposeId) -> &mut Verify {
+        self.purpose = Some(purpose);
+        self
+    }
+
+    /// Requires the certificate to be valid for the DNS name `host`, like `-verify_hostname`.
+    ///
+    /// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
+    #[cfg(any(ossl102, libressl261))]
+    pub fn host(&mut self, host: &str) -> &mut Verify {
+        self.host = Some(host.to_string());
+        self
+    }
+
+    /// Checks validity periods at `time`, in seconds since the Unix epoch, rather than the
+    /// current time, like `-attime`.
+    pub fn attime(&mut self, time: time_t) -> &mut Verify {
+        self.time = Some(time);
+        self
+    }
+
+    /// Verifies `cert`.
+    ///
+    /// An error is only returned when verification could not be performed. A certificate which
+    /// fails verification is reported through the returned [`VerifyOutcome`].
+    pub fn verify(&self, cert: &X509Ref) -> Result<VerifyOutcome, OpsError> {
+        let mut store = X509StoreBuilder::new()?;
+        for trusted in &self.trusted {
+            store.add_cert(trusted.clone())?;
+        }
+        if self.default_paths {
+            store.set_default_paths()?;
+        }
+        if let Some(purpose) = self.purpose {
+            store.set_purpose(purpose)?;
+        }
+        let store = store.build();
+
+        let mut untrusted = Stack::new()?;
+        for cert in &self.untrusted {
+            untrusted.push(cert.clone())?;
+        }
+
+        let mut ctx = X509StoreContext::new()?;
+        let outcome = ctx.init(&store, cert, &untrusted, |ctx| {
+            #[cfg(any(ossl102, libressl261))]
+            {
+                if let Some(host) = &self.host {
+                    ctx.param_mut().set_host(host)?;
+                }
+            }
+            if let Some(time) = self.time {
+                ctx.set_time(time);
+            }
+
+            let verified = ctx.verify_cert()?;
+            let chain = match ctx.chain() {
+                Some(chain) if verified => chain.iter().map(|c| c.to_owned()).collect(),
+                _ => vec![],
+            };
+            Ok(VerifyOutcome {
+                result: ctx.error(),
+                error_depth: ctx.error_depth(),
+                chain,
+            })
+        })?;
+        Ok(outcome)
+    }
+}
+
+/// The outcome of a verification operation.
+pub struct VerifyOutcome {
+    result: X509VerifyResult,
+    error_depth: u32,
+    chain: Vec<X509>,
+}
+
+impl VerifyOutcome {
+    /// Returns the verification result, which is [`X509VerifyResult::OK`] on success.
+    pub fn result(&self) -> X509VerifyResult {
+        self.result
+    }
+
+    /// Determines if the certificate was verified successfully.
+    pub fn is_ok(&self) -> bool {
+        self.result == X509VerifyResult::OK
+    }
+
+    /// Returns the depth in the chain of the certificate which failed verification, where zero is
+    /// the certificate itself.
+    pub fn error_depth(&self) -> u32 {
+        self.error_depth
+    }
+
+    /// Returns the verified chain, from the certificate to the trust anchor, like `-show_chain`.
+    ///
+    /// The chain is empty if verification failed.
+    pub fn chain(&self) -> &[X509] {
+        &self.chain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ec::{EcGroup, EcKey};
+    use crate::nid::Nid;
+    use crate::ops::req::Req;
+    use crate::ops::x509::X509Sign;
+    use crate::pkey::PKey;
+    use crate::x509::extension::SubjectAlternativeName;
+
+    #[test]
+    fn verify_chain() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let root_key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let root = Req::new()
+            .subject("/CN=Root")
+            .unwrap()
+            .self_signed(&root_key)
+            .unwrap();
+
+        let ca_key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let ca_req = Req::new()
+            .subject("/CN=Intermediate")
+            .unwrap()
+            .new_request(&ca_key)
+            .unwrap();
+        let ca = X509Sign::new()
+            .ca()
+            .sign(&ca_req, &root, &root_key)
+            .unwrap();
+
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let mut req = Req::new();
+        let mut san = SubjectAlternativeName::new();
+        san.dns("example.com");
+        req.subject("/CN=example.com")
+            .unwrap()
+            .profile_mut()
+            .subject_alt_name(san);
+        let req = req.new_request(&key).unwrap();
+        let cert = X509Sign::new()
+            .copy_extensions()
+            .sign(&req, &ca, &ca_key)
+            .unwrap();
+
+        let outcome = Verify::new().trusted(root.clone()).verify(&cert).unwrap();
+        assert_eq!(
+            outcome.result().as_raw(),
+            ffi::X509_V_ERR_UNABLE_TO_GET_ISSUER_CERT_LOCALLY
+        );
+        assert!(outcome.chain().is_empty());
+
+        let mut verify = Verify::new();
+        verify.trusted(root).untrusted(ca);
+        let outcome = verify.verify(&cert).unwrap();
+        assert!(outcome.is_ok());
+        assert_eq!(outcome.chain().len(), 3);
+
+        #[cfg(any(ossl102, libressl261))]
+        {
+            assert!(verify.host("example.com").verify(&cert).unwrap().is_ok());
+            let outcome = verify.host("example.org").verify(&cert).unwrap();
+            assert_eq!(outcome.result().as_raw(), ffi::X509_V_ERR_HOSTNAME_MISMATCH);
+            assert_eq!(outcome.error_depth(), 0);
+        }
+    }
+
+    #[test]
+    fn verify_expired() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let cert = Req::new()
+            .subject("/CN=example.com")
+            .unwrap()
+            .days(1)
+            .self_signed(&key)
+            .unwrap();
+
+        let mut verify = Verify::new();
+        verify.trusted(cert.clone());
+        assert!(verify.verify(&cert).unwrap().is_ok());
+
+        let outcome = verify.attime(0).verify(&cert).unwrap();
+        assert_eq!(
+            outcome.result().as_raw(),
+            ffi::X509_V_ERR_CERT_NOT_YET_VALID
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//! Certificate issuance and inspection, like `openssl x509`.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::ec::{EcGroup, EcKey};
+//! use openssl::hash::MessageDigest;
+//! use openssl::nid::Nid;
+//! use openssl::ops::req::Req;
+//! use openssl::ops::x509::{info, X509Sign};
+//! use openssl::pkey::PKey;
+//!
+//! let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+//! let ca_key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+//! let ca = Req::new().subject("/CN=CA").unwrap().self_signed(&ca_key).unwrap();
+//!
+//! let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+//! let req = Req::new().subject("/CN=example.com").unwrap().new_request(&key).unwrap();
+//!
+//! // openssl x509 -req -in req.pem -CA ca.pem -CAkey ca.key -days 90
+//! let cert = X509Sign::new().days(90).sign(&req, &ca, &ca_key).unwrap();
+//!
+//! // openssl x509 -noout -subject -issuer -fingerprint -sha256
+//! let info = info(&cert, MessageDigest::sha256()).unwrap();
+//! assert_eq!(info.subject(), "CN = example.com");
+//! assert_eq!(info.issuer(), "CN = CA");
+//! ```
+use crate::asn1::{Asn1Integer, Asn1Time};
+use crate::bn::BigNum;
+use crate::hash::MessageDigest;
+use crate::ops::OpsError;
+use crate::pkey::{HasPrivate, PKeyRef};
+use crate::x509::csr::default_digest;
+use crate::x509::extension::{AuthorityKeyIdentifier, BasicConstraints, SubjectKeyIdentifier};
+use crate::x509::{X509Builder, X509NameRef, X509Ref, X509ReqRef, X509};
+
+const DEFAULT_DAYS: u32 = 30;
+
+/// The options of a certificate signing operation.
+pub struct X509Sign {
+    days: u32,
+    digest: Option<MessageDigest>,
+    serial: Option<BigNum>,
+    copy_extensions: bool,
+    ca: bool,
+}
+
+impl Default for X509Sign {
+    fn default() -> X509Sign {
+        X509Sign::new()
+    }
+}
+
+impl X509Sign {
+    /// Creates a new `X509Sign`.
+    ///
+    /// Certificates are valid for 30 days and given a random serial number, the defaults of
+    /// `openssl x509 -req`.
+    pub fn new() -> X509Sign {
+        X509Sign {
+            days: DEFAULT_DAYS,
+            digest: None,
+            serial: None,
+            copy_extensions: false,
+            ca: false,
+        }
+    }
+
+    /// Sets the number of days the certificate is valid for, like `-days`.
+    pub fn days(&mut self, days: u32) -> &mut X509Sign {
+        self.days = days;
+        self
+    }
+
+    /// Sets the digest used to sign the certificate, like `-sha256`.
+    ///
+    /// By default, the digest is chosen based on the signing key in the same way as
+    /// [`CsrProfile::digest`].
+    ///
+    /// [`CsrProfile::digest`]: crate::x509::csr::CsrProfile::digest
+    pub fn digest(&mut self, digest: MessageDigest) -> &mut X509Sign {
+        self.digest = Some(digest);
+        self
+    }
+
+    /// Sets the serial number of the certificate, like `-set_serial`.
+    pub fn serial(&mut self, serial: BigNum) -> &mut X509Sign {
+        self.serial = Some(serial);
+        self
+    }
+
+    /// Copies the extensions of the request into the certificate, like `-copy_extensions copy`.
+    ///
+    /// By default the extensions of the request are ignored.
+    pub fn copy_extensions(&mut self) -> &mut X509Sign {
+        self.copy_extensions = true;
+        self
+    }
+
+    /// Marks the certificate as a certificate authority with a critical basic constraints
+    /// extension, as `openssl req -x509` does by default.
+    pub fn ca(&mut self) -> &mut X509Sign {
+        self.ca = true;
+        self
+    }
+
+    /// Issues a certificate for `req`, signed by `ca` and its private key `ca_key`.
+    ///
+    /// The signature of the request is checked first.
+    pub fn sign<T>(
+        &self,
+        req: &X509ReqRef,
+        ca: &X509Ref,
+        ca_key: &PKeyRef<T>,
+    ) -> Result<X509, OpsError>
+    where
+        T: HasPrivate,
+    {
+        self.sign_inner(req, Some(ca), ca_key)
+    }
+
+    /// Issues a certificate for `req` signed by its own key, like `-signkey`.
+    ///
+    /// The signature of the request is checked first.
+    pub fn self_sign<T>(&self, req: &X509ReqRef, key: &PKeyRef<T>) -> Result<X509, OpsError>
+    where
+        T: HasPrivate,
+    {
+        self.sign_inner(req, None, key)
+    }
+
+    fn sign_inner<T>(
+        &self,
+        req: &X509ReqRef,
+        ca: Option<&X509Ref>,
+        key: &PKeyRef<T>,
+    ) -> Result<X509, OpsError>
+    where
+        T: HasPrivate,
+    {
+        let public_key = req.public_key()?;
+        if !req.verify(&public_key)? {
+            return Err(OpsError::invalid(
+                "certificate request signature is invalid",
+            ));
+        }
+
+        let mut builder = X509Builder::new()?;
+        builder.set_version(2)?;
+        let serial = match &self.serial {
+            Some(serial) => serial.to_asn1_integer()?,
+            None => Asn1Integer::rand_serial()?,
+        };
+        builder.set_serial_number(&serial)?;
+        builder.set_subject_name(req.subject_name())?;
+        builder.set_issuer_name(match ca {
+            Some(ca) => ca.subject_name(),
+            None => req.subject_name(),
+        })?;
+        let not_before = Asn1Time::days_from_now(0)?;
+        builder.set_not_before(&not_before)?;
+        let not_after = Asn1Time::days_from_now(self.days)?;
+        builder.set_not_after(&not_after)?;
+        builder.set_pubkey(&public_key)?;
+
+        if self.copy_extensions {
+            for extension in req.extensions()? {
+                builder.append_extension(extension)?;
+            }
+        }
+        if self.ca {
+            builder.append_extension(BasicConstraints::new().critical().ca().build()?)?;
+        }
+        let subject_key_identifier =
+            SubjectKeyIdentifier::new().build(&builder.x509v3_context(ca, None))?;
+        builder.append_extension(subject_key_identifier)?;
+        let authority_key_identifier = AuthorityKeyIdentifier::new()
+            .keyid(false)
+            .build(&builder.x509v3_context(ca, None))?;
+        builder.append_extension(authority_key_identifier)?;
+
+        let digest = match self.digest {
+            Some(digest) => digest,
+            None => default_digest(key),
+        };
+        builder.sign(key, digest)?;
+        Ok(builder.build())
+    }
+}
+
+/// A summary of a certificate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct X509Info {
+    subject: String,
+    issuer: String,
+    serial: String,
+    not_before: String,
+    not_after: String,
+    fingerprint: String,
+}
+
+impl X509Info {
+    /// Returns the subject name, like `-subject -nameopt oneline`.
+    ///
+    /// Entries are written as `CN = example.com, O = Example`.
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Returns the issuer name, like `-issuer`, in the same format as [`subject`].
+    ///
+    /// [`subject`]: X509Info::subject
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    /// Returns the serial number as uppercase hexadecimal, like `-serial`.
+    pub fn serial(&self) -> &str {
+        &self.serial
+    }
+
+    /// Returns the start of the validity period, like `-startdate`.
+    pub fn not_before(&self) -> &str {
+        &self.not_before
+    }
+
+    /// Returns the end of the validity period, like `-enddate`.
+    pub fn not_after(&self) -> &str {
+        &self.not_after
+    }
+
+    /// Returns the fingerprint as colon-separated uppercase hexadecimal, like `-fingerprint`.
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+}
+
+/// Summarizes `cert`, computing its fingerprint with `digest`.
+pub fn info(cert: &X509Ref, digest: MessageDigest) -> Result<X509Info, OpsError> {
+    let fingerprint = cert
+        .digest(digest)?
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":");
+
+    Ok(X509Info {
+        subject: format_name(cert.subject_name())?,
+        issuer: format_name(cert.issuer_name())?,
+        serial: cert.serial_number().to_bn()?.to_hex_str()?.to_string(),
+        not_before: cert.not_before().to_string(),
+        not_after: cert.not_after().to_string(),
+        fingerprint,
+    })
+}
+
+fn format_name(name: &X509NameRef) -> Result<String, OpsError> {
+    let mut entries = vec![];
+    for entry in name.entries() {
+        let object = entry.object();
+        let key = match object.nid().short_name() {
+            Ok(name) => name.to_string(),
+            Err(_) => object.to_string(),
+        };
+        entries.push(format!("{} = {}", key, entry.data().as_utf8()?));
+    }
+    Ok(entries.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ec::{EcGroup, EcKey};
+    use crate::nid::Nid;
+    use crate::ops::req::Req;
+    use crate::pkey::PKey;
+    use crate::x509::extension::SubjectAlternativeName;
+
+    #[test]
+    fn sign() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let ca_key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let ca = Req::new()
+            .subject("/CN=CA")
+            .unwrap()
+            .self_signed(&ca_key)
+            .unwrap();
+
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let mut req = Req::new();
+        let mut san = SubjectAlternativeName::new();
+        san.dns("example.com");
+        req.subject("/O=Example/CN=example.com")
+            .unwrap()
+            .profile_mut()
+            .subject_alt_name(san);
+        let req = req.new_request(&key).unwrap();
+
+        let cert = X509Sign::new()
+            .serial(BigNum::from_u32(0x1234).unwrap())
+            .copy_extensions()
+            .sign(&req, &ca, &ca_key)
+            .unwrap();
+        assert!(cert.verify(&ca_key).unwrap());
+        assert_eq!(
+            cert.subject_alt_names().unwrap()[0].dnsname(),
+            Some("example.com")
+        );
+
+        let info = info(&cert, MessageDigest::sha1()).unwrap();
+        assert_eq!(info.subject(), "O = Example, CN = example.com");
+        assert_eq!(info.issuer(), "CN = CA");
+        assert_eq!(info.serial(), "1234");
+        assert_eq!(info.fingerprint().len(), 20 * 3 - 1);
+        assert_eq!(
+            info.fingerprint().replace(':', ""),
+            hex::encode_upper(cert.digest(MessageDigest::sha1()).unwrap())
+        );
+    }
+
+    #[test]
+    fn sign_without_extensions() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let mut req = Req::new();
+        let mut san = SubjectAlternativeName::new();
+        san.dns("example.com");
+        req.profile_mut().subject_alt_name(san);
+        let req = req.new_request(&key).unwrap();
+
+        let cert = X509Sign::new().self_sign(&req, &key).unwrap();
+        assert!(cert.subject_alt_names().is_none());
+    }
+}
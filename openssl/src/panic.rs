@@ -0,0 +1,144 @@
+//! Handling of panics in callbacks called by OpenSSL.
+//!
+//! Many APIs in this crate take Rust callbacks which OpenSSL calls while one of its own functions
+//! is running, such as the verification and SNI callbacks of a TLS connection, the reads and
+//! writes of the stream underneath an [`SslStream`], and pass phrase callbacks. A panic cannot
+//! unwind through OpenSSL's C frames, so every callback catches it, reports a failure to OpenSSL,
+//! and then handles the panic according to the process-wide [`PanicPolicy`] set with
+//! [`set_policy`].
+//!
+//! A panic in a callback which belongs to a TLS connection poisons that connection: every later
+//! operation on its [`SslStream`] fails with an I/O error describing the panic, rather than
+//! continuing with OpenSSL in an unknown state.
+//!
+//! Some callbacks are not called on behalf of a connection or a Rust function which the panic
+//! could resume from, such as the remove session callback of an [`SslContext`] and the operations
+//! of a [`RemoteKey`]. Under [`PanicPolicy::Resume`], a panic in one of those aborts the process.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::panic::{self, PanicPolicy};
+//! use openssl::pkey::PKey;
+//!
+//! panic::set_policy(PanicPolicy::Error);
+//!
+//! // the panic is printed by the panic hook, but only fails the call
+//! let pem = include_bytes!("../test/rsa-encrypted.pem");
+//! let key = PKey::private_key_from_pem_callback(pem, |_| panic!("no pass phrase"));
+//! assert!(key.is_err());
+//! ```
+//!
+//! [`SslStream`]: crate::ssl::SslStream
+//! [`SslContext`]: crate::ssl::SslContext
+//! [`RemoteKey`]: crate::remote_key::RemoteKey
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+static POLICY: AtomicU8 = AtomicU8::new(PanicPolicy::Resume as u8);
+
+/// What happens when a callback called by OpenSSL panics.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PanicPolicy {
+    /// The panic resumes once the OpenSSL function which called the callback returns to Rust,
+    /// as if it had unwound through OpenSSL.
+    ///
+    /// This is the default.
+    Resume = 0,
+
+    /// The panic is discarded, and the operation which called the callback fails.
+    ///
+    /// A TLS connection fails with an I/O error describing the panic.
+    Error = 1,
+
+    /// The process aborts without returning to OpenSSL.
+    ///
+    /// The panic hook runs before the process aborts, so the panic's message is still printed.
+    Abort = 2,
+}
+
+/// Sets the policy for panics in callbacks, for all threads.
+///
+/// The policy applies to panics which happen after it is set, so it should be set at the start
+/// of the program.
+pub fn set_policy(policy: PanicPolicy) {
+    POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+/// Returns the policy for panics in callbacks.
+pub fn policy() -> PanicPolicy {
+    match POLICY.load(Ordering::Relaxed) {
+        0 => PanicPolicy::Resume,
+        1 => PanicPolicy::Error,
+        _ => PanicPolicy::Abort,
+    }
+}
+
+/// Runs a callback, catching a panic to be handled once control returns to Rust.
+///
+/// Aborts the process under `PanicPolicy::Abort`.
+pub(crate) fn catch<F, R>(f: F) -> Result<R, Box<dyn Any + Send>>
+where
+    F: FnOnce() -> R,
+{
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(r) => Ok(r),
+        Err(_) if policy() == PanicPolicy::Abort => process::abort(),
+        Err(payload) => Err(payload),
+    }
+}
+
+/// Runs a callback whose panics cannot be resumed, returning `None` if it panicked.
+///
+/// Aborts the process unless the policy is `PanicPolicy::Error`.
+pub(crate) fn catch_detached<F, R>(f: F) -> Option<R>
+where
+    F: FnOnce() -> R,
+{
+    match catch(f) {
+        Ok(r) => Some(r),
+        Err(_) if policy() == PanicPolicy::Error => None,
+        Err(_) => process::abort(),
+    }
+}
+
+/// Resumes a caught panic under `PanicPolicy::Resume`, and otherwise discards it.
+pub(crate) fn resume(payload: Box<dyn Any + Send>) {
+    if policy() == PanicPolicy::Resume {
+        panic::resume_unwind(payload);
+    }
+}
+
+/// Returns a description of a caught panic.
+pub(crate) fn message(payload: &(dyn Any + Send)) -> String {
+    let msg = match payload.downcast_ref::<&'static str>() {
+        Some(msg) => *msg,
+        None => match payload.downcast_ref::<String>() {
+            Some(msg) => msg,
+            None => "Box<dyn Any>",
+        },
+    };
+    format!("callback panicked: {}", msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages() {
+        let payload = catch(|| panic!("blammo")).unwrap_err();
+        assert_eq!(message(&*payload), "callback panicked: blammo");
+
+        let payload = catch(|| panic!("{}", 1)).unwrap_err();
+        assert_eq!(message(&*payload), "callback panicked: 1");
+
+        let payload = catch(|| panic::panic_any(1)).unwrap_err();
+        assert_eq!(message(&*payload), "callback panicked: Box<dyn Any>");
+
+        assert_eq!(catch(|| 1).unwrap(), 1);
+    }
+}
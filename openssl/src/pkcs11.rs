@@ -0,0 +1,492 @@
+//! PKCS#11 URIs.
+//!
+//! [RFC 7512] URIs identify tokens and the keys and certificates stored on them, for example
+//! `pkcs11:token=My%20Token;object=signing-key;type=private`. [`Pkcs11Uri`] parses and formats
+//! them, and on OpenSSL 3.0 and newer loads the objects they identify through a provider which
+//! handles `pkcs11:` URIs, such as pkcs11-provider.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::pkcs11::{Pkcs11ObjectType, Pkcs11Uri};
+//!
+//! let uri = Pkcs11Uri::parse("pkcs11:token=My%20Token;id=%01%02;type=cert?pin-value=1234").unwrap();
+//! assert_eq!(uri.token(), Some("My Token"));
+//! assert_eq!(uri.id(), Some(&[1, 2][..]));
+//! assert_eq!(uri.object_type(), Some(Pkcs11ObjectType::CERT));
+//! assert_eq!(uri.pin_value(), Some("1234"));
+//!
+//! let mut key = uri.clone();
+//! key.remove_query_attr("pin-value");
+//! key.set_object_type(Pkcs11ObjectType::PRIVATE);
+//! assert_eq!(key.to_string(), "pkcs11:token=My%20Token;id=%01%02;type=private");
+//! ```
+//!
+//! [RFC 7512]: https://www.rfc-editor.org/rfc/rfc7512
+use std::error;
+use std::fmt;
+use std::str;
+
+#[cfg(ossl300)]
+use crate::error::ErrorStack;
+#[cfg(ossl300)]
+use crate::lib_ctx::LibCtxRef;
+#[cfg(ossl300)]
+use crate::store::{Store, StoreInfo};
+
+/// An error parsing a PKCS#11 URI.
+pub struct Pkcs11UriError(String);
+
+impl fmt::Debug for Pkcs11UriError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_tuple("Pkcs11UriError").field(&self.0).finish()
+    }
+}
+
+impl fmt::Display for Pkcs11UriError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "invalid PKCS#11 URI: {}", self.0)
+    }
+}
+
+impl error::Error for Pkcs11UriError {}
+
+/// The type of a PKCS#11 object, from the `type` attribute of a URI.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Pkcs11ObjectType(&'static str);
+
+impl Pkcs11ObjectType {
+    pub const PUBLIC: Pkcs11ObjectType = Pkcs11ObjectType("public");
+    pub const PRIVATE: Pkcs11ObjectType = Pkcs11ObjectType("private");
+    pub const CERT: Pkcs11ObjectType = Pkcs11ObjectType("cert");
+    pub const SECRET_KEY: Pkcs11ObjectType = Pkcs11ObjectType("secret-key");
+    pub const DATA: Pkcs11ObjectType = Pkcs11ObjectType("data");
+
+    /// Returns the value of the `type` attribute.
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+/// A PKCS#11 URI.
+///
+/// Attributes are kept in the order they were parsed or added. Values are stored decoded, and
+/// are percent-encoded as needed when the URI is formatted with `Display`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Pkcs11Uri {
+    path: Vec<(String, Vec<u8>)>,
+    query: Vec<(String, Vec<u8>)>,
+}
+
+const SCHEME: &str = "pkcs11:";
+
+impl Pkcs11Uri {
+    /// Creates a URI without any attributes, which matches every object.
+    pub fn new() -> Pkcs11Uri {
+        Pkcs11Uri::default()
+    }
+
+    /// Parses a URI.
+    ///
+    /// The scheme is matched case-insensitively. An attribute may not appear more than once,
+    /// apart from vendor-specific `x-` query attributes.
+    pub fn parse(uri: &str) -> Result<Pkcs11Uri, Pkcs11UriError> {
+        if uri.len() < SCHEME.len() || !uri[..SCHEME.len()].eq_ignore_ascii_case(SCHEME) {
+            return Err(Pkcs11UriError("missing pkcs11: scheme".to_string()));
+        }
+        let rest = &uri[SCHEME.len()..];
+        let (path, query) = match rest.find('?') {
+            Some(idx) => (&rest[..idx], Some(&rest[idx + 1..])),
+            None => (rest, None),
+        };
+
+        let mut parsed = Pkcs11Uri::new();
+        for attr in path.split(';').filter(|a| !a.is_empty()) {
+            let (name, value) = parse_attr(attr)?;
+            if parsed.path_attr(&name).is_some() {
+                return Err(Pkcs11UriError(format!("duplicate attribute {}", name)));
+            }
+            validate_path_attr(&name, &value)?;
+            parsed.path.push((name, value));
+        }
+        for attr in query.into_iter().flat_map(|q| q.split('&')) {
+            if attr.is_empty() {
+                continue;
+            }
+            let (name, value) = parse_attr(attr)?;
+            if !name.starts_with("x-") && parsed.query_attr(&name).is_some() {
+                return Err(Pkcs11UriError(format!("duplicate attribute {}", name)));
+            }
+            parsed.query.push((name, value));
+        }
+
+        Ok(parsed)
+    }
+
+    /// Returns the value of a path attribute, such as `token` or `object`.
+    pub fn path_attr(&self, name: &str) -> Option<&[u8]> {
+        self.path.iter().find(|(n, _)| n == name).map(|(_, v)| &**v)
+    }
+
+    /// Sets a path attribute, replacing any previous value.
+    pub fn set_path_attr(&mut self, name: &str, value: &[u8]) {
+        set_attr(&mut self.path, name, value)
+    }
+
+    /// Removes a path attribute.
+    pub fn remove_path_attr(&mut self, name: &str) {
+        self.path.retain(|(n, _)| n != name)
+    }
+
+    /// Returns the value of the first query attribute with a name, such as `pin-value`.
+    pub fn query_attr(&self, name: &str) -> Option<&[u8]> {
+        self.query
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| &**v)
+    }
+
+    /// Sets a query attribute, replacing any previous values.
+    pub fn set_query_attr(&mut self, name: &str, value: &[u8]) {
+        set_attr(&mut self.query, name, value)
+    }
+
+    /// Removes a query attribute.
+    pub fn remove_query_attr(&mut self, name: &str) {
+        self.query.retain(|(n, _)| n != name)
+    }
+
+    /// Returns the label of the token.
+    pub fn token(&self) -> Option<&str> {
+        self.path_str("token")
+    }
+
+    /// Returns the manufacturer of the token.
+    pub fn manufacturer(&self) -> Option<&str> {
+        self.path_str("manufacturer")
+    }
+
+    /// Returns the serial number of the token.
+    pub fn serial(&self) -> Option<&str> {
+        self.path_str("serial")
+    }
+
+    /// Returns the model of the token.
+    pub fn model(&self) -> Option<&str> {
+        self.path_str("model")
+    }
+
+    /// Returns the ID of the slot.
+    pub fn slot_id(&self) -> Option<u64> {
+        self.path_str("slot-id").and_then(|s| s.parse().ok())
+    }
+
+    /// Returns the description of the slot.
+    pub fn slot_description(&self) -> Option<&str> {
+        self.path_str("slot-description")
+    }
+
+    /// Returns the label of the object.
+    pub fn object(&self) -> Option<&str> {
+        self.path_str("object")
+    }
+
+    /// Returns the ID of the object.
+    pub fn id(&self) -> Option<&[u8]> {
+        self.path_attr("id")
+    }
+
+    /// Returns the type of the object.
+    pub fn object_type(&self) -> Option<Pkcs11ObjectType> {
+        let type_ = self.path_attr("type")?;
+        [
+            Pkcs11ObjectType::PUBLIC,
+            Pkcs11ObjectType::PRIVATE,
+            Pkcs11ObjectType::CERT,
+            Pkcs11ObjectType::SECRET_KEY,
+            Pkcs11ObjectType::DATA,
+        ]
+        .iter()
+        .find(|t| t.0.as_bytes() == type_)
+        .copied()
+    }
+
+    /// Sets the label of the token.
+    pub fn set_token(&mut self, token: &str) {
+        self.set_path_attr("token", token.as_bytes())
+    }
+
+    /// Sets the label of the object.
+    pub fn set_object(&mut self, object: &str) {
+        self.set_path_attr("object", object.as_bytes())
+    }
+
+    /// Sets the ID of the object.
+    pub fn set_id(&mut self, id: &[u8]) {
+        self.set_path_attr("id", id)
+    }
+
+    /// Sets the type of the object.
+    pub fn set_object_type(&mut self, type_: Pkcs11ObjectType) {
+        self.set_path_attr("type", type_.0.as_bytes())
+    }
+
+    /// Returns the PIN of the token.
+    pub fn pin_value(&self) -> Option<&str> {
+        self.query_str("pin-value")
+    }
+
+    /// Returns the URI from which the PIN of the token can be read.
+    pub fn pin_source(&self) -> Option<&str> {
+        self.query_str("pin-source")
+    }
+
+    /// Returns the name of the PKCS#11 module.
+    pub fn module_name(&self) -> Option<&str> {
+        self.query_str("module-name")
+    }
+
+    /// Returns the path of the PKCS#11 module.
+    pub fn module_path(&self) -> Option<&str> {
+        self.query_str("module-path")
+    }
+
+    /// Sets the PIN of the token.
+    pub fn set_pin_value(&mut self, pin: &str) {
+        self.set_query_attr("pin-value", pin.as_bytes())
+    }
+
+    /// Opens the objects identified by the URI.
+    ///
+    /// A provider which handles `pkcs11:` URIs must be loaded. See [`Store::open`] for the
+    /// meaning of `ctx` and `properties`.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    pub fn open(
+        &self,
+        ctx: Option<&LibCtxRef>,
+        properties: Option<&str>,
+    ) -> Result<Store, ErrorStack> {
+        Store::open(ctx, &self.to_string(), properties)
+    }
+
+    /// Loads every object identified by the URI.
+    ///
+    /// This can be used to list the keys and certificates of a token, for example with a URI
+    /// naming only the token.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[cfg(ossl300)]
+    pub fn objects(
+        &self,
+        ctx: Option<&LibCtxRef>,
+        properties: Option<&str>,
+    ) -> Result<Vec<StoreInfo>, ErrorStack> {
+        let mut store = self.open(ctx, properties)?;
+        let mut objects = vec![];
+        while let Some(info) = store.load()? {
+            objects.push(info);
+        }
+        Ok(objects)
+    }
+
+    fn path_str(&self, name: &str) -> Option<&str> {
+        self.path_attr(name).and_then(|v| str::from_utf8(v).ok())
+    }
+
+    fn query_str(&self, name: &str) -> Option<&str> {
+        self.query_attr(name).and_then(|v| str::from_utf8(v).ok())
+    }
+}
+
+impl str::FromStr for Pkcs11Uri {
+    type Err = Pkcs11UriError;
+
+    fn from_str(s: &str) -> Result<Pkcs11Uri, Pkcs11UriError> {
+        Pkcs11Uri::parse(s)
+    }
+}
+
+impl fmt::Display for Pkcs11Uri {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(SCHEME)?;
+        for (i, (name, value)) in self.path.iter().enumerate() {
+            if i != 0 {
+                fmt.write_str(";")?;
+            }
+            // the ID is binary, so every byte is encoded
+            let encode_all = name == "id";
+            write_attr(fmt, name, value, |c| {
+                !encode_all && (is_unreserved(c) || b":[]@!$'()*+,=&".contains(&c))
+            })?;
+        }
+        for (i, (name, value)) in self.query.iter().enumerate() {
+            fmt.write_str(if i == 0 { "?" } else { "&" })?;
+            write_attr(fmt, name, value, |c| {
+                is_unreserved(c) || b":[]@!$'()*+,=/?|".contains(&c)
+            })?;
+        }
+        Ok(())
+    }
+}
+
+fn set_attr(attrs: &mut Vec<(String, Vec<u8>)>, name: &str, value: &[u8]) {
+    let attr = (name.to_string(), value.to_vec());
+    match attrs.iter().position(|(n, _)| n == name) {
+        Some(idx) => {
+            attrs.retain(|(n, _)| n != name);
+            attrs.insert(idx, attr);
+        }
+        None => attrs.push(attr),
+    }
+}
+
+fn parse_attr(attr: &str) -> Result<(String, Vec<u8>), Pkcs11UriError> {
+    let idx = attr
+        .find('=')
+        .ok_or_else(|| Pkcs11UriError(format!("attribute {} has no value", attr)))?;
+    let name = &attr[..idx];
+    if name.is_empty() || !name.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-') {
+        return Err(Pkcs11UriError(format!("invalid attribute name {}", name)));
+    }
+    Ok((name.to_ascii_lowercase(), percent_decode(&attr[idx + 1..])?))
+}
+
+fn validate_path_attr(name: &str, value: &[u8]) -> Result<(), Pkcs11UriError> {
+    let value = str::from_utf8(value);
+    let valid = match name {
+        "id" => true,
+        "slot-id" => {
+            matches!(value, Ok(v) if !v.is_empty() && v.bytes().all(|c| c.is_ascii_digit()))
+        }
+        "library-version" => matches!(value, Ok(v) if {
+            let mut parts = v.splitn(2, '.');
+            parts
+                .all(|p| !p.is_empty() && p.bytes().all(|c| c.is_ascii_digit()))
+        }),
+        _ => value.is_ok(),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(Pkcs11UriError(format!(
+            "invalid value for attribute {}",
+            name
+        )))
+    }
+}
+
+fn percent_decode(value: &str) -> Result<Vec<u8>, Pkcs11UriError> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let byte = bytes
+                .get(i + 1..i + 3)
+                .and_then(|hex| str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| Pkcs11UriError("invalid percent-encoding".to_string()))?;
+            out.push(byte);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(out)
+}
+
+fn is_unreserved(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || b"-._~".contains(&c)
+}
+
+fn write_attr<F>(fmt: &mut fmt::Formatter<'_>, name: &str, value: &[u8], allowed: F) -> fmt::Result
+where
+    F: Fn(u8) -> bool,
+{
+    fmt.write_str(name)?;
+    fmt.write_str("=")?;
+    for &c in value {
+        if allowed(c) {
+            write!(fmt, "{}", c as char)?;
+        } else {
+            write!(fmt, "%{:02X}", c)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let uri = Pkcs11Uri::parse(
+            "PKCS11:token=The%20Software%20PKCS%2311%20Softtoken;manufacturer=Snake%20Oil,%20Inc.;\
+             model=1.0;serial=42;object=my-certificate;type=cert;slot-id=3;library-version=7.1\
+             ?pin-source=file:/etc/token_pin&x-vendor=a&x-vendor=b",
+        )
+        .unwrap();
+        assert_eq!(uri.token(), Some("The Software PKCS#11 Softtoken"));
+        assert_eq!(uri.manufacturer(), Some("Snake Oil, Inc."));
+        assert_eq!(uri.model(), Some("1.0"));
+        assert_eq!(uri.serial(), Some("42"));
+        assert_eq!(uri.object(), Some("my-certificate"));
+        assert_eq!(uri.object_type(), Some(Pkcs11ObjectType::CERT));
+        assert_eq!(uri.slot_id(), Some(3));
+        assert_eq!(uri.path_attr("library-version"), Some(&b"7.1"[..]));
+        assert_eq!(uri.pin_source(), Some("file:/etc/token_pin"));
+        assert_eq!(uri.query_attr("x-vendor"), Some(&b"a"[..]));
+        assert_eq!(uri.id(), None);
+        assert_eq!(uri.pin_value(), None);
+
+        let empty = Pkcs11Uri::parse("pkcs11:").unwrap();
+        assert_eq!(empty, Pkcs11Uri::new());
+        assert_eq!(empty.to_string(), "pkcs11:");
+    }
+
+    #[test]
+    fn invalid() {
+        for uri in [
+            "pkcs:token=a",
+            "pkcs11:token",
+            "pkcs11:token=a;token=b",
+            "pkcs11:=a",
+            "pkcs11:id=%0",
+            "pkcs11:id=%zz",
+            "pkcs11:slot-id=x",
+            "pkcs11:library-version=1.",
+            "pkcs11:token=%ff",
+            "pkcs11:?pin-value=1&pin-value=2",
+        ] {
+            assert!(Pkcs11Uri::parse(uri).is_err(), "{}", uri);
+        }
+    }
+
+    #[test]
+    fn format() {
+        let mut uri = Pkcs11Uri::new();
+        uri.set_token("my token/1");
+        uri.set_id(b"\x01a");
+        uri.set_object_type(Pkcs11ObjectType::PRIVATE);
+        uri.set_pin_value("1 2/3");
+        uri.set_query_attr("module-path", b"/usr/lib/softhsm/libsofthsm2.so");
+        assert_eq!(
+            uri.to_string(),
+            "pkcs11:token=my%20token%2F1;id=%01%61;type=private\
+             ?pin-value=1%202/3&module-path=/usr/lib/softhsm/libsofthsm2.so"
+        );
+        assert_eq!(uri.to_string().parse::<Pkcs11Uri>().unwrap(), uri);
+
+        uri.set_token("other");
+        uri.remove_path_attr("id");
+        uri.remove_query_attr("pin-value");
+        assert_eq!(
+            uri.to_string(),
+            "pkcs11:token=other;type=private?module-path=/usr/lib/softhsm/libsofthsm2.so"
+        );
+    }
+}
@@ -3,12 +3,23 @@ use libc::c_int;
 use std::convert::TryInto;
 #[cfg(not(boringssl))]
 use std::ptr;
+use std::time::{Duration, Instant};
 
 use crate::cvt;
+#[cfg(not(boringssl))]
+use crate::cvt_p;
 use crate::error::ErrorStack;
 use crate::hash::MessageDigest;
 #[cfg(not(boringssl))]
+use crate::nid::Nid;
+#[cfg(not(boringssl))]
 use crate::symm::Cipher;
+#[cfg(not(boringssl))]
+use crate::x509::der;
+#[cfg(not(boringssl))]
+use crate::x509::{X509Algorithm, X509AlgorithmRef};
+#[cfg(not(boringssl))]
+use foreign_types::{ForeignType, ForeignTypeRef};
 use openssl_macros::corresponds;
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
@@ -144,11 +155,315 @@ pub fn scrypt(
     }
 }
 
+/// Key derivation parameters recommended by [`calibrate`].
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
+pub struct KdfParams {
+    pbkdf2_iter: usize,
+    #[cfg(any(ossl110, boringssl))]
+    scrypt_n: u64,
+    #[cfg(any(ossl110, boringssl))]
+    scrypt_r: u64,
+    #[cfg(any(ossl110, boringssl))]
+    scrypt_p: u64,
+}
+
+impl KdfParams {
+    /// Returns the number of PBKDF2 iterations, for use with [`pbkdf2_hmac`].
+    pub fn pbkdf2_iter(&self) -> usize {
+        self.pbkdf2_iter
+    }
+
+    /// Returns the scrypt CPU/memory cost parameter `n`, for use with [`scrypt`].
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(any(ossl110, boringssl))]
+    pub fn scrypt_n(&self) -> u64 {
+        self.scrypt_n
+    }
+
+    /// Returns the scrypt block size parameter `r`, for use with [`scrypt`].
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(any(ossl110, boringssl))]
+    pub fn scrypt_r(&self) -> u64 {
+        self.scrypt_r
+    }
+
+    /// Returns the scrypt parallelization parameter `p`, for use with [`scrypt`].
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(any(ossl110, boringssl))]
+    pub fn scrypt_p(&self) -> u64 {
+        self.scrypt_p
+    }
+}
+
+#[cfg(any(ossl110, boringssl))]
+const SCRYPT_R: u64 = 8;
+
+// OpenSSL's default limit when `maxmem` is 0
+#[cfg(any(ossl110, boringssl))]
+const SCRYPT_DEFAULT_MAXMEM: u64 = 32 * 1024 * 1024;
+
+/// Measures the host and recommends key derivation parameters which take about `target` to
+/// derive a key.
+///
+/// The PBKDF2 iteration count is calibrated for `hash`, assuming keys no longer than its output.
+/// For scrypt, `r` is fixed at 8 and `n` is the largest power of two which takes no longer than
+/// `target` and for which the derivation fits in `maxmem` bytes, or OpenSSL's default of 32 MiB
+/// if `maxmem` is 0, which should also be passed to [`scrypt`]. If the memory limit is reached
+/// first, `p` is increased to make up the remaining time, as libsodium does.
+///
+/// Calibration runs each function for a fraction of `target`, and the recommendations are
+/// extrapolated from those runs, so they are only as stable as the load on the host. Applications
+/// should store the parameters alongside each derived key rather than calibrating again to verify
+/// it.
+pub fn calibrate(
+    hash: MessageDigest,
+    target: Duration,
+    maxmem: u64,
+) -> Result<KdfParams, ErrorStack> {
+    let sample = target / 8;
+    let mut key = vec![0; hash.size()];
+
+    // OpenSSL takes the iteration count as an int
+    let max_iter = i32::MAX as u64;
+    let (iter, elapsed) = measure(1024, max_iter, sample, |iter| {
+        pbkdf2_hmac(
+            b"password",
+            b"saltsaltsaltsalt",
+            iter as usize,
+            hash,
+            &mut key,
+        )
+    })?;
+    let pbkdf2_iter = scale(iter, elapsed, target).max(1).min(max_iter) as usize;
+
+    #[cfg(any(ossl110, boringssl))]
+    {
+        let maxmem = if maxmem == 0 {
+            SCRYPT_DEFAULT_MAXMEM
+        } else {
+            maxmem
+        };
+        let mut max_n = 2;
+        while max_n < 1 << 62 && scrypt_mem(max_n * 2, 1) <= maxmem {
+            max_n *= 2;
+        }
+
+        let mut key = [0; 32];
+        let (n, elapsed) = measure(1024.min(max_n), max_n, sample, |n| {
+            scrypt(
+                b"password",
+                b"saltsaltsaltsalt",
+                n,
+                SCRYPT_R,
+                1,
+                maxmem,
+                &mut key,
+            )
+        })?;
+        // the total work done, in units of one iteration with p = 1
+        let budget = scale(n, elapsed, target).max(2);
+
+        let mut scrypt_n = 2;
+        while scrypt_n * 2 <= budget && scrypt_n * 2 <= max_n {
+            scrypt_n *= 2;
+        }
+        let mut scrypt_p = (budget / scrypt_n).max(1);
+        while scrypt_n > 2 && scrypt_mem(scrypt_n, scrypt_p) > maxmem {
+            scrypt_n /= 2;
+            scrypt_p = (budget / scrypt_n).max(1);
+        }
+
+        Ok(KdfParams {
+            pbkdf2_iter,
+            scrypt_n,
+            scrypt_r: SCRYPT_R,
+            scrypt_p,
+        })
+    }
+
+    #[cfg(not(any(ossl110, boringssl)))]
+    {
+        let _ = maxmem;
+        Ok(KdfParams { pbkdf2_iter })
+    }
+}
+
+/// Doubles `work` from `start` until running `f` with it takes at least `min`, or `work` reaches
+/// `max`.
+fn measure<F>(start: u64, max: u64, min: Duration, mut f: F) -> Result<(u64, Duration), ErrorStack>
+where
+    F: FnMut(u64) -> Result<(), ErrorStack>,
+{
+    let mut work = start;
+    loop {
+        let now = Instant::now();
+        f(work)?;
+        let elapsed = now.elapsed();
+        if elapsed >= min || work > max / 2 {
+            return Ok((work, elapsed));
+        }
+        work *= 2;
+    }
+}
+
+/// Extrapolates the amount of work taking `elapsed` to the amount taking `target`.
+fn scale(work: u64, elapsed: Duration, target: Duration) -> u64 {
+    let elapsed = elapsed.as_nanos().max(1);
+    let work = u128::from(work) * target.as_nanos() / elapsed;
+    work.try_into().unwrap_or(u64::MAX)
+}
+
+/// The memory used by scrypt, as checked by OpenSSL against `maxmem`.
+#[cfg(any(ossl110, boringssl))]
+fn scrypt_mem(n: u64, p: u64) -> u64 {
+    128u64
+        .saturating_mul(SCRYPT_R)
+        .saturating_mul(n.saturating_add(2).saturating_add(p))
+}
+
+/// Encrypts `data` with PBES2, deriving the key from `pass` with PBKDF2.
+///
+/// A random salt and IV are generated, the key is derived with `iter` iterations of PBKDF2 using
+/// the HMAC `prf`, such as `Nid::HMACWITHSHA256`, and `data` is encrypted with `cipher`.
+///
+/// The result is DER-encoded with the same structure as a PKCS#8 `EncryptedPrivateKeyInfo`:
+///
+/// ```text
+/// SEQUENCE {
+///     encryptionAlgorithm AlgorithmIdentifier,
+///     encryptedData OCTET STRING }
+/// ```
+///
+/// It can be decrypted with [`pbes2_decrypt`], or by any other tool implementing [RFC 8018].
+///
+/// [RFC 8018]: https://tools.ietf.org/html/rfc8018#section-6.2
+#[corresponds(PKCS5_pbe2_set_iv)]
+#[cfg(not(boringssl))]
+pub fn pbes2_encrypt(
+    pass: &[u8],
+    data: &[u8],
+    cipher: Cipher,
+    iter: u32,
+    prf: Nid,
+) -> Result<Vec<u8>, ErrorStack> {
+    unsafe {
+        ffi::init();
+        let alg = cvt_p(ffi::PKCS5_pbe2_set_iv(
+            cipher.as_ptr(),
+            iter.try_into().unwrap(),
+            ptr::null_mut(),
+            0,
+            ptr::null_mut(),
+            prf.as_raw(),
+        ))?;
+        let alg = X509Algorithm::from_ptr(alg);
+        pbe_encrypt(&alg, pass, data)
+    }
+}
+
+/// Encrypts `data` with PBES2, deriving the key from `pass` with scrypt.
+///
+/// This is the same as [`pbes2_encrypt`], with the key instead derived using the scrypt
+/// parameters `n`, `r`, and `p`, as defined by [RFC 7914].
+///
+/// Requires OpenSSL 1.1.0 or newer.
+///
+/// [RFC 7914]: https://tools.ietf.org/html/rfc7914#section-7
+#[corresponds(PKCS5_pbe2_set_scrypt)]
+#[cfg(ossl110)]
+pub fn pbes2_encrypt_scrypt(
+    pass: &[u8],
+    data: &[u8],
+    cipher: Cipher,
+    n: u64,
+    r: u64,
+    p: u64,
+) -> Result<Vec<u8>, ErrorStack> {
+    unsafe {
+        ffi::init();
+        let alg = cvt_p(ffi::PKCS5_pbe2_set_scrypt(
+            cipher.as_ptr(),
+            ptr::null(),
+            0,
+            ptr::null_mut(),
+            n,
+            r,
+            p,
+        ))?;
+        let alg = X509Algorithm::from_ptr(alg);
+        pbe_encrypt(&alg, pass, data)
+    }
+}
+
+/// Decrypts a DER-encoded structure produced by [`pbes2_encrypt`] or [`pbes2_encrypt_scrypt`].
+///
+/// Any password-based encryption scheme supported by OpenSSL is accepted, including the PKCS#5
+/// v1.5 and PKCS#12 schemes used by older tools.
+#[corresponds(PKCS12_pbe_crypt)]
+#[cfg(not(boringssl))]
+pub fn pbes2_decrypt(pass: &[u8], der: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+    let mut der = der;
+    let mut contents = der::read_tlv(&mut der, true, ffi::V_ASN1_SEQUENCE)?;
+    der::expect_end(der)?;
+
+    let alg = der::read_element(&mut contents, true, ffi::V_ASN1_SEQUENCE)?;
+    let alg = X509Algorithm::from_der(alg)?;
+    let data = der::read_tlv(&mut contents, false, ffi::V_ASN1_OCTET_STRING)?;
+    der::expect_end(contents)?;
+
+    pbe_crypt(&alg, pass, data, 0)
+}
+
+#[cfg(not(boringssl))]
+fn pbe_encrypt(alg: &X509AlgorithmRef, pass: &[u8], data: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+    let encrypted = pbe_crypt(alg, pass, data, 1)?;
+
+    let mut contents = alg.to_der()?;
+    der::write_tlv(&mut contents, false, ffi::V_ASN1_OCTET_STRING, &encrypted)?;
+    let mut der = vec![];
+    der::write_tlv(&mut der, true, ffi::V_ASN1_SEQUENCE, &contents)?;
+    Ok(der)
+}
+
+#[cfg(not(boringssl))]
+fn pbe_crypt(
+    alg: &X509AlgorithmRef,
+    pass: &[u8],
+    data: &[u8],
+    en_de: c_int,
+) -> Result<Vec<u8>, ErrorStack> {
+    unsafe {
+        let mut out = ptr::null_mut();
+        let mut out_len = 0;
+        cvt_p(ffi::PKCS12_pbe_crypt(
+            alg.as_ptr(),
+            pass.as_ptr() as *const _,
+            pass.len().try_into().unwrap(),
+            data.as_ptr() as *mut _,
+            data.len().try_into().unwrap(),
+            &mut out,
+            &mut out_len,
+            en_de,
+        ))?;
+        let buf = std::slice::from_raw_parts(out, out_len as usize).to_vec();
+        ffi::OPENSSL_free(out as *mut _);
+        Ok(buf)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::hash::MessageDigest;
     #[cfg(not(boringssl))]
+    use crate::nid::Nid;
+    #[cfg(not(boringssl))]
+    use crate::pkey::PKey;
+    #[cfg(not(boringssl))]
     use crate::symm::Cipher;
+    use std::time::Duration;
 
     // Test vectors from
     // https://git.lysator.liu.se/nettle/nettle/blob/nettle_3.1.1_release_20150424/testsuite/pbkdf2-test.c
@@ -307,4 +622,101 @@ mod tests {
         .unwrap();
         assert_eq!(hex::encode(&actual[..]), expected);
     }
+
+    #[test]
+    fn calibrate() {
+        let params = super::calibrate(
+            MessageDigest::sha256(),
+            Duration::from_millis(40),
+            16 * 1024 * 1024,
+        )
+        .unwrap();
+        assert!(params.pbkdf2_iter() >= 1);
+
+        let mut key = [0; 32];
+        super::pbkdf2_hmac(
+            b"password",
+            b"salt",
+            params.pbkdf2_iter(),
+            MessageDigest::sha256(),
+            &mut key,
+        )
+        .unwrap();
+
+        #[cfg(any(ossl110, boringssl))]
+        {
+            assert!(params.scrypt_n().is_power_of_two());
+            assert!(params.scrypt_n() >= 2);
+            assert_eq!(params.scrypt_r(), 8);
+            assert!(params.scrypt_p() >= 1);
+
+            super::scrypt(
+                b"password",
+                b"salt",
+                params.scrypt_n(),
+                params.scrypt_r(),
+                params.scrypt_p(),
+                16 * 1024 * 1024,
+                &mut key,
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg(not(boringssl))]
+    fn pbes2() {
+        let der = super::pbes2_encrypt(
+            b"hunter2",
+            b"secret",
+            Cipher::aes_256_cbc(),
+            2048,
+            Nid::HMACWITHSHA256,
+        )
+        .unwrap();
+        assert_eq!(super::pbes2_decrypt(b"hunter2", &der).unwrap(), b"secret");
+        // a wrong password usually fails the padding check, but may produce garbage instead
+        assert_ne!(
+            super::pbes2_decrypt(b"hunter3", &der).ok(),
+            Some(b"secret".to_vec())
+        );
+
+        // the same password and data encrypt differently, as the salt and IV are random
+        let der2 = super::pbes2_encrypt(
+            b"hunter2",
+            b"secret",
+            Cipher::aes_256_cbc(),
+            2048,
+            Nid::HMACWITHSHA256,
+        )
+        .unwrap();
+        assert_ne!(der, der2);
+    }
+
+    #[test]
+    #[cfg(not(boringssl))]
+    fn pbes2_pkcs8() {
+        let key = PKey::private_key_from_pem(include_bytes!("../test/rsa.pem")).unwrap();
+        let pkcs8 = key.private_key_to_pkcs8().unwrap();
+        let der = super::pbes2_encrypt(
+            b"mypass",
+            &pkcs8,
+            Cipher::aes_128_cbc(),
+            2048,
+            Nid::HMACWITHSHA256,
+        )
+        .unwrap();
+
+        let decrypted = PKey::private_key_from_pkcs8_passphrase(&der, b"mypass").unwrap();
+        assert!(key.public_eq(&decrypted));
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn pbes2_scrypt() {
+        let der =
+            super::pbes2_encrypt_scrypt(b"hunter2", b"secret", Cipher::aes_256_cbc(), 1024, 8, 1)
+                .unwrap();
+        assert_eq!(super::pbes2_decrypt(b"hunter2", &der).unwrap(), b"secret");
+    }
 }
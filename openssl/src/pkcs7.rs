@@ -1,10 +1,11 @@
 use bitflags::bitflags;
 use foreign_types::{ForeignType, ForeignTypeRef};
 use libc::c_int;
+use std::io::{self, Read, Write};
 use std::mem;
 use std::ptr;
 
-use crate::bio::{MemBio, MemBioSlice};
+use crate::bio::{self, MemBio, MemBioSlice};
 use crate::error::ErrorStack;
 use crate::pkey::{HasPrivate, PKeyRef};
 use crate::stack::{Stack, StackRef};
@@ -165,6 +166,56 @@ impl Pkcs7Ref {
         }
     }
 
+    /// Serializes the PKCS#7 structure using BER, reading the content from `input` as it is
+    /// written to `output`.
+    ///
+    /// The structure must have been created with `Pkcs7Flags::STREAM`, in which case
+    /// `Pkcs7::sign` and `Pkcs7::encrypt` do not read their input, and the content is signed or
+    /// encrypted here instead. The content is never held in memory in full, so this is suitable
+    /// for payloads too large to buffer. The content is omitted from the output if
+    /// `Pkcs7Flags::DETACHED` was set.
+    ///
+    /// `flags` should be the flags the structure was created with.
+    #[corresponds(i2d_PKCS7_bio_stream)]
+    pub fn write_der_stream<R, W>(
+        &mut self,
+        input: R,
+        output: W,
+        flags: Pkcs7Flags,
+    ) -> io::Result<()>
+    where
+        R: Read,
+        W: Write,
+    {
+        bio::stream(input, output, |input, output| unsafe {
+            ffi::i2d_PKCS7_bio_stream(output, self.as_ptr(), input, flags.bits)
+        })
+    }
+
+    /// Converts the PKCS#7 structure to S/MIME format, reading the content from `input` as it is
+    /// written to `output`.
+    ///
+    /// This is the streaming equivalent of [`to_smime`], with the same requirements as
+    /// [`write_der_stream`].
+    ///
+    /// [`to_smime`]: Pkcs7Ref::to_smime
+    /// [`write_der_stream`]: Pkcs7Ref::write_der_stream
+    #[corresponds(SMIME_write_PKCS7)]
+    pub fn write_smime_stream<R, W>(
+        &mut self,
+        input: R,
+        output: W,
+        flags: Pkcs7Flags,
+    ) -> io::Result<()>
+    where
+        R: Read,
+        W: Write,
+    {
+        bio::stream(input, output, |input, output| unsafe {
+            ffi::SMIME_write_PKCS7(output, self.as_ptr(), input, flags.bits)
+        })
+    }
+
     to_pem! {
         /// Serializes the data into a PEM-encoded PKCS#7 structure.
         ///
@@ -319,6 +370,36 @@ mod tests {
         assert_eq!(decoded, message.into_bytes());
     }
 
+    #[test]
+    fn stream_encrypt_decrypt() {
+        let cert = X509::from_pem(include_bytes!("../test/certs.pem")).unwrap();
+        let mut certs = Stack::new().unwrap();
+        certs.push(cert.clone()).unwrap();
+        let pkey = PKey::private_key_from_pem(include_bytes!("../test/key.pem")).unwrap();
+
+        let input = vec![b'x'; 1 << 20];
+        let flags = Pkcs7Flags::STREAM | Pkcs7Flags::BINARY;
+        let mut pkcs7 = Pkcs7::encrypt(&certs, &[], Cipher::aes_256_cbc(), flags).unwrap();
+        let mut encrypted = vec![];
+        pkcs7
+            .write_smime_stream(&input[..], &mut encrypted, flags)
+            .unwrap();
+
+        let (pkcs7, _) = Pkcs7::from_smime(&encrypted).unwrap();
+        let decrypted = pkcs7.decrypt(&pkey, &cert, Pkcs7Flags::empty()).unwrap();
+        assert_eq!(decrypted, input);
+
+        let mut pkcs7 = Pkcs7::encrypt(&certs, &[], Cipher::aes_256_cbc(), flags).unwrap();
+        let mut encrypted = vec![];
+        pkcs7
+            .write_der_stream(&input[..], &mut encrypted, flags)
+            .unwrap();
+
+        let pkcs7 = Pkcs7::from_der(&encrypted).unwrap();
+        let decrypted = pkcs7.decrypt(&pkey, &cert, Pkcs7Flags::empty()).unwrap();
+        assert_eq!(decrypted, input);
+    }
+
     #[test]
     fn sign_verify_test_detached() {
         let cert = include_bytes!("../test/cert.pem");
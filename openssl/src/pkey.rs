@@ -47,6 +47,10 @@ use crate::dh::Dh;
 use crate::dsa::Dsa;
 use crate::ec::EcKey;
 use crate::error::ErrorStack;
+#[cfg(ossl300)]
+use crate::hash::MessageDigest;
+#[cfg(not(boringssl))]
+use crate::nid::Nid;
 #[cfg(any(ossl110, boringssl, libressl370))]
 use crate::pkey_ctx::PkeyCtx;
 use crate::rsa::Rsa;
@@ -58,6 +62,8 @@ use foreign_types::{ForeignType, ForeignTypeRef};
 use libc::{c_int, c_long};
 use openssl_macros::corresponds;
 use std::convert::{TryFrom, TryInto};
+#[cfg(ossl300)]
+use std::ffi::CStr;
 use std::ffi::CString;
 use std::fmt;
 use std::mem;
@@ -202,6 +208,113 @@ impl<T> PKeyRef<T> {
     pub fn size(&self) -> usize {
         unsafe { ffi::EVP_PKEY_size(self.as_ptr()) as usize }
     }
+
+    /// Returns the name of the key's type, as used by its provider.
+    ///
+    /// Unlike [`id`], this identifies key types which do not have an [`Id`].
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// [`id`]: PKeyRef::id
+    #[corresponds(EVP_PKEY_get0_type_name)]
+    #[cfg(ossl300)]
+    pub fn type_name(&self) -> Option<&str> {
+        unsafe {
+            let ptr = ffi::EVP_PKEY_get0_type_name(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                CStr::from_ptr(ptr).to_str().ok()
+            }
+        }
+    }
+
+    /// Determines if the key's type supports signing.
+    ///
+    /// This does not check whether the private key is present.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[corresponds(EVP_PKEY_can_sign)]
+    #[cfg(ossl300)]
+    pub fn can_sign(&self) -> bool {
+        unsafe { ffi::EVP_PKEY_can_sign(self.as_ptr()) == 1 }
+    }
+
+    /// Returns the digest which should be used when signing with this key.
+    #[corresponds(EVP_PKEY_get_default_digest_nid)]
+    #[cfg(not(boringssl))]
+    pub fn default_digest(&self) -> Result<DefaultDigest, ErrorStack> {
+        unsafe {
+            cfg_if! {
+                if #[cfg(ossl300)] {
+                    // the NID based function fails for keys which must be used without a digest
+                    let mut name = [0; 80];
+                    let r = cvt(ffi::EVP_PKEY_get_default_digest_name(
+                        self.as_ptr(),
+                        name.as_mut_ptr(),
+                        name.len(),
+                    ))?;
+                    let name = CStr::from_ptr(name.as_ptr()).to_string_lossy();
+                    let nid = if name == "UNDEF" {
+                        Nid::UNDEF
+                    } else {
+                        match MessageDigest::from_name(&name) {
+                            Some(md) => md.type_(),
+                            None => return Err(ErrorStack::report()),
+                        }
+                    };
+                } else {
+                    let mut nid = 0;
+                    let r = cvt(ffi::EVP_PKEY_get_default_digest_nid(
+                        self.as_ptr(),
+                        &mut nid,
+                    ))?;
+                    let nid = Nid::from_raw(nid);
+                }
+            }
+            Ok(DefaultDigest {
+                nid,
+                mandatory: r == 2,
+            })
+        }
+    }
+}
+
+/// The digest a key should be signed with, as returned by [`PKeyRef::default_digest`].
+#[cfg(not(boringssl))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DefaultDigest {
+    nid: Nid,
+    mandatory: bool,
+}
+
+#[cfg(not(boringssl))]
+impl DefaultDigest {
+    /// Returns the digest.
+    ///
+    /// This is [`Nid::UNDEF`] if the key signs messages directly without a separate digest, as with Ed25519.
+    pub fn nid(&self) -> Nid {
+        self.nid
+    }
+
+    /// Determines if the key can only be used with this digest, rather than it merely being a recommendation.
+    pub fn is_mandatory(&self) -> bool {
+        self.mandatory
+    }
+
+    /// Returns the digest to pass to signing functions such as [`X509Builder::sign`].
+    ///
+    /// This is [`MessageDigest::null`] if the key signs messages directly, and `None` if the digest is not
+    /// supported.
+    ///
+    /// [`X509Builder::sign`]: crate::x509::X509Builder::sign
+    pub fn message_digest(&self) -> Option<MessageDigest> {
+        if self.nid == Nid::UNDEF {
+            Some(MessageDigest::null())
+        } else {
+            MessageDigest::from_nid(self.nid)
+        }
+    }
 }
 
 impl<T> PKeyRef<T>
@@ -375,6 +488,7 @@ where
     }
 }
 
+// Only the algorithm is printed so that private keys can't leak into logs.
 impl<T> fmt::Debug for PKey<T> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         let alg = match self.id() {
@@ -894,6 +1008,13 @@ mod tests {
         assert!(PKey::private_key_from_pem_passphrase(&pem, b"fizzbuzz").is_err());
     }
 
+    #[test]
+    fn test_debug_redacted() {
+        let rsa = Rsa::generate(2048).unwrap();
+        let pkey = PKey::from_rsa(rsa).unwrap();
+        assert_eq!(format!("{:?}", pkey), "PKey { algorithm: \"RSA\" }");
+    }
+
     #[test]
     fn test_unencrypted_pkcs8() {
         let key = include_bytes!("../test/pkcs8-nocrypt.der");
@@ -1170,4 +1291,35 @@ mod tests {
         assert!(!pkey1.public_eq(&pkey2));
         assert!(Error::get().is_none());
     }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn type_name_and_can_sign() {
+        let rsa = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        assert_eq!(rsa.type_name(), Some("RSA"));
+        assert!(rsa.can_sign());
+
+        let x25519 = PKey::generate_x25519().unwrap();
+        assert_eq!(x25519.type_name(), Some("X25519"));
+        assert!(!x25519.can_sign());
+    }
+
+    #[test]
+    #[cfg(not(boringssl))]
+    fn default_digest() {
+        let rsa = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let digest = rsa.default_digest().unwrap();
+        assert_eq!(digest.nid(), Nid::SHA256);
+        assert!(!digest.is_mandatory());
+        assert!(digest.message_digest() == Some(MessageDigest::sha256()));
+
+        #[cfg(ossl111)]
+        {
+            let ed25519 = PKey::generate_ed25519().unwrap();
+            let digest = ed25519.default_digest().unwrap();
+            assert_eq!(digest.nid(), Nid::UNDEF);
+            assert!(digest.is_mandatory());
+            assert!(digest.message_digest() == Some(MessageDigest::null()));
+        }
+    }
 }
@@ -67,6 +67,8 @@ let cmac_key = ctx.keygen().unwrap();
 #[cfg(not(boringssl))]
 use crate::cipher::CipherRef;
 use crate::error::ErrorStack;
+#[cfg(ossl300)]
+use crate::lib_ctx::LibCtxRef;
 use crate::md::MdRef;
 use crate::pkey::{HasPrivate, HasPublic, Id, PKey, PKeyRef, Private};
 use crate::rsa::Padding;
@@ -77,6 +79,8 @@ use foreign_types::{ForeignType, ForeignTypeRef};
 use libc::c_int;
 use openssl_macros::corresponds;
 use std::convert::TryFrom;
+#[cfg(ossl300)]
+use std::ffi::CString;
 use std::ptr;
 
 /// HKDF modes of operation.
@@ -125,6 +129,31 @@ impl<T> PkeyCtx<T> {
             Ok(PkeyCtx::from_ptr(ptr))
         }
     }
+
+    /// Creates a new pkey context using the provided key, fetching the implementation of its
+    /// algorithm from `ctx` according to the property query string `properties`.
+    ///
+    /// If `ctx` is `None`, the default library context is used.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[corresponds(EVP_PKEY_CTX_new_from_pkey)]
+    #[cfg(ossl300)]
+    pub fn new_from_pkey(
+        ctx: Option<&LibCtxRef>,
+        pkey: &PKeyRef<T>,
+        properties: Option<&str>,
+    ) -> Result<Self, ErrorStack> {
+        let properties = properties.map(|s| CString::new(s).unwrap());
+
+        unsafe {
+            let ptr = cvt_p(ffi::EVP_PKEY_CTX_new_from_pkey(
+                ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+                pkey.as_ptr(),
+                properties.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+            ))?;
+            Ok(PkeyCtx::from_ptr(ptr))
+        }
+    }
 }
 
 impl PkeyCtx<()> {
@@ -137,6 +166,32 @@ impl PkeyCtx<()> {
             Ok(PkeyCtx::from_ptr(ptr))
         }
     }
+
+    /// Creates a new pkey context for the algorithm named `name`, fetching its implementation
+    /// from `ctx` according to the property query string `properties`.
+    ///
+    /// If `ctx` is `None`, the default library context is used.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[corresponds(EVP_PKEY_CTX_new_from_name)]
+    #[cfg(ossl300)]
+    pub fn new_from_name(
+        ctx: Option<&LibCtxRef>,
+        name: &str,
+        properties: Option<&str>,
+    ) -> Result<Self, ErrorStack> {
+        let name = CString::new(name).unwrap();
+        let properties = properties.map(|s| CString::new(s).unwrap());
+
+        unsafe {
+            let ptr = cvt_p(ffi::EVP_PKEY_CTX_new_from_name(
+                ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+                name.as_ptr(),
+                properties.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+            ))?;
+            Ok(PkeyCtx::from_ptr(ptr))
+        }
+    }
 }
 
 impl<T> PkeyCtxRef<T>
@@ -234,7 +289,7 @@ where
             // errors on the stack, we return `Err()`, else we return
             // `Ok(false)`.
             if r <= 0 {
-                let errors = ErrorStack::get();
+                let errors = ErrorStack::report();
                 if !errors.errors().is_empty() {
                     return Err(errors);
                 }
@@ -999,4 +1054,19 @@ mod test {
         // The digest is the end of the DigestInfo structure.
         assert_eq!(result_buf[length - digest.len()..length], digest);
     }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn new_from_name() {
+        let mut ctx = PkeyCtx::new_from_name(None, "X25519", Some("provider=default")).unwrap();
+        ctx.keygen_init().unwrap();
+        let key = ctx.keygen().unwrap();
+        assert_eq!(key.id(), Id::X25519);
+
+        let mut ctx = PkeyCtx::new_from_pkey(None, &key, Some("provider=default")).unwrap();
+        ctx.derive_init().unwrap();
+
+        let ctx = PkeyCtx::new_from_name(None, "X25519", Some("provider=bogus"));
+        assert!(ctx.and_then(|mut ctx| ctx.keygen_init()).is_err());
+    }
 }
@@ -0,0 +1,281 @@
+//! Algorithm policies for compliance-controlled builds.
+//!
+//! A [`Policy`] combines a default property query string, which OpenSSL applies to every
+//! algorithm fetched from a library context, with a list of algorithms which may not be used for
+//! particular operations. Policies are installed per library context; installing one for the
+//! default library context makes it process-wide.
+//!
+//! The deny list is enforced by this crate rather than by OpenSSL. It applies to [`Hasher`],
+//! [`Signer`], [`Verifier`], [`MdCtxRef`], [`CipherCtxRef`] (and so [`Crypter`]), [`Md::fetch`],
+//! and [`Cipher::fetch`], but not to algorithms used internally by OpenSSL, such as those used to
+//! verify X.509 certificates or TLS handshakes. Security levels restrict those.
+//!
+//! Requires OpenSSL 3.0.0 or newer.
+//!
+//! [`Hasher`]: crate::hash::Hasher
+//! [`Signer`]: crate::sign::Signer
+//! [`Verifier`]: crate::sign::Verifier
+//! [`MdCtxRef`]: crate::md_ctx::MdCtxRef
+//! [`CipherCtxRef`]: crate::cipher_ctx::CipherCtxRef
+//! [`Crypter`]: crate::symm::Crypter
+//! [`Md::fetch`]: crate::md::Md::fetch
+//! [`Cipher::fetch`]: crate::cipher::Cipher::fetch
+//!
+//! # Examples
+//!
+//! Refuse to verify SHA-1 signatures anywhere in the process:
+//!
+//! ```
+//! use openssl::hash::MessageDigest;
+//! use openssl::nid::Nid;
+//! use openssl::pkey::PKey;
+//! use openssl::policy::{self, Operation, Policy};
+//! use openssl::rsa::Rsa;
+//! use openssl::sign::Verifier;
+//!
+//! Policy::new().deny(Nid::SHA1, Operation::Verify).install(None).unwrap();
+//!
+//! let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+//! assert!(Verifier::new(MessageDigest::sha1(), &key).is_err());
+//! assert!(Verifier::new(MessageDigest::sha256(), &key).is_ok());
+//!
+//! policy::clear(None);
+//! ```
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+use foreign_types::ForeignTypeRef;
+use once_cell::sync::Lazy;
+
+use crate::error::ErrorStack;
+use crate::lib_ctx::{LibCtx, LibCtxRef};
+use crate::nid::Nid;
+
+/// An operation which a policy can deny.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Operation {
+    /// Computing a message digest with the algorithm.
+    Digest,
+    /// Creating signatures using the algorithm as the digest.
+    Sign,
+    /// Verifying signatures using the algorithm as the digest.
+    Verify,
+    /// Encrypting or decrypting with the algorithm.
+    Cipher,
+}
+
+/// A set of restrictions on the algorithms of a library context.
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    properties: Option<String>,
+    denied: Vec<(Nid, Operation)>,
+}
+
+impl Policy {
+    /// Creates a new policy which permits everything.
+    pub fn new() -> Policy {
+        Policy::default()
+    }
+
+    /// Sets the default property query string, such as `"fips=yes"`.
+    ///
+    /// See [`LibCtx::set_default_properties`].
+    ///
+    /// [`LibCtx::set_default_properties`]: crate::lib_ctx::LibCtx::set_default_properties
+    pub fn default_properties(&mut self, properties: &str) -> &mut Policy {
+        self.properties = Some(properties.to_string());
+        self
+    }
+
+    /// Denies the use of the algorithm `nid` for `operation`.
+    ///
+    /// Denying `Operation::Digest` does not deny signatures using the digest, which must be
+    /// denied separately.
+    pub fn deny(&mut self, nid: Nid, operation: Operation) -> &mut Policy {
+        self.denied.push((nid, operation));
+        self
+    }
+
+    /// Installs the policy in `ctx`, replacing any deny list previously installed there.
+    ///
+    /// If `ctx` is `None`, the policy is installed in the default library context, and applies to
+    /// all operations which do not use an explicit library context. The policy of a context is
+    /// removed when the context is freed.
+    pub fn install(&self, ctx: Option<&LibCtxRef>) -> Result<(), ErrorStack> {
+        if let Some(properties) = &self.properties {
+            LibCtx::set_default_properties(ctx, properties)?;
+        }
+
+        let mut policies = POLICIES.write().unwrap();
+        policies.insert(key(ctx), Arc::new(self.denied.clone()));
+        ACTIVE.store(true, Ordering::Release);
+        Ok(())
+    }
+}
+
+/// Removes the deny list installed in `ctx`.
+///
+/// The default property query string is left in place.
+pub fn clear(ctx: Option<&LibCtxRef>) {
+    forget(key(ctx));
+}
+
+/// Determines if the policy of `ctx` denies the use of `nid` for `operation`.
+pub fn is_denied(ctx: Option<&LibCtxRef>, nid: Nid, operation: Operation) -> bool {
+    denied(key(ctx), nid, operation)
+}
+
+type DenyList = Arc<Vec<(Nid, Operation)>>;
+
+static POLICIES: Lazy<RwLock<HashMap<usize, DenyList>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+// Avoids taking the lock when no policy has ever been installed.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+fn key(ctx: Option<&LibCtxRef>) -> usize {
+    ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr) as usize
+}
+
+fn denied(key: usize, nid: Nid, operation: Operation) -> bool {
+    if !ACTIVE.load(Ordering::Acquire) {
+        return false;
+    }
+
+    let denied = match POLICIES.read().unwrap().get(&key) {
+        Some(denied) => denied.clone(),
+        None => return false,
+    };
+    denied.contains(&(nid, operation))
+}
+
+/// Removes the policy of the context at `key`, which is about to be freed.
+pub(crate) fn forget(key: usize) {
+    if !ACTIVE.load(Ordering::Acquire) {
+        return;
+    }
+    POLICIES.write().unwrap().remove(&key);
+}
+
+/// Returns an error if the policy of `ctx` denies the use of `nid` for `operation`.
+pub(crate) fn check(
+    ctx: *mut ffi::OSSL_LIB_CTX,
+    nid: Nid,
+    operation: Operation,
+) -> Result<(), ErrorStack> {
+    if !denied(ctx as usize, nid, operation) {
+        return Ok(());
+    }
+
+    let name = CString::new(nid.short_name().unwrap_or("unknown")).unwrap();
+    unsafe {
+        ffi::ERR_new();
+        ffi::ERR_set_debug(
+            concat!(file!(), "\0").as_ptr() as *const _,
+            line!() as _,
+            ptr::null(),
+        );
+        ffi::ERR_set_error(
+            ffi::ERR_LIB_EVP,
+            ffi::EVP_R_UNSUPPORTED_ALGORITHM,
+            b"%s denied by policy\0".as_ptr() as *const _,
+            name.as_ptr(),
+        );
+    }
+    Err(ErrorStack::report())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cipher::Cipher;
+    use crate::hash::{hash, MessageDigest};
+    use crate::md::Md;
+    use crate::md_ctx::MdCtx;
+    use crate::pkey::PKey;
+    use crate::rsa::Rsa;
+    use crate::sign::{Signer, Verifier};
+
+    // Tests run concurrently, so policies are only installed in private library contexts here;
+    // the default context is exercised by the module documentation.
+    #[test]
+    fn deny() {
+        let ctx = LibCtx::new().unwrap();
+        assert!(!is_denied(Some(&ctx), Nid::SHA1, Operation::Digest));
+
+        Policy::new()
+            .deny(Nid::SHA1, Operation::Digest)
+            .deny(Nid::AES_128_CBC, Operation::Cipher)
+            .install(Some(&ctx))
+            .unwrap();
+        assert!(is_denied(Some(&ctx), Nid::SHA1, Operation::Digest));
+        assert!(!is_denied(Some(&ctx), Nid::SHA1, Operation::Verify));
+        assert!(!is_denied(None, Nid::SHA1, Operation::Digest));
+
+        let e = match Md::fetch(Some(&ctx), "SHA1", None) {
+            Ok(_) => panic!("SHA-1 was not denied"),
+            Err(e) => e,
+        };
+        assert_eq!(e.errors()[0].data(), Some("SHA1 denied by policy"));
+        Md::fetch(Some(&ctx), "SHA256", None).unwrap();
+        Md::fetch(None, "SHA1", None).unwrap();
+        assert!(Cipher::fetch(Some(&ctx), "AES-128-CBC", None).is_err());
+        Cipher::fetch(Some(&ctx), "AES-256-CBC", None).unwrap();
+
+        // the default context is unaffected
+        hash(MessageDigest::sha1(), b"hello").unwrap();
+
+        clear(Some(&ctx));
+        Md::fetch(Some(&ctx), "SHA1", None).unwrap();
+    }
+
+    #[test]
+    fn deny_signatures() {
+        let ctx = LibCtx::new().unwrap();
+        Policy::new()
+            .deny(Nid::SHA1, Operation::Sign)
+            .install(Some(&ctx))
+            .unwrap();
+
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut md_ctx = MdCtx::new().unwrap();
+        assert!(md_ctx
+            .digest_sign_init_ex(Some("SHA1"), Some(&ctx), None, &key)
+            .is_err());
+        md_ctx
+            .digest_sign_init_ex(Some("SHA256"), Some(&ctx), None, &key)
+            .unwrap();
+        md_ctx
+            .digest_verify_init_ex(Some("SHA1"), Some(&ctx), None, &key)
+            .unwrap();
+
+        // the default context is unaffected
+        Signer::new(MessageDigest::sha1(), &key).unwrap();
+        Verifier::new(MessageDigest::sha1(), &key).unwrap();
+    }
+
+    #[test]
+    fn default_properties() {
+        let ctx = LibCtx::new().unwrap();
+        Policy::new()
+            .default_properties("provider=bogus")
+            .install(Some(&ctx))
+            .unwrap();
+        assert!(Md::fetch(Some(&ctx), "SHA256", None).is_err());
+    }
+
+    #[test]
+    fn freed_context() {
+        let ctx = LibCtx::new().unwrap();
+        let ptr = ctx.as_ptr() as usize;
+        Policy::new()
+            .deny(Nid::SHA1, Operation::Digest)
+            .install(Some(&ctx))
+            .unwrap();
+        drop(ctx);
+        assert!(!POLICIES.read().unwrap().contains_key(&ptr));
+    }
+}
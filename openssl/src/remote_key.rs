@@ -0,0 +1,535 @@
+//! Private keys whose operations are performed outside of OpenSSL.
+//!
+//! A [`RemoteKey`] performs the private key operations of a key held elsewhere, such as in a
+//! cloud key management service or a hardware module. [`PKey::from_remote_key`] wraps it in a
+//! `PKey<Private>` which can be used anywhere a private key is accepted, including for TLS
+//! with [`SslContextBuilder::set_private_key`] and for CMS signing, without the key material
+//! ever being available to OpenSSL.
+//!
+//! RSA and EC keys are supported.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::error::ErrorStack;
+//! use openssl::hash::MessageDigest;
+//! use openssl::md::Md;
+//! use openssl::nid::Nid;
+//! use openssl::pkey::{PKey, Private};
+//! use openssl::pkey_ctx::PkeyCtx;
+//! use openssl::remote_key::RemoteKey;
+//! use openssl::rsa::{Padding, Rsa};
+//! use openssl::sign::{Signer, Verifier};
+//!
+//! // Stands in for a key management service client.
+//! struct Kms(PKey<Private>);
+//!
+//! impl RemoteKey for Kms {
+//!     fn sign(&self, digest_type: Nid, digest: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+//!         let mut ctx = PkeyCtx::new(&self.0)?;
+//!         ctx.sign_init()?;
+//!         ctx.set_rsa_padding(Padding::PKCS1)?;
+//!         ctx.set_signature_md(Md::from_nid(digest_type).unwrap())?;
+//!         let mut signature = vec![];
+//!         ctx.sign_to_vec(digest, &mut signature)?;
+//!         Ok(signature)
+//!     }
+//! }
+//!
+//! let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+//! let public_key = PKey::public_key_from_pem(&key.public_key_to_pem().unwrap()).unwrap();
+//! let remote = PKey::from_remote_key(&public_key, Kms(key)).unwrap();
+//!
+//! let mut signer = Signer::new(MessageDigest::sha256(), &remote).unwrap();
+//! signer.update(b"hello world").unwrap();
+//! let signature = signer.sign_to_vec().unwrap();
+//!
+//! let mut verifier = Verifier::new(MessageDigest::sha256(), &public_key).unwrap();
+//! verifier.update(b"hello world").unwrap();
+//! assert!(verifier.verify(&signature).unwrap());
+//! ```
+//!
+//! [`PKey::from_remote_key`]: crate::pkey::PKey::from_remote_key
+//! [`SslContextBuilder::set_private_key`]: crate::ssl::SslContextBuilder::set_private_key
+use cfg_if::cfg_if;
+use foreign_types::ForeignType;
+use libc::{c_int, c_long, c_uchar, c_uint, c_void};
+use once_cell::sync::OnceCell;
+use std::mem;
+use std::ptr;
+use std::slice;
+
+use crate::ec::EcKey;
+use crate::ecdsa::EcdsaSig;
+use crate::error::ErrorStack;
+use crate::nid::Nid;
+use crate::pkey::{HasPublic, Id, PKey, PKeyRef, Private};
+use crate::rsa::{Padding, Rsa};
+use crate::{cvt, cvt_n, cvt_p};
+
+/// The private key operations of a key held outside of OpenSSL.
+///
+/// Errors returned from the methods are added to the OpenSSL error stack, and cause the
+/// operation that used the key to fail.
+pub trait RemoteKey: Send + Sync + 'static {
+    /// Signs a message digest.
+    ///
+    /// `digest_type` is the algorithm used to compute `digest`. For RSA keys the signature uses
+    /// PKCS#1 v1.5 padding, and for EC keys it is a DER-encoded ECDSA signature.
+    ///
+    /// `digest_type` is `Nid::MD5_SHA1` for the RSA signatures of TLS 1.1 and older, and may be
+    /// `Nid::UNDEF` for EC keys if the digest algorithm is not known.
+    fn sign(&self, digest_type: Nid, digest: &[u8]) -> Result<Vec<u8>, ErrorStack>;
+
+    /// Decrypts data with an RSA key.
+    ///
+    /// With `Padding::NONE`, this is the raw RSA private key operation, which OpenSSL also uses
+    /// to create RSA-PSS signatures after applying the padding itself, and to decrypt OAEP
+    /// ciphertexts before removing the padding. RSA-PSS is required for TLS 1.3, so keys which
+    /// do not support it can only be used with older protocol versions.
+    ///
+    /// The default implementation fails, which is appropriate for EC keys.
+    fn decrypt(&self, input: &[u8], padding: Padding) -> Result<Vec<u8>, ErrorStack> {
+        let _ = (input, padding);
+        Err(unsupported_key_type())
+    }
+}
+
+impl PKey<Private> {
+    /// Creates a private key whose operations are performed by `key`.
+    ///
+    /// `public_key` is the public half of the key, which must be an RSA or EC key.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    pub fn from_remote_key<T, K>(
+        public_key: &PKeyRef<T>,
+        key: K,
+    ) -> Result<PKey<Private>, ErrorStack>
+    where
+        T: HasPublic,
+        K: RemoteKey,
+    {
+        let key: Box<dyn RemoteKey> = Box::new(key);
+
+        unsafe {
+            match public_key.id() {
+                Id::RSA => {
+                    let public_key = public_key.rsa()?;
+                    let public_rsa = Rsa::from_public_components(
+                        public_key.n().to_owned()?,
+                        public_key.e().to_owned()?,
+                    )?;
+                    let rsa = Rsa::<Private>::from_ptr(public_rsa.as_ptr());
+                    mem::forget(public_rsa);
+                    cvt(ffi::RSA_set_method(rsa.as_ptr(), rsa_method()?))?;
+                    let data = Box::into_raw(Box::new(key)) as *mut c_void;
+                    cvt(ffi::RSA_set_ex_data(rsa.as_ptr(), rsa_index()?, data))?;
+                    PKey::from_rsa(rsa)
+                }
+                Id::EC => {
+                    let public_key = public_key.ec_key()?;
+                    let public_ec_key =
+                        EcKey::from_public_key(public_key.group(), public_key.public_key())?;
+                    let ec_key = EcKey::<Private>::from_ptr(public_ec_key.as_ptr());
+                    mem::forget(public_ec_key);
+                    cvt(ffi::EC_KEY_set_method(ec_key.as_ptr(), ec_key_method()?))?;
+                    let data = Box::into_raw(Box::new(key)) as *mut c_void;
+                    cvt(ffi::EC_KEY_set_ex_data(
+                        ec_key.as_ptr(),
+                        ec_key_index()?,
+                        data,
+                    ))?;
+                    PKey::from_ec_key(ec_key)
+                }
+                _ => Err(unsupported_key_type()),
+            }
+        }
+    }
+}
+
+fn unsupported_key_type() -> ErrorStack {
+    unsafe {
+        cfg_if! {
+            if #[cfg(ossl300)] {
+                ffi::ERR_new();
+                ffi::ERR_set_debug(concat!(file!(), "\0").as_ptr() as *const _, line!() as _, ptr::null());
+                ffi::ERR_set_error(
+                    ffi::ERR_LIB_EVP,
+                    ffi::EVP_R_OPERATION_NOT_SUPPORTED_FOR_THIS_KEYTYPE,
+                    ptr::null(),
+                );
+            } else {
+                ffi::ERR_put_error(
+                    ffi::ERR_LIB_EVP,
+                    0,
+                    ffi::EVP_R_OPERATION_NOT_SUPPORTED_FOR_THIS_KEYTYPE,
+                    concat!(file!(), "\0").as_ptr() as *const _,
+                    line!() as _,
+                );
+            }
+        }
+    }
+    ErrorStack::report()
+}
+
+struct RsaMethod(*mut ffi::RSA_METHOD);
+
+unsafe impl Sync for RsaMethod {}
+unsafe impl Send for RsaMethod {}
+
+struct EcKeyMethod(*mut ffi::EC_KEY_METHOD);
+
+unsafe impl Sync for EcKeyMethod {}
+unsafe impl Send for EcKeyMethod {}
+
+fn rsa_method() -> Result<*const ffi::RSA_METHOD, ErrorStack> {
+    static METHOD: OnceCell<RsaMethod> = OnceCell::new();
+
+    METHOD
+        .get_or_try_init(|| unsafe {
+            let method = cvt_p(ffi::RSA_meth_dup(ffi::RSA_PKCS1_OpenSSL()))?;
+            let method = RsaMethod(method);
+            cvt(ffi::RSA_meth_set1_name(
+                method.0,
+                b"rust-openssl remote key\0".as_ptr().cast(),
+            ))?;
+            cvt(ffi::RSA_meth_set_sign(method.0, Some(rsa_sign)))?;
+            cvt(ffi::RSA_meth_set_priv_enc(method.0, Some(rsa_priv_enc)))?;
+            cvt(ffi::RSA_meth_set_priv_dec(method.0, Some(rsa_priv_dec)))?;
+            Ok(method)
+        })
+        .map(|method| method.0 as *const _)
+}
+
+fn ec_key_method() -> Result<*const ffi::EC_KEY_METHOD, ErrorStack> {
+    static METHOD: OnceCell<EcKeyMethod> = OnceCell::new();
+
+    METHOD
+        .get_or_try_init(|| unsafe {
+            let method = cvt_p(ffi::EC_KEY_METHOD_new(ffi::EC_KEY_OpenSSL()))?;
+            ffi::EC_KEY_METHOD_set_sign(method, Some(ec_sign), None, Some(ec_sign_sig));
+            Ok(EcKeyMethod(method))
+        })
+        .map(|method| method.0 as *const _)
+}
+
+fn rsa_index() -> Result<c_int, ErrorStack> {
+    static INDEX: OnceCell<c_int> = OnceCell::new();
+
+    let idx = INDEX.get_or_try_init(|| unsafe {
+        cvt_n(ffi::RSA_get_ex_new_index(
+            0,
+            ptr::null_mut(),
+            None,
+            None,
+            Some(free_remote_key),
+        ))
+    })?;
+    Ok(*idx)
+}
+
+fn ec_key_index() -> Result<c_int, ErrorStack> {
+    static INDEX: OnceCell<c_int> = OnceCell::new();
+
+    let idx = INDEX.get_or_try_init(|| unsafe {
+        cvt_n(ffi::EC_KEY_get_ex_new_index(
+            0,
+            ptr::null_mut(),
+            None,
+            None,
+            Some(free_remote_key),
+        ))
+    })?;
+    Ok(*idx)
+}
+
+unsafe extern "C" fn free_remote_key(
+    _parent: *mut c_void,
+    ptr: *mut c_void,
+    _ad: *mut ffi::CRYPTO_EX_DATA,
+    _idx: c_int,
+    _argl: c_long,
+    _argp: *mut c_void,
+) {
+    if !ptr.is_null() {
+        let key = Box::<Box<dyn RemoteKey>>::from_raw(ptr as *mut Box<dyn RemoteKey>);
+        crate::panic::catch_detached(|| drop(key));
+    }
+}
+
+unsafe fn rsa_remote_key<'a>(rsa: *const ffi::RSA) -> &'a dyn RemoteKey {
+    let idx = rsa_index().expect("BUG: remote key index missing");
+    let key = ffi::RSA_get_ex_data(rsa, idx) as *const Box<dyn RemoteKey>;
+    &**key.as_ref().expect("BUG: remote key missing")
+}
+
+unsafe fn ec_remote_key<'a>(ec_key: *const ffi::EC_KEY) -> &'a dyn RemoteKey {
+    let idx = ec_key_index().expect("BUG: remote key index missing");
+    let key = ffi::EC_KEY_get_ex_data(ec_key, idx) as *const Box<dyn RemoteKey>;
+    &**key.as_ref().expect("BUG: remote key missing")
+}
+
+/// Copies the output of a remote key operation into a buffer of `max` bytes.
+unsafe fn write_output(
+    r: Result<Vec<u8>, ErrorStack>,
+    out: *mut c_uchar,
+    max: usize,
+) -> Option<usize> {
+    match r {
+        Ok(buf) if buf.len() <= max => {
+            ptr::copy_nonoverlapping(buf.as_ptr(), out, buf.len());
+            Some(buf.len())
+        }
+        Ok(_) => None,
+        Err(e) => {
+            e.put();
+            None
+        }
+    }
+}
+
+unsafe extern "C" fn rsa_sign(
+    type_: c_int,
+    m: *const c_uchar,
+    m_length: c_uint,
+    sigret: *mut c_uchar,
+    siglen: *mut c_uint,
+    rsa: *const ffi::RSA,
+) -> c_int {
+    crate::panic::catch_detached(|| {
+        let key = rsa_remote_key(rsa);
+        let digest = slice::from_raw_parts(m, m_length as usize);
+        let max = ffi::RSA_size(rsa) as usize;
+
+        match write_output(key.sign(Nid::from_raw(type_), digest), sigret, max) {
+            Some(len) => {
+                *siglen = len as c_uint;
+                1
+            }
+            None => 0,
+        }
+    })
+    .unwrap_or(0)
+}
+
+unsafe extern "C" fn rsa_priv_enc(
+    flen: c_int,
+    from: *const c_uchar,
+    to: *mut c_uchar,
+    rsa: *mut ffi::RSA,
+    padding: c_int,
+) -> c_int {
+    // only the raw operation is the same as decryption
+    if padding != ffi::RSA_NO_PADDING {
+        return -1;
+    }
+    rsa_priv_dec(flen, from, to, rsa, padding)
+}
+
+unsafe extern "C" fn rsa_priv_dec(
+    flen: c_int,
+    from: *const c_uchar,
+    to: *mut c_uchar,
+    rsa: *mut ffi::RSA,
+    padding: c_int,
+) -> c_int {
+    crate::panic::catch_detached(|| {
+        let key = rsa_remote_key(rsa);
+        let input = slice::from_raw_parts(from, flen as usize);
+        let max = ffi::RSA_size(rsa) as usize;
+
+        match write_output(key.decrypt(input, Padding::from_raw(padding)), to, max) {
+            Some(len) => len as c_int,
+            None => -1,
+        }
+    })
+    .unwrap_or(-1)
+}
+
+unsafe extern "C" fn ec_sign(
+    type_: c_int,
+    dgst: *const c_uchar,
+    dlen: c_int,
+    sig: *mut c_uchar,
+    siglen: *mut c_uint,
+    _kinv: *const ffi::BIGNUM,
+    _r: *const ffi::BIGNUM,
+    eckey: *mut ffi::EC_KEY,
+) -> c_int {
+    crate::panic::catch_detached(|| {
+        let key = ec_remote_key(eckey);
+        let digest = slice::from_raw_parts(dgst, dlen as usize);
+        let max = ffi::ECDSA_size(eckey) as usize;
+
+        match write_output(key.sign(Nid::from_raw(type_), digest), sig, max) {
+            Some(len) => {
+                *siglen = len as c_uint;
+                1
+            }
+            None => 0,
+        }
+    })
+    .unwrap_or(0)
+}
+
+unsafe extern "C" fn ec_sign_sig(
+    dgst: *const c_uchar,
+    dgst_len: c_int,
+    _in_kinv: *const ffi::BIGNUM,
+    _in_r: *const ffi::BIGNUM,
+    eckey: *mut ffi::EC_KEY,
+) -> *mut ffi::ECDSA_SIG {
+    crate::panic::catch_detached(|| {
+        let key = ec_remote_key(eckey);
+        let digest = slice::from_raw_parts(dgst, dgst_len as usize);
+
+        match key
+            .sign(Nid::UNDEF, digest)
+            .and_then(|der| EcdsaSig::from_der(&der))
+        {
+            Ok(sig) => {
+                let ptr = sig.as_ptr();
+                mem::forget(sig);
+                ptr
+            }
+            Err(e) => {
+                e.put();
+                ptr::null_mut()
+            }
+        }
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ec::{EcGroup, EcKey};
+    use crate::hash::MessageDigest;
+    use crate::md::Md;
+    use crate::pkey::{PKey, Public};
+    use crate::pkey_ctx::PkeyCtx;
+    use crate::sign::{RsaPssSaltlen, Signer, Verifier};
+
+    struct LocalKey(PKey<Private>);
+
+    impl RemoteKey for LocalKey {
+        fn sign(&self, digest_type: Nid, digest: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+            let mut ctx = PkeyCtx::new(&self.0)?;
+            ctx.sign_init()?;
+            if self.0.id() == Id::RSA {
+                ctx.set_rsa_padding(Padding::PKCS1)?;
+            }
+            if let Some(md) = Md::from_nid(digest_type) {
+                ctx.set_signature_md(md)?;
+            }
+            let mut signature = vec![];
+            ctx.sign_to_vec(digest, &mut signature)?;
+            Ok(signature)
+        }
+
+        fn decrypt(&self, input: &[u8], padding: Padding) -> Result<Vec<u8>, ErrorStack> {
+            let rsa = self.0.rsa()?;
+            let mut out = vec![0; rsa.size() as usize];
+            let len = rsa.private_decrypt(input, &mut out, padding)?;
+            out.truncate(len);
+            Ok(out)
+        }
+    }
+
+    fn public(key: &PKey<Private>) -> PKey<Public> {
+        PKey::public_key_from_der(&key.public_key_to_der().unwrap()).unwrap()
+    }
+
+    fn sign_verify(
+        key: &PKeyRef<Private>,
+        public_key: &PKeyRef<Public>,
+        padding: Option<Padding>,
+    ) -> bool {
+        let mut signer = Signer::new(MessageDigest::sha256(), key).unwrap();
+        if let Some(padding) = padding {
+            signer.set_rsa_padding(padding).unwrap();
+            signer
+                .set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)
+                .unwrap();
+        }
+        signer.update(b"hello world").unwrap();
+        let signature = signer.sign_to_vec().unwrap();
+
+        let mut verifier = Verifier::new(MessageDigest::sha256(), public_key).unwrap();
+        if let Some(padding) = padding {
+            verifier.set_rsa_padding(padding).unwrap();
+        }
+        verifier.update(b"hello world").unwrap();
+        verifier.verify(&signature).unwrap()
+    }
+
+    #[test]
+    fn rsa() {
+        let key = PKey::private_key_from_pem(include_bytes!("../test/key.pem")).unwrap();
+        let public_key = public(&key);
+        let remote = PKey::from_remote_key(&public_key, LocalKey(key)).unwrap();
+        assert!(remote.public_eq(&public_key));
+
+        assert!(sign_verify(&remote, &public_key, None));
+        assert!(sign_verify(&remote, &public_key, Some(Padding::PKCS1_PSS)));
+
+        for padding in [Padding::PKCS1, Padding::PKCS1_OAEP] {
+            let mut ctx = PkeyCtx::new(&public_key).unwrap();
+            ctx.encrypt_init().unwrap();
+            ctx.set_rsa_padding(padding).unwrap();
+            let mut ciphertext = vec![];
+            ctx.encrypt_to_vec(b"hello world", &mut ciphertext).unwrap();
+
+            let mut ctx = PkeyCtx::new(&remote).unwrap();
+            ctx.decrypt_init().unwrap();
+            ctx.set_rsa_padding(padding).unwrap();
+            let mut plaintext = vec![];
+            ctx.decrypt_to_vec(&ciphertext, &mut plaintext).unwrap();
+            assert_eq!(plaintext, b"hello world");
+        }
+    }
+
+    #[test]
+    fn ec() {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let public_key = public(&key);
+        let remote = PKey::from_remote_key(&public_key, LocalKey(key)).unwrap();
+
+        assert!(sign_verify(&remote, &public_key, None));
+    }
+
+    #[test]
+    fn remote_error() {
+        struct Failing;
+
+        impl RemoteKey for Failing {
+            fn sign(&self, _: Nid, _: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+                Err(unsupported_key_type())
+            }
+        }
+
+        let key = PKey::private_key_from_pem(include_bytes!("../test/key.pem")).unwrap();
+        let remote = PKey::from_remote_key(&public(&key), Failing).unwrap();
+        let mut signer = Signer::new(MessageDigest::sha256(), &remote).unwrap();
+        signer.update(b"hello world").unwrap();
+        signer.sign_to_vec().unwrap_err();
+
+        let mut ctx = PkeyCtx::new(&remote).unwrap();
+        ctx.decrypt_init().unwrap();
+        let e = ctx.decrypt_to_vec(&[0; 256], &mut vec![]).unwrap_err();
+        assert!(e
+            .errors()
+            .iter()
+            .any(|e| e.library_code() == ffi::ERR_LIB_EVP
+                && e.reason_code() == ffi::EVP_R_OPERATION_NOT_SUPPORTED_FOR_THIS_KEYTYPE));
+    }
+
+    #[test]
+    #[cfg(ossl111)]
+    fn unsupported() {
+        let key = PKey::generate_ed25519().unwrap();
+        PKey::from_remote_key(&public(&key), LocalKey(key)).unwrap_err();
+    }
+}
@@ -23,6 +23,7 @@
 //! let mut buf = vec![0; rsa.size() as usize];
 //! let encrypted_len = rsa.public_encrypt(data, &mut buf, Padding::PKCS1).unwrap();
 //! ```
+use bitflags::bitflags;
 use cfg_if::cfg_if;
 use foreign_types::{ForeignType, ForeignTypeRef};
 use libc::c_int;
@@ -30,7 +31,7 @@ use std::fmt;
 use std::mem;
 use std::ptr;
 
-use crate::bn::{BigNum, BigNumRef};
+use crate::bn::{BigNum, BigNumContext, BigNumRef};
 use crate::error::ErrorStack;
 use crate::pkey::{HasPrivate, HasPublic, Private, Public};
 use crate::util::ForeignTypeRefExt;
@@ -91,6 +92,34 @@ impl<T> ToOwned for RsaRef<T> {
     }
 }
 
+bitflags! {
+    /// Flags controlling the behavior of an RSA key.
+    pub struct RsaFlags: c_int {
+        /// Caches Montgomery parameters for the public modulus.
+        const CACHE_PUBLIC = ffi::RSA_FLAG_CACHE_PUBLIC;
+        /// Caches Montgomery parameters for the private primes.
+        const CACHE_PRIVATE = ffi::RSA_FLAG_CACHE_PRIVATE;
+        /// Legacy flag which has no effect on blinding; see [`RsaFlags::NO_BLINDING`].
+        const BLINDING = ffi::RSA_FLAG_BLINDING;
+        /// Legacy flag which has no effect on the built-in implementation.
+        const THREAD_SAFE = ffi::RSA_FLAG_THREAD_SAFE;
+        /// Indicates that private key operations are handled by the key's `RSA_METHOD` and do
+        /// not require the private components to be present, as with keys stored in hardware.
+        const EXT_PKEY = ffi::RSA_FLAG_EXT_PKEY;
+        /// Disables blinding of private key operations.
+        ///
+        /// Blinding protects against timing attacks, and should only be disabled when private key
+        /// operations are performed by an implementation which does not need it.
+        const NO_BLINDING = ffi::RSA_FLAG_NO_BLINDING;
+        /// Disables the use of constant-time modular exponentiation.
+        ///
+        /// OpenSSL 1.1.0 and newer always use constant-time operations, so this flag is only
+        /// available on older versions.
+        #[cfg(not(ossl110))]
+        const NO_CONSTTIME = ffi::RSA_FLAG_NO_CONSTTIME;
+    }
+}
+
 impl<T> RsaRef<T>
 where
     T: HasPrivate,
@@ -232,6 +261,37 @@ where
         }
     }
 
+    /// Enables blinding of private key operations, computing a new blinding factor.
+    ///
+    /// Blinding is enabled by default, so this is only needed after calling
+    /// [`RsaRef::blinding_off`] or setting [`RsaFlags::NO_BLINDING`].
+    ///
+    /// # Safety
+    ///
+    /// The key must not be in use on another thread. Clones of an `Rsa`, and keys returned by
+    /// [`PKeyRef::rsa`], share the underlying `RSA` object, and OpenSSL does not synchronize
+    /// changes to its blinding state with operations on other threads.
+    ///
+    /// [`PKeyRef::rsa`]: crate::pkey::PKeyRef::rsa
+    #[corresponds(RSA_blinding_on)]
+    pub unsafe fn blinding_on(&mut self) -> Result<(), ErrorStack> {
+        cvt(ffi::RSA_blinding_on(self.as_ptr(), ptr::null_mut())).map(|_| ())
+    }
+
+    /// Disables blinding of private key operations.
+    ///
+    /// Blinding protects against timing attacks, and should only be disabled when private key
+    /// operations are performed by an implementation which does not need it.
+    ///
+    /// # Safety
+    ///
+    /// The key must not be in use on another thread, including through clones of an `Rsa` or
+    /// `PKey` sharing the same `RSA` object. See [`RsaRef::blinding_on`].
+    #[corresponds(RSA_blinding_off)]
+    pub unsafe fn blinding_off(&mut self) {
+        ffi::RSA_blinding_off(self.as_ptr())
+    }
+
     /// Validates RSA parameters for correctness
     #[corresponds(RSA_check_key)]
     #[allow(clippy::unnecessary_cast)]
@@ -239,12 +299,71 @@ where
         unsafe {
             let result = ffi::RSA_check_key(self.as_ptr()) as i32;
             if result == -1 {
-                Err(ErrorStack::get())
+                Err(ErrorStack::report())
             } else {
+                if result != 1 {
+                    ErrorStack::get();
+                }
                 Ok(result == 1)
             }
         }
     }
+
+    /// Validates RSA parameters for correctness, returning the reasons for any failures.
+    ///
+    /// Unlike [`RsaRef::check_key`], an invalid key results in an error, which contains an entry
+    /// for each check that failed. These can be matched against the RSA [`Reason`]s, such as
+    /// [`Reason::RSA_P_NOT_PRIME`].
+    ///
+    /// [`Reason`]: crate::error::Reason
+    /// [`Reason::RSA_P_NOT_PRIME`]: crate::error::Reason::RSA_P_NOT_PRIME
+    #[corresponds(RSA_check_key)]
+    pub fn validate(&self) -> Result<(), ErrorStack> {
+        unsafe {
+            if ffi::RSA_check_key(self.as_ptr()) == 1 {
+                Ok(())
+            } else {
+                Err(ErrorStack::report())
+            }
+        }
+    }
+}
+
+#[cfg(any(ossl110, libressl273))]
+impl<T> RsaRef<T> {
+    /// Returns the flags set on the key.
+    ///
+    /// Requires OpenSSL 1.1.0 or LibreSSL 2.7.3 or newer.
+    #[corresponds(RSA_test_flags)]
+    pub fn flags(&self) -> RsaFlags {
+        unsafe { RsaFlags::from_bits_truncate(ffi::RSA_test_flags(self.as_ptr(), !0)) }
+    }
+
+    /// Sets flags on the key, leaving other flags unchanged.
+    ///
+    /// Requires OpenSSL 1.1.0 or LibreSSL 2.7.3 or newer.
+    ///
+    /// # Safety
+    ///
+    /// The flags are read without synchronization by operations using the key, so the key must
+    /// not be in use on another thread, including through clones of an `Rsa` or `PKey` sharing
+    /// the same `RSA` object.
+    #[corresponds(RSA_set_flags)]
+    pub unsafe fn set_flags(&mut self, flags: RsaFlags) {
+        ffi::RSA_set_flags(self.as_ptr(), flags.bits())
+    }
+
+    /// Clears flags on the key, leaving other flags unchanged.
+    ///
+    /// Requires OpenSSL 1.1.0 or LibreSSL 2.7.3 or newer.
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as for [`RsaRef::set_flags`] apply.
+    #[corresponds(RSA_clear_flags)]
+    pub unsafe fn clear_flags(&mut self, flags: RsaFlags) {
+        ffi::RSA_clear_flags(self.as_ptr(), flags.bits())
+    }
 }
 
 impl<T> RsaRef<T>
@@ -482,6 +601,50 @@ impl RsaPrivateKeyBuilder {
         Ok(self)
     }
 
+    /// Computes the Chinese Remainder Theorem params of the Rsa key from its private exponent and
+    /// factors.
+    ///
+    /// Keys imported from other ecosystems, such as JSON Web Keys, sometimes omit these params.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the factors have not been set.
+    pub fn compute_crt_params(self) -> Result<RsaPrivateKeyBuilder, ErrorStack> {
+        let (dmp1, dmq1, iqmp) = {
+            let p = self.rsa.p().expect("factors have not been set");
+            let q = self.rsa.q().expect("factors have not been set");
+
+            #[allow(unused_mut)]
+            let mut d = self.rsa.d().to_owned()?;
+            #[allow(unused_mut)]
+            let mut p = p.to_owned()?;
+            #[allow(unused_mut)]
+            let mut q = q.to_owned()?;
+            #[cfg(ossl110)]
+            {
+                d.set_const_time();
+                p.set_const_time();
+                q.set_const_time();
+            }
+
+            let mut ctx = BigNumContext::new()?;
+            let one = BigNum::from_u32(1)?;
+            let mut p1 = BigNum::new()?;
+            p1.checked_sub(&p, &one)?;
+            let mut q1 = BigNum::new()?;
+            q1.checked_sub(&q, &one)?;
+
+            let mut dmp1 = BigNum::new()?;
+            dmp1.checked_rem(&d, &p1, &mut ctx)?;
+            let mut dmq1 = BigNum::new()?;
+            dmq1.checked_rem(&d, &q1, &mut ctx)?;
+            let mut iqmp = BigNum::new()?;
+            iqmp.mod_inverse(&q, &p, &mut ctx)?;
+            (dmp1, dmq1, iqmp)
+        };
+        self.set_crt_params(dmp1, dmq1, iqmp)
+    }
+
     /// Returns the Rsa key.
     pub fn build(self) -> Rsa<Private> {
         self.rsa
@@ -678,6 +841,8 @@ cfg_if! {
 
 #[cfg(test)]
 mod test {
+    #[cfg(not(boringssl))]
+    use crate::error::Reason;
     use crate::symm::Cipher;
 
     use super::*;
@@ -844,6 +1009,97 @@ mod test {
         drop(key.clone());
     }
 
+    #[test]
+    fn blinding() {
+        let mut key = Rsa::generate(2048).unwrap();
+        let msg = b"foo";
+        let mut encrypted = vec![0; key.size() as usize];
+        key.public_encrypt(msg, &mut encrypted, Padding::PKCS1)
+            .unwrap();
+
+        unsafe { key.blinding_off() };
+        let mut decrypted = vec![0; key.size() as usize];
+        let len = key
+            .private_decrypt(&encrypted, &mut decrypted, Padding::PKCS1)
+            .unwrap();
+        assert_eq!(&decrypted[..len], msg);
+
+        unsafe { key.blinding_on().unwrap() };
+        let len = key
+            .private_decrypt(&encrypted, &mut decrypted, Padding::PKCS1)
+            .unwrap();
+        assert_eq!(&decrypted[..len], msg);
+    }
+
+    #[test]
+    #[cfg(any(ossl110, libressl273))]
+    fn flags() {
+        let mut key = Rsa::generate(2048).unwrap();
+        assert!(!key.flags().contains(RsaFlags::NO_BLINDING));
+
+        unsafe { key.set_flags(RsaFlags::NO_BLINDING | RsaFlags::EXT_PKEY) };
+        assert!(key
+            .flags()
+            .contains(RsaFlags::NO_BLINDING | RsaFlags::EXT_PKEY));
+
+        unsafe { key.clear_flags(RsaFlags::EXT_PKEY) };
+        assert!(key.flags().contains(RsaFlags::NO_BLINDING));
+        assert!(!key.flags().contains(RsaFlags::EXT_PKEY));
+    }
+
+    #[test]
+    fn validate() {
+        let key = Rsa::generate(2048).unwrap();
+        key.validate().unwrap();
+
+        let mut q = key.q().unwrap().to_owned().unwrap();
+        q.add_word(2).unwrap();
+        let invalid = Rsa::from_private_components(
+            key.n().to_owned().unwrap(),
+            key.e().to_owned().unwrap(),
+            key.d().to_owned().unwrap(),
+            key.p().unwrap().to_owned().unwrap(),
+            q,
+            key.dmp1().unwrap().to_owned().unwrap(),
+            key.dmq1().unwrap().to_owned().unwrap(),
+            key.iqmp().unwrap().to_owned().unwrap(),
+        )
+        .unwrap();
+        assert!(!invalid.check_key().unwrap());
+        let errors = invalid.validate().unwrap_err();
+        #[cfg(not(boringssl))]
+        assert!(errors
+            .errors()
+            .iter()
+            .any(|e| e.reason_id() == Reason::RSA_N_DOES_NOT_EQUAL_P_Q));
+        #[cfg(boringssl)]
+        assert!(!errors.errors().is_empty());
+    }
+
+    #[test]
+    fn compute_crt_params() {
+        let key = Rsa::generate(2048).unwrap();
+        let rebuilt = RsaPrivateKeyBuilder::new(
+            key.n().to_owned().unwrap(),
+            key.e().to_owned().unwrap(),
+            key.d().to_owned().unwrap(),
+        )
+        .unwrap()
+        .set_factors(
+            key.p().unwrap().to_owned().unwrap(),
+            key.q().unwrap().to_owned().unwrap(),
+        )
+        .unwrap()
+        .compute_crt_params()
+        .unwrap()
+        .build();
+
+        assert_eq!(rebuilt.dmp1().unwrap(), key.dmp1().unwrap());
+        assert_eq!(rebuilt.dmq1().unwrap(), key.dmq1().unwrap());
+        assert_eq!(rebuilt.iqmp().unwrap(), key.iqmp().unwrap());
+        rebuilt.validate().unwrap();
+    }
+
     #[test]
     fn generate_with_e() {
         let e = BigNum::from_u32(0x10001).unwrap();
@@ -0,0 +1,488 @@
+//! Serde support for certificates, keys, times, and object identifiers.
+//!
+//! This module is only available when the `serde` Cargo feature is enabled.
+//!
+//! [`X509`] and [`PKey<Public>`] are serialized as PEM strings by human-readable formats like JSON, and as DER
+//! byte strings by binary formats. The [`pem`] and [`der`] modules can be used with serde's `with` attribute to
+//! pick an encoding explicitly.
+//!
+//! [`Asn1Time`] is serialized as its ASN.1 time string (e.g. `20230101000000Z`), and [`Nid`] as its short name
+//! in human-readable formats and as its raw integer value otherwise.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::pkey::{PKey, Public};
+//! use openssl::x509::X509;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Identity {
+//!     #[serde(with = "openssl::serde::der")]
+//!     certificate: X509,
+//!     key: PKey<Public>,
+//! }
+//! ```
+//!
+//! [`PKey<Public>`]: crate::pkey::PKey
+use foreign_types::ForeignTypeRef;
+use libc::c_int;
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, Serializer};
+use std::cmp;
+use std::convert::TryFrom;
+use std::ffi::CString;
+use std::fmt;
+use std::marker::PhantomData;
+use std::slice;
+use std::str;
+
+use crate::asn1::{Asn1Time, Asn1TimeRef};
+use crate::error::ErrorStack;
+use crate::nid::Nid;
+use crate::pkey::{PKey, Public};
+use crate::x509::{X509Ref, X509};
+
+mod private {
+    use crate::error::ErrorStack;
+
+    pub trait Sealed: Sized {
+        fn encode_der(&self) -> Result<Vec<u8>, ErrorStack>;
+
+        fn encode_pem(&self) -> Result<Vec<u8>, ErrorStack>;
+
+        fn decode_der(der: &[u8]) -> Result<Self, ErrorStack>;
+
+        fn decode_pem(pem: &[u8]) -> Result<Self, ErrorStack>;
+    }
+}
+
+/// A type which can be serialized as either PEM or DER.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait Encodable: private::Sealed {}
+
+impl private::Sealed for X509 {
+    fn encode_der(&self) -> Result<Vec<u8>, ErrorStack> {
+        self.to_der()
+    }
+
+    fn encode_pem(&self) -> Result<Vec<u8>, ErrorStack> {
+        self.to_pem()
+    }
+
+    fn decode_der(der: &[u8]) -> Result<Self, ErrorStack> {
+        X509::from_der(der)
+    }
+
+    fn decode_pem(pem: &[u8]) -> Result<Self, ErrorStack> {
+        X509::from_pem(pem)
+    }
+}
+
+impl Encodable for X509 {}
+
+impl private::Sealed for PKey<Public> {
+    fn encode_der(&self) -> Result<Vec<u8>, ErrorStack> {
+        self.public_key_to_der()
+    }
+
+    fn encode_pem(&self) -> Result<Vec<u8>, ErrorStack> {
+        self.public_key_to_pem()
+    }
+
+    fn decode_der(der: &[u8]) -> Result<Self, ErrorStack> {
+        PKey::public_key_from_der(der)
+    }
+
+    fn decode_pem(pem: &[u8]) -> Result<Self, ErrorStack> {
+        PKey::public_key_from_pem(pem)
+    }
+}
+
+impl Encodable for PKey<Public> {}
+
+/// Serializes and deserializes values as DER byte strings.
+///
+/// Use with `#[serde(with = "openssl::serde::der")]`.
+pub mod der {
+    use super::*;
+
+    /// Serializes `value` as a DER byte string.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Encodable,
+        S: Serializer,
+    {
+        let der = value.encode_der().map_err(ser::Error::custom)?;
+        serializer.serialize_bytes(&der)
+    }
+
+    /// Deserializes a value from a DER byte string.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Encodable,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_byte_buf(DerVisitor(PhantomData))
+    }
+}
+
+/// Serializes and deserializes values as PEM strings.
+///
+/// Use with `#[serde(with = "openssl::serde::pem")]`.
+pub mod pem {
+    use super::*;
+
+    /// Serializes `value` as a PEM string.
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Encodable,
+        S: Serializer,
+    {
+        let pem = value.encode_pem().map_err(ser::Error::custom)?;
+        let pem = str::from_utf8(&pem).map_err(ser::Error::custom)?;
+        serializer.serialize_str(pem)
+    }
+
+    /// Deserializes a value from a PEM string.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Encodable,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(PemVisitor(PhantomData))
+    }
+}
+
+struct DerVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for DerVisitor<T>
+where
+    T: Encodable,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a DER byte string")
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        T::decode_der(v).map_err(E::custom)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<T, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        // the size hint comes from the input, so don't let it force a large allocation
+        let mut der = Vec::with_capacity(cmp::min(seq.size_hint().unwrap_or(0), 4096));
+        while let Some(byte) = seq.next_element()? {
+            der.push(byte);
+        }
+        self.visit_bytes(&der)
+    }
+}
+
+struct PemVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for PemVisitor<T>
+where
+    T: Encodable,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a PEM string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        T::decode_pem(v.as_bytes()).map_err(E::custom)
+    }
+}
+
+fn serialize_default<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Encodable,
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        pem::serialize(value, serializer)
+    } else {
+        der::serialize(value, serializer)
+    }
+}
+
+fn deserialize_default<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Encodable,
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        pem::deserialize(deserializer)
+    } else {
+        der::deserialize(deserializer)
+    }
+}
+
+impl Serialize for X509Ref {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            let pem = self.to_pem().map_err(ser::Error::custom)?;
+            let pem = str::from_utf8(&pem).map_err(ser::Error::custom)?;
+            serializer.serialize_str(pem)
+        } else {
+            let der = self.to_der().map_err(ser::Error::custom)?;
+            serializer.serialize_bytes(&der)
+        }
+    }
+}
+
+impl Serialize for X509 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        X509Ref::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for X509 {
+    fn deserialize<D>(deserializer: D) -> Result<X509, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_default(deserializer)
+    }
+}
+
+impl Serialize for PKey<Public> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_default(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PKey<Public> {
+    fn deserialize<D>(deserializer: D) -> Result<PKey<Public>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_default(deserializer)
+    }
+}
+
+impl Serialize for Asn1TimeRef {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let s = unsafe {
+            let s = self.as_ptr() as *mut ffi::ASN1_STRING;
+            let len = ffi::ASN1_STRING_length(s) as usize;
+            slice::from_raw_parts(crate::asn1::ASN1_STRING_get0_data(s), len)
+        };
+        let s = str::from_utf8(s).map_err(ser::Error::custom)?;
+        serializer.serialize_str(s)
+    }
+}
+
+impl Serialize for Asn1Time {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Asn1TimeRef::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Asn1Time {
+    fn deserialize<D>(deserializer: D) -> Result<Asn1Time, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TimeVisitor;
+
+        impl<'de> Visitor<'de> for TimeVisitor {
+            type Value = Asn1Time;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an ASN.1 time string")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Asn1Time, E>
+            where
+                E: de::Error,
+            {
+                if v.contains('\0') {
+                    return Err(E::invalid_value(de::Unexpected::Str(v), &self));
+                }
+                Asn1Time::from_str(v).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(TimeVisitor)
+    }
+}
+
+impl Serialize for Nid {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            if let Ok(name) = self.short_name() {
+                return serializer.serialize_str(name);
+            }
+        }
+        serializer.serialize_i32(self.as_raw())
+    }
+}
+
+impl<'de> Deserialize<'de> for Nid {
+    fn deserialize<D>(deserializer: D) -> Result<Nid, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct NidVisitor;
+
+        impl<'de> Visitor<'de> for NidVisitor {
+            type Value = Nid;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an object name, OID, or NID")
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Nid, E>
+            where
+                E: de::Error,
+            {
+                c_int::try_from(v)
+                    .map(Nid::from_raw)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Nid, E>
+            where
+                E: de::Error,
+            {
+                c_int::try_from(v)
+                    .map(Nid::from_raw)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Nid, E>
+            where
+                E: de::Error,
+            {
+                let raw = CString::new(v)
+                    .map(|s| unsafe {
+                        ffi::init();
+                        ffi::OBJ_txt2nid(s.as_ptr())
+                    })
+                    .unwrap_or(ffi::NID_undef);
+                if raw == ffi::NID_undef {
+                    // OBJ_txt2nid leaves an error on the stack for unknown names
+                    let _ = ErrorStack::get();
+                    return Err(E::invalid_value(de::Unexpected::Str(v), &self));
+                }
+                Ok(Nid::from_raw(raw))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(NidVisitor)
+        } else {
+            deserializer.deserialize_i32(NidVisitor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::{Deserialize, Serialize};
+
+    use crate::asn1::Asn1Time;
+    use crate::nid::Nid;
+    use crate::pkey::{PKey, Public};
+    use crate::x509::X509;
+
+    #[derive(Serialize, Deserialize)]
+    struct Explicit {
+        #[serde(with = "crate::serde::der")]
+        der: X509,
+        #[serde(with = "crate::serde::pem")]
+        pem: PKey<Public>,
+    }
+
+    #[test]
+    fn x509_round_trip() {
+        let cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+
+        let json = serde_json::to_string(&cert).unwrap();
+        assert!(json.starts_with("\"-----BEGIN CERTIFICATE-----"));
+        let decoded: X509 = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.to_der().unwrap(), cert.to_der().unwrap());
+    }
+
+    #[test]
+    fn explicit_encodings() {
+        let cert = X509::from_pem(include_bytes!("../test/cert.pem")).unwrap();
+        let key = PKey::public_key_from_pem(include_bytes!("../test/rsa.pem.pub")).unwrap();
+        let value = Explicit {
+            der: cert.clone(),
+            pem: key,
+        };
+
+        let json = serde_json::to_value(&value).unwrap();
+        assert!(json["der"].is_array());
+        assert!(json["pem"]
+            .as_str()
+            .unwrap()
+            .starts_with("-----BEGIN PUBLIC KEY-----"));
+
+        let decoded: Explicit = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.der.to_der().unwrap(), cert.to_der().unwrap());
+        assert_eq!(
+            decoded.pem.public_key_to_der().unwrap(),
+            value.pem.public_key_to_der().unwrap()
+        );
+    }
+
+    #[test]
+    fn asn1_time_round_trip() {
+        let time = Asn1Time::from_str("99991231235959Z").unwrap();
+
+        let json = serde_json::to_string(&time).unwrap();
+        assert_eq!(json, "\"99991231235959Z\"");
+        let decoded: Asn1Time = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.to_string(), time.to_string());
+    }
+
+    #[test]
+    fn nid_round_trip() {
+        let json = serde_json::to_string(&Nid::SHA256).unwrap();
+        assert_eq!(json, "\"SHA256\"");
+        assert_eq!(serde_json::from_str::<Nid>(&json).unwrap(), Nid::SHA256);
+        assert_eq!(
+            serde_json::from_str::<Nid>("\"2.16.840.1.101.3.4.2.1\"").unwrap(),
+            Nid::SHA256
+        );
+        assert_eq!(
+            serde_json::from_str::<Nid>(&Nid::SHA256.as_raw().to_string()).unwrap(),
+            Nid::SHA256
+        );
+        assert!(serde_json::from_str::<Nid>("\"not-an-object\"").is_err());
+    }
+}
@@ -72,12 +72,24 @@ use foreign_types::ForeignTypeRef;
 use libc::c_int;
 use std::io::{self, Write};
 use std::marker::PhantomData;
+#[cfg(ossl110)]
+use std::ops::{Deref, DerefMut};
 use std::ptr;
+#[cfg(ossl110)]
+use std::sync::Mutex;
 
 use crate::error::ErrorStack;
 use crate::hash::MessageDigest;
-use crate::pkey::{HasPrivate, HasPublic, PKeyRef};
+use crate::md::MdRef;
+#[cfg(not(boringssl))]
+use crate::pkey::PKey;
+use crate::pkey::{HasPrivate, HasPublic, Id, PKeyRef, Private, Public};
+use crate::pkey_ctx::{PkeyCtx, PkeyCtxRef};
+#[cfg(ossl300)]
+use crate::policy::{self, Operation};
 use crate::rsa::Padding;
+#[cfg(not(boringssl))]
+use crate::thread_cache;
 use crate::{cvt, cvt_p};
 
 cfg_if! {
@@ -114,6 +126,7 @@ impl RsaPssSaltlen {
 pub struct Signer<'a> {
     md_ctx: *mut ffi::EVP_MD_CTX,
     pctx: *mut ffi::EVP_PKEY_CTX,
+    pss: PssParams,
     _p: PhantomData<&'a ()>,
 }
 
@@ -168,6 +181,11 @@ impl Signer<'_> {
     where
         T: HasPrivate,
     {
+        #[cfg(ossl300)]
+        if let Some(type_) = type_ {
+            policy::check(ptr::null_mut(), type_.type_(), Operation::Sign)?;
+        }
+
         unsafe {
             ffi::init();
 
@@ -182,7 +200,7 @@ impl Signer<'_> {
             );
             if r != 1 {
                 EVP_MD_CTX_free(ctx);
-                return Err(ErrorStack::get());
+                return Err(ErrorStack::report());
             }
 
             assert!(!pctx.is_null());
@@ -190,6 +208,7 @@ impl Signer<'_> {
             Ok(Signer {
                 md_ctx: ctx,
                 pctx,
+                pss: PssParams::default(),
                 _p: PhantomData,
             })
         }
@@ -237,9 +256,10 @@ impl Signer<'_> {
             cvt(ffi::EVP_PKEY_CTX_set_rsa_pss_saltlen(
                 self.pctx,
                 len.as_raw(),
-            ))
-            .map(|_| ())
+            ))?;
         }
+        self.pss.saltlen = Some(len.as_raw());
+        Ok(())
     }
 
     /// Sets the RSA MGF1 algorithm.
@@ -254,9 +274,10 @@ impl Signer<'_> {
             cvt(ffi::EVP_PKEY_CTX_set_rsa_mgf1_md(
                 self.pctx,
                 md.as_ptr() as *mut _,
-            ))
-            .map(|_| ())
+            ))?;
         }
+        self.pss.mgf1_md = Some(md);
+        Ok(())
     }
 
     /// Feeds more data into the `Signer`.
@@ -390,6 +411,59 @@ impl Signer<'_> {
         sig_buf.truncate(len);
         Ok(sig_buf)
     }
+
+    /// Signs a digest which has already been computed, rather than hashing data with `update`.
+    ///
+    /// The digest must have been computed with the `MessageDigest` this `Signer` was created with. RSA padding
+    /// configured on the `Signer` is used as well, along with any PSS salt length and MGF1 digest set on it. Any
+    /// data passed to `update` is ignored.
+    ///
+    /// This fails for signers created with `new_without_digest`, as PureEdDSA (Ed25519 and Ed448 keys) signs the
+    /// message itself.
+    ///
+    /// OpenSSL documentation at [`EVP_PKEY_sign`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `digest` does not match the output size of the `Signer`'s digest.
+    ///
+    /// [`EVP_PKEY_sign`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_sign.html
+    pub fn sign_prehashed(&self, digest: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let mut ctx =
+                prehashed_ctx::<Private, _>(self.md_ctx, self.pctx, self.pss, digest, |c| {
+                    c.sign_init()
+                })?;
+            let mut signature = vec![];
+            ctx.sign_to_vec(digest, &mut signature)?;
+            Ok(signature)
+        }
+    }
+}
+
+#[cfg(ossl110)]
+impl<'a> Signer<'a> {
+    fn try_clone(&self) -> Result<Signer<'a>, ErrorStack> {
+        unsafe {
+            let mut signer = Signer {
+                md_ctx: cvt_p(EVP_MD_CTX_new())?,
+                pctx: ptr::null_mut(),
+                pss: self.pss,
+                _p: PhantomData,
+            };
+            signer.copy_from(self)?;
+            Ok(signer)
+        }
+    }
+
+    fn copy_from(&mut self, other: &Signer<'a>) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::EVP_MD_CTX_copy_ex(self.md_ctx, other.md_ctx))?;
+            self.pctx = ffi::EVP_MD_CTX_pkey_ctx(self.md_ctx);
+        }
+        self.pss = other.pss;
+        Ok(())
+    }
 }
 
 impl<'a> Write for Signer<'a> {
@@ -403,11 +477,125 @@ impl<'a> Write for Signer<'a> {
     }
 }
 
+/// A pool of reusable signing contexts created from a configured [`Signer`].
+///
+/// Creating a `Signer` and configuring it, for example for RSA-PSS, can take longer than
+/// computing the signature itself. A `SignerPool` instead keeps the configured `Signer` as a
+/// template and hands out copies of it, reusing the underlying contexts as they are returned. The
+/// pool can be shared between threads.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+///
+/// # Examples
+///
+/// ```
+/// use openssl::hash::MessageDigest;
+/// use openssl::pkey::PKey;
+/// use openssl::rsa::{Padding, Rsa};
+/// use openssl::sign::{RsaPssSaltlen, Signer, SignerPool};
+///
+/// let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+///
+/// let mut template = Signer::new(MessageDigest::sha256(), &key).unwrap();
+/// template.set_rsa_padding(Padding::PKCS1_PSS).unwrap();
+/// template.set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH).unwrap();
+/// let pool = SignerPool::new(template, 4).unwrap();
+///
+/// let mut signer = pool.get().unwrap();
+/// signer.update(b"hello, world!").unwrap();
+/// let signature = signer.sign_to_vec().unwrap();
+/// ```
+#[cfg(ossl110)]
+pub struct SignerPool<'a> {
+    template: Signer<'a>,
+    idle: Mutex<Vec<Signer<'a>>>,
+}
+
+#[cfg(ossl110)]
+impl<'a> SignerPool<'a> {
+    /// Creates a new `SignerPool` from a configured `Signer`, preallocating `size` contexts.
+    ///
+    /// No data should have been passed to `template`.
+    pub fn new(template: Signer<'a>, size: usize) -> Result<SignerPool<'a>, ErrorStack> {
+        let mut idle = Vec::with_capacity(size);
+        for _ in 0..size {
+            idle.push(template.try_clone()?);
+        }
+
+        Ok(SignerPool {
+            template,
+            idle: Mutex::new(idle),
+        })
+    }
+
+    /// Returns a `Signer` in the same state as the template.
+    ///
+    /// An idle context is reused if one is available, and a new one is created otherwise. The
+    /// context is returned to the pool when the `PooledSigner` is dropped.
+    pub fn get(&self) -> Result<PooledSigner<'_, 'a>, ErrorStack> {
+        let idle = self.idle.lock().unwrap().pop();
+        let signer = match idle {
+            Some(mut signer) => {
+                signer.copy_from(&self.template)?;
+                signer
+            }
+            None => self.template.try_clone()?,
+        };
+
+        Ok(PooledSigner {
+            pool: self,
+            signer: Some(signer),
+        })
+    }
+
+    /// Returns the number of idle contexts in the pool.
+    pub fn idle(&self) -> usize {
+        self.idle.lock().unwrap().len()
+    }
+}
+
+/// A [`Signer`] borrowed from a [`SignerPool`].
+///
+/// The context is returned to the pool when this is dropped.
+#[cfg(ossl110)]
+pub struct PooledSigner<'p, 'a> {
+    pool: &'p SignerPool<'a>,
+    signer: Option<Signer<'a>>,
+}
+
+#[cfg(ossl110)]
+impl<'a> Deref for PooledSigner<'_, 'a> {
+    type Target = Signer<'a>;
+
+    fn deref(&self) -> &Signer<'a> {
+        self.signer.as_ref().unwrap()
+    }
+}
+
+#[cfg(ossl110)]
+impl<'a> DerefMut for PooledSigner<'_, 'a> {
+    fn deref_mut(&mut self) -> &mut Signer<'a> {
+        self.signer.as_mut().unwrap()
+    }
+}
+
+#[cfg(ossl110)]
+impl Drop for PooledSigner<'_, '_> {
+    fn drop(&mut self) {
+        if let Some(signer) = self.signer.take() {
+            if let Ok(mut idle) = self.pool.idle.lock() {
+                idle.push(signer);
+            }
+        }
+    }
+}
+
 /// A type which can be used to verify the integrity and authenticity
 /// of data given the signature.
 pub struct Verifier<'a> {
     md_ctx: *mut ffi::EVP_MD_CTX,
     pctx: *mut ffi::EVP_PKEY_CTX,
+    pss: PssParams,
     pkey_pd: PhantomData<&'a ()>,
 }
 
@@ -461,6 +649,11 @@ impl<'a> Verifier<'a> {
     where
         T: HasPublic,
     {
+        #[cfg(ossl300)]
+        if let Some(type_) = type_ {
+            policy::check(ptr::null_mut(), type_.type_(), Operation::Verify)?;
+        }
+
         unsafe {
             ffi::init();
 
@@ -475,7 +668,7 @@ impl<'a> Verifier<'a> {
             );
             if r != 1 {
                 EVP_MD_CTX_free(ctx);
-                return Err(ErrorStack::get());
+                return Err(ErrorStack::report());
             }
 
             assert!(!pctx.is_null());
@@ -483,6 +676,7 @@ impl<'a> Verifier<'a> {
             Ok(Verifier {
                 md_ctx: ctx,
                 pctx,
+                pss: PssParams::default(),
                 pkey_pd: PhantomData,
             })
         }
@@ -530,9 +724,10 @@ impl<'a> Verifier<'a> {
             cvt(ffi::EVP_PKEY_CTX_set_rsa_pss_saltlen(
                 self.pctx,
                 len.as_raw(),
-            ))
-            .map(|_| ())
+            ))?;
         }
+        self.pss.saltlen = Some(len.as_raw());
+        Ok(())
     }
 
     /// Sets the RSA MGF1 algorithm.
@@ -547,9 +742,10 @@ impl<'a> Verifier<'a> {
             cvt(ffi::EVP_PKEY_CTX_set_rsa_mgf1_md(
                 self.pctx,
                 md.as_ptr() as *mut _,
-            ))
-            .map(|_| ())
+            ))?;
         }
+        self.pss.mgf1_md = Some(md);
+        Ok(())
     }
 
     /// Feeds more data into the `Verifier`.
@@ -586,7 +782,7 @@ impl<'a> Verifier<'a> {
                     ErrorStack::get(); // discard error stack
                     Ok(false)
                 }
-                _ => Err(ErrorStack::get()),
+                _ => Err(ErrorStack::report()),
             }
         }
     }
@@ -612,10 +808,140 @@ impl<'a> Verifier<'a> {
                     ErrorStack::get();
                     Ok(false)
                 }
-                _ => Err(ErrorStack::get()),
+                _ => Err(ErrorStack::report()),
+            }
+        }
+    }
+
+    /// Determines if `signature` is a valid signature of a digest which has already been computed, rather than of
+    /// data passed to `update`.
+    ///
+    /// The digest must have been computed with the `MessageDigest` this `Verifier` was created with. RSA padding
+    /// configured on the `Verifier` is used as well, along with any PSS salt length and MGF1 digest set on it.
+    ///
+    /// This fails for verifiers created with `new_without_digest`, as PureEdDSA (Ed25519 and Ed448 keys) signs the
+    /// message itself.
+    ///
+    /// OpenSSL documentation at [`EVP_PKEY_verify`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `digest` does not match the output size of the `Verifier`'s digest.
+    ///
+    /// [`EVP_PKEY_verify`]: https://www.openssl.org/docs/manmaster/man3/EVP_PKEY_verify.html
+    pub fn verify_prehashed(&self, signature: &[u8], digest: &[u8]) -> Result<bool, ErrorStack> {
+        // when verifying, -2 is RSA_PSS_SALTLEN_AUTO, which accepts any salt length
+        let pss = PssParams {
+            saltlen: Some(
+                self.pss
+                    .saltlen
+                    .unwrap_or(RsaPssSaltlen::MAXIMUM_LENGTH.as_raw()),
+            ),
+            ..self.pss
+        };
+        unsafe {
+            let ctx = prehashed_ctx::<Public, _>(self.md_ctx, self.pctx, pss, digest, |c| {
+                c.verify_init()
+            })?;
+            let r = ffi::EVP_PKEY_verify(
+                ctx.as_ptr(),
+                signature.as_ptr(),
+                signature.len(),
+                digest.as_ptr(),
+                digest.len(),
+            );
+            match r {
+                1 => Ok(true),
+                0 => {
+                    ErrorStack::get();
+                    Ok(false)
+                }
+                _ => Err(ErrorStack::report()),
+            }
+        }
+    }
+}
+
+/// The RSA-PSS parameters set explicitly on a `Signer` or `Verifier`.
+///
+/// They are tracked rather than read back from the context, since the salt length OpenSSL reports
+/// for a context does not always match the one it uses.
+#[derive(Copy, Clone, Default)]
+struct PssParams {
+    saltlen: Option<c_int>,
+    mgf1_md: Option<MessageDigest>,
+}
+
+/// Creates a context which operates on a digest directly, configured like the digest signing or verification
+/// context `pctx`.
+///
+/// PSS parameters which were not set explicitly keep the new context's defaults.
+unsafe fn prehashed_ctx<T, F>(
+    md_ctx: *mut ffi::EVP_MD_CTX,
+    pctx: *mut ffi::EVP_PKEY_CTX,
+    pss: PssParams,
+    digest: &[u8],
+    init: F,
+) -> Result<PkeyCtx<T>, ErrorStack>
+where
+    F: FnOnce(&mut PkeyCtxRef<T>) -> Result<(), ErrorStack>,
+{
+    let md = ffi::EVP_MD_CTX_md(md_ctx);
+    if md.is_null() {
+        return Err(prehash_unsupported());
+    }
+    assert_eq!(
+        digest.len(),
+        ffi::EVP_MD_size(md) as usize,
+        "digest length does not match"
+    );
+
+    let pkey = PKeyRef::<T>::from_ptr(ffi::EVP_PKEY_CTX_get0_pkey(pctx));
+    let mut ctx = PkeyCtx::new(pkey)?;
+    init(&mut ctx)?;
+    ctx.set_signature_md(MdRef::from_ptr(md as *mut _))?;
+
+    if pkey.id() == Id::RSA {
+        let mut pad = 0;
+        cvt(ffi::EVP_PKEY_CTX_get_rsa_padding(pctx, &mut pad))?;
+        ctx.set_rsa_padding(Padding::from_raw(pad))?;
+
+        if pad == ffi::RSA_PKCS1_PSS_PADDING {
+            if let Some(saltlen) = pss.saltlen {
+                ctx.set_rsa_pss_saltlen(RsaPssSaltlen(saltlen))?;
+            }
+            if let Some(mgf1_md) = pss.mgf1_md {
+                ctx.set_rsa_mgf1_md(MdRef::from_ptr(mgf1_md.as_ptr() as *mut _))?;
+            }
+        }
+    }
+
+    Ok(ctx)
+}
+
+fn prehash_unsupported() -> ErrorStack {
+    unsafe {
+        cfg_if! {
+            if #[cfg(ossl300)] {
+                ffi::ERR_new();
+                ffi::ERR_set_debug(concat!(file!(), "\0").as_ptr() as *const _, line!() as _, ptr::null());
+                ffi::ERR_set_error(
+                    ffi::ERR_LIB_EVP,
+                    ffi::EVP_R_OPERATION_NOT_SUPPORTED_FOR_THIS_KEYTYPE,
+                    ptr::null(),
+                );
+            } else {
+                ffi::ERR_put_error(
+                    ffi::ERR_LIB_EVP,
+                    0,
+                    ffi::EVP_R_OPERATION_NOT_SUPPORTED_FOR_THIS_KEYTYPE,
+                    concat!(file!(), "\0").as_ptr() as *const _,
+                    line!() as _,
+                );
             }
         }
     }
+    ErrorStack::report()
 }
 
 impl<'a> Write for Verifier<'a> {
@@ -629,6 +955,163 @@ impl<'a> Write for Verifier<'a> {
     }
 }
 
+/// A signature to be checked by [`verify_batch`].
+#[cfg(any(ossl111, boringssl, libressl370))]
+pub struct BatchItem<'a, T> {
+    key: &'a PKeyRef<T>,
+    message: &'a [u8],
+    signature: &'a [u8],
+}
+
+#[cfg(any(ossl111, boringssl, libressl370))]
+impl<T> Clone for BatchItem<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[cfg(any(ossl111, boringssl, libressl370))]
+impl<T> Copy for BatchItem<'_, T> {}
+
+#[cfg(any(ossl111, boringssl, libressl370))]
+impl<'a, T> BatchItem<'a, T>
+where
+    T: HasPublic,
+{
+    /// Creates a new `BatchItem` for a signature over `message` made by `key`.
+    pub fn new(key: &'a PKeyRef<T>, message: &'a [u8], signature: &'a [u8]) -> BatchItem<'a, T> {
+        BatchItem {
+            key,
+            message,
+            signature,
+        }
+    }
+
+    fn verify(&self) -> Result<bool, ErrorStack> {
+        Verifier::new_without_digest(self.key)?.verify_oneshot(self.signature, self.message)
+    }
+}
+
+/// Verifies a batch of signatures made with a one-shot algorithm such as Ed25519 or Ed448.
+///
+/// Returns `true` only if every signature in the batch is valid, stopping at the first invalid
+/// one. Use [`verify_batch_each`] to find out which signatures are invalid.
+///
+/// OpenSSL does not implement batch verification for any algorithm, so signatures are currently
+/// checked sequentially. Batches are independent of each other, so a large batch can be split up
+/// with [`slice::chunks`] and the chunks verified in parallel, for example on a thread pool.
+///
+/// # Examples
+///
+/// ```
+/// use openssl::pkey::PKey;
+/// use openssl::sign::{verify_batch, BatchItem, Signer};
+///
+/// let keys = (0..4)
+///     .map(|_| PKey::generate_ed25519().unwrap())
+///     .collect::<Vec<_>>();
+/// let messages = [b"zero", b"one_", b"two_", b"thre"];
+/// let signatures = keys
+///     .iter()
+///     .zip(&messages)
+///     .map(|(key, message)| {
+///         let mut signer = Signer::new_without_digest(key).unwrap();
+///         signer.sign_oneshot_to_vec(*message).unwrap()
+///     })
+///     .collect::<Vec<_>>();
+///
+/// let batch = keys
+///     .iter()
+///     .zip(&messages)
+///     .zip(&signatures)
+///     .map(|((key, message), signature)| BatchItem::new(key, *message, signature))
+///     .collect::<Vec<_>>();
+/// assert!(verify_batch(&batch).unwrap());
+/// ```
+///
+/// OpenSSL documentation at [`EVP_DigestVerify`].
+///
+/// [`EVP_DigestVerify`]: https://www.openssl.org/docs/man1.1.1/man3/EVP_DigestVerify.html
+#[cfg(any(ossl111, boringssl, libressl370))]
+pub fn verify_batch<T>(batch: &[BatchItem<'_, T>]) -> Result<bool, ErrorStack>
+where
+    T: HasPublic,
+{
+    for item in batch {
+        if !item.verify()? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Like [`verify_batch`], but returns the validity of each signature in the batch.
+#[cfg(any(ossl111, boringssl, libressl370))]
+pub fn verify_batch_each<T>(batch: &[BatchItem<'_, T>]) -> Result<Vec<bool>, ErrorStack>
+where
+    T: HasPublic,
+{
+    batch.iter().map(BatchItem::verify).collect()
+}
+
+/// Signs `data` with `pkey` in one go, returning the signature.
+///
+/// `digest` must be `None` for keys such as Ed25519 which do not use one. Unlike
+/// [`Signer::sign_oneshot`], this uses the calling thread's cached digest context when
+/// [`thread_cache`] is enabled.
+///
+/// Requires OpenSSL 1.1.1 or newer.
+///
+/// [`thread_cache`]: crate::thread_cache
+#[cfg(ossl111)]
+pub fn sign_oneshot<T>(
+    digest: Option<MessageDigest>,
+    pkey: &PKeyRef<T>,
+    data: &[u8],
+) -> Result<Vec<u8>, ErrorStack>
+where
+    T: HasPrivate,
+{
+    thread_cache::with_md_ctx(|ctx| {
+        let md = digest.map(|md| unsafe { MdRef::from_ptr(md.as_ptr() as *mut _) });
+        ctx.digest_sign_init(md, pkey)?;
+        let mut signature = vec![];
+        ctx.digest_sign_to_vec(data, &mut signature)?;
+        Ok(signature)
+    })
+}
+
+/// Computes the HMAC of `data` with the key `key` and the digest `digest`.
+///
+/// This uses the calling thread's cached digest context when [`thread_cache`] is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use openssl::hash::MessageDigest;
+/// use openssl::sign::hmac;
+///
+/// let mac = hmac(MessageDigest::sha256(), b"key", b"The quick brown fox jumps over the lazy dog")
+///     .unwrap();
+/// assert_eq!(mac.len(), 32);
+/// ```
+///
+/// [`thread_cache`]: crate::thread_cache
+#[cfg(not(boringssl))]
+pub fn hmac(digest: MessageDigest, key: &[u8], data: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+    let pkey = PKey::hmac(key)?;
+    thread_cache::with_md_ctx(|ctx| {
+        ctx.digest_sign_init(
+            Some(unsafe { MdRef::from_ptr(digest.as_ptr() as *mut _) }),
+            &pkey,
+        )?;
+        ctx.digest_sign_update(data)?;
+        let mut mac = vec![];
+        ctx.digest_sign_final_to_vec(&mut mac)?;
+        Ok(mac)
+    })
+}
+
 #[cfg(not(ossl101))]
 use ffi::EVP_DigestVerifyFinal;
 
@@ -652,9 +1135,19 @@ mod test {
     use crate::hash::MessageDigest;
     use crate::nid::Nid;
     use crate::pkey::PKey;
+    #[cfg(any(ossl111, boringssl, libressl370))]
+    use crate::pkey::Private;
     use crate::rsa::{Padding, Rsa};
+    #[cfg(not(boringssl))]
+    use crate::sign::hmac;
+    #[cfg(ossl111)]
+    use crate::sign::sign_oneshot;
     #[cfg(ossl111)]
     use crate::sign::RsaPssSaltlen;
+    #[cfg(ossl110)]
+    use crate::sign::SignerPool;
+    #[cfg(any(ossl111, boringssl, libressl370))]
+    use crate::sign::{verify_batch, verify_batch_each, BatchItem};
     use crate::sign::{Signer, Verifier};
 
     const INPUT: &str =
@@ -685,6 +1178,73 @@ mod test {
         assert_eq!(hex::encode(result), SIGNATURE);
     }
 
+    #[test]
+    #[cfg(ossl110)]
+    fn rsa_sign_pool() {
+        let key = include_bytes!("../test/rsa.pem");
+        let private_key = Rsa::private_key_from_pem(key).unwrap();
+        let pkey = PKey::from_rsa(private_key).unwrap();
+
+        let template = Signer::new(MessageDigest::sha256(), &pkey).unwrap();
+        let pool = SignerPool::new(template, 1).unwrap();
+        assert_eq!(pool.idle(), 1);
+
+        for _ in 0..2 {
+            let mut signer = pool.get().unwrap();
+            assert_eq!(pool.idle(), 0);
+            assert_eq!(signer.rsa_padding().unwrap(), Padding::PKCS1);
+            signer.update(&Vec::from_hex(INPUT).unwrap()).unwrap();
+            let result = signer.sign_to_vec().unwrap();
+            assert_eq!(hex::encode(result), SIGNATURE);
+        }
+        assert_eq!(pool.idle(), 1);
+
+        let first = pool.get().unwrap();
+        let second = pool.get().unwrap();
+        drop(first);
+        drop(second);
+        assert_eq!(pool.idle(), 2);
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn rsa_sign_pool_pss_threads() {
+        use std::sync::Arc;
+        use std::thread;
+
+        let key = include_bytes!("../test/rsa.pem");
+        let private_key = Rsa::private_key_from_pem(key).unwrap();
+        let pkey = PKey::from_rsa(private_key).unwrap();
+
+        let mut template = Signer::new(MessageDigest::sha256(), &pkey).unwrap();
+        template.set_rsa_padding(Padding::PKCS1_PSS).unwrap();
+        let pool = Arc::new(SignerPool::new(template, 2).unwrap());
+
+        let handles = (0..4)
+            .map(|_| {
+                let pool = pool.clone();
+                thread::spawn(move || {
+                    (0..4)
+                        .map(|_| {
+                            let mut signer = pool.get().unwrap();
+                            signer.update(b"hello, world!").unwrap();
+                            signer.sign_to_vec().unwrap()
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            for signature in handle.join().unwrap() {
+                let mut verifier = Verifier::new(MessageDigest::sha256(), &pkey).unwrap();
+                verifier.set_rsa_padding(Padding::PKCS1_PSS).unwrap();
+                verifier.update(b"hello, world!").unwrap();
+                assert!(verifier.verify(&signature).unwrap());
+            }
+        }
+    }
+
     #[test]
     fn rsa_verify_ok() {
         let key = include_bytes!("../test/rsa.pem");
@@ -716,6 +1276,7 @@ mod test {
             let mut signer = Signer::new(ty, &pkey).unwrap();
             signer.update(data).unwrap();
             assert_eq!(signer.sign_to_vec().unwrap(), *res);
+            assert_eq!(hmac(ty, key, data).unwrap(), *res);
         }
     }
 
@@ -845,6 +1406,102 @@ mod test {
         assert!(verifier.verify(&signature).unwrap());
     }
 
+    #[test]
+    fn prehashed() {
+        let digest = crate::hash::hash(MessageDigest::sha256(), b"hello world").unwrap();
+
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let ec = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+        let rsa =
+            PKey::from_rsa(Rsa::private_key_from_pem(include_bytes!("../test/rsa.pem")).unwrap())
+                .unwrap();
+
+        for (key, padding) in [
+            (&ec, None),
+            (&rsa, Some(Padding::PKCS1)),
+            (&rsa, Some(Padding::PKCS1_PSS)),
+        ] {
+            let mut signer = Signer::new(MessageDigest::sha256(), key).unwrap();
+            let mut verifier = Verifier::new(MessageDigest::sha256(), key).unwrap();
+            if let Some(padding) = padding {
+                signer.set_rsa_padding(padding).unwrap();
+                verifier.set_rsa_padding(padding).unwrap();
+            }
+
+            let signature = signer.sign_prehashed(&digest).unwrap();
+            verifier.update(b"hello world").unwrap();
+            assert!(verifier.verify(&signature).unwrap());
+
+            signer.update(b"hello world").unwrap();
+            let signature = signer.sign_to_vec().unwrap();
+            assert!(verifier.verify_prehashed(&signature, &digest).unwrap());
+
+            let other = crate::hash::hash(MessageDigest::sha256(), b"goodbye world").unwrap();
+            assert!(!verifier.verify_prehashed(&signature, &other).unwrap());
+        }
+    }
+
+    #[test]
+    fn prehashed_pss() {
+        let digest = crate::hash::hash(MessageDigest::sha256(), b"hello world").unwrap();
+        let key =
+            PKey::from_rsa(Rsa::private_key_from_pem(include_bytes!("../test/rsa.pem")).unwrap())
+                .unwrap();
+        let verifier = |saltlen: Option<libc::c_int>, mgf1_md: Option<MessageDigest>| {
+            let mut verifier = Verifier::new(MessageDigest::sha256(), &key).unwrap();
+            verifier.set_rsa_padding(Padding::PKCS1_PSS).unwrap();
+            if let Some(saltlen) = saltlen {
+                verifier
+                    .set_rsa_pss_saltlen(RsaPssSaltlen::custom(saltlen))
+                    .unwrap();
+            }
+            if let Some(md) = mgf1_md {
+                verifier.set_rsa_mgf1_md(md).unwrap();
+            }
+            verifier
+        };
+
+        let mut signer = Signer::new(MessageDigest::sha256(), &key).unwrap();
+        signer.set_rsa_padding(Padding::PKCS1_PSS).unwrap();
+        signer
+            .set_rsa_pss_saltlen(RsaPssSaltlen::custom(20))
+            .unwrap();
+        signer.set_rsa_mgf1_md(MessageDigest::sha1()).unwrap();
+
+        let signature = signer.sign_prehashed(&digest).unwrap();
+        let mut streaming = verifier(Some(20), Some(MessageDigest::sha1()));
+        streaming.update(b"hello world").unwrap();
+        assert!(streaming.verify(&signature).unwrap());
+        let mut streaming = verifier(Some(32), Some(MessageDigest::sha1()));
+        streaming.update(b"hello world").unwrap();
+        assert!(!streaming.verify(&signature).unwrap());
+
+        signer.update(b"hello world").unwrap();
+        let signature = signer.sign_to_vec().unwrap();
+        let check = |saltlen, mgf1_md| {
+            verifier(saltlen, mgf1_md)
+                .verify_prehashed(&signature, &digest)
+                .unwrap()
+        };
+        assert!(check(Some(20), Some(MessageDigest::sha1())));
+        assert!(check(None, Some(MessageDigest::sha1())));
+        assert!(!check(Some(32), Some(MessageDigest::sha1())));
+        assert!(!check(Some(20), None));
+    }
+
+    #[test]
+    #[cfg(any(ossl111, boringssl, libressl370))]
+    fn eddsa_prehashed() {
+        let key = PKey::generate_ed25519().unwrap();
+        let digest = crate::hash::hash(MessageDigest::sha512(), b"hello world").unwrap();
+
+        let signer = Signer::new_without_digest(&key).unwrap();
+        assert!(signer.sign_prehashed(&digest).is_err());
+
+        let verifier = Verifier::new_without_digest(&key).unwrap();
+        assert!(verifier.verify_prehashed(&[0; 64], &digest).is_err());
+    }
+
     #[test]
     #[cfg(any(ossl111, boringssl, libressl370))]
     fn eddsa() {
@@ -857,6 +1514,65 @@ mod test {
         assert!(verifier.verify_oneshot(&signature, b"hello world").unwrap());
     }
 
+    #[test]
+    #[cfg(ossl111)]
+    fn sign_oneshot_fn() {
+        let key = PKey::generate_ed25519().unwrap();
+        let signature = sign_oneshot(None, &key, b"hello world").unwrap();
+        let mut verifier = Verifier::new_without_digest(&key).unwrap();
+        assert!(verifier.verify_oneshot(&signature, b"hello world").unwrap());
+
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let signature = sign_oneshot(Some(MessageDigest::sha256()), &key, b"hello world").unwrap();
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &key).unwrap();
+        assert!(verifier.verify_oneshot(&signature, b"hello world").unwrap());
+    }
+
+    #[test]
+    #[cfg(any(ossl111, boringssl, libressl370))]
+    fn eddsa_verify_batch() {
+        let mut keys = (0..3)
+            .map(|_| PKey::generate_ed25519().unwrap())
+            .collect::<Vec<_>>();
+        #[cfg(not(any(boringssl, libressl370)))]
+        keys.push(PKey::generate_ed448().unwrap());
+        let messages = keys
+            .iter()
+            .enumerate()
+            .map(|(i, _)| format!("message {}", i).into_bytes())
+            .collect::<Vec<_>>();
+        let mut signatures = keys
+            .iter()
+            .zip(&messages)
+            .map(|(key, message)| {
+                let mut signer = Signer::new_without_digest(key).unwrap();
+                signer.sign_oneshot_to_vec(message).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let batch = keys
+            .iter()
+            .zip(&messages)
+            .zip(&signatures)
+            .map(|((key, message), signature)| BatchItem::new(key, message, signature))
+            .collect::<Vec<_>>();
+        assert!(verify_batch(&batch).unwrap());
+        assert!(verify_batch_each(&batch).unwrap().iter().all(|&v| v));
+        assert!(verify_batch::<Private>(&[]).unwrap());
+
+        signatures[1][0] ^= 1;
+        let batch = keys
+            .iter()
+            .zip(&messages)
+            .zip(&signatures)
+            .map(|((key, message), signature)| BatchItem::new(key, message, signature))
+            .collect::<Vec<_>>();
+        assert!(!verify_batch(&batch).unwrap());
+        let results = verify_batch_each(&batch).unwrap();
+        assert_eq!(results.iter().filter(|&&v| !v).count(), 1);
+        assert!(!results[1]);
+    }
+
     #[test]
     #[cfg(ossl111)]
     fn rsa_sign_verify() {
@@ -0,0 +1,267 @@
+//! OpenSSH public key conversion.
+//!
+//! [`PKey`]s can be converted to and from the single line public key format used by `authorized_keys` and `.pub`
+//! files, and the [RFC 4253] wire encoding carried inside it. RSA, ECDSA on the P-256, P-384 and P-521 curves
+//! ([RFC 5656]), and Ed25519 ([RFC 8709]) keys are supported.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::pkey::{PKey, Public};
+//!
+//! let line = "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIKD4rB6u83Tv9hYYSegYkFw0qnfKRo3Qu0Cbi9J5zP3o user@host";
+//! # #[cfg(any(ossl111, boringssl, libressl370))] {
+//! let key = PKey::<Public>::public_key_from_openssh(line).unwrap();
+//! assert_eq!(
+//!     key.public_key_to_openssh().unwrap(),
+//!     "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIKD4rB6u83Tv9hYYSegYkFw0qnfKRo3Qu0Cbi9J5zP3o",
+//! );
+//! # }
+//! ```
+//!
+//! [RFC 4253]: https://datatracker.ietf.org/doc/html/rfc4253#section-6.6
+//! [RFC 5656]: https://datatracker.ietf.org/doc/html/rfc5656#section-3.1
+//! [RFC 8709]: https://datatracker.ietf.org/doc/html/rfc8709#section-4
+use std::convert::TryFrom;
+use std::str;
+
+use crate::base64;
+use crate::bn::{BigNum, BigNumRef};
+use crate::ec::{EcGroup, EcKey, EcPoint, PointConversionForm};
+use crate::nid::Nid;
+use crate::pkey::{HasPublic, Id, PKey, PKeyRef, Public};
+use crate::rsa::Rsa;
+use crate::thread_cache;
+
+const SSH_RSA: &str = "ssh-rsa";
+#[cfg(any(ossl111, boringssl, libressl370))]
+const SSH_ED25519: &str = "ssh-ed25519";
+
+const ECDSA_CURVES: [(&str, &str, Nid); 3] = [
+    ("ecdsa-sha2-nistp256", "nistp256", Nid::X9_62_PRIME256V1),
+    ("ecdsa-sha2-nistp384", "nistp384", Nid::SECP384R1),
+    ("ecdsa-sha2-nistp521", "nistp521", Nid::SECP521R1),
+];
+
+invalid_input_error! {
+    /// An error converting a key to or from the OpenSSH format.
+    pub struct SshKeyError => "invalid OpenSSH public key: {}";
+}
+
+impl PKey<Public> {
+    /// Parses a public key in the OpenSSH single line format, for example `ssh-rsa AAAA... user@host`.
+    ///
+    /// The trailing comment is optional and ignored. `authorized_keys` options preceding the key type are not
+    /// supported.
+    pub fn public_key_from_openssh(line: &str) -> Result<PKey<Public>, SshKeyError> {
+        let mut parts = line.split_whitespace();
+        let key_type = parts
+            .next()
+            .ok_or_else(|| SshKeyError::invalid("empty key"))?;
+        let blob = parts
+            .next()
+            .ok_or_else(|| SshKeyError::invalid("missing key data"))?;
+        let blob = base64::decode_block(blob)?;
+
+        let blob_type = Reader(&blob).string()?;
+        if blob_type != key_type.as_bytes() {
+            return Err(SshKeyError::invalid(
+                "key type does not match the encoded key",
+            ));
+        }
+        PKey::public_key_from_openssh_blob(&blob)
+    }
+
+    /// Parses a public key from its binary SSH wire encoding.
+    pub fn public_key_from_openssh_blob(blob: &[u8]) -> Result<PKey<Public>, SshKeyError> {
+        let mut reader = Reader(blob);
+        let key_type = reader.string()?;
+        let key_type =
+            str::from_utf8(key_type).map_err(|_| SshKeyError::invalid("invalid key type"))?;
+
+        let key = match key_type {
+            SSH_RSA => {
+                let e = reader.mpint()?;
+                let n = reader.mpint()?;
+                PKey::from_rsa(Rsa::from_public_components(n, e)?)?
+            }
+            #[cfg(any(ossl111, boringssl, libressl370))]
+            SSH_ED25519 => PKey::public_key_from_raw_bytes(reader.string()?, Id::ED25519)?,
+            _ => {
+                let &(_, curve, nid) = ECDSA_CURVES
+                    .iter()
+                    .find(|&&(name, _, _)| name == key_type)
+                    .ok_or_else(|| {
+                        SshKeyError::invalid(format!("unsupported key type `{}`", key_type))
+                    })?;
+                if reader.string()? != curve.as_bytes() {
+                    return Err(SshKeyError::invalid("curve does not match the key type"));
+                }
+                let group = EcGroup::from_curve_name(nid)?;
+                let point = reader.string()?;
+                let point =
+                    thread_cache::with_bn_ctx(|ctx| EcPoint::from_bytes(&group, point, ctx))?;
+                let ec_key = EcKey::from_public_key(&group, &point)?;
+                ec_key.check_key()?;
+                PKey::from_ec_key(ec_key)?
+            }
+        };
+
+        if !reader.0.is_empty() {
+            return Err(SshKeyError::invalid("trailing data after key"));
+        }
+        Ok(key)
+    }
+}
+
+impl<T> PKeyRef<T>
+where
+    T: HasPublic,
+{
+    /// Serializes the public key in the OpenSSH single line format, without a comment.
+    pub fn public_key_to_openssh(&self) -> Result<String, SshKeyError> {
+        let blob = self.public_key_to_openssh_blob()?;
+        let key_type = Reader(&blob).string()?;
+        let key_type = str::from_utf8(key_type).unwrap();
+        Ok(format!("{} {}", key_type, base64::encode_block(&blob)))
+    }
+
+    /// Serializes the public key in its binary SSH wire encoding.
+    pub fn public_key_to_openssh_blob(&self) -> Result<Vec<u8>, SshKeyError> {
+        let mut blob = vec![];
+        match self.id() {
+            Id::RSA => {
+                let rsa = self.rsa()?;
+                put_string(&mut blob, SSH_RSA.as_bytes());
+                put_mpint(&mut blob, rsa.e());
+                put_mpint(&mut blob, rsa.n());
+            }
+            Id::EC => {
+                let ec_key = self.ec_key()?;
+                let group = ec_key.group();
+                let &(name, curve, _) = ECDSA_CURVES
+                    .iter()
+                    .find(|&&(_, _, nid)| group.curve_name() == Some(nid))
+                    .ok_or_else(|| SshKeyError::invalid("unsupported curve"))?;
+                let point = thread_cache::with_bn_ctx(|ctx| {
+                    ec_key
+                        .public_key()
+                        .to_bytes(group, PointConversionForm::UNCOMPRESSED, ctx)
+                })?;
+                put_string(&mut blob, name.as_bytes());
+                put_string(&mut blob, curve.as_bytes());
+                put_string(&mut blob, &point);
+            }
+            #[cfg(any(ossl111, boringssl, libressl370))]
+            Id::ED25519 => {
+                put_string(&mut blob, SSH_ED25519.as_bytes());
+                put_string(&mut blob, &self.raw_public_key()?);
+            }
+            _ => return Err(SshKeyError::invalid("unsupported key type")),
+        }
+        Ok(blob)
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn string(&mut self) -> Result<&'a [u8], SshKeyError> {
+        if self.0.len() < 4 {
+            return Err(SshKeyError::invalid("truncated key"));
+        }
+        let (len, rest) = self.0.split_at(4);
+        let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]);
+        let len = usize::try_from(len).map_err(|_| SshKeyError::invalid("truncated key"))?;
+        if rest.len() < len {
+            return Err(SshKeyError::invalid("truncated key"));
+        }
+        let (value, rest) = rest.split_at(len);
+        self.0 = rest;
+        Ok(value)
+    }
+
+    fn mpint(&mut self) -> Result<BigNum, SshKeyError> {
+        let value = self.string()?;
+        if value.first().map_or(false, |b| b & 0x80 != 0) {
+            return Err(SshKeyError::invalid("negative integer"));
+        }
+        Ok(BigNum::from_slice(value)?)
+    }
+}
+
+fn put_string(buf: &mut Vec<u8>, value: &[u8]) {
+    let len = u32::try_from(value.len()).unwrap();
+    buf.extend_from_slice(&len.to_be_bytes());
+    buf.extend_from_slice(value);
+}
+
+fn put_mpint(buf: &mut Vec<u8>, value: &BigNumRef) {
+    let mut bytes = value.to_vec();
+    if bytes.first().map_or(false, |b| b & 0x80 != 0) {
+        bytes.insert(0, 0);
+    }
+    put_string(buf, &bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RSA_KEY: &str = "ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAABAQCh+BYK4uPJtGXOjS1lYmM2K5J9vinh8CR3/BYlzJChNuOL2TSXxbbqY913EeZ8dCn5VrD7io8ImtxLaYk8wTM/U+3QGbh3hCUv7JFP5IV3aVlL6kKA0ywPVb9ilE8TA5a8bpvfbr3SvaNnjuygxmj3AbONv/s4yDQs4v5tJ/reSlpIdJed1LnPmt7Ex1sFhSwsD174pcF1A5L5ROjtZMEQxrZHYJqkeDrrnGya11UxMFBji4NmXG9veoKjlnAqH2QbgtPr8jkiGUkftoaHLFcW9Qr4NY2ai50Xw0Byj3+H2JoY2PyrZ62EWQwuz3WTOTY8BwNNb2BvniHgVFbK5emh";
+    const P256_KEY: &str = "ecdsa-sha2-nistp256 AAAAE2VjZHNhLXNoYTItbmlzdHAyNTYAAAAIbmlzdHAyNTYAAABBBAlIlo1vPZ35ANDYgL+qZG694ALTtjTBgdxuHfRvCerKPldMJU0Qct23ByUNJtVVx+pyiRbYLYCUiA+rLIbEGj4=";
+    #[cfg(any(ossl111, boringssl, libressl370))]
+    const ED25519_KEY: &str =
+        "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIKD4rB6u83Tv9hYYSegYkFw0qnfKRo3Qu0Cbi9J5zP3o";
+
+    fn check(pem: &[u8], line: &str) {
+        let key = PKey::private_key_from_pem(pem).unwrap();
+        assert_eq!(key.public_key_to_openssh().unwrap(), line);
+
+        let parsed = PKey::public_key_from_openssh(&format!("{} user@host", line)).unwrap();
+        assert!(parsed.public_eq(&key));
+    }
+
+    #[test]
+    fn rsa() {
+        check(include_bytes!("../test/rsa.pem"), RSA_KEY);
+    }
+
+    #[test]
+    fn ecdsa() {
+        check(include_bytes!("../test/p256.pem"), P256_KEY);
+
+        for &nid in &[Nid::SECP384R1, Nid::SECP521R1] {
+            let group = EcGroup::from_curve_name(nid).unwrap();
+            let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+            let line = key.public_key_to_openssh().unwrap();
+            assert!(line.starts_with("ecdsa-sha2-nistp"));
+            let parsed = PKey::public_key_from_openssh(&line).unwrap();
+            assert!(parsed.public_eq(&key));
+        }
+    }
+
+    #[test]
+    #[cfg(any(ossl111, boringssl, libressl370))]
+    fn ed25519() {
+        check(include_bytes!("../test/ed25519.pem"), ED25519_KEY);
+    }
+
+    #[test]
+    fn invalid() {
+        PKey::public_key_from_openssh("").unwrap_err();
+        PKey::public_key_from_openssh("ssh-rsa").unwrap_err();
+        // type doesn't match the blob
+        let mismatched = RSA_KEY.replacen("ssh-rsa", "ssh-dss", 1);
+        PKey::public_key_from_openssh(&mismatched).unwrap_err();
+        // truncated blob
+        PKey::public_key_from_openssh(&RSA_KEY[..RSA_KEY.len() - 8]).unwrap_err();
+
+        let mut blob = PKey::public_key_from_openssh(P256_KEY)
+            .unwrap()
+            .public_key_to_openssh_blob()
+            .unwrap();
+        blob.push(0);
+        PKey::public_key_from_openssh_blob(&blob).unwrap_err();
+    }
+}
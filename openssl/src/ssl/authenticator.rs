@@ -0,0 +1,620 @@
+//! Exported authenticators, as defined in RFC 9261.
+
+use crate::error::ErrorStack;
+use crate::hash::{hash, MessageDigest};
+use crate::memcmp;
+use crate::nid::Nid;
+use crate::pkey::{HasPrivate, HasPublic, Id, PKey, PKeyRef};
+use crate::rand::rand_bytes;
+use crate::rsa::Padding;
+use crate::sign::{RsaPssSaltlen, Signer, Verifier};
+use crate::ssl::{SslRef, SslVersion};
+use crate::x509::X509;
+
+const CERTIFICATE: u8 = 11;
+const CERTIFICATE_REQUEST: u8 = 13;
+const CERTIFICATE_VERIFY: u8 = 15;
+const CLIENT_CERTIFICATE_REQUEST: u8 = 17;
+const FINISHED: u8 = 20;
+
+const SIGNATURE_ALGORITHMS: u16 = 13;
+
+// the signature schemes offered in requests, in order of preference
+const SIGNATURE_SCHEMES: &[u16] = &[
+    0x0403, // ecdsa_secp256r1_sha256
+    0x0503, // ecdsa_secp384r1_sha384
+    0x0603, // ecdsa_secp521r1_sha512
+    0x0807, // ed25519
+    0x0808, // ed448
+    0x0804, // rsa_pss_rsae_sha256
+    0x0805, // rsa_pss_rsae_sha384
+    0x0806, // rsa_pss_rsae_sha512
+];
+
+/// A request for an exported authenticator.
+///
+/// Requests are created by the party which wants the peer to authenticate, and sent to the peer
+/// over the application protocol. A server requesting an authenticator from a client sends a
+/// `CertificateRequest` message, and a client requesting one from a server sends a
+/// `ClientCertificateRequest` message.
+#[derive(Debug, Clone)]
+pub struct AuthenticatorRequest {
+    msg_type: u8,
+    context: Vec<u8>,
+    schemes: Vec<u16>,
+    encoded: Vec<u8>,
+}
+
+impl AuthenticatorRequest {
+    /// Creates a request for an authenticator from the peer of `ssl`.
+    ///
+    /// `context` identifies the request, and must be unique for the connection. It must not be
+    /// longer than 255 bytes.
+    pub fn new(
+        ssl: &SslRef,
+        context: &[u8],
+    ) -> Result<AuthenticatorRequest, ExportedAuthenticatorError> {
+        let msg_type = if ssl.is_server() {
+            CERTIFICATE_REQUEST
+        } else {
+            CLIENT_CERTIFICATE_REQUEST
+        };
+
+        let mut schemes = vec![];
+        for scheme in SIGNATURE_SCHEMES {
+            schemes.extend_from_slice(&scheme.to_be_bytes());
+        }
+        let mut data = vec![];
+        put_u16_vec(&mut data, &schemes)?;
+        let mut extension = SIGNATURE_ALGORITHMS.to_be_bytes().to_vec();
+        put_u16_vec(&mut extension, &data)?;
+
+        let mut body = vec![];
+        put_u8_vec(&mut body, context)?;
+        put_u16_vec(&mut body, &extension)?;
+
+        Ok(AuthenticatorRequest {
+            msg_type,
+            context: context.to_vec(),
+            schemes: SIGNATURE_SCHEMES.to_vec(),
+            encoded: handshake_message(msg_type, &body)?,
+        })
+    }
+
+    /// Parses a request received from the peer of `ssl`.
+    pub fn from_bytes(
+        ssl: &SslRef,
+        bytes: &[u8],
+    ) -> Result<AuthenticatorRequest, ExportedAuthenticatorError> {
+        let expected = if ssl.is_server() {
+            CLIENT_CERTIFICATE_REQUEST
+        } else {
+            CERTIFICATE_REQUEST
+        };
+
+        let mut reader = Reader(bytes);
+        let (msg_type, mut body) = reader.handshake_message()?;
+        if msg_type != expected || !reader.0.is_empty() {
+            return Err(ExportedAuthenticatorError::invalid(
+                "unexpected authenticator request message",
+            ));
+        }
+
+        let context = body.u8_vec()?.to_vec();
+        let mut extensions = Reader(body.u16_vec()?);
+        body.finish()?;
+
+        let mut schemes = None;
+        while !extensions.0.is_empty() {
+            let extension_type = extensions.u16()?;
+            let mut data = Reader(extensions.u16_vec()?);
+            if extension_type == SIGNATURE_ALGORITHMS {
+                let mut list = Reader(data.u16_vec()?);
+                data.finish()?;
+                let mut parsed = vec![];
+                while !list.0.is_empty() {
+                    parsed.push(list.u16()?);
+                }
+                schemes = Some(parsed);
+            }
+        }
+        let schemes = match schemes {
+            Some(schemes) => schemes,
+            None => {
+                return Err(ExportedAuthenticatorError::invalid(
+                    "authenticator request has no signature_algorithms extension",
+                ))
+            }
+        };
+
+        Ok(AuthenticatorRequest {
+            msg_type,
+            context,
+            schemes,
+            encoded: bytes.to_vec(),
+        })
+    }
+
+    /// Returns the context identifying the request.
+    pub fn context(&self) -> &[u8] {
+        &self.context
+    }
+
+    /// Returns the encoded request, which is sent to the peer.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.encoded
+    }
+}
+
+/// The exporter values and digest used to create and validate authenticators.
+struct Keys {
+    digest: MessageDigest,
+    handshake_context: Vec<u8>,
+    finished_key: Vec<u8>,
+}
+
+impl Keys {
+    fn new(ssl: &SslRef, sender_is_server: bool) -> Result<Keys, ExportedAuthenticatorError> {
+        let supported = match ssl.version2() {
+            Some(SslVersion::TLS1_3) => true,
+            Some(SslVersion::TLS1_2) => ssl.extms_support() == Some(true),
+            _ => false,
+        };
+        if !supported {
+            return Err(ExportedAuthenticatorError::invalid(
+                "exported authenticators require TLS 1.3 or the extended master secret extension",
+            ));
+        }
+
+        let digest = match ssl.current_cipher().and_then(|c| c.handshake_digest()) {
+            Some(digest) => digest,
+            None => {
+                return Err(ExportedAuthenticatorError::invalid(
+                    "the handshake has not completed",
+                ))
+            }
+        };
+
+        let role = if sender_is_server { "server" } else { "client" };
+        let mut handshake_context = vec![0; digest.size()];
+        ssl.export_keying_material(
+            &mut handshake_context,
+            &format!("EXPORTER-{} authenticator handshake context", role),
+            None,
+        )?;
+        let mut finished_key = vec![0; digest.size()];
+        ssl.export_keying_material(
+            &mut finished_key,
+            &format!("EXPORTER-{} authenticator finished key", role),
+            None,
+        )?;
+
+        Ok(Keys {
+            digest,
+            handshake_context,
+            finished_key,
+        })
+    }
+
+    fn transcript_hash(&self, messages: &[&[u8]]) -> Result<Vec<u8>, ErrorStack> {
+        let mut transcript = self.handshake_context.clone();
+        for message in messages {
+            transcript.extend_from_slice(message);
+        }
+        Ok(hash(self.digest, &transcript)?.to_vec())
+    }
+
+    fn finished(&self, messages: &[&[u8]]) -> Result<Vec<u8>, ErrorStack> {
+        let transcript_hash = self.transcript_hash(messages)?;
+        let key = PKey::hmac(&self.finished_key)?;
+        let mut signer = Signer::new(self.digest, &key)?;
+        signer.sign_oneshot_to_vec(&transcript_hash)
+    }
+}
+
+/// Checks that `request` was sent to the party sending the authenticator.
+fn check_request(
+    request: &AuthenticatorRequest,
+    sender_is_server: bool,
+) -> Result<(), ExportedAuthenticatorError> {
+    let expected = if sender_is_server {
+        CLIENT_CERTIFICATE_REQUEST
+    } else {
+        CERTIFICATE_REQUEST
+    };
+    if request.msg_type == expected {
+        Ok(())
+    } else {
+        Err(ExportedAuthenticatorError::invalid(
+            "the authenticator request was sent by the other party",
+        ))
+    }
+}
+
+pub(crate) fn export_authenticator<T>(
+    ssl: &SslRef,
+    request: Option<&AuthenticatorRequest>,
+    chain: &[X509],
+    key: &PKeyRef<T>,
+) -> Result<Vec<u8>, ExportedAuthenticatorError>
+where
+    T: HasPrivate,
+{
+    let sender_is_server = ssl.is_server();
+    let (context, schemes, encoded_request) = match request {
+        Some(request) => {
+            check_request(request, sender_is_server)?;
+            (
+                request.context.clone(),
+                &*request.schemes,
+                &*request.encoded,
+            )
+        }
+        None if sender_is_server => {
+            let mut context = vec![0; 32];
+            rand_bytes(&mut context)?;
+            (context, SIGNATURE_SCHEMES, &[][..])
+        }
+        None => {
+            return Err(ExportedAuthenticatorError::invalid(
+                "only servers can send spontaneous authenticators",
+            ))
+        }
+    };
+    if chain.is_empty() {
+        return Err(ExportedAuthenticatorError::invalid(
+            "the certificate chain is empty",
+        ));
+    }
+
+    let scheme = match schemes
+        .iter()
+        .copied()
+        .find(|&scheme| scheme_matches(scheme, key))
+    {
+        Some(scheme) => scheme,
+        None => {
+            return Err(ExportedAuthenticatorError::invalid(
+                "the key does not support any requested signature scheme",
+            ))
+        }
+    };
+
+    let keys = Keys::new(ssl, sender_is_server)?;
+
+    let mut entries = vec![];
+    for cert in chain {
+        put_u24_vec(&mut entries, &cert.to_der()?)?;
+        put_u16_vec(&mut entries, &[])?;
+    }
+    let mut body = vec![];
+    put_u8_vec(&mut body, &context)?;
+    put_u24_vec(&mut body, &entries)?;
+    let certificate = handshake_message(CERTIFICATE, &body)?;
+
+    let transcript_hash = keys.transcript_hash(&[encoded_request, &certificate])?;
+    let signature = sign(scheme, key, &signed_content(&transcript_hash))?;
+    let mut body = scheme.to_be_bytes().to_vec();
+    put_u16_vec(&mut body, &signature)?;
+    let certificate_verify = handshake_message(CERTIFICATE_VERIFY, &body)?;
+
+    let finished = keys.finished(&[encoded_request, &certificate, &certificate_verify])?;
+    let finished = handshake_message(FINISHED, &finished)?;
+
+    let mut authenticator = certificate;
+    authenticator.extend_from_slice(&certificate_verify);
+    authenticator.extend_from_slice(&finished);
+    Ok(authenticator)
+}
+
+pub(crate) fn export_empty_authenticator(
+    ssl: &SslRef,
+    request: &AuthenticatorRequest,
+) -> Result<Vec<u8>, ExportedAuthenticatorError> {
+    check_request(request, ssl.is_server())?;
+    let keys = Keys::new(ssl, ssl.is_server())?;
+    let finished = keys.finished(&[&request.encoded])?;
+    handshake_message(FINISHED, &finished)
+}
+
+pub(crate) fn validate_authenticator(
+    ssl: &SslRef,
+    request: Option<&AuthenticatorRequest>,
+    authenticator: &[u8],
+) -> Result<Option<Vec<X509>>, ExportedAuthenticatorError> {
+    let sender_is_server = !ssl.is_server();
+    let encoded_request = match request {
+        Some(request) => {
+            check_request(request, sender_is_server)?;
+            &*request.encoded
+        }
+        None if sender_is_server => &[][..],
+        None => {
+            return Err(ExportedAuthenticatorError::invalid(
+                "only servers can send spontaneous authenticators",
+            ))
+        }
+    };
+    let keys = Keys::new(ssl, sender_is_server)?;
+
+    let mut reader = Reader(authenticator);
+    let start = reader.0;
+    let (msg_type, body) = reader.handshake_message()?;
+    if msg_type == FINISHED {
+        // an empty authenticator, refusing the request
+        if request.is_none() || !reader.0.is_empty() {
+            return Err(ExportedAuthenticatorError::invalid(
+                "unexpected empty authenticator",
+            ));
+        }
+        check_finished(&keys, &[encoded_request], body.0)?;
+        return Ok(None);
+    }
+    if msg_type != CERTIFICATE {
+        return Err(ExportedAuthenticatorError::invalid(
+            "unexpected authenticator message",
+        ));
+    }
+    let certificate = &start[..start.len() - reader.0.len()];
+
+    let mut body = body;
+    let context = body.u8_vec()?;
+    let mut entries = Reader(body.u24_vec()?);
+    body.finish()?;
+    match request {
+        Some(request) if request.context != context => {
+            return Err(ExportedAuthenticatorError::invalid(
+                "the authenticator does not match the request",
+            ))
+        }
+        None if context.is_empty() => {
+            return Err(ExportedAuthenticatorError::invalid(
+                "a spontaneous authenticator must have a context",
+            ))
+        }
+        _ => {}
+    }
+    let mut chain = vec![];
+    while !entries.0.is_empty() {
+        chain.push(X509::from_der(entries.u24_vec()?)?);
+        entries.u16_vec()?;
+    }
+    let leaf = match chain.first() {
+        Some(leaf) => leaf,
+        None => {
+            return Err(ExportedAuthenticatorError::invalid(
+                "the authenticator has no certificates",
+            ))
+        }
+    };
+
+    let start = reader.0;
+    let (msg_type, mut body) = reader.handshake_message()?;
+    if msg_type != CERTIFICATE_VERIFY {
+        return Err(ExportedAuthenticatorError::invalid(
+            "unexpected authenticator message",
+        ));
+    }
+    let certificate_verify = &start[..start.len() - reader.0.len()];
+    let scheme = body.u16()?;
+    let signature = body.u16_vec()?;
+    body.finish()?;
+
+    let offered = match request {
+        Some(request) => request.schemes.contains(&scheme),
+        None => SIGNATURE_SCHEMES.contains(&scheme),
+    };
+    let public_key = leaf.public_key()?;
+    if !offered || !scheme_matches(scheme, &public_key) {
+        return Err(ExportedAuthenticatorError::invalid(
+            "the authenticator uses an unexpected signature scheme",
+        ));
+    }
+    let transcript_hash = keys.transcript_hash(&[encoded_request, certificate])?;
+    if !verify(
+        scheme,
+        &public_key,
+        &signed_content(&transcript_hash),
+        signature,
+    )? {
+        return Err(ExportedAuthenticatorError::invalid(
+            "the authenticator signature is invalid",
+        ));
+    }
+
+    let (msg_type, finished) = reader.handshake_message()?;
+    if msg_type != FINISHED || !reader.0.is_empty() {
+        return Err(ExportedAuthenticatorError::invalid(
+            "unexpected authenticator message",
+        ));
+    }
+    check_finished(
+        &keys,
+        &[encoded_request, certificate, certificate_verify],
+        finished.0,
+    )?;
+
+    Ok(Some(chain))
+}
+
+fn check_finished(
+    keys: &Keys,
+    messages: &[&[u8]],
+    finished: &[u8],
+) -> Result<(), ExportedAuthenticatorError> {
+    let expected = keys.finished(messages)?;
+    if expected.len() == finished.len() && memcmp::eq(&expected, finished) {
+        Ok(())
+    } else {
+        Err(ExportedAuthenticatorError::invalid(
+            "the authenticator Finished message is invalid",
+        ))
+    }
+}
+
+fn signed_content(transcript_hash: &[u8]) -> Vec<u8> {
+    let mut content = vec![0x20; 64];
+    content.extend_from_slice(b"Exported Authenticator\0");
+    content.extend_from_slice(transcript_hash);
+    content
+}
+
+fn scheme_digest(scheme: u16) -> Option<MessageDigest> {
+    match scheme {
+        0x0403 | 0x0804 => Some(MessageDigest::sha256()),
+        0x0503 | 0x0805 => Some(MessageDigest::sha384()),
+        0x0603 | 0x0806 => Some(MessageDigest::sha512()),
+        _ => None,
+    }
+}
+
+fn scheme_matches<T>(scheme: u16, key: &PKeyRef<T>) -> bool
+where
+    T: HasPublic,
+{
+    let curve = || key.ec_key().ok().and_then(|key| key.group().curve_name());
+    match scheme {
+        0x0403 => curve() == Some(Nid::X9_62_PRIME256V1),
+        0x0503 => curve() == Some(Nid::SECP384R1),
+        0x0603 => curve() == Some(Nid::SECP521R1),
+        0x0804..=0x0806 => key.id() == Id::RSA,
+        0x0807 => key.id() == Id::ED25519,
+        0x0808 => key.id() == Id::ED448,
+        _ => false,
+    }
+}
+
+fn sign<T>(scheme: u16, key: &PKeyRef<T>, data: &[u8]) -> Result<Vec<u8>, ErrorStack>
+where
+    T: HasPrivate,
+{
+    let mut signer = match scheme_digest(scheme) {
+        Some(digest) => {
+            let mut signer = Signer::new(digest, key)?;
+            if key.id() == Id::RSA {
+                signer.set_rsa_padding(Padding::PKCS1_PSS)?;
+                signer.set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)?;
+                signer.set_rsa_mgf1_md(digest)?;
+            }
+            signer
+        }
+        None => Signer::new_without_digest(key)?,
+    };
+    signer.sign_oneshot_to_vec(data)
+}
+
+fn verify<T>(
+    scheme: u16,
+    key: &PKeyRef<T>,
+    data: &[u8],
+    signature: &[u8],
+) -> Result<bool, ErrorStack>
+where
+    T: HasPublic,
+{
+    let mut verifier = match scheme_digest(scheme) {
+        Some(digest) => {
+            let mut verifier = Verifier::new(digest, key)?;
+            if key.id() == Id::RSA {
+                verifier.set_rsa_padding(Padding::PKCS1_PSS)?;
+                verifier.set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)?;
+                verifier.set_rsa_mgf1_md(digest)?;
+            }
+            verifier
+        }
+        None => Verifier::new_without_digest(key)?,
+    };
+    verifier.verify_oneshot(signature, data)
+}
+
+fn handshake_message(msg_type: u8, body: &[u8]) -> Result<Vec<u8>, ExportedAuthenticatorError> {
+    let mut message = vec![msg_type];
+    put_u24_vec(&mut message, body)?;
+    Ok(message)
+}
+
+fn put_u8_vec(out: &mut Vec<u8>, data: &[u8]) -> Result<(), ExportedAuthenticatorError> {
+    if data.len() > 0xff {
+        return Err(ExportedAuthenticatorError::invalid("value is too long"));
+    }
+    out.push(data.len() as u8);
+    out.extend_from_slice(data);
+    Ok(())
+}
+
+fn put_u16_vec(out: &mut Vec<u8>, data: &[u8]) -> Result<(), ExportedAuthenticatorError> {
+    if data.len() > 0xffff {
+        return Err(ExportedAuthenticatorError::invalid("value is too long"));
+    }
+    out.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    out.extend_from_slice(data);
+    Ok(())
+}
+
+fn put_u24_vec(out: &mut Vec<u8>, data: &[u8]) -> Result<(), ExportedAuthenticatorError> {
+    if data.len() > 0xff_ffff {
+        return Err(ExportedAuthenticatorError::invalid("value is too long"));
+    }
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes()[1..]);
+    out.extend_from_slice(data);
+    Ok(())
+}
+
+/// A reader of TLS presentation language structures.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], ExportedAuthenticatorError> {
+        if self.0.len() < len {
+            return Err(ExportedAuthenticatorError::invalid("message is truncated"));
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn len(&mut self, size: usize) -> Result<usize, ExportedAuthenticatorError> {
+        let bytes = self.bytes(size)?;
+        Ok(bytes.iter().fold(0, |len, &b| len << 8 | usize::from(b)))
+    }
+
+    fn u16(&mut self) -> Result<u16, ExportedAuthenticatorError> {
+        self.len(2).map(|n| n as u16)
+    }
+
+    fn u8_vec(&mut self) -> Result<&'a [u8], ExportedAuthenticatorError> {
+        let len = self.len(1)?;
+        self.bytes(len)
+    }
+
+    fn u16_vec(&mut self) -> Result<&'a [u8], ExportedAuthenticatorError> {
+        let len = self.len(2)?;
+        self.bytes(len)
+    }
+
+    fn u24_vec(&mut self) -> Result<&'a [u8], ExportedAuthenticatorError> {
+        let len = self.len(3)?;
+        self.bytes(len)
+    }
+
+    fn handshake_message(&mut self) -> Result<(u8, Reader<'a>), ExportedAuthenticatorError> {
+        let msg_type = self.len(1)? as u8;
+        let body = self.u24_vec()?;
+        Ok((msg_type, Reader(body)))
+    }
+
+    fn finish(&self) -> Result<(), ExportedAuthenticatorError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(ExportedAuthenticatorError::invalid(
+                "message has trailing data",
+            ))
+        }
+    }
+}
+
+invalid_input_error! {
+    /// An error creating or validating an exported authenticator.
+    pub struct ExportedAuthenticatorError => "invalid exported authenticator: {}";
+}
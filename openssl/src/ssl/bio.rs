@@ -5,9 +5,9 @@ use ffi::{
 };
 use libc::{c_char, c_int, c_long, c_void, strlen};
 use std::any::Any;
+use std::cmp;
 use std::io;
 use std::io::prelude::*;
-use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::ptr;
 use std::slice;
 
@@ -19,6 +19,9 @@ pub struct StreamState<S> {
     pub error: Option<io::Error>,
     pub panic: Option<Box<dyn Any + Send>>,
     pub dtls_mtu_size: c_long,
+    // bytes already read from the stream, which are returned before reading any more
+    pub buffered: Vec<u8>,
+    pub buffered_pos: usize,
 }
 
 /// Safe wrapper for `BIO_METHOD`
@@ -34,6 +37,13 @@ unsafe impl Sync for BioMethod {}
 unsafe impl Send for BioMethod {}
 
 pub fn new<S: Read + Write>(stream: S) -> Result<(*mut BIO, BioMethod), ErrorStack> {
+    new_buffered(stream, vec![])
+}
+
+pub fn new_buffered<S: Read + Write>(
+    stream: S,
+    buffered: Vec<u8>,
+) -> Result<(*mut BIO, BioMethod), ErrorStack> {
     let method = BioMethod::new::<S>()?;
 
     let state = Box::new(StreamState {
@@ -41,6 +51,8 @@ pub fn new<S: Read + Write>(stream: S) -> Result<(*mut BIO, BioMethod), ErrorSta
         error: None,
         panic: None,
         dtls_mtu_size: 0,
+        buffered,
+        buffered_pos: 0,
     });
 
     unsafe {
@@ -81,17 +93,37 @@ pub unsafe fn set_dtls_mtu_size<S>(bio: *mut BIO, mtu_size: usize) {
     state::<S>(bio).dtls_mtu_size = mtu_size as c_long;
 }
 
+/// Removes the stream from the BIO, returning it along with the buffered bytes which have not been
+/// read.
+///
+/// The BIO fails all further reads and writes, and must only be freed.
+pub unsafe fn take_stream<S>(bio: *mut BIO) -> (S, Vec<u8>) {
+    let data = BIO_get_data(bio);
+    assert!(!data.is_null());
+    BIO_set_data(bio, ptr::null_mut());
+    let mut state = Box::<StreamState<S>>::from_raw(data as *mut _);
+    let buffered = state.buffered.split_off(state.buffered_pos);
+    (state.stream, buffered)
+}
+
 unsafe fn state<'a, S: 'a>(bio: *mut BIO) -> &'a mut StreamState<S> {
     &mut *(BIO_get_data(bio) as *mut _)
 }
 
+unsafe fn try_state<'a, S: 'a>(bio: *mut BIO) -> Option<&'a mut StreamState<S>> {
+    (BIO_get_data(bio) as *mut StreamState<S>).as_mut()
+}
+
 unsafe extern "C" fn bwrite<S: Write>(bio: *mut BIO, buf: *const c_char, len: c_int) -> c_int {
     BIO_clear_retry_flags(bio);
 
-    let state = state::<S>(bio);
+    let state = match try_state::<S>(bio) {
+        Some(state) => state,
+        None => return -1,
+    };
     let buf = slice::from_raw_parts(buf as *const _, len as usize);
 
-    match catch_unwind(AssertUnwindSafe(|| state.stream.write(buf))) {
+    match crate::panic::catch(|| state.stream.write(buf)) {
         Ok(Ok(len)) => len as c_int,
         Ok(Err(err)) => {
             if retriable_error(&err) {
@@ -110,10 +142,25 @@ unsafe extern "C" fn bwrite<S: Write>(bio: *mut BIO, buf: *const c_char, len: c_
 unsafe extern "C" fn bread<S: Read>(bio: *mut BIO, buf: *mut c_char, len: c_int) -> c_int {
     BIO_clear_retry_flags(bio);
 
-    let state = state::<S>(bio);
+    let state = match try_state::<S>(bio) {
+        Some(state) => state,
+        None => return -1,
+    };
     let buf = slice::from_raw_parts_mut(buf as *mut _, len as usize);
 
-    match catch_unwind(AssertUnwindSafe(|| state.stream.read(buf))) {
+    if state.buffered_pos < state.buffered.len() {
+        let buffered = &state.buffered[state.buffered_pos..];
+        let len = cmp::min(buf.len(), buffered.len());
+        buf[..len].copy_from_slice(&buffered[..len]);
+        state.buffered_pos += len;
+        if state.buffered_pos == state.buffered.len() {
+            state.buffered = vec![];
+            state.buffered_pos = 0;
+        }
+        return len as c_int;
+    }
+
+    match crate::panic::catch(|| state.stream.read(buf)) {
         Ok(Ok(len)) => len as c_int,
         Ok(Err(err)) => {
             if retriable_error(&err) {
@@ -147,10 +194,13 @@ unsafe extern "C" fn ctrl<S: Write>(
     _num: c_long,
     _ptr: *mut c_void,
 ) -> c_long {
-    let state = state::<S>(bio);
+    let state = match try_state::<S>(bio) {
+        Some(state) => state,
+        None => return 0,
+    };
 
     if cmd == BIO_CTRL_FLUSH {
-        match catch_unwind(AssertUnwindSafe(|| state.stream.flush())) {
+        match crate::panic::catch(|| state.stream.flush()) {
             Ok(Ok(())) => 1,
             Ok(Err(err)) => {
                 state.error = Some(err);
@@ -181,10 +231,12 @@ unsafe extern "C" fn destroy<S>(bio: *mut BIO) -> c_int {
         return 0;
     }
 
+    // the stream has been taken out of the BIO by `take_stream`
     let data = BIO_get_data(bio);
-    assert!(!data.is_null());
-    let _ = Box::<StreamState<S>>::from_raw(data as *mut _);
-    BIO_set_data(bio, ptr::null_mut());
+    if !data.is_null() {
+        let _ = Box::<StreamState<S>>::from_raw(data as *mut _);
+        BIO_set_data(bio, ptr::null_mut());
+    }
     BIO_set_init(bio, 0);
     1
 }
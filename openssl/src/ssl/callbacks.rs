@@ -3,17 +3,25 @@ use foreign_types::ForeignType;
 use foreign_types::ForeignTypeRef;
 #[cfg(any(ossl111, not(osslconf = "OPENSSL_NO_PSK")))]
 use libc::c_char;
-#[cfg(ossl111)]
 use libc::size_t;
 use libc::{c_int, c_uchar, c_uint, c_void};
+use std::any::Any;
+#[cfg(ossl111)]
+use std::cmp;
 #[cfg(any(ossl111, not(osslconf = "OPENSSL_NO_PSK")))]
 use std::ffi::CStr;
+#[cfg(boringssl)]
+use std::io;
+#[cfg(boringssl)]
+use std::marker::PhantomData;
 use std::mem;
 use std::ptr;
 use std::slice;
 #[cfg(ossl111)]
 use std::str;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+#[cfg(ossl111)]
+use std::time::Duration;
 
 use crate::dh::Dh;
 #[cfg(all(ossl101, not(ossl110)))]
@@ -21,19 +29,91 @@ use crate::ec::EcKey;
 use crate::error::ErrorStack;
 use crate::pkey::Params;
 #[cfg(any(ossl102, libressl261))]
-use crate::ssl::AlpnError;
+use crate::ssl::overrides::AlpnOverride;
+use crate::ssl::HandshakeTimings;
+#[cfg(any(ossl102, libressl261))]
+use crate::ssl::{select_next_proto, AlpnError};
 use crate::ssl::{
     try_get_session_ctx_index, SniError, Ssl, SslAlert, SslContext, SslContextRef, SslRef,
     SslSession, SslSessionRef,
 };
 #[cfg(ossl111)]
 use crate::ssl::{ClientHelloResponse, ExtensionContext};
+#[cfg(boringssl)]
+use crate::ssl::{PrivateKeyMethod, PrivateKeyMethodError};
 #[cfg(ossl111)]
 use crate::util::ForeignTypeRefExt;
 #[cfg(ossl111)]
 use crate::x509::X509Ref;
 use crate::x509::{X509StoreContext, X509StoreContextRef};
 
+/// The state of a connection whose callbacks have panicked.
+pub struct CallbackPanic {
+    // a panic which has not been handled yet
+    payload: Mutex<Option<Box<dyn Any + Send>>>,
+    // the description of the first panic
+    poison: Option<String>,
+}
+
+/// Runs a callback of a connection, returning `None` if it panicked.
+///
+/// The panic is recorded on the connection, poisoning it, and is handled by `check_panic` once
+/// control returns to Rust.
+unsafe fn catch<F, R>(ssl: *mut ffi::SSL, f: F) -> Option<R>
+where
+    F: FnOnce() -> R,
+{
+    match crate::panic::catch(f) {
+        Ok(r) => Some(r),
+        Err(payload) => {
+            record_panic(SslRef::from_ptr_mut(ssl), payload);
+            None
+        }
+    }
+}
+
+/// Records a panic on a connection, poisoning it.
+pub fn record_panic(ssl: &mut SslRef, payload: Box<dyn Any + Send>) {
+    let idx = Ssl::cached_ex_index::<CallbackPanic>();
+    let mut payload = Some(payload);
+    let new = match ssl.ex_data_mut(idx) {
+        Some(state) => {
+            let pending = state.payload.get_mut().unwrap_or_else(|e| e.into_inner());
+            // only the first of several panics is resumed
+            if pending.is_none() {
+                *pending = payload.take();
+            }
+            false
+        }
+        None => true,
+    };
+    if new {
+        ssl.set_ex_data(
+            idx,
+            CallbackPanic {
+                payload: Mutex::new(payload),
+                poison: None,
+            },
+        );
+    }
+}
+
+/// Handles a panic recorded on a connection, and returns the description of the panic which
+/// poisoned it.
+///
+/// The panic is resumed depending on the `PanicPolicy`.
+pub fn check_panic(ssl: &mut SslRef) -> Option<String> {
+    let state = ssl.ex_data_mut(Ssl::cached_ex_index::<CallbackPanic>())?;
+    let pending = state.payload.get_mut().unwrap_or_else(|e| e.into_inner());
+    if let Some(payload) = pending.take() {
+        // the errors OpenSSL queued because the callback failed are meaningless
+        ErrorStack::get();
+        state.poison = Some(crate::panic::message(&*payload));
+        crate::panic::resume(payload);
+    }
+    state.poison.clone()
+}
+
 pub extern "C" fn raw_verify<F>(preverify_ok: c_int, x509_ctx: *mut ffi::X509_STORE_CTX) -> c_int
 where
     F: Fn(bool, &mut X509StoreContextRef) -> bool + 'static + Sync + Send,
@@ -45,14 +125,15 @@ where
 
         // raw pointer shenanigans to break the borrow of ctx
         // the callback can't mess with its own ex_data slot so this is safe
-        let verify = ctx
+        let ssl = ctx
             .ex_data(ssl_idx)
-            .expect("BUG: store context missing ssl")
+            .expect("BUG: store context missing ssl");
+        let verify = ssl
             .ssl_context()
             .ex_data(verify_idx)
             .expect("BUG: verify callback missing") as *const F;
 
-        (*verify)(preverify_ok != 0, ctx) as c_int
+        catch(ssl.as_ptr(), || (*verify)(preverify_ok != 0, ctx) as c_int).unwrap_or(0)
     }
 }
 
@@ -87,12 +168,13 @@ where
         // Give the callback mutable slices into which it can write the identity and psk.
         let identity_sl = slice::from_raw_parts_mut(identity as *mut u8, max_identity_len as usize);
         let psk_sl = slice::from_raw_parts_mut(psk as *mut u8, max_psk_len as usize);
-        match (*callback)(ssl, hint, identity_sl, psk_sl) {
-            Ok(psk_len) => psk_len as u32,
-            Err(e) => {
+        match catch(ssl.as_ptr(), || (*callback)(ssl, hint, identity_sl, psk_sl)) {
+            Some(Ok(psk_len)) => psk_len as u32,
+            Some(Err(e)) => {
                 e.put();
                 0
             }
+            None => 0,
         }
     }
 }
@@ -125,12 +207,13 @@ where
         };
         // Give the callback mutable slices into which it can write the psk.
         let psk_sl = slice::from_raw_parts_mut(psk as *mut u8, max_psk_len as usize);
-        match (*callback)(ssl, identity, psk_sl) {
-            Ok(psk_len) => psk_len as u32,
-            Err(e) => {
+        match catch(ssl.as_ptr(), || (*callback)(ssl, identity, psk_sl)) {
+            Some(Ok(psk_len)) => psk_len as u32,
+            Some(Err(e)) => {
                 e.put();
                 0
             }
+            None => 0,
         }
     }
 }
@@ -147,14 +230,15 @@ where
         let ssl_idx = X509StoreContext::ssl_idx().expect("BUG: store context ssl index missing");
         let callback_idx = Ssl::cached_ex_index::<Arc<F>>();
 
-        let callback = ctx
+        let ssl = ctx
             .ex_data(ssl_idx)
-            .expect("BUG: store context missing ssl")
+            .expect("BUG: store context missing ssl");
+        let callback = ssl
             .ex_data(callback_idx)
             .expect("BUG: ssl verify callback missing")
             .clone();
 
-        callback(preverify_ok != 0, ctx) as c_int
+        catch(ssl.as_ptr(), || callback(preverify_ok != 0, ctx) as c_int).unwrap_or(0)
     }
 }
 
@@ -167,11 +251,15 @@ where
         let callback = arg as *const F;
         let mut alert = SslAlert(*al);
 
-        let r = (*callback)(ssl, &mut alert);
+        let r = catch(ssl.as_ptr(), || (*callback)(ssl, &mut alert));
         *al = alert.0;
         match r {
-            Ok(()) => ffi::SSL_TLSEXT_ERR_OK,
-            Err(e) => e.0,
+            Some(Ok(())) => ffi::SSL_TLSEXT_ERR_OK,
+            Some(Err(e)) => e.0,
+            None => {
+                *al = ffi::SSL_AD_INTERNAL_ERROR;
+                ffi::SSL_TLSEXT_ERR_ALERT_FATAL
+            }
         }
     }
 }
@@ -190,13 +278,23 @@ where
 {
     unsafe {
         let ssl = SslRef::from_ptr_mut(ssl);
-        let callback = ssl
-            .ssl_context()
-            .ex_data(SslContext::cached_ex_index::<F>())
-            .expect("BUG: alpn callback missing") as *const F;
         let protos = slice::from_raw_parts(inbuf as *const u8, inlen as usize);
 
-        match (*callback)(ssl, protos) {
+        let r = match ssl.ex_data(Ssl::cached_ex_index::<AlpnOverride>()) {
+            Some(alpn) => select_next_proto(&alpn.0, protos).ok_or(AlpnError::NOACK),
+            None => {
+                let callback =
+                    ssl.ssl_context()
+                        .ex_data(SslContext::cached_ex_index::<F>())
+                        .expect("BUG: alpn callback missing") as *const F;
+                match catch(ssl.as_ptr(), || (*callback)(ssl, protos)) {
+                    Some(r) => r,
+                    None => return ffi::SSL_TLSEXT_ERR_ALERT_FATAL,
+                }
+            }
+        };
+
+        match r {
             Ok(proto) => {
                 *out = proto.as_ptr() as *const c_uchar;
                 *outlen = proto.len() as c_uchar;
@@ -221,16 +319,19 @@ where
         .ex_data(SslContext::cached_ex_index::<F>())
         .expect("BUG: tmp dh callback missing") as *const F;
 
-    match (*callback)(ssl, is_export != 0, keylength as u32) {
-        Ok(dh) => {
+    match catch(ssl.as_ptr(), || {
+        (*callback)(ssl, is_export != 0, keylength as u32)
+    }) {
+        Some(Ok(dh)) => {
             let ptr = dh.as_ptr();
             mem::forget(dh);
             ptr
         }
-        Err(e) => {
+        Some(Err(e)) => {
             e.put();
             ptr::null_mut()
         }
+        None => ptr::null_mut(),
     }
 }
 
@@ -249,16 +350,19 @@ where
         .ex_data(SslContext::cached_ex_index::<F>())
         .expect("BUG: tmp ecdh callback missing") as *const F;
 
-    match (*callback)(ssl, is_export != 0, keylength as u32) {
-        Ok(ec_key) => {
+    match catch(ssl.as_ptr(), || {
+        (*callback)(ssl, is_export != 0, keylength as u32)
+    }) {
+        Some(Ok(ec_key)) => {
             let ptr = ec_key.as_ptr();
             mem::forget(ec_key);
             ptr
         }
-        Err(e) => {
+        Some(Err(e)) => {
             e.put();
             ptr::null_mut()
         }
+        None => ptr::null_mut(),
     }
 }
 
@@ -276,16 +380,19 @@ where
         .expect("BUG: ssl tmp dh callback missing")
         .clone();
 
-    match callback(ssl, is_export != 0, keylength as u32) {
-        Ok(dh) => {
+    match catch(ssl.as_ptr(), || {
+        callback(ssl, is_export != 0, keylength as u32)
+    }) {
+        Some(Ok(dh)) => {
             let ptr = dh.as_ptr();
             mem::forget(dh);
             ptr
         }
-        Err(e) => {
+        Some(Err(e)) => {
             e.put();
             ptr::null_mut()
         }
+        None => ptr::null_mut(),
     }
 }
 
@@ -304,16 +411,19 @@ where
         .expect("BUG: ssl tmp ecdh callback missing")
         .clone();
 
-    match callback(ssl, is_export != 0, keylength as u32) {
-        Ok(ec_key) => {
+    match catch(ssl.as_ptr(), || {
+        callback(ssl, is_export != 0, keylength as u32)
+    }) {
+        Some(Ok(ec_key)) => {
             let ptr = ec_key.as_ptr();
             mem::forget(ec_key);
             ptr
         }
-        Err(e) => {
+        Some(Err(e)) => {
             e.put();
             ptr::null_mut()
         }
+        None => ptr::null_mut(),
     }
 }
 
@@ -326,25 +436,27 @@ where
         .ssl_context()
         .ex_data(SslContext::cached_ex_index::<F>())
         .expect("BUG: ocsp callback missing") as *const F;
-    let ret = (*callback)(ssl);
+    let ret = catch(ssl.as_ptr(), || (*callback)(ssl));
 
     if ssl.is_server() {
         match ret {
-            Ok(true) => ffi::SSL_TLSEXT_ERR_OK,
-            Ok(false) => ffi::SSL_TLSEXT_ERR_NOACK,
-            Err(e) => {
+            Some(Ok(true)) => ffi::SSL_TLSEXT_ERR_OK,
+            Some(Ok(false)) => ffi::SSL_TLSEXT_ERR_NOACK,
+            Some(Err(e)) => {
                 e.put();
                 ffi::SSL_TLSEXT_ERR_ALERT_FATAL
             }
+            None => ffi::SSL_TLSEXT_ERR_ALERT_FATAL,
         }
     } else {
         match ret {
-            Ok(true) => 1,
-            Ok(false) => 0,
-            Err(e) => {
+            Some(Ok(true)) => 1,
+            Some(Ok(false)) => 0,
+            Some(Err(e)) => {
                 e.put();
                 -1
             }
+            None => -1,
         }
     }
 }
@@ -366,7 +478,7 @@ where
         .expect("BUG: new session callback missing") as *const F;
     let session = SslSession::from_ptr(session);
 
-    (*callback)(ssl, session);
+    catch(ssl.as_ptr(), || (*callback)(ssl, session));
 
     // the return code doesn't indicate error vs success, but whether or not we consumed the session
     1
@@ -384,7 +496,8 @@ pub unsafe extern "C" fn raw_remove_session<F>(
         .expect("BUG: remove session callback missing");
     let session = SslSessionRef::from_ptr(session);
 
-    callback(ctx, session)
+    // there is no connection to report a panic to
+    crate::panic::catch_detached(|| callback(ctx, session));
 }
 
 cfg_if! {
@@ -414,14 +527,14 @@ where
         .expect("BUG: get session callback missing") as *const F;
     let data = slice::from_raw_parts(data as *const u8, len as usize);
 
-    match (*callback)(ssl, data) {
-        Some(session) => {
+    match catch(ssl.as_ptr(), || (*callback)(ssl, data)) {
+        Some(Some(session)) => {
             let p = session.as_ptr();
             mem::forget(session);
             *copy = 0;
             p
         }
-        None => ptr::null_mut(),
+        Some(None) | None => ptr::null_mut(),
     }
 }
 
@@ -438,7 +551,7 @@ where
     let line = CStr::from_ptr(line).to_bytes();
     let line = str::from_utf8_unchecked(line);
 
-    callback(ssl, line);
+    catch(ssl.as_ptr(), || callback(ssl, line));
 }
 
 #[cfg(ossl111)]
@@ -456,15 +569,16 @@ where
         .ex_data(SslContext::cached_ex_index::<F>())
         .expect("BUG: stateless cookie generate callback missing") as *const F;
     let slice = slice::from_raw_parts_mut(cookie as *mut u8, ffi::SSL_COOKIE_LENGTH as usize);
-    match (*callback)(ssl, slice) {
-        Ok(len) => {
+    match catch(ssl.as_ptr(), || (*callback)(ssl, slice)) {
+        Some(Ok(len)) => {
             *cookie_len = len as size_t;
             1
         }
-        Err(e) => {
+        Some(Err(e)) => {
             e.put();
             0
         }
+        None => 0,
     }
 }
 
@@ -483,7 +597,7 @@ where
         .ex_data(SslContext::cached_ex_index::<F>())
         .expect("BUG: stateless cookie verify callback missing") as *const F;
     let slice = slice::from_raw_parts(cookie as *const c_uchar as *const u8, cookie_len);
-    (*callback)(ssl, slice) as c_int
+    catch(ssl.as_ptr(), || (*callback)(ssl, slice) as c_int).unwrap_or(0)
 }
 
 #[cfg(not(boringssl))]
@@ -505,15 +619,16 @@ where
         // compatibility. See comments in dtls1.h.
         let slice =
             slice::from_raw_parts_mut(cookie as *mut u8, ffi::DTLS1_COOKIE_LENGTH as usize - 1);
-        match (*callback)(ssl, slice) {
-            Ok(len) => {
+        match catch(ssl.as_ptr(), || (*callback)(ssl, slice)) {
+            Some(Ok(len)) => {
                 *cookie_len = len as c_uint;
                 1
             }
-            Err(e) => {
+            Some(Err(e)) => {
                 e.put();
                 0
             }
+            None => 0,
         }
     }
 }
@@ -544,7 +659,7 @@ where
             .expect("BUG: cookie verify callback missing") as *const F;
         let slice =
             slice::from_raw_parts(cookie as *const c_uchar as *const u8, cookie_len as usize);
-        (*callback)(ssl, slice) as c_int
+        catch(ssl.as_ptr(), || (*callback)(ssl, slice) as c_int).unwrap_or(0)
     }
 }
 
@@ -582,7 +697,11 @@ where
         } else {
             None
         };
-        match (*callback)(ssl, ectx, cert) {
+        let r = match catch(ssl.as_ptr(), || (*callback)(ssl, ectx, cert)) {
+            Some(r) => r,
+            None => Err(SslAlert::INTERNAL_ERROR),
+        };
+        match r {
             Ok(None) => 0,
             Ok(Some(buf)) => {
                 *outlen = buf.as_ref().len();
@@ -660,7 +779,11 @@ where
         } else {
             None
         };
-        match (*callback)(ssl, ectx, slice, cert) {
+        let r = match catch(ssl.as_ptr(), || (*callback)(ssl, ectx, slice, cert)) {
+            Some(r) => r,
+            None => Err(SslAlert::INTERNAL_ERROR),
+        };
+        match r {
             Ok(()) => 1,
             Err(alert) => {
                 *al = alert.0;
@@ -670,6 +793,17 @@ where
     }
 }
 
+#[cfg(ossl111)]
+pub unsafe extern "C" fn raw_allow_early_data<F>(ssl: *mut ffi::SSL, arg: *mut c_void) -> c_int
+where
+    F: Fn(&mut SslRef) -> bool + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = arg as *const F;
+
+    catch(ssl.as_ptr(), || (*callback)(ssl) as c_int).unwrap_or(0)
+}
+
 #[cfg(ossl111)]
 pub unsafe extern "C" fn raw_client_hello<F>(
     ssl: *mut ffi::SSL,
@@ -686,13 +820,252 @@ where
     let callback = arg as *const F;
     let mut alert = SslAlert(*al);
 
-    let r = (*callback)(ssl, &mut alert);
+    let r = catch(ssl.as_ptr(), || (*callback)(ssl, &mut alert));
     *al = alert.0;
     match r {
-        Ok(c) => c.0,
-        Err(e) => {
+        Some(Ok(c)) => c.0,
+        Some(Err(e)) => {
             e.put();
             ffi::SSL_CLIENT_HELLO_ERROR
         }
+        None => {
+            *al = ffi::SSL_AD_INTERNAL_ERROR;
+            ffi::SSL_CLIENT_HELLO_ERROR
+        }
+    }
+}
+
+pub extern "C" fn raw_handshake_timing(
+    write_p: c_int,
+    _version: c_int,
+    content_type: c_int,
+    buf: *const c_void,
+    len: size_t,
+    ssl: *mut ffi::SSL,
+    _arg: *mut c_void,
+) {
+    unsafe {
+        if content_type != ffi::SSL3_RT_HANDSHAKE || len == 0 {
+            return;
+        }
+
+        let ssl = SslRef::from_ptr_mut(ssl);
+        let msg = slice::from_raw_parts(buf as *const u8, len);
+        let idx = Ssl::cached_ex_index::<HandshakeTimings>();
+        match ssl.ex_data_mut(idx) {
+            Some(timings) => timings.record(msg, write_p != 0),
+            None => {
+                let mut timings = HandshakeTimings::new();
+                timings.record(msg, write_p != 0);
+                ssl.set_ex_data(idx, timings);
+            }
+        }
+    }
+}
+
+#[cfg(boringssl)]
+fn private_key_method<M>(ssl: &SslRef) -> Arc<M>
+where
+    M: PrivateKeyMethod,
+{
+    match ssl.ex_data(Ssl::cached_ex_index::<Arc<M>>()) {
+        Some(method) => method.clone(),
+        None => ssl
+            .ssl_context()
+            .ex_data(SslContext::cached_ex_index::<Arc<M>>())
+            .expect("BUG: private key method missing")
+            .clone(),
+    }
+}
+
+#[cfg(boringssl)]
+fn private_key_result(
+    r: Result<usize, PrivateKeyMethodError>,
+    out_len: *mut size_t,
+) -> ffi::ssl_private_key_result_t {
+    match r {
+        Ok(len) => {
+            unsafe { *out_len = len };
+            ffi::ssl_private_key_result_t_ssl_private_key_success
+        }
+        Err(e) => e.0,
     }
 }
+
+#[cfg(boringssl)]
+pub extern "C" fn raw_private_key_sign<M>(
+    ssl: *mut ffi::SSL,
+    out: *mut u8,
+    out_len: *mut size_t,
+    max_out: size_t,
+    signature_algorithm: u16,
+    in_: *const u8,
+    in_len: size_t,
+) -> ffi::ssl_private_key_result_t
+where
+    M: PrivateKeyMethod,
+{
+    unsafe {
+        let ssl = SslRef::from_ptr_mut(ssl);
+        let method = private_key_method::<M>(ssl);
+        let input = slice::from_raw_parts(in_, in_len);
+        let output = slice::from_raw_parts_mut(out, max_out);
+
+        match catch(ssl.as_ptr(), || {
+            method.sign(ssl, input, signature_algorithm, output)
+        }) {
+            Some(r) => private_key_result(r, out_len),
+            None => ffi::ssl_private_key_result_t_ssl_private_key_failure,
+        }
+    }
+}
+
+#[cfg(boringssl)]
+pub extern "C" fn raw_private_key_decrypt<M>(
+    ssl: *mut ffi::SSL,
+    out: *mut u8,
+    out_len: *mut size_t,
+    max_out: size_t,
+    in_: *const u8,
+    in_len: size_t,
+) -> ffi::ssl_private_key_result_t
+where
+    M: PrivateKeyMethod,
+{
+    unsafe {
+        let ssl = SslRef::from_ptr_mut(ssl);
+        let method = private_key_method::<M>(ssl);
+        let input = slice::from_raw_parts(in_, in_len);
+        let output = slice::from_raw_parts_mut(out, max_out);
+
+        match catch(ssl.as_ptr(), || method.decrypt(ssl, input, output)) {
+            Some(r) => private_key_result(r, out_len),
+            None => ffi::ssl_private_key_result_t_ssl_private_key_failure,
+        }
+    }
+}
+
+#[cfg(boringssl)]
+pub extern "C" fn raw_private_key_complete<M>(
+    ssl: *mut ffi::SSL,
+    out: *mut u8,
+    out_len: *mut size_t,
+    max_out: size_t,
+) -> ffi::ssl_private_key_result_t
+where
+    M: PrivateKeyMethod,
+{
+    unsafe {
+        let ssl = SslRef::from_ptr_mut(ssl);
+        let method = private_key_method::<M>(ssl);
+        let output = slice::from_raw_parts_mut(out, max_out);
+
+        match catch(ssl.as_ptr(), || method.complete(ssl, output)) {
+            Some(r) => private_key_result(r, out_len),
+            None => ffi::ssl_private_key_result_t_ssl_private_key_failure,
+        }
+    }
+}
+
+/// The method table passed to BoringSSL, which must outlive every connection using it.
+#[cfg(boringssl)]
+pub struct PrivateKeyMethodTable<M>(PhantomData<M>);
+
+#[cfg(boringssl)]
+impl<M> PrivateKeyMethodTable<M>
+where
+    M: PrivateKeyMethod,
+{
+    pub const METHOD: ffi::SSL_PRIVATE_KEY_METHOD = ffi::SSL_PRIVATE_KEY_METHOD {
+        sign: Some(raw_private_key_sign::<M>),
+        decrypt: Some(raw_private_key_decrypt::<M>),
+        complete: Some(raw_private_key_complete::<M>),
+    };
+}
+
+#[cfg(boringssl)]
+pub extern "C" fn raw_cert_compress<F>(
+    ssl: *mut ffi::SSL,
+    out: *mut ffi::CBB,
+    in_: *const u8,
+    in_len: size_t,
+) -> c_int
+where
+    F: Fn(&mut SslRef, &[u8]) -> io::Result<Vec<u8>> + 'static + Sync + Send,
+{
+    unsafe {
+        let ssl = SslRef::from_ptr_mut(ssl);
+        let callback =
+            ssl.ssl_context()
+                .ex_data(SslContext::cached_ex_index::<F>())
+                .expect("BUG: certificate compression callback missing") as *const F;
+        let input = slice::from_raw_parts(in_, in_len);
+
+        match catch(ssl.as_ptr(), || (*callback)(ssl, input)) {
+            Some(Ok(compressed)) => ffi::CBB_add_bytes(out, compressed.as_ptr(), compressed.len()),
+            Some(Err(_)) | None => 0,
+        }
+    }
+}
+
+#[cfg(boringssl)]
+pub extern "C" fn raw_cert_decompress<F>(
+    ssl: *mut ffi::SSL,
+    out: *mut *mut ffi::CRYPTO_BUFFER,
+    uncompressed_len: size_t,
+    in_: *const u8,
+    in_len: size_t,
+) -> c_int
+where
+    F: Fn(&mut SslRef, &[u8], &mut [u8]) -> io::Result<()> + 'static + Sync + Send,
+{
+    unsafe {
+        let ssl = SslRef::from_ptr_mut(ssl);
+        let callback =
+            ssl.ssl_context()
+                .ex_data(SslContext::cached_ex_index::<F>())
+                .expect("BUG: certificate decompression callback missing") as *const F;
+        let input = slice::from_raw_parts(in_, in_len);
+
+        let mut data = ptr::null_mut();
+        let buffer = ffi::CRYPTO_BUFFER_alloc(&mut data, uncompressed_len);
+        if buffer.is_null() {
+            return 0;
+        }
+        let output = if uncompressed_len == 0 {
+            &mut []
+        } else {
+            slice::from_raw_parts_mut(data, uncompressed_len)
+        };
+
+        match catch(ssl.as_ptr(), || (*callback)(ssl, input, output)) {
+            Some(Ok(())) => {
+                *out = buffer;
+                1
+            }
+            Some(Err(_)) | None => {
+                ffi::CRYPTO_BUFFER_free(buffer);
+                0
+            }
+        }
+    }
+}
+
+#[cfg(ossl111)]
+pub unsafe extern "C" fn raw_dtls_timer<F>(ssl: *mut ffi::SSL, timer_us: c_uint) -> c_uint
+where
+    F: Fn(&mut SslRef, Duration) -> Duration + 'static + Sync + Send,
+{
+    let ssl = SslRef::from_ptr_mut(ssl);
+    let callback = ssl
+        .ex_data(Ssl::cached_ex_index::<Arc<F>>())
+        .expect("BUG: dtls timer callback missing")
+        .clone();
+
+    // fall back to OpenSSL's initial timeout of one second
+    let timeout = catch(ssl.as_ptr(), || {
+        callback(ssl, Duration::from_micros(u64::from(timer_us)))
+    })
+    .unwrap_or(Duration::from_secs(1));
+    cmp::min(timeout.as_micros(), u128::from(c_uint::MAX)) as c_uint
+}
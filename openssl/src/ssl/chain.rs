@@ -0,0 +1,121 @@
+use crate::error::ErrorStack;
+use crate::pkey::{PKey, Private};
+use crate::x509::{X509VerifyResult, X509};
+
+invalid_input_error! {
+    /// An error loading certificate chains with [`SslContextBuilder::set_certificate_chain_pem`].
+    ///
+    /// [`SslContextBuilder::set_certificate_chain_pem`]: crate::ssl::SslContextBuilder::set_certificate_chain_pem
+    pub struct CertificateChainError => "invalid certificate chain: {}";
+}
+
+/// A leaf certificate, its private key, and the rest of its chain in leaf to root order.
+pub(crate) struct Chain {
+    pub key: PKey<Private>,
+    pub leaf: X509,
+    pub chain: Vec<X509>,
+}
+
+/// Splits a PEM file into chains.
+///
+/// Each chain starts at a certificate matching one of the private keys in the file, and continues
+/// with the certificates following it up to the next such certificate. Every certificate in a
+/// chain must have been issued by the certificate before it.
+pub(crate) fn parse(pem: &[u8]) -> Result<Vec<Chain>, CertificateChainError> {
+    let certs = X509::stack_from_pem(pem)?;
+    let mut keys = private_keys_from_pem(pem)?
+        .into_iter()
+        .map(Some)
+        .collect::<Vec<_>>();
+
+    if certs.is_empty() {
+        return Err(CertificateChainError::invalid("no certificates found"));
+    }
+    if keys.is_empty() {
+        return Err(CertificateChainError::invalid("no private keys found"));
+    }
+
+    let mut chains: Vec<Chain> = vec![];
+    for (idx, cert) in certs.into_iter().enumerate() {
+        let public_key = cert.public_key()?;
+        let key = keys
+            .iter_mut()
+            .find(|key| matches!(key, Some(key) if key.public_eq(&public_key)))
+            .and_then(Option::take);
+
+        match (key, chains.last_mut()) {
+            (Some(key), _) => {
+                if chains.iter().any(|chain| chain.key.id() == key.id()) {
+                    return Err(CertificateChainError::invalid(format!(
+                        "certificate {} has the same key type as an earlier chain",
+                        idx
+                    )));
+                }
+                chains.push(Chain {
+                    key,
+                    leaf: cert,
+                    chain: vec![],
+                });
+            }
+            (None, Some(chain)) => {
+                let prev = chain.chain.last().unwrap_or(&chain.leaf);
+                if cert.issued(prev) != X509VerifyResult::OK {
+                    return Err(CertificateChainError::invalid(format!(
+                        "certificate {} did not issue the certificate before it",
+                        idx
+                    )));
+                }
+                chain.chain.push(cert);
+            }
+            (None, None) => {
+                return Err(CertificateChainError::invalid(
+                    "the first certificate does not match any private key, chains must start with the leaf",
+                ));
+            }
+        }
+    }
+
+    if keys.iter().any(Option::is_some) {
+        return Err(CertificateChainError::invalid(
+            "a private key does not match any certificate",
+        ));
+    }
+
+    Ok(chains)
+}
+
+/// Parses every private key in a PEM file, skipping other blocks.
+///
+/// OpenSSL 3 fails to decode rather than skipping when `PEM_read_bio_PrivateKey` encounters a
+/// block which is not a key, so the blocks are located here.
+fn private_keys_from_pem(pem: &[u8]) -> Result<Vec<PKey<Private>>, ErrorStack> {
+    const BEGIN: &[u8] = b"-----BEGIN ";
+    const END: &[u8] = b"-----END ";
+
+    let mut keys = vec![];
+    let mut rest = pem;
+    while let Some(start) = find(rest, BEGIN) {
+        let block = &rest[start..];
+        let end = match find(block, END) {
+            Some(end) => end + END.len(),
+            None => break,
+        };
+        let end = match find(&block[end..], b"-----") {
+            Some(len) => end + len + 5,
+            None => break,
+        };
+        let block = &block[..end];
+        let label = &block[BEGIN.len()..];
+        let label = &label[..find(label, b"-----").unwrap_or(0)];
+        if label.ends_with(b"PRIVATE KEY") {
+            keys.push(PKey::private_key_from_pem(block)?);
+        }
+        rest = &rest[start + end..];
+    }
+
+    Ok(keys)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
@@ -0,0 +1,224 @@
+use std::error;
+use std::fmt;
+
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::nid::Nid;
+use crate::ssl::SslRef;
+#[cfg(any(ossl111, libressl340))]
+use crate::ssl::SslVersion;
+
+const EXPORTER_LABEL: &str = "EXPORTER-Channel-Binding";
+const EXPORTER_LEN: usize = 32;
+
+/// A type of TLS channel binding.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChannelBinding {
+    /// `tls-unique`, the first Finished message of the most recent handshake, defined in
+    /// [RFC 5929].
+    ///
+    /// It is not defined for TLS 1.3. Without the extended master secret extension, it is
+    /// vulnerable to the triple handshake attack.
+    ///
+    /// [RFC 5929]: https://tools.ietf.org/html/rfc5929#section-3
+    TlsUnique,
+    /// `tls-server-end-point`, a hash of the server's certificate, defined in [RFC 5929].
+    ///
+    /// [RFC 5929]: https://tools.ietf.org/html/rfc5929#section-4
+    TlsServerEndPoint,
+    /// `tls-exporter`, keying material exported from the session, defined in [RFC 9266].
+    ///
+    /// It is defined for TLS 1.3, and for TLS 1.2 sessions using the extended master secret
+    /// extension.
+    ///
+    /// [RFC 9266]: https://tools.ietf.org/html/rfc9266
+    TlsExporter,
+}
+
+impl ChannelBinding {
+    /// Returns the registered name of the channel binding type, such as `tls-unique`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChannelBinding::TlsUnique => "tls-unique",
+            ChannelBinding::TlsServerEndPoint => "tls-server-end-point",
+            ChannelBinding::TlsExporter => "tls-exporter",
+        }
+    }
+}
+
+impl fmt::Display for ChannelBinding {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(self.name())
+    }
+}
+
+pub(crate) fn channel_binding(
+    ssl: &SslRef,
+    binding: ChannelBinding,
+) -> Result<Vec<u8>, ChannelBindingError> {
+    match binding {
+        ChannelBinding::TlsUnique => tls_unique(ssl),
+        ChannelBinding::TlsServerEndPoint => tls_server_end_point(ssl),
+        ChannelBinding::TlsExporter => tls_exporter(ssl),
+    }
+}
+
+#[cfg(any(ossl111, libressl340))]
+fn is_tls13(ssl: &SslRef) -> bool {
+    ssl.version2() == Some(SslVersion::TLS1_3)
+}
+
+#[cfg(not(any(ossl111, libressl340)))]
+fn is_tls13(_: &SslRef) -> bool {
+    false
+}
+
+fn tls_unique(ssl: &SslRef) -> Result<Vec<u8>, ChannelBindingError> {
+    if is_tls13(ssl) {
+        return Err(ChannelBindingError::unavailable(
+            "tls-unique is not defined for TLS 1.3",
+        ));
+    }
+
+    // the client sends the first Finished message of a full handshake, and the server that of
+    // an abbreviated one
+    let ours = ssl.is_server() == ssl.session_reused();
+    let finished = |buf: &mut [u8]| {
+        if ours {
+            ssl.finished(buf)
+        } else {
+            ssl.peer_finished(buf)
+        }
+    };
+
+    let len = finished(&mut []);
+    if len == 0 {
+        return Err(ChannelBindingError::unavailable(
+            "the handshake has not completed",
+        ));
+    }
+    let mut buf = vec![0; len];
+    finished(&mut buf);
+    Ok(buf)
+}
+
+fn tls_server_end_point(ssl: &SslRef) -> Result<Vec<u8>, ChannelBindingError> {
+    let cert = if ssl.is_server() {
+        ssl.certificate().map(|cert| cert.to_owned())
+    } else {
+        ssl.peer_certificate()
+    };
+    let cert = match cert {
+        Some(cert) => cert,
+        None => {
+            return Err(ChannelBindingError::unavailable(
+                "the server did not present a certificate",
+            ))
+        }
+    };
+
+    let digest = cert
+        .signature_algorithm()
+        .object()
+        .nid()
+        .signature_algorithms()
+        .map(|algs| algs.digest);
+    let digest = match digest {
+        Some(Nid::MD5) | Some(Nid::SHA1) => MessageDigest::sha256(),
+        Some(nid) => match MessageDigest::from_nid(nid) {
+            Some(digest) => digest,
+            None => {
+                return Err(ChannelBindingError::unavailable(
+                    "the certificate signature algorithm has no associated digest",
+                ))
+            }
+        },
+        None => {
+            return Err(ChannelBindingError::unavailable(
+                "the certificate signature algorithm is unknown",
+            ))
+        }
+    };
+
+    Ok(cert.digest(digest)?.to_vec())
+}
+
+fn tls_exporter(ssl: &SslRef) -> Result<Vec<u8>, ChannelBindingError> {
+    if !is_tls13(ssl) && !extms(ssl) {
+        return Err(ChannelBindingError::unavailable(
+            "tls-exporter requires TLS 1.3 or the extended master secret extension",
+        ));
+    }
+
+    let mut buf = vec![0; EXPORTER_LEN];
+    ssl.export_keying_material(&mut buf, EXPORTER_LABEL, None)?;
+    Ok(buf)
+}
+
+#[cfg(ossl110)]
+fn extms(ssl: &SslRef) -> bool {
+    ssl.extms_support() == Some(true)
+}
+
+#[cfg(not(ossl110))]
+fn extms(_: &SslRef) -> bool {
+    false
+}
+
+/// An error computing channel binding data with [`SslRef::channel_binding`].
+///
+/// [`SslRef::channel_binding`]: crate::ssl::SslRef::channel_binding
+pub struct ChannelBindingError(Inner);
+
+enum Inner {
+    Unavailable(&'static str),
+    Ssl(ErrorStack),
+}
+
+impl ChannelBindingError {
+    fn unavailable(msg: &'static str) -> ChannelBindingError {
+        ChannelBindingError(Inner::Unavailable(msg))
+    }
+
+    /// Returns the underlying OpenSSL error stack, if the error was reported by OpenSSL.
+    pub fn ssl_error(&self) -> Option<&ErrorStack> {
+        match self.0 {
+            Inner::Ssl(ref e) => Some(e),
+            Inner::Unavailable(_) => None,
+        }
+    }
+}
+
+impl fmt::Debug for ChannelBindingError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Inner::Unavailable(e) => fmt.debug_tuple("Unavailable").field(&e).finish(),
+            Inner::Ssl(ref e) => fmt.debug_tuple("Ssl").field(e).finish(),
+        }
+    }
+}
+
+impl fmt::Display for ChannelBindingError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Inner::Unavailable(e) => write!(fmt, "channel binding unavailable: {}", e),
+            Inner::Ssl(ref e) => fmt::Display::fmt(e, fmt),
+        }
+    }
+}
+
+impl error::Error for ChannelBindingError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self.0 {
+            Inner::Unavailable(_) => None,
+            Inner::Ssl(ref e) => Some(e),
+        }
+    }
+}
+
+impl From<ErrorStack> for ChannelBindingError {
+    fn from(e: ErrorStack) -> ChannelBindingError {
+        ChannelBindingError(Inner::Ssl(e))
+    }
+}
@@ -1,16 +1,21 @@
 use cfg_if::cfg_if;
-use std::io::{Read, Write};
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
 use std::ops::{Deref, DerefMut};
+use std::time::{Duration, Instant};
 
 use crate::dh::Dh;
 use crate::error::ErrorStack;
+use crate::ssl::pipeline::{self, VerifyPipeline};
 #[cfg(any(ossl111, libressl340))]
 use crate::ssl::SslVersion;
 use crate::ssl::{
-    HandshakeError, Ssl, SslContext, SslContextBuilder, SslContextRef, SslMethod, SslMode,
-    SslOptions, SslRef, SslStream, SslVerifyMode,
+    ConnectDeadlineError, HandshakeError, MidHandshakeSslStream, Ssl, SslContext,
+    SslContextBuilder, SslContextRef, SslMethod, SslMode, SslOptions, SslRef, SslStream,
+    SslVerifyMode,
 };
 use crate::version;
+use crate::x509::X509StoreContextRef;
 use std::net::IpAddr;
 
 const FFDHE_2048: &str = "
@@ -92,6 +97,28 @@ impl SslConnector {
         self.configure()?.connect(domain, stream)
     }
 
+    /// Initiates a client-side TLS session on a TCP stream, failing if the handshake does not
+    /// complete by `deadline`.
+    ///
+    /// The deadline covers the entire handshake, including any HelloRetryRequest round trips,
+    /// so a peer which stalls partway through cannot hold the connection open. Since it is an
+    /// absolute time, the same deadline can bound both the TCP connection attempts, such as those
+    /// raced against each other by a Happy Eyeballs implementation, and the handshake.
+    ///
+    /// The stream must be in blocking mode. Its read and write timeouts are lowered to the time
+    /// remaining before the deadline while the handshake runs, and are restored afterwards.
+    ///
+    /// The domain is used for SNI and hostname verification.
+    pub fn connect_with_deadline(
+        &self,
+        domain: &str,
+        stream: TcpStream,
+        deadline: Instant,
+    ) -> Result<SslStream<TcpStream>, ConnectDeadlineError> {
+        self.configure()?
+            .connect_with_deadline(domain, stream, deadline)
+    }
+
     /// Returns a structure allowing for configuration of a single TLS session before connection.
     pub fn configure(&self) -> Result<ConnectConfiguration, ErrorStack> {
         Ssl::new(&self.0).map(|ssl| ConnectConfiguration {
@@ -116,6 +143,24 @@ impl SslConnector {
 pub struct SslConnectorBuilder(SslContextBuilder);
 
 impl SslConnectorBuilder {
+    /// Installs a pipeline of checks run on the server's certificate chain once OpenSSL has
+    /// verified it.
+    ///
+    /// This replaces any verification callback configured on the builder, and sets the
+    /// verification mode to `SslVerifyMode::PEER`. Hostname verification configured through
+    /// `ConnectConfiguration` still applies.
+    pub fn set_verify_pipeline(&mut self, pipeline: VerifyPipeline) {
+        self.0.set_ex_data(
+            SslContext::cached_ex_index::<pipeline::Installed>(),
+            pipeline::Installed,
+        );
+        self.0
+            .set_verify_callback(SslVerifyMode::PEER, move |preverify_ok, x509_ctx| {
+                let preverify_ok = default_verify(preverify_ok, x509_ctx);
+                pipeline.verify_callback(preverify_ok, x509_ctx)
+            });
+    }
+
     /// Consumes the builder, returning an `SslConnector`.
     pub fn build(self) -> SslConnector {
         SslConnector(self.0.build())
@@ -188,6 +233,17 @@ impl ConnectConfiguration {
             setup_verify_hostname(&mut self.ssl, domain)?;
         }
 
+        // the domain is only needed by the steps of a verification pipeline
+        let has_pipeline = self
+            .ssl
+            .ssl_context()
+            .ex_data(SslContext::cached_ex_index::<pipeline::Installed>())
+            .is_some();
+        if has_pipeline {
+            let domain_idx = pipeline::try_get_domain_idx()?;
+            self.ssl.set_ex_data(*domain_idx, domain.to_string());
+        }
+
         Ok(self.ssl)
     }
 
@@ -200,6 +256,73 @@ impl ConnectConfiguration {
     {
         self.into_ssl(domain)?.connect(stream)
     }
+
+    /// Initiates a client-side TLS session on a TCP stream, failing if the handshake does not
+    /// complete by `deadline`.
+    ///
+    /// See [`SslConnector::connect_with_deadline`] for details.
+    pub fn connect_with_deadline(
+        self,
+        domain: &str,
+        stream: TcpStream,
+        deadline: Instant,
+    ) -> Result<SslStream<TcpStream>, ConnectDeadlineError> {
+        let ssl = self.into_ssl(domain)?;
+        let timeouts = (stream.read_timeout()?, stream.write_timeout()?);
+
+        set_remaining_timeouts(&stream, deadline)?;
+        let mut result = ssl.connect(stream);
+        loop {
+            let mid = match result {
+                Ok(stream) => {
+                    restore_timeouts(stream.get_ref(), timeouts)?;
+                    return Ok(stream);
+                }
+                // socket timeouts are reported as `WouldBlock` on Unix and `TimedOut` on Windows
+                Err(HandshakeError::WouldBlock(mid)) if Instant::now() < deadline => mid,
+                Err(HandshakeError::WouldBlock(mid)) => {
+                    let _ = restore_timeouts(mid.get_ref(), timeouts);
+                    return Err(ConnectDeadlineError::TimedOut(mid));
+                }
+                Err(HandshakeError::Failure(mid)) => {
+                    let _ = restore_timeouts(mid.get_ref(), timeouts);
+                    if is_timeout(&mid) {
+                        return Err(ConnectDeadlineError::TimedOut(mid));
+                    }
+                    return Err(ConnectDeadlineError::Handshake(HandshakeError::Failure(
+                        mid,
+                    )));
+                }
+                Err(e) => return Err(ConnectDeadlineError::Handshake(e)),
+            };
+
+            set_remaining_timeouts(mid.get_ref(), deadline)?;
+            result = mid.handshake();
+        }
+    }
+}
+
+fn set_remaining_timeouts(stream: &TcpStream, deadline: Instant) -> io::Result<()> {
+    // a zero timeout is rejected, so an expired deadline times out as soon as possible instead
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    let remaining = Duration::max(remaining, Duration::from_micros(1));
+    stream.set_read_timeout(Some(remaining))?;
+    stream.set_write_timeout(Some(remaining))
+}
+
+fn restore_timeouts(
+    stream: &TcpStream,
+    (read, write): (Option<Duration>, Option<Duration>),
+) -> io::Result<()> {
+    stream.set_read_timeout(read)?;
+    stream.set_write_timeout(write)
+}
+
+fn is_timeout(mid: &MidHandshakeSslStream<TcpStream>) -> bool {
+    match mid.error().io_error() {
+        Some(e) => e.kind() == io::ErrorKind::TimedOut,
+        None => false,
+    }
 }
 
 impl Deref for ConnectConfiguration {
@@ -390,6 +513,10 @@ cfg_if! {
             ctx.set_verify(SslVerifyMode::PEER);
         }
 
+        fn default_verify(preverify_ok: bool, _: &mut X509StoreContextRef) -> bool {
+            preverify_ok
+        }
+
         fn setup_verify_hostname(ssl: &mut SslRef, domain: &str) -> Result<(), ErrorStack> {
             use crate::x509::verify::X509CheckFlags;
 
@@ -405,6 +532,10 @@ cfg_if! {
             ctx.set_verify_callback(SslVerifyMode::PEER, verify::verify_callback);
         }
 
+        fn default_verify(preverify_ok: bool, x509_ctx: &mut X509StoreContextRef) -> bool {
+            verify::verify_callback(preverify_ok, x509_ctx)
+        }
+
         fn setup_verify_hostname(ssl: &mut Ssl, domain: &str) -> Result<(), ErrorStack> {
             let domain = domain.to_string();
             let hostname_idx = verify::try_get_hostname_idx()?;
@@ -3,6 +3,7 @@ use std::error;
 use std::error::Error as StdError;
 use std::fmt;
 use std::io;
+use std::net::TcpStream;
 
 use crate::error::ErrorStack;
 use crate::ssl::MidHandshakeSslStream;
@@ -38,6 +39,13 @@ impl ErrorCode {
     #[cfg(ossl111)]
     pub const WANT_CLIENT_HELLO_CB: ErrorCode = ErrorCode(ffi::SSL_ERROR_WANT_CLIENT_HELLO_CB);
 
+    /// A private key method indicated that its operation needed to be retried.
+    ///
+    /// Requires BoringSSL.
+    #[cfg(boringssl)]
+    pub const WANT_PRIVATE_KEY_OPERATION: ErrorCode =
+        ErrorCode(ffi::SSL_ERROR_WANT_PRIVATE_KEY_OPERATION);
+
     pub fn from_raw(raw: c_int) -> ErrorCode {
         ErrorCode(raw)
     }
@@ -86,6 +94,24 @@ impl Error {
             _ => None,
         }
     }
+
+    pub(crate) fn from_io(e: io::Error) -> Error {
+        Error {
+            code: ErrorCode::SYSCALL,
+            cause: Some(InnerError::Io(e)),
+        }
+    }
+
+    /// Returns `true` if the error was caused by the underlying stream timing out.
+    pub(crate) fn is_timeout(&self) -> bool {
+        match self.io_error() {
+            Some(e) => matches!(
+                e.kind(),
+                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+            ),
+            None => self.code == ErrorCode::WANT_READ,
+        }
+    }
 }
 
 impl From<ErrorStack> for Error {
@@ -183,3 +209,48 @@ impl<S> From<ErrorStack> for HandshakeError<S> {
         HandshakeError::SetupFailure(e)
     }
 }
+
+/// An error from [`SslConnector::connect_with_deadline`].
+///
+/// [`SslConnector::connect_with_deadline`]: crate::ssl::SslConnector::connect_with_deadline
+#[derive(Debug)]
+pub enum ConnectDeadlineError {
+    /// Reading or setting the timeouts of the stream failed.
+    Io(io::Error),
+    /// The handshake failed before the deadline.
+    Handshake(HandshakeError<TcpStream>),
+    /// The deadline passed before the handshake completed.
+    TimedOut(MidHandshakeSslStream<TcpStream>),
+}
+
+impl StdError for ConnectDeadlineError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            ConnectDeadlineError::Io(ref e) => Some(e),
+            ConnectDeadlineError::Handshake(ref e) => Some(e),
+            ConnectDeadlineError::TimedOut(ref s) => Some(s.error()),
+        }
+    }
+}
+
+impl fmt::Display for ConnectDeadlineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ConnectDeadlineError::Io(ref e) => write!(f, "failed to set stream timeouts: {}", e),
+            ConnectDeadlineError::Handshake(ref e) => fmt::Display::fmt(e, f),
+            ConnectDeadlineError::TimedOut(_) => f.write_str("the handshake timed out"),
+        }
+    }
+}
+
+impl From<ErrorStack> for ConnectDeadlineError {
+    fn from(e: ErrorStack) -> ConnectDeadlineError {
+        ConnectDeadlineError::Handshake(HandshakeError::SetupFailure(e))
+    }
+}
+
+impl From<io::Error> for ConnectDeadlineError {
+    fn from(e: io::Error) -> ConnectDeadlineError {
+        ConnectDeadlineError::Io(e)
+    }
+}
@@ -0,0 +1,250 @@
+use std::cmp;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// A stream wrapper which enforces deadlines and bandwidth caps on the stream underneath it.
+///
+/// A `LimitedStream` is intended to be placed underneath an [`SslStream`], where it limits the
+/// raw TLS records exchanged with the peer without changing the type of the underlying stream.
+/// This protects servers against peers which send or receive data very slowly to hold
+/// connections open, such as slow-loris attacks against the handshake.
+///
+/// Once the read or write deadline has passed, every read or write fails with an error of kind
+/// [`io::ErrorKind::TimedOut`], which the `SslStream` reports as an I/O error. The deadlines are
+/// checked before each operation on the underlying stream, so a peer which stops sending data
+/// entirely is only detected when the underlying stream returns. For blocking sockets, combine
+/// the deadlines with the socket's own timeouts, such as [`TcpStream::set_read_timeout`].
+///
+/// Bandwidth caps are enforced with a token bucket which holds up to one second's worth of
+/// bytes. When the bucket is empty the wrapper sleeps until enough bytes are available, so caps
+/// are intended for blocking streams. A sleep never extends past a deadline.
+///
+/// Deadlines and caps can be changed at any time through [`SslStream::get_mut`], for example to
+/// allow a short time for the handshake and a longer time for the rest of the connection.
+///
+/// [`SslStream`]: crate::ssl::SslStream
+/// [`SslStream::get_mut`]: crate::ssl::SslStream::get_mut
+/// [`TcpStream::set_read_timeout`]: std::net::TcpStream::set_read_timeout
+pub struct LimitedStream<S> {
+    stream: S,
+    read: Limit,
+    write: Limit,
+}
+
+impl<S> LimitedStream<S> {
+    /// Wraps a stream, initially without any deadlines or caps.
+    pub fn new(stream: S) -> LimitedStream<S> {
+        LimitedStream {
+            stream,
+            read: Limit::new(),
+            write: Limit::new(),
+        }
+    }
+
+    /// Sets the time after which reads from the underlying stream fail.
+    ///
+    /// Defaults to `None`, which never times out.
+    pub fn set_read_deadline(&mut self, deadline: Option<Instant>) {
+        self.read.deadline = deadline;
+    }
+
+    /// Returns the time after which reads from the underlying stream fail.
+    pub fn read_deadline(&self) -> Option<Instant> {
+        self.read.deadline
+    }
+
+    /// Sets the time after which writes to the underlying stream fail.
+    ///
+    /// Defaults to `None`, which never times out.
+    pub fn set_write_deadline(&mut self, deadline: Option<Instant>) {
+        self.write.deadline = deadline;
+    }
+
+    /// Returns the time after which writes to the underlying stream fail.
+    pub fn write_deadline(&self) -> Option<Instant> {
+        self.write.deadline
+    }
+
+    /// Sets the read deadline to `timeout` from now.
+    ///
+    /// `None` clears the deadline.
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) {
+        self.set_read_deadline(timeout.map(|timeout| Instant::now() + timeout));
+    }
+
+    /// Sets the write deadline to `timeout` from now.
+    ///
+    /// `None` clears the deadline.
+    pub fn set_write_timeout(&mut self, timeout: Option<Duration>) {
+        self.set_write_deadline(timeout.map(|timeout| Instant::now() + timeout));
+    }
+
+    /// Sets the maximum number of bytes read from the underlying stream per second.
+    ///
+    /// Defaults to `None`, which is unlimited.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is `Some(0)`.
+    pub fn set_read_rate(&mut self, rate: Option<u64>) {
+        self.read.set_rate(rate);
+    }
+
+    /// Returns the maximum number of bytes read from the underlying stream per second.
+    pub fn read_rate(&self) -> Option<u64> {
+        self.read.rate
+    }
+
+    /// Sets the maximum number of bytes written to the underlying stream per second.
+    ///
+    /// Defaults to `None`, which is unlimited.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is `Some(0)`.
+    pub fn set_write_rate(&mut self, rate: Option<u64>) {
+        self.write.set_rate(rate);
+    }
+
+    /// Returns the maximum number of bytes written to the underlying stream per second.
+    pub fn write_rate(&self) -> Option<u64> {
+        self.write.rate
+    }
+
+    /// Returns a shared reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Returns the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S> Read for LimitedStream<S>
+where
+    S: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.read.acquire(buf.len(), "read deadline exceeded")?;
+        let len = self.stream.read(&mut buf[..len])?;
+        self.read.consume(len);
+        Ok(len)
+    }
+}
+
+impl<S> Write for LimitedStream<S>
+where
+    S: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.write.acquire(buf.len(), "write deadline exceeded")?;
+        let len = self.stream.write(&buf[..len])?;
+        self.write.consume(len);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write
+            .check_deadline(Instant::now(), "write deadline exceeded")?;
+        self.stream.flush()
+    }
+}
+
+impl<S> fmt::Debug for LimitedStream<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("LimitedStream")
+            .field("stream", &self.stream)
+            .field("read_deadline", &self.read.deadline)
+            .field("write_deadline", &self.write.deadline)
+            .field("read_rate", &self.read.rate)
+            .field("write_rate", &self.write.rate)
+            .finish()
+    }
+}
+
+struct Limit {
+    deadline: Option<Instant>,
+    rate: Option<u64>,
+    tokens: u64,
+    refilled: Instant,
+}
+
+impl Limit {
+    fn new() -> Limit {
+        Limit {
+            deadline: None,
+            rate: None,
+            tokens: 0,
+            refilled: Instant::now(),
+        }
+    }
+
+    fn set_rate(&mut self, rate: Option<u64>) {
+        assert!(rate != Some(0), "rate must be positive");
+        self.rate = rate;
+        self.tokens = rate.unwrap_or(0);
+        self.refilled = Instant::now();
+    }
+
+    fn check_deadline(&self, now: Instant, msg: &'static str) -> io::Result<()> {
+        match self.deadline {
+            Some(deadline) if now >= deadline => Err(io::Error::new(io::ErrorKind::TimedOut, msg)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Waits until some of `len` bytes may be transferred, and returns how many.
+    fn acquire(&mut self, len: usize, msg: &'static str) -> io::Result<usize> {
+        let rate = match self.rate {
+            Some(rate) if len > 0 => rate,
+            _ => {
+                self.check_deadline(Instant::now(), msg)?;
+                return Ok(len);
+            }
+        };
+
+        loop {
+            let now = Instant::now();
+            self.check_deadline(now, msg)?;
+
+            let elapsed = now.duration_since(self.refilled).as_nanos();
+            let refill = elapsed * rate as u128 / NANOS_PER_SEC;
+            if refill > 0 {
+                self.tokens = cmp::min(rate as u128, self.tokens as u128 + refill) as u64;
+                self.refilled = now;
+            }
+            if self.tokens > 0 {
+                return Ok(cmp::min(len as u64, self.tokens) as usize);
+            }
+
+            // wait for at least 10ms worth of bytes to avoid tiny reads and writes
+            let wanted = cmp::min(len as u64, cmp::max(rate / 100, 1));
+            let nanos = wanted as u128 * NANOS_PER_SEC / rate as u128 + 1;
+            let mut wake = now + Duration::from_nanos(nanos as u64);
+            if let Some(deadline) = self.deadline {
+                wake = cmp::min(wake, deadline);
+            }
+            thread::sleep(wake.saturating_duration_since(now));
+        }
+    }
+
+    fn consume(&mut self, len: usize) {
+        if self.rate.is_some() {
+            self.tokens = self.tokens.saturating_sub(len as u64);
+        }
+    }
+}
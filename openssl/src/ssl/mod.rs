@@ -65,6 +65,8 @@ use crate::error::ErrorStack;
 use crate::ex_data::Index;
 #[cfg(ossl111)]
 use crate::hash::MessageDigest;
+#[cfg(ossl300)]
+use crate::lib_ctx::LibCtxRef;
 #[cfg(any(ossl110, libressl270))]
 use crate::nid::Nid;
 use crate::pkey::{HasPrivate, PKeyRef, Params, Private};
@@ -72,7 +74,10 @@ use crate::srtp::{SrtpProtectionProfile, SrtpProtectionProfileRef};
 use crate::ssl::bio::BioMethod;
 use crate::ssl::callbacks::*;
 use crate::ssl::error::InnerError;
-use crate::stack::{Stack, StackRef, Stackable};
+use crate::ssl::peer::PeerAddr;
+#[cfg(unix)]
+use crate::ssl::peer::PeerName;
+use crate::stack::{Iter, Stack, StackRef, Stackable};
 use crate::util::{ForeignTypeExt, ForeignTypeRefExt};
 use crate::x509::store::{X509Store, X509StoreBuilderRef, X509StoreRef};
 #[cfg(any(ossl102, libressl261))]
@@ -94,25 +99,63 @@ use std::io;
 use std::io::prelude::*;
 use std::marker::PhantomData;
 use std::mem::{self, ManuallyDrop};
+use std::net::{SocketAddr, TcpStream};
 use std::ops::{Deref, DerefMut};
-use std::panic::resume_unwind;
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
 use std::path::Path;
 use std::ptr;
 use std::slice;
 use std::str;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+#[cfg(ossl111)]
+pub use crate::ssl::authenticator::{AuthenticatorRequest, ExportedAuthenticatorError};
+#[cfg(ossl102)]
+pub use crate::ssl::chain::CertificateChainError;
+pub use crate::ssl::channel_binding::{ChannelBinding, ChannelBindingError};
 pub use crate::ssl::connector::{
     ConnectConfiguration, SslAcceptor, SslAcceptorBuilder, SslConnector, SslConnectorBuilder,
 };
-pub use crate::ssl::error::{Error, ErrorCode, HandshakeError};
+pub use crate::ssl::error::{ConnectDeadlineError, Error, ErrorCode, HandshakeError};
+pub use crate::ssl::limit::LimitedStream;
+pub use crate::ssl::overrides::SslOverrides;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use crate::ssl::peer::PeerCredentials;
+#[cfg(ossl102)]
+pub use crate::ssl::pipeline::HostnameCheck;
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+pub use crate::ssl::pipeline::SctPolicy;
+pub use crate::ssl::pipeline::{
+    RevocationCheck, SpkiPinning, VerifyContext, VerifyPipeline, VerifyStep,
+};
+#[cfg(boringssl)]
+pub use crate::ssl::private_key::{PrivateKeyMethod, PrivateKeyMethodError};
+pub use crate::ssl::stats::{handshake_failures, HandshakeFailures, SessionCacheStats};
+pub use crate::ssl::tee::{TeeDirection, TeeMode, TeeStream};
+pub use crate::ssl::timing::{HandshakeMessage, HandshakeTimings};
 
-mod bio;
+#[cfg(ossl111)]
+mod authenticator;
+pub(crate) mod bio;
 mod callbacks;
+#[cfg(ossl102)]
+mod chain;
+mod channel_binding;
 mod connector;
 mod error;
+mod limit;
+mod overrides;
+mod peer;
+mod pipeline;
+#[cfg(boringssl)]
+mod private_key;
+mod stats;
+mod tee;
 #[cfg(test)]
 mod test;
+mod timing;
 
 /// Returns the OpenSSL name of a cipher corresponding to an RFC-standard cipher name.
 ///
@@ -176,6 +219,14 @@ bitflags! {
         #[cfg(not(boringssl))]
         const NO_COMPRESSION = ffi::SSL_OP_NO_COMPRESSION as SslOptionsRepr;
 
+        /// Allow connecting to servers that do not support secure renegotiation.
+        ///
+        /// This is enabled by default before OpenSSL 3.0.0. Clearing it with
+        /// [`SslContextBuilder::clear_options`] makes clients reject servers which do not send the
+        /// renegotiation indication extension defined in RFC 5746.
+        #[cfg(not(boringssl))]
+        const LEGACY_SERVER_CONNECT = ffi::SSL_OP_LEGACY_SERVER_CONNECT as SslOptionsRepr;
+
         /// Allow legacy insecure renegotiation with servers or clients that do not support secure
         /// renegotiation.
         const ALLOW_UNSAFE_LEGACY_RENEGOTIATION =
@@ -250,6 +301,13 @@ bitflags! {
         #[cfg(any(ossl102, ossl110))]
         const NO_SSL_MASK = ffi::SSL_OP_NO_SSL_MASK as SslOptionsRepr;
 
+        /// Disables the extended master secret extension defined in RFC 7627.
+        ///
+        /// Requires OpenSSL 3.0.0 or newer.
+        #[cfg(ossl300)]
+        const NO_EXTENDED_MASTER_SECRET =
+            ffi::SSL_OP_NO_EXTENDED_MASTER_SECRET as SslOptionsRepr;
+
         /// Disallow all renegotiation in TLSv1.2 and earlier.
         ///
         /// Requires OpenSSL 1.1.0h or newer.
@@ -276,6 +334,18 @@ bitflags! {
         /// [`SslOptions::CIPHER_SERVER_PREFERENCE`]: struct.SslOptions.html#associatedconstant.CIPHER_SERVER_PREFERENCE
         #[cfg(ossl111)]
         const PRIORITIZE_CHACHA = ffi::SSL_OP_PRIORITIZE_CHACHA as SslOptionsRepr;
+
+        /// Disables OpenSSL's protection against replayed early data.
+        ///
+        /// By default, a server records the session tickets used to send early data in its session
+        /// cache, and rejects the early data of a replayed ClientHello whose ticket has already been
+        /// used. Servers of a deployment do not share their caches, so several of them can set this
+        /// option and check for replays against a shared cache with
+        /// [`SslContextBuilder::set_allow_early_data_callback`] instead.
+        ///
+        /// Requires OpenSSL 1.1.1 or newer.
+        #[cfg(ossl111)]
+        const NO_ANTI_REPLAY = ffi::SSL_OP_NO_ANTI_REPLAY as SslOptionsRepr;
     }
 }
 
@@ -340,6 +410,11 @@ impl SslMethod {
     }
 
     /// Support all versions of the DTLS protocol.
+    ///
+    /// DTLS connection IDs ([RFC 9146]) are not supported. They need support in the DTLS record
+    /// layer, which none of OpenSSL (as of 3.5), LibreSSL, or BoringSSL provide.
+    ///
+    /// [RFC 9146]: https://datatracker.ietf.org/doc/html/rfc9146
     #[corresponds(DTLS_method)]
     pub fn dtls() -> SslMethod {
         unsafe { SslMethod(DTLS_method()) }
@@ -521,6 +596,53 @@ impl StatusType {
     }
 }
 
+/// An operation selecting the current certificate of a context or connection.
+///
+/// A context holds one certificate, private key, and chain for each key type. Methods which
+/// configure a certificate's chain apply to the current certificate.
+#[cfg(ossl102)]
+#[derive(Copy, Clone)]
+pub struct SslCurrentCert(c_long);
+
+#[cfg(ossl102)]
+impl SslCurrentCert {
+    /// Selects the first certificate which has been set.
+    ///
+    /// This corresponds to `SSL_CERT_SET_FIRST`.
+    pub const FIRST: SslCurrentCert = SslCurrentCert(ffi::SSL_CERT_SET_FIRST);
+
+    /// Selects the next certificate after the current one.
+    ///
+    /// This corresponds to `SSL_CERT_SET_NEXT`.
+    pub const NEXT: SslCurrentCert = SslCurrentCert(ffi::SSL_CERT_SET_NEXT);
+
+    /// Selects the certificate used by the server in a handshake.
+    ///
+    /// Only valid for connections, after the certificate has been chosen.
+    ///
+    /// This corresponds to `SSL_CERT_SET_SERVER`.
+    pub const SERVER: SslCurrentCert = SslCurrentCert(ffi::SSL_CERT_SET_SERVER);
+
+    /// Constructs an `SslCurrentCert` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_long) -> SslCurrentCert {
+        SslCurrentCert(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_long {
+        self.0
+    }
+}
+
+#[cfg(ossl111)]
+fn u16_values(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_be_bytes([c[0], c[1]]))
+        .collect()
+}
+
 /// An identifier of a session name type.
 #[derive(Copy, Clone)]
 pub struct NameType(c_int);
@@ -546,7 +668,12 @@ static SSL_INDEXES: Lazy<Mutex<HashMap<TypeId, c_int>>> = Lazy::new(|| Mutex::ne
 static SESSION_CTX_INDEX: OnceCell<Index<Ssl, SslContext>> = OnceCell::new();
 
 fn try_get_session_ctx_index() -> Result<&'static Index<Ssl, SslContext>, ErrorStack> {
-    SESSION_CTX_INDEX.get_or_try_init(Ssl::new_ex_index)
+    // the session context is carried over by `SslRef::dup`
+    #[cfg(ossl110)]
+    let new_index = Ssl::new_cloneable_ex_index;
+    #[cfg(not(ossl110))]
+    let new_index = Ssl::new_ex_index;
+    SESSION_CTX_INDEX.get_or_try_init(new_index)
 }
 
 unsafe extern "C" fn free_data_box<T>(
@@ -562,6 +689,10 @@ unsafe extern "C" fn free_data_box<T>(
     }
 }
 
+// application state attached with `SslRef::set_app_data`, wrapped so that it is kept apart from
+// the crate's own extra data of the same type
+struct AppData<T>(T);
+
 /// An error returned from the SNI callback.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct SniError(c_int);
@@ -585,6 +716,7 @@ impl SslAlert {
     pub const UNRECOGNIZED_NAME: SslAlert = SslAlert(ffi::SSL_AD_UNRECOGNIZED_NAME);
     pub const ILLEGAL_PARAMETER: SslAlert = SslAlert(ffi::SSL_AD_ILLEGAL_PARAMETER);
     pub const DECODE_ERROR: SslAlert = SslAlert(ffi::SSL_AD_DECODE_ERROR);
+    pub const INTERNAL_ERROR: SslAlert = SslAlert(ffi::SSL_AD_INTERNAL_ERROR);
 }
 
 /// An error returned from an ALPN selection callback.
@@ -657,6 +789,38 @@ impl SslVersion {
     pub const DTLS1_2: SslVersion = SslVersion(ffi::DTLS1_2_VERSION);
 }
 
+/// A certificate compression algorithm, as registered for [RFC 8879].
+///
+/// Requires BoringSSL.
+///
+/// [RFC 8879]: https://tools.ietf.org/html/rfc8879
+#[cfg(boringssl)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CertificateCompressionAlgorithm(u16);
+
+#[cfg(boringssl)]
+impl CertificateCompressionAlgorithm {
+    /// zlib, as defined in RFC 1950.
+    pub const ZLIB: CertificateCompressionAlgorithm = CertificateCompressionAlgorithm(1);
+
+    /// Brotli, as defined in RFC 7932.
+    pub const BROTLI: CertificateCompressionAlgorithm = CertificateCompressionAlgorithm(2);
+
+    /// Zstandard, as defined in RFC 8478.
+    pub const ZSTD: CertificateCompressionAlgorithm = CertificateCompressionAlgorithm(3);
+
+    /// Constructs a `CertificateCompressionAlgorithm` from its registered identifier.
+    pub fn from_raw(raw: u16) -> CertificateCompressionAlgorithm {
+        CertificateCompressionAlgorithm(raw)
+    }
+
+    /// Returns the registered identifier of the algorithm.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> u16 {
+        self.0
+    }
+}
+
 cfg_if! {
     if #[cfg(boringssl)] {
         type SslCacheTy = i64;
@@ -705,6 +869,15 @@ pub fn select_next_proto<'a>(server: &[u8], client: &'a [u8]) -> Option<&'a [u8]
 /// A builder for `SslContext`s.
 pub struct SslContextBuilder(SslContext);
 
+// The private key is never printed, only whether one has been configured.
+impl fmt::Debug for SslContextBuilder {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("SslContextBuilder")
+            .field("private_key", &self.0.private_key().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
 impl SslContextBuilder {
     /// Creates a new `SslContextBuilder`.
     #[corresponds(SSL_CTX_new)]
@@ -717,6 +890,35 @@ impl SslContextBuilder {
         }
     }
 
+    /// Creates a new `SslContextBuilder` which fetches the digests, ciphers, and other algorithms
+    /// used by its connections from `ctx` according to the property query string `properties`.
+    ///
+    /// If `ctx` is `None`, the default library context is used. This allows the TLS stack of a
+    /// process using several providers to be directed to one of them, for example with
+    /// `"fips=yes"`.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    #[corresponds(SSL_CTX_new_ex)]
+    #[cfg(ossl300)]
+    pub fn new_ex(
+        ctx: Option<&LibCtxRef>,
+        properties: Option<&str>,
+        method: SslMethod,
+    ) -> Result<SslContextBuilder, ErrorStack> {
+        let properties = properties.map(|s| CString::new(s).unwrap());
+
+        unsafe {
+            init();
+            let ctx = cvt_p(ffi::SSL_CTX_new_ex(
+                ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+                properties.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+                method.as_ptr(),
+            ))?;
+
+            Ok(SslContextBuilder::from_ptr(ctx))
+        }
+    }
+
     /// Creates an `SslContextBuilder` from a pointer to a raw OpenSSL value.
     ///
     /// # Safety
@@ -787,6 +989,32 @@ impl SslContextBuilder {
         }
     }
 
+    /// Configures a server name indication (SNI) callback which returns per-connection overrides.
+    ///
+    /// This is like [`set_servername_callback`], but rather than switching to another context
+    /// with `set_ssl_context`, the callback can return the [`SslOverrides`] for the requested
+    /// server name, which are then applied to the connection. If applying them fails, the
+    /// handshake is aborted with an `internal_error` alert.
+    ///
+    /// [`set_servername_callback`]: SslContextBuilder::set_servername_callback
+    pub fn set_servername_overrides_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, &mut SslAlert) -> Result<Option<Arc<SslOverrides>>, SniError>
+            + 'static
+            + Sync
+            + Send,
+    {
+        self.set_servername_callback(move |ssl, alert| {
+            if let Some(overrides) = callback(ssl, alert)? {
+                if ssl.apply_overrides(&overrides).is_err() {
+                    *alert = SslAlert::INTERNAL_ERROR;
+                    return Err(SniError::ALERT_FATAL);
+                }
+            }
+            Ok(())
+        })
+    }
+
     /// Sets the certificate verification depth.
     ///
     /// If the peer's certificate chain is longer than this value, verification will fail.
@@ -1025,6 +1253,115 @@ impl SslContextBuilder {
         }
     }
 
+    /// Loads certificate chains and their private keys from an in-memory PEM file.
+    ///
+    /// Each chain consists of a leaf certificate followed by its intermediates in order, each
+    /// certificate having been issued by the one before it. The private key of each leaf must also
+    /// be present, anywhere in the file. A file may contain several chains with different key
+    /// types, for example one RSA and one ECDSA chain, and OpenSSL will pick the one matching the
+    /// peer's capabilities. Each chain replaces any chain previously configured for its key type.
+    ///
+    /// The file is validated before the context is modified.
+    ///
+    /// On LibreSSL, which does not support per-certificate chains, the chains are loaded with
+    /// `SSL_CTX_use_certificate_chain_mem`.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer, or LibreSSL.
+    #[corresponds(SSL_CTX_add0_chain_cert)]
+    #[cfg(any(ossl102, libressl))]
+    pub fn set_certificate_chain_pem(&mut self, pem: &[u8]) -> Result<(), CertificateChainError> {
+        for chain in chain::parse(pem)? {
+            cfg_if! {
+                if #[cfg(libressl)] {
+                    let mut buf = chain.leaf.to_pem()?;
+                    for cert in &chain.chain {
+                        buf.extend_from_slice(&cert.to_pem()?);
+                    }
+                    unsafe {
+                        cvt(ffi::SSL_CTX_use_certificate_chain_mem(
+                            self.as_ptr(),
+                            buf.as_mut_ptr() as *mut c_void,
+                            buf.len() as c_int,
+                        ))?;
+                    }
+                    self.set_private_key(&chain.key)?;
+                } else {
+                    self.set_certificate(&chain.leaf)?;
+                    self.set_private_key(&chain.key)?;
+                    unsafe {
+                        cvt(ffi::SSL_CTX_clear_chain_certs(self.as_ptr()) as c_int)?;
+                        for cert in chain.chain {
+                            cvt(ffi::SSL_CTX_add0_chain_cert(self.as_ptr(), cert.as_ptr()) as c_int)?;
+                            mem::forget(cert);
+                        }
+                    }
+                }
+            }
+            self.check_private_key()?;
+        }
+        Ok(())
+    }
+
+    /// Records the timing of the handshake messages of connections created from this context.
+    ///
+    /// The timings are available from [`SslRef::handshake_timings`]. This replaces any message
+    /// callback set on the context.
+    #[corresponds(SSL_CTX_set_msg_callback)]
+    pub fn enable_handshake_timing(&mut self) {
+        unsafe {
+            ffi::SSL_CTX_set_msg_callback(self.as_ptr(), Some(callbacks::raw_handshake_timing));
+        }
+    }
+
+    /// Makes `cert` the current certificate.
+    ///
+    /// `cert` must have been previously set with [`set_certificate`] or a similar method.
+    ///
+    /// [`set_certificate`]: SslContextBuilder::set_certificate
+    #[corresponds(SSL_CTX_select_current_cert)]
+    #[cfg(ossl102)]
+    pub fn select_current_cert(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_select_current_cert(self.as_ptr(), cert.as_ptr()) as c_int).map(|_| ())
+        }
+    }
+
+    /// Changes the current certificate.
+    ///
+    /// An error is returned if there is no certificate to select. Iterating with
+    /// [`SslCurrentCert::FIRST`] followed by [`SslCurrentCert::NEXT`] until an error is returned
+    /// visits each certificate in turn.
+    #[corresponds(SSL_CTX_set_current_cert)]
+    #[cfg(ossl102)]
+    pub fn set_current_cert(&mut self, op: SslCurrentCert) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_set_current_cert(self.as_ptr(), op.as_raw()) as c_int).map(|_| ())
+        }
+    }
+
+    /// Appends a certificate to the chain of the current certificate.
+    ///
+    /// Unlike [`add_extra_chain_cert`], which adds to a chain shared by every certificate, each
+    /// certificate of the context has its own chain.
+    ///
+    /// [`add_extra_chain_cert`]: SslContextBuilder::add_extra_chain_cert
+    #[corresponds(SSL_CTX_add0_chain_cert)]
+    #[cfg(ossl102)]
+    pub fn add_chain_cert(&mut self, cert: X509) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_CTX_add0_chain_cert(self.as_ptr(), cert.as_ptr()) as c_int)?;
+            mem::forget(cert);
+            Ok(())
+        }
+    }
+
+    /// Clears the chain of the current certificate.
+    #[corresponds(SSL_CTX_clear_chain_certs)]
+    #[cfg(ossl102)]
+    pub fn clear_chain_certs(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_CTX_clear_chain_certs(self.as_ptr()) as c_int).map(|_| ()) }
+    }
+
     /// Loads the private key from a file.
     #[corresponds(SSL_CTX_use_PrivateKey_file)]
     pub fn set_private_key_file<P: AsRef<Path>>(
@@ -1225,7 +1562,7 @@ impl SslContextBuilder {
             if r == 0 {
                 Ok(())
             } else {
-                Err(ErrorStack::get())
+                Err(ErrorStack::report())
             }
         }
     }
@@ -1241,7 +1578,7 @@ impl SslContextBuilder {
             if r == 0 {
                 Ok(())
             } else {
-                Err(ErrorStack::get())
+                Err(ErrorStack::report())
             }
         }
     }
@@ -1275,6 +1612,10 @@ impl SslContextBuilder {
     }
 
     /// Checks for consistency between the private key and certificate.
+    ///
+    /// On a mismatch, the error stack identifies whether the key is of the wrong type
+    /// (`X509_R_KEY_TYPE_MISMATCH`) or does not correspond to the certificate's public key
+    /// (`X509_R_KEY_VALUES_MISMATCH`). An error is also returned if no certificate or key is set.
     #[corresponds(SSL_CTX_check_private_key)]
     pub fn check_private_key(&self) -> Result<(), ErrorStack> {
         unsafe { cvt(ffi::SSL_CTX_check_private_key(self.as_ptr())).map(|_| ()) }
@@ -1621,7 +1962,7 @@ impl SslContextBuilder {
         if ret == 1 {
             Ok(())
         } else {
-            Err(ErrorStack::get())
+            Err(ErrorStack::report())
         }
     }
 
@@ -1636,10 +1977,123 @@ impl SslContextBuilder {
         if unsafe { ffi::SSL_CTX_set_max_early_data(self.as_ptr(), bytes) } == 1 {
             Ok(())
         } else {
-            Err(ErrorStack::get())
+            Err(ErrorStack::report())
+        }
+    }
+
+    /// Sets the maximum amount of early data that the server will read on incoming connections.
+    ///
+    /// This is the limit that is enforced, while the limit set by [`set_max_early_data`] is the one
+    /// advertised in session tickets. Lowering it allows the advertised limit to be reduced without
+    /// rejecting connections which resume sessions issued with the old one. Defaults to 16384.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// [`set_max_early_data`]: SslContextBuilder::set_max_early_data
+    #[corresponds(SSL_CTX_set_recv_max_early_data)]
+    #[cfg(ossl111)]
+    pub fn set_recv_max_early_data(&mut self, bytes: u32) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_CTX_set_recv_max_early_data(self.as_ptr(), bytes)).map(|_| ()) }
+    }
+
+    /// Sets a callback which decides whether a server accepts the early data of a connection.
+    ///
+    /// The callback is invoked once OpenSSL itself has decided to accept the early data of a
+    /// resumed connection, and the early data is rejected if it returns `false`. Together with
+    /// [`SslOptions::NO_ANTI_REPLAY`], this allows a replay cache shared between several servers to
+    /// be used in place of OpenSSL's own. A replayed ClientHello has the same random as the
+    /// original, which can be retrieved with [`SslRef::client_random`].
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_set_allow_early_data_cb)]
+    #[cfg(ossl111)]
+    pub fn set_allow_early_data_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef) -> bool + 'static + Sync + Send,
+    {
+        unsafe {
+            let ptr = self.set_ex_data_inner(SslContext::cached_ex_index::<F>(), callback);
+            ffi::SSL_CTX_set_allow_early_data_cb(
+                self.as_ptr(),
+                Some(callbacks::raw_allow_early_data::<F>),
+                ptr,
+            );
+        }
+    }
+
+    /// Sets the maximum number of records that will be encrypted or decrypted in parallel.
+    ///
+    /// Values greater than 1 only have an effect when the negotiated cipher supports pipelining,
+    /// which is typically only the case for ciphers provided by hardware accelerator engines. Must
+    /// be between 1 and 32. Defaults to 1.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CTX_set_max_pipelines)]
+    #[cfg(ossl110)]
+    pub fn set_max_pipelines(&mut self, pipelines: usize) -> Result<(), ErrorStack> {
+        if unsafe { ffi::SSL_CTX_set_max_pipelines(self.as_ptr(), pipelines as c_long) } == 1 {
+            Ok(())
+        } else {
+            Err(ErrorStack::report())
         }
     }
 
+    /// Sets the size of the fragments that outgoing data is split into when pipelining.
+    ///
+    /// Must be between 512 and the maximum send fragment size. Defaults to the maximum send
+    /// fragment size.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CTX_set_split_send_fragment)]
+    #[cfg(ossl110)]
+    pub fn set_split_send_fragment(&mut self, size: usize) -> Result<(), ErrorStack> {
+        if unsafe { ffi::SSL_CTX_set_split_send_fragment(self.as_ptr(), size as c_long) } == 1 {
+            Ok(())
+        } else {
+            Err(ErrorStack::report())
+        }
+    }
+
+    /// Sets the maximum size of the plaintext in each outgoing record.
+    ///
+    /// Must be between 512 and 16384. Defaults to 16384.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CTX_set_max_send_fragment)]
+    #[cfg(ossl110)]
+    pub fn set_max_send_fragment(&mut self, size: usize) -> Result<(), ErrorStack> {
+        if unsafe { ffi::SSL_CTX_set_max_send_fragment(self.as_ptr(), size as c_long) } == 1 {
+            Ok(())
+        } else {
+            Err(ErrorStack::report())
+        }
+    }
+
+    /// Sets the initial size of the read buffer used by new connections.
+    ///
+    /// When pipelining, a larger read buffer allows several records to be read and decrypted at
+    /// once. Read ahead must also be enabled for this to have an effect.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CTX_set_default_read_buffer_len)]
+    #[cfg(ossl110)]
+    pub fn set_default_read_buffer_len(&mut self, len: usize) {
+        unsafe { ffi::SSL_CTX_set_default_read_buffer_len(self.as_ptr(), len) }
+    }
+
+    /// Enables or disables quiet shutdown.
+    ///
+    /// When quiet shutdown is enabled, [`SslStream::shutdown`] marks the session as shut down in
+    /// both directions without sending a close notify message to the peer. This does not conform
+    /// to the TLS specification, but can be useful for interoperating with peers which do not
+    /// handle close notify messages.
+    ///
+    /// Defaults to `false`.
+    #[corresponds(SSL_CTX_set_quiet_shutdown)]
+    pub fn set_quiet_shutdown(&mut self, quiet: bool) {
+        unsafe { ffi::SSL_CTX_set_quiet_shutdown(self.as_ptr(), quiet as c_int) }
+    }
+
     /// Sets a callback which will be invoked just after the client's hello message is received.
     ///
     /// Requires OpenSSL 1.1.1 or newer.
@@ -1662,6 +2116,93 @@ impl SslContextBuilder {
         }
     }
 
+    /// Sets a method which performs the private key operations of the handshake.
+    ///
+    /// This is used in place of a private key, allowing it to be held in a hardware module or
+    /// remote key management service. See [`PrivateKeyMethod`] for details.
+    ///
+    /// Requires BoringSSL.
+    #[corresponds(SSL_CTX_set_private_key_method)]
+    #[cfg(boringssl)]
+    pub fn set_private_key_method<M>(&mut self, method: M)
+    where
+        M: PrivateKeyMethod,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<Arc<M>>(), Arc::new(method));
+            ffi::SSL_CTX_set_private_key_method(
+                self.as_ptr(),
+                &callbacks::PrivateKeyMethodTable::<M>::METHOD,
+            );
+        }
+    }
+
+    /// Enables or disables randomly permuting the order of extensions in the ClientHello.
+    ///
+    /// Permuting the extensions prevents servers from relying on their order, which keeps the
+    /// ecosystem free to change it. It is disabled by default.
+    ///
+    /// Requires BoringSSL.
+    #[corresponds(SSL_CTX_set_permute_extensions)]
+    #[cfg(boringssl)]
+    pub fn set_permute_extensions(&mut self, enabled: bool) {
+        unsafe { ffi::SSL_CTX_set_permute_extensions(self.as_ptr(), enabled as c_int) }
+    }
+
+    /// Registers a certificate compression algorithm, as defined in [RFC 8879].
+    ///
+    /// BoringSSL does not implement any compression algorithms itself, so the application
+    /// supplies them. `compress` compresses an encoded Certificate message when sending it;
+    /// `decompress` decompresses a received message into a buffer whose length is the uncompressed
+    /// length sent by the peer, and must fill it exactly. Either callback may return an error to
+    /// abort the handshake.
+    ///
+    /// Algorithms are offered to the peer in the order they are registered. An error is returned
+    /// if `algorithm` has already been registered.
+    ///
+    /// Requires BoringSSL.
+    ///
+    /// [RFC 8879]: https://tools.ietf.org/html/rfc8879
+    #[corresponds(SSL_CTX_add_cert_compression_alg)]
+    #[cfg(boringssl)]
+    pub fn add_certificate_compression_algorithm<C, D>(
+        &mut self,
+        algorithm: CertificateCompressionAlgorithm,
+        compress: C,
+        decompress: D,
+    ) -> Result<(), ErrorStack>
+    where
+        C: Fn(&mut SslRef, &[u8]) -> io::Result<Vec<u8>> + 'static + Sync + Send,
+        D: Fn(&mut SslRef, &[u8], &mut [u8]) -> io::Result<()> + 'static + Sync + Send,
+    {
+        unsafe {
+            self.set_ex_data(SslContext::cached_ex_index::<C>(), compress);
+            self.set_ex_data(SslContext::cached_ex_index::<D>(), decompress);
+            cvt(ffi::SSL_CTX_add_cert_compression_alg(
+                self.as_ptr(),
+                algorithm.as_raw(),
+                Some(raw_cert_compress::<C>),
+                Some(raw_cert_decompress::<D>),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Enables or disables sending GREASE values in the ClientHello.
+    ///
+    /// GREASE, described in [RFC 8701], reserves values for cipher suites, extensions, groups and
+    /// other lists which are sent to detect servers that fail on unknown values. It is disabled by
+    /// default.
+    ///
+    /// Requires BoringSSL.
+    ///
+    /// [RFC 8701]: https://tools.ietf.org/html/rfc8701
+    #[corresponds(SSL_CTX_set_grease_enabled)]
+    #[cfg(boringssl)]
+    pub fn set_grease_enabled(&mut self, enabled: bool) {
+        unsafe { ffi::SSL_CTX_set_grease_enabled(self.as_ptr(), enabled as c_int) }
+    }
+
     /// Sets the context's session cache size limit, returning the previous limit.
     ///
     /// A value of 0 means that the cache size is unbounded.
@@ -1722,6 +2263,13 @@ foreign_type_and_impl_send_sync! {
     ///
     /// Applications commonly configure a single `SslContext` that is shared by all of its
     /// `SslStreams`.
+    ///
+    /// Cloning an `SslContext` only increments a reference count, so clones are cheap and refer
+    /// to the same underlying context, including its extra data and session cache. State which
+    /// should differ between connections belongs in the extra data of each [`Ssl`] instead.
+    ///
+    /// A context built before forking can be used by each worker process. OpenSSL 1.1.1 and
+    /// newer automatically reseed their random number generators in the child process.
     pub struct SslContext;
 
     /// Reference to [`SslContext`]
@@ -1836,6 +2384,17 @@ impl SslContextRef {
         }
     }
 
+    /// Returns the chain of the current certificate, if one has been set.
+    #[corresponds(SSL_CTX_get0_chain_certs)]
+    #[cfg(ossl102)]
+    pub fn chain_certs(&self) -> Option<&StackRef<X509>> {
+        unsafe {
+            let mut chain = ptr::null_mut();
+            ffi::SSL_CTX_get0_chain_certs(self.as_ptr(), &mut chain);
+            StackRef::from_const_ptr_opt(chain)
+        }
+    }
+
     /// Returns a reference to the extra data at the specified index.
     #[corresponds(SSL_CTX_get_ex_data)]
     pub fn ex_data<T>(&self, index: Index<SslContext, T>) -> Option<&T> {
@@ -1858,6 +2417,15 @@ impl SslContextRef {
         unsafe { ffi::SSL_CTX_get_max_early_data(self.as_ptr()) }
     }
 
+    /// Gets the maximum amount of early data that the server will read on incoming connections.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_get_recv_max_early_data)]
+    #[cfg(ossl111)]
+    pub fn recv_max_early_data(&self) -> u32 {
+        unsafe { ffi::SSL_CTX_get_recv_max_early_data(self.as_ptr()) }
+    }
+
     /// Adds a session to the context's cache.
     ///
     /// Returns `true` if the session was successfully added to the cache, and `false` if it was already present.
@@ -1893,6 +2461,28 @@ impl SslContextRef {
         unsafe { ffi::SSL_CTX_sess_get_cache_size(self.as_ptr()) as i64 }
     }
 
+    /// Returns the statistics of the context's session cache.
+    #[corresponds(SSL_CTX_sess_number)]
+    pub fn session_cache_stats(&self) -> SessionCacheStats {
+        unsafe {
+            let ctx = self.as_ptr();
+            SessionCacheStats {
+                sessions: ffi::SSL_CTX_sess_number(ctx) as u64,
+                connects: ffi::SSL_CTX_sess_connect(ctx) as u64,
+                connects_good: ffi::SSL_CTX_sess_connect_good(ctx) as u64,
+                connects_renegotiate: ffi::SSL_CTX_sess_connect_renegotiate(ctx) as u64,
+                accepts: ffi::SSL_CTX_sess_accept(ctx) as u64,
+                accepts_good: ffi::SSL_CTX_sess_accept_good(ctx) as u64,
+                accepts_renegotiate: ffi::SSL_CTX_sess_accept_renegotiate(ctx) as u64,
+                hits: ffi::SSL_CTX_sess_hits(ctx) as u64,
+                callback_hits: ffi::SSL_CTX_sess_cb_hits(ctx) as u64,
+                misses: ffi::SSL_CTX_sess_misses(ctx) as u64,
+                timeouts: ffi::SSL_CTX_sess_timeouts(ctx) as u64,
+                cache_full: ffi::SSL_CTX_sess_cache_full(ctx) as u64,
+            }
+        }
+    }
+
     /// Returns the verify mode that was set on this context from [`SslContextBuilder::set_verify`].
     ///
     /// [`SslContextBuilder::set_verify`]: struct.SslContextBuilder.html#method.set_verify
@@ -1902,6 +2492,21 @@ impl SslContextRef {
         SslVerifyMode::from_bits(mode).expect("SSL_CTX_get_verify_mode returned invalid mode")
     }
 
+    /// Returns the list of CA names sent to clients when requesting a client certificate.
+    #[corresponds(SSL_CTX_get_client_CA_list)]
+    pub fn client_ca_list(&self) -> Option<&StackRef<X509Name>> {
+        unsafe {
+            let list = ffi::SSL_CTX_get_client_CA_list(self.as_ptr());
+            StackRef::from_const_ptr_opt(list)
+        }
+    }
+
+    /// Returns whether quiet shutdown is enabled.
+    #[corresponds(SSL_CTX_get_quiet_shutdown)]
+    pub fn quiet_shutdown(&self) -> bool {
+        unsafe { ffi::SSL_CTX_get_quiet_shutdown(self.as_ptr()) != 0 }
+    }
+
     /// Gets the number of TLS 1.3 session tickets that will be sent to a client after a full
     /// handshake.
     ///
@@ -2058,21 +2663,188 @@ impl SslCipherRef {
             Some(Nid::from_raw(n))
         }
     }
-}
-
-impl fmt::Debug for SslCipherRef {
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(fmt, "{}", self.name())
-    }
-}
 
-/// A stack of selected ciphers, and a stack of selected signalling cipher suites
-#[derive(Debug)]
+    /// Returns the NID of the MAC digest used by the cipher.
+    ///
+    /// Returns `None` for AEAD ciphers, which do not use a separate MAC.
+    ///
+    /// Requires OpenSSL 1.1.0 or LibreSSL 2.7.0 or newer.
+    #[corresponds(SSL_CIPHER_get_digest_nid)]
+    #[cfg(any(ossl110, libressl270))]
+    pub fn digest_nid(&self) -> Option<Nid> {
+        let n = unsafe { ffi::SSL_CIPHER_get_digest_nid(self.as_ptr()) };
+        if n == 0 {
+            None
+        } else {
+            Some(Nid::from_raw(n))
+        }
+    }
+
+    /// Returns the NID of the key exchange algorithm used by the cipher.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CIPHER_get_kx_nid)]
+    #[cfg(ossl110)]
+    pub fn kx_nid(&self) -> Option<Nid> {
+        let n = unsafe { ffi::SSL_CIPHER_get_kx_nid(self.as_ptr()) };
+        if n == 0 {
+            None
+        } else {
+            Some(Nid::from_raw(n))
+        }
+    }
+
+    /// Returns the NID of the authentication algorithm used by the cipher.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CIPHER_get_auth_nid)]
+    #[cfg(ossl110)]
+    pub fn auth_nid(&self) -> Option<Nid> {
+        let n = unsafe { ffi::SSL_CIPHER_get_auth_nid(self.as_ptr()) };
+        if n == 0 {
+            None
+        } else {
+            Some(Nid::from_raw(n))
+        }
+    }
+
+    /// Determines if the cipher is an AEAD cipher.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_CIPHER_is_aead)]
+    #[cfg(ossl110)]
+    pub fn is_aead(&self) -> bool {
+        unsafe { ffi::SSL_CIPHER_is_aead(self.as_ptr()) == 1 }
+    }
+
+    /// Returns the two-byte IANA identifier of the cipher suite.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CIPHER_get_protocol_id)]
+    #[cfg(ossl111)]
+    pub fn protocol_id(&self) -> u16 {
+        unsafe { ffi::SSL_CIPHER_get_protocol_id(self.as_ptr()) }
+    }
+}
+
+impl fmt::Debug for SslCipherRef {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{}", self.name())
+    }
+}
+
+/// A stack of selected ciphers, and a stack of selected signalling cipher suites
+#[derive(Debug)]
 pub struct CipherLists {
     pub suites: Stack<SslCipher>,
     pub signalling_suites: Stack<SslCipher>,
 }
 
+/// The ordered set of ciphers selected by an OpenSSL cipher string.
+///
+/// Cipher strings are expanded exactly as [`SslContextBuilder::set_cipher_list`] and
+/// [`SslContextBuilder::set_ciphersuites`] would, but without the caller having to construct a
+/// context, which makes this suitable for validating configuration ahead of time.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+///
+/// # Examples
+///
+/// ```
+/// use openssl::ssl::CipherList;
+///
+/// let list = CipherList::parse("ECDHE+AESGCM:!aNULL").unwrap();
+/// assert!(list.iter().all(|c| c.name().starts_with("ECDHE-")));
+/// ```
+#[cfg(ossl110)]
+pub struct CipherList(Stack<SslCipher>);
+
+#[cfg(ossl110)]
+impl CipherList {
+    /// Expands a TLSv1.2 and below cipher list string, such as `ECDHE+AESGCM:!aNULL`.
+    ///
+    /// TLSv1.3 cipher suites are configured separately and are not included in the result.
+    ///
+    /// Returns an error if the string is malformed or does not select any ciphers.
+    #[corresponds(SSL_CTX_set_cipher_list)]
+    pub fn parse(cipher_list: &str) -> Result<CipherList, ErrorStack> {
+        let mut ctx = SslContextBuilder::new(SslMethod::tls())?;
+        #[cfg(ossl111)]
+        ctx.set_ciphersuites("")?;
+        ctx.set_cipher_list(cipher_list)?;
+        CipherList::from_context(&ctx.0, |_| true)
+    }
+
+    /// Expands a TLSv1.3 cipher suite string, such as `TLS_AES_256_GCM_SHA384:TLS_AES_128_GCM_SHA256`.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_CTX_set_ciphersuites)]
+    #[cfg(ossl111)]
+    pub fn parse_ciphersuites(ciphersuites: &str) -> Result<CipherList, ErrorStack> {
+        let mut ctx = SslContextBuilder::new(SslMethod::tls())?;
+        ctx.set_ciphersuites(ciphersuites)?;
+        CipherList::from_context(&ctx.0, |c| c.version() == "TLSv1.3")
+    }
+
+    fn from_context<F>(ctx: &SslContextRef, filter: F) -> Result<CipherList, ErrorStack>
+    where
+        F: Fn(&SslCipherRef) -> bool,
+    {
+        unsafe {
+            let ciphers =
+                StackRef::<SslCipher>::from_ptr(cvt_p(ffi::SSL_CTX_get_ciphers(ctx.as_ptr()))?);
+            let mut list = Stack::new()?;
+            // SSL_CIPHERs are static, so they remain valid after the context is freed.
+            for cipher in ciphers.iter().filter(|c| filter(c)) {
+                list.push(SslCipher::from_ptr(cipher.as_ptr()))?;
+            }
+            Ok(CipherList(list))
+        }
+    }
+
+    /// Returns the number of ciphers in the list.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Determines if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over the ciphers in preference order.
+    pub fn iter(&self) -> Iter<'_, SslCipher> {
+        self.0.iter()
+    }
+
+    /// Returns the names of the ciphers in preference order.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.iter().map(|c| c.name()).collect()
+    }
+
+    /// Consumes the list, returning the underlying stack of ciphers.
+    pub fn into_stack(self) -> Stack<SslCipher> {
+        self.0
+    }
+}
+
+#[cfg(ossl110)]
+impl<'a> IntoIterator for &'a CipherList {
+    type Item = &'a SslCipherRef;
+    type IntoIter = Iter<'a, SslCipher>;
+
+    fn into_iter(self) -> Iter<'a, SslCipher> {
+        self.iter()
+    }
+}
+
+#[cfg(ossl110)]
+impl fmt::Debug for CipherList {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_list().entries(self.iter()).finish()
+    }
+}
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::SSL_SESSION;
     fn drop = ffi::SSL_SESSION_free;
@@ -2177,6 +2949,86 @@ impl SslSessionRef {
         }
     }
 
+    /// Returns the cipher negotiated for the session.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_SESSION_get0_cipher)]
+    #[cfg(ossl110)]
+    pub fn cipher(&self) -> Option<&SslCipherRef> {
+        unsafe {
+            let ptr = ffi::SSL_SESSION_get0_cipher(self.as_ptr());
+            SslCipherRef::from_const_ptr_opt(ptr)
+        }
+    }
+
+    /// Returns the certificate the peer presented when the session was established, if any.
+    #[corresponds(SSL_SESSION_get0_peer)]
+    #[cfg(not(boringssl))]
+    pub fn peer_certificate(&self) -> Option<&X509Ref> {
+        unsafe {
+            let ptr = ffi::SSL_SESSION_get0_peer(self.as_ptr());
+            X509Ref::from_const_ptr_opt(ptr)
+        }
+    }
+
+    /// Returns the ALPN protocol negotiated for the session, if any.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_SESSION_get0_alpn_selected)]
+    #[cfg(ossl111)]
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        unsafe {
+            let mut data = ptr::null();
+            let mut len = 0;
+            ffi::SSL_SESSION_get0_alpn_selected(self.as_ptr(), &mut data, &mut len);
+
+            if data.is_null() || len == 0 {
+                None
+            } else {
+                Some(slice::from_raw_parts(data, len))
+            }
+        }
+    }
+
+    /// Returns the session ticket the server issued for the session, if any.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_SESSION_get0_ticket)]
+    #[cfg(ossl110)]
+    pub fn ticket(&self) -> Option<&[u8]> {
+        unsafe {
+            let mut data = ptr::null();
+            let mut len = 0;
+            ffi::SSL_SESSION_get0_ticket(self.as_ptr(), &mut data, &mut len);
+
+            if data.is_null() || len == 0 {
+                None
+            } else {
+                Some(slice::from_raw_parts(data, len))
+            }
+        }
+    }
+
+    /// Returns the lifetime of the session ticket suggested by the server, in seconds.
+    ///
+    /// This is 0 if the session has no ticket, or if the server did not suggest a lifetime.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_SESSION_get_ticket_lifetime_hint)]
+    #[cfg(ossl110)]
+    pub fn ticket_lifetime_hint(&self) -> u32 {
+        unsafe { ffi::SSL_SESSION_get_ticket_lifetime_hint(self.as_ptr()) as u32 }
+    }
+
+    /// Determines if the session can be used to resume a connection.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_SESSION_is_resumable)]
+    #[cfg(ossl111)]
+    pub fn is_resumable(&self) -> bool {
+        unsafe { ffi::SSL_SESSION_is_resumable(self.as_ptr()) == 1 }
+    }
+
     to_der! {
         /// Serializes the session into a DER-encoded structure.
         #[corresponds(i2d_SSL_SESSION)]
@@ -2185,6 +3037,142 @@ impl SslSessionRef {
     }
 }
 
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::SSL_SESSION;
+    fn drop = ffi::SSL_SESSION_free;
+
+    /// A builder for [`SslSession`]s.
+    ///
+    /// Sessions are shared between every connection and cache which uses them, so they can only be
+    /// modified while they are being built. This allows external session caches to reconstruct a
+    /// session from its parameters, and tests to fabricate sessions for resumption.
+    pub struct SslSessionBuilder;
+
+    /// Reference to an [`SslSessionBuilder`].
+    pub struct SslSessionBuilderRef;
+}
+
+impl SslSessionBuilder {
+    /// Creates a builder for an empty session.
+    #[corresponds(SSL_SESSION_new)]
+    #[cfg(not(boringssl))]
+    pub fn new() -> Result<SslSessionBuilder, ErrorStack> {
+        unsafe { cvt_p(ffi::SSL_SESSION_new()).map(SslSessionBuilder) }
+    }
+
+    /// Creates a builder for a copy of `session`.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_SESSION_dup)]
+    #[cfg(ossl111)]
+    pub fn from_session(session: &SslSessionRef) -> Result<SslSessionBuilder, ErrorStack> {
+        unsafe { cvt_p(ffi::SSL_SESSION_dup(session.as_ptr())).map(SslSessionBuilder) }
+    }
+
+    /// Constructs the `SslSession`.
+    pub fn build(self) -> SslSession {
+        let session = SslSession(self.0);
+        mem::forget(self);
+        session
+    }
+}
+
+impl SslSessionBuilderRef {
+    /// Sets the session ID.
+    ///
+    /// The ID can be at most 32 bytes long.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_SESSION_set1_id)]
+    #[cfg(ossl110)]
+    pub fn set_id(&mut self, id: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            assert!(id.len() <= c_uint::MAX as usize);
+            cvt(ffi::SSL_SESSION_set1_id(
+                self.as_ptr(),
+                id.as_ptr(),
+                id.len() as c_uint,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the master key.
+    ///
+    /// The key can be at most 48 bytes long.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_SESSION_set1_master_key)]
+    #[cfg(ossl110)]
+    pub fn set_master_key(&mut self, key: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_SESSION_set1_master_key(
+                self.as_ptr(),
+                key.as_ptr(),
+                key.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the cipher negotiated for the session.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_SESSION_set_cipher)]
+    #[cfg(ossl111)]
+    pub fn set_cipher(&mut self, cipher: &SslCipherRef) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_SESSION_set_cipher(self.as_ptr(), cipher.as_ptr())).map(|_| ()) }
+    }
+
+    /// Sets the TLS protocol version of the session.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_SESSION_set_protocol_version)]
+    #[cfg(ossl111)]
+    pub fn set_protocol_version(&mut self, version: SslVersion) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_SESSION_set_protocol_version(
+                self.as_ptr(),
+                version.0,
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the ALPN protocol negotiated for the session.
+    ///
+    /// The protocol is given without a length prefix.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_SESSION_set1_alpn_selected)]
+    #[cfg(ossl111)]
+    pub fn set_alpn_protocol(&mut self, protocol: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_SESSION_set1_alpn_selected(
+                self.as_ptr(),
+                protocol.as_ptr(),
+                protocol.len(),
+            ))
+            .map(|_| ())
+        }
+    }
+
+    /// Sets the maximum amount of early data that can be sent on this session.
+    ///
+    /// Requires OpenSSL 1.1.1 or LibreSSL 3.4.0 or newer.
+    #[corresponds(SSL_SESSION_set_max_early_data)]
+    #[cfg(any(ossl111, libressl340))]
+    pub fn set_max_early_data(&mut self, max_early_data: u32) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_SESSION_set_max_early_data(
+                self.as_ptr(),
+                max_early_data,
+            ))
+            .map(|_| ())
+        }
+    }
+}
+
 foreign_type_and_impl_send_sync! {
     type CType = ffi::SSL;
     fn drop = ffi::SSL_free;
@@ -2229,6 +3217,28 @@ impl Ssl {
         }
     }
 
+    /// Returns a new extra data index whose data is cloned by [`SslRef::dup`].
+    ///
+    /// Data stored at indices created by [`Ssl::new_ex_index`] cannot be duplicated, and causes
+    /// [`SslRef::dup`] to fail.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_get_ex_new_index)]
+    #[cfg(ossl110)]
+    pub fn new_cloneable_ex_index<T>() -> Result<Index<Ssl, T>, ErrorStack>
+    where
+        T: 'static + Sync + Send + Clone,
+    {
+        unsafe {
+            ffi::init();
+            let idx = cvt_n(get_new_ssl_idx_with_dup(
+                free_data_box::<T>,
+                dup_data_box::<T>,
+            ))?;
+            Ok(Index::from_raw(idx))
+        }
+    }
+
     // FIXME should return a result?
     fn cached_ex_index<T>() -> Index<Ssl, T>
     where
@@ -2375,6 +3385,33 @@ impl SslRef {
         }
     }
 
+    /// Like [`SslContextBuilder::set_private_key_method`].
+    ///
+    /// Requires BoringSSL.
+    #[corresponds(SSL_set_private_key_method)]
+    #[cfg(boringssl)]
+    pub fn set_private_key_method<M>(&mut self, method: M)
+    where
+        M: PrivateKeyMethod,
+    {
+        unsafe {
+            self.set_ex_data(Ssl::cached_ex_index::<Arc<M>>(), Arc::new(method));
+            ffi::SSL_set_private_key_method(
+                self.as_ptr(),
+                &callbacks::PrivateKeyMethodTable::<M>::METHOD,
+            );
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_permute_extensions`].
+    ///
+    /// Requires BoringSSL.
+    #[corresponds(SSL_set_permute_extensions)]
+    #[cfg(boringssl)]
+    pub fn set_permute_extensions(&mut self, enabled: bool) {
+        unsafe { ffi::SSL_set_permute_extensions(self.as_ptr(), enabled as c_int) }
+    }
+
     /// Like [`SslContextBuilder::set_tmp_dh`].
     ///
     /// [`SslContextBuilder::set_tmp_dh`]: struct.SslContextBuilder.html#method.set_tmp_dh
@@ -2456,7 +3493,7 @@ impl SslRef {
             if r == 0 {
                 Ok(())
             } else {
-                Err(ErrorStack::get())
+                Err(ErrorStack::report())
             }
         }
     }
@@ -2545,6 +3582,9 @@ impl SslRef {
     }
 
     /// Like [`SslContext::certificate`].
+    ///
+    /// On a server, after the certificate has been chosen for the handshake, this is the
+    /// certificate which was sent to the peer.
     #[corresponds(SSL_get_certificate)]
     pub fn certificate(&self) -> Option<&X509Ref> {
         unsafe {
@@ -2553,6 +3593,20 @@ impl SslRef {
         }
     }
 
+    /// Like [`SslContext::chain_certs`].
+    ///
+    /// On a server, after the certificate has been chosen for the handshake, this is the chain
+    /// which was sent to the peer, unless only the extra chain certificates were used.
+    #[corresponds(SSL_get0_chain_certs)]
+    #[cfg(ossl102)]
+    pub fn chain_certs(&self) -> Option<&StackRef<X509>> {
+        unsafe {
+            let mut chain = ptr::null_mut();
+            ffi::SSL_get0_chain_certs(self.as_ptr(), &mut chain);
+            StackRef::from_const_ptr_opt(chain)
+        }
+    }
+
     /// Like [`SslContext::private_key`].
     ///
     /// [`SslContext::private_key`]: struct.SslContext.html#method.private_key
@@ -2632,7 +3686,7 @@ impl SslRef {
             if r == 0 {
                 Ok(())
             } else {
-                Err(ErrorStack::get())
+                Err(ErrorStack::report())
             }
         }
     }
@@ -2781,6 +3835,29 @@ impl SslRef {
         }
     }
 
+    /// Returns the TLS 1.3 downgrade protection sentinel in the server's random value, if any.
+    ///
+    /// A server which supports a newer protocol version than the one negotiated marks its random
+    /// value as described in [RFC 8446]. Clients supporting TLS 1.3 abort such handshakes
+    /// themselves, but clients restricted to older versions complete them, and can use this to
+    /// detect that the server could have used a newer version.
+    ///
+    /// Requires OpenSSL 1.1.0 or LibreSSL 2.7.0 or newer.
+    ///
+    /// [RFC 8446]: https://tools.ietf.org/html/rfc8446#section-4.1.3
+    #[cfg(any(ossl110, libressl270))]
+    pub fn downgrade_sentinel(&self) -> Option<DowngradeSentinel> {
+        let mut random = [0; 32];
+        if self.server_random(&mut random) != random.len() {
+            return None;
+        }
+        match &random[24..] {
+            b"DOWNGRD\x01" => Some(DowngradeSentinel::Tls12),
+            b"DOWNGRD\x00" => Some(DowngradeSentinel::Tls11),
+            _ => None,
+        }
+    }
+
     /// Derives keying material for application use in accordance to RFC 5705.
     #[corresponds(SSL_export_keying_material)]
     pub fn export_keying_material(
@@ -2867,7 +3944,9 @@ impl SslRef {
 
     /// Determines if current session used Extended Master Secret
     ///
-    /// Returns `None` if the handshake is still in-progress.
+    /// Returns `None` if the handshake is still in-progress. OpenSSL does not refuse handshakes
+    /// without the extension, so connections which require it should check this once the
+    /// handshake has completed.
     #[corresponds(SSL_get_extms_support)]
     #[cfg(ossl110)]
     pub fn extms_support(&self) -> Option<bool> {
@@ -2879,6 +3958,13 @@ impl SslRef {
         }
     }
 
+    /// Determines if the peer supports secure renegotiation, as defined in RFC 5746.
+    #[corresponds(SSL_get_secure_renegotiation_support)]
+    #[cfg(not(boringssl))]
+    pub fn secure_renegotiation_support(&self) -> bool {
+        unsafe { ffi::SSL_get_secure_renegotiation_support(self.as_ptr()) != 0 }
+    }
+
     /// Returns the server's OCSP response, if present.
     #[corresponds(SSL_get_tlsext_status_ocsp_resp)]
     #[cfg(not(boringssl))]
@@ -2973,7 +4059,7 @@ impl SslRef {
         if unsafe { ffi::SSL_set_max_early_data(self.as_ptr(), bytes) } == 1 {
             Ok(())
         } else {
-            Err(ErrorStack::get())
+            Err(ErrorStack::report())
         }
     }
 
@@ -2986,6 +4072,123 @@ impl SslRef {
         unsafe { ffi::SSL_get_max_early_data(self.as_ptr()) }
     }
 
+    /// Sets the maximum amount of early data that the server will read on this connection.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_set_recv_max_early_data)]
+    #[cfg(ossl111)]
+    pub fn set_recv_max_early_data(&mut self, bytes: u32) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_set_recv_max_early_data(self.as_ptr(), bytes)).map(|_| ()) }
+    }
+
+    /// Gets the maximum amount of early data that the server will read on this connection.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_get_recv_max_early_data)]
+    #[cfg(ossl111)]
+    pub fn recv_max_early_data(&self) -> u32 {
+        unsafe { ffi::SSL_get_recv_max_early_data(self.as_ptr()) }
+    }
+
+    /// Sets the maximum number of records that will be encrypted or decrypted in parallel.
+    ///
+    /// Values greater than 1 only have an effect when the negotiated cipher supports pipelining,
+    /// which is typically only the case for ciphers provided by hardware accelerator engines. Must
+    /// be between 1 and 32. Defaults to 1.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_set_max_pipelines)]
+    #[cfg(ossl110)]
+    pub fn set_max_pipelines(&mut self, pipelines: usize) -> Result<(), ErrorStack> {
+        if unsafe { ffi::SSL_set_max_pipelines(self.as_ptr(), pipelines as c_long) } == 1 {
+            Ok(())
+        } else {
+            Err(ErrorStack::report())
+        }
+    }
+
+    /// Sets the size of the fragments that outgoing data is split into when pipelining.
+    ///
+    /// Must be between 512 and the maximum send fragment size. Defaults to the maximum send
+    /// fragment size.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_set_split_send_fragment)]
+    #[cfg(ossl110)]
+    pub fn set_split_send_fragment(&mut self, size: usize) -> Result<(), ErrorStack> {
+        if unsafe { ffi::SSL_set_split_send_fragment(self.as_ptr(), size as c_long) } == 1 {
+            Ok(())
+        } else {
+            Err(ErrorStack::report())
+        }
+    }
+
+    /// Sets the maximum size of the plaintext in each outgoing record.
+    ///
+    /// Must be between 512 and 16384. Defaults to 16384.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_set_max_send_fragment)]
+    #[cfg(ossl110)]
+    pub fn set_max_send_fragment(&mut self, size: usize) -> Result<(), ErrorStack> {
+        if unsafe { ffi::SSL_set_max_send_fragment(self.as_ptr(), size as c_long) } == 1 {
+            Ok(())
+        } else {
+            Err(ErrorStack::report())
+        }
+    }
+
+    /// Sets the initial size of the read buffer used by this connection.
+    ///
+    /// When pipelining, a larger read buffer allows several records to be read and decrypted at
+    /// once. Read ahead must also be enabled for this to have an effect.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_set_default_read_buffer_len)]
+    #[cfg(ossl110)]
+    pub fn set_default_read_buffer_len(&mut self, len: usize) {
+        unsafe { ffi::SSL_set_default_read_buffer_len(self.as_ptr(), len) }
+    }
+
+    /// Like [`SslContextBuilder::set_quiet_shutdown`].
+    #[corresponds(SSL_set_quiet_shutdown)]
+    pub fn set_quiet_shutdown(&mut self, quiet: bool) {
+        unsafe { ffi::SSL_set_quiet_shutdown(self.as_ptr(), quiet as c_int) }
+    }
+
+    /// Returns whether quiet shutdown is enabled.
+    #[corresponds(SSL_get_quiet_shutdown)]
+    pub fn quiet_shutdown(&self) -> bool {
+        unsafe { ffi::SSL_get_quiet_shutdown(self.as_ptr()) != 0 }
+    }
+
+    /// Creates a copy of this `Ssl` which has not yet been used for a connection.
+    ///
+    /// The copy shares this object's context and session, and has the same configuration, but
+    /// its extra data is independent. Values stored at indices created with
+    /// [`Ssl::new_cloneable_ex_index`] are cloned into the copy. Duplication fails if any other
+    /// extra data is present, which includes callbacks configured directly on this `Ssl` rather
+    /// than on its `SslContext`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a stream has been associated with this `Ssl`.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(SSL_dup)]
+    #[cfg(ossl110)]
+    pub fn dup(&self) -> Result<Ssl, ErrorStack> {
+        unsafe {
+            // SSL_dup returns the same object once a connection has started, and can't
+            // duplicate our stream BIO
+            assert!(
+                self.get_raw_rbio().is_null() && ffi::SSL_in_before(self.as_ptr()) != 0,
+                "cannot duplicate an Ssl which has been associated with a stream"
+            );
+            cvt_p(ffi::SSL_dup(self.as_ptr())).map(|p| Ssl::from_ptr(p))
+        }
+    }
+
     /// Copies the contents of the last Finished message sent to the peer into the provided buffer.
     ///
     /// The total size of the message is returned, so this can be used to determine the size of the
@@ -3007,6 +4210,62 @@ impl SslRef {
         }
     }
 
+    /// Returns the channel binding data of the connection, as used by SCRAM-PLUS and GSS-API.
+    ///
+    /// The data is the same on the client and the server. An error is returned if `binding` is
+    /// not defined for the connection, for example `tls-unique` with TLS 1.3, or if the
+    /// handshake has not completed.
+    pub fn channel_binding(&self, binding: ChannelBinding) -> Result<Vec<u8>, ChannelBindingError> {
+        channel_binding::channel_binding(self, binding)
+    }
+
+    /// Creates an exported authenticator, as defined in [RFC 9261].
+    ///
+    /// The authenticator proves possession of `key`, the private key of the first certificate in
+    /// `chain`, and is sent to the peer over the application protocol. `request` is a request
+    /// received from the peer; servers may pass `None` to create a spontaneous authenticator.
+    ///
+    /// The connection must use TLS 1.3, or TLS 1.2 with the extended master secret extension.
+    ///
+    /// [RFC 9261]: https://tools.ietf.org/html/rfc9261
+    #[cfg(ossl111)]
+    pub fn export_authenticator<T>(
+        &self,
+        request: Option<&AuthenticatorRequest>,
+        chain: &[X509],
+        key: &PKeyRef<T>,
+    ) -> Result<Vec<u8>, ExportedAuthenticatorError>
+    where
+        T: HasPrivate,
+    {
+        authenticator::export_authenticator(self, request, chain, key)
+    }
+
+    /// Creates an empty exported authenticator, refusing the peer's `request`.
+    #[cfg(ossl111)]
+    pub fn export_empty_authenticator(
+        &self,
+        request: &AuthenticatorRequest,
+    ) -> Result<Vec<u8>, ExportedAuthenticatorError> {
+        authenticator::export_empty_authenticator(self, request)
+    }
+
+    /// Validates an exported authenticator received from the peer.
+    ///
+    /// `request` is the request previously sent to the peer, or `None` for a spontaneous
+    /// authenticator from the server. On success, the certificate chain of the authenticator is
+    /// returned, or `None` if the peer refused the request with an empty authenticator.
+    ///
+    /// The chain itself is not verified; callers should verify it against their trust store.
+    #[cfg(ossl111)]
+    pub fn validate_authenticator(
+        &self,
+        request: Option<&AuthenticatorRequest>,
+        authenticator: &[u8],
+    ) -> Result<Option<Vec<X509>>, ExportedAuthenticatorError> {
+        authenticator::validate_authenticator(self, request, authenticator)
+    }
+
     /// Determines if the initial handshake has been completed.
     #[corresponds(SSL_is_init_finished)]
     #[cfg(ossl110)]
@@ -3014,6 +4273,184 @@ impl SslRef {
         unsafe { ffi::SSL_is_init_finished(self.as_ptr()) != 0 }
     }
 
+    /// Like [`SslContextBuilder::enable_handshake_timing`].
+    ///
+    /// This must be called before the handshake starts.
+    #[corresponds(SSL_set_msg_callback)]
+    pub fn enable_handshake_timing(&mut self) {
+        unsafe {
+            ffi::SSL_set_msg_callback(self.as_ptr(), Some(callbacks::raw_handshake_timing));
+        }
+    }
+
+    /// Returns the timing of the handshake messages of this connection.
+    ///
+    /// `None` is returned if timing was not enabled or the handshake has not started. During a
+    /// renegotiation, the timings of the new handshake are returned.
+    pub fn handshake_timings(&self) -> Option<&HandshakeTimings> {
+        self.ex_data(Ssl::cached_ex_index::<HandshakeTimings>())
+    }
+
+    /// Records the address of the peer of this connection.
+    ///
+    /// OpenSSL does not use the address, but it can be retrieved with [`peer_addr`] from
+    /// callbacks such as the verify and SNI callbacks, which are passed the `SslRef` but not the
+    /// underlying stream.
+    ///
+    /// [`peer_addr`]: SslRef::peer_addr
+    pub fn set_peer_addr(&mut self, addr: SocketAddr) {
+        match self.ex_data_mut(Ssl::cached_ex_index::<PeerAddr>()) {
+            Some(peer) => peer.0 = addr,
+            None => self.set_ex_data(Ssl::cached_ex_index(), PeerAddr(addr)),
+        }
+    }
+
+    /// Returns the address of the peer recorded by [`set_peer_addr`] or [`set_peer_socket`].
+    ///
+    /// [`set_peer_addr`]: SslRef::set_peer_addr
+    /// [`set_peer_socket`]: SslRef::set_peer_socket
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.ex_data(Ssl::cached_ex_index::<PeerAddr>())
+            .map(|peer| peer.0)
+    }
+
+    /// Records the credentials of the peer of this connection.
+    ///
+    /// Like the peer address, the credentials can be retrieved with [`peer_credentials`] from
+    /// callbacks.
+    ///
+    /// Requires Linux or Android.
+    ///
+    /// [`peer_credentials`]: SslRef::peer_credentials
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_peer_credentials(&mut self, credentials: PeerCredentials) {
+        match self.ex_data_mut(Ssl::cached_ex_index::<PeerCredentials>()) {
+            Some(peer) => *peer = credentials,
+            None => self.set_ex_data(Ssl::cached_ex_index(), credentials),
+        }
+    }
+
+    /// Returns the credentials of the peer recorded by [`set_peer_credentials`] or
+    /// [`set_peer_socket`].
+    ///
+    /// Requires Linux or Android.
+    ///
+    /// [`set_peer_credentials`]: SslRef::set_peer_credentials
+    /// [`set_peer_socket`]: SslRef::set_peer_socket
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn peer_credentials(&self) -> Option<PeerCredentials> {
+        self.ex_data(Ssl::cached_ex_index::<PeerCredentials>())
+            .copied()
+    }
+
+    /// Records the peer of a socket before it is used for this connection.
+    ///
+    /// The peer address is recorded for IP sockets, such as a `TcpStream` or a connected
+    /// `UdpSocket`. On Linux and Android, the peer credentials are recorded for Unix domain
+    /// sockets. Nothing is recorded for other sockets.
+    ///
+    /// Requires a Unix platform.
+    #[cfg(unix)]
+    pub fn set_peer_socket<S>(&mut self, socket: &S) -> io::Result<()>
+    where
+        S: AsRawFd,
+    {
+        match peer::peer_name(socket.as_raw_fd())? {
+            PeerName::Ip(addr) => self.set_peer_addr(addr),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            PeerName::Unix => self.set_peer_credentials(PeerCredentials::from_socket(socket)?),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Attaches application state to this connection, replacing any state of the same type.
+    ///
+    /// The state is dropped along with the `Ssl`. It can be retrieved with [`app_data`] from any
+    /// callback which is passed the `SslRef`, which allows state such as the accepted socket's
+    /// metadata to be attached before the handshake instead of being looked up by the address of
+    /// the `Ssl`.
+    ///
+    /// State is keyed by its type, so other code attaching state to the same `Ssl` should use
+    /// distinct types, such as a newtype private to each crate.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use openssl::ssl::{Ssl, SslAcceptor, SslMethod, SniError};
+    /// use std::net::TcpListener;
+    ///
+    /// struct ListenerName(&'static str);
+    ///
+    /// let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+    /// acceptor.set_servername_callback(|ssl, _| {
+    ///     let listener = ssl.app_data::<ListenerName>().ok_or(SniError::ALERT_FATAL)?;
+    ///     println!("{} accepted {:?}", listener.0, ssl.peer_addr());
+    ///     Ok(())
+    /// });
+    /// let acceptor = acceptor.build();
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:8443").unwrap();
+    /// let (stream, addr) = listener.accept().unwrap();
+    /// let mut ssl = Ssl::new(acceptor.context()).unwrap();
+    /// ssl.set_app_data(ListenerName("public"));
+    /// ssl.set_peer_addr(addr);
+    /// let stream = ssl.accept(stream).unwrap();
+    /// ```
+    ///
+    /// [`app_data`]: SslRef::app_data
+    pub fn set_app_data<T>(&mut self, data: T)
+    where
+        T: 'static + Sync + Send,
+    {
+        match self.app_data_mut::<T>() {
+            Some(old) => *old = data,
+            None => self.set_ex_data(Ssl::cached_ex_index(), AppData(data)),
+        }
+    }
+
+    /// Returns the application state of type `T` attached with [`set_app_data`].
+    ///
+    /// [`set_app_data`]: SslRef::set_app_data
+    pub fn app_data<T>(&self) -> Option<&T>
+    where
+        T: 'static + Sync + Send,
+    {
+        self.ex_data(Ssl::cached_ex_index::<AppData<T>>())
+            .map(|data| &data.0)
+    }
+
+    /// Returns a mutable reference to the application state of type `T` attached with
+    /// [`set_app_data`].
+    ///
+    /// [`set_app_data`]: SslRef::set_app_data
+    pub fn app_data_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: 'static + Sync + Send,
+    {
+        self.ex_data_mut(Ssl::cached_ex_index::<AppData<T>>())
+            .map(|data| &mut data.0)
+    }
+
+    /// Detaches the application state of type `T` attached with [`set_app_data`], returning it.
+    ///
+    /// [`set_app_data`]: SslRef::set_app_data
+    #[corresponds(SSL_set_ex_data)]
+    pub fn take_app_data<T>(&mut self) -> Option<T>
+    where
+        T: 'static + Sync + Send,
+    {
+        let index = Ssl::cached_ex_index::<AppData<T>>();
+        unsafe {
+            let data = ffi::SSL_get_ex_data(self.as_ptr(), index.as_raw());
+            if data.is_null() {
+                return None;
+            }
+            ffi::SSL_set_ex_data(self.as_ptr(), index.as_raw(), ptr::null_mut());
+            Some(Box::from_raw(data as *mut AppData<T>).0)
+        }
+    }
+
     /// Determines if the client's hello message is in the SSLv2 format.
     ///
     /// This can only be used inside of the client hello callback. Otherwise, `false` is returned.
@@ -3130,7 +4567,7 @@ impl SslRef {
                     signalling_suites: Stack::from_ptr(scsvs),
                 })
             } else {
-                Err(ErrorStack::get())
+                Err(ErrorStack::report())
             }
         }
     }
@@ -3154,12 +4591,207 @@ impl SslRef {
         }
     }
 
+    /// Returns the cipher suite values of the client's hello message, in the order sent.
+    ///
+    /// Unlike [`client_hello_ciphers`], the wire format is decoded into the 16 bit values used by
+    /// fingerprinting schemes such as JA3 and JA4. Signalling and GREASE values are included.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, and for SSLv2 format
+    /// hellos, `None` is returned.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    ///
+    /// [`client_hello_ciphers`]: SslRef::client_hello_ciphers
+    #[cfg(ossl111)]
+    pub fn client_hello_cipher_values(&self) -> Option<Vec<u16>> {
+        if self.client_hello_isv2() {
+            return None;
+        }
+        self.client_hello_ciphers().map(u16_values)
+    }
+
+    /// Returns the types of the extensions in the client's hello message, in the order sent.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, `None` is returned.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_client_hello_get1_extensions_present)]
+    #[cfg(ossl111)]
+    pub fn client_hello_extension_types(&self) -> Option<Vec<u16>> {
+        unsafe {
+            let mut ptr = ptr::null_mut();
+            let mut len = 0;
+            if ffi::SSL_client_hello_get1_extensions_present(self.as_ptr(), &mut ptr, &mut len) != 1
+            {
+                ffi::ERR_clear_error();
+                return None;
+            }
+            let types = if len == 0 {
+                vec![]
+            } else {
+                slice::from_raw_parts(ptr, len)
+                    .iter()
+                    .map(|&t| t as u16)
+                    .collect()
+            };
+            ffi::OPENSSL_free(ptr as *mut c_void);
+            Some(types)
+        }
+    }
+
+    /// Returns the raw contents of an extension in the client's hello message.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, and if the client did
+    /// not send the extension, `None` is returned.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(SSL_client_hello_get0_ext)]
+    #[cfg(ossl111)]
+    pub fn client_hello_ext(&self, ext_type: u16) -> Option<&[u8]> {
+        unsafe {
+            let mut ptr = ptr::null();
+            let mut len = 0;
+            if ffi::SSL_client_hello_get0_ext(
+                self.as_ptr(),
+                c_uint::from(ext_type),
+                &mut ptr,
+                &mut len,
+            ) != 1
+            {
+                return None;
+            }
+            if len == 0 {
+                Some(&[])
+            } else {
+                Some(slice::from_raw_parts(ptr, len))
+            }
+        }
+    }
+
+    /// Returns the group values of the client's supported groups extension, in the order sent.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, and if the extension
+    /// is absent or malformed, `None` is returned.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_groups(&self) -> Option<Vec<u16>> {
+        // supported_groups
+        let ext = self.client_hello_ext(10)?;
+        if ext.len() < 2 || ext.len() % 2 != 0 {
+            return None;
+        }
+        let (len, groups) = ext.split_at(2);
+        if usize::from(u16::from_be_bytes([len[0], len[1]])) != groups.len() {
+            return None;
+        }
+        Some(u16_values(groups))
+    }
+
+    /// Returns the values of the client's EC point formats extension, in the order sent.
+    ///
+    /// This can only be used inside of the client hello callback. Otherwise, and if the extension
+    /// is absent or malformed, `None` is returned.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[cfg(ossl111)]
+    pub fn client_hello_ec_point_formats(&self) -> Option<Vec<u8>> {
+        // ec_point_formats
+        let ext = self.client_hello_ext(11)?;
+        match ext.split_first() {
+            Some((&len, formats)) if usize::from(len) == formats.len() => Some(formats.to_vec()),
+            _ => None,
+        }
+    }
+
     /// Sets the MTU used for DTLS connections.
     #[corresponds(SSL_set_mtu)]
     pub fn set_mtu(&mut self, mtu: u32) -> Result<(), ErrorStack> {
         unsafe { cvt(ffi::SSL_set_mtu(self.as_ptr(), mtu as MtuTy) as c_int).map(|_| ()) }
     }
 
+    /// Sets the MTU of the link used for DTLS connections.
+    ///
+    /// Unlike [`set_mtu`], the MTU includes the overhead of the datagram's headers, such as the
+    /// IP and UDP headers, which are subtracted based on the BIO's transport. This is the value
+    /// to pass when the path MTU is discovered or changes. MTUs smaller than [`link_min_mtu`] are
+    /// rejected.
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// [`set_mtu`]: SslRef::set_mtu
+    /// [`link_min_mtu`]: SslRef::link_min_mtu
+    #[corresponds(DTLS_set_link_mtu)]
+    #[cfg(ossl102)]
+    pub fn set_link_mtu(&mut self, mtu: u32) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::DTLS_set_link_mtu(self.as_ptr(), mtu as c_long) as c_int).map(|_| ()) }
+    }
+
+    /// Returns the smallest link MTU accepted by [`set_link_mtu`].
+    ///
+    /// Requires OpenSSL 1.0.2 or newer.
+    ///
+    /// [`set_link_mtu`]: SslRef::set_link_mtu
+    #[corresponds(DTLS_get_link_min_mtu)]
+    #[cfg(ossl102)]
+    pub fn link_min_mtu(&self) -> u32 {
+        unsafe { ffi::DTLS_get_link_min_mtu(self.as_ptr()) as u32 }
+    }
+
+    /// Returns the largest amount of application data which fits in a single DTLS datagram.
+    ///
+    /// This is the MTU less the overhead of the record layer of the current cipher, or 0 if no
+    /// cipher has been negotiated yet.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(DTLS_get_data_mtu)]
+    #[cfg(ossl111)]
+    pub fn data_mtu(&self) -> usize {
+        unsafe { ffi::DTLS_get_data_mtu(self.as_ptr()) }
+    }
+
+    /// Returns the time remaining until the DTLS retransmission timer expires.
+    ///
+    /// Returns `None` if the timer is not running, which is the case when no handshake flight is
+    /// awaiting a response. When the timer expires, [`SslStream::handle_dtls_timeout`] must be
+    /// called to retransmit the flight. This allows DTLS handshakes over non-blocking sockets to
+    /// be driven by an external timer rather than by socket timeouts.
+    #[corresponds(DTLSv1_get_timeout)]
+    #[cfg(not(boringssl))]
+    pub fn dtls_timeout(&self) -> Option<Duration> {
+        unsafe {
+            let mut timeout = mem::zeroed::<libc::timeval>();
+            if ffi::DTLSv1_get_timeout(self.as_ptr(), &mut timeout) <= 0 {
+                return None;
+            }
+            Some(Duration::new(
+                timeout.tv_sec as u64,
+                timeout.tv_usec as u32 * 1000,
+            ))
+        }
+    }
+
+    /// Sets the callback which determines the DTLS retransmission timeout.
+    ///
+    /// The callback is passed the previous timeout, or zero when a new handshake flight is sent,
+    /// and returns the timeout to use until the flight is next retransmitted. By default, OpenSSL
+    /// starts at one second and doubles the timeout on every retransmission, up to 60 seconds.
+    /// Timeouts are rounded to microseconds.
+    ///
+    /// Requires OpenSSL 1.1.1 or newer.
+    #[corresponds(DTLS_set_timer_cb)]
+    #[cfg(ossl111)]
+    pub fn set_dtls_timer_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&mut SslRef, Duration) -> Duration + 'static + Sync + Send,
+    {
+        unsafe {
+            // this needs to be in an Arc since the callback can register a new callback!
+            self.set_ex_data(Ssl::cached_ex_index(), Arc::new(callback));
+            ffi::DTLS_set_timer_cb(self.as_ptr(), Some(raw_dtls_timer::<F>));
+        }
+    }
+
     /// Returns the PSK identity hint used during connection setup.
     ///
     /// May return `None` if no PSK identity hint was used during the connection setup.
@@ -3200,6 +4832,24 @@ impl SslRef {
         Ok(())
     }
 
+    /// Like [`SslContextBuilder::select_current_cert`].
+    #[corresponds(SSL_select_current_cert)]
+    #[cfg(ossl102)]
+    pub fn select_current_cert(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::SSL_select_current_cert(self.as_ptr(), cert.as_ptr()) as c_int).map(|_| ())
+        }
+    }
+
+    /// Like [`SslContextBuilder::set_current_cert`].
+    ///
+    /// On a server, [`SslCurrentCert::SERVER`] selects the certificate chosen for the handshake.
+    #[corresponds(SSL_set_current_cert)]
+    #[cfg(ossl102)]
+    pub fn set_current_cert(&mut self, op: SslCurrentCert) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_set_current_cert(self.as_ptr(), op.as_raw()) as c_int).map(|_| ()) }
+    }
+
     /// Sets a new default TLS/SSL method for SSL objects
     #[cfg(not(boringssl))]
     pub fn set_method(&mut self, method: SslMethod) -> Result<(), ErrorStack> {
@@ -3237,6 +4887,14 @@ impl SslRef {
         Ok(())
     }
 
+    /// Checks for consistency between the private key and certificate.
+    ///
+    /// See [`SslContextBuilder::check_private_key`] for the errors returned.
+    #[corresponds(SSL_check_private_key)]
+    pub fn check_private_key(&self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_check_private_key(self.as_ptr())).map(|_| ()) }
+    }
+
     /// Sets the certificate
     #[corresponds(SSL_use_certificate)]
     pub fn set_certificate(&mut self, cert: &X509Ref) -> Result<(), ErrorStack> {
@@ -3284,6 +4942,55 @@ impl SslRef {
         mem::forget(list);
     }
 
+    /// Returns the list of CA names associated with the connection.
+    ///
+    /// On a server, this is the list sent to the client when requesting a client certificate. On
+    /// a client, this is the list received from the server, which can be used to choose the
+    /// certificate to present, and is only available once the server has requested a certificate.
+    #[corresponds(SSL_get_client_CA_list)]
+    pub fn client_ca_list(&self) -> Option<&StackRef<X509Name>> {
+        unsafe {
+            let list = ffi::SSL_get_client_CA_list(self.as_ptr());
+            StackRef::from_const_ptr_opt(list)
+        }
+    }
+
+    /// Applies a set of configuration overrides to the connection.
+    ///
+    /// Settings which are not overridden are left unchanged.
+    pub fn apply_overrides(&mut self, overrides: &SslOverrides) -> Result<(), ErrorStack> {
+        overrides.apply(self)
+    }
+
+    /// Sets the options used by the connection, returning the old set.
+    ///
+    /// This overrides the options inherited from the context for this connection only.
+    ///
+    /// # Note
+    ///
+    /// This *enables* the specified options, but does not disable unspecified options. Use
+    /// `clear_options` for that.
+    #[corresponds(SSL_set_options)]
+    pub fn set_options(&mut self, option: SslOptions) -> SslOptions {
+        let bits = unsafe { ffi::SSL_set_options(self.as_ptr(), option.bits()) } as SslOptionsRepr;
+        SslOptions { bits }
+    }
+
+    /// Returns the options used by the connection.
+    #[corresponds(SSL_get_options)]
+    pub fn options(&self) -> SslOptions {
+        let bits = unsafe { ffi::SSL_get_options(self.as_ptr()) } as SslOptionsRepr;
+        SslOptions { bits }
+    }
+
+    /// Clears the options used by the connection, returning the old set.
+    #[corresponds(SSL_clear_options)]
+    pub fn clear_options(&mut self, option: SslOptions) -> SslOptions {
+        let bits =
+            unsafe { ffi::SSL_clear_options(self.as_ptr(), option.bits()) } as SslOptionsRepr;
+        SslOptions { bits }
+    }
+
     /// Sets the minimum supported protocol version.
     ///
     /// A value of `None` will enable protocol versions down to the lowest version supported by
@@ -3320,6 +5027,44 @@ impl SslRef {
         }
     }
 
+    /// Gets the minimum supported protocol version.
+    ///
+    /// A value of `None` indicates that all versions down to the lowest version supported by
+    /// OpenSSL are enabled.
+    ///
+    /// Requires OpenSSL 1.1.0g or LibreSSL 2.7.0 or newer.
+    #[corresponds(SSL_get_min_proto_version)]
+    #[cfg(any(ossl110g, libressl270))]
+    pub fn min_proto_version(&self) -> Option<SslVersion> {
+        unsafe {
+            let r = ffi::SSL_get_min_proto_version(self.as_ptr());
+            if r == 0 {
+                None
+            } else {
+                Some(SslVersion(r))
+            }
+        }
+    }
+
+    /// Gets the maximum supported protocol version.
+    ///
+    /// A value of `None` indicates that all versions up to the highest version supported by
+    /// OpenSSL are enabled.
+    ///
+    /// Requires OpenSSL 1.1.0g or LibreSSL 2.7.0 or newer.
+    #[corresponds(SSL_get_max_proto_version)]
+    #[cfg(any(ossl110g, libressl270))]
+    pub fn max_proto_version(&self) -> Option<SslVersion> {
+        unsafe {
+            let r = ffi::SSL_get_max_proto_version(self.as_ptr());
+            if r == 0 {
+                None
+            } else {
+                Some(SslVersion(r))
+            }
+        }
+    }
+
     /// Sets the list of supported ciphers for the TLSv1.3 protocol.
     ///
     /// The `set_cipher_list` method controls the cipher suites for protocols before TLSv1.3.
@@ -3449,6 +5194,16 @@ where
             }
         }
     }
+
+    /// Like [`SslStream::handle_dtls_timeout`].
+    ///
+    /// After a DTLS handshake over a non-blocking stream is interrupted, this retransmits the
+    /// handshake flight if the peer has not responded in time.
+    #[corresponds(DTLSv1_handle_timeout)]
+    #[cfg(not(boringssl))]
+    pub fn handle_dtls_timeout(&mut self) -> Result<bool, Error> {
+        self.stream.handle_dtls_timeout()
+    }
 }
 
 /// A TLS session over a stream.
@@ -3490,7 +5245,19 @@ impl<S: Read + Write> SslStream<S> {
     /// explicitly perform the handshake.
     #[corresponds(SSL_set_bio)]
     pub fn new(ssl: Ssl, stream: S) -> Result<Self, ErrorStack> {
-        let (bio, method) = bio::new(stream)?;
+        Self::from_parts(ssl, stream, vec![])
+    }
+
+    /// Creates a new `SslStream` over a stream from which `buffered` has already been read.
+    ///
+    /// The bytes of `buffered` are processed before any more are read from `stream`. This
+    /// supports STARTTLS-style protocols, where the reader used for the plaintext part of the
+    /// protocol may have consumed the start of the peer's first TLS message.
+    ///
+    /// Like `new`, this function performs no IO.
+    #[corresponds(SSL_set_bio)]
+    pub fn from_parts(ssl: Ssl, stream: S, buffered: Vec<u8>) -> Result<Self, ErrorStack> {
+        let (bio, method) = bio::new_buffered(stream, buffered)?;
         unsafe {
             ffi::SSL_set_bio(ssl.as_ptr(), bio, bio);
         }
@@ -3529,6 +5296,7 @@ impl<S: Read + Write> SslStream<S> {
     #[corresponds(SSL_read_early_data)]
     #[cfg(any(ossl111, libressl340))]
     pub fn read_early_data(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        self.check_panic()?;
         let mut read = 0;
         let ret = unsafe {
             ffi::SSL_read_early_data(
@@ -3555,6 +5323,7 @@ impl<S: Read + Write> SslStream<S> {
     #[corresponds(SSL_write_early_data)]
     #[cfg(any(ossl111, libressl340))]
     pub fn write_early_data(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.check_panic()?;
         let mut written = 0;
         let ret = unsafe {
             ffi::SSL_write_early_data(
@@ -3579,12 +5348,9 @@ impl<S: Read + Write> SslStream<S> {
     /// `SslConnector` rather than `Ssl` directly, as it manages that configuration.
     #[corresponds(SSL_connect)]
     pub fn connect(&mut self) -> Result<(), Error> {
+        self.check_panic()?;
         let ret = unsafe { ffi::SSL_connect(self.ssl.as_ptr()) };
-        if ret > 0 {
-            Ok(())
-        } else {
-            Err(self.make_error(ret))
-        }
+        self.handshake_result(ret)
     }
 
     /// Initiates a server-side TLS handshake.
@@ -3595,12 +5361,9 @@ impl<S: Read + Write> SslStream<S> {
     /// `SslAcceptor` rather than `Ssl` directly, as it manages that configuration.
     #[corresponds(SSL_accept)]
     pub fn accept(&mut self) -> Result<(), Error> {
+        self.check_panic()?;
         let ret = unsafe { ffi::SSL_accept(self.ssl.as_ptr()) };
-        if ret > 0 {
-            Ok(())
-        } else {
-            Err(self.make_error(ret))
-        }
+        self.handshake_result(ret)
     }
 
     /// Initiates the handshake.
@@ -3608,12 +5371,25 @@ impl<S: Read + Write> SslStream<S> {
     /// This will fail if `set_accept_state` or `set_connect_state` was not called first.
     #[corresponds(SSL_do_handshake)]
     pub fn do_handshake(&mut self) -> Result<(), Error> {
+        self.check_panic()?;
         let ret = unsafe { ffi::SSL_do_handshake(self.ssl.as_ptr()) };
-        if ret > 0 {
-            Ok(())
-        } else {
-            Err(self.make_error(ret))
-        }
+        self.handshake_result(ret)
+    }
+
+    /// Requests that a new TLS 1.3 session ticket be sent to the client.
+    ///
+    /// This can only be called on a server after the handshake has completed. The ticket is sent
+    /// with the next write or by the next call to [`do_handshake`], in addition to the tickets sent
+    /// after the handshake, so that the client can resume that many more connections, for example
+    /// when pooling connections to the server.
+    ///
+    /// Requires OpenSSL 3.0.0 or newer.
+    ///
+    /// [`do_handshake`]: SslStream::do_handshake
+    #[corresponds(SSL_new_session_ticket)]
+    #[cfg(ossl300)]
+    pub fn new_session_ticket(&mut self) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::SSL_new_session_ticket(self.ssl.as_ptr())).map(|_| ()) }
     }
 
     /// Perform a stateless server-side handshake.
@@ -3632,11 +5408,29 @@ impl<S: Read + Write> SslStream<S> {
         match unsafe { ffi::SSL_stateless(self.ssl.as_ptr()) } {
             1 => Ok(true),
             0 => Ok(false),
-            -1 => Err(ErrorStack::get()),
+            -1 => Err(ErrorStack::report()),
             _ => unreachable!(),
         }
     }
 
+    /// Retransmits the current DTLS handshake flight if the retransmission timer has expired.
+    ///
+    /// Returns `Ok(true)` if the flight was retransmitted, and `Ok(false)` if the timer has not
+    /// expired or is not running. An error is returned if retransmitting the flight failed or if
+    /// it had been retransmitted too many times without a response.
+    ///
+    /// [`SslRef::dtls_timeout`] returns the time at which this should next be called.
+    #[corresponds(DTLSv1_handle_timeout)]
+    #[cfg(not(boringssl))]
+    pub fn handle_dtls_timeout(&mut self) -> Result<bool, Error> {
+        self.check_panic()?;
+        match unsafe { ffi::DTLSv1_handle_timeout(self.ssl.as_ptr()) } as c_int {
+            0 => Ok(false),
+            ret if ret > 0 => Ok(true),
+            ret => Err(self.make_error(ret)),
+        }
+    }
+
     /// Like `read`, but returns an `ssl::Error` rather than an `io::Error`.
     ///
     /// It is particularly useful with a non-blocking socket, where the error value will identify if
@@ -3652,6 +5446,7 @@ impl<S: Read + Write> SslStream<S> {
             return Ok(0);
         }
 
+        self.check_panic()?;
         let ret = self.ssl.read(buf);
         if ret > 0 {
             Ok(ret as usize)
@@ -3671,6 +5466,7 @@ impl<S: Read + Write> SslStream<S> {
             return Ok(0);
         }
 
+        self.check_panic()?;
         let ret = self.ssl.write(buf);
         if ret > 0 {
             Ok(ret as usize)
@@ -3687,6 +5483,7 @@ impl<S: Read + Write> SslStream<S> {
             return Ok(0);
         }
 
+        self.check_panic()?;
         let ret = self.ssl.peek(buf);
         if ret > 0 {
             Ok(ret as usize)
@@ -3706,6 +5503,7 @@ impl<S: Read + Write> SslStream<S> {
     /// further communication in the future.
     #[corresponds(SSL_shutdown)]
     pub fn shutdown(&mut self) -> Result<ShutdownResult, Error> {
+        self.check_panic()?;
         match unsafe { ffi::SSL_shutdown(self.ssl.as_ptr()) } {
             0 => Ok(ShutdownResult::Sent),
             1 => Ok(ShutdownResult::Received),
@@ -3713,6 +5511,44 @@ impl<S: Read + Write> SslStream<S> {
         }
     }
 
+    /// Fully shuts down the session.
+    ///
+    /// A close notify message is sent to the peer if that has not already happened, and then the
+    /// peer's close notify message is awaited. Any application data received from the peer in the
+    /// meantime is discarded.
+    ///
+    /// With a nonblocking stream this returns an error with a code of `WANT_READ` or `WANT_WRITE`
+    /// when the underlying stream is not ready. The method can then be called again to resume the
+    /// shutdown from where it left off. The same applies to a blocking stream with a read timeout,
+    /// though see [`SslStream::shutdown_timeout`] for a convenience wrapper around that case.
+    #[corresponds(SSL_shutdown)]
+    pub fn shutdown_complete(&mut self) -> Result<(), Error> {
+        let state = self.get_shutdown();
+        // resume a close notify which couldn't be written out in full
+        let flush = unsafe { ffi::SSL_want(self.ssl.as_ptr()) == ffi::SSL_WRITING };
+        if !state.contains(ShutdownState::SENT) || flush {
+            if let ShutdownResult::Received = self.shutdown()? {
+                return Ok(());
+            }
+        }
+
+        // OpenSSL treats application data as an error when it is encountered by SSL_shutdown, so
+        // wait for the peer's close notify via SSL_read instead.
+        let mut buf = [0; 1024];
+        loop {
+            match self.ssl_read(&mut buf) {
+                Ok(_) => {}
+                Err(ref e) if e.code() == ErrorCode::ZERO_RETURN => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        // this only updates the session's state since both close notify messages have been
+        // exchanged
+        self.shutdown()?;
+        Ok(())
+    }
+
     /// Returns the session's shutdown state.
     #[corresponds(SSL_get_shutdown)]
     pub fn get_shutdown(&mut self) -> ShutdownState {
@@ -3733,15 +5569,26 @@ impl<S: Read + Write> SslStream<S> {
 }
 
 impl<S> SslStream<S> {
+    fn handshake_result(&mut self, ret: c_int) -> Result<(), Error> {
+        if ret > 0 {
+            return Ok(());
+        }
+        let error = self.make_error(ret);
+        stats::record_handshake_failure(&self.ssl, &error);
+        Err(error)
+    }
+
     fn make_error(&mut self, ret: c_int) -> Error {
-        self.check_panic();
+        if let Err(error) = self.check_panic() {
+            return error;
+        }
 
         let code = self.ssl.get_error(ret);
 
         let cause = match code {
-            ErrorCode::SSL => Some(InnerError::Ssl(ErrorStack::get())),
+            ErrorCode::SSL => Some(InnerError::Ssl(ErrorStack::report())),
             ErrorCode::SYSCALL => {
-                let errs = ErrorStack::get();
+                let errs = ErrorStack::report();
                 if errs.errors().is_empty() {
                     self.get_bio_error().map(InnerError::Io)
                 } else {
@@ -3758,9 +5605,18 @@ impl<S> SslStream<S> {
         Error { code, cause }
     }
 
-    fn check_panic(&mut self) {
-        if let Some(err) = unsafe { bio::take_panic::<S>(self.ssl.get_raw_rbio()) } {
-            resume_unwind(err)
+    /// Handles a panic of the underlying stream or of a callback, failing if the session has been
+    /// poisoned by one.
+    fn check_panic(&mut self) -> Result<(), Error> {
+        if let Some(payload) = unsafe { bio::take_panic::<S>(self.ssl.get_raw_rbio()) } {
+            callbacks::record_panic(&mut self.ssl, payload);
+        }
+        match callbacks::check_panic(&mut self.ssl) {
+            Some(msg) => Err(Error {
+                code: ErrorCode::SYSCALL,
+                cause: Some(InnerError::Io(io::Error::new(io::ErrorKind::Other, msg))),
+            }),
+            None => Ok(()),
         }
     }
 
@@ -3793,6 +5649,81 @@ impl<S> SslStream<S> {
     pub fn ssl(&self) -> &SslRef {
         &self.ssl
     }
+
+    /// Consumes the stream, returning the underlying transport.
+    ///
+    /// This is used to continue in plaintext once the session has ended, after close notify
+    /// messages have been exchanged with [`shutdown`], or at a point where the application
+    /// protocol guarantees that no further TLS records are in flight. Application data which has
+    /// been decrypted but not read, and any records OpenSSL has read ahead, are discarded. Bytes
+    /// passed to [`from_parts`] which have not been processed are discarded as well; use
+    /// [`into_parts`] to retrieve them.
+    ///
+    /// [`shutdown`]: SslStream::shutdown
+    /// [`from_parts`]: SslStream::from_parts
+    /// [`into_parts`]: SslStream::into_parts
+    pub fn into_inner(self) -> S {
+        self.into_parts().0
+    }
+
+    /// Consumes the stream, returning the underlying transport and the bytes passed to
+    /// [`from_parts`] which OpenSSL has not processed.
+    ///
+    /// See [`into_inner`] for when this can be used.
+    ///
+    /// [`from_parts`]: SslStream::from_parts
+    /// [`into_inner`]: SslStream::into_inner
+    pub fn into_parts(self) -> (S, Vec<u8>) {
+        let mut this = ManuallyDrop::new(self);
+        unsafe {
+            let parts = bio::take_stream::<S>(this.ssl.get_raw_rbio());
+            // ssl holds a reference to method internally so it has to drop first
+            ManuallyDrop::drop(&mut this.ssl);
+            ManuallyDrop::drop(&mut this.method);
+            parts
+        }
+    }
+}
+
+impl SslStream<TcpStream> {
+    /// Fully shuts down the session, waiting at most `timeout` for the peer's close notify message.
+    ///
+    /// A close notify message is sent to the peer if that has not already happened. Returns
+    /// `ShutdownResult::Received` if the peer's close notify message arrives before the timeout
+    /// elapses, and `ShutdownResult::Sent` otherwise, in which case the connection can simply be
+    /// closed. Any application data received from the peer in the meantime is discarded.
+    ///
+    /// The socket's read timeout is adjusted while waiting and restored before returning.
+    #[corresponds(SSL_shutdown)]
+    pub fn shutdown_timeout(&mut self, timeout: Duration) -> Result<ShutdownResult, Error> {
+        let previous = self.get_ref().read_timeout().map_err(Error::from_io)?;
+        let deadline = Instant::now() + timeout;
+        let result = self.shutdown_until(deadline);
+        let restored = self.get_ref().set_read_timeout(previous);
+        let result = result?;
+        restored.map_err(Error::from_io)?;
+        Ok(result)
+    }
+
+    fn shutdown_until(&mut self, deadline: Instant) -> Result<ShutdownResult, Error> {
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining == Duration::from_secs(0)
+                && self.get_shutdown().contains(ShutdownState::SENT)
+            {
+                return Ok(ShutdownResult::Sent);
+            }
+            self.get_ref()
+                .set_read_timeout(Some(remaining.max(Duration::from_millis(1))))
+                .map_err(Error::from_io)?;
+
+            match self.shutdown_complete() {
+                Ok(()) => return Ok(ShutdownResult::Received),
+                Err(ref e) if e.is_timeout() => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
 }
 
 impl<S: Read + Write> Read for SslStream<S> {
@@ -3875,7 +5806,7 @@ where
         match unsafe { ffi::SSL_stateless(self.inner.ssl.as_ptr()) } {
             1 => Ok(true),
             0 => Ok(false),
-            -1 => Err(ErrorStack::get()),
+            -1 => Err(ErrorStack::report()),
             _ => unreachable!(),
         }
     }
@@ -4039,6 +5970,17 @@ impl<S> SslStreamBuilder<S> {
     }
 }
 
+/// A TLS 1.3 downgrade protection sentinel.
+#[cfg(any(ossl110, libressl270))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DowngradeSentinel {
+    /// The server supports TLS 1.3, but negotiated TLS 1.2.
+    Tls12,
+
+    /// The server supports TLS 1.2 or newer, but negotiated TLS 1.1 or older.
+    Tls11,
+}
+
 /// The result of a shutdown request.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ShutdownResult {
@@ -4141,15 +6083,63 @@ cfg_if! {
         }
 
         unsafe fn get_new_ssl_idx(f: ffi::CRYPTO_EX_free) -> c_int {
+            get_new_ssl_idx_with_dup(f, dup_data_unsupported)
+        }
+
+        unsafe fn get_new_ssl_idx_with_dup(f: ffi::CRYPTO_EX_free, dup: ffi::CRYPTO_EX_dup) -> c_int {
             ffi::CRYPTO_get_ex_new_index(
                 ffi::CRYPTO_EX_INDEX_SSL,
                 0,
                 ptr::null_mut(),
                 None,
-                None,
+                Some(dup),
                 Some(f),
             )
         }
+
+        #[cfg(ossl300)]
+        type ExDupFromD = *mut *mut c_void;
+        // OpenSSL 1.1.x declares this as a `void *`, but it points to the data pointer
+        #[cfg(not(ossl300))]
+        type ExDupFromD = *mut c_void;
+
+        #[allow(clippy::unnecessary_cast)]
+        unsafe extern "C" fn dup_data_box<T: Clone>(
+            _to: *mut ffi::CRYPTO_EX_DATA,
+            _from: *const ffi::CRYPTO_EX_DATA,
+            from_d: ExDupFromD,
+            _idx: c_int,
+            _argl: c_long,
+            _argp: *mut c_void,
+        ) -> c_int {
+            let ptr = from_d as *mut *mut c_void;
+            if !(*ptr).is_null() {
+                let data = &*(*ptr as *const T);
+                *ptr = Box::into_raw(Box::new(data.clone())) as *mut c_void;
+            }
+            1
+        }
+
+        // Rust values can't be shared between two owners, so duplication fails unless the index
+        // is empty. The copied pointer is cleared first, since OpenSSL 3 stores it in the new
+        // object even when duplication fails.
+        #[allow(clippy::unnecessary_cast)]
+        unsafe extern "C" fn dup_data_unsupported(
+            _to: *mut ffi::CRYPTO_EX_DATA,
+            _from: *const ffi::CRYPTO_EX_DATA,
+            from_d: ExDupFromD,
+            _idx: c_int,
+            _argl: c_long,
+            _argp: *mut c_void,
+        ) -> c_int {
+            let ptr = from_d as *mut *mut c_void;
+            if (*ptr).is_null() {
+                1
+            } else {
+                *ptr = ptr::null_mut();
+                0
+            }
+        }
     } else {
         use std::sync::Once;
 
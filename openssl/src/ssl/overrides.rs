@@ -0,0 +1,138 @@
+use foreign_types::ForeignTypeRef;
+use libc::c_int;
+use std::fmt;
+
+use crate::error::ErrorStack;
+#[cfg(any(ossl102, libressl261))]
+use crate::ssl::Ssl;
+use crate::ssl::{SslRef, SslVerifyMode};
+#[cfg(any(boringssl, ossl110, libressl270))]
+use crate::stack::Stack;
+use crate::x509::{X509Name, X509NameRef};
+
+/// A set of per-connection configuration overrides.
+///
+/// A server handling many tenants can keep one `SslOverrides` per tenant and return it from the
+/// callback registered with [`SslContextBuilder::set_servername_overrides_callback`], rather than
+/// building a separate [`SslContext`] for every tenant. Overrides can also be applied directly
+/// with [`SslRef::apply_overrides`]. Settings which are not overridden are inherited from the
+/// context.
+///
+/// [`SslContextBuilder::set_servername_overrides_callback`]: crate::ssl::SslContextBuilder::set_servername_overrides_callback
+/// [`SslContext`]: crate::ssl::SslContext
+/// [`SslRef::apply_overrides`]: crate::ssl::SslRef::apply_overrides
+#[derive(Default)]
+pub struct SslOverrides {
+    verify_mode: Option<SslVerifyMode>,
+    alpn_protos: Option<Vec<u8>>,
+    client_ca_list: Option<Vec<X509Name>>,
+    cipher_list: Option<String>,
+}
+
+impl SslOverrides {
+    /// Creates a new, empty set of overrides.
+    pub fn new() -> SslOverrides {
+        SslOverrides::default()
+    }
+
+    /// Overrides the certificate verification mode.
+    ///
+    /// Unlike [`SslRef::set_verify`], this keeps any verification callback configured on the
+    /// context.
+    ///
+    /// [`SslRef::set_verify`]: crate::ssl::SslRef::set_verify
+    pub fn set_verify(&mut self, mode: SslVerifyMode) -> &mut SslOverrides {
+        self.verify_mode = Some(mode);
+        self
+    }
+
+    /// Overrides the list of protocols a server selects from during ALPN, in order of preference.
+    ///
+    /// The protocols must be in the ALPN wire format, as for
+    /// [`SslContextBuilder::set_alpn_protos`]. The first protocol in the list supported by the
+    /// client is selected, and the handshake proceeds without ALPN if there is none.
+    ///
+    /// ALPN selection only takes place if the context has a callback registered with
+    /// [`SslContextBuilder::set_alpn_select_callback`]. The override is used instead of that
+    /// callback.
+    ///
+    /// Requires OpenSSL 1.0.2 or LibreSSL 2.6.1 or newer.
+    ///
+    /// [`SslContextBuilder::set_alpn_protos`]: crate::ssl::SslContextBuilder::set_alpn_protos
+    /// [`SslContextBuilder::set_alpn_select_callback`]: crate::ssl::SslContextBuilder::set_alpn_select_callback
+    #[cfg(any(ossl102, libressl261))]
+    pub fn set_alpn_protos(&mut self, protocols: &[u8]) -> &mut SslOverrides {
+        self.alpn_protos = Some(protocols.to_vec());
+        self
+    }
+
+    /// Overrides the list of CA names sent to the client when requesting a client certificate.
+    ///
+    /// Requires OpenSSL 1.1.0, LibreSSL 2.7.0, or BoringSSL.
+    #[cfg(any(boringssl, ossl110, libressl270))]
+    pub fn set_client_ca_list(&mut self, list: Vec<X509Name>) -> &mut SslOverrides {
+        self.client_ca_list = Some(list);
+        self
+    }
+
+    /// Overrides the list of supported ciphers for protocols before TLSv1.3.
+    ///
+    /// See [`SslRef::set_cipher_list`] for details on the format. The TLSv1.3 cipher suites are
+    /// chosen before the server name callback is called, so they cannot be overridden there.
+    ///
+    /// [`SslRef::set_cipher_list`]: crate::ssl::SslRef::set_cipher_list
+    pub fn set_cipher_list(&mut self, cipher_list: &str) -> &mut SslOverrides {
+        self.cipher_list = Some(cipher_list.to_string());
+        self
+    }
+
+    pub(crate) fn apply(&self, ssl: &mut SslRef) -> Result<(), ErrorStack> {
+        if let Some(mode) = self.verify_mode {
+            unsafe {
+                let callback = ffi::SSL_get_verify_callback(ssl.as_ptr());
+                ffi::SSL_set_verify(ssl.as_ptr(), mode.bits() as c_int, callback);
+            }
+        }
+        #[cfg(any(ossl102, libressl261))]
+        if let Some(protocols) = &self.alpn_protos {
+            let index = Ssl::cached_ex_index::<AlpnOverride>();
+            match ssl.ex_data_mut(index) {
+                Some(alpn) => alpn.0 = protocols.clone(),
+                None => ssl.set_ex_data(index, AlpnOverride(protocols.clone())),
+            }
+        }
+        #[cfg(any(boringssl, ossl110, libressl270))]
+        if let Some(names) = &self.client_ca_list {
+            let mut list = Stack::new()?;
+            for name in names {
+                list.push(X509NameRef::to_owned(name)?)?;
+            }
+            ssl.set_client_ca_list(list);
+        }
+        if let Some(cipher_list) = &self.cipher_list {
+            ssl.set_cipher_list(cipher_list)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for SslOverrides {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("SslOverrides")
+            .field("verify_mode", &self.verify_mode)
+            .field("alpn_protos", &self.alpn_protos)
+            .field(
+                "client_ca_list",
+                &self
+                    .client_ca_list
+                    .as_ref()
+                    .map(|names| names.iter().map(|n| &**n).collect::<Vec<&X509NameRef>>()),
+            )
+            .field("cipher_list", &self.cipher_list)
+            .finish()
+    }
+}
+
+/// The ALPN protocol list of an `SslOverrides`, stored in the connection's ex data.
+#[cfg(any(ossl102, libressl261))]
+pub(crate) struct AlpnOverride(pub(crate) Vec<u8>);
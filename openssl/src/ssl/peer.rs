@@ -0,0 +1,107 @@
+#[cfg(unix)]
+use std::io;
+#[cfg(unix)]
+use std::mem;
+use std::net::SocketAddr;
+#[cfg(unix)]
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, RawFd};
+
+/// The credentials of the process on the other end of a Unix domain socket.
+///
+/// The credentials are those of the peer when it connected, or when it created the socket pair.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PeerCredentials {
+    /// The peer's process ID.
+    pub pid: libc::pid_t,
+
+    /// The peer's effective user ID.
+    pub uid: libc::uid_t,
+
+    /// The peer's effective group ID.
+    pub gid: libc::gid_t,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl PeerCredentials {
+    /// Returns the credentials of the peer of a Unix domain socket.
+    ///
+    /// This corresponds to the `SO_PEERCRED` socket option.
+    pub fn from_socket<S>(socket: &S) -> io::Result<PeerCredentials>
+    where
+        S: AsRawFd,
+    {
+        unsafe {
+            let mut cred = mem::zeroed::<libc::ucred>();
+            let mut len = mem::size_of::<libc::ucred>() as libc::socklen_t;
+            let ret = libc::getsockopt(
+                socket.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_PEERCRED,
+                &mut cred as *mut libc::ucred as *mut libc::c_void,
+                &mut len,
+            );
+            if ret != 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(PeerCredentials {
+                pid: cred.pid,
+                uid: cred.uid,
+                gid: cred.gid,
+            })
+        }
+    }
+}
+
+pub(crate) struct PeerAddr(pub SocketAddr);
+
+#[cfg(unix)]
+pub(crate) enum PeerName {
+    Ip(SocketAddr),
+    Unix,
+    Other,
+}
+
+/// Returns the name of the peer of a socket.
+#[cfg(unix)]
+pub(crate) fn peer_name(fd: RawFd) -> io::Result<PeerName> {
+    unsafe {
+        let mut storage = mem::zeroed::<libc::sockaddr_storage>();
+        let mut len = mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+        let ret = libc::getpeername(
+            fd,
+            &mut storage as *mut libc::sockaddr_storage as *mut libc::sockaddr,
+            &mut len,
+        );
+        if ret != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        match storage.ss_family as libc::c_int {
+            libc::AF_INET => {
+                let addr =
+                    &*(&storage as *const libc::sockaddr_storage as *const libc::sockaddr_in);
+                let ip = Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr));
+                let port = u16::from_be(addr.sin_port);
+                Ok(PeerName::Ip(SocketAddr::V4(SocketAddrV4::new(ip, port))))
+            }
+            libc::AF_INET6 => {
+                let addr =
+                    &*(&storage as *const libc::sockaddr_storage as *const libc::sockaddr_in6);
+                let ip = Ipv6Addr::from(addr.sin6_addr.s6_addr);
+                let port = u16::from_be(addr.sin6_port);
+                Ok(PeerName::Ip(SocketAddr::V6(SocketAddrV6::new(
+                    ip,
+                    port,
+                    addr.sin6_flowinfo,
+                    addr.sin6_scope_id,
+                ))))
+            }
+            libc::AF_UNIX => Ok(PeerName::Unix),
+            _ => Ok(PeerName::Other),
+        }
+    }
+}
@@ -0,0 +1,420 @@
+use foreign_types::ForeignTypeRef;
+use once_cell::sync::OnceCell;
+use std::cmp::Ordering;
+#[cfg(ossl102)]
+use std::ffi::CString;
+use std::fmt;
+#[cfg(ossl102)]
+use std::net::IpAddr;
+#[cfg(ossl102)]
+use std::ptr;
+
+use crate::error::ErrorStack;
+use crate::ex_data::Index;
+use crate::hash::{hash, MessageDigest};
+use crate::ssl::Ssl;
+use crate::stack::StackRef;
+use crate::x509::{
+    CrlStatus, X509Crl, X509Ref, X509StoreContext, X509StoreContextRef, X509VerifyResult, X509,
+};
+
+static DOMAIN_IDX: OnceCell<Index<Ssl, String>> = OnceCell::new();
+
+/// Marks an `SslContext` which has a pipeline installed by `SslConnectorBuilder`.
+pub(crate) struct Installed;
+
+/// Returns the index of the domain an `Ssl` created by `ConnectConfiguration` connects to.
+pub(crate) fn try_get_domain_idx() -> Result<&'static Index<Ssl, String>, ErrorStack> {
+    DOMAIN_IDX.get_or_try_init(Ssl::new_ex_index)
+}
+
+/// A step of a [`VerifyPipeline`].
+///
+/// Steps are run after OpenSSL has verified the peer's certificate chain, and can only reject a
+/// chain which OpenSSL accepted. Closures taking a [`VerifyContext`] implement this trait.
+pub trait VerifyStep: Send + Sync + 'static {
+    /// Checks the verified chain, returning the reason it is rejected on failure.
+    fn verify(&self, ctx: &VerifyContext<'_>) -> Result<(), X509VerifyResult>;
+}
+
+impl<F> VerifyStep for F
+where
+    F: Fn(&VerifyContext<'_>) -> Result<(), X509VerifyResult> + Send + Sync + 'static,
+{
+    fn verify(&self, ctx: &VerifyContext<'_>) -> Result<(), X509VerifyResult> {
+        self(ctx)
+    }
+}
+
+/// The chain being checked by a [`VerifyPipeline`].
+pub struct VerifyContext<'a> {
+    chain: &'a StackRef<X509>,
+    domain: Option<&'a str>,
+}
+
+impl<'a> VerifyContext<'a> {
+    /// Returns the verified chain, from the peer's certificate to the trust anchor.
+    pub fn chain(&self) -> &'a StackRef<X509> {
+        self.chain
+    }
+
+    /// Returns the peer's certificate.
+    pub fn leaf(&self) -> &'a X509Ref {
+        &self.chain[0]
+    }
+
+    /// Returns the domain passed to [`SslConnector::connect`] or [`ConnectConfiguration::into_ssl`],
+    /// if any.
+    ///
+    /// [`SslConnector::connect`]: crate::ssl::SslConnector::connect
+    /// [`ConnectConfiguration::into_ssl`]: crate::ssl::ConnectConfiguration::into_ssl
+    pub fn domain(&self) -> Option<&'a str> {
+        self.domain
+    }
+}
+
+/// An ordered list of checks applied to verified certificate chains.
+///
+/// A pipeline is installed on a connector with [`SslConnectorBuilder::set_verify_pipeline`]. Each
+/// step runs in turn once OpenSSL has verified the peer's chain, and the first step to fail
+/// aborts the handshake with the error it returns.
+///
+/// [`SslConnectorBuilder::set_verify_pipeline`]: crate::ssl::SslConnectorBuilder::set_verify_pipeline
+#[derive(Default)]
+pub struct VerifyPipeline {
+    steps: Vec<Box<dyn VerifyStep>>,
+}
+
+impl VerifyPipeline {
+    /// Creates a new, empty pipeline.
+    pub fn new() -> VerifyPipeline {
+        VerifyPipeline::default()
+    }
+
+    /// Appends a step to the pipeline.
+    pub fn step<S>(&mut self, step: S) -> &mut VerifyPipeline
+    where
+        S: VerifyStep,
+    {
+        self.steps.push(Box::new(step));
+        self
+    }
+
+    /// Runs the steps of the pipeline against `chain`, which must already have been verified.
+    ///
+    /// An empty chain is rejected with `X509VerifyResult::APPLICATION_VERIFICATION`.
+    pub fn verify(
+        &self,
+        chain: &StackRef<X509>,
+        domain: Option<&str>,
+    ) -> Result<(), X509VerifyResult> {
+        if chain.is_empty() {
+            return Err(X509VerifyResult::APPLICATION_VERIFICATION);
+        }
+
+        let ctx = VerifyContext { chain, domain };
+        self.steps.iter().try_for_each(|step| step.verify(&ctx))
+    }
+
+    pub(crate) fn verify_callback(
+        &self,
+        preverify_ok: bool,
+        x509_ctx: &mut X509StoreContextRef,
+    ) -> bool {
+        // the chain is complete once OpenSSL reaches the peer's certificate
+        if !preverify_ok || x509_ctx.error_depth() != 0 {
+            return preverify_ok;
+        }
+
+        let result = match x509_ctx.chain() {
+            Some(chain) => {
+                let domain = match (X509StoreContext::ssl_idx(), try_get_domain_idx()) {
+                    (Ok(ssl_idx), Ok(domain_idx)) => x509_ctx
+                        .ex_data(ssl_idx)
+                        .and_then(|ssl| ssl.ex_data(*domain_idx))
+                        .map(|domain| &**domain),
+                    _ => None,
+                };
+                self.verify(chain, domain)
+            }
+            None => Err(X509VerifyResult::APPLICATION_VERIFICATION),
+        };
+
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                x509_ctx.set_error(e);
+                false
+            }
+        }
+    }
+}
+
+impl fmt::Debug for VerifyPipeline {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("VerifyPipeline")
+            .field("steps", &self.steps.len())
+            .finish()
+    }
+}
+
+/// A step which checks the peer's certificate against a DNS name or IP address.
+///
+/// Wildcards only match entire labels, as with the hostname verification of `SslConnector`.
+///
+/// Requires OpenSSL 1.0.2 or newer.
+#[cfg(ossl102)]
+#[derive(Debug, Clone, Default)]
+pub struct HostnameCheck {
+    name: Option<String>,
+}
+
+#[cfg(ossl102)]
+impl HostnameCheck {
+    /// Creates a step which checks the domain of the connection.
+    ///
+    /// A chain being verified without a domain is rejected.
+    pub fn new() -> HostnameCheck {
+        HostnameCheck::default()
+    }
+
+    /// Creates a step which checks `name`, regardless of the domain of the connection.
+    pub fn name(name: &str) -> HostnameCheck {
+        HostnameCheck {
+            name: Some(name.to_string()),
+        }
+    }
+}
+
+#[cfg(ossl102)]
+impl VerifyStep for HostnameCheck {
+    fn verify(&self, ctx: &VerifyContext<'_>) -> Result<(), X509VerifyResult> {
+        let mismatch = unsafe { X509VerifyResult::from_raw(ffi::X509_V_ERR_HOSTNAME_MISMATCH) };
+        let name = match self.name.as_deref().or_else(|| ctx.domain()) {
+            Some(name) => name,
+            None => return Err(mismatch),
+        };
+
+        let leaf = ctx.leaf().as_ptr();
+        let r = unsafe {
+            match name.parse::<IpAddr>() {
+                Ok(_) => {
+                    let name = CString::new(name).unwrap();
+                    ffi::X509_check_ip_asc(leaf, name.as_ptr(), 0)
+                }
+                Err(_) => ffi::X509_check_host(
+                    leaf,
+                    name.as_ptr() as *const _,
+                    name.len(),
+                    ffi::X509_CHECK_FLAG_NO_PARTIAL_WILDCARDS,
+                    ptr::null_mut(),
+                ),
+            }
+        };
+        if r == 1 {
+            Ok(())
+        } else {
+            Err(mismatch)
+        }
+    }
+}
+
+/// A step which requires a certificate of the chain to have one of a set of public keys.
+///
+/// Keys are pinned by the SHA-256 digest of their DER-encoded SubjectPublicKeyInfo, the format
+/// used by HTTP Public Key Pinning. Pinning the key of an intermediate or root certificate
+/// accepts every certificate it issues. A chain without a pinned key is rejected with
+/// `X509VerifyResult::APPLICATION_VERIFICATION`.
+#[derive(Debug, Clone, Default)]
+pub struct SpkiPinning {
+    pins: Vec<Vec<u8>>,
+}
+
+impl SpkiPinning {
+    /// Creates a step with no pinned keys, which rejects every chain.
+    pub fn new() -> SpkiPinning {
+        SpkiPinning::default()
+    }
+
+    /// Pins the key with the SHA-256 SubjectPublicKeyInfo digest `digest`.
+    pub fn pin_sha256(&mut self, digest: &[u8]) -> &mut SpkiPinning {
+        self.pins.push(digest.to_vec());
+        self
+    }
+
+    /// Pins the public key of `cert`.
+    pub fn pin_certificate(&mut self, cert: &X509Ref) -> Result<&mut SpkiPinning, ErrorStack> {
+        let digest = spki_sha256(cert)?;
+        self.pins.push(digest);
+        Ok(self)
+    }
+}
+
+impl VerifyStep for SpkiPinning {
+    fn verify(&self, ctx: &VerifyContext<'_>) -> Result<(), X509VerifyResult> {
+        for cert in ctx.chain() {
+            if let Ok(digest) = spki_sha256(cert) {
+                if self.pins.contains(&digest) {
+                    return Ok(());
+                }
+            }
+        }
+        Err(X509VerifyResult::APPLICATION_VERIFICATION)
+    }
+}
+
+fn spki_sha256(cert: &X509Ref) -> Result<Vec<u8>, ErrorStack> {
+    let spki = cert.public_key()?.public_key_to_der()?;
+    Ok(hash(MessageDigest::sha256(), &spki)?.to_vec())
+}
+
+/// A step which requires the peer's certificate to embed signed certificate timestamps.
+///
+/// Only the presence of the timestamps and the logs they were issued by are checked: their
+/// signatures are not verified. A certificate without enough timestamps is rejected with
+/// `X509VerifyResult::APPLICATION_VERIFICATION`.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+#[derive(Debug, Clone)]
+pub struct SctPolicy {
+    min_logs: usize,
+    logs: Vec<Vec<u8>>,
+}
+
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+impl Default for SctPolicy {
+    fn default() -> SctPolicy {
+        SctPolicy::new()
+    }
+}
+
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+impl SctPolicy {
+    /// Creates a step which requires a timestamp from at least one log.
+    pub fn new() -> SctPolicy {
+        SctPolicy {
+            min_logs: 1,
+            logs: vec![],
+        }
+    }
+
+    /// Sets the number of distinct logs the certificate must have timestamps from.
+    pub fn min_logs(&mut self, min_logs: usize) -> &mut SctPolicy {
+        self.min_logs = min_logs;
+        self
+    }
+
+    /// Only counts timestamps issued by the log with the ID `log_id`.
+    ///
+    /// This may be called multiple times. By default, timestamps from any log are counted.
+    pub fn trusted_log(&mut self, log_id: &[u8]) -> &mut SctPolicy {
+        self.logs.push(log_id.to_vec());
+        self
+    }
+}
+
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+impl VerifyStep for SctPolicy {
+    fn verify(&self, ctx: &VerifyContext<'_>) -> Result<(), X509VerifyResult> {
+        let mut logs: Vec<&[u8]> = vec![];
+        let scts = ctx.leaf().scts();
+        for sct in scts.iter().flatten() {
+            let log_id = sct.log_id();
+            let trusted = self.logs.is_empty() || self.logs.iter().any(|log| log == log_id);
+            if trusted && !logs.contains(&log_id) {
+                logs.push(log_id);
+            }
+        }
+
+        if logs.len() >= self.min_logs {
+            Ok(())
+        } else {
+            Err(X509VerifyResult::APPLICATION_VERIFICATION)
+        }
+    }
+}
+
+/// A step which checks the certificates of the chain against certificate revocation lists.
+///
+/// A list applies to a certificate if it was issued by the certificate's issuer and is signed by
+/// the next certificate of the chain, and lists whose next update time has passed are ignored.
+/// The trust anchor itself is not checked. A revoked certificate is rejected with
+/// `X509_V_ERR_CERT_REVOKED`.
+#[derive(Default)]
+pub struct RevocationCheck {
+    crls: Vec<X509Crl>,
+    require_crl: bool,
+}
+
+impl RevocationCheck {
+    /// Creates a step with no revocation lists.
+    pub fn new() -> RevocationCheck {
+        RevocationCheck::default()
+    }
+
+    /// Adds a revocation list.
+    pub fn crl(&mut self, crl: X509Crl) -> &mut RevocationCheck {
+        self.crls.push(crl);
+        self
+    }
+
+    /// Rejects certificates which no revocation list applies to with
+    /// `X509_V_ERR_UNABLE_TO_GET_CRL`.
+    ///
+    /// By default, such certificates are accepted.
+    pub fn require_crl(&mut self) -> &mut RevocationCheck {
+        self.require_crl = true;
+        self
+    }
+
+    fn find_crl(&self, cert: &X509Ref, issuer: &X509Ref) -> Option<&X509Crl> {
+        let key = issuer.public_key().ok()?;
+        self.crls.iter().find(|crl| {
+            crl.issuer_name().try_cmp(cert.issuer_name()).ok() == Some(Ordering::Equal)
+                && !is_expired(crl)
+                && crl.verify(&key).unwrap_or(false)
+        })
+    }
+}
+
+fn is_expired(crl: &X509Crl) -> bool {
+    match crl.next_update() {
+        // 0 is returned if the time cannot be parsed, which is treated as expired
+        Some(next_update) => unsafe { ffi::X509_cmp_current_time(next_update.as_ptr()) <= 0 },
+        None => false,
+    }
+}
+
+impl VerifyStep for RevocationCheck {
+    fn verify(&self, ctx: &VerifyContext<'_>) -> Result<(), X509VerifyResult> {
+        let chain = ctx.chain();
+        for (cert, issuer) in chain.iter().zip(chain.iter().skip(1)) {
+            match self.find_crl(cert, issuer) {
+                Some(crl) => {
+                    if let CrlStatus::Revoked(_) = crl.get_by_cert(&cert.to_owned()) {
+                        return Err(unsafe {
+                            X509VerifyResult::from_raw(ffi::X509_V_ERR_CERT_REVOKED)
+                        });
+                    }
+                }
+                None if self.require_crl => {
+                    return Err(unsafe {
+                        X509VerifyResult::from_raw(ffi::X509_V_ERR_UNABLE_TO_GET_CRL)
+                    });
+                }
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for RevocationCheck {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("RevocationCheck")
+            .field("crls", &self.crls.len())
+            .field("require_crl", &self.require_crl)
+            .finish()
+    }
+}
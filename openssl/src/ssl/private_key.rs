@@ -0,0 +1,83 @@
+use std::fmt;
+
+use crate::ssl::SslRef;
+
+/// An error returned from a [`PrivateKeyMethod`].
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct PrivateKeyMethodError(pub(crate) ffi::ssl_private_key_result_t);
+
+impl PrivateKeyMethodError {
+    /// The operation failed, and the handshake will be aborted.
+    pub const FAILURE: PrivateKeyMethodError =
+        PrivateKeyMethodError(ffi::ssl_private_key_result_t_ssl_private_key_failure);
+
+    /// The operation has not completed yet.
+    ///
+    /// The handshake returns with an `ErrorCode::WANT_PRIVATE_KEY_OPERATION` error. Once the
+    /// operation has finished, the handshake should be resumed, at which point
+    /// [`PrivateKeyMethod::complete`] is called to retrieve the result.
+    pub const RETRY: PrivateKeyMethodError =
+        PrivateKeyMethodError(ffi::ssl_private_key_result_t_ssl_private_key_retry);
+}
+
+impl fmt::Debug for PrivateKeyMethodError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            PrivateKeyMethodError::FAILURE => fmt.write_str("FAILURE"),
+            PrivateKeyMethodError::RETRY => fmt.write_str("RETRY"),
+            PrivateKeyMethodError(other) => write!(fmt, "PrivateKeyMethodError({})", other),
+        }
+    }
+}
+
+/// Private key operations performed outside of the TLS library.
+///
+/// This allows the private key of the certificate to be held elsewhere, such as in a hardware
+/// module or a remote key management service. Operations may complete asynchronously by
+/// returning [`PrivateKeyMethodError::RETRY`]; the handshake then returns with an
+/// `ErrorCode::WANT_PRIVATE_KEY_OPERATION` error, and [`complete`] is called when it is resumed.
+///
+/// The method is installed with [`SslContextBuilder::set_private_key_method`] or
+/// [`SslRef::set_private_key_method`]. A certificate must still be configured, but no private key.
+///
+/// Requires BoringSSL.
+///
+/// [`complete`]: PrivateKeyMethod::complete
+/// [`SslContextBuilder::set_private_key_method`]: crate::ssl::SslContextBuilder::set_private_key_method
+/// [`SslRef::set_private_key_method`]: crate::ssl::SslRef::set_private_key_method
+pub trait PrivateKeyMethod: Send + Sync + 'static {
+    /// Signs `input` with the TLS signature algorithm `signature_algorithm`, for example
+    /// `0x0804` for `rsa_pss_rsae_sha256`.
+    ///
+    /// `input` is the unhashed message to sign. On success, the signature is written to `output`
+    /// and its length is returned.
+    fn sign(
+        &self,
+        ssl: &mut SslRef,
+        input: &[u8],
+        signature_algorithm: u16,
+        output: &mut [u8],
+    ) -> Result<usize, PrivateKeyMethodError>;
+
+    /// Decrypts `input` with the RSA private key, without padding.
+    ///
+    /// This is only used by the RSA key exchange of TLS 1.2 and older. On success, the plaintext
+    /// is written to `output` and its length is returned.
+    fn decrypt(
+        &self,
+        ssl: &mut SslRef,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<usize, PrivateKeyMethodError>;
+
+    /// Retrieves the result of a pending operation.
+    ///
+    /// This is called when the handshake is resumed after [`sign`] or [`decrypt`] returned
+    /// [`PrivateKeyMethodError::RETRY`], and may itself return `RETRY` if the operation has still
+    /// not finished.
+    ///
+    /// [`sign`]: PrivateKeyMethod::sign
+    /// [`decrypt`]: PrivateKeyMethod::decrypt
+    fn complete(&self, ssl: &mut SslRef, output: &mut [u8])
+        -> Result<usize, PrivateKeyMethodError>;
+}
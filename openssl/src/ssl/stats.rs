@@ -0,0 +1,112 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::ssl::{Error, ErrorCode, SslRef};
+use crate::x509::X509VerifyResult;
+
+/// Statistics of the session cache of an [`SslContext`].
+///
+/// The counters are maintained by OpenSSL for every connection created from the context, and
+/// are returned by [`SslContextRef::session_cache_stats`].
+///
+/// [`SslContext`]: crate::ssl::SslContext
+/// [`SslContextRef::session_cache_stats`]: crate::ssl::SslContextRef::session_cache_stats
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct SessionCacheStats {
+    /// The number of sessions currently in the cache.
+    pub sessions: u64,
+
+    /// The number of client handshakes started.
+    pub connects: u64,
+
+    /// The number of client handshakes completed.
+    pub connects_good: u64,
+
+    /// The number of client renegotiations started.
+    pub connects_renegotiate: u64,
+
+    /// The number of server handshakes started.
+    pub accepts: u64,
+
+    /// The number of server handshakes completed.
+    pub accepts_good: u64,
+
+    /// The number of server renegotiations started.
+    pub accepts_renegotiate: u64,
+
+    /// The number of sessions resumed from the internal cache.
+    pub hits: u64,
+
+    /// The number of sessions resumed from an external cache through the get session callback.
+    pub callback_hits: u64,
+
+    /// The number of sessions proposed by clients which were not found in the cache.
+    pub misses: u64,
+
+    /// The number of sessions proposed by clients which were found in the cache but had
+    /// expired.
+    pub timeouts: u64,
+
+    /// The number of sessions which were removed from the cache because it was full.
+    pub cache_full: u64,
+}
+
+// 64 bit atomics are not available on every target, so the counters are only as wide as a
+// pointer and are widened when they are read.
+static IO_FAILURES: AtomicUsize = AtomicUsize::new(0);
+static CERTIFICATE_FAILURES: AtomicUsize = AtomicUsize::new(0);
+static PROTOCOL_FAILURES: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of failed handshakes in this process, by the class of failure.
+///
+/// Every handshake run by an [`SslStream`], [`SslStreamBuilder`], or [`MidHandshakeSslStream`]
+/// is counted, whether it was started through an [`SslConnector`], an [`SslAcceptor`], or
+/// directly. Handshakes which need to be retried because the underlying stream would block are
+/// not failures. The counters start at 0 and are never reset, so that they can be exported as
+/// monotonic metrics. On targets with 32 bit pointers they wrap around after 2^32
+/// failures.
+///
+/// [`SslStream`]: crate::ssl::SslStream
+/// [`SslStreamBuilder`]: crate::ssl::SslStreamBuilder
+/// [`MidHandshakeSslStream`]: crate::ssl::MidHandshakeSslStream
+/// [`SslConnector`]: crate::ssl::SslConnector
+/// [`SslAcceptor`]: crate::ssl::SslAcceptor
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct HandshakeFailures {
+    /// The number of handshakes which failed because of an error of the underlying stream, or
+    /// because the peer closed the connection.
+    pub io: u64,
+
+    /// The number of handshakes which failed because the peer's certificate could not be
+    /// verified.
+    pub certificate: u64,
+
+    /// The number of handshakes which failed for any other reason, such as the peer sending an
+    /// alert or the two sides having no protocol version or cipher in common.
+    pub protocol: u64,
+}
+
+impl HandshakeFailures {
+    /// Returns the total number of failed handshakes.
+    pub fn total(&self) -> u64 {
+        self.io + self.certificate + self.protocol
+    }
+}
+
+/// Returns the number of failed handshakes in this process.
+pub fn handshake_failures() -> HandshakeFailures {
+    HandshakeFailures {
+        io: IO_FAILURES.load(Ordering::Relaxed) as u64,
+        certificate: CERTIFICATE_FAILURES.load(Ordering::Relaxed) as u64,
+        protocol: PROTOCOL_FAILURES.load(Ordering::Relaxed) as u64,
+    }
+}
+
+pub(crate) fn record_handshake_failure(ssl: &SslRef, error: &Error) {
+    let counter = match error.code() {
+        ErrorCode::SYSCALL | ErrorCode::ZERO_RETURN => &IO_FAILURES,
+        ErrorCode::SSL if ssl.verify_result() != X509VerifyResult::OK => &CERTIFICATE_FAILURES,
+        ErrorCode::SSL => &PROTOCOL_FAILURES,
+        _ => return,
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
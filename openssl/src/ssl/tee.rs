@@ -0,0 +1,211 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+
+const RECORD_HEADER_LEN: usize = 5;
+
+/// The direction of data passing through a [`TeeStream`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TeeDirection {
+    /// Data read from the underlying stream.
+    Read,
+    /// Data written to the underlying stream.
+    Write,
+}
+
+/// The data a [`TeeStream`] passes to its sink.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TeeMode {
+    /// Every byte read from or written to the underlying stream.
+    Bytes,
+    /// Only the 5 byte header of each TLS record.
+    ///
+    /// This mode assumes the stream carries TLS rather than DTLS records.
+    RecordHeaders,
+}
+
+/// A stream wrapper which copies the data passing through it to a sink.
+///
+/// A `TeeStream` is intended to be placed underneath an [`SslStream`], where it observes the raw
+/// TLS records exchanged with the peer. This is useful for debugging and conformance testing. The
+/// sink is called with the direction of each chunk of data, and the wrapper can be enabled and
+/// disabled at runtime through [`SslStream::get_mut`].
+///
+/// Record boundaries are tracked even while the wrapper is disabled, so switching to
+/// [`TeeMode::RecordHeaders`] partway through a connection reports correctly aligned headers.
+///
+/// [`SslStream`]: crate::ssl::SslStream
+/// [`SslStream::get_mut`]: crate::ssl::SslStream::get_mut
+pub struct TeeStream<S, F> {
+    stream: S,
+    sink: F,
+    mode: TeeMode,
+    enabled: bool,
+    read: RecordTracker,
+    write: RecordTracker,
+}
+
+impl<S, F> TeeStream<S, F>
+where
+    F: FnMut(TeeDirection, &[u8]),
+{
+    /// Wraps a stream, passing all data read from or written to it to `sink`.
+    pub fn new(stream: S, sink: F) -> TeeStream<S, F> {
+        TeeStream {
+            stream,
+            sink,
+            mode: TeeMode::Bytes,
+            enabled: true,
+            read: RecordTracker::new(),
+            write: RecordTracker::new(),
+        }
+    }
+
+    /// Sets the data passed to the sink.
+    ///
+    /// Defaults to [`TeeMode::Bytes`].
+    pub fn set_mode(&mut self, mode: TeeMode) {
+        self.mode = mode;
+    }
+
+    /// Returns the data passed to the sink.
+    pub fn mode(&self) -> TeeMode {
+        self.mode
+    }
+
+    /// Enables or disables passing data to the sink.
+    ///
+    /// Defaults to `true`.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Returns whether data is being passed to the sink.
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns a shared reference to the underlying stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the underlying stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Returns a mutable reference to the sink.
+    pub fn sink_mut(&mut self) -> &mut F {
+        &mut self.sink
+    }
+
+    /// Returns the underlying stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    fn tee(&mut self, direction: TeeDirection, data: &[u8]) {
+        let tracker = match direction {
+            TeeDirection::Read => &mut self.read,
+            TeeDirection::Write => &mut self.write,
+        };
+        let enabled = self.enabled;
+        let sink = &mut self.sink;
+
+        match self.mode {
+            TeeMode::Bytes => {
+                tracker.feed(data, |_| {});
+                if enabled && !data.is_empty() {
+                    sink(direction, data);
+                }
+            }
+            TeeMode::RecordHeaders => tracker.feed(data, |header| {
+                if enabled {
+                    sink(direction, header);
+                }
+            }),
+        }
+    }
+}
+
+impl<S, F> Read for TeeStream<S, F>
+where
+    S: Read,
+    F: FnMut(TeeDirection, &[u8]),
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.stream.read(buf)?;
+        self.tee(TeeDirection::Read, &buf[..len]);
+        Ok(len)
+    }
+}
+
+impl<S, F> Write for TeeStream<S, F>
+where
+    S: Write,
+    F: FnMut(TeeDirection, &[u8]),
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.stream.write(buf)?;
+        self.tee(TeeDirection::Write, &buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl<S, F> fmt::Debug for TeeStream<S, F>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("TeeStream")
+            .field("stream", &self.stream)
+            .field("mode", &self.mode)
+            .field("enabled", &self.enabled)
+            .finish()
+    }
+}
+
+struct RecordTracker {
+    header: [u8; RECORD_HEADER_LEN],
+    header_len: usize,
+    remaining: usize,
+}
+
+impl RecordTracker {
+    fn new() -> RecordTracker {
+        RecordTracker {
+            header: [0; RECORD_HEADER_LEN],
+            header_len: 0,
+            remaining: 0,
+        }
+    }
+
+    fn feed<F>(&mut self, mut data: &[u8], mut on_header: F)
+    where
+        F: FnMut(&[u8]),
+    {
+        while !data.is_empty() {
+            if self.remaining > 0 {
+                let len = usize::min(self.remaining, data.len());
+                self.remaining -= len;
+                data = &data[len..];
+                continue;
+            }
+
+            let len = usize::min(RECORD_HEADER_LEN - self.header_len, data.len());
+            self.header[self.header_len..self.header_len + len].copy_from_slice(&data[..len]);
+            self.header_len += len;
+            data = &data[len..];
+
+            if self.header_len == RECORD_HEADER_LEN {
+                self.header_len = 0;
+                self.remaining = u16::from_be_bytes([self.header[3], self.header[4]]) as usize;
+                on_header(&self.header);
+            }
+        }
+    }
+}
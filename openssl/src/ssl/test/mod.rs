@@ -8,29 +8,46 @@ use std::iter;
 use std::mem;
 use std::net::UdpSocket;
 use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::panic::{self, AssertUnwindSafe};
 use std::path::Path;
 use std::process::{Child, ChildStdin, Command, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+#[cfg(ossl102)]
+use crate::asn1::Asn1Time;
 use crate::dh::Dh;
+#[cfg(ossl102)]
+use crate::ec::{EcGroup, EcKey};
 use crate::error::ErrorStack;
 use crate::hash::MessageDigest;
+#[cfg(ossl300)]
+use crate::lib_ctx::LibCtx;
+#[cfg(ossl102)]
+use crate::nid::Nid;
 #[cfg(not(boringssl))]
 use crate::ocsp::{OcspResponse, OcspResponseStatus};
+#[cfg(ossl102)]
+use crate::pkey::Id;
 use crate::pkey::PKey;
 use crate::srtp::SrtpProfileId;
 use crate::ssl::test::server::Server;
+#[cfg(ossl110)]
+use crate::ssl::CipherList;
+#[cfg(ossl102)]
+use crate::ssl::SslCurrentCert;
 #[cfg(any(ossl110, ossl111, libressl261))]
 use crate::ssl::SslVersion;
 use crate::ssl::{self, NameType, SslConnectorBuilder};
 #[cfg(ossl111)]
-use crate::ssl::{ClientHelloResponse, ExtensionContext};
+use crate::ssl::{ClientHelloResponse, DowngradeSentinel, ExtensionContext};
 use crate::ssl::{
-    Error, HandshakeError, MidHandshakeSslStream, ShutdownResult, ShutdownState, Ssl, SslAcceptor,
-    SslAcceptorBuilder, SslConnector, SslContext, SslContextBuilder, SslFiletype, SslMethod,
-    SslOptions, SslSessionCacheMode, SslStream, SslVerifyMode, StatusType,
+    ConnectDeadlineError, Error, ErrorCode, HandshakeError, LimitedStream, MidHandshakeSslStream,
+    ShutdownResult, ShutdownState, SniError, Ssl, SslAcceptor, SslAcceptorBuilder, SslConnector,
+    SslContext, SslContextBuilder, SslFiletype, SslMethod, SslOptions, SslOverrides,
+    SslSessionCacheMode, SslStream, SslVerifyMode, StatusType, TeeDirection, TeeMode, TeeStream,
 };
 #[cfg(ossl102)]
 use crate::x509::store::X509StoreBuilder;
@@ -263,6 +280,52 @@ fn set_ctx_options() {
     assert!(opts.contains(SslOptions::NO_TICKET));
 }
 
+#[test]
+#[cfg(ossl110)]
+fn cipher_list_parse() {
+    let list = CipherList::parse("ECDHE+AESGCM:!aNULL").unwrap();
+    assert!(!list.is_empty());
+    for cipher in &list {
+        assert!(cipher.name().starts_with("ECDHE-"), "{}", cipher.name());
+        assert!(cipher.is_aead());
+        assert_eq!(cipher.digest_nid(), None);
+    }
+
+    let list = CipherList::parse("AES128-SHA").unwrap();
+    assert_eq!(list.names(), ["AES128-SHA"]);
+    assert!(!list.iter().next().unwrap().is_aead());
+
+    assert!(CipherList::parse("NOT-A-CIPHER").is_err());
+}
+
+#[test]
+#[cfg(ossl111)]
+fn cipher_list_parse_ciphersuites() {
+    let list =
+        CipherList::parse_ciphersuites("TLS_AES_256_GCM_SHA384:TLS_AES_128_GCM_SHA256").unwrap();
+    assert_eq!(
+        list.names(),
+        ["TLS_AES_256_GCM_SHA384", "TLS_AES_128_GCM_SHA256"]
+    );
+    assert_eq!(list.iter().next().unwrap().protocol_id(), 0x1302);
+}
+
+#[test]
+fn ctx_builder_debug_redacts_key() {
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    assert_eq!(
+        format!("{:?}", ctx),
+        "SslContextBuilder { private_key: None }"
+    );
+
+    ctx.set_private_key_file(Path::new("test/key.pem"), SslFiletype::PEM)
+        .unwrap();
+    assert_eq!(
+        format!("{:?}", ctx),
+        "SslContextBuilder { private_key: Some(\"<redacted>\") }"
+    );
+}
+
 #[test]
 #[cfg(not(boringssl))]
 fn clear_ctx_options() {
@@ -449,6 +512,68 @@ fn test_connect_with_srtp_ssl() {
     assert_eq!(buf[..], buf2[..]);
 }
 
+#[test]
+#[cfg(ossl111)]
+fn dtls_timer() {
+    // a peer which never answers
+    struct Unresponsive(usize);
+
+    impl Read for Unresponsive {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::WouldBlock.into())
+        }
+    }
+
+    impl Write for Unresponsive {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 += 1;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let ctx = SslContext::builder(SslMethod::dtls()).unwrap().build();
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    let min_mtu = ssl.link_min_mtu();
+    assert!(min_mtu > 0);
+    ssl.set_link_mtu(min_mtu - 1).unwrap_err();
+    ssl.set_link_mtu(1400).unwrap();
+
+    let previous = Arc::new(Mutex::new(vec![]));
+    ssl.set_dtls_timer_callback({
+        let previous = previous.clone();
+        move |_, timeout| {
+            previous.lock().unwrap().push(timeout);
+            Duration::from_millis(200)
+        }
+    });
+    assert_eq!(ssl.dtls_timeout(), None);
+
+    let mut mid = match ssl.connect(Unresponsive(0)) {
+        Err(HandshakeError::WouldBlock(mid)) => mid,
+        _ => panic!("expected the handshake to block"),
+    };
+    assert_eq!(mid.get_ref().0, 1);
+    // no cipher has been negotiated yet
+    assert_eq!(mid.ssl().data_mtu(), 0);
+
+    let timeout = mid.ssl().dtls_timeout().unwrap();
+    assert!(timeout <= Duration::from_millis(200));
+    assert!(!mid.handle_dtls_timeout().unwrap());
+    assert_eq!(mid.get_ref().0, 1);
+
+    thread::sleep(timeout + Duration::from_millis(10));
+    assert!(mid.handle_dtls_timeout().unwrap());
+    assert_eq!(mid.get_ref().0, 2);
+    assert_eq!(
+        *previous.lock().unwrap(),
+        [Duration::from_secs(0), Duration::from_millis(200)]
+    );
+}
+
 /// Tests that when the `SslStream` is created as a server stream, the protocols
 /// are correctly advertised to the client.
 #[test]
@@ -606,6 +731,44 @@ fn flush_panic() {
     let _ = Ssl::new(&ctx.build()).unwrap().connect(stream);
 }
 
+#[test]
+#[should_panic(expected = "blammo")]
+fn callback_panic() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let stream = server.connect_tcp();
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_verify_callback(SslVerifyMode::PEER, |_, _| panic!("blammo"));
+    let _ = Ssl::new(&ctx.build()).unwrap().connect(stream);
+}
+
+#[test]
+fn callback_panic_poisons() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_verify_callback(SslVerifyMode::PEER, |_, _| panic!("blammo"));
+    let ssl = Ssl::new(&ctx.build()).unwrap();
+    let mut stream = SslStream::new(ssl, server.connect_tcp()).unwrap();
+
+    let r = panic::catch_unwind(AssertUnwindSafe(|| stream.connect()));
+    assert!(r.is_err());
+
+    let e = stream.connect().unwrap_err();
+    assert_eq!(e.code(), ErrorCode::SYSCALL);
+    assert_eq!(
+        e.io_error().unwrap().to_string(),
+        "callback panicked: blammo"
+    );
+    let e = stream.ssl_write(b"hello").unwrap_err();
+    assert_eq!(e.code(), ErrorCode::SYSCALL);
+}
+
 #[test]
 fn refcount_ssl_context() {
     let mut ssl = {
@@ -645,6 +808,159 @@ fn default_verify_paths() {
     assert!(result.ends_with(b"</HTML>\r\n") || result.ends_with(b"</html>"));
 }
 
+#[test]
+fn ssl_check_private_key() {
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    assert!(ssl.check_private_key().is_err());
+
+    let cert = X509::from_pem(CERT).unwrap();
+    ssl.set_certificate(&cert).unwrap();
+    let key = PKey::private_key_from_pem(KEY).unwrap();
+    ssl.set_private_key(&key).unwrap();
+    ssl.check_private_key().unwrap();
+}
+
+#[test]
+fn handshake_timing() {
+    let mut server = Server::builder();
+    server.ctx().enable_handshake_timing();
+    server.io_cb(|s| {
+        let timings = s.ssl().handshake_timings().unwrap();
+        let first = timings.messages()[0];
+        assert_eq!(first.msg_type(), 1);
+        assert!(!first.is_sent());
+        assert!(timings.server_hello().is_some());
+        assert!(timings.certificate_verify().is_none());
+        assert!(timings.finished().is_some());
+    });
+    let server = server.build();
+
+    let mut client = server.client().build().builder();
+    assert!(client.ssl().handshake_timings().is_none());
+    client.ssl().enable_handshake_timing();
+    let s = client.connect();
+
+    let timings = s.ssl().handshake_timings().unwrap();
+    let messages = timings.messages();
+    assert_eq!(messages[0].msg_type(), 1);
+    assert!(messages[0].is_sent());
+    assert!(messages
+        .windows(2)
+        .all(|w| w[0].elapsed() <= w[1].elapsed()));
+    assert!(messages.iter().any(|m| !m.is_sent() && m.msg_type() == 11));
+    assert!(timings.certificate_verify().is_some());
+    assert!(timings.server_hello().unwrap() <= timings.finished().unwrap());
+    assert_eq!(timings.finished(), Some(messages.last().unwrap().elapsed()));
+}
+
+#[test]
+#[cfg(ossl111)]
+fn handshake_shape() {
+    use crate::test_support::TestPairBuilder;
+
+    const X25519: u16 = 29;
+    const SECP256R1: u16 = 23;
+
+    let mut pair = TestPairBuilder::new().unwrap();
+    pair.client_ctx().enable_handshake_timing();
+    pair.client_ctx().set_groups_list("X25519:P-256").unwrap();
+    pair.server_ctx().enable_handshake_timing();
+    pair.server_ctx().set_groups_list("P-256").unwrap();
+    pair.server_ctx().set_verify(SslVerifyMode::PEER);
+    let (client, server) = pair.build().unwrap();
+
+    for s in [&client, &server] {
+        let timings = s.ssl().handshake_timings().unwrap();
+        assert!(timings.hello_retry_request());
+        assert_eq!(timings.offered_key_shares(), [X25519]);
+        assert_eq!(timings.key_share(), Some(SECP256R1));
+        assert!(timings.certificate_requested());
+    }
+
+    let mut pair = TestPairBuilder::new().unwrap();
+    pair.client_ctx().enable_handshake_timing();
+    pair.client_ctx().set_groups_list("X25519:P-256").unwrap();
+    let (client, _) = pair.build().unwrap();
+    let timings = client.ssl().handshake_timings().unwrap();
+    assert!(!timings.hello_retry_request());
+    assert_eq!(timings.key_share(), Some(X25519));
+    assert!(!timings.certificate_requested());
+
+    let mut pair = TestPairBuilder::new().unwrap();
+    pair.client_ctx().enable_handshake_timing();
+    pair.client_ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    let (client, _) = pair.build().unwrap();
+    let timings = client.ssl().handshake_timings().unwrap();
+    assert!(!timings.hello_retry_request());
+    assert!(timings.offered_key_shares().is_empty());
+    assert_eq!(timings.key_share(), None);
+}
+
+#[test]
+#[cfg(ossl110)]
+fn remote_key() {
+    use crate::md::Md;
+    use crate::pkey::{PKeyRef, Private};
+    use crate::pkey_ctx::PkeyCtx;
+    use crate::remote_key::RemoteKey;
+    use crate::rsa::Padding;
+
+    struct LocalKey(PKey<Private>);
+
+    impl RemoteKey for LocalKey {
+        fn sign(&self, digest_type: Nid, digest: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+            assert_eq!(digest_type, Nid::SHA256);
+            let mut ctx = PkeyCtx::new(&self.0)?;
+            ctx.sign_init()?;
+            ctx.set_rsa_padding(Padding::PKCS1)?;
+            ctx.set_signature_md(Md::sha256())?;
+            let mut sig = vec![];
+            ctx.sign_to_vec(digest, &mut sig)?;
+            Ok(sig)
+        }
+
+        fn decrypt(&self, input: &[u8], padding: Padding) -> Result<Vec<u8>, ErrorStack> {
+            let rsa = self.0.rsa()?;
+            let mut out = vec![0; rsa.size() as usize];
+            let len = rsa.private_decrypt(input, &mut out, padding)?;
+            out.truncate(len);
+            Ok(out)
+        }
+    }
+
+    fn remote() -> PKey<Private> {
+        let key = PKey::private_key_from_pem(KEY).unwrap();
+        let public_key = X509::from_pem(CERT).unwrap().public_key().unwrap();
+        PKey::from_remote_key(&public_key, LocalKey(key)).unwrap()
+    }
+
+    fn connect(remote: &PKeyRef<Private>, sigalgs: Option<&str>) {
+        let mut server = Server::builder();
+        server.ctx().set_private_key(remote).unwrap();
+        server.ctx().check_private_key().unwrap();
+        let server = server.build();
+
+        let mut client = server.client();
+        if let Some(sigalgs) = sigalgs {
+            client
+                .ctx()
+                .set_max_proto_version(Some(SslVersion::TLS1_2))
+                .unwrap();
+            client.ctx().set_sigalgs_list(sigalgs).unwrap();
+        }
+        client.connect();
+    }
+
+    let remote = remote();
+    // RSA-PSS, padded by OpenSSL
+    connect(&remote, None);
+    // PKCS#1 v1.5
+    connect(&remote, Some("RSA+SHA256"));
+}
+
 #[test]
 fn add_extra_chain_cert() {
     let cert = X509::from_pem(CERT).unwrap();
@@ -652,6 +968,146 @@ fn add_extra_chain_cert() {
     ctx.add_extra_chain_cert(cert).unwrap();
 }
 
+#[cfg(ossl102)]
+fn ec_cert_pem() -> Vec<u8> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "foobar.com")
+        .unwrap();
+    let name = name.build();
+
+    let mut cert = X509::builder().unwrap();
+    cert.set_version(2).unwrap();
+    cert.set_subject_name(&name).unwrap();
+    cert.set_issuer_name(&name).unwrap();
+    cert.set_pubkey(&key).unwrap();
+    cert.set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    cert.set_not_after(&Asn1Time::days_from_now(1).unwrap())
+        .unwrap();
+    cert.sign(&key, MessageDigest::sha256()).unwrap();
+
+    let mut pem = cert.build().to_pem().unwrap();
+    pem.extend_from_slice(&key.private_key_to_pem_pkcs8().unwrap());
+    pem
+}
+
+#[test]
+#[cfg(ossl102)]
+fn set_certificate_chain_pem() {
+    let ec = ec_cert_pem();
+    let mut pem = CERT.to_vec();
+    pem.extend_from_slice(ROOT_CERT);
+    pem.extend_from_slice(&ec);
+    pem.extend_from_slice(KEY);
+
+    for &(sigalgs, id, len) in &[("ECDSA+SHA256", Id::EC, 1), ("RSA-PSS+SHA256", Id::RSA, 2)] {
+        let mut server = Server::builder();
+        server.ctx().set_certificate_chain_pem(&pem).unwrap();
+        let server = server.build();
+
+        let mut client = server.client();
+        client.ctx().set_sigalgs_list(sigalgs).unwrap();
+        let s = client.connect();
+        let peer = s.ssl().peer_certificate().unwrap();
+        assert_eq!(peer.public_key().unwrap().id(), id);
+        assert_eq!(s.ssl().peer_cert_chain().unwrap().len(), len);
+    }
+}
+
+#[test]
+#[cfg(ossl102)]
+fn current_cert() {
+    let ec = ec_cert_pem();
+    let ec_cert = X509::from_pem(&ec).unwrap();
+    let ec_key = PKey::private_key_from_pem(&ec).unwrap();
+    let cert = X509::from_pem(CERT).unwrap();
+    let key = PKey::private_key_from_pem(KEY).unwrap();
+    let root = X509::from_pem(ROOT_CERT).unwrap();
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_certificate(&cert).unwrap();
+    ctx.set_private_key(&key).unwrap();
+    ctx.set_certificate(&ec_cert).unwrap();
+    ctx.set_private_key(&ec_key).unwrap();
+
+    ctx.select_current_cert(&cert).unwrap();
+    ctx.add_chain_cert(root.clone()).unwrap();
+
+    let mut chains = 0;
+    ctx.set_current_cert(SslCurrentCert::FIRST).unwrap();
+    loop {
+        chains += 1;
+        if ctx.set_current_cert(SslCurrentCert::NEXT).is_err() {
+            break;
+        }
+    }
+    assert_eq!(chains, 2);
+
+    ctx.select_current_cert(&cert).unwrap();
+    let ctx = ctx.build();
+    assert!(ctx.certificate().unwrap() == &*cert);
+    assert_eq!(ctx.chain_certs().unwrap().len(), 1);
+
+    for &(sigalgs, id, len) in &[("ECDSA+SHA256", Id::EC, 0), ("RSA-PSS+SHA256", Id::RSA, 1)] {
+        let mut server = Server::builder();
+        server.ctx().set_certificate(&cert).unwrap();
+        server.ctx().set_private_key(&key).unwrap();
+        server.ctx().add_chain_cert(root.clone()).unwrap();
+        server.ctx().set_certificate(&ec_cert).unwrap();
+        server.ctx().set_private_key(&ec_key).unwrap();
+        server.io_cb(move |s| {
+            let used = s.ssl().certificate().unwrap();
+            assert_eq!(used.public_key().unwrap().id(), id);
+            assert_eq!(s.ssl().chain_certs().map_or(0, |c| c.len()), len);
+        });
+        let server = server.build();
+
+        let mut client = server.client();
+        client.ctx().set_sigalgs_list(sigalgs).unwrap();
+        client.connect();
+    }
+}
+
+#[test]
+#[cfg(ossl102)]
+fn set_certificate_chain_pem_invalid() {
+    let intermediate = include_bytes!("../../../test/intermediate-ca.pem");
+    let intermediate_key = include_bytes!("../../../test/intermediate-ca.key");
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+
+    let check = |ctx: &mut SslContextBuilder, parts: &[&[u8]], msg: &str| {
+        let err = ctx.set_certificate_chain_pem(&parts.concat()).unwrap_err();
+        assert!(err.ssl_error().is_none());
+        assert!(err.to_string().contains(msg), "{}", err);
+    };
+
+    check(&mut ctx, &[CERT], "no private keys");
+    check(&mut ctx, &[KEY], "no certificates");
+    check(
+        &mut ctx,
+        &[ROOT_CERT, CERT, KEY],
+        "chains must start with the leaf",
+    );
+    check(
+        &mut ctx,
+        &[CERT, intermediate, KEY],
+        "certificate 1 did not issue",
+    );
+    check(
+        &mut ctx,
+        &[CERT, KEY, intermediate, intermediate_key],
+        "certificate 1 has the same key type",
+    );
+    check(
+        &mut ctx,
+        &[CERT, KEY, intermediate_key],
+        "does not match any certificate",
+    );
+}
+
 #[test]
 #[cfg(ossl102)]
 fn verify_valid_hostname() {
@@ -702,6 +1158,126 @@ fn connector_valid_hostname() {
     s.read_exact(&mut [0]).unwrap();
 }
 
+#[test]
+fn connector_connect_with_deadline() {
+    let server = Server::builder().build();
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.set_verify(SslVerifyMode::NONE);
+
+    let s = server.connect_tcp();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut s = connector
+        .build()
+        .connect_with_deadline("foobar.com", s, deadline)
+        .unwrap();
+    assert_eq!(s.get_ref().read_timeout().unwrap(), None);
+    assert_eq!(s.get_ref().write_timeout().unwrap(), None);
+    s.read_exact(&mut [0]).unwrap();
+}
+
+#[test]
+fn connector_connect_with_deadline_timeout() {
+    // the listener completes the TCP handshake but never responds to the ClientHello
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let s = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    s.set_read_timeout(Some(Duration::from_secs(60))).unwrap();
+
+    let connector = SslConnector::builder(SslMethod::tls()).unwrap().build();
+    let start = Instant::now();
+    let deadline = start + Duration::from_millis(200);
+    let mid = match connector.connect_with_deadline("foobar.com", s, deadline) {
+        Err(ConnectDeadlineError::TimedOut(mid)) => mid,
+        r => panic!("expected a timeout, got {:?}", r.map(|_| ())),
+    };
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert_eq!(
+        mid.get_ref().read_timeout().unwrap(),
+        Some(Duration::from_secs(60))
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn peer_socket() {
+    let server = Server::builder().build();
+    let s = server.connect_tcp();
+    let addr = s.peer_addr().unwrap();
+
+    let seen = Arc::new(Mutex::new(None));
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_verify_callback(SslVerifyMode::PEER, {
+        let seen = seen.clone();
+        move |_, x509_ctx| {
+            let ssl_idx = X509StoreContext::ssl_idx().unwrap();
+            *seen.lock().unwrap() = x509_ctx.ex_data(ssl_idx).unwrap().peer_addr();
+            true
+        }
+    });
+
+    let mut ssl = Ssl::new(&ctx.build()).unwrap();
+    assert_eq!(ssl.peer_addr(), None);
+    ssl.set_peer_socket(&s).unwrap();
+    assert_eq!(ssl.peer_addr(), Some(addr));
+    let mut s = ssl.connect(s).unwrap();
+    s.read_exact(&mut [0]).unwrap();
+
+    assert_eq!(*seen.lock().unwrap(), Some(addr));
+}
+
+#[test]
+fn app_data() {
+    #[derive(Debug, PartialEq)]
+    struct Listener(&'static str);
+
+    let mut server = Server::builder();
+    server.ctx().set_servername_callback(|ssl, _| {
+        let listener = ssl.app_data::<Listener>().ok_or(SniError::ALERT_FATAL)?;
+        assert_eq!(listener.0, "public");
+        Ok(())
+    });
+    server.ssl_cb(|ssl| ssl.set_app_data(Listener("public")));
+    let server = server.build();
+    server.client().connect();
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    assert_eq!(ssl.app_data::<Listener>(), None);
+    ssl.set_app_data(Listener("first"));
+    ssl.set_app_data(Listener("second"));
+    ssl.set_app_data(1u32);
+    *ssl.app_data_mut::<u32>().unwrap() += 1;
+    assert_eq!(ssl.app_data::<Listener>(), Some(&Listener("second")));
+    assert_eq!(ssl.app_data::<u32>(), Some(&2));
+    assert_eq!(ssl.take_app_data::<Listener>(), Some(Listener("second")));
+    assert_eq!(ssl.app_data::<Listener>(), None);
+    assert_eq!(ssl.take_app_data::<Listener>(), None);
+}
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn peer_credentials() {
+    use std::os::unix::net::UnixStream;
+
+    let (socket, _peer) = UnixStream::pair().unwrap();
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    assert_eq!(ssl.peer_credentials(), None);
+    ssl.set_peer_socket(&socket).unwrap();
+    assert_eq!(ssl.peer_addr(), None);
+
+    let credentials = ssl.peer_credentials().unwrap();
+    assert_eq!(credentials.pid as u32, std::process::id());
+    assert_eq!(credentials.uid, unsafe { libc::geteuid() });
+    assert_eq!(credentials.gid, unsafe { libc::getegid() });
+
+    let addr = "127.0.0.1:4433".parse().unwrap();
+    ssl.set_peer_addr("127.0.0.1:443".parse().unwrap());
+    ssl.set_peer_addr(addr);
+    assert_eq!(ssl.peer_addr(), Some(addr));
+}
+
 #[test]
 fn connector_invalid_hostname() {
     let mut server = Server::builder();
@@ -896,42 +1472,198 @@ fn shutdown() {
 }
 
 #[test]
-fn client_ca_list() {
-    let names = X509Name::load_client_ca_file("test/root-ca.pem").unwrap();
-    assert_eq!(names.len(), 1);
+fn shutdown_complete() {
+    let mut server = Server::builder();
+    server.io_cb(|mut s| {
+        s.write_all(b"hello").unwrap();
+        assert_eq!(s.read(&mut [0]).unwrap(), 0);
+        s.shutdown_complete().unwrap();
+    });
+    let server = server.build();
 
-    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
-    ctx.set_client_ca_list(names);
+    let mut s = server.client().connect();
+    s.shutdown_complete().unwrap();
+    assert_eq!(
+        s.get_shutdown(),
+        ShutdownState::SENT | ShutdownState::RECEIVED
+    );
+    s.shutdown_complete().unwrap();
 }
 
 #[test]
-fn cert_store() {
-    let server = Server::builder().build();
+fn starttls() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
 
-    let mut client = server.client();
-    let cert = X509::from_pem(ROOT_CERT).unwrap();
-    client.ctx().cert_store_mut().add_cert(cert).unwrap();
-    client.ctx().set_verify(SslVerifyMode::PEER);
+    let server = thread::spawn(move || {
+        let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+        ctx.set_certificate_chain_file("test/cert.pem").unwrap();
+        ctx.set_private_key_file("test/key.pem", SslFiletype::PEM)
+            .unwrap();
+        let ssl = Ssl::new(&ctx.build()).unwrap();
 
-    client.connect();
+        // the plaintext reader consumes the header of the first TLS record
+        let mut socket = listener.accept().unwrap().0;
+        let mut buf = [0; 14];
+        socket.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf[..9], b"STARTTLS\n");
+
+        let mut s = SslStream::from_parts(ssl, socket, buf[9..].to_vec()).unwrap();
+        s.accept().unwrap();
+        s.write_all(b"tls").unwrap();
+        s.shutdown_complete().unwrap();
+
+        let (mut socket, buffered) = s.into_parts();
+        assert!(buffered.is_empty());
+        socket.write_all(b"plain").unwrap();
+    });
+
+    let mut socket = TcpStream::connect(addr).unwrap();
+    socket.write_all(b"STARTTLS\n").unwrap();
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let mut s = Ssl::new(&ctx).unwrap().connect(socket).unwrap();
+    let mut buf = [0; 3];
+    s.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"tls");
+    s.shutdown_complete().unwrap();
+
+    let mut socket = s.into_inner();
+    let mut buf = vec![];
+    socket.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, b"plain");
+
+    server.join().unwrap();
 }
 
 #[test]
-#[cfg_attr(any(all(libressl321, not(libressl340)), boringssl), ignore)]
-fn tmp_dh_callback() {
-    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+fn shutdown_timeout() {
+    let (tx, rx) = mpsc::channel::<()>();
 
     let mut server = Server::builder();
-    server.ctx().set_tmp_dh_callback(|_, _, _| {
-        CALLED_BACK.store(true, Ordering::SeqCst);
-        let dh = include_bytes!("../../../test/dhparams.pem");
-        Dh::params_from_pem(dh)
+    server.io_cb(move |mut s| {
+        assert_eq!(s.read(&mut [0]).unwrap(), 0);
+        rx.recv().unwrap();
     });
-
     let server = server.build();
 
-    let mut client = server.client();
-    // TLS 1.3 has no DH suites, so make sure we don't pick that version
+    let mut s = server.client().connect();
+    let result = s.shutdown_timeout(Duration::from_millis(50)).unwrap();
+    assert_eq!(result, ShutdownResult::Sent);
+    assert_eq!(s.get_shutdown(), ShutdownState::SENT);
+    assert_eq!(s.get_ref().read_timeout().unwrap(), None);
+    tx.send(()).unwrap();
+}
+
+#[test]
+fn quiet_shutdown() {
+    let server = Server::builder().build();
+
+    let mut client = server.client();
+    client.ctx().set_quiet_shutdown(true);
+    let client = client.build();
+    let mut builder = client.builder();
+    assert!(builder.ssl().quiet_shutdown());
+    builder.ssl().set_quiet_shutdown(false);
+    assert!(!builder.ssl().quiet_shutdown());
+    builder.ssl().set_quiet_shutdown(true);
+
+    let mut s = builder.connect();
+    assert!(s.ssl().ssl_context().quiet_shutdown());
+    assert_eq!(s.shutdown().unwrap(), ShutdownResult::Received);
+    assert_eq!(
+        s.get_shutdown(),
+        ShutdownState::SENT | ShutdownState::RECEIVED
+    );
+}
+
+#[test]
+fn client_ca_list() {
+    let names = X509Name::load_client_ca_file("test/root-ca.pem").unwrap();
+    assert_eq!(names.len(), 1);
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_client_ca_list(names);
+    let ctx = ctx.build();
+    assert_eq!(ctx.client_ca_list().unwrap().len(), 1);
+}
+
+#[test]
+fn client_ca_list_dir() {
+    let dir = env::temp_dir().join(format!("rust-openssl-client-ca-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("root.pem"), ROOT_CERT).unwrap();
+    std::fs::write(
+        dir.join("intermediate.pem"),
+        include_bytes!("../../../test/intermediate-ca.pem"),
+    )
+    .unwrap();
+    std::fs::write(dir.join("root-copy.pem"), ROOT_CERT).unwrap();
+
+    let names = X509Name::load_client_ca_dir(&dir);
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(names.unwrap().len(), 2);
+}
+
+#[test]
+fn client_ca_list_advertised() {
+    let names = X509Name::load_client_ca_file("test/root-ca.pem").unwrap();
+
+    let mut server = Server::builder();
+    server.ctx().set_client_ca_list(names);
+    server
+        .ctx()
+        .set_verify_callback(SslVerifyMode::PEER, |_, _| true);
+    server.ssl_cb(|ssl| assert_eq!(ssl.client_ca_list().unwrap().len(), 1));
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_certificate_file("test/cert.pem", SslFiletype::PEM)
+        .unwrap();
+    client
+        .ctx()
+        .set_private_key_file("test/key.pem", SslFiletype::PEM)
+        .unwrap();
+    let s = client.connect();
+
+    let root = X509::from_pem(ROOT_CERT).unwrap();
+    let list = s.ssl().client_ca_list().unwrap();
+    assert_eq!(list.len(), 1);
+    assert_eq!(
+        list[0].to_der().unwrap(),
+        root.subject_name().to_der().unwrap()
+    );
+}
+
+#[test]
+fn cert_store() {
+    let server = Server::builder().build();
+
+    let mut client = server.client();
+    let cert = X509::from_pem(ROOT_CERT).unwrap();
+    client.ctx().cert_store_mut().add_cert(cert).unwrap();
+    client.ctx().set_verify(SslVerifyMode::PEER);
+
+    client.connect();
+}
+
+#[test]
+#[cfg_attr(any(all(libressl321, not(libressl340)), boringssl), ignore)]
+fn tmp_dh_callback() {
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+
+    let mut server = Server::builder();
+    server.ctx().set_tmp_dh_callback(|_, _, _| {
+        CALLED_BACK.store(true, Ordering::SeqCst);
+        let dh = include_bytes!("../../../test/dhparams.pem");
+        Dh::params_from_pem(dh)
+    });
+
+    let server = server.build();
+
+    let mut client = server.client();
+    // TLS 1.3 has no DH suites, so make sure we don't pick that version
     #[cfg(any(ossl111, libressl340))]
     client.ctx().set_options(super::SslOptions::NO_TLSV1_3);
     client.ctx().set_cipher_list("EDH").unwrap();
@@ -1041,6 +1773,81 @@ fn active_session() {
     assert_eq!(copied, len);
 }
 
+#[test]
+#[cfg(ossl111)]
+fn session_details() {
+    use crate::test_support::TestPairBuilder;
+
+    let mut pair = TestPairBuilder::new().unwrap();
+    pair.client_ctx().set_alpn_protos(b"\x02h2").unwrap();
+    pair.server_ctx().set_alpn_select_callback(|_, client| {
+        ssl::select_next_proto(b"\x02h2", client).ok_or(ssl::AlpnError::NOACK)
+    });
+    let (mut client, mut server) = pair.build().unwrap();
+
+    // TLS 1.3 tickets are sent after the handshake
+    server.write_all(&[0]).unwrap();
+    client.read_exact(&mut [0]).unwrap();
+
+    let session = client.ssl().session().unwrap();
+    assert_eq!(session.protocol_version(), SslVersion::TLS1_3);
+    assert_eq!(
+        session.cipher().unwrap().name(),
+        client.ssl().current_cipher().unwrap().name()
+    );
+    assert!(session.peer_certificate().unwrap() == server.ssl().certificate().unwrap());
+    assert_eq!(session.alpn_protocol(), Some(&b"h2"[..]));
+    assert!(session.ticket().is_some());
+    assert!(session.ticket_lifetime_hint() > 0);
+    assert!(session.is_resumable());
+}
+
+#[test]
+#[cfg(ossl300)]
+fn session_builder_resumption() {
+    use crate::ssl::{SslSessionBuilder, SslSessionCacheMode};
+    use crate::test_support::{handshake, Sender, TestPairBuilder};
+
+    let mut pair = TestPairBuilder::new().unwrap();
+    pair.client_ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    // a fabricated session does not record the use of the extended master secret
+    pair.client_ctx()
+        .set_options(SslOptions::NO_EXTENDED_MASTER_SECRET);
+    pair.server_ctx().set_options(SslOptions::NO_TICKET);
+    let (client, mut server) = pair.build().unwrap();
+    let original = client.ssl().session().unwrap().to_owned();
+    assert!(original.ticket().is_none());
+    // the server evicts sessions of connections which are not shut down from its cache
+    server.shutdown().unwrap();
+    let client_ctx = client.ssl().ssl_context().to_owned();
+    let server_ctx = server.ssl().ssl_context().to_owned();
+    let cert = server.ssl().certificate().unwrap().to_der().unwrap();
+
+    let mut master_key = vec![0; original.master_key_len()];
+    original.master_key(&mut master_key);
+    let mut builder = SslSessionBuilder::new().unwrap();
+    builder.set_protocol_version(SslVersion::TLS1_2).unwrap();
+    builder.set_cipher(original.cipher().unwrap()).unwrap();
+    builder.set_id(original.id()).unwrap();
+    builder.set_master_key(&master_key).unwrap();
+    let session = builder.build();
+    assert_eq!(session.id(), original.id());
+    assert_eq!(session.protocol_version(), SslVersion::TLS1_2);
+    assert!(session.peer_certificate().is_none());
+    assert!(session.is_resumable());
+
+    let mut ssl = Ssl::new(&client_ctx).unwrap();
+    unsafe { ssl.set_session(&session).unwrap() };
+    let transcript = handshake(ssl, Ssl::new(&server_ctx).unwrap()).unwrap();
+    // an abbreviated handshake takes a single round trip, and the server sends no certificate
+    assert_eq!(transcript.flights().len(), 3);
+    assert!(!transcript
+        .flights_from(Sender::Server)
+        .any(|flight| flight.windows(cert.len()).any(|w| w == &cert[..])));
+}
+
 #[test]
 #[cfg(not(boringssl))]
 fn status_callbacks() {
@@ -1246,6 +2053,33 @@ fn custom_extensions() {
     assert!(FOUND_EXTENSION.load(Ordering::SeqCst));
 }
 
+#[test]
+#[cfg(ossl111)]
+fn grease_tolerated() {
+    // a reserved GREASE extension type, which servers must ignore
+    const GREASE_EXTENSION: u16 = 0x0a0a;
+
+    let server = Server::builder().build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .add_custom_ext(
+            GREASE_EXTENSION,
+            ssl::ExtensionContext::CLIENT_HELLO,
+            |_, _, _| Ok(Some(b"")),
+            |_, _, _, _| unreachable!(),
+        )
+        .unwrap();
+    #[cfg(boringssl)]
+    {
+        client.ctx().set_grease_enabled(true);
+        client.ctx().set_permute_extensions(true);
+    }
+
+    client.connect();
+}
+
 fn _check_kinds() {
     fn is_send<T: Send>() {}
     fn is_sync<T: Sync>() {}
@@ -1462,6 +2296,20 @@ fn client_hello() {
             .bytes_to_cipher_list(ssl.client_hello_ciphers().unwrap(), ssl.client_hello_isv2())
             .is_ok());
 
+        let ciphers = ssl.client_hello_cipher_values().unwrap();
+        assert_eq!(ciphers.len() * 2, ssl.client_hello_ciphers().unwrap().len());
+        // TLS_AES_128_GCM_SHA256
+        assert!(ciphers.contains(&0x1301));
+        let extensions = ssl.client_hello_extension_types().unwrap();
+        // supported_groups and ec_point_formats
+        assert!(extensions.contains(&10));
+        assert!(extensions.contains(&11));
+        assert_eq!(ssl.client_hello_ext(0xfe0d), None);
+        let groups = ssl.client_hello_groups().unwrap();
+        // x25519
+        assert!(groups.contains(&29));
+        assert_eq!(ssl.client_hello_ec_point_formats().unwrap()[0], 0);
+
         CALLED_BACK.store(true, Ordering::SeqCst);
         Ok(ClientHelloResponse::SUCCESS)
     });
@@ -1491,6 +2339,78 @@ fn session_cache_size() {
     assert_eq!(ctx.session_cache_size(), 1234);
 }
 
+#[test]
+#[cfg(ossl111)]
+fn session_cache_stats() {
+    use crate::test_support::{handshake, TestPairBuilder};
+
+    let mut pair = TestPairBuilder::new().unwrap();
+    pair.client_ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    pair.server_ctx().set_options(SslOptions::NO_TICKET);
+    let (client, mut server) = pair.build().unwrap();
+    server.shutdown().unwrap();
+    let session = client.ssl().session().unwrap().to_owned();
+    let client_ctx = client.ssl().ssl_context().to_owned();
+    let server_ctx = server.ssl().ssl_context().to_owned();
+    assert_eq!(server_ctx.session_cache_stats().sessions, 1);
+
+    let mut ssl = Ssl::new(&client_ctx).unwrap();
+    unsafe { ssl.set_session(&session).unwrap() };
+    handshake(ssl, Ssl::new(&server_ctx).unwrap()).unwrap();
+
+    let stats = server_ctx.session_cache_stats();
+    assert_eq!(stats.accepts, 2);
+    assert_eq!(stats.accepts_good, 2);
+    assert_eq!(stats.hits, 1);
+    assert_eq!(stats.misses, 0);
+    assert_eq!(stats.connects, 0);
+
+    let stats = client_ctx.session_cache_stats();
+    assert_eq!(stats.connects, 2);
+    assert_eq!(stats.connects_good, 2);
+    assert_eq!(stats.accepts, 0);
+}
+
+#[test]
+#[cfg(ossl111)]
+fn handshake_failure_counters() {
+    use crate::test_support::{handshake, TestPairBuilder};
+
+    let before = ssl::handshake_failures();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let guard = thread::spawn(move || drop(listener.accept().unwrap()));
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    Ssl::new(&ctx)
+        .unwrap()
+        .connect(TcpStream::connect(addr).unwrap())
+        .unwrap_err();
+    guard.join().unwrap();
+
+    let mut pair = TestPairBuilder::new().unwrap();
+    pair.client_ctx().set_verify(SslVerifyMode::PEER);
+    pair.build().unwrap_err();
+
+    let mut pair = TestPairBuilder::new().unwrap();
+    pair.client_ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    pair.server_ctx()
+        .set_min_proto_version(Some(SslVersion::TLS1_3))
+        .unwrap();
+    pair.build().unwrap_err();
+
+    // other tests may fail handshakes concurrently
+    let after = ssl::handshake_failures();
+    assert!(after.io > before.io);
+    assert!(after.certificate > before.certificate);
+    assert!(after.protocol > before.protocol);
+    assert_eq!(after.total(), after.io + after.certificate + after.protocol);
+}
+
 #[test]
 #[cfg(ossl102)]
 fn add_chain_cert() {
@@ -1568,3 +2488,833 @@ fn set_num_tickets() {
     let ssl = ssl;
     assert_eq!(5, ssl.num_tickets());
 }
+
+#[test]
+#[cfg(ossl111)]
+fn early_data_replay_cache() {
+    use crate::test_support::{MemoryStream, TestPairBuilder};
+    use std::collections::HashSet;
+
+    let seen = Arc::new(Mutex::new(HashSet::new()));
+    let mut pair = TestPairBuilder::new().unwrap();
+    pair.client_ctx()
+        .set_session_cache_mode(SslSessionCacheMode::CLIENT | SslSessionCacheMode::NO_INTERNAL);
+    pair.server_ctx().set_max_early_data(1024).unwrap();
+    pair.server_ctx().set_recv_max_early_data(512).unwrap();
+    pair.server_ctx().set_options(SslOptions::NO_ANTI_REPLAY);
+    let cache = seen.clone();
+    pair.server_ctx().set_allow_early_data_callback(move |ssl| {
+        let mut random = [0; 32];
+        ssl.client_random(&mut random);
+        cache.lock().unwrap().insert(random)
+    });
+    let (mut client, mut server) = pair.build().unwrap();
+    assert_eq!(server.ssl().ssl_context().recv_max_early_data(), 512);
+    assert_eq!(server.ssl().recv_max_early_data(), 512);
+
+    server.write_all(&[0]).unwrap();
+    client.read_exact(&mut [0]).unwrap();
+    let session = client.ssl().session().unwrap().to_owned();
+    assert_eq!(session.max_early_data(), 1024);
+    let client_ctx = client.ssl().ssl_context().to_owned();
+    let server_ctx = server.ssl().ssl_context().to_owned();
+
+    let (client_stream, mut peer) = MemoryStream::pair();
+    let mut ssl = Ssl::new(&client_ctx).unwrap();
+    unsafe { ssl.set_session(&session).unwrap() };
+    ssl.set_connect_state();
+    let mut client = SslStream::new(ssl, client_stream).unwrap();
+    assert_eq!(client.write_early_data(b"hello").unwrap(), 5);
+    // read the ClientHello and early data until the stream would block
+    let mut flight = vec![];
+    peer.read_to_end(&mut flight).unwrap_err();
+
+    // the same ClientHello sent to two servers is only accepted by the first
+    let mut received = vec![];
+    for _ in 0..2 {
+        let (mut attacker, server_stream) = MemoryStream::pair();
+        attacker.write_all(&flight).unwrap();
+        let mut ssl = Ssl::new(&server_ctx).unwrap();
+        ssl.set_accept_state();
+        let mut server = SslStream::new(ssl, server_stream).unwrap();
+        let mut buf = [0; 5];
+        let len = server.read_early_data(&mut buf).unwrap_or(0);
+        received.push(buf[..len].to_vec());
+    }
+    assert_eq!(received, [&b"hello"[..], &b""[..]]);
+    assert_eq!(seen.lock().unwrap().len(), 1);
+}
+
+#[test]
+#[cfg(ossl300)]
+fn new_session_ticket() {
+    use crate::test_support::TestPairBuilder;
+    use std::sync::atomic::AtomicUsize;
+
+    let tickets = Arc::new(AtomicUsize::new(0));
+    let mut pair = TestPairBuilder::new().unwrap();
+    pair.client_ctx()
+        .set_session_cache_mode(SslSessionCacheMode::CLIENT);
+    let count = tickets.clone();
+    pair.client_ctx().set_new_session_callback(move |_, _| {
+        count.fetch_add(1, Ordering::SeqCst);
+    });
+    pair.server_ctx().set_num_tickets(1).unwrap();
+    let (mut client, mut server) = pair.build().unwrap();
+
+    server.new_session_ticket().unwrap();
+    server.write_all(&[0]).unwrap();
+    client.read_exact(&mut [0]).unwrap();
+    assert_eq!(tickets.load(Ordering::SeqCst), 2);
+
+    // only servers issue tickets
+    client.new_session_ticket().unwrap_err();
+}
+
+#[test]
+#[cfg(ossl110)]
+fn pipeline_settings() {
+    let mut server = Server::builder();
+    server.ctx().set_max_pipelines(4).unwrap();
+    server.ctx().set_max_pipelines(33).unwrap_err();
+    server.ctx().set_max_send_fragment(4096).unwrap();
+    server.ctx().set_max_send_fragment(100).unwrap_err();
+    server.ctx().set_split_send_fragment(1024).unwrap();
+    server.ctx().set_split_send_fragment(8192).unwrap_err();
+    server.ctx().set_default_read_buffer_len(64 * 1024);
+    server.ssl_cb(|ssl| {
+        ssl.set_max_pipelines(2).unwrap();
+        ssl.set_split_send_fragment(512).unwrap();
+        ssl.set_default_read_buffer_len(32 * 1024);
+    });
+    server.io_cb(|mut s| {
+        s.write_all(&[1; 10000]).unwrap();
+    });
+    let server = server.build();
+
+    let mut client = server.client();
+    client.ctx().set_read_ahead(true);
+    client.ctx().set_max_pipelines(4).unwrap();
+    let mut s = client.connect();
+    let mut buf = [0; 10000];
+    s.read_exact(&mut buf).unwrap();
+    assert!(buf.iter().all(|&b| b == 1));
+}
+
+#[test]
+fn tee_stream_record_headers() {
+    let mut server = Server::builder();
+    server.io_cb(|mut s| {
+        s.write_all(b"hello").unwrap();
+        s.write_all(b"world").unwrap();
+    });
+    let server = server.build();
+
+    let records = Arc::new(Mutex::new(vec![]));
+    let mut stream = TeeStream::new(server.connect_tcp(), {
+        let records = records.clone();
+        move |direction, header: &[u8]| {
+            records.lock().unwrap().push((direction, header.to_vec()));
+        }
+    });
+    stream.set_mode(TeeMode::RecordHeaders);
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let mut s = Ssl::new(&ctx).unwrap().connect(stream).unwrap();
+    {
+        let records = records.lock().unwrap();
+        // ClientHello
+        assert_eq!(records[0].0, TeeDirection::Write);
+        assert_eq!(records[0].1[0], 22);
+        assert!(records.iter().any(|(d, _)| *d == TeeDirection::Read));
+        assert!(records.iter().all(|(_, h)| h.len() == 5));
+    }
+
+    s.get_mut().set_enabled(false);
+    let mut buf = [0; 6];
+    s.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"\0hello");
+    let seen = records.lock().unwrap().len();
+
+    s.get_mut().set_enabled(true);
+    let mut buf = [0; 5];
+    s.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"world");
+
+    let records = records.lock().unwrap();
+    let (direction, header) = records.last().unwrap();
+    assert!(records.len() > seen);
+    assert_eq!(*direction, TeeDirection::Read);
+    // application data
+    assert_eq!(header[0], 23);
+    assert!(u16::from_be_bytes([header[3], header[4]]) as usize > buf.len());
+}
+
+#[test]
+fn tee_stream_bytes() {
+    let server = Server::builder().build();
+
+    let mut written = 0;
+    let mut read = 0;
+    let stream = TeeStream::new(
+        server.connect_tcp(),
+        |direction, data: &[u8]| match direction {
+            TeeDirection::Read => read += data.len(),
+            TeeDirection::Write => written += data.len(),
+        },
+    );
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let mut s = Ssl::new(&ctx).unwrap().connect(stream).unwrap();
+    s.read_exact(&mut [0]).unwrap();
+    drop(s);
+
+    assert!(written > 0);
+    assert!(read > 0);
+}
+
+#[test]
+fn limited_stream_deadline() {
+    let mut server = Server::builder();
+    server.should_error();
+    let server = server.build();
+
+    let mut stream = LimitedStream::new(server.connect_tcp());
+    stream.set_read_deadline(Some(Instant::now()));
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let e = match Ssl::new(&ctx).unwrap().connect(stream) {
+        Err(HandshakeError::Failure(e)) => e,
+        _ => panic!("expected a handshake failure"),
+    };
+    assert_eq!(e.error().code(), ErrorCode::SYSCALL);
+    assert_eq!(
+        e.error().io_error().unwrap().kind(),
+        io::ErrorKind::TimedOut
+    );
+}
+
+#[test]
+fn limited_stream_rate() {
+    let mut server = Server::builder();
+    server.io_cb(|mut s| s.write_all(&[0; 30_000]).unwrap());
+    let server = server.build();
+
+    let mut stream = LimitedStream::new(server.connect_tcp());
+    stream.set_read_rate(Some(20_000));
+    stream.set_write_deadline(Some(Instant::now() + Duration::from_secs(10)));
+    let start = Instant::now();
+
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let mut s = Ssl::new(&ctx).unwrap().connect(stream).unwrap();
+    assert_eq!(s.get_ref().read_rate(), Some(20_000));
+    let mut buf = vec![0; 30_000];
+    s.read_exact(&mut buf).unwrap();
+
+    // one second's worth of bytes is available immediately, and the rest takes at least half a
+    // second more
+    assert!(start.elapsed() >= Duration::from_millis(500));
+}
+
+#[test]
+#[cfg(ossl300)]
+fn tls_record_protection() {
+    use crate::tls_record::{CipherSuite, RecordProtection};
+
+    let mut server = Server::builder();
+    server.io_cb(|mut s| {
+        let mut buf = [0; 5];
+        s.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"hello");
+        s.write_all(b"world").unwrap();
+        let mut buf = [0; 1];
+        s.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"!");
+    });
+    let server = server.build();
+
+    let secrets = Arc::new(Mutex::new(vec![]));
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    ctx.set_ciphersuites("TLS_AES_128_GCM_SHA256").unwrap();
+    ctx.set_keylog_callback({
+        let secrets = secrets.clone();
+        move |_, line| secrets.lock().unwrap().push(line.to_string())
+    });
+    let ctx = ctx.build();
+
+    let captured = Arc::new(Mutex::new((vec![], vec![])));
+    let mut stream = TeeStream::new(server.connect_tcp(), {
+        let captured = captured.clone();
+        move |direction, data: &[u8]| {
+            let mut captured = captured.lock().unwrap();
+            match direction {
+                TeeDirection::Write => captured.0.extend_from_slice(data),
+                TeeDirection::Read => captured.1.extend_from_slice(data),
+            }
+        }
+    });
+    stream.set_enabled(false);
+    let mut s = Ssl::new(&ctx).unwrap().connect(stream).unwrap();
+    s.get_mut().set_enabled(true);
+    s.write_all(b"hello").unwrap();
+    let mut buf = [0; 6];
+    s.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"\0world");
+
+    let secret = |label: &str| {
+        let secrets = secrets.lock().unwrap();
+        let line = secrets.iter().find(|l| l.starts_with(label)).unwrap();
+        hex::decode(line.rsplit(' ').next().unwrap()).unwrap()
+    };
+    let suite = CipherSuite::TLS_AES_128_GCM_SHA256;
+    let mut client = RecordProtection::new(suite, &secret("CLIENT_TRAFFIC_SECRET_0")).unwrap();
+    let mut server = RecordProtection::new(suite, &secret("SERVER_TRAFFIC_SECRET_0")).unwrap();
+
+    let captured = captured.lock().unwrap();
+    assert_eq!(client.open(&captured.0).unwrap(), (23, b"hello".to_vec()));
+
+    // the server's session tickets precede the application data
+    let mut read = &captured.1[..];
+    let mut data = vec![];
+    while !read.is_empty() {
+        let len = 5 + usize::from(u16::from_be_bytes([read[3], read[4]]));
+        match server.open(&read[..len]).unwrap() {
+            (23, content) => data.extend_from_slice(&content),
+            (content_type, _) => assert_eq!(content_type, 22),
+        }
+        read = &read[len..];
+    }
+    assert_eq!(data, b"\0world");
+
+    // records sealed here are accepted by OpenSSL
+    let record = client.seal(23, b"!", 0).unwrap();
+    assert_eq!(client.sequence_number(), 2);
+    s.get_mut().get_mut().write_all(&record).unwrap();
+}
+
+#[test]
+#[cfg(ossl110)]
+fn ssl_dup() {
+    let server = Server::builder().build();
+
+    let name_idx = Ssl::new_cloneable_ex_index::<String>().unwrap();
+    let client = server.client().build();
+    let mut builder = client.builder();
+    builder.ssl().set_verify(SslVerifyMode::NONE);
+    builder.ssl().set_ex_data(name_idx, "original".to_string());
+
+    let mut dup = builder.ssl().dup().unwrap();
+    assert_eq!(dup.verify_mode(), SslVerifyMode::NONE);
+    assert_eq!(dup.ex_data(name_idx).unwrap(), "original");
+    dup.set_ex_data(name_idx, "dup".to_string());
+    assert_eq!(builder.ssl().ex_data(name_idx).unwrap(), "original");
+
+    let mut s = dup.connect(server.connect_tcp()).unwrap();
+    s.read_exact(&mut [0]).unwrap();
+}
+
+#[test]
+#[cfg(ossl110)]
+fn ssl_dup_uncloneable_ex_data() {
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let mut ssl = Ssl::new(&ctx).unwrap();
+    let idx = Ssl::new_ex_index::<u32>().unwrap();
+    ssl.set_ex_data(idx, 5);
+
+    assert!(ssl.dup().is_err());
+    assert_eq!(ssl.ex_data(idx), Some(&5));
+}
+
+#[test]
+#[cfg(ossl110)]
+#[should_panic(expected = "cannot duplicate")]
+fn ssl_dup_with_stream() {
+    let ctx = SslContext::builder(SslMethod::tls()).unwrap().build();
+    let ssl = Ssl::new(&ctx).unwrap();
+    let s = SslStream::new(ssl, io::Cursor::new(vec![])).unwrap();
+    let _ = s.ssl().dup();
+}
+
+#[test]
+#[cfg(ossl300)]
+fn renegotiation_hardening() {
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    server.ctx().set_options(SslOptions::NO_RENEGOTIATION);
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .clear_options(SslOptions::LEGACY_SERVER_CONNECT);
+    assert!(!client
+        .ctx()
+        .options()
+        .contains(SslOptions::LEGACY_SERVER_CONNECT));
+    let s = client.connect();
+    assert!(s.ssl().secure_renegotiation_support());
+    assert_eq!(s.ssl().extms_support(), Some(true));
+}
+
+#[test]
+#[cfg(ossl300)]
+fn no_extended_master_secret() {
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    server
+        .ctx()
+        .set_options(SslOptions::NO_EXTENDED_MASTER_SECRET);
+    let server = server.build();
+
+    let s = server.client().connect();
+    assert_eq!(s.ssl().extms_support(), Some(false));
+}
+
+#[test]
+#[cfg(ossl111)]
+fn downgrade_sentinel() {
+    let server = Server::builder().build();
+
+    let mut client = server.client().build().builder();
+    client
+        .ssl()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    assert_eq!(client.ssl().max_proto_version(), Some(SslVersion::TLS1_2));
+    assert_eq!(client.ssl().min_proto_version(), None);
+    let s = client.connect();
+    assert_eq!(s.ssl().version2(), Some(SslVersion::TLS1_2));
+    assert_eq!(s.ssl().downgrade_sentinel(), Some(DowngradeSentinel::Tls12));
+
+    let server = Server::builder().build();
+    let s = server.client().connect();
+    assert_eq!(s.ssl().downgrade_sentinel(), None);
+}
+
+#[test]
+#[cfg(ossl111)]
+fn per_connection_options() {
+    let server = Server::builder().build();
+
+    let mut client = server.client().build().builder();
+    assert!(client
+        .ssl()
+        .options()
+        .contains(SslOptions::ENABLE_MIDDLEBOX_COMPAT));
+    client
+        .ssl()
+        .clear_options(SslOptions::ENABLE_MIDDLEBOX_COMPAT);
+    assert!(!client
+        .ssl()
+        .options()
+        .contains(SslOptions::ENABLE_MIDDLEBOX_COMPAT));
+    client.ssl().set_options(SslOptions::NO_TICKET);
+    assert!(client.ssl().options().contains(SslOptions::NO_TICKET));
+    client.connect();
+}
+
+#[test]
+#[cfg(ossl111)]
+fn sni_overrides() {
+    let mut overrides = SslOverrides::new();
+    overrides
+        .set_verify(SslVerifyMode::PEER)
+        .set_alpn_protos(b"\x02h2\x08http/1.1")
+        .set_client_ca_list(vec![X509Name::load_client_ca_file("test/root-ca.pem")
+            .unwrap()
+            .pop()
+            .unwrap()])
+        .set_cipher_list("ECDHE-RSA-CHACHA20-POLY1305");
+    let overrides = Arc::new(overrides);
+
+    let mut server = Server::builder();
+    // the context's verification callback is kept when the verification mode is overridden
+    static CALLED_BACK: AtomicBool = AtomicBool::new(false);
+    server
+        .ctx()
+        .set_verify_callback(SslVerifyMode::NONE, |_, _| {
+            CALLED_BACK.store(true, Ordering::SeqCst);
+            true
+        });
+    server.ctx().set_alpn_select_callback(|_, client| {
+        ssl::select_next_proto(b"\x08http/1.1", client).ok_or(ssl::AlpnError::NOACK)
+    });
+    server
+        .ctx()
+        .set_servername_overrides_callback(move |ssl, _| {
+            if ssl.servername(NameType::HOST_NAME) == Some("tenant.example.com") {
+                Ok(Some(overrides.clone()))
+            } else {
+                Ok(None)
+            }
+        });
+    server.ssl_cb(|ssl| assert_eq!(ssl.verify_mode(), SslVerifyMode::NONE));
+    let server = server.build();
+
+    let mut client = server.client();
+    client
+        .ctx()
+        .set_max_proto_version(Some(SslVersion::TLS1_2))
+        .unwrap();
+    client.ctx().set_alpn_protos(b"\x08http/1.1\x02h2").unwrap();
+    client
+        .ctx()
+        .set_certificate_file("test/cert.pem", SslFiletype::PEM)
+        .unwrap();
+    client
+        .ctx()
+        .set_private_key_file("test/key.pem", SslFiletype::PEM)
+        .unwrap();
+    let mut client = client.build().builder();
+    client.ssl().set_hostname("tenant.example.com").unwrap();
+    let s = client.connect();
+    assert_eq!(s.ssl().selected_alpn_protocol(), Some(&b"h2"[..]));
+    assert_eq!(
+        s.ssl().current_cipher().unwrap().name(),
+        "ECDHE-RSA-CHACHA20-POLY1305"
+    );
+    assert!(CALLED_BACK.load(Ordering::SeqCst));
+}
+
+#[test]
+fn sni_overrides_invalid() {
+    let mut overrides = SslOverrides::new();
+    overrides.set_cipher_list("invalid");
+    let overrides = Arc::new(overrides);
+
+    let mut server = Server::builder();
+    server
+        .ctx()
+        .set_servername_overrides_callback(move |_, _| Ok(Some(overrides.clone())));
+    server.should_error();
+    let server = server.build();
+
+    let mut client = server.client().build().builder();
+    client.ssl().set_hostname("tenant.example.com").unwrap();
+    client.connect_err();
+}
+
+#[test]
+#[cfg(ossl300)]
+fn context_properties() {
+    let server = Server::builder().build();
+
+    let ctx = SslContextBuilder::new_ex(None, Some("provider=default"), SslMethod::tls())
+        .unwrap()
+        .build();
+    let mut s = Ssl::new(&ctx)
+        .unwrap()
+        .connect(server.connect_tcp())
+        .unwrap();
+    s.read_exact(&mut [0]).unwrap();
+
+    let ctx = LibCtx::new().unwrap();
+    LibCtx::set_default_properties(Some(&ctx), "provider=bogus").unwrap();
+    assert!(SslContextBuilder::new_ex(Some(&ctx), None, SslMethod::tls()).is_err());
+}
+
+#[cfg(ossl102)]
+fn pipeline_certs() -> (X509, X509, PKey<crate::pkey::Private>) {
+    use crate::ops::req::Req;
+    use crate::ops::x509::X509Sign;
+    use crate::x509::extension::SubjectAlternativeName;
+
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    let root_key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+    let root = Req::new()
+        .subject("/CN=Root")
+        .unwrap()
+        .self_signed(&root_key)
+        .unwrap();
+
+    let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+    let mut san = SubjectAlternativeName::new();
+    san.dns("foobar.com");
+    let mut req = Req::new();
+    req.subject("/CN=foobar.com")
+        .unwrap()
+        .profile_mut()
+        .subject_alt_name(san);
+    let req = req.new_request(&key).unwrap();
+    let cert = X509Sign::new()
+        .copy_extensions()
+        .sign(&req, &root, &root_key)
+        .unwrap();
+
+    (root, cert, key)
+}
+
+#[test]
+#[cfg(ossl102)]
+fn verify_pipeline() {
+    use crate::ssl::{HostnameCheck, SpkiPinning, VerifyContext, VerifyPipeline};
+
+    let (root, cert, key) = pipeline_certs();
+    let domains = Arc::new(Mutex::new(vec![]));
+
+    let mut server = Server::builder();
+    server.ctx().set_certificate(&cert).unwrap();
+    server.ctx().set_private_key(&key).unwrap();
+    let server = server.build();
+
+    let mut pins = SpkiPinning::new();
+    pins.pin_certificate(&root).unwrap();
+    let mut pipeline = VerifyPipeline::new();
+    let seen = domains.clone();
+    pipeline
+        .step(HostnameCheck::name("foobar.com"))
+        .step(pins)
+        .step(move |ctx: &VerifyContext<'_>| {
+            assert_eq!(ctx.chain().len(), 2);
+            seen.lock().unwrap().push(ctx.domain().map(str::to_string));
+            Ok(())
+        });
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.cert_store_mut().add_cert(root.clone()).unwrap();
+    connector.set_verify_pipeline(pipeline);
+    let s = server.connect_tcp();
+    let mut s = connector.build().connect("foobar.com", s).unwrap();
+    s.read_exact(&mut [0]).unwrap();
+    assert_eq!(*domains.lock().unwrap(), [Some("foobar.com".to_string())]);
+
+    let mut server = Server::builder();
+    server.ctx().set_certificate(&cert).unwrap();
+    server.ctx().set_private_key(&key).unwrap();
+    server.should_error();
+    let server = server.build();
+
+    let mut pins = SpkiPinning::new();
+    pins.pin_sha256(&[0; 32]);
+    let mut pipeline = VerifyPipeline::new();
+    pipeline.step(pins);
+
+    let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+    connector.cert_store_mut().add_cert(root).unwrap();
+    connector.set_verify_pipeline(pipeline);
+    let s = server.connect_tcp();
+    match connector.build().connect("foobar.com", s) {
+        Err(HandshakeError::Failure(s)) => assert_eq!(
+            s.ssl().verify_result(),
+            X509VerifyResult::APPLICATION_VERIFICATION
+        ),
+        _ => panic!("pinning should fail"),
+    }
+}
+
+#[test]
+fn verify_pipeline_revocation() {
+    use crate::bn::BigNum;
+    use crate::ssl::{RevocationCheck, VerifyPipeline};
+    use crate::stack::Stack;
+    use crate::x509::ca::{CertificateAuthority, IssuanceProfile, Revocation};
+    use crate::x509::self_signed::SelfSignedOptions;
+
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    let root_key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "Root").unwrap();
+    let root = X509::self_signed(&name.build(), &root_key, SelfSignedOptions::new().ca()).unwrap();
+    let mut ca = CertificateAuthority::new(root.clone(), root_key).unwrap();
+
+    let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "revoked.example.com")
+        .unwrap();
+    let cert = ca
+        .issue(&name.build(), &key, IssuanceProfile::new().days(1))
+        .unwrap();
+    let crl = ca
+        .crl(
+            &BigNum::from_u32(1).unwrap(),
+            &[Revocation::from_certificate(&cert).unwrap()],
+            7,
+        )
+        .unwrap();
+
+    let mut chain = Stack::new().unwrap();
+    chain.push(cert).unwrap();
+    chain.push(root).unwrap();
+
+    let mut revocation = RevocationCheck::new();
+    revocation.require_crl();
+    let mut pipeline = VerifyPipeline::new();
+    pipeline.step(revocation);
+    let e = pipeline.verify(&chain, None).unwrap_err();
+    assert_eq!(e.as_raw(), ffi::X509_V_ERR_UNABLE_TO_GET_CRL);
+
+    let mut pipeline = VerifyPipeline::new();
+    let mut revocation = RevocationCheck::new();
+    revocation.crl(crl);
+    pipeline.step(revocation);
+    let e = pipeline.verify(&chain, None).unwrap_err();
+    assert_eq!(e.as_raw(), ffi::X509_V_ERR_CERT_REVOKED);
+
+    assert!(VerifyPipeline::new().verify(&chain, None).is_ok());
+}
+
+#[test]
+fn verify_pipeline_expired_crl() {
+    use crate::ssl::{RevocationCheck, VerifyPipeline};
+    use crate::stack::Stack;
+    use crate::x509::X509Crl;
+
+    let mut chain = Stack::new().unwrap();
+    chain
+        .push(X509::from_pem(include_bytes!("../../../test/subca.crt")).unwrap())
+        .unwrap();
+    chain
+        .push(X509::from_pem(include_bytes!("../../../test/crl-ca.crt")).unwrap())
+        .unwrap();
+    // revokes subca.crt, but its next update was in 2019
+    let crl = include_bytes!("../../../test/test.crl");
+
+    let mut revocation = RevocationCheck::new();
+    revocation.crl(X509Crl::from_der(crl).unwrap());
+    let mut pipeline = VerifyPipeline::new();
+    pipeline.step(revocation);
+    assert!(pipeline.verify(&chain, None).is_ok());
+
+    let mut revocation = RevocationCheck::new();
+    revocation
+        .crl(X509Crl::from_der(crl).unwrap())
+        .require_crl();
+    let mut pipeline = VerifyPipeline::new();
+    pipeline.step(revocation);
+    let e = pipeline.verify(&chain, None).unwrap_err();
+    assert_eq!(e.as_raw(), ffi::X509_V_ERR_UNABLE_TO_GET_CRL);
+}
+
+#[test]
+fn channel_binding() {
+    use crate::ssl::{ChannelBinding, SslRef};
+
+    fn bindings(ssl: &SslRef) -> Vec<Option<Vec<u8>>> {
+        [
+            ChannelBinding::TlsUnique,
+            ChannelBinding::TlsServerEndPoint,
+            ChannelBinding::TlsExporter,
+        ]
+        .iter()
+        .map(|&binding| ssl.channel_binding(binding).ok())
+        .collect()
+    }
+
+    #[cfg(any(ossl110, libressl261))]
+    let versions = [Some(SslVersion::TLS1_2), None];
+    #[cfg(not(any(ossl110, libressl261)))]
+    let versions = [None];
+
+    for &version in &versions {
+        let (tx, rx) = mpsc::channel();
+        let mut server = Server::builder();
+        server.io_cb(move |s| tx.send(bindings(s.ssl())).unwrap());
+        let server = server.build();
+
+        let mut client = server.client();
+        #[cfg(any(ossl110, libressl261))]
+        client.ctx().set_max_proto_version(version).unwrap();
+        let s = client.connect();
+
+        let ours = bindings(s.ssl());
+        assert_eq!(ours, rx.recv().unwrap());
+
+        let cert = X509::from_pem(CERT).unwrap();
+        assert_eq!(
+            ours[1].as_deref(),
+            Some(&*cert.digest(MessageDigest::sha256()).unwrap())
+        );
+
+        if version.is_some() {
+            assert_eq!(ours[0].as_ref().map(Vec::len), Some(12));
+        }
+        #[cfg(any(ossl111, libressl340))]
+        {
+            if version.is_none() {
+                assert!(ours[0].is_none());
+                assert_eq!(ours[2].as_ref().map(Vec::len), Some(32));
+            }
+        }
+    }
+}
+
+#[test]
+#[cfg(ossl111)]
+fn exported_authenticator() {
+    use crate::ssl::AuthenticatorRequest;
+
+    let cert = X509::from_pem(CERT).unwrap();
+    let key = PKey::private_key_from_pem(KEY).unwrap();
+
+    let (request_tx, request_rx) = mpsc::channel();
+    let (auth_tx, auth_rx) = mpsc::channel::<Vec<u8>>();
+    let (result_tx, result_rx) = mpsc::channel();
+    let mut server = Server::builder();
+    let server_cert = cert.clone();
+    let server_key = key.clone();
+    server.io_cb(move |s| {
+        let ssl = s.ssl();
+        let request = AuthenticatorRequest::new(ssl, b"request").unwrap();
+        request_tx.send(request.as_bytes().to_vec()).unwrap();
+
+        let mut authenticator = auth_rx.recv().unwrap();
+        let chain = ssl
+            .validate_authenticator(Some(&request), &authenticator)
+            .unwrap()
+            .unwrap();
+        let last = authenticator.len() - 1;
+        authenticator[last] ^= 1;
+        let tampered = ssl.validate_authenticator(Some(&request), &authenticator);
+
+        let empty = auth_rx.recv().unwrap();
+        let refused = ssl.validate_authenticator(Some(&request), &empty).unwrap();
+
+        let spontaneous = ssl
+            .export_authenticator(None, std::slice::from_ref(&server_cert), &server_key)
+            .unwrap();
+        result_tx
+            .send((
+                chain[0].to_der().unwrap(),
+                tampered.is_err(),
+                refused.is_none(),
+                spontaneous,
+            ))
+            .unwrap();
+    });
+    let server = server.build();
+
+    let s = server.client().connect();
+    let ssl = s.ssl();
+
+    let request = AuthenticatorRequest::from_bytes(ssl, &request_rx.recv().unwrap()).unwrap();
+    assert_eq!(request.context(), b"request");
+    assert!(AuthenticatorRequest::new(ssl, b"request")
+        .and_then(|ours| ssl.export_authenticator(Some(&ours), std::slice::from_ref(&cert), &key))
+        .is_err());
+    auth_tx
+        .send(
+            ssl.export_authenticator(Some(&request), std::slice::from_ref(&cert), &key)
+                .unwrap(),
+        )
+        .unwrap();
+    auth_tx
+        .send(ssl.export_empty_authenticator(&request).unwrap())
+        .unwrap();
+
+    let (leaf, tampered, refused, spontaneous) = result_rx.recv().unwrap();
+    assert_eq!(leaf, cert.to_der().unwrap());
+    assert!(tampered);
+    assert!(refused);
+
+    let chain = ssl
+        .validate_authenticator(None, &spontaneous)
+        .unwrap()
+        .unwrap();
+    assert_eq!(chain[0].to_der().unwrap(), cert.to_der().unwrap());
+}
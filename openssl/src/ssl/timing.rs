@@ -0,0 +1,245 @@
+use std::time::{Duration, Instant};
+
+// the random of a ServerHello which is a HelloRetryRequest, from RFC 8446
+const HELLO_RETRY_REQUEST_RANDOM: [u8; 32] = [
+    0xcf, 0x21, 0xad, 0x74, 0xe5, 0x9a, 0x61, 0x11, 0xbe, 0x1d, 0x8c, 0x02, 0x1e, 0x65, 0xb8, 0x91,
+    0xc2, 0xa2, 0x11, 0x16, 0x7a, 0xbb, 0x8c, 0x5e, 0x07, 0x9e, 0x09, 0xe2, 0xc8, 0xa8, 0x33, 0x9c,
+];
+const KEY_SHARE: u16 = 51;
+
+/// A handshake message recorded by [`HandshakeTimings`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HandshakeMessage {
+    msg_type: u8,
+    sent: bool,
+    elapsed: Duration,
+}
+
+impl HandshakeMessage {
+    /// Returns the handshake message type, for example 1 for a ClientHello.
+    pub fn msg_type(&self) -> u8 {
+        self.msg_type
+    }
+
+    /// Determines if the message was sent, rather than received.
+    pub fn is_sent(&self) -> bool {
+        self.sent
+    }
+
+    /// Returns the time between the ClientHello and this message.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+/// The timing and shape of the handshake messages of a connection.
+///
+/// Timing is enabled with [`SslContextBuilder::enable_handshake_timing`] or
+/// [`SslRef::enable_handshake_timing`], and the result is available from
+/// [`SslRef::handshake_timings`]. Messages are timestamped as OpenSSL sends or receives them, so
+/// the time between a received message and the next message includes the time spent processing
+/// it, such as verifying a certificate chain.
+///
+/// The ClientHello and ServerHello messages are also inspected, to describe the TLS 1.3 key
+/// exchange for analytics and for debugging peers which are incompatible with each other.
+///
+/// [`SslContextBuilder::enable_handshake_timing`]: crate::ssl::SslContextBuilder::enable_handshake_timing
+/// [`SslRef::enable_handshake_timing`]: crate::ssl::SslRef::enable_handshake_timing
+/// [`SslRef::handshake_timings`]: crate::ssl::SslRef::handshake_timings
+#[derive(Debug, Clone)]
+pub struct HandshakeTimings {
+    start: Instant,
+    messages: Vec<HandshakeMessage>,
+    hello_retry_request: bool,
+    offered_key_shares: Vec<u16>,
+    key_share: Option<u16>,
+}
+
+impl HandshakeTimings {
+    pub(crate) fn new() -> HandshakeTimings {
+        HandshakeTimings {
+            start: Instant::now(),
+            messages: vec![],
+            hello_retry_request: false,
+            offered_key_shares: vec![],
+            key_share: None,
+        }
+    }
+
+    /// Records a handshake message, including its 4 byte header.
+    pub(crate) fn record(&mut self, msg: &[u8], sent: bool) {
+        let now = Instant::now();
+        let msg_type = msg[0];
+        let client_hello = msg_type == ffi::SSL3_MT_CLIENT_HELLO as u8;
+        // post-handshake messages are ignored, and a renegotiation starts a new handshake
+        if self.messages.is_empty() || self.is_complete() {
+            if !client_hello {
+                return;
+            }
+            *self = HandshakeTimings::new();
+            self.start = now;
+        }
+
+        let first_client_hello = client_hello && self.messages.is_empty();
+        self.messages.push(HandshakeMessage {
+            msg_type,
+            sent,
+            elapsed: now - self.start,
+        });
+
+        // DTLS messages have a longer header, and are not inspected
+        let body = match split_vec(&msg[1..], 3) {
+            Some((body, [])) => body,
+            _ => return,
+        };
+        if first_client_hello {
+            self.offered_key_shares = client_key_shares(body).unwrap_or_default();
+        } else if msg_type == ffi::SSL3_MT_SERVER_HELLO as u8 {
+            if body.get(2..34) == Some(&HELLO_RETRY_REQUEST_RANDOM[..]) {
+                self.hello_retry_request = true;
+            } else {
+                self.key_share = server_key_share(body);
+            }
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.messages
+            .iter()
+            .filter(|m| m.msg_type == ffi::SSL3_MT_FINISHED as u8)
+            .count()
+            >= 2
+    }
+
+    /// Returns the handshake messages in the order they were sent or received.
+    pub fn messages(&self) -> &[HandshakeMessage] {
+        &self.messages
+    }
+
+    /// Returns the time between the ClientHello and the ServerHello.
+    pub fn server_hello(&self) -> Option<Duration> {
+        self.messages
+            .iter()
+            .find(|m| m.msg_type == ffi::SSL3_MT_SERVER_HELLO as u8)
+            .map(|m| m.elapsed)
+    }
+
+    /// Returns the time between receiving the peer's Certificate message and the following
+    /// message.
+    ///
+    /// This is dominated by the verification of the peer's certificate chain. `None` is returned
+    /// if the peer did not send a certificate.
+    pub fn certificate_verify(&self) -> Option<Duration> {
+        let idx = self
+            .messages
+            .iter()
+            .position(|m| !m.sent && m.msg_type == ffi::SSL3_MT_CERTIFICATE as u8)?;
+        let next = self.messages.get(idx + 1)?;
+        Some(next.elapsed - self.messages[idx].elapsed)
+    }
+
+    /// Determines if the server sent a HelloRetryRequest.
+    ///
+    /// A TLS 1.3 server does so if none of the key shares offered by the client are for a group
+    /// it accepts, for example, and the client then offers a new key share in a second
+    /// ClientHello.
+    pub fn hello_retry_request(&self) -> bool {
+        self.hello_retry_request
+    }
+
+    /// Returns the groups of the key shares offered in the client's first ClientHello, in the order
+    /// sent.
+    ///
+    /// The values are TLS group identifiers, such as 29 for X25519. This is empty if the client
+    /// offered no key shares, as is the case before TLS 1.3.
+    pub fn offered_key_shares(&self) -> &[u16] {
+        &self.offered_key_shares
+    }
+
+    /// Returns the group of the key share accepted by the server.
+    ///
+    /// `None` is returned if the ServerHello has not been seen yet, or if it contains no key
+    /// share, as is the case before TLS 1.3.
+    pub fn key_share(&self) -> Option<u16> {
+        self.key_share
+    }
+
+    /// Determines if the server requested a client certificate.
+    pub fn certificate_requested(&self) -> bool {
+        self.messages
+            .iter()
+            .any(|m| m.msg_type == ffi::SSL3_MT_CERTIFICATE_REQUEST as u8)
+    }
+
+    /// Returns the time between the ClientHello and the last Finished message.
+    pub fn finished(&self) -> Option<Duration> {
+        self.messages
+            .iter()
+            .rev()
+            .find(|m| m.msg_type == ffi::SSL3_MT_FINISHED as u8)
+            .map(|m| m.elapsed)
+    }
+}
+
+/// Splits a value with a big-endian length prefix of `len_bytes` bytes off the front of `buf`.
+fn split_vec(buf: &[u8], len_bytes: usize) -> Option<(&[u8], &[u8])> {
+    let len = buf
+        .get(..len_bytes)?
+        .iter()
+        .fold(0, |len, &b| len << 8 | usize::from(b));
+    let rest = &buf[len_bytes..];
+    if rest.len() < len {
+        return None;
+    }
+    Some(rest.split_at(len))
+}
+
+fn split_u16(buf: &[u8]) -> Option<(u16, &[u8])> {
+    let value = buf.get(..2)?;
+    Some((u16::from_be_bytes([value[0], value[1]]), &buf[2..]))
+}
+
+/// Returns the data of the extension of type `ext_type` from an extensions block.
+fn find_extension(mut extensions: &[u8], ext_type: u16) -> Option<&[u8]> {
+    while !extensions.is_empty() {
+        let (ty, rest) = split_u16(extensions)?;
+        let (data, rest) = split_vec(rest, 2)?;
+        if ty == ext_type {
+            return Some(data);
+        }
+        extensions = rest;
+    }
+    None
+}
+
+fn client_key_shares(client_hello: &[u8]) -> Option<Vec<u16>> {
+    // legacy_version and random
+    let rest = client_hello.get(34..)?;
+    // legacy_session_id, cipher_suites and legacy_compression_methods
+    let (_, rest) = split_vec(rest, 1)?;
+    let (_, rest) = split_vec(rest, 2)?;
+    let (_, rest) = split_vec(rest, 1)?;
+    let (extensions, _) = split_vec(rest, 2)?;
+
+    let (mut shares, _) = split_vec(find_extension(extensions, KEY_SHARE)?, 2)?;
+    let mut groups = vec![];
+    while !shares.is_empty() {
+        let (group, rest) = split_u16(shares)?;
+        let (_, rest) = split_vec(rest, 2)?;
+        groups.push(group);
+        shares = rest;
+    }
+    Some(groups)
+}
+
+fn server_key_share(server_hello: &[u8]) -> Option<u16> {
+    // legacy_version and random
+    let rest = server_hello.get(34..)?;
+    // legacy_session_id_echo, cipher_suite and legacy_compression_method
+    let (_, rest) = split_vec(rest, 1)?;
+    let rest = rest.get(3..)?;
+    let (extensions, _) = split_vec(rest, 2)?;
+
+    let (group, _) = split_u16(find_extension(extensions, KEY_SHARE)?)?;
+    Some(group)
+}
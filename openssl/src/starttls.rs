@@ -0,0 +1,609 @@
+//! Upgrading plaintext connections to TLS with STARTTLS.
+//!
+//! Several protocols start in plaintext and switch to TLS on request: SMTP ([RFC 3207]), IMAP
+//! and POP3 ([RFC 2595]), and LDAP ([RFC 4511]). [`StartTls`] performs the client side of that
+//! exchange over a blocking stream, then hands the stream to OpenSSL.
+//!
+//! Bytes the server sends after its response to the STARTTLS command are never interpreted as
+//! plaintext responses. They are passed to OpenSSL as the start of the TLS session, which guards
+//! against responses injected ahead of the handshake.
+//!
+//! [RFC 3207]: https://tools.ietf.org/html/rfc3207
+//! [RFC 2595]: https://tools.ietf.org/html/rfc2595
+//! [RFC 4511]: https://tools.ietf.org/html/rfc4511#section-4.14
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use openssl::ssl::{SslConnector, SslMethod};
+//! use openssl::starttls::{Protocol, StartTls};
+//! use std::io::Write;
+//! use std::net::TcpStream;
+//!
+//! let connector = SslConnector::builder(SslMethod::tls()).unwrap().build();
+//! let stream = TcpStream::connect("mail.example.com:587").unwrap();
+//! let mut stream = StartTls::new(Protocol::Smtp)
+//!     .connect(connector.configure().unwrap(), "mail.example.com", stream)
+//!     .unwrap();
+//! stream.write_all(b"EHLO localhost\r\n").unwrap();
+//! ```
+use std::error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::error::ErrorStack;
+use crate::ssl::{self, ConnectConfiguration, SslStream};
+
+// a response line longer than this is treated as a protocol error
+const MAX_LINE: usize = 8192;
+
+// the OID of the LDAP StartTLS extended operation
+const LDAP_START_TLS_OID: &[u8] = b"1.3.6.1.4.1.1466.20037";
+
+/// A protocol with a STARTTLS command.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Protocol {
+    /// SMTP, with `EHLO` followed by `STARTTLS`.
+    Smtp,
+    /// IMAP, with `STARTTLS`.
+    Imap,
+    /// POP3, with `STLS`.
+    Pop3,
+    /// LDAP, with the StartTLS extended operation.
+    Ldap,
+}
+
+/// The client side of a STARTTLS exchange.
+#[derive(Debug, Clone)]
+pub struct StartTls {
+    protocol: Protocol,
+    ehlo_name: String,
+}
+
+impl StartTls {
+    /// Creates a new `StartTls` for `protocol`.
+    pub fn new(protocol: Protocol) -> StartTls {
+        StartTls {
+            protocol,
+            ehlo_name: "localhost".to_string(),
+        }
+    }
+
+    /// Sets the name the client identifies itself with in the SMTP `EHLO` command.
+    ///
+    /// Defaults to `localhost`.
+    pub fn ehlo_name(&mut self, name: &str) -> &mut StartTls {
+        self.ehlo_name = name.to_string();
+        self
+    }
+
+    /// Performs the plaintext part of the exchange, up to the server accepting the upgrade.
+    ///
+    /// Returns the stream along with any bytes which were read after the server's response. They
+    /// belong to the TLS session, and should be passed to [`SslStream::from_parts`].
+    pub fn negotiate<S>(&self, stream: S) -> Result<(S, Vec<u8>), StartTlsError>
+    where
+        S: Read + Write,
+    {
+        let mut conn = Conn {
+            stream,
+            buf: vec![],
+        };
+        match self.protocol {
+            Protocol::Smtp => self.smtp(&mut conn)?,
+            Protocol::Imap => imap(&mut conn)?,
+            Protocol::Pop3 => pop3(&mut conn)?,
+            Protocol::Ldap => ldap(&mut conn)?,
+        }
+        Ok((conn.stream, conn.buf))
+    }
+
+    /// Performs the exchange, then the TLS handshake with `config` for `domain`.
+    pub fn connect<S>(
+        &self,
+        config: ConnectConfiguration,
+        domain: &str,
+        stream: S,
+    ) -> Result<SslStream<S>, StartTlsError>
+    where
+        S: Read + Write,
+    {
+        let (stream, buffered) = self.negotiate(stream)?;
+        let ssl = config.into_ssl(domain)?;
+        let mut stream = SslStream::from_parts(ssl, stream, buffered)?;
+        stream.connect().map_err(|e| StartTlsError(Inner::Tls(e)))?;
+        Ok(stream)
+    }
+
+    fn smtp<S>(&self, conn: &mut Conn<S>) -> Result<(), StartTlsError>
+    where
+        S: Read + Write,
+    {
+        expect_smtp(conn, 220)?;
+
+        conn.write(format!("EHLO {}\r\n", self.ehlo_name).as_bytes())?;
+        let lines = expect_smtp(conn, 250)?;
+        let supported = lines.iter().skip(1).any(|line| {
+            let keyword = line.split(' ').next().unwrap_or("");
+            keyword.eq_ignore_ascii_case("STARTTLS")
+        });
+        if !supported {
+            return Err(StartTlsError::protocol(
+                "server does not advertise STARTTLS",
+            ));
+        }
+
+        conn.write(b"STARTTLS\r\n")?;
+        expect_smtp(conn, 220)?;
+        Ok(())
+    }
+}
+
+/// Reads an SMTP reply, returning the text of its lines if it has the code `expected`.
+fn expect_smtp<S>(conn: &mut Conn<S>, expected: u16) -> Result<Vec<String>, StartTlsError>
+where
+    S: Read + Write,
+{
+    let mut lines = vec![];
+    loop {
+        let line = conn.read_line()?;
+        let code = line.get(..3).and_then(|code| code.parse::<u16>().ok());
+        let code = match code {
+            Some(code) => code,
+            None => {
+                return Err(StartTlsError::protocol(format!(
+                    "invalid SMTP reply `{}`",
+                    line
+                )))
+            }
+        };
+        if code != expected {
+            return Err(StartTlsError::protocol(format!(
+                "unexpected SMTP reply `{}`",
+                line
+            )));
+        }
+        let last = !line[3..].starts_with('-');
+        lines.push(line.get(4..).unwrap_or("").to_string());
+        if last {
+            return Ok(lines);
+        }
+    }
+}
+
+fn imap<S>(conn: &mut Conn<S>) -> Result<(), StartTlsError>
+where
+    S: Read + Write,
+{
+    let greeting = conn.read_line()?;
+    if !starts_with_ignore_case(&greeting, "* OK") {
+        return Err(StartTlsError::protocol(format!(
+            "unexpected IMAP greeting `{}`",
+            greeting
+        )));
+    }
+
+    conn.write(b"a1 STARTTLS\r\n")?;
+    loop {
+        let line = conn.read_line()?;
+        if line.starts_with("* ") {
+            continue;
+        }
+        if starts_with_ignore_case(&line, "a1 OK") {
+            return Ok(());
+        }
+        return Err(StartTlsError::protocol(format!(
+            "unexpected IMAP response `{}`",
+            line
+        )));
+    }
+}
+
+fn pop3<S>(conn: &mut Conn<S>) -> Result<(), StartTlsError>
+where
+    S: Read + Write,
+{
+    let greeting = conn.read_line()?;
+    if !greeting.starts_with("+OK") {
+        return Err(StartTlsError::protocol(format!(
+            "unexpected POP3 greeting `{}`",
+            greeting
+        )));
+    }
+
+    conn.write(b"STLS\r\n")?;
+    let line = conn.read_line()?;
+    if !line.starts_with("+OK") {
+        return Err(StartTlsError::protocol(format!(
+            "unexpected POP3 response `{}`",
+            line
+        )));
+    }
+    Ok(())
+}
+
+fn ldap<S>(conn: &mut Conn<S>) -> Result<(), StartTlsError>
+where
+    S: Read + Write,
+{
+    // LDAPMessage ::= SEQUENCE {
+    //     messageID INTEGER (1),
+    //     extendedReq [APPLICATION 23] SEQUENCE { requestName [0] LDAPOID } }
+    let oid_len = LDAP_START_TLS_OID.len() as u8;
+    let mut request = vec![0x30, oid_len + 7, 0x02, 0x01, 0x01, 0x77, oid_len + 2, 0x80];
+    request.push(oid_len);
+    request.extend_from_slice(LDAP_START_TLS_OID);
+    conn.write(&request)?;
+
+    let invalid = || StartTlsError::protocol("invalid LDAP response");
+    let message = conn.read_ber(0x30)?;
+    let mut message = ber_element(&mut &message[..], 0x30).ok_or_else(invalid)?;
+    let id = ber_element(&mut message, 0x02).ok_or_else(invalid)?;
+    let mut response = ber_element(&mut message, 0x78).ok_or_else(invalid)?;
+    let result = ber_element(&mut response, 0x0a).ok_or_else(invalid)?;
+    if id != [0x01] {
+        return Err(invalid());
+    }
+    if result != [0x00] {
+        return Err(StartTlsError::protocol(format!(
+            "LDAP StartTLS failed with result code {}",
+            result.iter().fold(0u64, |n, &b| n << 8 | u64::from(b))
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the header length and content length of the BER element at the start of `buf`, if
+/// its header is complete.
+fn ber_header(buf: &[u8]) -> Result<Option<(usize, usize)>, StartTlsError> {
+    let first = match buf.get(1) {
+        Some(&first) => first,
+        None => return Ok(None),
+    };
+    if first & 0x80 == 0 {
+        return Ok(Some((2, usize::from(first))));
+    }
+
+    let n = usize::from(first & 0x7f);
+    if n == 0 || n > 4 {
+        return Err(StartTlsError::protocol("invalid LDAP message length"));
+    }
+    let bytes = match buf.get(2..2 + n) {
+        Some(bytes) => bytes,
+        None => return Ok(None),
+    };
+    let len = bytes.iter().fold(0, |len, &b| len << 8 | usize::from(b));
+    Ok(Some((2 + n, len)))
+}
+
+/// Removes the BER element with the tag `tag` from the start of `buf`, returning its contents.
+fn ber_element<'a>(buf: &mut &'a [u8], tag: u8) -> Option<&'a [u8]> {
+    if buf.first() != Some(&tag) {
+        return None;
+    }
+    let (header, len) = ber_header(buf).ok()??;
+    let contents = buf.get(header..header + len)?;
+    *buf = &buf[header + len..];
+    Some(contents)
+}
+
+fn starts_with_ignore_case(s: &str, prefix: &str) -> bool {
+    s.len() >= prefix.len() && s.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+}
+
+/// A stream along with the bytes read from it which have not been consumed.
+struct Conn<S> {
+    stream: S,
+    buf: Vec<u8>,
+}
+
+impl<S> Conn<S>
+where
+    S: Read + Write,
+{
+    fn fill(&mut self) -> Result<(), StartTlsError> {
+        let mut chunk = [0; 1024];
+        let n = loop {
+            match self.stream.read(&mut chunk) {
+                Ok(n) => break n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        };
+        if n == 0 {
+            return Err(StartTlsError::protocol("connection closed during STARTTLS"));
+        }
+        self.buf.extend_from_slice(&chunk[..n]);
+        Ok(())
+    }
+
+    fn read_line(&mut self) -> Result<String, StartTlsError> {
+        loop {
+            if let Some(idx) = self.buf.iter().position(|&b| b == b'\n') {
+                let mut line = self.buf.drain(..=idx).collect::<Vec<_>>();
+                line.pop();
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                return String::from_utf8(line)
+                    .map_err(|_| StartTlsError::protocol("response is not valid UTF-8"));
+            }
+            if self.buf.len() > MAX_LINE {
+                return Err(StartTlsError::protocol("response line is too long"));
+            }
+            self.fill()?;
+        }
+    }
+
+    fn read_ber(&mut self, tag: u8) -> Result<Vec<u8>, StartTlsError> {
+        let len = loop {
+            if let Some((header, len)) = ber_header(&self.buf)? {
+                break header + len;
+            }
+            self.fill()?;
+        };
+        if self.buf[0] != tag || len > MAX_LINE {
+            return Err(StartTlsError::protocol("invalid LDAP response"));
+        }
+        while self.buf.len() < len {
+            self.fill()?;
+        }
+        Ok(self.buf.drain(..len).collect())
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<(), StartTlsError> {
+        self.stream.write_all(data)?;
+        self.stream.flush()?;
+        Ok(())
+    }
+}
+
+/// An error performing a STARTTLS exchange.
+pub struct StartTlsError(Inner);
+
+enum Inner {
+    Protocol(String),
+    Io(io::Error),
+    Ssl(ErrorStack),
+    Tls(ssl::Error),
+}
+
+impl StartTlsError {
+    fn protocol<S>(msg: S) -> StartTlsError
+    where
+        S: Into<String>,
+    {
+        StartTlsError(Inner::Protocol(msg.into()))
+    }
+
+    /// Returns the underlying OpenSSL error stack, if the error was reported by OpenSSL.
+    pub fn ssl_error(&self) -> Option<&ErrorStack> {
+        match self.0 {
+            Inner::Ssl(ref e) => Some(e),
+            Inner::Tls(ref e) => e.ssl_error(),
+            _ => None,
+        }
+    }
+
+    /// Returns the underlying I/O error, if the error occurred reading from or writing to the
+    /// stream.
+    pub fn io_error(&self) -> Option<&io::Error> {
+        match self.0 {
+            Inner::Io(ref e) => Some(e),
+            Inner::Tls(ref e) => e.io_error(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Debug for StartTlsError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Inner::Protocol(ref e) => fmt.debug_tuple("Protocol").field(e).finish(),
+            Inner::Io(ref e) => fmt.debug_tuple("Io").field(e).finish(),
+            Inner::Ssl(ref e) => fmt.debug_tuple("Ssl").field(e).finish(),
+            Inner::Tls(ref e) => fmt.debug_tuple("Tls").field(e).finish(),
+        }
+    }
+}
+
+impl fmt::Display for StartTlsError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Inner::Protocol(ref e) => write!(fmt, "STARTTLS failed: {}", e),
+            Inner::Io(ref e) => fmt::Display::fmt(e, fmt),
+            Inner::Ssl(ref e) => fmt::Display::fmt(e, fmt),
+            Inner::Tls(ref e) => fmt::Display::fmt(e, fmt),
+        }
+    }
+}
+
+impl error::Error for StartTlsError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self.0 {
+            Inner::Protocol(_) => None,
+            Inner::Io(ref e) => Some(e),
+            Inner::Ssl(ref e) => Some(e),
+            Inner::Tls(ref e) => Some(e),
+        }
+    }
+}
+
+impl From<ErrorStack> for StartTlsError {
+    fn from(e: ErrorStack) -> StartTlsError {
+        StartTlsError(Inner::Ssl(e))
+    }
+}
+
+impl From<io::Error> for StartTlsError {
+    fn from(e: io::Error) -> StartTlsError {
+        StartTlsError(Inner::Io(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ssl::{Ssl, SslConnector, SslContext, SslFiletype, SslMethod, SslVerifyMode};
+    use std::io::Cursor;
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    /// A stream which replays a scripted server and records what the client writes.
+    #[derive(Debug)]
+    struct Script {
+        input: Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+
+    impl Script {
+        fn new(input: &[u8]) -> Script {
+            Script {
+                input: Cursor::new(input.to_vec()),
+                output: vec![],
+            }
+        }
+    }
+
+    impl Read for Script {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for Script {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn negotiate(protocol: Protocol, input: &[u8]) -> Result<(Script, Vec<u8>), StartTlsError> {
+        StartTls::new(protocol).negotiate(Script::new(input))
+    }
+
+    #[test]
+    fn smtp() {
+        let (script, buffered) = negotiate(
+            Protocol::Smtp,
+            b"220 mail.example.com ESMTP\r\n\
+              250-mail.example.com\r\n250-PIPELINING\r\n250 STARTTLS\r\n\
+              220 go ahead\r\n\x16\x03\x03",
+        )
+        .unwrap();
+        assert_eq!(script.output, b"EHLO localhost\r\nSTARTTLS\r\n");
+        assert_eq!(buffered, b"\x16\x03\x03");
+
+        let e = negotiate(
+            Protocol::Smtp,
+            b"220 mail.example.com\r\n250-mail.example.com\r\n250 PIPELINING\r\n",
+        )
+        .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "STARTTLS failed: server does not advertise STARTTLS"
+        );
+
+        assert!(negotiate(Protocol::Smtp, b"554 go away\r\n").is_err());
+        assert!(negotiate(Protocol::Smtp, b"220 hi\r\n").is_err());
+    }
+
+    #[test]
+    fn imap() {
+        let (script, buffered) = negotiate(
+            Protocol::Imap,
+            b"* OK IMAP4rev1 ready\r\n* CAPABILITY IMAP4rev1 STARTTLS\r\na1 OK begin TLS\r\n",
+        )
+        .unwrap();
+        assert_eq!(script.output, b"a1 STARTTLS\r\n");
+        assert!(buffered.is_empty());
+
+        assert!(negotiate(Protocol::Imap, b"* OK ready\r\na1 BAD no\r\n").is_err());
+        assert!(negotiate(Protocol::Imap, b"* PREAUTH ready\r\n").is_err());
+    }
+
+    #[test]
+    fn pop3() {
+        let (script, _) =
+            negotiate(Protocol::Pop3, b"+OK POP3 ready\r\n+OK begin TLS\r\n").unwrap();
+        assert_eq!(script.output, b"STLS\r\n");
+
+        assert!(negotiate(Protocol::Pop3, b"+OK POP3 ready\r\n-ERR no\r\n").is_err());
+    }
+
+    #[test]
+    fn ldap() {
+        let (script, buffered) = negotiate(
+            Protocol::Ldap,
+            b"\x30\x0c\x02\x01\x01\x78\x07\x0a\x01\x00\x04\x00\x04\x00\x16",
+        )
+        .unwrap();
+        assert_eq!(script.output.len(), 31);
+        assert_eq!(&script.output[9..], LDAP_START_TLS_OID);
+        assert_eq!(buffered, b"\x16");
+
+        // protocolError
+        let e = negotiate(
+            Protocol::Ldap,
+            b"\x30\x0c\x02\x01\x01\x78\x07\x0a\x01\x02\x04\x00\x04\x00",
+        )
+        .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "STARTTLS failed: LDAP StartTLS failed with result code 2"
+        );
+        assert!(negotiate(Protocol::Ldap, b"\x30\x03\x02\x01\x01").is_err());
+        assert!(negotiate(Protocol::Ldap, b"\x31\x02").is_err());
+    }
+
+    #[test]
+    fn line_too_long() {
+        let input = vec![b'a'; MAX_LINE * 2];
+        assert!(negotiate(Protocol::Pop3, &input).is_err());
+    }
+
+    #[test]
+    fn connect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+            ctx.set_certificate_chain_file("test/cert.pem").unwrap();
+            ctx.set_private_key_file("test/key.pem", SslFiletype::PEM)
+                .unwrap();
+            let ssl = Ssl::new(&ctx.build()).unwrap();
+
+            let mut socket = listener.accept().unwrap().0;
+            socket.write_all(b"+OK POP3 ready\r\n").unwrap();
+            let mut buf = [0; 6];
+            socket.read_exact(&mut buf).unwrap();
+            assert_eq!(&buf, b"STLS\r\n");
+            socket.write_all(b"+OK begin TLS\r\n").unwrap();
+
+            let mut s = ssl.accept(socket).unwrap();
+            s.write_all(b"+OK").unwrap();
+        });
+
+        let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+        connector.set_verify(SslVerifyMode::NONE);
+        let config = connector
+            .build()
+            .configure()
+            .unwrap()
+            .verify_hostname(false);
+        let stream = TcpStream::connect(addr).unwrap();
+        let mut s = StartTls::new(Protocol::Pop3)
+            .connect(config, "foobar.com", stream)
+            .unwrap();
+        let mut buf = [0; 3];
+        s.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"+OK");
+
+        server.join().unwrap();
+    }
+}
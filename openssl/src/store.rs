@@ -0,0 +1,405 @@
+//! Loading keys and certificates by URI.
+//!
+//! A [`Store`] loads the objects named by a URI, using the loaders of the built-in and any loaded
+//! providers. The default provider handles `file:` URIs and plain paths, while providers for
+//! hardware keys handle their own schemes. Private keys loaded from those providers never leave
+//! the hardware, but can be used like any other `PKey`, for example for TLS client
+//! authentication.
+//!
+//! # Examples
+//!
+//! Use a persistent TPM 2.0 key through the [tpm2-openssl] provider for TLS client
+//! authentication.
+//!
+//! ```no_run
+//! use openssl::provider::Provider;
+//! use openssl::ssl::{SslConnector, SslMethod};
+//! use openssl::store::{Store, StoreInfoType};
+//!
+//! let _tpm2 = Provider::load(None, "tpm2").unwrap();
+//! let _default = Provider::load(None, "default").unwrap();
+//!
+//! let mut store =
+//!     Store::open_with_passphrase_callback(None, "handle:0x81000001", None, |buf| {
+//!         // the authorization value of the key
+//!         buf[..6].copy_from_slice(b"123456");
+//!         Ok(6)
+//!     })
+//!     .unwrap();
+//! store.expect(StoreInfoType::PKEY).unwrap();
+//! let key = store.load().unwrap().unwrap().private_key().unwrap();
+//!
+//! let mut connector = SslConnector::builder(SslMethod::tls()).unwrap();
+//! connector.set_private_key(&key).unwrap();
+//! ```
+//!
+//! [tpm2-openssl]: https://github.com/tpm2-software/tpm2-openssl
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::{c_char, c_int, c_void};
+use openssl_macros::corresponds;
+use std::any::Any;
+use std::cmp;
+use std::ffi::CString;
+use std::ptr;
+use std::slice;
+
+use crate::error::ErrorStack;
+use crate::lib_ctx::LibCtxRef;
+use crate::pkey::{PKey, Private, Public};
+use crate::x509::X509;
+use crate::{cvt, cvt_p};
+
+/// The type of an object loaded from a [`Store`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StoreInfoType(c_int);
+
+impl StoreInfoType {
+    /// The name of another object, for example an entry of a directory.
+    pub const NAME: StoreInfoType = StoreInfoType(ffi::OSSL_STORE_INFO_NAME);
+    /// Key parameters.
+    pub const PARAMS: StoreInfoType = StoreInfoType(ffi::OSSL_STORE_INFO_PARAMS);
+    /// A public key.
+    pub const PUBKEY: StoreInfoType = StoreInfoType(ffi::OSSL_STORE_INFO_PUBKEY);
+    /// A private key.
+    pub const PKEY: StoreInfoType = StoreInfoType(ffi::OSSL_STORE_INFO_PKEY);
+    /// A certificate.
+    pub const CERT: StoreInfoType = StoreInfoType(ffi::OSSL_STORE_INFO_CERT);
+    /// A certificate revocation list.
+    pub const CRL: StoreInfoType = StoreInfoType(ffi::OSSL_STORE_INFO_CRL);
+
+    /// Constructs a `StoreInfoType` from a raw OpenSSL value.
+    pub fn from_raw(raw: c_int) -> StoreInfoType {
+        StoreInfoType(raw)
+    }
+
+    /// Returns the raw OpenSSL value represented by this type.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_raw(&self) -> c_int {
+        self.0
+    }
+}
+
+foreign_type_and_impl_send_sync! {
+    type CType = ffi::OSSL_STORE_INFO;
+    fn drop = ffi::OSSL_STORE_INFO_free;
+
+    /// An object loaded from a [`Store`].
+    pub struct StoreInfo;
+    /// A reference to a [`StoreInfo`].
+    pub struct StoreInfoRef;
+}
+
+impl StoreInfoRef {
+    /// Returns the type of the object.
+    #[corresponds(OSSL_STORE_INFO_get_type)]
+    pub fn type_(&self) -> StoreInfoType {
+        unsafe { StoreInfoType(ffi::OSSL_STORE_INFO_get_type(self.as_ptr())) }
+    }
+
+    /// Returns the object if it is a private key.
+    #[corresponds(OSSL_STORE_INFO_get1_PKEY)]
+    pub fn private_key(&self) -> Option<PKey<Private>> {
+        if self.type_() != StoreInfoType::PKEY {
+            return None;
+        }
+        unsafe {
+            let pkey = ffi::OSSL_STORE_INFO_get1_PKEY(self.as_ptr());
+            if pkey.is_null() {
+                None
+            } else {
+                Some(PKey::from_ptr(pkey))
+            }
+        }
+    }
+
+    /// Returns the object if it is a public key.
+    #[corresponds(OSSL_STORE_INFO_get1_PUBKEY)]
+    pub fn public_key(&self) -> Option<PKey<Public>> {
+        if self.type_() != StoreInfoType::PUBKEY {
+            return None;
+        }
+        unsafe {
+            let pkey = ffi::OSSL_STORE_INFO_get1_PUBKEY(self.as_ptr());
+            if pkey.is_null() {
+                None
+            } else {
+                Some(PKey::from_ptr(pkey))
+            }
+        }
+    }
+
+    /// Returns the object if it is a certificate.
+    #[corresponds(OSSL_STORE_INFO_get1_CERT)]
+    pub fn certificate(&self) -> Option<X509> {
+        if self.type_() != StoreInfoType::CERT {
+            return None;
+        }
+        unsafe {
+            let cert = ffi::OSSL_STORE_INFO_get1_CERT(self.as_ptr());
+            if cert.is_null() {
+                None
+            } else {
+                Some(X509::from_ptr(cert))
+            }
+        }
+    }
+}
+
+type PassphraseCallback = dyn FnMut(&mut [u8]) -> Result<usize, ErrorStack> + Send;
+
+struct PassphraseState {
+    callback: Box<PassphraseCallback>,
+    panic: Option<Box<dyn Any + Send + 'static>>,
+}
+
+struct Passphrase {
+    method: *mut ffi::UI_METHOD,
+    state: Box<PassphraseState>,
+}
+
+impl Drop for Passphrase {
+    fn drop(&mut self) {
+        unsafe { ffi::UI_destroy_method(self.method) }
+    }
+}
+
+/// A source of keys and certificates, opened from a URI.
+pub struct Store {
+    ctx: *mut ffi::OSSL_STORE_CTX,
+    // must outlive ctx
+    passphrase: Option<Passphrase>,
+}
+
+unsafe impl Send for Store {}
+
+impl Drop for Store {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::OSSL_STORE_close(self.ctx);
+        }
+    }
+}
+
+impl Store {
+    /// Opens the objects at `uri`.
+    ///
+    /// If `ctx` is `None`, the default library context is used. `properties` is the property
+    /// query used to select the loader, for example `provider=tpm2`.
+    #[corresponds(OSSL_STORE_open_ex)]
+    pub fn open(
+        ctx: Option<&LibCtxRef>,
+        uri: &str,
+        properties: Option<&str>,
+    ) -> Result<Store, ErrorStack> {
+        Store::open_inner(ctx, uri, properties, None)
+    }
+
+    /// Like [`Store::open`], but uses a callback to provide pass phrases.
+    ///
+    /// The callback is called each time the loader needs a pass phrase or authorization value,
+    /// which may happen more than once, for example for both a key and the parent key it is
+    /// protected by. It should write the pass phrase into the provided buffer and return its
+    /// length. A length larger than the buffer is truncated to the buffer's length.
+    #[corresponds(OSSL_STORE_open_ex)]
+    pub fn open_with_passphrase_callback<F>(
+        ctx: Option<&LibCtxRef>,
+        uri: &str,
+        properties: Option<&str>,
+        callback: F,
+    ) -> Result<Store, ErrorStack>
+    where
+        F: FnMut(&mut [u8]) -> Result<usize, ErrorStack> + Send + 'static,
+    {
+        let passphrase = unsafe {
+            Passphrase {
+                method: cvt_p(ffi::UI_UTIL_wrap_read_pem_callback(
+                    Some(invoke_passphrase_cb),
+                    0,
+                ))?,
+                state: Box::new(PassphraseState {
+                    callback: Box::new(callback),
+                    panic: None,
+                }),
+            }
+        };
+        Store::open_inner(ctx, uri, properties, Some(passphrase))
+    }
+
+    fn open_inner(
+        ctx: Option<&LibCtxRef>,
+        uri: &str,
+        properties: Option<&str>,
+        mut passphrase: Option<Passphrase>,
+    ) -> Result<Store, ErrorStack> {
+        let uri = CString::new(uri).unwrap();
+        let properties = properties.map(|p| CString::new(p).unwrap());
+
+        unsafe {
+            let (method, data) = match passphrase {
+                Some(ref mut passphrase) => (
+                    passphrase.method as *const _,
+                    &mut *passphrase.state as *mut PassphraseState as *mut c_void,
+                ),
+                None => (ptr::null(), ptr::null_mut()),
+            };
+            let r = cvt_p(ffi::OSSL_STORE_open_ex(
+                uri.as_ptr(),
+                ctx.map_or(ptr::null_mut(), ForeignTypeRef::as_ptr),
+                properties.as_ref().map_or(ptr::null(), |p| p.as_ptr()),
+                method,
+                data,
+                ptr::null(),
+                None,
+                ptr::null_mut(),
+            ));
+            resume_panic(&mut passphrase);
+
+            Ok(Store {
+                ctx: r?,
+                passphrase,
+            })
+        }
+    }
+
+    /// Restricts the objects which will be loaded to those of one type.
+    ///
+    /// This must be called before the first call to [`load`], and allows the loader to skip
+    /// other objects, for example without asking for their pass phrase.
+    ///
+    /// [`load`]: Store::load
+    #[corresponds(OSSL_STORE_expect)]
+    pub fn expect(&mut self, type_: StoreInfoType) -> Result<(), ErrorStack> {
+        unsafe { cvt(ffi::OSSL_STORE_expect(self.ctx, type_.0)).map(|_| ()) }
+    }
+
+    /// Loads the next object.
+    ///
+    /// `None` is returned once every object has been loaded. An object which could not be
+    /// loaded is reported as an error, after which the following objects can still be loaded.
+    #[corresponds(OSSL_STORE_load)]
+    pub fn load(&mut self) -> Result<Option<StoreInfo>, ErrorStack> {
+        unsafe {
+            loop {
+                if ffi::OSSL_STORE_eof(self.ctx) != 0 {
+                    return Ok(None);
+                }
+
+                let info = ffi::OSSL_STORE_load(self.ctx);
+                resume_panic(&mut self.passphrase);
+                if !info.is_null() {
+                    return Ok(Some(StoreInfo::from_ptr(info)));
+                }
+                // the end of the objects is only detected by the failure to load another
+                if ffi::OSSL_STORE_eof(self.ctx) != 0 {
+                    ErrorStack::get();
+                    return Ok(None);
+                }
+                if ffi::OSSL_STORE_error(self.ctx) != 0 {
+                    return Err(ErrorStack::report());
+                }
+            }
+        }
+    }
+}
+
+fn resume_panic(passphrase: &mut Option<Passphrase>) {
+    if let Some(panic) = passphrase.as_mut().and_then(|p| p.state.panic.take()) {
+        crate::panic::resume(panic);
+    }
+}
+
+unsafe extern "C" fn invoke_passphrase_cb(
+    buf: *mut c_char,
+    size: c_int,
+    _rwflag: c_int,
+    data: *mut c_void,
+) -> c_int {
+    let state = &mut *(data as *mut PassphraseState);
+
+    let result = crate::panic::catch(|| {
+        let buf = slice::from_raw_parts_mut(buf as *mut u8, size as usize);
+        (state.callback)(buf)
+    });
+
+    match result {
+        Ok(Ok(len)) => cmp::min(len, size as usize) as c_int,
+        Ok(Err(e)) => {
+            e.put();
+            -1
+        }
+        Err(err) => {
+            state.panic = Some(err);
+            -1
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn path(name: &str) -> String {
+        format!("{}/test/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    #[test]
+    fn private_key() {
+        let mut store = Store::open(None, &path("key.pem"), None).unwrap();
+        let info = store.load().unwrap().unwrap();
+        assert_eq!(info.type_(), StoreInfoType::PKEY);
+        assert!(info.certificate().is_none());
+        let key = info.private_key().unwrap();
+        assert!(store.load().unwrap().is_none());
+
+        let expected = PKey::private_key_from_pem(include_bytes!("../test/key.pem")).unwrap();
+        assert!(key.public_eq(&expected));
+    }
+
+    #[test]
+    fn certificates() {
+        let uri = format!("file:{}", path("certs.pem"));
+        let mut store = Store::open(None, &uri, Some("provider=default")).unwrap();
+        store.expect(StoreInfoType::CERT).unwrap();
+
+        let mut certs = vec![];
+        while let Some(info) = store.load().unwrap() {
+            certs.push(info.certificate().unwrap());
+        }
+        assert_eq!(
+            certs,
+            X509::stack_from_pem(include_bytes!("../test/certs.pem")).unwrap()
+        );
+    }
+
+    #[test]
+    fn passphrase() {
+        let mut store =
+            Store::open_with_passphrase_callback(None, &path("rsa-encrypted.pem"), None, |buf| {
+                buf[..6].copy_from_slice(b"mypass");
+                Ok(6)
+            })
+            .unwrap();
+        let info = store.load().unwrap().unwrap();
+        assert!(info.private_key().is_some());
+
+        let mut store =
+            Store::open_with_passphrase_callback(None, &path("rsa-encrypted.pem"), None, |buf| {
+                buf[..5].copy_from_slice(b"wrong");
+                Ok(5)
+            })
+            .unwrap();
+        assert!(!matches!(store.load(), Ok(Some(info)) if info.private_key().is_some()));
+
+        let mut store =
+            Store::open_with_passphrase_callback(None, &path("rsa-encrypted.pem"), None, |buf| {
+                Ok(buf.len() + 1)
+            })
+            .unwrap();
+        assert!(!matches!(store.load(), Ok(Some(info)) if info.private_key().is_some()));
+    }
+
+    #[test]
+    fn missing() {
+        assert!(Store::open(None, &path("missing.pem"), None).is_err());
+    }
+}
@@ -55,8 +55,13 @@ use crate::cipher::CipherRef;
 use crate::cipher_ctx::{CipherCtx, CipherCtxRef};
 use crate::error::ErrorStack;
 use crate::nid::Nid;
+use crate::thread_cache;
 use cfg_if::cfg_if;
 use foreign_types::ForeignTypeRef;
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CHACHA")))]
+use std::cmp;
+use std::convert::TryFrom;
+use std::ptr;
 
 #[derive(Copy, Clone)]
 pub enum Mode {
@@ -413,6 +418,31 @@ impl Cipher {
     const fn is_ocb(self) -> bool {
         false
     }
+
+    /// Determines whether the cipher is using CTR mode
+    fn is_ctr(self) -> bool {
+        #[cfg(all(any(ossl111, libressl291), not(osslconf = "OPENSSL_NO_SM4")))]
+        {
+            if self == Cipher::sm4_ctr() {
+                return true;
+            }
+        }
+
+        self == Cipher::aes_128_ctr()
+            || self == Cipher::aes_192_ctr()
+            || self == Cipher::aes_256_ctr()
+    }
+
+    /// Determines whether the cipher is using OFB or CFB mode
+    #[cfg(ossl110)]
+    fn feedback(self) -> Option<Feedback> {
+        let cfb = match unsafe { ffi::EVP_CIPHER_flags(self.as_ptr()) } & ffi::EVP_CIPH_MODE {
+            ffi::EVP_CIPH_OFB_MODE => false,
+            ffi::EVP_CIPH_CFB_MODE => true,
+            _ => return None,
+        };
+        Some(Feedback { cfb, partial: None })
+    }
 }
 
 unsafe impl Sync for Cipher {}
@@ -480,6 +510,97 @@ unsafe impl Send for Cipher {}
 /// ```
 pub struct Crypter {
     ctx: CipherCtx,
+    mode: Mode,
+    // only tracked for ciphers in CTR mode, where the keystream can be repositioned
+    ctr: Option<Ctr>,
+    // only tracked for ciphers in OFB or CFB mode, whose state can be exported
+    #[cfg(ossl110)]
+    feedback: Option<Feedback>,
+}
+
+struct Ctr {
+    iv: Vec<u8>,
+    // the counter block is one block of the underlying cipher, which is our IV length, since
+    // OpenSSL reports a block size of 1 for CTR mode
+    block_len: usize,
+    position: u64,
+}
+
+#[cfg(ossl110)]
+struct Feedback {
+    cfb: bool,
+    // the rest of a block restored partway through, which is processed here since OpenSSL
+    // cannot reliably be told how much of its IV has been used
+    partial: Option<FeedbackState>,
+}
+
+/// The position of a [`Crypter`] in a CTR mode keystream.
+///
+/// This can be persisted and later applied to a new `Crypter` with the same cipher and key to
+/// resume encryption or decryption at the same byte offset, for example to continue an
+/// interrupted upload.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CtrState {
+    iv: Vec<u8>,
+    position: u64,
+}
+
+impl CtrState {
+    /// Creates a new `CtrState` from the initial counter block and a byte offset into the
+    /// keystream.
+    pub fn new(iv: &[u8], position: u64) -> CtrState {
+        CtrState {
+            iv: iv.to_vec(),
+            position,
+        }
+    }
+
+    /// Returns the initial counter block.
+    pub fn iv(&self) -> &[u8] {
+        &self.iv
+    }
+
+    /// Returns the byte offset into the keystream.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+/// The state of a [`Crypter`] partway through a message in OFB or CFB mode.
+///
+/// Each block in these modes depends on the previous one, so the state is the IV as updated by
+/// the data processed so far together with the number of bytes already used of the current block.
+/// It can be persisted and later applied to a new `Crypter` with the same cipher and key to resume
+/// encryption or decryption where it left off.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+#[cfg(ossl110)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeedbackState {
+    iv: Vec<u8>,
+    num: usize,
+}
+
+#[cfg(ossl110)]
+impl FeedbackState {
+    /// Creates a new `FeedbackState` from an updated IV and the number of bytes used of the
+    /// current block.
+    pub fn new(iv: &[u8], num: usize) -> FeedbackState {
+        FeedbackState {
+            iv: iv.to_vec(),
+            num,
+        }
+    }
+
+    /// Returns the updated IV.
+    pub fn iv(&self) -> &[u8] {
+        &self.iv
+    }
+
+    /// Returns the number of bytes used of the current block.
+    pub fn num(&self) -> usize {
+        self.num
+    }
 }
 
 impl Crypter {
@@ -497,30 +618,247 @@ impl Crypter {
         iv: Option<&[u8]>,
     ) -> Result<Crypter, ErrorStack> {
         let mut ctx = CipherCtx::new()?;
-
-        let f = match mode {
-            Mode::Encrypt => CipherCtxRef::encrypt_init,
-            Mode::Decrypt => CipherCtxRef::decrypt_init,
+        init(&mut ctx, t, mode, key, iv)?;
+
+        let ctr = match t.iv_len() {
+            Some(block_len) if t.is_ctr() => Some(Ctr {
+                iv: iv.map(|iv| iv.to_vec()).unwrap_or_default(),
+                block_len,
+                position: 0,
+            }),
+            _ => None,
         };
 
-        f(
-            &mut ctx,
-            Some(unsafe { CipherRef::from_ptr(t.as_ptr() as *mut _) }),
-            None,
-            None,
-        )?;
+        Ok(Crypter {
+            ctx,
+            mode,
+            ctr,
+            #[cfg(ossl110)]
+            feedback: t.feedback(),
+        })
+    }
 
-        ctx.set_key_length(key.len())?;
+    /// Starts a new message with the IV `iv`, keeping the key.
+    ///
+    /// Setting up the key schedule is the most expensive part of [`Crypter::new`], so this is
+    /// considerably faster than creating a new `Crypter` for every message, for example when
+    /// encrypting individual packets. For AEAD ciphers such as AES GCM, `iv` is the nonce of the
+    /// next message, and any AAD and tag of the previous message are discarded. In CCM mode, the
+    /// tag length and data length must be set again before the next message.
+    ///
+    /// The padding setting is retained.
+    pub fn reset_with_iv(&mut self, iv: &[u8]) -> Result<(), ErrorStack> {
+        if iv.len() != self.ctx.iv_length() {
+            self.ctx.set_iv_length(iv.len())?;
+        }
 
-        if let (Some(iv), Some(iv_len)) = (iv, t.iv_len()) {
-            if iv.len() != iv_len {
-                ctx.set_iv_length(iv.len())?;
+        match self.mode {
+            Mode::Encrypt => self.ctx.encrypt_init(None, None, Some(iv))?,
+            Mode::Decrypt => self.ctx.decrypt_init(None, None, Some(iv))?,
+        }
+        if let Some(ctr) = &mut self.ctr {
+            ctr.iv.clear();
+            ctr.iv.extend_from_slice(iv);
+            ctr.position = 0;
+        }
+        #[cfg(ossl110)]
+        if let Some(feedback) = &mut self.feedback {
+            feedback.partial = None;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the initial counter block in CTR mode and positions the keystream at the start of
+    /// block `block`.
+    ///
+    /// The counter block is treated as a single big-endian integer, so `block` is added to the
+    /// whole of `iv`. The key is left unchanged.
+    ///
+    /// Returns an error if the cipher is not in CTR mode.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `iv` is not one block long.
+    pub fn set_iv_counter(&mut self, iv: &[u8], block: u64) -> Result<(), ErrorStack> {
+        let ctr = self.ctr_mut()?;
+        assert_eq!(iv.len(), ctr.block_len);
+
+        ctr.iv.clear();
+        ctr.iv.extend_from_slice(iv);
+        self.seek_to_block(block)
+    }
+
+    /// Positions the keystream at the start of block `block` in CTR mode, relative to the initial
+    /// counter block.
+    ///
+    /// The initial counter block is the IV passed to [`Crypter::new`] or the most recent call to
+    /// [`Crypter::set_iv_counter`].
+    ///
+    /// Returns an error if the cipher is not in CTR mode, or if no IV has been set.
+    pub fn seek_to_block(&mut self, block: u64) -> Result<(), ErrorStack> {
+        let ctr = self.ctr_mut()?;
+        if ctr.iv.is_empty() {
+            return Err(unsupported_cipher());
+        }
+
+        let mut iv = ctr.iv.clone();
+        let mut carry = block;
+        for b in iv.iter_mut().rev() {
+            if carry == 0 {
+                break;
             }
+            let sum = u64::from(*b) + (carry & 0xff);
+            *b = sum as u8;
+            carry = (carry >> 8) + (sum >> 8);
+        }
+
+        match self.mode {
+            Mode::Encrypt => self.ctx.encrypt_init(None, None, Some(&iv))?,
+            Mode::Decrypt => self.ctx.decrypt_init(None, None, Some(&iv))?,
+        }
+        let ctr = self.ctr_mut()?;
+        ctr.position = block.wrapping_mul(ctr.block_len as u64);
+
+        Ok(())
+    }
+
+    /// Returns the current position in a CTR mode keystream.
+    ///
+    /// The position is the number of bytes passed to [`Crypter::update`] since the initial
+    /// counter block was set, adjusted by any calls to [`Crypter::seek_to_block`].
+    ///
+    /// Returns `None` if the cipher is not in CTR mode. The state of OFB and CFB modes is
+    /// returned by [`Crypter::feedback_state`] instead.
+    pub fn ctr_state(&self) -> Option<CtrState> {
+        self.ctr.as_ref().map(|ctr| CtrState {
+            iv: ctr.iv.clone(),
+            position: ctr.position,
+        })
+    }
+
+    /// Restores a position in a CTR mode keystream previously returned by
+    /// [`Crypter::ctr_state`].
+    ///
+    /// Returns an error if the cipher is not in CTR mode.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state's counter block is not one block long.
+    pub fn set_ctr_state(&mut self, state: &CtrState) -> Result<(), ErrorStack> {
+        let block_len = self.ctr_mut()?.block_len as u64;
+
+        self.set_iv_counter(&state.iv, state.position / block_len)?;
+
+        // Discard the keystream preceding the offset in the current block.
+        let skip = (state.position % block_len) as usize;
+        if skip != 0 {
+            let input = vec![0; skip];
+            let mut output = vec![0; skip];
+            self.update(&input, &mut output)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the state of an OFB or CFB mode cipher partway through a message.
+    ///
+    /// Returns an error if the cipher is not in OFB or CFB mode.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(ossl110)]
+    pub fn feedback_state(&self) -> Result<FeedbackState, ErrorStack> {
+        let feedback = self.feedback.as_ref().ok_or_else(unsupported_cipher)?;
+        if let Some(partial) = &feedback.partial {
+            return Ok(partial.clone());
+        }
+
+        let mut iv = vec![0; self.ctx.iv_length()];
+        self.ctx.updated_iv(&mut iv)?;
+        Ok(FeedbackState {
+            iv,
+            num: self.ctx.num(),
+        })
+    }
+
+    /// Restores the state of an OFB or CFB mode cipher previously returned by
+    /// [`Crypter::feedback_state`].
+    ///
+    /// Returns an error if the cipher is not in OFB or CFB mode.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state's IV is not the length of the cipher's IV, or if the number of bytes
+    /// used is not less than that.
+    #[cfg(ossl110)]
+    pub fn set_feedback_state(&mut self, state: &FeedbackState) -> Result<(), ErrorStack> {
+        if self.feedback.is_none() {
+            return Err(unsupported_cipher());
+        }
+        assert_eq!(state.iv.len(), self.ctx.iv_length());
+        assert!(state.num < state.iv.len());
+
+        self.reinit_feedback(&state.iv)?;
+        if let Some(feedback) = &mut self.feedback {
+            feedback.partial = if state.num == 0 {
+                None
+            } else {
+                Some(state.clone())
+            };
+        }
+
+        Ok(())
+    }
+
+    // Starts a new block with the IV `iv`.
+    #[cfg(ossl110)]
+    fn reinit_feedback(&mut self, iv: &[u8]) -> Result<(), ErrorStack> {
+        match self.mode {
+            Mode::Encrypt => self.ctx.encrypt_init(None, None, Some(iv)),
+            Mode::Decrypt => self.ctx.decrypt_init(None, None, Some(iv)),
         }
+    }
 
-        f(&mut ctx, None, Some(key), iv)?;
+    // Processes the rest of a block restored partway through, returning the number of bytes
+    // consumed. In both modes the unused part of the IV is the keystream, and in CFB mode it is
+    // replaced by the ciphertext to form the IV of the next block.
+    #[cfg(ossl110)]
+    fn update_partial(&mut self, input: &[u8], output: &mut [u8]) -> Result<usize, ErrorStack> {
+        let mode = self.mode;
+        let feedback = match &mut self.feedback {
+            Some(feedback) => feedback,
+            None => return Ok(0),
+        };
+        let cfb = feedback.cfb;
+        let state = match &mut feedback.partial {
+            Some(state) => state,
+            None => return Ok(0),
+        };
 
-        Ok(Crypter { ctx })
+        let len = cmp::min(input.len(), state.iv.len() - state.num);
+        let keystream = &mut state.iv[state.num..state.num + len];
+        for ((k, &i), o) in keystream
+            .iter_mut()
+            .zip(&input[..len])
+            .zip(&mut output[..len])
+        {
+            *o = i ^ *k;
+            if cfb {
+                *k = match mode {
+                    Mode::Encrypt => *o,
+                    Mode::Decrypt => i,
+                };
+            }
+        }
+        state.num += len;
+
+        if state.num == state.iv.len() {
+            let iv = feedback.partial.take().unwrap().iv;
+            self.reinit_feedback(&iv)?;
+        }
+        Ok(len)
     }
 
     /// Enables or disables padding.
@@ -579,7 +917,22 @@ impl Crypter {
     ///
     /// Panics if `output.len() > c_int::max_value()`.
     pub fn update(&mut self, input: &[u8], output: &mut [u8]) -> Result<usize, ErrorStack> {
-        self.ctx.cipher_update(input, Some(output))
+        #[cfg(ossl110)]
+        let done = self.update_partial(input, output)?;
+        #[cfg(not(ossl110))]
+        let done = 0;
+
+        let len = self
+            .ctx
+            .cipher_update(&input[done..], Some(&mut output[done..]))?;
+        if let Some(ctr) = &mut self.ctr {
+            ctr.position = ctr.position.wrapping_add(input.len() as u64);
+        }
+        Ok(done + len)
+    }
+
+    fn ctr_mut(&mut self) -> Result<&mut Ctr, ErrorStack> {
+        self.ctr.as_mut().ok_or_else(unsupported_cipher)
     }
 
     /// Finishes the encryption/decryption process, writing any remaining data
@@ -610,6 +963,153 @@ impl Crypter {
     }
 }
 
+/// An unauthenticated ChaCha20 stream cipher with an explicit block counter.
+///
+/// OpenSSL's [`Cipher::chacha20`] takes a 16 byte IV made up of a little-endian block counter
+/// followed by the nonce. This type builds that IV from either the 32-bit counter and 96-bit nonce
+/// of [RFC 8439] or the original 64-bit counter and 64-bit nonce construction, and allows the
+/// keystream to be repositioned at any block boundary.
+///
+/// ChaCha20 provides no integrity protection. Prefer [`Cipher::chacha20_poly1305`] unless a
+/// protocol specifically requires the raw stream cipher.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+///
+/// # Examples
+///
+/// ```
+/// use openssl::symm::{ChaCha20, Mode};
+///
+/// let key = [0x42; 32];
+/// let nonce = [0x24; 12];
+/// let data = [0; 256];
+///
+/// let mut ciphertext = [0; 256];
+/// let mut c = ChaCha20::new(Mode::Encrypt, &key, &nonce, 0).unwrap();
+/// c.update(&data, &mut ciphertext).unwrap();
+///
+/// // Decrypt only the third 64 byte block.
+/// let mut plaintext = [0; 64];
+/// let mut c = ChaCha20::new(Mode::Decrypt, &key, &nonce, 0).unwrap();
+/// c.seek(2).unwrap();
+/// c.update(&ciphertext[128..192], &mut plaintext).unwrap();
+/// assert_eq!(plaintext, data[128..192]);
+/// ```
+///
+/// [RFC 8439]: https://datatracker.ietf.org/doc/html/rfc8439
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CHACHA")))]
+pub struct ChaCha20 {
+    ctx: CipherCtx,
+    mode: Mode,
+    nonce: ChaCha20Nonce,
+}
+
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CHACHA")))]
+enum ChaCha20Nonce {
+    Ietf([u8; 12]),
+    Legacy([u8; 8]),
+}
+
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CHACHA")))]
+impl ChaCha20 {
+    /// The size of a ChaCha20 block in bytes.
+    pub const BLOCK_SIZE: usize = 64;
+
+    /// Creates a new `ChaCha20` using the [RFC 8439] 32-bit block counter and 96-bit nonce.
+    ///
+    /// `counter` is the index of the first keystream block to use.
+    ///
+    /// [RFC 8439]: https://datatracker.ietf.org/doc/html/rfc8439
+    pub fn new(
+        mode: Mode,
+        key: &[u8; 32],
+        nonce: &[u8; 12],
+        counter: u32,
+    ) -> Result<ChaCha20, ErrorStack> {
+        ChaCha20::new_inner(mode, key, ChaCha20Nonce::Ietf(*nonce), u64::from(counter))
+    }
+
+    /// Creates a new `ChaCha20` using the original 64-bit block counter and 64-bit nonce.
+    ///
+    /// `counter` is the index of the first keystream block to use.
+    pub fn new_64(
+        mode: Mode,
+        key: &[u8; 32],
+        nonce: &[u8; 8],
+        counter: u64,
+    ) -> Result<ChaCha20, ErrorStack> {
+        ChaCha20::new_inner(mode, key, ChaCha20Nonce::Legacy(*nonce), counter)
+    }
+
+    fn new_inner(
+        mode: Mode,
+        key: &[u8; 32],
+        nonce: ChaCha20Nonce,
+        counter: u64,
+    ) -> Result<ChaCha20, ErrorStack> {
+        let mut c = ChaCha20 {
+            ctx: CipherCtx::new()?,
+            mode,
+            nonce,
+        };
+        let iv = c.iv(counter);
+        c.init(Some(crate::cipher::Cipher::chacha20()), Some(key), &iv)?;
+        Ok(c)
+    }
+
+    /// Repositions the keystream at the start of the block with index `block`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this `ChaCha20` was created with [`ChaCha20::new`] and `block` does not fit in
+    /// 32 bits.
+    pub fn seek(&mut self, block: u64) -> Result<(), ErrorStack> {
+        let iv = self.iv(block);
+        self.init(None, None, &iv)
+    }
+
+    /// Feeds data from `input` through the cipher, writing encrypted/decrypted bytes into
+    /// `output`.
+    ///
+    /// The number of bytes written to `output` is returned, which is always the length of
+    /// `input`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `output.len() < input.len()`.
+    pub fn update(&mut self, input: &[u8], output: &mut [u8]) -> Result<usize, ErrorStack> {
+        self.ctx.cipher_update(input, Some(output))
+    }
+
+    fn iv(&self, counter: u64) -> [u8; 16] {
+        let mut iv = [0; 16];
+        match &self.nonce {
+            ChaCha20Nonce::Ietf(nonce) => {
+                let counter = u32::try_from(counter).unwrap();
+                iv[..4].copy_from_slice(&counter.to_le_bytes());
+                iv[4..].copy_from_slice(nonce);
+            }
+            ChaCha20Nonce::Legacy(nonce) => {
+                iv[..8].copy_from_slice(&counter.to_le_bytes());
+                iv[8..].copy_from_slice(nonce);
+            }
+        }
+        iv
+    }
+
+    fn init(
+        &mut self,
+        cipher: Option<&CipherRef>,
+        key: Option<&[u8]>,
+        iv: &[u8],
+    ) -> Result<(), ErrorStack> {
+        match self.mode {
+            Mode::Encrypt => self.ctx.encrypt_init(cipher, key, Some(iv)),
+            Mode::Decrypt => self.ctx.decrypt_init(cipher, key, Some(iv)),
+        }
+    }
+}
+
 /// Encrypts data in one go, and returns the encrypted data.
 ///
 /// Data is encrypted using the specified cipher type `t` in encrypt mode with the specified `key`
@@ -688,6 +1188,60 @@ pub fn decrypt(
     cipher(t, Mode::Decrypt, key, iv, data)
 }
 
+/// Encrypts data in one go into a caller-provided buffer.
+///
+/// This behaves like [`encrypt`], but writes the ciphertext into `out` rather than allocating a
+/// new buffer. Returns the number of bytes written.
+///
+/// # Panics
+///
+/// Panics if `out.len() < data.len() + t.block_size()`.
+///
+/// # Examples
+///
+/// ```
+/// use openssl::symm::{encrypt_to_slice, Cipher};
+///
+/// let cipher = Cipher::aes_128_cbc();
+/// let data = b"Some Crypto Text";
+/// let key = b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0A\x0B\x0C\x0D\x0E\x0F";
+/// let iv = b"\x00\x01\x02\x03\x04\x05\x06\x07\x00\x01\x02\x03\x04\x05\x06\x07";
+/// let mut out = [0; 48];
+/// let len = encrypt_to_slice(cipher, key, Some(iv), data, &mut out).unwrap();
+///
+/// assert_eq!(
+///     b"\xB4\xB9\xE7\x30\xD6\xD6\xF7\xDE\x77\x3F\x1C\xFF\xB3\x3E\x44\x5A\x91\xD7\x27\x62\x87\x4D\
+///       \xFB\x3C\x5E\xC4\x59\x72\x4A\xF4\x7C\xA1",
+///     &out[..len]);
+/// ```
+pub fn encrypt_to_slice(
+    t: Cipher,
+    key: &[u8],
+    iv: Option<&[u8]>,
+    data: &[u8],
+    out: &mut [u8],
+) -> Result<usize, ErrorStack> {
+    cipher_to_slice(t, Mode::Encrypt, key, iv, data, out)
+}
+
+/// Decrypts data in one go into a caller-provided buffer.
+///
+/// This behaves like [`decrypt`], but writes the plaintext into `out` rather than allocating a
+/// new buffer. Returns the number of bytes written.
+///
+/// # Panics
+///
+/// Panics if `out.len() < data.len() + t.block_size()`.
+pub fn decrypt_to_slice(
+    t: Cipher,
+    key: &[u8],
+    iv: Option<&[u8]>,
+    data: &[u8],
+    out: &mut [u8],
+) -> Result<usize, ErrorStack> {
+    cipher_to_slice(t, Mode::Decrypt, key, iv, data, out)
+}
+
 fn cipher(
     t: Cipher,
     mode: Mode,
@@ -695,14 +1249,79 @@ fn cipher(
     iv: Option<&[u8]>,
     data: &[u8],
 ) -> Result<Vec<u8>, ErrorStack> {
-    let mut c = Crypter::new(t, mode, key, iv)?;
     let mut out = vec![0; data.len() + t.block_size()];
-    let count = c.update(data, &mut out)?;
-    let rest = c.finalize(&mut out[count..])?;
-    out.truncate(count + rest);
+    let len = cipher_to_slice(t, mode, key, iv, data, &mut out)?;
+    out.truncate(len);
     Ok(out)
 }
 
+fn cipher_to_slice(
+    t: Cipher,
+    mode: Mode,
+    key: &[u8],
+    iv: Option<&[u8]>,
+    data: &[u8],
+    out: &mut [u8],
+) -> Result<usize, ErrorStack> {
+    thread_cache::with_cipher_ctx(|ctx| {
+        init(ctx, t, mode, key, iv)?;
+        let count = ctx.cipher_update(data, Some(out))?;
+        let rest = ctx.cipher_final(&mut out[count..])?;
+        Ok(count + rest)
+    })
+}
+
+fn init(
+    ctx: &mut CipherCtxRef,
+    t: Cipher,
+    mode: Mode,
+    key: &[u8],
+    iv: Option<&[u8]>,
+) -> Result<(), ErrorStack> {
+    let f = match mode {
+        Mode::Encrypt => CipherCtxRef::encrypt_init,
+        Mode::Decrypt => CipherCtxRef::decrypt_init,
+    };
+
+    f(
+        ctx,
+        Some(unsafe { CipherRef::from_ptr(t.as_ptr() as *mut _) }),
+        None,
+        None,
+    )?;
+
+    ctx.set_key_length(key.len())?;
+
+    if let (Some(iv), Some(iv_len)) = (iv, t.iv_len()) {
+        if iv.len() != iv_len {
+            ctx.set_iv_length(iv.len())?;
+        }
+    }
+
+    f(ctx, None, Some(key), iv)
+}
+
+fn unsupported_cipher() -> ErrorStack {
+    unsafe {
+        cfg_if! {
+            if #[cfg(ossl300)] {
+                ffi::ERR_new();
+                ffi::ERR_set_debug(concat!(file!(), "\0").as_ptr() as *const _, line!() as _, ptr::null());
+                ffi::ERR_set_error(ffi::ERR_LIB_EVP, ffi::EVP_R_UNSUPPORTED_CIPHER, ptr::null());
+            } else {
+                ffi::ERR_put_error(
+                    ffi::ERR_LIB_EVP,
+                    0,
+                    ffi::EVP_R_UNSUPPORTED_CIPHER,
+                    concat!(file!(), "\0").as_ptr() as *const _,
+                    line!() as _,
+                );
+            }
+        }
+    }
+    ErrorStack::report()
+}
+
 /// Like `encrypt`, but for AEAD ciphers such as AES GCM.
 ///
 /// Additional Authenticated Data can be provided in the `aad` field, and the authentication tag
@@ -719,8 +1338,29 @@ pub fn encrypt_aead(
     data: &[u8],
     tag: &mut [u8],
 ) -> Result<Vec<u8>, ErrorStack> {
-    let mut c = Crypter::new(t, Mode::Encrypt, key, iv)?;
     let mut out = vec![0; data.len() + t.block_size()];
+    let len = encrypt_aead_to_slice(t, key, iv, aad, data, tag, &mut out)?;
+    out.truncate(len);
+    Ok(out)
+}
+
+/// Like [`encrypt_aead`], but writes the ciphertext into a caller-provided buffer.
+///
+/// Returns the number of bytes written to `out`.
+///
+/// # Panics
+///
+/// Panics if `out.len() < data.len() + t.block_size()`.
+pub fn encrypt_aead_to_slice(
+    t: Cipher,
+    key: &[u8],
+    iv: Option<&[u8]>,
+    aad: &[u8],
+    data: &[u8],
+    tag: &mut [u8],
+    out: &mut [u8],
+) -> Result<usize, ErrorStack> {
+    let mut c = Crypter::new(t, Mode::Encrypt, key, iv)?;
 
     let is_ccm = t.is_ccm();
     if is_ccm || t.is_ocb() {
@@ -731,11 +1371,10 @@ pub fn encrypt_aead(
     }
 
     c.aad_update(aad)?;
-    let count = c.update(data, &mut out)?;
+    let count = c.update(data, out)?;
     let rest = c.finalize(&mut out[count..])?;
     c.get_tag(tag)?;
-    out.truncate(count + rest);
-    Ok(out)
+    Ok(count + rest)
 }
 
 /// Like `decrypt`, but for AEAD ciphers such as AES GCM.
@@ -750,8 +1389,29 @@ pub fn decrypt_aead(
     data: &[u8],
     tag: &[u8],
 ) -> Result<Vec<u8>, ErrorStack> {
-    let mut c = Crypter::new(t, Mode::Decrypt, key, iv)?;
     let mut out = vec![0; data.len() + t.block_size()];
+    let len = decrypt_aead_to_slice(t, key, iv, aad, data, tag, &mut out)?;
+    out.truncate(len);
+    Ok(out)
+}
+
+/// Like [`decrypt_aead`], but writes the plaintext into a caller-provided buffer.
+///
+/// Returns the number of bytes written to `out`.
+///
+/// # Panics
+///
+/// Panics if `out.len() < data.len() + t.block_size()`.
+pub fn decrypt_aead_to_slice(
+    t: Cipher,
+    key: &[u8],
+    iv: Option<&[u8]>,
+    aad: &[u8],
+    data: &[u8],
+    tag: &[u8],
+    out: &mut [u8],
+) -> Result<usize, ErrorStack> {
+    let mut c = Crypter::new(t, Mode::Decrypt, key, iv)?;
 
     let is_ccm = t.is_ccm();
     if is_ccm || t.is_ocb() {
@@ -762,7 +1422,7 @@ pub fn decrypt_aead(
     }
 
     c.aad_update(aad)?;
-    let count = c.update(data, &mut out)?;
+    let count = c.update(data, out)?;
 
     let rest = if t.is_ccm() {
         0
@@ -771,8 +1431,7 @@ pub fn decrypt_aead(
         c.finalize(&mut out[count..])?
     };
 
-    out.truncate(count + rest);
-    Ok(out)
+    Ok(count + rest)
 }
 
 cfg_if! {
@@ -1492,6 +2151,269 @@ mod tests {
         cipher_test(Cipher::chacha20(), pt, ct, key, iv);
     }
 
+    #[test]
+    fn test_to_slice() {
+        let key = [0x42; 16];
+        let iv = [0x24; 16];
+        let data = b"Some Crypto Text that spans blocks";
+
+        let mut ct = [0; 64];
+        let len = encrypt_to_slice(Cipher::aes_128_cbc(), &key, Some(&iv), data, &mut ct).unwrap();
+        assert_eq!(
+            &ct[..len],
+            &encrypt(Cipher::aes_128_cbc(), &key, Some(&iv), data).unwrap()[..]
+        );
+
+        let mut pt = [0; 64];
+        let len =
+            decrypt_to_slice(Cipher::aes_128_cbc(), &key, Some(&iv), &ct[..len], &mut pt).unwrap();
+        assert_eq!(&pt[..len], &data[..]);
+    }
+
+    #[test]
+    fn test_aead_to_slice() {
+        let key = [0x42; 16];
+        let iv = [0x24; 12];
+        let data = b"Some Crypto Text";
+
+        let mut tag = [0; 16];
+        let mut ct = [0; 32];
+        let len = encrypt_aead_to_slice(
+            Cipher::aes_128_gcm(),
+            &key,
+            Some(&iv),
+            b"aad",
+            data,
+            &mut tag,
+            &mut ct,
+        )
+        .unwrap();
+
+        let mut pt = [0; 32];
+        let len = decrypt_aead_to_slice(
+            Cipher::aes_128_gcm(),
+            &key,
+            Some(&iv),
+            b"aad",
+            &ct[..len],
+            &tag,
+            &mut pt,
+        )
+        .unwrap();
+        assert_eq!(&pt[..len], &data[..]);
+    }
+
+    // NIST SP 800-38A F.5.1
+    #[test]
+    fn test_aes_128_ctr_seek() {
+        let key = Vec::from_hex("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let iv = Vec::from_hex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff").unwrap();
+        let pt = Vec::from_hex(
+            "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+             30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+        )
+        .unwrap();
+        let ct = Vec::from_hex(
+            "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff\
+             5ae4df3edbd5d35e5b4f09020db03eab1e031dda2fbe03d1792170a0f3009cee",
+        )
+        .unwrap();
+
+        let mut c = Crypter::new(Cipher::aes_128_ctr(), Mode::Encrypt, &key, Some(&iv)).unwrap();
+        let mut out = vec![0; 64];
+        c.seek_to_block(3).unwrap();
+        c.update(&pt[48..], &mut out).unwrap();
+        assert_eq!(&out[..16], &ct[48..]);
+
+        // The counter carries across byte boundaries.
+        let mut c = Crypter::new(Cipher::aes_128_ctr(), Mode::Decrypt, &key, None).unwrap();
+        let base = Vec::from_hex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfe00").unwrap();
+        c.set_iv_counter(&base, 0x101).unwrap();
+        c.update(&ct[32..], &mut out).unwrap();
+        assert_eq!(&out[..32], &pt[32..]);
+    }
+
+    #[test]
+    fn test_aes_128_ctr_state() {
+        let key = [0x11; 16];
+        let iv = [0xfe; 16];
+        let pt = [0x42; 100];
+
+        let mut c = Crypter::new(Cipher::aes_128_ctr(), Mode::Encrypt, &key, Some(&iv)).unwrap();
+        let mut ct = [0; 100];
+        c.update(&pt, &mut ct).unwrap();
+
+        let mut c = Crypter::new(Cipher::aes_128_ctr(), Mode::Encrypt, &key, Some(&iv)).unwrap();
+        let mut out = [0; 100];
+        c.update(&pt[..37], &mut out[..37]).unwrap();
+        let state = c.ctr_state().unwrap();
+        assert_eq!(state, CtrState::new(&iv, 37));
+
+        let mut c = Crypter::new(Cipher::aes_128_ctr(), Mode::Encrypt, &key, None).unwrap();
+        c.set_ctr_state(&state).unwrap();
+        c.update(&pt[37..], &mut out[37..]).unwrap();
+        assert_eq!(out[..], ct[..]);
+    }
+
+    #[test]
+    fn test_ctr_state_requires_ctr() {
+        let mut c = Crypter::new(
+            Cipher::aes_128_gcm(),
+            Mode::Encrypt,
+            &[0x11; 16],
+            Some(&[0xfe; 12]),
+        )
+        .unwrap();
+        assert_eq!(c.ctr_state(), None);
+        assert!(c.seek_to_block(1).is_err());
+        assert!(c.set_ctr_state(&CtrState::new(&[0xfe; 16], 0)).is_err());
+        #[cfg(ossl110)]
+        assert!(c.feedback_state().is_err());
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn test_feedback_state() {
+        let key = [0x11; 16];
+        let iv = [0xfe; 16];
+        let pt = [0x42; 100];
+
+        for &cipher in &[
+            Cipher::aes_128_ofb(),
+            Cipher::aes_128_cfb128(),
+            Cipher::aes_128_cfb8(),
+        ] {
+            let ct = encrypt(cipher, &key, Some(&iv), &pt).unwrap();
+
+            let mut c = Crypter::new(cipher, Mode::Encrypt, &key, Some(&iv)).unwrap();
+            let mut out = [0; 100];
+            c.update(&pt[..37], &mut out[..37]).unwrap();
+            let state = c.feedback_state().unwrap();
+            // CFB8 mode works a byte at a time, so no block is ever partially used
+            let num = if cipher == Cipher::aes_128_cfb8() {
+                0
+            } else {
+                5
+            };
+            assert_eq!(state.num(), num);
+
+            c.update(&pt[37..40], &mut out[37..40]).unwrap();
+            let later = c.feedback_state().unwrap();
+
+            let mut c = Crypter::new(cipher, Mode::Encrypt, &key, Some(&[0; 16])).unwrap();
+            c.set_feedback_state(&state).unwrap();
+            c.update(&pt[37..40], &mut out[37..40]).unwrap();
+            assert_eq!(c.feedback_state().unwrap(), later);
+            c.update(&pt[40..], &mut out[40..]).unwrap();
+            assert_eq!(out[..], ct[..]);
+
+            let mut c = Crypter::new(cipher, Mode::Decrypt, &key, Some(&[0; 16])).unwrap();
+            c.set_feedback_state(&state).unwrap();
+            let mut dec = [0; 100];
+            c.update(&ct[37..], &mut dec[37..]).unwrap();
+            assert_eq!(dec[37..], pt[37..]);
+        }
+    }
+
+    #[test]
+    fn test_reset_with_iv() {
+        let key = [0x11; 32];
+        let messages: [(&[u8], &[u8]); 3] = [
+            (&[0x01; 12], b"first packet"),
+            (&[0x02; 12], b"second, longer packet"),
+            (&[0x03; 16], b"third packet with a longer nonce"),
+        ];
+
+        let cipher = Cipher::aes_256_gcm();
+        let mut c = Crypter::new(cipher, Mode::Encrypt, &key, Some(messages[0].0)).unwrap();
+        for (i, &(nonce, data)) in messages.iter().enumerate() {
+            if i != 0 {
+                c.reset_with_iv(nonce).unwrap();
+            }
+            c.aad_update(b"header").unwrap();
+            let mut out = vec![0; data.len() + cipher.block_size()];
+            let mut len = c.update(data, &mut out).unwrap();
+            len += c.finalize(&mut out[len..]).unwrap();
+            out.truncate(len);
+            let mut tag = [0; 16];
+            c.get_tag(&mut tag).unwrap();
+
+            let mut expected_tag = [0; 16];
+            let expected = encrypt_aead(
+                cipher,
+                &key,
+                Some(nonce),
+                b"header",
+                data,
+                &mut expected_tag,
+            )
+            .unwrap();
+            assert_eq!(out, expected);
+            assert_eq!(tag, expected_tag);
+        }
+
+        let cipher = Cipher::aes_128_cbc();
+        let mut c = Crypter::new(cipher, Mode::Decrypt, &key[..16], Some(&[0; 16])).unwrap();
+        for &iv in &[[0x04; 16], [0x05; 16]] {
+            let ct = encrypt(cipher, &key[..16], Some(&iv), b"a block cipher message").unwrap();
+            c.reset_with_iv(&iv).unwrap();
+            let mut out = vec![0; ct.len() + cipher.block_size()];
+            let mut len = c.update(&ct, &mut out).unwrap();
+            len += c.finalize(&mut out[len..]).unwrap();
+            assert_eq!(&out[..len], b"a block cipher message");
+        }
+    }
+
+    // RFC 8439 section 2.4.2
+    #[test]
+    #[cfg(ossl110)]
+    fn test_chacha20_counter() {
+        let key = <[u8; 32]>::from_hex(
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        )
+        .unwrap();
+        let nonce = <[u8; 12]>::from_hex("000000000000004a00000000").unwrap();
+        let pt =
+            b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for \
+                   the future, sunscreen would be it.";
+        let ct = "6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0bf91b65c5524733ab\
+                  8f593dabcd62b3571639d624e65152ab8f530c359f0861d807ca0dbf500d6a6156a38e088a22b65e\
+                  52bc514d16ccf806818ce91ab77937365af90bbf74a35be6b40b8eedf2785e42874d";
+
+        let mut out = vec![0; pt.len()];
+        let mut c = ChaCha20::new(Mode::Encrypt, &key, &nonce, 1).unwrap();
+        assert_eq!(c.update(pt, &mut out).unwrap(), pt.len());
+        assert_eq!(hex::encode(&out), ct);
+
+        let mut c = ChaCha20::new(Mode::Decrypt, &key, &nonce, 0).unwrap();
+        c.seek(2).unwrap();
+        let mut out = vec![0; pt.len() - 64];
+        c.update(&Vec::from_hex(ct).unwrap()[64..], &mut out)
+            .unwrap();
+        assert_eq!(out, &pt[64..]);
+    }
+
+    #[test]
+    #[cfg(ossl110)]
+    fn test_chacha20_64_bit_counter() {
+        let key = [0; 32];
+        let nonce = [0; 8];
+        let ct = "ace4cd09e294d1912d4ad205d06f95d9c2f2bfcf453e8753f128765b62215f4d92c74f2f626c6a64\
+                  0c0b1284d839ec81f1696281dafc3e684593937023b58b1d3db41d3aa0d329285de6f225e6e24bd5\
+                  9c9a17006943d5c9b680e3873bdc683a5819469899989690c281cd17c96159af0682b5b903468a61\
+                  f50228cf09622b5a";
+
+        let mut out = [0; 128];
+        let mut c = ChaCha20::new_64(Mode::Encrypt, &key, &nonce, 0xffff_ffff).unwrap();
+        c.update(&[0; 128], &mut out).unwrap();
+        assert_eq!(hex::encode(&out[..]), ct);
+
+        let mut out = [0; 64];
+        c.seek(0x1_0000_0000).unwrap();
+        c.update(&[0; 64], &mut out).unwrap();
+        assert_eq!(hex::encode(&out[..]), &ct[128..]);
+    }
+
     #[test]
     #[cfg(ossl110)]
     fn test_chacha20_poly1305() {
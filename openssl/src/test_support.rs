@@ -0,0 +1,529 @@
+//! Utilities for tests and fuzzers which exercise TLS connections.
+//!
+//! [`test_pair`] creates a connected client and server in memory in one call, and
+//! [`TestPairBuilder`] allows either side to be configured first. [`test_key`] and
+//! [`self_signed_certificate`] generate keys and certificates which are the same on every run.
+//!
+//! TLS handshakes are otherwise unrepeatable: client and server randoms, key shares, session
+//! ticket keys and signature nonces are all drawn from the library's random number generators.
+//! [`set_deterministic_rand`] replaces the generators of a library context with generators
+//! seeded from a fixed value, so that a handshake between contexts created with
+//! [`SslContextBuilder::new_ex`] produces the same bytes on every run. [`handshake`] runs such
+//! a handshake in memory and returns the messages exchanged, for comparison against a golden
+//! transcript.
+//!
+//! None of this must be used outside of tests: the keys it generates are predictable.
+//!
+//! # Determinism
+//!
+//! Only randomness drawn from the library context is fixed. To produce identical transcripts:
+//!
+//! * Give the client and the server separate library contexts, so that the order in which they
+//!   draw from their generators does not depend on thread scheduling.
+//! * Use a server key with deterministic signatures, such as one from [`test_key`]. RSA-PSS
+//!   salts and ECDSA nonces are drawn from the context the key was loaded into, which is the
+//!   default context for keys loaded by most of this crate.
+//! * Disable session tickets, whose contents include the current time.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::io::{Read, Write};
+//! use openssl::test_support::test_pair;
+//!
+//! let (mut client, mut server) = test_pair().unwrap();
+//! client.write_all(b"hello").unwrap();
+//!
+//! let mut buf = [0; 5];
+//! server.read_exact(&mut buf).unwrap();
+//! assert_eq!(&buf, b"hello");
+//! ```
+//!
+//! [`SslContextBuilder::new_ex`]: crate::ssl::SslContextBuilder::new_ex
+#[cfg(ossl300)]
+use libc::c_uint;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+#[cfg(ossl300)]
+use std::ptr;
+use std::sync::{Arc, Mutex};
+
+#[cfg(any(ossl111, boringssl, libressl370))]
+use crate::asn1::Asn1Time;
+use crate::error::ErrorStack;
+use crate::hash::{hash, MessageDigest};
+#[cfg(ossl300)]
+use crate::kdf::ParamBuilder;
+#[cfg(ossl300)]
+use crate::lib_ctx::LibCtxRef;
+#[cfg(any(ossl111, boringssl, libressl370))]
+use crate::pkey::{HasPrivate, Id, PKey, PKeyRef, Private};
+use crate::ssl::{self, ErrorCode, Ssl, SslContextBuilder, SslMethod, SslStream};
+#[cfg(any(ossl111, boringssl, libressl370))]
+use crate::x509::{X509Name, X509};
+#[cfg(ossl300)]
+use crate::{cvt, cvt_p};
+#[cfg(ossl300)]
+use foreign_types::ForeignTypeRef;
+#[cfg(ossl300)]
+use openssl_macros::corresponds;
+
+/// The number of bytes each of the generators of a deterministic library context can produce.
+#[cfg(ossl300)]
+pub const DETERMINISTIC_RAND_LEN: usize = 256 * 1024;
+
+/// Replaces the random number generators of `ctx` with deterministic generators seeded from
+/// `seed`.
+///
+/// This uses OpenSSL's `TEST-RAND` generator, which returns a fixed sequence of bytes. The
+/// public and private generators of `ctx` each produce a stream of [`DETERMINISTIC_RAND_LEN`]
+/// bytes derived from `seed`, after which they fail.
+///
+/// This must be called before anything has drawn random bytes from `ctx`, including the
+/// creation of an `SslContext` using it.
+///
+/// Requires OpenSSL 3.0.0 or newer.
+#[corresponds(RAND_set_DRBG_type)]
+#[cfg(ossl300)]
+pub fn set_deterministic_rand(ctx: &LibCtxRef, seed: &[u8]) -> Result<(), ErrorStack> {
+    ffi::init();
+
+    unsafe {
+        cvt(ffi::RAND_set_DRBG_type(
+            ctx.as_ptr(),
+            b"TEST-RAND\0".as_ptr() as *const _,
+            ptr::null(),
+            ptr::null(),
+            ptr::null(),
+        ))?;
+
+        let public = cvt_p(ffi::RAND_get0_public(ctx.as_ptr()))?;
+        set_entropy(public, &expand(seed, b"public", DETERMINISTIC_RAND_LEN)?)?;
+        let private = cvt_p(ffi::RAND_get0_private(ctx.as_ptr()))?;
+        set_entropy(private, &expand(seed, b"private", DETERMINISTIC_RAND_LEN)?)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(ossl300)]
+unsafe fn set_entropy(rand: *mut ffi::EVP_RAND_CTX, entropy: &[u8]) -> Result<(), ErrorStack> {
+    let strength: c_uint = 256;
+    let mut params = ParamBuilder::new();
+    params
+        .octet_string(b"test_entropy\0", entropy)
+        .uint(b"strength\0", &strength);
+    let params = params.build();
+    cvt(ffi::EVP_RAND_CTX_set_params(rand, params.as_ptr())).map(|_| ())
+}
+
+/// Expands `seed` into `len` bytes of output for the purpose named `name`.
+fn expand(seed: &[u8], name: &[u8], len: usize) -> Result<Vec<u8>, ErrorStack> {
+    let mut out = Vec::with_capacity(len);
+    let mut counter = 0u32;
+    while out.len() < len {
+        let mut input = name.to_vec();
+        input.extend_from_slice(&counter.to_be_bytes());
+        input.extend_from_slice(seed);
+        out.extend_from_slice(&hash(MessageDigest::sha256(), &input)?);
+        counter += 1;
+    }
+    out.truncate(len);
+    Ok(out)
+}
+
+/// The side of a connection which sent a flight.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Sender {
+    /// The client.
+    Client,
+    /// The server.
+    Server,
+}
+
+/// The messages exchanged by a handshake run with [`handshake`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transcript {
+    flights: Vec<(Sender, Vec<u8>)>,
+}
+
+impl Transcript {
+    /// Returns the flights of the handshake, in the order they were sent.
+    ///
+    /// Each flight contains all of the records one side sent before waiting for its peer.
+    pub fn flights(&self) -> &[(Sender, Vec<u8>)] {
+        &self.flights
+    }
+
+    /// Returns the flights sent by `sender`.
+    pub fn flights_from(&self, sender: Sender) -> impl Iterator<Item = &[u8]> {
+        self.flights
+            .iter()
+            .filter(move |(s, _)| *s == sender)
+            .map(|(_, flight)| &**flight)
+    }
+}
+
+/// Generates an Ed25519 private key derived from `seed`.
+///
+/// The same seed always produces the same key, and Ed25519 signatures are deterministic, so
+/// certificates and handshakes signed with the key are repeatable.
+#[cfg(any(ossl111, boringssl, libressl370))]
+pub fn test_key(seed: &[u8]) -> Result<PKey<Private>, ErrorStack> {
+    let key = expand(seed, b"ed25519 key", 32)?;
+    PKey::private_key_from_raw_bytes(&key, Id::ED25519)
+}
+
+/// Creates a self-signed certificate for `key` with the subject common name `common_name`.
+///
+/// The certificate is created as by [`X509::self_signed`], but has a fixed serial number and a
+/// validity period from 1970 to 2100, so that it is the same on every run if `key` produces
+/// deterministic signatures, as keys from [`test_key`] do.
+#[cfg(any(ossl111, boringssl, libressl370))]
+pub fn self_signed_certificate<T>(key: &PKeyRef<T>, common_name: &str) -> Result<X509, ErrorStack>
+where
+    T: HasPrivate,
+{
+    use crate::bn::BigNum;
+    use crate::x509::self_signed::SelfSignedOptions;
+
+    let mut name = X509Name::builder()?;
+    name.append_entry_by_text("CN", common_name)?;
+    let name = name.build();
+
+    let mut options = SelfSignedOptions::new();
+    options
+        .serial_number(BigNum::from_u32(1)?)
+        .validity(Asn1Time::from_unix(0)?, Asn1Time::from_unix(4_102_444_800)?);
+    X509::self_signed(&name, key, &options)
+}
+
+/// One end of an in-memory, bidirectional byte stream.
+///
+/// Bytes written to one end of a pair can be read from the other. Reads return an error of kind
+/// `WouldBlock` when no bytes are available, like a nonblocking socket.
+#[derive(Debug)]
+pub struct MemoryStream {
+    incoming: Arc<Mutex<VecDeque<u8>>>,
+    outgoing: Arc<Mutex<VecDeque<u8>>>,
+}
+
+impl MemoryStream {
+    /// Creates a connected pair of streams.
+    pub fn pair() -> (MemoryStream, MemoryStream) {
+        let a = Arc::new(Mutex::new(VecDeque::new()));
+        let b = Arc::new(Mutex::new(VecDeque::new()));
+        (
+            MemoryStream {
+                incoming: a.clone(),
+                outgoing: b.clone(),
+            },
+            MemoryStream {
+                incoming: b,
+                outgoing: a,
+            },
+        )
+    }
+
+    /// Returns the number of bytes written to this stream which the peer has not yet read.
+    pub fn pending(&self) -> usize {
+        self.outgoing.lock().unwrap().len()
+    }
+
+    fn written_since(&self, start: usize) -> Vec<u8> {
+        self.outgoing
+            .lock()
+            .unwrap()
+            .iter()
+            .skip(start)
+            .copied()
+            .collect()
+    }
+}
+
+impl Read for MemoryStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut incoming = self.incoming.lock().unwrap();
+        if incoming.is_empty() && !buf.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "no data available",
+            ));
+        }
+        let len = buf.len().min(incoming.len());
+        for (dst, src) in buf.iter_mut().zip(incoming.drain(..len)) {
+            *dst = src;
+        }
+        Ok(len)
+    }
+}
+
+impl Write for MemoryStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.outgoing.lock().unwrap().extend(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A builder for a connected client and server.
+///
+/// By default, the server presents a self-signed certificate for `localhost` with a key from
+/// [`test_key`], and the client does not verify it.
+pub struct TestPairBuilder {
+    client: SslContextBuilder,
+    server: SslContextBuilder,
+}
+
+impl TestPairBuilder {
+    /// Creates a builder with default client and server contexts.
+    #[cfg(any(ossl111, boringssl, libressl370))]
+    pub fn new() -> Result<TestPairBuilder, ErrorStack> {
+        let key = test_key(b"server")?;
+        let cert = self_signed_certificate(&key, "localhost")?;
+        let mut server = SslContextBuilder::new(SslMethod::tls())?;
+        server.set_certificate(&cert)?;
+        server.set_private_key(&key)?;
+
+        Ok(TestPairBuilder::with_contexts(
+            SslContextBuilder::new(SslMethod::tls())?,
+            server,
+        ))
+    }
+
+    /// Creates a builder from existing client and server contexts.
+    ///
+    /// The server context must already have a certificate and key, if the protocol requires one.
+    pub fn with_contexts(client: SslContextBuilder, server: SslContextBuilder) -> TestPairBuilder {
+        TestPairBuilder { client, server }
+    }
+
+    /// Returns the client's context, for configuration.
+    pub fn client_ctx(&mut self) -> &mut SslContextBuilder {
+        &mut self.client
+    }
+
+    /// Returns the server's context, for configuration.
+    pub fn server_ctx(&mut self) -> &mut SslContextBuilder {
+        &mut self.server
+    }
+
+    /// Creates the client and server and completes a handshake between them.
+    pub fn build(self) -> io::Result<(SslStream<MemoryStream>, SslStream<MemoryStream>)> {
+        let client = Ssl::new(&self.client.build()).map_err(io_error)?;
+        let server = Ssl::new(&self.server.build()).map_err(io_error)?;
+        let (client, server, _) = connect(client, server)?;
+        Ok((client, server))
+    }
+}
+
+/// Creates a client and a server which have completed a handshake with each other in memory.
+///
+/// This is equivalent to `TestPairBuilder::new()?.build()`.
+#[cfg(any(ossl111, boringssl, libressl370))]
+pub fn test_pair() -> io::Result<(SslStream<MemoryStream>, SslStream<MemoryStream>)> {
+    TestPairBuilder::new().map_err(io_error)?.build()
+}
+
+/// Runs a handshake between `client` and `server` in memory, returning the messages exchanged.
+///
+/// The handshake runs on the calling thread, with each side in turn running until it needs a
+/// message from its peer. It fails if either side fails, or if neither side can make progress.
+pub fn handshake(client: Ssl, server: Ssl) -> io::Result<Transcript> {
+    connect(client, server).map(|(_, _, transcript)| transcript)
+}
+
+fn connect(
+    client: Ssl,
+    server: Ssl,
+) -> io::Result<(SslStream<MemoryStream>, SslStream<MemoryStream>, Transcript)> {
+    let (client_stream, server_stream) = MemoryStream::pair();
+    let mut client = SslStream::new(client, client_stream).map_err(io_error)?;
+    let mut server = SslStream::new(server, server_stream).map_err(io_error)?;
+    let mut client_done = false;
+    let mut server_done = false;
+    let mut flights = vec![];
+
+    loop {
+        let mut progress = false;
+
+        let start = client.get_ref().pending();
+        if !client_done {
+            client_done = step(client.connect())?;
+        }
+        let flight = client.get_ref().written_since(start);
+        if !flight.is_empty() {
+            flights.push((Sender::Client, flight));
+            progress = true;
+        }
+
+        let start = server.get_ref().pending();
+        if !server_done {
+            server_done = step(server.accept())?;
+        }
+        let flight = server.get_ref().written_since(start);
+        if !flight.is_empty() {
+            flights.push((Sender::Server, flight));
+            progress = true;
+        }
+
+        if client_done && server_done && !progress {
+            return Ok((client, server, Transcript { flights }));
+        }
+        if !progress {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "the handshake stalled",
+            ));
+        }
+    }
+}
+
+/// Returns whether a handshake step completed, treating a need for more data as incomplete.
+fn step(r: Result<(), ssl::Error>) -> io::Result<bool> {
+    match r {
+        Ok(()) => Ok(true),
+        Err(ref e) if e.code() == ErrorCode::WANT_READ => Ok(false),
+        Err(e) => Err(io_error(e)),
+    }
+}
+
+fn io_error<E>(e: E) -> io::Error
+where
+    E: Into<ssl::Error>,
+{
+    e.into()
+        .into_io_error()
+        .unwrap_or_else(|e| io::Error::new(io::ErrorKind::Other, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(ossl300)]
+    use crate::lib_ctx::LibCtx;
+    #[cfg(ossl300)]
+    use crate::ssl::SslOptions;
+    use crate::ssl::SslVerifyMode;
+    use crate::x509::store::X509StoreBuilder;
+
+    #[cfg(ossl300)]
+    fn rand_bytes_ex(ctx: &LibCtxRef, buf: &mut [u8]) {
+        unsafe {
+            cvt(ffi::RAND_bytes_ex(
+                ctx.as_ptr(),
+                buf.as_mut_ptr(),
+                buf.len(),
+                0,
+            ))
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn pair() {
+        let (mut client, mut server) = test_pair().unwrap();
+
+        client.write_all(b"hello").unwrap();
+        let mut buf = [0; 5];
+        server.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"hello");
+
+        server.write_all(b"world").unwrap();
+        client.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"world");
+
+        let err = client.read(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+    }
+
+    #[test]
+    fn pair_verified() {
+        let key = test_key(b"server").unwrap();
+        let cert = self_signed_certificate(&key, "localhost").unwrap();
+        assert_eq!(
+            cert.to_der().unwrap(),
+            self_signed_certificate(&test_key(b"server").unwrap(), "localhost")
+                .unwrap()
+                .to_der()
+                .unwrap()
+        );
+
+        let mut store = X509StoreBuilder::new().unwrap();
+        store.add_cert(cert).unwrap();
+
+        let mut builder = TestPairBuilder::new().unwrap();
+        builder.client_ctx().set_verify(SslVerifyMode::PEER);
+        builder.client_ctx().set_cert_store(store.build());
+        builder
+            .client_ctx()
+            .verify_param_mut()
+            .set_host("localhost")
+            .unwrap();
+        let (client, _) = builder.build().unwrap();
+        assert!(client.ssl().peer_certificate().is_some());
+
+        let mut builder = TestPairBuilder::new().unwrap();
+        builder.client_ctx().set_verify(SslVerifyMode::PEER);
+        builder.build().unwrap_err();
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn deterministic_rand() {
+        let draw = |seed: &[u8]| {
+            let ctx = LibCtx::new().unwrap();
+            set_deterministic_rand(&ctx, seed).unwrap();
+            let mut buf = [0; 64];
+            rand_bytes_ex(&ctx, &mut buf);
+            let mut second = [0; 64];
+            rand_bytes_ex(&ctx, &mut second);
+            assert_ne!(buf, second);
+            buf
+        };
+
+        assert_eq!(draw(b"seed"), draw(b"seed"));
+        assert_ne!(draw(b"seed"), draw(b"other seed"));
+    }
+
+    #[test]
+    #[cfg(ossl300)]
+    fn deterministic_handshake() {
+        let key = test_key(b"server").unwrap();
+        let cert = self_signed_certificate(&key, "localhost").unwrap();
+
+        let run = |seed: &[u8]| {
+            let context = |name: &[u8]| {
+                let ctx = LibCtx::new().unwrap();
+                set_deterministic_rand(&ctx, &[seed, name].concat()).unwrap();
+                let mut builder =
+                    SslContextBuilder::new_ex(Some(&ctx), None, SslMethod::tls()).unwrap();
+                builder.set_options(SslOptions::NO_TICKET);
+                builder.set_num_tickets(0).unwrap();
+                (ctx, builder)
+            };
+
+            let (_client_lib_ctx, client) = context(b"client");
+            let (_server_lib_ctx, mut server) = context(b"server");
+            server.set_certificate(&cert).unwrap();
+            server.set_private_key(&key).unwrap();
+
+            let client = client.build();
+            let server = server.build();
+            handshake(Ssl::new(&client).unwrap(), Ssl::new(&server).unwrap()).unwrap()
+        };
+
+        let transcript = run(b"seed");
+        assert!(transcript.flights().len() >= 3);
+        assert_eq!(transcript, run(b"seed"));
+
+        let other = run(b"other seed");
+        assert_ne!(
+            transcript.flights_from(Sender::Client).next(),
+            other.flights_from(Sender::Client).next()
+        );
+    }
+}
@@ -0,0 +1,292 @@
+//! Per-thread caches of OpenSSL contexts.
+//!
+//! Code which performs many small operations allocates and frees an OpenSSL context for each of
+//! them. Once caching is enabled with [`set_enabled`], [`with_md_ctx`], [`with_cipher_ctx`], and
+//! [`with_bn_ctx`] instead keep one context of each type per thread and reuse it across calls,
+//! avoiding the allocations in hot paths. Caching is disabled by default. A context is only
+//! returned to the cache if the closure succeeds, and a nested call made while the context is in
+//! use receives a new one.
+//!
+//! The one-shot functions [`hash::hash`], [`hash::hash_into`], [`symm::encrypt`],
+//! [`symm::decrypt`], [`sign::sign_oneshot`], and [`sign::hmac`] are built on these accessors, so
+//! enabling caching applies to them without further changes.
+//!
+//! Digest and cipher contexts are reset before they are cached, so that no key material is
+//! retained between calls. `BN_CTX` cannot be reset, and temporary values computed with a cached
+//! [`BigNumContext`] remain in its memory until it is reused or the thread exits. This crate only
+//! uses it for public values.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::md::Md;
+//! use openssl::thread_cache;
+//!
+//! thread_cache::set_enabled(true);
+//!
+//! // every call after the first reuses the thread's digest context
+//! for message in &[&b"first"[..], b"second", b"third"] {
+//!     let mut digest = [0; 32];
+//!     thread_cache::with_md_ctx(|ctx| {
+//!         ctx.digest_init(Md::sha256())?;
+//!         ctx.digest_update(message)?;
+//!         ctx.digest_final(&mut digest)
+//!     })
+//!     .unwrap();
+//! }
+//! ```
+//!
+//! [`hash::hash`]: crate::hash::hash
+//! [`hash::hash_into`]: crate::hash::hash_into
+//! [`symm::encrypt`]: crate::symm::encrypt
+//! [`symm::decrypt`]: crate::symm::decrypt
+//! [`sign::sign_oneshot`]: crate::sign::sign_oneshot
+//! [`sign::hmac`]: crate::sign::hmac
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::LocalKey;
+
+use crate::bn::{BigNumContext, BigNumContextRef};
+use crate::cipher_ctx::{CipherCtx, CipherCtxRef};
+use crate::error::ErrorStack;
+use crate::md_ctx::{MdCtx, MdCtxRef};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    // const initializers in thread_local! require Rust 1.59
+    #[allow(clippy::missing_const_for_thread_local)]
+    static MD_CTX: RefCell<Option<MdCtx>> = RefCell::new(None);
+    #[allow(clippy::missing_const_for_thread_local)]
+    static CIPHER_CTX: RefCell<Option<CipherCtx>> = RefCell::new(None);
+    #[allow(clippy::missing_const_for_thread_local)]
+    static BN_CTX: RefCell<Option<BigNumContext>> = RefCell::new(None);
+}
+
+/// Enables or disables caching for all threads.
+///
+/// Disabling caching does not free contexts which are already cached. Use [`clear`] to free those
+/// of the calling thread.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns whether caching is enabled.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Frees the contexts cached by the calling thread.
+pub fn clear() {
+    let _ = MD_CTX.try_with(|slot| slot.borrow_mut().take());
+    let _ = CIPHER_CTX.try_with(|slot| slot.borrow_mut().take());
+    let _ = BN_CTX.try_with(|slot| slot.borrow_mut().take());
+}
+
+/// Calls `f` with a digest context, which is cached by the calling thread if caching is enabled.
+///
+/// The context is reset before it is passed to `f`, so `f` must initialize it with a digest.
+///
+/// Digest contexts are only cached with OpenSSL 1.1.1 or newer.
+pub fn with_md_ctx<F, T>(f: F) -> Result<T, ErrorStack>
+where
+    F: FnOnce(&mut MdCtxRef) -> Result<T, ErrorStack>,
+{
+    with(&MD_CTX, MdCtx::new, reset_md_ctx, f)
+}
+
+/// Calls `f` with a cipher context, which is cached by the calling thread if caching is enabled.
+///
+/// The context is reset before it is passed to `f`, so `f` must initialize it with a cipher.
+///
+/// Cipher contexts are only cached with OpenSSL 1.1.0 or LibreSSL 2.7.0 or newer, as older
+/// versions cannot reset them.
+pub fn with_cipher_ctx<F, T>(f: F) -> Result<T, ErrorStack>
+where
+    F: FnOnce(&mut CipherCtxRef) -> Result<T, ErrorStack>,
+{
+    with(&CIPHER_CTX, CipherCtx::new, reset_cipher_ctx, f)
+}
+
+/// Calls `f` with a `BN_CTX`, which is cached by the calling thread if caching is enabled.
+///
+/// See the [module documentation](self) for the implications of caching a `BN_CTX` used with
+/// secret values.
+pub fn with_bn_ctx<F, T>(f: F) -> Result<T, ErrorStack>
+where
+    F: FnOnce(&mut BigNumContextRef) -> Result<T, ErrorStack>,
+{
+    with(&BN_CTX, BigNumContext::new, |_| true, f)
+}
+
+fn with<C, R, F, T>(
+    key: &'static LocalKey<RefCell<Option<C>>>,
+    new: fn() -> Result<C, ErrorStack>,
+    reset: fn(&mut R) -> bool,
+    f: F,
+) -> Result<T, ErrorStack>
+where
+    C: std::ops::DerefMut<Target = R>,
+    R: ?Sized,
+    F: FnOnce(&mut R) -> Result<T, ErrorStack>,
+{
+    if !is_enabled() {
+        return f(&mut *new()?);
+    }
+
+    // the slot is emptied while the context is in use, so that nested calls get their own
+    let cached = key.try_with(|slot| slot.borrow_mut().take()).ok().flatten();
+    let mut ctx = match cached {
+        Some(ctx) => ctx,
+        None => new()?,
+    };
+
+    let r = f(&mut *ctx);
+    if r.is_ok() && reset(&mut *ctx) {
+        let _ = key.try_with(move |slot| *slot.borrow_mut() = Some(ctx));
+    }
+    r
+}
+
+fn reset_md_ctx(ctx: &mut MdCtxRef) -> bool {
+    cfg_if::cfg_if! {
+        if #[cfg(ossl111)] {
+            ctx.reset().is_ok()
+        } else {
+            let _ = ctx;
+            false
+        }
+    }
+}
+
+fn reset_cipher_ctx(ctx: &mut CipherCtxRef) -> bool {
+    cfg_if::cfg_if! {
+        if #[cfg(any(ossl110, libressl270, boringssl))] {
+            ctx.reset().is_ok()
+        } else {
+            let _ = ctx;
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::{hash, MessageDigest};
+    use crate::md::Md;
+    use crate::symm::{decrypt, encrypt, Cipher, Mode};
+    use once_cell::sync::Lazy;
+    use std::sync::{Mutex, MutexGuard};
+
+    static LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+    // enables caching for the duration of a test, serialized with other tests which change it
+    struct Enabled {
+        _guard: MutexGuard<'static, ()>,
+    }
+
+    impl Enabled {
+        fn new() -> Enabled {
+            let guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            set_enabled(true);
+            Enabled { _guard: guard }
+        }
+    }
+
+    impl Drop for Enabled {
+        fn drop(&mut self) {
+            set_enabled(false);
+            clear();
+        }
+    }
+
+    fn sha256(data: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        with_md_ctx(|ctx| {
+            ctx.digest_init(Md::sha256())?;
+            ctx.digest_update(data)?;
+            let mut digest = vec![0; 32];
+            ctx.digest_final(&mut digest)?;
+            Ok(digest)
+        })
+    }
+
+    fn aes_128_cbc(mode: Mode, data: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        with_cipher_ctx(|ctx| {
+            let cipher = crate::cipher::Cipher::aes_128_cbc();
+            match mode {
+                Mode::Encrypt => {
+                    ctx.encrypt_init(Some(cipher), Some(&[0x42; 16]), Some(&[0x24; 16]))?
+                }
+                Mode::Decrypt => {
+                    ctx.decrypt_init(Some(cipher), Some(&[0x42; 16]), Some(&[0x24; 16]))?
+                }
+            }
+            let mut out = vec![];
+            ctx.cipher_update_vec(data, &mut out)?;
+            ctx.cipher_final_vec(&mut out)?;
+            Ok(out)
+        })
+    }
+
+    #[test]
+    fn cached() {
+        let _enabled = Enabled::new();
+
+        let expected = hash(MessageDigest::sha256(), b"hello").unwrap();
+        for _ in 0..3 {
+            assert_eq!(sha256(b"hello").unwrap(), *expected);
+        }
+
+        let ct = encrypt(
+            Cipher::aes_128_cbc(),
+            &[0x42; 16],
+            Some(&[0x24; 16]),
+            b"message",
+        )
+        .unwrap();
+        for _ in 0..3 {
+            assert_eq!(aes_128_cbc(Mode::Encrypt, b"message").unwrap(), ct);
+            assert_eq!(aes_128_cbc(Mode::Decrypt, &ct).unwrap(), b"message");
+        }
+        // a failed operation does not poison the cache
+        assert!(aes_128_cbc(Mode::Decrypt, &ct[..ct.len() - 1]).is_err());
+        assert_eq!(aes_128_cbc(Mode::Decrypt, &ct).unwrap(), b"message");
+    }
+
+    #[test]
+    fn one_shots() {
+        let _enabled = Enabled::new();
+
+        let expected = sha256(b"hello").unwrap();
+        for _ in 0..3 {
+            assert_eq!(*hash(MessageDigest::sha256(), b"hello").unwrap(), *expected);
+        }
+        #[cfg(ossl111)]
+        assert!(MD_CTX.with(|slot| slot.borrow().is_some()));
+
+        let ct = aes_128_cbc(Mode::Encrypt, b"message").unwrap();
+        for _ in 0..3 {
+            let key = [0x42; 16];
+            let iv = Some(&[0x24; 16][..]);
+            assert_eq!(
+                encrypt(Cipher::aes_128_cbc(), &key, iv, b"message").unwrap(),
+                ct
+            );
+            assert_eq!(
+                decrypt(Cipher::aes_128_cbc(), &key, iv, &ct).unwrap(),
+                b"message"
+            );
+        }
+        #[cfg(any(ossl110, libressl270, boringssl))]
+        assert!(CIPHER_CTX.with(|slot| slot.borrow().is_some()));
+    }
+
+    #[test]
+    fn nested() {
+        let _enabled = Enabled::new();
+
+        let digest = with_md_ctx(|_| sha256(b"nested")).unwrap();
+        assert_eq!(digest, *hash(MessageDigest::sha256(), b"nested").unwrap());
+    }
+}
@@ -0,0 +1,195 @@
+//! Runtime checks of how OpenSSL objects are shared between threads.
+//!
+//! The types in this crate are `Send` and `Sync` when OpenSSL allows the objects they wrap to be
+//! used from multiple threads. Some of those claims depend on how OpenSSL was built, or on how
+//! the objects are used: an OpenSSL built without thread support does no locking at all, and
+//! state which callbacks reach through a shared object, such as the extra data of a context used
+//! by concurrent handshakes, is only as thread-safe as the code which mutates it.
+//!
+//! [`ThreadChecked`] wraps an object and asserts that it is only accessed through shared
+//! references from the thread which owns it, which catches accidental sharing in tests before it
+//! corrupts state in production. The assertions are only compiled in when the `thread_checks`
+//! feature is enabled, or when OpenSSL was built without thread support, and otherwise the
+//! wrapper has no cost.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::bn::BigNum;
+//! use openssl::thread_check::ThreadChecked;
+//! use std::thread;
+//!
+//! let n = ThreadChecked::new(BigNum::from_u32(7).unwrap());
+//! assert_eq!(n.num_bits(), 3);
+//!
+//! // moving the object to another thread transfers its ownership
+//! let n = thread::spawn(move || {
+//!     let mut n = n;
+//!     n.add_word(1).unwrap();
+//!     n
+//! })
+//! .join()
+//! .unwrap();
+//! assert_eq!(n.into_inner(), BigNum::from_u32(8).unwrap());
+//! ```
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+#[cfg(any(feature = "thread_checks", osslconf = "OPENSSL_NO_THREADS"))]
+use std::thread::{self, ThreadId};
+
+/// Returns whether the linked OpenSSL was built with thread support.
+///
+/// Without thread support, OpenSSL does not lock any of its objects, and no object may be used
+/// from several threads at once.
+pub fn openssl_threads() -> bool {
+    !cfg!(osslconf = "OPENSSL_NO_THREADS")
+}
+
+/// An object which asserts that it is not shared between threads.
+///
+/// Shared references to the object may only be taken on the thread which owns it, which is the
+/// thread which created the wrapper. Taking a mutable reference, which requires exclusive access,
+/// transfers ownership to the current thread, so the wrapper can still be moved between threads.
+///
+/// # Panics
+///
+/// With the `thread_checks` feature enabled, or when OpenSSL was built without thread support,
+/// [`get`] and the [`Deref`] implementation panic when called from a thread which does not own
+/// the object.
+///
+/// [`get`]: ThreadChecked::get
+pub struct ThreadChecked<T> {
+    value: T,
+    #[cfg(any(feature = "thread_checks", osslconf = "OPENSSL_NO_THREADS"))]
+    owner: ThreadId,
+}
+
+impl<T> ThreadChecked<T> {
+    /// Wraps an object, owned by the current thread.
+    pub fn new(value: T) -> ThreadChecked<T> {
+        ThreadChecked {
+            value,
+            #[cfg(any(feature = "thread_checks", osslconf = "OPENSSL_NO_THREADS"))]
+            owner: thread::current().id(),
+        }
+    }
+
+    /// Returns a shared reference to the object.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the checks are enabled and the current thread does not own the object.
+    #[track_caller]
+    pub fn get(&self) -> &T {
+        #[cfg(any(feature = "thread_checks", osslconf = "OPENSSL_NO_THREADS"))]
+        assert!(
+            self.owner == thread::current().id(),
+            "object owned by {:?} accessed from {:?}",
+            self.owner,
+            thread::current().id(),
+        );
+        &self.value
+    }
+
+    /// Returns a mutable reference to the object, transferring its ownership to the current
+    /// thread.
+    pub fn get_mut(&mut self) -> &mut T {
+        #[cfg(any(feature = "thread_checks", osslconf = "OPENSSL_NO_THREADS"))]
+        {
+            self.owner = thread::current().id();
+        }
+        &mut self.value
+    }
+
+    /// Returns whether the current thread owns the object.
+    ///
+    /// Always returns `true` if the checks are disabled.
+    pub fn is_owned(&self) -> bool {
+        #[cfg(any(feature = "thread_checks", osslconf = "OPENSSL_NO_THREADS"))]
+        {
+            self.owner == thread::current().id()
+        }
+        #[cfg(not(any(feature = "thread_checks", osslconf = "OPENSSL_NO_THREADS")))]
+        {
+            true
+        }
+    }
+
+    /// Returns the object.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for ThreadChecked<T> {
+    type Target = T;
+
+    #[track_caller]
+    fn deref(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<T> DerefMut for ThreadChecked<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.get_mut()
+    }
+}
+
+impl<T> fmt::Debug for ThreadChecked<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = fmt.debug_struct("ThreadChecked");
+        debug.field("value", &self.value);
+        #[cfg(any(feature = "thread_checks", osslconf = "OPENSSL_NO_THREADS"))]
+        debug.field("owner", &self.owner);
+        debug.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bn::BigNum;
+    use std::thread;
+
+    #[test]
+    fn moved() {
+        let n = ThreadChecked::new(BigNum::from_u32(7).unwrap());
+        assert!(n.is_owned());
+
+        let n = thread::spawn(move || {
+            let mut n = n;
+            n.add_word(1).unwrap();
+            assert!(n.is_owned());
+            assert_eq!(n.num_bits(), 4);
+            n
+        })
+        .join()
+        .unwrap();
+
+        let checked = cfg!(any(
+            feature = "thread_checks",
+            osslconf = "OPENSSL_NO_THREADS"
+        ));
+        assert_eq!(n.is_owned(), !checked);
+    }
+
+    #[test]
+    #[cfg(feature = "thread_checks")]
+    fn shared() {
+        use std::sync::Arc;
+
+        let n = Arc::new(ThreadChecked::new(BigNum::from_u32(7).unwrap()));
+        assert_eq!(n.num_bits(), 3);
+
+        let result = thread::spawn({
+            let n = n.clone();
+            move || n.num_bits()
+        })
+        .join();
+        assert!(result.is_err());
+    }
+}
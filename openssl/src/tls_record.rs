@@ -0,0 +1,369 @@
+//! TLS 1.3 record protection without a handshake.
+//!
+//! Tunneling protocols which already share a secret can use [`RecordProtection`] to protect frames exactly as
+//! TLS 1.3 protects application data records ([RFC 8446 section 5.2]): the AEAD key and IV are derived from a
+//! traffic secret, each record's nonce combines the IV with a 64 bit sequence number, the record header is
+//! authenticated, and the real content type is carried inside the encrypted payload along with optional padding.
+//! Keys are updated as for a TLS `KeyUpdate` message.
+//!
+//! Records produced by one end can be opened by a TLS 1.3 implementation using the same traffic secret and
+//! sequence number, and vice versa.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::tls_record::{CipherSuite, RecordProtection};
+//!
+//! let secret = [7; 32];
+//! let mut sender = RecordProtection::new(CipherSuite::TLS_AES_128_GCM_SHA256, &secret).unwrap();
+//! let mut receiver = RecordProtection::new(CipherSuite::TLS_AES_128_GCM_SHA256, &secret).unwrap();
+//!
+//! let record = sender.seal(23, b"hello", 0).unwrap();
+//! let (content_type, content) = receiver.open(&record).unwrap();
+//! assert_eq!(content_type, 23);
+//! assert_eq!(content, b"hello");
+//! ```
+//!
+//! [RFC 8446 section 5.2]: https://datatracker.ietf.org/doc/html/rfc8446#section-5.2
+use std::error;
+use std::fmt;
+
+use crate::error::ErrorStack;
+use crate::kdf::tls13_hkdf_expand_label;
+use crate::md::{Md, MdRef};
+use crate::symm::{self, Cipher};
+
+const HEADER_LEN: usize = 5;
+const TAG_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const APPLICATION_DATA: u8 = 23;
+const MAX_PLAINTEXT_LEN: usize = 1 << 14;
+const MAX_CIPHERTEXT_LEN: usize = MAX_PLAINTEXT_LEN + 256;
+
+/// A TLS 1.3 cipher suite.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CipherSuite(u16);
+
+impl CipherSuite {
+    /// `TLS_AES_128_GCM_SHA256`.
+    pub const TLS_AES_128_GCM_SHA256: CipherSuite = CipherSuite(0x1301);
+    /// `TLS_AES_256_GCM_SHA384`.
+    pub const TLS_AES_256_GCM_SHA384: CipherSuite = CipherSuite(0x1302);
+    /// `TLS_CHACHA20_POLY1305_SHA256`.
+    pub const TLS_CHACHA20_POLY1305_SHA256: CipherSuite = CipherSuite(0x1303);
+
+    /// Returns the cipher suite with the given IANA value, if it is supported.
+    pub fn from_u16(value: u16) -> Option<CipherSuite> {
+        match CipherSuite(value) {
+            CipherSuite::TLS_AES_128_GCM_SHA256
+            | CipherSuite::TLS_AES_256_GCM_SHA384
+            | CipherSuite::TLS_CHACHA20_POLY1305_SHA256 => Some(CipherSuite(value)),
+            _ => None,
+        }
+    }
+
+    /// Returns the IANA value of the cipher suite.
+    pub fn as_u16(&self) -> u16 {
+        self.0
+    }
+
+    fn cipher(&self) -> Cipher {
+        match *self {
+            CipherSuite::TLS_AES_128_GCM_SHA256 => Cipher::aes_128_gcm(),
+            CipherSuite::TLS_AES_256_GCM_SHA384 => Cipher::aes_256_gcm(),
+            _ => Cipher::chacha20_poly1305(),
+        }
+    }
+
+    fn md(&self) -> &'static MdRef {
+        match *self {
+            CipherSuite::TLS_AES_256_GCM_SHA384 => Md::sha384(),
+            _ => Md::sha256(),
+        }
+    }
+}
+
+/// An error protecting or unprotecting a record.
+pub struct RecordError(Inner);
+
+enum Inner {
+    Invalid(&'static str),
+    Ssl(ErrorStack),
+}
+
+impl RecordError {
+    /// Returns the underlying OpenSSL error stack, if the error was reported by OpenSSL.
+    ///
+    /// A record which fails authentication is reported by OpenSSL.
+    pub fn ssl_error(&self) -> Option<&ErrorStack> {
+        match self.0 {
+            Inner::Ssl(ref e) => Some(e),
+            Inner::Invalid(_) => None,
+        }
+    }
+}
+
+impl fmt::Debug for RecordError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Inner::Invalid(e) => fmt.debug_tuple("Invalid").field(&e).finish(),
+            Inner::Ssl(ref e) => fmt.debug_tuple("Ssl").field(e).finish(),
+        }
+    }
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Inner::Invalid(e) => write!(fmt, "invalid TLS record: {}", e),
+            Inner::Ssl(ref e) => fmt::Display::fmt(e, fmt),
+        }
+    }
+}
+
+impl error::Error for RecordError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self.0 {
+            Inner::Invalid(_) => None,
+            Inner::Ssl(ref e) => Some(e),
+        }
+    }
+}
+
+impl From<ErrorStack> for RecordError {
+    fn from(e: ErrorStack) -> RecordError {
+        RecordError(Inner::Ssl(e))
+    }
+}
+
+/// The protection state of one direction of a TLS 1.3 connection.
+///
+/// Each `RecordProtection` holds a traffic secret, the key and IV derived from it, and the sequence number of
+/// the next record. A separate instance is needed for each direction.
+pub struct RecordProtection {
+    suite: CipherSuite,
+    secret: Vec<u8>,
+    key: Vec<u8>,
+    iv: [u8; NONCE_LEN],
+    seq: u64,
+}
+
+impl RecordProtection {
+    /// Creates the record protection state for a traffic secret.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `secret` is not the cipher suite's hash length.
+    pub fn new(suite: CipherSuite, secret: &[u8]) -> Result<RecordProtection, ErrorStack> {
+        assert_eq!(secret.len(), suite.md().size());
+
+        let mut key = vec![0; suite.cipher().key_len()];
+        tls13_hkdf_expand_label(suite.md(), secret, b"key", b"", &mut key)?;
+        let mut iv = [0; NONCE_LEN];
+        tls13_hkdf_expand_label(suite.md(), secret, b"iv", b"", &mut iv)?;
+
+        Ok(RecordProtection {
+            suite,
+            secret: secret.to_vec(),
+            key,
+            iv,
+            seq: 0,
+        })
+    }
+
+    /// Returns the cipher suite.
+    pub fn cipher_suite(&self) -> CipherSuite {
+        self.suite
+    }
+
+    /// Returns the sequence number of the next record.
+    pub fn sequence_number(&self) -> u64 {
+        self.seq
+    }
+
+    /// Replaces the traffic secret with the next one, as done on a `KeyUpdate` message.
+    ///
+    /// The sequence number is reset to 0.
+    pub fn update_key(&mut self) -> Result<(), ErrorStack> {
+        let mut secret = vec![0; self.secret.len()];
+        tls13_hkdf_expand_label(
+            self.suite.md(),
+            &self.secret,
+            b"traffic upd",
+            b"",
+            &mut secret,
+        )?;
+        *self = RecordProtection::new(self.suite, &secret)?;
+        Ok(())
+    }
+
+    /// Protects a record, returning it with its 5 byte header.
+    ///
+    /// `content_type` is the real content type of `content`, for example 23 for application data, and is
+    /// encrypted with it. `padding` zero bytes are appended to hide the length of the content.
+    ///
+    /// An error is returned if the record would exceed the TLS size limit of 2^14 bytes of plaintext, or the
+    /// sequence number is exhausted.
+    pub fn seal(
+        &mut self,
+        content_type: u8,
+        content: &[u8],
+        padding: usize,
+    ) -> Result<Vec<u8>, RecordError> {
+        if content_type == 0 {
+            return Err(RecordError(Inner::Invalid("content type must not be 0")));
+        }
+        // TLSInnerPlaintext is limited to 2^14 + 1 bytes
+        if content.len().saturating_add(padding) > MAX_PLAINTEXT_LEN {
+            return Err(RecordError(Inner::Invalid("record too large")));
+        }
+        if self.seq == u64::MAX {
+            return Err(RecordError(Inner::Invalid("sequence number exhausted")));
+        }
+
+        let mut inner = Vec::with_capacity(content.len() + 1 + padding);
+        inner.extend_from_slice(content);
+        inner.push(content_type);
+        inner.resize(content.len() + 1 + padding, 0);
+
+        let header = header(inner.len() + TAG_LEN);
+        let mut tag = [0; TAG_LEN];
+        let ciphertext = symm::encrypt_aead(
+            self.suite.cipher(),
+            &self.key,
+            Some(&self.nonce()),
+            &header,
+            &inner,
+            &mut tag,
+        )?;
+        self.seq += 1;
+
+        let mut record = Vec::with_capacity(HEADER_LEN + ciphertext.len() + TAG_LEN);
+        record.extend_from_slice(&header);
+        record.extend_from_slice(&ciphertext);
+        record.extend_from_slice(&tag);
+        Ok(record)
+    }
+
+    /// Unprotects a record, including its 5 byte header, returning its real content type and content.
+    ///
+    /// The sequence number is only advanced if the record is authentic.
+    pub fn open(&mut self, record: &[u8]) -> Result<(u8, Vec<u8>), RecordError> {
+        if record.len() < HEADER_LEN {
+            return Err(RecordError(Inner::Invalid("truncated record header")));
+        }
+        let (header, payload) = record.split_at(HEADER_LEN);
+        if header[0] != APPLICATION_DATA {
+            return Err(RecordError(Inner::Invalid("unexpected outer content type")));
+        }
+        // legacy_record_version is ignored
+        if usize::from(u16::from_be_bytes([header[3], header[4]])) != payload.len() {
+            return Err(RecordError(Inner::Invalid("record length mismatch")));
+        }
+        if payload.len() < TAG_LEN + 1 || payload.len() > MAX_CIPHERTEXT_LEN {
+            return Err(RecordError(Inner::Invalid("invalid record length")));
+        }
+        if self.seq == u64::MAX {
+            return Err(RecordError(Inner::Invalid("sequence number exhausted")));
+        }
+
+        let (ciphertext, tag) = payload.split_at(payload.len() - TAG_LEN);
+        let mut inner = symm::decrypt_aead(
+            self.suite.cipher(),
+            &self.key,
+            Some(&self.nonce()),
+            header,
+            ciphertext,
+            tag,
+        )?;
+        self.seq += 1;
+
+        let len = match inner.iter().rposition(|&b| b != 0) {
+            Some(len) => len,
+            None => return Err(RecordError(Inner::Invalid("missing inner content type"))),
+        };
+        let content_type = inner[len];
+        inner.truncate(len);
+        Ok((content_type, inner))
+    }
+
+    fn nonce(&self) -> [u8; NONCE_LEN] {
+        let mut nonce = self.iv;
+        for (n, s) in nonce[NONCE_LEN - 8..]
+            .iter_mut()
+            .zip(&self.seq.to_be_bytes())
+        {
+            *n ^= s;
+        }
+        nonce
+    }
+}
+
+fn header(len: usize) -> [u8; HEADER_LEN] {
+    let len = (len as u16).to_be_bytes();
+    [APPLICATION_DATA, 3, 3, len[0], len[1]]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // RFC 8448 section 3, server handshake traffic secret
+    const SECRET: &str = "b67b7d690cc16c4e75e54213cb2d37b4e9c912bcded9105d42befd59d391ad38";
+
+    #[test]
+    fn key_derivation() {
+        let secret = hex::decode(SECRET).unwrap();
+        let p = RecordProtection::new(CipherSuite::TLS_AES_128_GCM_SHA256, &secret).unwrap();
+        assert_eq!(hex::encode(&p.key), "3fce516009c21727d0f2e4e86ee403bc");
+        assert_eq!(hex::encode(p.iv), "5d313eb2671276ee13000b30");
+    }
+
+    #[test]
+    fn seal_open() {
+        let secret = hex::decode(SECRET).unwrap();
+        for &suite in &[
+            CipherSuite::TLS_AES_128_GCM_SHA256,
+            CipherSuite::TLS_CHACHA20_POLY1305_SHA256,
+        ] {
+            let mut sender = RecordProtection::new(suite, &secret).unwrap();
+            let mut receiver = RecordProtection::new(suite, &secret).unwrap();
+
+            let record = sender.seal(23, b"hello", 10).unwrap();
+            assert_eq!(record.len(), HEADER_LEN + 5 + 1 + 10 + TAG_LEN);
+            assert_eq!(&record[..3], &[23, 3, 3]);
+            assert_eq!(receiver.open(&record).unwrap(), (23, b"hello".to_vec()));
+
+            // replaying a record fails as the sequence number has moved on
+            assert!(receiver.open(&record).unwrap_err().ssl_error().is_some());
+
+            let record = sender.seal(21, b"", 0).unwrap();
+            assert_eq!(receiver.open(&record).unwrap(), (21, vec![]));
+            assert_eq!(sender.sequence_number(), 2);
+            assert_eq!(receiver.sequence_number(), 2);
+
+            sender.update_key().unwrap();
+            receiver.update_key().unwrap();
+            assert_eq!(receiver.sequence_number(), 0);
+            let mut record = sender.seal(23, b"world", 0).unwrap();
+            record[7] ^= 1;
+            assert!(receiver.open(&record).is_err());
+            assert_eq!(receiver.sequence_number(), 0);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let secret = hex::decode(SECRET).unwrap();
+        let mut p = RecordProtection::new(CipherSuite::TLS_AES_128_GCM_SHA256, &secret).unwrap();
+        assert!(p.seal(23, &[0; MAX_PLAINTEXT_LEN + 1], 0).is_err());
+        assert!(p.seal(0, b"", 0).is_err());
+        assert!(p.open(&[23, 3, 3, 0, 1, 0]).is_err());
+        assert!(p.open(&[22, 3, 3, 0, 0]).is_err());
+        assert_eq!(
+            CipherSuite::from_u16(0x1302),
+            Some(CipherSuite::TLS_AES_256_GCM_SHA384)
+        );
+        assert_eq!(CipherSuite::from_u16(0x1304), None);
+    }
+}
@@ -0,0 +1,109 @@
+//! Trust tokens.
+//!
+//! Trust tokens are an anonymous authentication protocol, based on Privacy Pass, which allows an
+//! issuer to attest to a client without being able to link the redemption of a token to its
+//! issuance. Only issuer key generation is currently exposed; the issuance and redemption
+//! protocols require the raw `ffi` functions.
+//!
+//! Requires BoringSSL.
+use std::fmt;
+
+use crate::cvt;
+use crate::error::ErrorStack;
+use openssl_macros::corresponds;
+
+/// A trust token protocol variant.
+#[derive(Copy, Clone)]
+pub struct TrustTokenMethod(*const ffi::TRUST_TOKEN_METHOD);
+
+impl TrustTokenMethod {
+    /// The experimental version 2 protocol with a verifiable oblivious PRF, whose tokens carry no
+    /// private metadata.
+    #[corresponds(TRUST_TOKEN_experiment_v2_voprf)]
+    pub fn experiment_v2_voprf() -> TrustTokenMethod {
+        unsafe { TrustTokenMethod(ffi::TRUST_TOKEN_experiment_v2_voprf()) }
+    }
+
+    /// The experimental version 2 protocol with private metadata.
+    #[corresponds(TRUST_TOKEN_experiment_v2_pmb)]
+    pub fn experiment_v2_pmb() -> TrustTokenMethod {
+        unsafe { TrustTokenMethod(ffi::TRUST_TOKEN_experiment_v2_pmb()) }
+    }
+
+    /// Returns a pointer to the underlying OpenSSL value.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn as_ptr(&self) -> *const ffi::TRUST_TOKEN_METHOD {
+        self.0
+    }
+}
+
+unsafe impl Sync for TrustTokenMethod {}
+unsafe impl Send for TrustTokenMethod {}
+
+/// A serialized trust token issuer key pair.
+pub struct TrustTokenKeyPair {
+    private_key: Vec<u8>,
+    public_key: Vec<u8>,
+}
+
+impl TrustTokenKeyPair {
+    /// Returns the serialized private key, which is used by the issuer.
+    pub fn private_key(&self) -> &[u8] {
+        &self.private_key
+    }
+
+    /// Returns the serialized public key, which is distributed to clients.
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+}
+
+impl fmt::Debug for TrustTokenKeyPair {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("TrustTokenKeyPair")
+            .field("public_key", &self.public_key)
+            .finish()
+    }
+}
+
+/// Generates a new issuer key pair for `method`.
+///
+/// The `id` is embedded in the key, and identifies it in the issuer's key commitment.
+#[corresponds(TRUST_TOKEN_generate_key)]
+pub fn generate_key(method: TrustTokenMethod, id: u32) -> Result<TrustTokenKeyPair, ErrorStack> {
+    let mut private_key = vec![0; ffi::TRUST_TOKEN_MAX_PRIVATE_KEY_SIZE as usize];
+    let mut public_key = vec![0; ffi::TRUST_TOKEN_MAX_PUBLIC_KEY_SIZE as usize];
+    let mut private_len = 0;
+    let mut public_len = 0;
+    unsafe {
+        ffi::init();
+        cvt(ffi::TRUST_TOKEN_generate_key(
+            method.as_ptr(),
+            private_key.as_mut_ptr(),
+            &mut private_len,
+            private_key.len(),
+            public_key.as_mut_ptr(),
+            &mut public_len,
+            public_key.len(),
+            id,
+        ))?;
+    }
+    private_key.truncate(private_len);
+    public_key.truncate(public_len);
+    Ok(TrustTokenKeyPair {
+        private_key,
+        public_key,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_key() {
+        let key = super::generate_key(TrustTokenMethod::experiment_v2_voprf(), 1).unwrap();
+        assert!(!key.private_key().is_empty());
+        assert!(!key.public_key().is_empty());
+    }
+}
@@ -2,13 +2,15 @@ use crate::error::ErrorStack;
 use foreign_types::{ForeignType, ForeignTypeRef};
 use libc::{c_char, c_int, c_void};
 use std::any::Any;
-use std::panic::{self, AssertUnwindSafe};
 use std::slice;
 
 /// Wraps a user-supplied callback and a slot for panics thrown inside the callback (while FFI
 /// frames are on the stack).
 ///
-/// When dropped, checks if the callback has panicked, and resumes unwinding if so.
+/// When dropped, checks if the callback has panicked, and resumes unwinding if so, depending on the
+/// [`PanicPolicy`].
+///
+/// [`PanicPolicy`]: crate::panic::PanicPolicy
 pub struct CallbackState<F> {
     /// The user callback. Taken out of the `Option` when called.
     cb: Option<F>,
@@ -29,7 +31,7 @@ impl<F> CallbackState<F> {
 impl<F> Drop for CallbackState<F> {
     fn drop(&mut self) {
         if let Some(panic) = self.panic.take() {
-            panic::resume_unwind(panic);
+            crate::panic::resume(panic);
         }
     }
 }
@@ -48,10 +50,10 @@ where
 {
     let callback = &mut *(cb_state as *mut CallbackState<F>);
 
-    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+    let result = crate::panic::catch(|| {
         let pass_slice = slice::from_raw_parts_mut(buf as *mut u8, size as usize);
         callback.cb.take().unwrap()(pass_slice)
-    }));
+    });
 
     match result {
         Ok(Ok(len)) => len as c_int,
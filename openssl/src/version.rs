@@ -100,6 +100,122 @@ pub fn dir() -> &'static str {
     }
 }
 
+/// The library this crate was built against.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Backend {
+    /// OpenSSL.
+    OpenSsl,
+    /// LibreSSL.
+    LibreSsl,
+    /// BoringSSL.
+    BoringSsl,
+}
+
+/// A report of the optional APIs available in this build of the crate.
+///
+/// Many APIs are only present when building against particular libraries or versions. This
+/// allows code to report, or choose between, the APIs available without duplicating the crate's
+/// `cfg` logic. Each method returns `true` if the corresponding API is present.
+#[derive(Debug, Copy, Clone)]
+pub struct Capabilities {
+    backend: Backend,
+    tls13: bool,
+    hkdf: bool,
+    hkdf_extract_expand: bool,
+    kdf: bool,
+    providers: bool,
+    certificate_compression: bool,
+    grease: bool,
+    permute_extensions: bool,
+    private_key_method: bool,
+    trust_tokens: bool,
+}
+
+impl Capabilities {
+    /// Returns the library this crate was built against.
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Returns whether TLS 1.3 is supported.
+    pub fn tls13(&self) -> bool {
+        self.tls13
+    }
+
+    /// Returns whether `kdf::hkdf` is present.
+    pub fn hkdf(&self) -> bool {
+        self.hkdf
+    }
+
+    /// Returns whether `kdf::hkdf_extract`, `kdf::hkdf_expand`, and `kdf::tls13_hkdf_expand_label`
+    /// are present.
+    pub fn hkdf_extract_expand(&self) -> bool {
+        self.hkdf_extract_expand
+    }
+
+    /// Returns whether the `EVP_KDF` based functions of the `kdf` module, such as
+    /// `kdf::sskdf_digest`, are present.
+    pub fn kdf(&self) -> bool {
+        self.kdf
+    }
+
+    /// Returns whether the `provider` and `lib_ctx` modules are present.
+    pub fn providers(&self) -> bool {
+        self.providers
+    }
+
+    /// Returns whether `SslContextBuilder::add_certificate_compression_algorithm` is present.
+    pub fn certificate_compression(&self) -> bool {
+        self.certificate_compression
+    }
+
+    /// Returns whether `SslContextBuilder::set_grease_enabled` is present.
+    pub fn grease(&self) -> bool {
+        self.grease
+    }
+
+    /// Returns whether `SslContextBuilder::set_permute_extensions` is present.
+    pub fn permute_extensions(&self) -> bool {
+        self.permute_extensions
+    }
+
+    /// Returns whether `SslContextBuilder::set_private_key_method` is present.
+    pub fn private_key_method(&self) -> bool {
+        self.private_key_method
+    }
+
+    /// Returns whether the `trust_token` module is present.
+    pub fn trust_tokens(&self) -> bool {
+        self.trust_tokens
+    }
+}
+
+/// Returns a report of the optional APIs available in this build of the crate.
+pub fn capabilities() -> Capabilities {
+    let backend = if cfg!(boringssl) {
+        Backend::BoringSsl
+    } else if cfg!(libressl) {
+        Backend::LibreSsl
+    } else {
+        Backend::OpenSsl
+    };
+
+    Capabilities {
+        backend,
+        tls13: cfg!(any(boringssl, ossl111, libressl340)),
+        hkdf: cfg!(any(ossl110, boringssl)),
+        hkdf_extract_expand: cfg!(any(ossl111, boringssl)),
+        kdf: cfg!(ossl300),
+        providers: cfg!(ossl300),
+        certificate_compression: cfg!(boringssl),
+        grease: cfg!(boringssl),
+        permute_extensions: cfg!(boringssl),
+        private_key_method: cfg!(boringssl),
+        trust_tokens: cfg!(boringssl),
+    }
+}
+
 /// This test ensures that we do not segfault when calling the functions of this module
 /// and that the strings respect a reasonable format.
 #[test]
@@ -133,3 +249,17 @@ fn test_versions() {
     }
     assert!(dir().starts_with("OPENSSLDIR:"));
 }
+
+#[test]
+fn test_capabilities() {
+    let capabilities = capabilities();
+    println!("{:?}", capabilities);
+
+    #[cfg(not(any(libressl, boringssl)))]
+    assert_eq!(capabilities.backend(), Backend::OpenSsl);
+    #[cfg(libressl)]
+    assert_eq!(capabilities.backend(), Backend::LibreSsl);
+    #[cfg(boringssl)]
+    assert_eq!(capabilities.backend(), Backend::BoringSsl);
+    assert_eq!(capabilities.hkdf(), cfg!(any(ossl110, boringssl)));
+}
@@ -0,0 +1,199 @@
+//! Utilities for PEM certificate chain bundles.
+//!
+//! Chain files uploaded by operators are frequently out of order, contain duplicate or unrelated certificates, or
+//! are missing an intermediate. [`CertificateBundle`] splits a bundle into certificates, puts them in leaf to root
+//! order, and reports the first certificate whose issuer is neither in the bundle nor in a trusted [`X509Store`].
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::x509::bundle::CertificateBundle;
+//! use openssl::x509::store::X509StoreBuilder;
+//! use openssl::x509::X509;
+//!
+//! let mut pem = include_bytes!("../../test/intermediate-ca.pem").to_vec();
+//! pem.extend_from_slice(include_bytes!("../../test/leaf.pem"));
+//!
+//! let mut bundle = CertificateBundle::from_pem(&pem).unwrap();
+//! assert!(bundle.order().is_empty());
+//!
+//! let mut store = X509StoreBuilder::new().unwrap();
+//! let root = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+//! store.add_cert(root).unwrap();
+//! let store = store.build();
+//!
+//! assert!(bundle.missing_issuer(&store).is_none());
+//! ```
+//!
+//! [`X509Store`]: crate::x509::store::X509Store
+#[cfg(ossl110)]
+use crate::asn1::Asn1OctetStringRef;
+use crate::error::ErrorStack;
+use crate::x509::store::X509StoreRef;
+use crate::x509::{X509NameRef, X509Ref, X509VerifyResult, X509};
+
+/// A list of certificates forming, or intended to form, a certificate chain.
+#[derive(Clone)]
+pub struct CertificateBundle {
+    certs: Vec<X509>,
+}
+
+impl CertificateBundle {
+    /// Creates a bundle from a list of certificates.
+    pub fn new(certs: Vec<X509>) -> CertificateBundle {
+        CertificateBundle { certs }
+    }
+
+    /// Splits a PEM file containing any number of certificates.
+    pub fn from_pem(pem: &[u8]) -> Result<CertificateBundle, ErrorStack> {
+        X509::stack_from_pem(pem).map(CertificateBundle::new)
+    }
+
+    /// Returns the certificates in the bundle.
+    pub fn certs(&self) -> &[X509] {
+        &self.certs
+    }
+
+    /// Returns the certificates in the bundle, consuming it.
+    pub fn into_certs(self) -> Vec<X509> {
+        self.certs
+    }
+
+    /// Serializes the bundle as concatenated PEM certificates.
+    pub fn to_pem(&self) -> Result<Vec<u8>, ErrorStack> {
+        let mut pem = vec![];
+        for cert in &self.certs {
+            pem.extend_from_slice(&cert.to_pem()?);
+        }
+        Ok(pem)
+    }
+
+    /// Removes duplicate certificates, keeping the first occurrence of each.
+    ///
+    /// Returns the number of certificates removed.
+    pub fn dedup(&mut self) -> usize {
+        let len = self.certs.len();
+        let mut unique: Vec<X509> = Vec::with_capacity(len);
+        for cert in self.certs.drain(..) {
+            if !unique.contains(&cert) {
+                unique.push(cert);
+            }
+        }
+        self.certs = unique;
+        len - self.certs.len()
+    }
+
+    /// Puts the bundle in leaf to root order, and removes certificates which are not part of the leaf's chain.
+    ///
+    /// The leaf is the first certificate which did not issue any other certificate in the bundle, preferring
+    /// certificates which are not self-issued. Each following
+    /// certificate is the issuer of the one before it, as determined by [`X509Ref::issued`], which compares both
+    /// names and key identifiers. Duplicates should be removed with [`dedup`] first, or they will be returned as
+    /// unrelated.
+    ///
+    /// Returns the removed certificates, in their original order.
+    ///
+    /// [`dedup`]: CertificateBundle::dedup
+    pub fn order(&mut self) -> Vec<X509> {
+        let mut remaining = self.certs.drain(..).map(Some).collect::<Vec<_>>();
+
+        let issued_none = |candidate: &X509Ref| {
+            !remaining
+                .iter()
+                .map(|other| other.as_ref().unwrap())
+                .any(|other| !is_self_issued(other) && issued(candidate, other))
+        };
+        let leaf = remaining
+            .iter()
+            .map(|c| c.as_ref().unwrap())
+            .position(|c| !is_self_issued(c) && issued_none(c))
+            .or_else(|| {
+                remaining
+                    .iter()
+                    .position(|c| issued_none(c.as_ref().unwrap()))
+            });
+
+        if let Some(mut idx) = leaf {
+            loop {
+                let cert = remaining[idx].take().unwrap();
+                let next = if is_self_issued(&cert) {
+                    None
+                } else {
+                    remaining
+                        .iter()
+                        .position(|c| matches!(c, Some(c) if issued(c, &cert)))
+                };
+                self.certs.push(cert);
+                match next {
+                    Some(next) => idx = next,
+                    None => break,
+                }
+            }
+        }
+
+        remaining.into_iter().flatten().collect()
+    }
+
+    /// Returns the first certificate in the chain whose issuer is neither in the bundle nor trusted by `store`.
+    ///
+    /// The bundle is assumed to be in leaf to root order, as produced by [`order`]. The chain is complete if a
+    /// certificate was issued by a certificate in `store`, or is self-issued. Only certificates already loaded into
+    /// the store are consulted, not those in a hashed directory lookup which have not been used yet.
+    ///
+    /// [`order`]: CertificateBundle::order
+    pub fn missing_issuer(&self, store: &X509StoreRef) -> Option<MissingIssuer<'_>> {
+        let (last, rest) = self.certs.split_last()?;
+
+        for (cert, issuer) in rest.iter().zip(&self.certs[1..]) {
+            if !issued(issuer, cert) && !trusted_issuer(store, cert) {
+                return Some(MissingIssuer { cert });
+            }
+        }
+
+        if is_self_issued(last) || trusted_issuer(store, last) {
+            None
+        } else {
+            Some(MissingIssuer { cert: last })
+        }
+    }
+}
+
+/// A certificate whose issuer could not be found.
+pub struct MissingIssuer<'a> {
+    cert: &'a X509Ref,
+}
+
+impl<'a> MissingIssuer<'a> {
+    /// Returns the certificate whose issuer is missing.
+    pub fn certificate(&self) -> &'a X509Ref {
+        self.cert
+    }
+
+    /// Returns the subject name of the missing issuer.
+    pub fn issuer_name(&self) -> &'a X509NameRef {
+        self.cert.issuer_name()
+    }
+
+    /// Returns the subject key identifier of the missing issuer, from the certificate's authority key identifier
+    /// extension.
+    #[cfg(ossl110)]
+    pub fn authority_key_id(&self) -> Option<&'a Asn1OctetStringRef> {
+        self.cert.authority_key_id()
+    }
+}
+
+fn issued(issuer: &X509Ref, subject: &X509Ref) -> bool {
+    issuer.issued(subject) == X509VerifyResult::OK
+}
+
+fn is_self_issued(cert: &X509Ref) -> bool {
+    issued(cert, cert)
+}
+
+fn trusted_issuer(store: &X509StoreRef, cert: &X509Ref) -> bool {
+    store
+        .objects()
+        .iter()
+        .filter_map(|object| object.x509())
+        .any(|issuer| issued(issuer, cert))
+}
@@ -0,0 +1,538 @@
+//! A minimal certificate authority.
+//!
+//! [`CertificateAuthority`] issues certificates and certificate revocation lists signed by a CA
+//! certificate and its private key. Certificates are issued from a certificate request or from a
+//! public key and subject, with the extensions and validity period configured by an
+//! [`IssuanceProfile`]. Serial numbers are random by default, or allocated from a
+//! [`SerialAllocator`] whose persistence callback records each one before it is used.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::bn::BigNum;
+//! use openssl::x509::ca::{CertificateAuthority, IssuanceProfile, Revocation};
+//! use openssl::x509::extension::SubjectAlternativeName;
+//! use openssl::x509::self_signed::{generate_dev_identity, SelfSignedOptions};
+//! use openssl::x509::{CrlReason, X509Name, X509};
+//!
+//! let (ca_key, _) = generate_dev_identity().unwrap();
+//! let mut name = X509Name::builder().unwrap();
+//! name.append_entry_by_text("CN", "Example CA").unwrap();
+//! let ca_cert = X509::self_signed(&name.build(), &ca_key, SelfSignedOptions::new().ca()).unwrap();
+//! let mut ca = CertificateAuthority::new(ca_cert, ca_key).unwrap();
+//!
+//! let (key, _) = generate_dev_identity().unwrap();
+//! let mut name = X509Name::builder().unwrap();
+//! name.append_entry_by_text("CN", "www.example.com").unwrap();
+//! let mut san = SubjectAlternativeName::new();
+//! san.dns("www.example.com");
+//! let cert = ca
+//!     .issue(&name.build(), &key, IssuanceProfile::new().subject_alt_name(san))
+//!     .unwrap();
+//! assert!(cert.verify(&ca.certificate().public_key().unwrap()).unwrap());
+//!
+//! let mut revocation = Revocation::from_certificate(&cert).unwrap();
+//! revocation.reason(CrlReason::KEY_COMPROMISE);
+//! let crl = ca
+//!     .crl(&BigNum::from_u32(1).unwrap(), &[revocation], 7)
+//!     .unwrap();
+//! assert_eq!(crl.get_revoked().unwrap().len(), 1);
+//! ```
+//!
+//! [`SerialAllocator`]: crate::x509::serial::SerialAllocator
+use cfg_if::cfg_if;
+use std::error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+
+use crate::asn1::{Asn1Integer, Asn1IntegerRef, Asn1Object, Asn1OctetString, Asn1Time};
+use crate::bn::BigNumRef;
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::nid::Nid;
+use crate::pkey::{HasPublic, Id, PKey, PKeyRef, Private};
+use crate::x509::csr::default_digest;
+use crate::x509::extension::{
+    AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
+    SubjectKeyIdentifier,
+};
+use crate::x509::serial::{SerialAllocator, SerialError};
+use crate::x509::{
+    CrlReason, X509Crl, X509Extension, X509ExtensionRef, X509NameRef, X509Ref, X509ReqRef,
+    X509Revoked, X509v3Context, X509,
+};
+use crate::{cvt, cvt_p};
+use foreign_types::{ForeignType, ForeignTypeRef};
+
+const DEFAULT_DAYS: u32 = 365;
+const CRL_REASON_OID: &str = "2.5.29.21";
+
+/// An error issuing a certificate or CRL.
+pub struct CaError(Inner);
+
+enum Inner {
+    Invalid(&'static str),
+    Serial(SerialError),
+    Ssl(ErrorStack),
+}
+
+impl CaError {
+    fn invalid(msg: &'static str) -> CaError {
+        CaError(Inner::Invalid(msg))
+    }
+
+    /// Returns the underlying OpenSSL error stack, if the error was reported by OpenSSL.
+    pub fn ssl_error(&self) -> Option<&ErrorStack> {
+        match self.0 {
+            Inner::Ssl(ref e) => Some(e),
+            Inner::Serial(ref e) => e.ssl_error(),
+            Inner::Invalid(_) => None,
+        }
+    }
+}
+
+impl fmt::Debug for CaError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Inner::Invalid(ref e) => fmt.debug_tuple("Invalid").field(e).finish(),
+            Inner::Serial(ref e) => fmt.debug_tuple("Serial").field(e).finish(),
+            Inner::Ssl(ref e) => fmt.debug_tuple("Ssl").field(e).finish(),
+        }
+    }
+}
+
+impl fmt::Display for CaError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Inner::Invalid(e) => write!(fmt, "certificate authority error: {}", e),
+            Inner::Serial(ref e) => fmt::Display::fmt(e, fmt),
+            Inner::Ssl(ref e) => fmt::Display::fmt(e, fmt),
+        }
+    }
+}
+
+impl error::Error for CaError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self.0 {
+            Inner::Invalid(_) => None,
+            Inner::Serial(ref e) => Some(e),
+            Inner::Ssl(ref e) => Some(e),
+        }
+    }
+}
+
+impl From<ErrorStack> for CaError {
+    fn from(e: ErrorStack) -> CaError {
+        CaError(Inner::Ssl(e))
+    }
+}
+
+impl From<SerialError> for CaError {
+    fn from(e: SerialError) -> CaError {
+        CaError(Inner::Serial(e))
+    }
+}
+
+/// The extensions and validity period of certificates issued by a [`CertificateAuthority`].
+///
+/// By default, issued certificates are end-entity certificates valid for 365 days from the time
+/// of issuance, with a critical key usage extension permitting digital signatures, and key
+/// encipherment for RSA keys. Validity periods are truncated to end when the CA certificate
+/// expires.
+pub struct IssuanceProfile {
+    days: u32,
+    ca: Option<Option<u32>>,
+    key_usage: Option<KeyUsage>,
+    extended_key_usage: Option<ExtendedKeyUsage>,
+    subject_alt_name: Option<SubjectAlternativeName>,
+}
+
+impl Default for IssuanceProfile {
+    fn default() -> IssuanceProfile {
+        IssuanceProfile::new()
+    }
+}
+
+impl IssuanceProfile {
+    /// Creates the default profile.
+    pub fn new() -> IssuanceProfile {
+        IssuanceProfile {
+            days: DEFAULT_DAYS,
+            ca: None,
+            key_usage: None,
+            extended_key_usage: None,
+            subject_alt_name: None,
+        }
+    }
+
+    /// Sets the number of days issued certificates are valid for.
+    pub fn days(&mut self, days: u32) -> &mut IssuanceProfile {
+        self.days = days;
+        self
+    }
+
+    /// Makes issued certificates intermediate CA certificates.
+    ///
+    /// `pathlen` is the maximum number of CAs that can appear below them in a chain. The default
+    /// key usage of CA certificates permits signing certificates and CRLs.
+    pub fn ca(&mut self, pathlen: Option<u32>) -> &mut IssuanceProfile {
+        self.ca = Some(pathlen);
+        self
+    }
+
+    /// Sets the key usage of issued certificates.
+    pub fn key_usage(&mut self, key_usage: KeyUsage) -> &mut IssuanceProfile {
+        self.key_usage = Some(key_usage);
+        self
+    }
+
+    /// Sets the extended key usage of issued certificates.
+    pub fn extended_key_usage(
+        &mut self,
+        extended_key_usage: ExtendedKeyUsage,
+    ) -> &mut IssuanceProfile {
+        self.extended_key_usage = Some(extended_key_usage);
+        self
+    }
+
+    /// Sets the subject alternative names of issued certificates.
+    ///
+    /// For certificates issued from a certificate request, this replaces the subject alternative
+    /// names requested by the subject.
+    pub fn subject_alt_name(
+        &mut self,
+        subject_alt_name: SubjectAlternativeName,
+    ) -> &mut IssuanceProfile {
+        self.subject_alt_name = Some(subject_alt_name);
+        self
+    }
+}
+
+/// A certificate to be listed in a CRL.
+pub struct Revocation {
+    serial: Asn1Integer,
+    date: Asn1Time,
+    reason: Option<CrlReason>,
+}
+
+impl Revocation {
+    /// Creates a revocation of the certificate with serial number `serial`, revoked now.
+    pub fn new(serial: &Asn1IntegerRef) -> Result<Revocation, ErrorStack> {
+        Ok(Revocation {
+            serial: serial.to_bn()?.to_asn1_integer()?,
+            date: Asn1Time::days_from_now(0)?,
+            reason: None,
+        })
+    }
+
+    /// Creates a revocation of `cert`, revoked now.
+    pub fn from_certificate(cert: &X509Ref) -> Result<Revocation, ErrorStack> {
+        Revocation::new(cert.serial_number())
+    }
+
+    /// Sets the time the certificate was revoked.
+    pub fn date(&mut self, date: Asn1Time) -> &mut Revocation {
+        self.date = date;
+        self
+    }
+
+    /// Sets the reason the certificate was revoked.
+    ///
+    /// By default, the CRL entry has no reason code extension.
+    pub fn reason(&mut self, reason: CrlReason) -> &mut Revocation {
+        self.reason = Some(reason);
+        self
+    }
+}
+
+/// A certificate authority issuing certificates and CRLs.
+pub struct CertificateAuthority {
+    cert: X509,
+    key: PKey<Private>,
+    serials: Option<SerialAllocator>,
+    digest: Option<MessageDigest>,
+}
+
+impl CertificateAuthority {
+    /// Creates a certificate authority from its certificate and private key.
+    ///
+    /// Fails if `key` does not match the public key of `cert`.
+    pub fn new(cert: X509, key: PKey<Private>) -> Result<CertificateAuthority, CaError> {
+        if !cert.public_key()?.public_eq(&key) {
+            return Err(CaError::invalid(
+                "the private key does not match the CA certificate",
+            ));
+        }
+
+        Ok(CertificateAuthority {
+            cert,
+            key,
+            serials: None,
+            digest: None,
+        })
+    }
+
+    /// Returns the CA certificate.
+    pub fn certificate(&self) -> &X509Ref {
+        &self.cert
+    }
+
+    /// Allocates the serial numbers of issued certificates from `allocator`.
+    ///
+    /// By default, serial numbers are generated with [`Asn1Integer::rand_serial`].
+    pub fn set_serial_allocator(&mut self, allocator: SerialAllocator) {
+        self.serials = Some(allocator);
+    }
+
+    /// Sets the digest used to sign certificates and CRLs.
+    ///
+    /// By default, the digest is chosen based on the CA key, as for [`CsrProfile::digest`].
+    ///
+    /// [`CsrProfile::digest`]: crate::x509::csr::CsrProfile::digest
+    pub fn set_digest(&mut self, digest: MessageDigest) {
+        self.digest = Some(digest);
+    }
+
+    fn digest(&self) -> MessageDigest {
+        match self.digest {
+            Some(digest) => digest,
+            None => default_digest(&self.key),
+        }
+    }
+
+    /// Issues a certificate for the subject and public key of a certificate request.
+    ///
+    /// The request's signature is verified first. Its subject alternative name extension is
+    /// copied to the certificate if `profile` has none, and all other requested extensions are
+    /// ignored.
+    pub fn issue_request(
+        &mut self,
+        req: &X509ReqRef,
+        profile: &IssuanceProfile,
+    ) -> Result<X509, CaError> {
+        let key = req.public_key()?;
+        if !req.verify(&key)? {
+            return Err(CaError::invalid(
+                "the certificate request signature is invalid",
+            ));
+        }
+
+        let mut requested_alt_name = None;
+        if profile.subject_alt_name.is_none() {
+            // requests without extensions fail to return any
+            if let Ok(extensions) = req.extensions() {
+                requested_alt_name = extensions
+                    .into_iter()
+                    .find(|extension| extension.nid() == Nid::SUBJECT_ALT_NAME);
+            }
+        }
+
+        self.issue_internal(
+            req.subject_name(),
+            &key,
+            profile,
+            requested_alt_name.as_deref(),
+        )
+    }
+
+    /// Issues a certificate for `subject` and `key`.
+    pub fn issue<T>(
+        &mut self,
+        subject: &X509NameRef,
+        key: &PKeyRef<T>,
+        profile: &IssuanceProfile,
+    ) -> Result<X509, CaError>
+    where
+        T: HasPublic,
+    {
+        self.issue_internal(subject, key, profile, None)
+    }
+
+    fn issue_internal<T>(
+        &mut self,
+        subject: &X509NameRef,
+        key: &PKeyRef<T>,
+        profile: &IssuanceProfile,
+        requested_alt_name: Option<&X509ExtensionRef>,
+    ) -> Result<X509, CaError>
+    where
+        T: HasPublic,
+    {
+        let not_before = Asn1Time::days_from_now(0)?;
+        if self.cert.not_after() <= not_before {
+            return Err(CaError::invalid("the CA certificate has expired"));
+        }
+
+        let mut builder = X509::builder()?;
+        builder.set_version(2)?;
+        match &mut self.serials {
+            Some(serials) => builder.set_serial_number_from(serials)?,
+            None => builder.set_serial_number(&*Asn1Integer::rand_serial()?)?,
+        }
+        builder.set_subject_name(subject)?;
+        builder.set_issuer_name(self.cert.subject_name())?;
+        builder.set_pubkey(key)?;
+        builder.set_not_before(&not_before)?;
+        let not_after = Asn1Time::days_from_now(profile.days)?;
+        if not_after > *self.cert.not_after() {
+            builder.set_not_after(self.cert.not_after())?;
+        } else {
+            builder.set_not_after(&not_after)?;
+        }
+
+        let mut basic_constraints = BasicConstraints::new();
+        basic_constraints.critical();
+        if let Some(pathlen) = profile.ca {
+            basic_constraints.ca();
+            if let Some(pathlen) = pathlen {
+                basic_constraints.pathlen(pathlen);
+            }
+        }
+        builder.append_extension(basic_constraints.build()?)?;
+
+        match &profile.key_usage {
+            Some(key_usage) => builder.append_extension(key_usage.build()?)?,
+            None => {
+                let mut key_usage = KeyUsage::new();
+                key_usage.critical().digital_signature();
+                if profile.ca.is_some() {
+                    key_usage.key_cert_sign().crl_sign();
+                } else if key.id() == Id::RSA {
+                    key_usage.key_encipherment();
+                }
+                builder.append_extension(key_usage.build()?)?;
+            }
+        }
+
+        if let Some(extended_key_usage) = &profile.extended_key_usage {
+            builder.append_extension(extended_key_usage.build()?)?;
+        }
+
+        let empty_subject = subject.entries().next().is_none();
+        match (&profile.subject_alt_name, requested_alt_name) {
+            (Some(subject_alt_name), _) => {
+                builder.append_extension(subject_alt_name.build_internal(empty_subject)?)?
+            }
+            (None, Some(requested_alt_name)) => builder.append_extension2(requested_alt_name)?,
+            (None, None) if empty_subject => {
+                return Err(CaError::invalid(
+                    "certificates with an empty subject need a subject alternative name",
+                ))
+            }
+            (None, None) => {}
+        }
+
+        let subject_key_identifier =
+            SubjectKeyIdentifier::new().build(&builder.x509v3_context(Some(&self.cert), None))?;
+        builder.append_extension(subject_key_identifier)?;
+        let authority_key_identifier = AuthorityKeyIdentifier::new()
+            .keyid(false)
+            .build(&builder.x509v3_context(Some(&self.cert), None))?;
+        builder.append_extension(authority_key_identifier)?;
+
+        builder.sign(&self.key, self.digest())?;
+        Ok(builder.build())
+    }
+
+    /// Issues a CRL listing `revoked`, valid until `days` days from now.
+    ///
+    /// `number` is the CRL number, which must increase with every CRL issued by the CA.
+    pub fn crl(
+        &self,
+        number: &BigNumRef,
+        revoked: &[Revocation],
+        days: u32,
+    ) -> Result<X509Crl, CaError> {
+        let this_update = Asn1Time::days_from_now(0)?;
+        let next_update = Asn1Time::days_from_now(days)?;
+        let number = number.to_asn1_integer()?;
+
+        unsafe {
+            let crl = X509Crl::from_ptr(cvt_p(ffi::X509_CRL_new())?);
+            cvt(ffi::X509_CRL_set_version(crl.as_ptr(), 1))?;
+            cvt(ffi::X509_CRL_set_issuer_name(
+                crl.as_ptr(),
+                self.cert.subject_name().as_ptr(),
+            ))?;
+            cvt(X509_CRL_set1_lastUpdate(crl.as_ptr(), this_update.as_ptr()))?;
+            cvt(X509_CRL_set1_nextUpdate(crl.as_ptr(), next_update.as_ptr()))?;
+
+            for revocation in revoked {
+                let entry = X509Revoked::from_ptr(cvt_p(ffi::X509_REVOKED_new())?);
+                cvt(ffi::X509_REVOKED_set_serialNumber(
+                    entry.as_ptr(),
+                    revocation.serial.as_ptr(),
+                ))?;
+                cvt(ffi::X509_REVOKED_set_revocationDate(
+                    entry.as_ptr(),
+                    revocation.date.as_ptr(),
+                ))?;
+                if let Some(reason) = revocation.reason {
+                    let reason = reason_code(reason)?;
+                    cvt(ffi::X509_REVOKED_add_ext(
+                        entry.as_ptr(),
+                        reason.as_ptr(),
+                        -1,
+                    ))?;
+                }
+                cvt(ffi::X509_CRL_add0_revoked(crl.as_ptr(), entry.as_ptr()))?;
+                mem::forget(entry);
+            }
+            cvt(ffi::X509_CRL_sort(crl.as_ptr()))?;
+
+            let mut ctx = mem::zeroed();
+            ffi::X509V3_set_ctx(
+                &mut ctx,
+                self.cert.as_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                crl.as_ptr(),
+                0,
+            );
+            let ctx = X509v3Context(ctx, PhantomData);
+            let authority_key_identifier =
+                AuthorityKeyIdentifier::new().keyid(false).build(&ctx)?;
+            cvt(ffi::X509_CRL_add_ext(
+                crl.as_ptr(),
+                authority_key_identifier.as_ptr(),
+                -1,
+            ))?;
+            cvt(ffi::X509_CRL_add1_ext_i2d(
+                crl.as_ptr(),
+                ffi::NID_crl_number,
+                number.as_ptr().cast(),
+                0,
+                0,
+            ))?;
+
+            cvt(ffi::X509_CRL_sign(
+                crl.as_ptr(),
+                self.key.as_ptr(),
+                self.digest().as_ptr(),
+            ))?;
+
+            Ok(crl)
+        }
+    }
+}
+
+fn reason_code(reason: CrlReason) -> Result<X509Extension, ErrorStack> {
+    // an ENUMERATED of a single octet, as all reason codes are below 128
+    let der = [0x0a, 0x01, reason.as_raw() as u8];
+    let oid = Asn1Object::from_str(CRL_REASON_OID)?;
+    let der = Asn1OctetString::new_from_bytes(&der)?;
+    X509Extension::new_from_der(&oid, false, &der)
+}
+
+cfg_if! {
+    if #[cfg(any(ossl110, libressl270, boringssl))] {
+        use ffi::{X509_CRL_set1_lastUpdate, X509_CRL_set1_nextUpdate};
+    } else {
+        use ffi::{
+            X509_CRL_set_lastUpdate as X509_CRL_set1_lastUpdate,
+            X509_CRL_set_nextUpdate as X509_CRL_set1_nextUpdate,
+        };
+    }
+}
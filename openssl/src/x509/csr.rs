@@ -0,0 +1,186 @@
+//! Certificate signing request profiles.
+//!
+//! Enrollment protocols such as ACME ([RFC 8555]) and EST ([RFC 7030]) expect a PKCS#10
+//! certificate request carrying the requested identities as extensions. [`CsrProfile`] collects
+//! the subject, subject alternative names, key usages, and an optional challenge password, and
+//! produces a signed request from them.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::ec::{EcGroup, EcKey};
+//! use openssl::nid::Nid;
+//! use openssl::pkey::PKey;
+//! use openssl::x509::csr::CsrProfile;
+//! use openssl::x509::extension::{ExtendedKeyUsage, KeyUsage, SubjectAlternativeName};
+//!
+//! let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+//! let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+//!
+//! let mut san = SubjectAlternativeName::new();
+//! san.dns("example.com").dns("www.example.com");
+//! let mut key_usage = KeyUsage::new();
+//! key_usage.critical().digital_signature();
+//! let mut extended_key_usage = ExtendedKeyUsage::new();
+//! extended_key_usage.server_auth();
+//!
+//! let req = CsrProfile::new()
+//!     .subject_alt_name(san)
+//!     .key_usage(key_usage)
+//!     .extended_key_usage(extended_key_usage)
+//!     .build(&key)
+//!     .unwrap();
+//! assert!(req.verify(&key).unwrap());
+//! ```
+//!
+//! [RFC 8555]: https://tools.ietf.org/html/rfc8555#section-7.4
+//! [RFC 7030]: https://tools.ietf.org/html/rfc7030#section-4.2
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::pkey::{HasPrivate, HasPublic, Id, PKeyRef};
+use crate::stack::Stack;
+use crate::x509::extension::{ExtendedKeyUsage, KeyUsage, SubjectAlternativeName};
+use crate::x509::{X509Name, X509Req, X509ReqBuilder};
+
+/// A description of a certificate request.
+///
+/// The request is built with version 1, an empty subject unless one is set, the challenge
+/// password attribute if one is set, and an extension request attribute containing the subject
+/// alternative names, key usage, and extended key usage, in that order.
+///
+/// As required by [RFC 5280], the subject alternative name extension is marked critical when the
+/// subject is empty.
+///
+/// [RFC 5280]: https://tools.ietf.org/html/rfc5280#section-4.2.1.6
+pub struct CsrProfile {
+    subject: Option<X509Name>,
+    subject_alt_name: Option<SubjectAlternativeName>,
+    key_usage: Option<KeyUsage>,
+    extended_key_usage: Option<ExtendedKeyUsage>,
+    challenge_password: Option<String>,
+    digest: Option<MessageDigest>,
+}
+
+impl Default for CsrProfile {
+    fn default() -> CsrProfile {
+        CsrProfile::new()
+    }
+}
+
+impl CsrProfile {
+    /// Creates a new, empty `CsrProfile`.
+    pub fn new() -> CsrProfile {
+        CsrProfile {
+            subject: None,
+            subject_alt_name: None,
+            key_usage: None,
+            extended_key_usage: None,
+            challenge_password: None,
+            digest: None,
+        }
+    }
+
+    /// Sets the subject name of the request.
+    ///
+    /// ACME ignores the subject, so it is usually left empty there.
+    pub fn subject(&mut self, subject: X509Name) -> &mut CsrProfile {
+        self.subject = Some(subject);
+        self
+    }
+
+    /// Sets the subject alternative names of the request.
+    pub fn subject_alt_name(
+        &mut self,
+        subject_alt_name: SubjectAlternativeName,
+    ) -> &mut CsrProfile {
+        self.subject_alt_name = Some(subject_alt_name);
+        self
+    }
+
+    /// Sets the key usage of the request.
+    pub fn key_usage(&mut self, key_usage: KeyUsage) -> &mut CsrProfile {
+        self.key_usage = Some(key_usage);
+        self
+    }
+
+    /// Sets the extended key usage of the request.
+    pub fn extended_key_usage(&mut self, extended_key_usage: ExtendedKeyUsage) -> &mut CsrProfile {
+        self.extended_key_usage = Some(extended_key_usage);
+        self
+    }
+
+    /// Sets the challenge password of the request.
+    ///
+    /// EST servers may use this to carry the TLS channel binding or a one-time enrollment secret.
+    pub fn challenge_password(&mut self, password: &str) -> &mut CsrProfile {
+        self.challenge_password = Some(password.to_string());
+        self
+    }
+
+    /// Sets the digest used to sign the request.
+    ///
+    /// By default, the digest is chosen based on the key: no digest for Ed25519 and Ed448, SHA-384
+    /// and SHA-512 for elliptic curve keys larger than 256 and 384 bits respectively, and SHA-256
+    /// otherwise.
+    pub fn digest(&mut self, digest: MessageDigest) -> &mut CsrProfile {
+        self.digest = Some(digest);
+        self
+    }
+
+    /// Builds a certificate request for `key`, signed by `key`.
+    pub fn build<T>(&self, key: &PKeyRef<T>) -> Result<X509Req, ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        let mut builder = X509ReqBuilder::new()?;
+        builder.set_version(0)?;
+
+        let empty_subject = match &self.subject {
+            Some(subject) => {
+                builder.set_subject_name(subject)?;
+                subject.entries().next().is_none()
+            }
+            None => true,
+        };
+        builder.set_pubkey(key)?;
+
+        if let Some(password) = &self.challenge_password {
+            builder.set_challenge_password(password)?;
+        }
+
+        let mut extensions = Stack::new()?;
+        if let Some(subject_alt_name) = &self.subject_alt_name {
+            extensions.push(subject_alt_name.build_internal(empty_subject)?)?;
+        }
+        if let Some(key_usage) = &self.key_usage {
+            extensions.push(key_usage.build()?)?;
+        }
+        if let Some(extended_key_usage) = &self.extended_key_usage {
+            extensions.push(extended_key_usage.build()?)?;
+        }
+        if !extensions.is_empty() {
+            builder.add_extensions(&extensions)?;
+        }
+
+        let digest = match self.digest {
+            Some(digest) => digest,
+            None => default_digest(key),
+        };
+        builder.sign(key, digest)?;
+
+        Ok(builder.build())
+    }
+}
+
+pub(crate) fn default_digest<T>(key: &PKeyRef<T>) -> MessageDigest
+where
+    T: HasPublic,
+{
+    match key.id() {
+        #[cfg(ossl111)]
+        Id::ED25519 | Id::ED448 => MessageDigest::null(),
+        Id::EC if key.bits() > 384 => MessageDigest::sha512(),
+        Id::EC if key.bits() > 256 => MessageDigest::sha384(),
+        _ => MessageDigest::sha256(),
+    }
+}
@@ -0,0 +1,178 @@
+//! Helpers for encoding and decoding the small DER structures not covered by OpenSSL's templates.
+use cfg_if::cfg_if;
+use foreign_types::{ForeignType, ForeignTypeRef};
+use libc::{c_int, c_long};
+use std::convert::TryInto;
+use std::ptr;
+
+use crate::asn1::{Asn1Object, Asn1ObjectRef};
+use crate::error::ErrorStack;
+use crate::{cvt, cvt_n, cvt_p};
+
+pub(crate) fn object_to_der(obj: &Asn1ObjectRef) -> Result<Vec<u8>, ErrorStack> {
+    unsafe {
+        let len = cvt(ffi::i2d_ASN1_OBJECT(obj.as_ptr(), ptr::null_mut()))?;
+        let mut buf = vec![0; len as usize];
+        cvt(ffi::i2d_ASN1_OBJECT(obj.as_ptr(), &mut buf.as_mut_ptr()))?;
+        Ok(buf)
+    }
+}
+
+/// Appends a universal class TLV to `out`.
+pub(crate) fn write_tlv(
+    out: &mut Vec<u8>,
+    constructed: bool,
+    tag: c_int,
+    contents: &[u8],
+) -> Result<(), ErrorStack> {
+    put(out, constructed, tag, ffi::V_ASN1_UNIVERSAL, contents)
+}
+
+/// Appends an explicitly tagged, context-specific TLV to `out`.
+pub(crate) fn write_explicit(
+    out: &mut Vec<u8>,
+    tag: c_int,
+    contents: &[u8],
+) -> Result<(), ErrorStack> {
+    put(out, true, tag, ffi::V_ASN1_CONTEXT_SPECIFIC, contents)
+}
+
+fn put(
+    out: &mut Vec<u8>,
+    constructed: bool,
+    tag: c_int,
+    class: c_int,
+    contents: &[u8],
+) -> Result<(), ErrorStack> {
+    let constructed = constructed as c_int;
+    let len = contents.len().try_into().unwrap();
+    unsafe {
+        let size = cvt_n(ffi::ASN1_object_size(constructed, len, tag))? as usize;
+        let start = out.len();
+        out.resize(start + size, 0);
+
+        let mut p = out[start..].as_mut_ptr();
+        ffi::ASN1_put_object(&mut p, constructed, len, tag, class);
+        let header = p as usize - out[start..].as_ptr() as usize;
+        out[start + header..].copy_from_slice(contents);
+    }
+    Ok(())
+}
+
+/// Reads a universal class TLV from the front of `der`, returning its contents.
+pub(crate) fn read_tlv<'a>(
+    der: &mut &'a [u8],
+    constructed: bool,
+    tag: c_int,
+) -> Result<&'a [u8], ErrorStack> {
+    let tlv = get(der)?;
+    if tlv.constructed != constructed || tlv.tag != tag || tlv.class != ffi::V_ASN1_UNIVERSAL {
+        return Err(wrong_tag());
+    }
+    *der = tlv.rest;
+    Ok(tlv.contents)
+}
+
+/// Reads a universal class TLV from the front of `der`, returning its complete encoding.
+pub(crate) fn read_element<'a>(
+    der: &mut &'a [u8],
+    constructed: bool,
+    tag: c_int,
+) -> Result<&'a [u8], ErrorStack> {
+    let start = *der;
+    read_tlv(der, constructed, tag)?;
+    Ok(&start[..start.len() - der.len()])
+}
+
+/// Reads an explicitly tagged, context-specific TLV from the front of `der`, if the next element
+/// has that tag.
+pub(crate) fn read_explicit<'a>(
+    der: &mut &'a [u8],
+    tag: c_int,
+) -> Result<Option<&'a [u8]>, ErrorStack> {
+    if der.is_empty() {
+        return Ok(None);
+    }
+    let tlv = get(der)?;
+    if !tlv.constructed || tlv.tag != tag || tlv.class != ffi::V_ASN1_CONTEXT_SPECIFIC {
+        return Ok(None);
+    }
+    *der = tlv.rest;
+    Ok(Some(tlv.contents))
+}
+
+struct Tlv<'a> {
+    constructed: bool,
+    tag: c_int,
+    class: c_int,
+    contents: &'a [u8],
+    rest: &'a [u8],
+}
+
+fn get(der: &[u8]) -> Result<Tlv<'_>, ErrorStack> {
+    let max = der.len().try_into().unwrap_or(c_long::MAX);
+    unsafe {
+        let mut p = der.as_ptr();
+        let mut len = 0;
+        let mut tag = 0;
+        let mut class = 0;
+        let ret = ffi::ASN1_get_object(&mut p, &mut len, &mut tag, &mut class, max);
+        if ret & 0x80 != 0 {
+            return Err(ErrorStack::report());
+        }
+        // the low bit indicates an indefinite length, which is not permitted in DER
+        if ret & 0x01 != 0 {
+            return Err(wrong_tag());
+        }
+
+        let header = p as usize - der.as_ptr() as usize;
+        let (contents, rest) = der[header..].split_at(len as usize);
+        Ok(Tlv {
+            constructed: ret & ffi::V_ASN1_CONSTRUCTED != 0,
+            tag,
+            class,
+            contents,
+            rest,
+        })
+    }
+}
+
+pub(crate) fn read_object(der: &mut &[u8]) -> Result<Asn1Object, ErrorStack> {
+    let max = der.len().try_into().unwrap_or(c_long::MAX);
+    unsafe {
+        let mut p = der.as_ptr();
+        let obj = cvt_p(ffi::d2i_ASN1_OBJECT(ptr::null_mut(), &mut p, max))?;
+        let obj = Asn1Object::from_ptr(obj);
+        *der = &der[p as usize - der.as_ptr() as usize..];
+        Ok(obj)
+    }
+}
+
+pub(crate) fn expect_end(der: &[u8]) -> Result<(), ErrorStack> {
+    if der.is_empty() {
+        Ok(())
+    } else {
+        Err(wrong_tag())
+    }
+}
+
+pub(crate) fn wrong_tag() -> ErrorStack {
+    unsafe {
+        cfg_if! {
+            if #[cfg(ossl300)] {
+                ffi::ERR_new();
+                ffi::ERR_set_debug(concat!(file!(), "\0").as_ptr() as *const _, line!() as _, ptr::null());
+                ffi::ERR_set_error(ffi::ERR_LIB_ASN1, ffi::ASN1_R_WRONG_TAG, ptr::null());
+            } else {
+                ffi::ERR_put_error(
+                    ffi::ERR_LIB_ASN1,
+                    0,
+                    ffi::ASN1_R_WRONG_TAG,
+                    concat!(file!(), "\0").as_ptr() as *const _,
+                    line!() as _,
+                );
+            }
+        }
+    }
+    ErrorStack::report()
+}
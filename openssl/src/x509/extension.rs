@@ -21,6 +21,7 @@ use std::fmt::Write;
 use crate::asn1::Asn1Object;
 use crate::error::ErrorStack;
 use crate::nid::Nid;
+use crate::x509::other_name::{HardwareModuleName, PermanentIdentifier};
 use crate::x509::{GeneralName, Stack, X509Extension, X509v3Context};
 use foreign_types::ForeignType;
 
@@ -435,6 +436,8 @@ enum RustGeneralName {
     Ip(String),
     Rid(String),
     OtherName(Asn1Object, Vec<u8>),
+    HardwareModuleName(HardwareModuleName),
+    PermanentIdentifier(PermanentIdentifier),
 }
 
 /// An extension that allows additional identities to be bound to the subject
@@ -524,8 +527,33 @@ impl SubjectAlternativeName {
         self
     }
 
+    /// Sets the `otherName` flag to an `id-on-hardwareModuleName`, identifying a hardware module
+    /// by its type and serial number.
+    pub fn hardware_module_name(
+        &mut self,
+        name: &HardwareModuleName,
+    ) -> &mut SubjectAlternativeName {
+        self.items
+            .push(RustGeneralName::HardwareModuleName(name.clone()));
+        self
+    }
+
+    /// Sets the `otherName` flag to an `id-on-permanentIdentifier`.
+    pub fn permanent_identifier(
+        &mut self,
+        id: &PermanentIdentifier,
+    ) -> &mut SubjectAlternativeName {
+        self.items
+            .push(RustGeneralName::PermanentIdentifier(id.clone()));
+        self
+    }
+
     /// Return a `SubjectAlternativeName` extension as an `X509Extension`.
     pub fn build(&self, _ctx: &X509v3Context<'_>) -> Result<X509Extension, ErrorStack> {
+        self.build_internal(false)
+    }
+
+    pub(crate) fn build_internal(&self, force_critical: bool) -> Result<X509Extension, ErrorStack> {
         let mut stack = Stack::new()?;
         for item in &self.items {
             let gn = match item {
@@ -533,18 +561,28 @@ impl SubjectAlternativeName {
                 RustGeneralName::Email(s) => GeneralName::new_email(s.as_bytes())?,
                 RustGeneralName::Uri(s) => GeneralName::new_uri(s.as_bytes())?,
                 RustGeneralName::Ip(s) => {
-                    GeneralName::new_ip(s.parse().map_err(|_| ErrorStack::get())?)?
+                    GeneralName::new_ip(s.parse().map_err(|_| ErrorStack::report())?)?
                 }
                 RustGeneralName::Rid(s) => GeneralName::new_rid(Asn1Object::from_str(s)?)?,
                 RustGeneralName::OtherName(oid, content) => {
                     GeneralName::new_other_name(oid.clone(), content)?
                 }
+                RustGeneralName::HardwareModuleName(name) => {
+                    GeneralName::new_other_name(HardwareModuleName::type_id()?, &name.to_der()?)?
+                }
+                RustGeneralName::PermanentIdentifier(id) => {
+                    GeneralName::new_other_name(PermanentIdentifier::type_id()?, &id.to_der()?)?
+                }
             };
             stack.push(gn)?;
         }
 
         unsafe {
-            X509Extension::new_internal(Nid::SUBJECT_ALT_NAME, self.critical, stack.as_ptr().cast())
+            X509Extension::new_internal(
+                Nid::SUBJECT_ALT_NAME,
+                self.critical || force_critical,
+                stack.as_ptr().cast(),
+            )
         }
     }
 }
@@ -0,0 +1,191 @@
+//! Hashed certificate directories.
+//!
+//! The [`HashDir`] lookup method loads certificates and CRLs on demand from directories in which
+//! each file is named after the hash of the certificate's subject or the CRL's issuer, such as
+//! `9d66eef0.0` for a certificate and `9d66eef0.r0` for a CRL. These directories are
+//! traditionally maintained with the `c_rehash` script or `openssl rehash`. [`HashDirBuilder`]
+//! writes them directly from a set of certificates and CRLs, and [`rehash`] does the same for
+//! the certificates and CRLs already in a directory.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use openssl::x509::hash_dir::HashDirBuilder;
+//! use openssl::x509::X509;
+//!
+//! let pem = std::fs::read("ca-bundle.pem").unwrap();
+//! let mut builder = HashDirBuilder::new();
+//! for cert in X509::stack_from_pem(&pem).unwrap() {
+//!     builder.add_cert(cert);
+//! }
+//! builder.write("/etc/ssl/certs").unwrap();
+//! ```
+//!
+//! [`HashDir`]: crate::x509::store::HashDir
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::error::ErrorStack;
+use crate::hash::{hash, MessageDigest};
+use crate::x509::{X509Crl, X509CrlRef, X509Ref, X509};
+
+/// Returns the file name of `cert` in a hashed certificate directory.
+///
+/// `index` distinguishes certificates whose subjects have the same hash, and starts at 0.
+pub fn cert_file_name(cert: &X509Ref, index: u32) -> String {
+    format!("{:08x}.{}", cert.subject_name_hash(), index)
+}
+
+/// Returns the file name of `crl` in a hashed certificate directory.
+///
+/// `index` distinguishes CRLs whose issuers have the same hash, and starts at 0.
+pub fn crl_file_name(crl: &X509CrlRef, index: u32) -> String {
+    format!("{:08x}.r{}", crl.issuer_name_hash(), index)
+}
+
+/// A builder for hashed certificate directories.
+///
+/// Certificates and CRLs are written as PEM files, in the order they were added. Duplicates are
+/// skipped.
+#[derive(Default)]
+pub struct HashDirBuilder {
+    certs: Vec<X509>,
+    crls: Vec<X509Crl>,
+}
+
+impl HashDirBuilder {
+    /// Creates an empty builder.
+    pub fn new() -> HashDirBuilder {
+        HashDirBuilder::default()
+    }
+
+    /// Adds a certificate to the directory.
+    pub fn add_cert(&mut self, cert: X509) -> &mut HashDirBuilder {
+        self.certs.push(cert);
+        self
+    }
+
+    /// Adds a CRL to the directory.
+    pub fn add_crl(&mut self, crl: X509Crl) -> &mut HashDirBuilder {
+        self.crls.push(crl);
+        self
+    }
+
+    /// Writes the certificates and CRLs to `dir`, creating it if necessary.
+    ///
+    /// Files in `dir` with hashed names which are not part of the new contents are removed, and
+    /// all other files are left alone. Each file is written to a temporary file first and then
+    /// renamed into place, so that processes using the directory never see partially written
+    /// files.
+    pub fn write<P>(&self, dir: P) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        let entries = self.entries()?;
+        for (name, pem) in &entries {
+            let tmp = dir.join(format!(".{}.tmp", name));
+            fs::write(&tmp, pem)?;
+            fs::rename(&tmp, dir.join(name))?;
+        }
+
+        let names = entries
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<HashSet<_>>();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = match name.to_str() {
+                Some(name) => name,
+                None => continue,
+            };
+            if is_hashed_name(name) && !names.contains(name) {
+                fs::remove_file(entry.path())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn entries(&self) -> Result<Vec<(String, Vec<u8>)>, ErrorStack> {
+        let mut entries = vec![];
+        let mut seen = HashSet::new();
+        let mut indices = HashMap::new();
+
+        for cert in &self.certs {
+            if !seen.insert(cert.digest(MessageDigest::sha256())?.to_vec()) {
+                continue;
+            }
+            let index = indices
+                .entry((cert.subject_name_hash(), false))
+                .or_insert(0);
+            entries.push((cert_file_name(cert, *index), cert.to_pem()?));
+            *index += 1;
+        }
+
+        for crl in &self.crls {
+            if !seen.insert(hash(MessageDigest::sha256(), &crl.to_der()?)?.to_vec()) {
+                continue;
+            }
+            let index = indices.entry((crl.issuer_name_hash(), true)).or_insert(0);
+            entries.push((crl_file_name(crl, *index), crl.to_pem()?));
+            *index += 1;
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Rebuilds the hashed file names of a directory.
+///
+/// Like `c_rehash`, the certificates and CRLs in files in `dir` with a `.pem`, `.crt`, `.cer`,
+/// or `.crl` extension are collected, and written to files with hashed names by
+/// [`HashDirBuilder::write`]. Files which contain neither certificates nor CRLs are ignored.
+pub fn rehash<P>(dir: P) -> io::Result<()>
+where
+    P: AsRef<Path>,
+{
+    let dir = dir.as_ref();
+    let mut paths = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let extension = path.extension().and_then(|e| e.to_str());
+        if let Some("pem") | Some("crt") | Some("cer") | Some("crl") = extension {
+            paths.push(path);
+        }
+    }
+    // keep the indices stable across runs
+    paths.sort();
+
+    let mut builder = HashDirBuilder::new();
+    for path in paths {
+        let pem = fs::read(&path)?;
+        if let Ok(certs) = X509::stack_from_pem(&pem) {
+            for cert in certs {
+                builder.add_cert(cert);
+            }
+        }
+        if let Ok(crl) = X509Crl::from_pem(&pem) {
+            builder.add_crl(crl);
+        }
+    }
+
+    builder.write(dir)
+}
+
+fn is_hashed_name(name: &str) -> bool {
+    let (hash, index) = match name.split_once('.') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    let index = index.strip_prefix('r').unwrap_or(index);
+    hash.len() == 8
+        && hash.bytes().all(|b| b.is_ascii_hexdigit())
+        && !index.is_empty()
+        && index.bytes().all(|b| b.is_ascii_digit())
+}
@@ -0,0 +1,358 @@
+//! Certificate linting against the CA/Browser Forum Baseline Requirements.
+//!
+//! [`lint`] runs a set of checks against a certificate and returns a [`Finding`] for each problem it detects. The
+//! checks cover a subset of the [Baseline Requirements] for publicly trusted TLS certificates: validity period,
+//! subject key type and size, subject alternative names, serial number, key usage, and extended key usage.
+//!
+//! Certificates which are not marked as CAs by their basic constraints extension are checked as subscriber
+//! certificates.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::x509::lint::{self, Lint};
+//! use openssl::x509::X509;
+//!
+//! let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+//! let findings = lint::lint(&cert).unwrap();
+//! assert!(findings.iter().any(|f| f.lint() == Lint::MISSING_SUBJECT_ALT_NAME));
+//! ```
+//!
+//! [Baseline Requirements]: https://cabforum.org/baseline-requirements-documents/
+use std::fmt;
+use std::net::IpAddr;
+
+use crate::error::ErrorStack;
+use crate::nid::Nid;
+use crate::pkey::Id;
+use crate::x509::{ExtendedKeyUsageFlags, KeyUsageFlags, X509Ref};
+
+const MAX_SUBSCRIBER_VALIDITY_DAYS: i32 = 398;
+const MIN_RSA_BITS: u32 = 2048;
+const MIN_SERIAL_BITS: i32 = 64;
+const MAX_SERIAL_OCTETS: i32 = 20;
+
+/// The severity of a [`Finding`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// A deviation from a recommendation of the Baseline Requirements.
+    Warning,
+    /// A violation of a requirement of the Baseline Requirements.
+    Error,
+}
+
+/// An identifier for a check performed by [`lint`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Lint(&'static str);
+
+impl Lint {
+    /// The certificate is not an X.509 v3 certificate.
+    pub const NOT_V3: Lint = Lint("not_v3");
+    /// The certificate's validity period ends before it begins.
+    pub const INVALID_VALIDITY: Lint = Lint("invalid_validity");
+    /// The validity period of a subscriber certificate exceeds 398 days.
+    pub const VALIDITY_TOO_LONG: Lint = Lint("validity_too_long");
+    /// The subject key is neither an RSA nor an ECDSA key.
+    pub const UNSUPPORTED_KEY_TYPE: Lint = Lint("unsupported_key_type");
+    /// The RSA modulus is shorter than 2048 bits or not a multiple of 8 bits.
+    pub const RSA_KEY_SIZE: Lint = Lint("rsa_key_size");
+    /// The RSA public exponent is even, or smaller than 65537.
+    pub const RSA_EXPONENT: Lint = Lint("rsa_exponent");
+    /// The ECDSA key is not on the P-256, P-384 or P-521 curve.
+    pub const EC_CURVE: Lint = Lint("ec_curve");
+    /// The serial number is not positive, or is longer than 20 octets.
+    pub const INVALID_SERIAL: Lint = Lint("invalid_serial");
+    /// The serial number is shorter than 64 bits, so cannot contain the required entropy.
+    pub const LOW_SERIAL_ENTROPY: Lint = Lint("low_serial_entropy");
+    /// A subscriber certificate has no subject alternative names.
+    pub const MISSING_SUBJECT_ALT_NAME: Lint = Lint("missing_subject_alt_name");
+    /// The subject common name does not appear in the subject alternative names.
+    pub const COMMON_NAME_NOT_IN_SAN: Lint = Lint("common_name_not_in_san");
+    /// A CA certificate does not permit certificate signing.
+    pub const CA_KEY_USAGE: Lint = Lint("ca_key_usage");
+    /// A subscriber certificate permits certificate or CRL signing.
+    pub const SUBSCRIBER_KEY_USAGE: Lint = Lint("subscriber_key_usage");
+    /// A subscriber certificate has no extended key usage extension.
+    pub const MISSING_EXTENDED_KEY_USAGE: Lint = Lint("missing_extended_key_usage");
+    /// A subscriber certificate's extended key usages are not limited to server and client authentication.
+    pub const EXTENDED_KEY_USAGE: Lint = Lint("extended_key_usage");
+
+    /// Returns the name of the check, for example `validity_too_long`.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn name(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(self.0)
+    }
+}
+
+/// A problem detected by [`lint`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    lint: Lint,
+    severity: Severity,
+    description: String,
+}
+
+impl Finding {
+    /// Returns the check which produced the finding.
+    pub fn lint(&self) -> Lint {
+        self.lint
+    }
+
+    /// Returns the severity of the finding.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns a human readable description of the problem.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(fmt, "{}: {}: {}", severity, self.lint, self.description)
+    }
+}
+
+/// Checks a certificate against the Baseline Requirements, returning the problems found.
+///
+/// An empty result means no check failed; it does not mean the certificate complies with every requirement.
+pub fn lint(cert: &X509Ref) -> Result<Vec<Finding>, ErrorStack> {
+    let mut findings = Findings(vec![]);
+    let subscriber = !cert.is_ca();
+
+    if cert.version() != 2 {
+        findings.error(Lint::NOT_V3, "certificate is not version 3");
+    }
+
+    check_validity(cert, subscriber, &mut findings)?;
+    check_key(cert, &mut findings)?;
+    check_serial(cert, &mut findings)?;
+    if subscriber {
+        check_names(cert, &mut findings)?;
+    }
+    check_key_usage(cert, subscriber, &mut findings);
+    if subscriber {
+        check_extended_key_usage(cert, &mut findings);
+    }
+
+    Ok(findings.0)
+}
+
+struct Findings(Vec<Finding>);
+
+impl Findings {
+    fn push<S>(&mut self, lint: Lint, severity: Severity, description: S)
+    where
+        S: Into<String>,
+    {
+        self.0.push(Finding {
+            lint,
+            severity,
+            description: description.into(),
+        });
+    }
+
+    fn error<S>(&mut self, lint: Lint, description: S)
+    where
+        S: Into<String>,
+    {
+        self.push(lint, Severity::Error, description);
+    }
+
+    fn warning<S>(&mut self, lint: Lint, description: S)
+    where
+        S: Into<String>,
+    {
+        self.push(lint, Severity::Warning, description);
+    }
+}
+
+fn check_validity(
+    cert: &X509Ref,
+    subscriber: bool,
+    findings: &mut Findings,
+) -> Result<(), ErrorStack> {
+    let diff = cert.not_before().diff(cert.not_after())?;
+    if diff.days < 0 || diff.secs < 0 {
+        findings.error(Lint::INVALID_VALIDITY, "notAfter is earlier than notBefore");
+    } else if subscriber
+        && (diff.days > MAX_SUBSCRIBER_VALIDITY_DAYS
+            || diff.days == MAX_SUBSCRIBER_VALIDITY_DAYS && diff.secs > 0)
+    {
+        findings.error(
+            Lint::VALIDITY_TOO_LONG,
+            format!(
+                "validity period of {} days exceeds {} days",
+                diff.days, MAX_SUBSCRIBER_VALIDITY_DAYS
+            ),
+        );
+    }
+    Ok(())
+}
+
+fn check_key(cert: &X509Ref, findings: &mut Findings) -> Result<(), ErrorStack> {
+    let key = cert.public_key()?;
+    match key.id() {
+        Id::RSA => {
+            let rsa = key.rsa()?;
+            let bits = rsa.n().num_bits() as u32;
+            if bits < MIN_RSA_BITS || bits % 8 != 0 {
+                findings.error(Lint::RSA_KEY_SIZE, format!("RSA modulus is {} bits", bits));
+            }
+            let e = rsa.e();
+            if !e.is_bit_set(0) {
+                findings.error(Lint::RSA_EXPONENT, "RSA public exponent is even");
+            } else if e.num_bits() < 17 {
+                findings.warning(
+                    Lint::RSA_EXPONENT,
+                    format!("RSA public exponent {} is smaller than 65537", e),
+                );
+            }
+        }
+        Id::EC => {
+            let curve = key.ec_key()?.group().curve_name();
+            let allowed = [Nid::X9_62_PRIME256V1, Nid::SECP384R1, Nid::SECP521R1];
+            if !curve.map_or(false, |curve| allowed.contains(&curve)) {
+                let name = curve
+                    .and_then(|curve| curve.short_name().ok())
+                    .unwrap_or("explicit parameters");
+                findings.error(
+                    Lint::EC_CURVE,
+                    format!("ECDSA key uses unsupported curve {}", name),
+                );
+            }
+        }
+        id => findings.error(
+            Lint::UNSUPPORTED_KEY_TYPE,
+            format!("unsupported subject key type {}", id.as_raw()),
+        ),
+    }
+    Ok(())
+}
+
+fn check_serial(cert: &X509Ref, findings: &mut Findings) -> Result<(), ErrorStack> {
+    let serial = cert.serial_number().to_bn()?;
+    let bits = serial.num_bits();
+    if serial.is_negative() || bits == 0 {
+        findings.error(Lint::INVALID_SERIAL, "serial number is not positive");
+        return Ok(());
+    }
+
+    // DER integers require a leading zero octet when the high bit is set
+    let octets = bits / 8 + 1;
+    if octets > MAX_SERIAL_OCTETS {
+        findings.error(
+            Lint::INVALID_SERIAL,
+            format!("serial number is {} octets long", octets),
+        );
+    }
+    if bits < MIN_SERIAL_BITS {
+        findings.error(
+            Lint::LOW_SERIAL_ENTROPY,
+            format!("serial number is only {} bits long", bits),
+        );
+    }
+    Ok(())
+}
+
+fn check_names(cert: &X509Ref, findings: &mut Findings) -> Result<(), ErrorStack> {
+    let names = match cert.subject_alt_names() {
+        Some(names) if !names.is_empty() => names,
+        _ => {
+            findings.error(
+                Lint::MISSING_SUBJECT_ALT_NAME,
+                "certificate has no subject alternative names",
+            );
+            return Ok(());
+        }
+    };
+
+    for entry in cert.subject_name().entries_by_nid(Nid::COMMONNAME) {
+        let cn = entry.data().as_utf8()?;
+        let cn: &str = &cn;
+        let found = match cn.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => names.iter().any(|n| n.ipaddress() == Some(&ip.octets())),
+            Ok(IpAddr::V6(ip)) => names.iter().any(|n| n.ipaddress() == Some(&ip.octets())),
+            Err(_) => names
+                .iter()
+                .filter_map(|n| n.dnsname())
+                .any(|dns| dns.eq_ignore_ascii_case(cn)),
+        };
+        if !found {
+            findings.error(
+                Lint::COMMON_NAME_NOT_IN_SAN,
+                format!("common name `{}` is not a subject alternative name", cn),
+            );
+        }
+    }
+    Ok(())
+}
+
+fn check_key_usage(cert: &X509Ref, subscriber: bool, findings: &mut Findings) {
+    let key_usage = cert.key_usage();
+    if subscriber {
+        let signing = KeyUsageFlags::KEY_CERT_SIGN | KeyUsageFlags::CRL_SIGN;
+        if key_usage.map_or(false, |ku| ku.intersects(signing)) {
+            findings.error(
+                Lint::SUBSCRIBER_KEY_USAGE,
+                "subscriber certificate permits certificate or CRL signing",
+            );
+        }
+    } else if !key_usage.map_or(false, |ku| ku.contains(KeyUsageFlags::KEY_CERT_SIGN)) {
+        findings.error(
+            Lint::CA_KEY_USAGE,
+            "CA certificate does not permit certificate signing",
+        );
+    }
+}
+
+fn check_extended_key_usage(cert: &X509Ref, findings: &mut Findings) {
+    let eku = match cert.extended_key_usage() {
+        Some(eku) => eku,
+        None => {
+            findings.error(
+                Lint::MISSING_EXTENDED_KEY_USAGE,
+                "subscriber certificate has no extended key usage extension",
+            );
+            return;
+        }
+    };
+
+    if eku.contains(ExtendedKeyUsageFlags::ANY) {
+        findings.error(
+            Lint::EXTENDED_KEY_USAGE,
+            "subscriber certificate permits any extended key usage",
+        );
+    }
+    if !eku.contains(ExtendedKeyUsageFlags::SERVER_AUTH) {
+        findings.error(
+            Lint::EXTENDED_KEY_USAGE,
+            "subscriber certificate does not permit server authentication",
+        );
+    }
+    let other = eku
+        - ExtendedKeyUsageFlags::SERVER_AUTH
+        - ExtendedKeyUsageFlags::CLIENT_AUTH
+        - ExtendedKeyUsageFlags::ANY;
+    if !other.is_empty() {
+        findings.error(
+            Lint::EXTENDED_KEY_USAGE,
+            format!(
+                "subscriber certificate permits other extended key usages ({:?})",
+                other
+            ),
+        );
+    }
+}
@@ -7,9 +7,10 @@
 //! Internet protocols, including SSL/TLS, which is the basis for HTTPS,
 //! the secure protocol for browsing the web.
 
+use bitflags::bitflags;
 use cfg_if::cfg_if;
 use foreign_types::{ForeignType, ForeignTypeRef, Opaque};
-use libc::{c_int, c_long, c_uint, c_void};
+use libc::{c_int, c_long, c_uint, c_void, time_t};
 use std::cmp::{self, Ordering};
 use std::convert::{TryFrom, TryInto};
 use std::error::Error;
@@ -23,33 +24,87 @@ use std::ptr;
 use std::slice;
 use std::str;
 
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+use crate::asn1::Asn1OctetString;
 use crate::asn1::{
     Asn1BitStringRef, Asn1Enumerated, Asn1IntegerRef, Asn1Object, Asn1ObjectRef,
     Asn1OctetStringRef, Asn1StringRef, Asn1TimeRef, Asn1Type,
 };
 use crate::bio::MemBioSlice;
 use crate::conf::ConfRef;
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+use crate::ct::Sct;
 use crate::error::ErrorStack;
 use crate::ex_data::Index;
 use crate::hash::{DigestBytes, MessageDigest};
 use crate::nid::Nid;
 use crate::pkey::{HasPrivate, HasPublic, PKey, PKeyRef, Public};
+#[cfg(ossl110)]
+use crate::rsa::Padding;
+#[cfg(ossl110)]
+use crate::sign::{RsaPssSaltlen, Signer};
 use crate::ssl::SslRef;
 use crate::stack::{Stack, StackRef, Stackable};
 use crate::string::OpensslString;
 use crate::util::{ForeignTypeExt, ForeignTypeRefExt};
+use crate::x509::other_name::{HardwareModuleName, PermanentIdentifier};
+use crate::x509::serial::{SerialAllocator, SerialError};
+#[cfg(any(ossl102, libressl261))]
+use crate::x509::verify::X509VerifyParamRef;
 use crate::{cvt, cvt_n, cvt_p};
 use openssl_macros::corresponds;
 
 #[cfg(any(ossl102, libressl261))]
 pub mod verify;
 
+pub mod bundle;
+pub mod ca;
+pub mod csr;
+pub(crate) mod der;
 pub mod extension;
+pub mod hash_dir;
+#[cfg(ossl110)]
+pub mod lint;
+pub mod other_name;
+pub mod self_signed;
+pub mod serial;
 pub mod store;
 
 #[cfg(test)]
 mod tests;
 
+bitflags! {
+    /// The key usages permitted by a certificate's key usage extension.
+    pub struct KeyUsageFlags: u32 {
+        const DIGITAL_SIGNATURE = ffi::X509v3_KU_DIGITAL_SIGNATURE;
+        const NON_REPUDIATION = ffi::X509v3_KU_NON_REPUDIATION;
+        const KEY_ENCIPHERMENT = ffi::X509v3_KU_KEY_ENCIPHERMENT;
+        const DATA_ENCIPHERMENT = ffi::X509v3_KU_DATA_ENCIPHERMENT;
+        const KEY_AGREEMENT = ffi::X509v3_KU_KEY_AGREEMENT;
+        const KEY_CERT_SIGN = ffi::X509v3_KU_KEY_CERT_SIGN;
+        const CRL_SIGN = ffi::X509v3_KU_CRL_SIGN;
+        const ENCIPHER_ONLY = ffi::X509v3_KU_ENCIPHER_ONLY;
+        const DECIPHER_ONLY = ffi::X509v3_KU_DECIPHER_ONLY;
+    }
+}
+
+bitflags! {
+    /// The purposes permitted by a certificate's extended key usage extension.
+    pub struct ExtendedKeyUsageFlags: u32 {
+        const SERVER_AUTH = ffi::XKU_SSL_SERVER;
+        const CLIENT_AUTH = ffi::XKU_SSL_CLIENT;
+        const EMAIL_PROTECTION = ffi::XKU_SMIME;
+        const CODE_SIGNING = ffi::XKU_CODE_SIGN;
+        const NS_SGC = ffi::XKU_SGC;
+        const OCSP_SIGNING = ffi::XKU_OCSP_SIGN;
+        const TIME_STAMPING = ffi::XKU_TIMESTAMP;
+        const DVCS = ffi::XKU_DVCS;
+        /// Requires OpenSSL 1.1.0 or newer.
+        #[cfg(ossl110)]
+        const ANY = ffi::XKU_ANYEKU;
+    }
+}
+
 /// A type of X509 extension.
 ///
 /// # Safety
@@ -87,6 +142,31 @@ impl X509StoreContext {
             cvt_p(ffi::X509_STORE_CTX_new()).map(X509StoreContext)
         }
     }
+
+    /// Verifies a certificate as of a specific time rather than the current time.
+    ///
+    /// This answers whether the chain was valid at, for example, the time a signature was made. Any time set in
+    /// the store's verification parameters is overridden.
+    ///
+    /// * `cert` - The certificate that should be verified.
+    /// * `cert_chain` - Untrusted certificates which may be used to build the chain.
+    /// * `trust` - The certificate store with the trusted certificates.
+    /// * `time` - The time to verify at, in seconds since the Unix epoch.
+    ///
+    /// Returns [`X509VerifyResult::OK`] if the certificate was valid at `time`.
+    pub fn verify_at(
+        cert: &X509Ref,
+        cert_chain: &StackRef<X509>,
+        trust: &store::X509StoreRef,
+        time: time_t,
+    ) -> Result<X509VerifyResult, ErrorStack> {
+        let mut context = X509StoreContext::new()?;
+        context.init(trust, cert, cert_chain, |c| {
+            c.set_time(time);
+            c.verify_cert()?;
+            Ok(c.error())
+        })
+    }
 }
 
 impl X509StoreContextRef {
@@ -168,6 +248,27 @@ impl X509StoreContextRef {
         unsafe { cvt_n(ffi::X509_verify_cert(self.as_ptr())).map(|n| n != 0) }
     }
 
+    /// Sets the time used to check the validity periods of certificates and CRLs, in seconds since the Unix
+    /// epoch.
+    ///
+    /// This will only work inside of a call to `init`.
+    #[corresponds(X509_STORE_CTX_set_time)]
+    pub fn set_time(&mut self, time: time_t) {
+        unsafe {
+            ffi::X509_STORE_CTX_set_time(self.as_ptr(), 0, time);
+        }
+    }
+
+    /// Returns a mutable reference to the verification parameters of the context.
+    ///
+    /// The parameters are copied from the certificate store by `init`, so changes will only take effect inside
+    /// of a call to `init`.
+    #[corresponds(X509_STORE_CTX_get0_param)]
+    #[cfg(any(ossl102, libressl261))]
+    pub fn param_mut(&mut self) -> &mut X509VerifyParamRef {
+        unsafe { X509VerifyParamRef::from_ptr_mut(ffi::X509_STORE_CTX_get0_param(self.as_ptr())) }
+    }
+
     /// Set the error code of the context.
     #[corresponds(X509_STORE_CTX_set_error)]
     pub fn set_error(&mut self, result: X509VerifyResult) {
@@ -257,6 +358,16 @@ impl X509Builder {
         }
     }
 
+    /// Sets the serial number of the certificate to one allocated from `allocator`.
+    pub fn set_serial_number_from(
+        &mut self,
+        allocator: &mut SerialAllocator,
+    ) -> Result<(), SerialError> {
+        let serial_number = allocator.allocate()?;
+        self.set_serial_number(&serial_number)?;
+        Ok(())
+    }
+
     /// Sets the issuer name of the certificate.
     #[corresponds(X509_set_issuer_name)]
     pub fn set_issuer_name(&mut self, issuer_name: &X509NameRef) -> Result<(), ErrorStack> {
@@ -359,6 +470,9 @@ impl X509Builder {
     }
 
     /// Signs the certificate with a private key.
+    ///
+    /// Keys which sign messages directly, such as Ed25519 and Ed448 keys, must be used with
+    /// [`MessageDigest::null`]. [`PKeyRef::default_digest`] returns a suitable digest for a key.
     #[corresponds(X509_sign)]
     pub fn sign<T>(&mut self, key: &PKeyRef<T>, hash: MessageDigest) -> Result<(), ErrorStack>
     where
@@ -367,6 +481,92 @@ impl X509Builder {
         unsafe { cvt(ffi::X509_sign(self.0.as_ptr(), key.as_ptr(), hash.as_ptr())).map(|_| ()) }
     }
 
+    /// Signs the certificate with an RSA private key using RSASSA-PSS.
+    ///
+    /// `digest` hashes the certificate, `mgf1_digest` is used by the mask generation function, and
+    /// `salt_len` is the length of the salt in bytes. The signature algorithm is encoded as by
+    /// [`X509Algorithm::from_rsa_pss_parameters`].
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(ossl110)]
+    pub fn sign_pss<T>(
+        &mut self,
+        key: &PKeyRef<T>,
+        digest: MessageDigest,
+        mgf1_digest: MessageDigest,
+        salt_len: u32,
+    ) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        let params = RsaPssParameters::new(digest, mgf1_digest, salt_len);
+        let alg = X509Algorithm::from_rsa_pss_parameters(&params)?;
+        self.set_signature_algorithm(&alg)?;
+        let signature = rsa_pss_signature(key, &self.tbs_der()?, digest, mgf1_digest, salt_len)?;
+        self.set_signature(&signature)
+    }
+
+    /// Sets the signature algorithm of the certificate, without signing it.
+    ///
+    /// This is the first step of signing with a key that is only available through an external
+    /// signer, such as a hardware security module. The algorithm is recorded both inside and
+    /// outside the to-be-signed portion of the certificate, so it must be set before calling
+    /// [`tbs_der`].
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// [`tbs_der`]: X509Builder::tbs_der
+    #[corresponds(X509_ALGOR_set0)]
+    #[cfg(ossl110)]
+    pub fn set_signature_algorithm(&mut self, alg: &X509AlgorithmRef) -> Result<(), ErrorStack> {
+        unsafe {
+            let tbs_alg = ffi::X509_get0_tbs_sigalg(self.0.as_ptr());
+            copy_algorithm(tbs_alg as *mut _, alg)?;
+
+            let mut sig_alg = ptr::null();
+            ffi::X509_get0_signature(ptr::null_mut(), &mut sig_alg, self.0.as_ptr());
+            copy_algorithm(sig_alg as *mut _, alg)
+        }
+    }
+
+    /// Returns the DER encoding of the to-be-signed portion of the certificate.
+    ///
+    /// These are the bytes an external signer has to sign with the algorithm set by
+    /// [`set_signature_algorithm`]. The resulting signature is attached with [`set_signature`].
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// [`set_signature_algorithm`]: X509Builder::set_signature_algorithm
+    /// [`set_signature`]: X509Builder::set_signature
+    #[corresponds(i2d_re_X509_tbs)]
+    #[cfg(ossl110)]
+    pub fn tbs_der(&self) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let len = cvt(ffi::i2d_re_X509_tbs(self.0.as_ptr(), ptr::null_mut()))?;
+            let mut buf = vec![0; len as usize];
+            cvt(ffi::i2d_re_X509_tbs(self.0.as_ptr(), &mut buf.as_mut_ptr()))?;
+            Ok(buf)
+        }
+    }
+
+    /// Sets the signature of the certificate to one produced by an external signer.
+    ///
+    /// The signature must be over the output of [`tbs_der`], and any change to the certificate
+    /// after that invalidates it.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// [`tbs_der`]: X509Builder::tbs_der
+    #[corresponds(X509_get0_signature)]
+    #[cfg(ossl110)]
+    pub fn set_signature(&mut self, signature: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            let mut sig = ptr::null();
+            ffi::X509_get0_signature(&mut sig, ptr::null_mut(), self.0.as_ptr());
+            copy_signature(sig as *mut _, signature)
+        }
+    }
+
     /// Consumes the builder, returning the certificate.
     pub fn build(self) -> X509 {
         self.0
@@ -439,6 +639,23 @@ impl X509Ref {
         }
     }
 
+    /// Returns the signed certificate timestamps embedded in this certificate, if any.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(X509_get_ext_d2i)]
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+    pub fn scts(&self) -> Option<Stack<Sct>> {
+        unsafe {
+            let stack = ffi::X509_get_ext_d2i(
+                self.as_ptr(),
+                ffi::NID_ct_precert_scts,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            Stack::from_ptr_opt(stack as *mut _)
+        }
+    }
+
     /// Returns this certificate's CRL distribution points, if they exist.
     #[corresponds(X509_get_ext_d2i)]
     pub fn crl_distribution_points(&self) -> Option<Stack<DistPoint>> {
@@ -491,6 +708,43 @@ impl X509Ref {
         u32::try_from(v).ok()
     }
 
+    /// Returns whether the certificate's basic constraints mark it as a CA.
+    #[corresponds(X509_get_extension_flags)]
+    #[cfg(ossl110)]
+    pub fn is_ca(&self) -> bool {
+        unsafe { ffi::X509_get_extension_flags(self.as_ptr()) & ffi::EXFLAG_CA != 0 }
+    }
+
+    /// Returns the usages permitted by the key usage extension, if it exists.
+    #[corresponds(X509_get_key_usage)]
+    #[cfg(ossl110)]
+    pub fn key_usage(&self) -> Option<KeyUsageFlags> {
+        unsafe {
+            if ffi::X509_get_extension_flags(self.as_ptr()) & ffi::EXFLAG_KUSAGE == 0 {
+                return None;
+            }
+            Some(KeyUsageFlags::from_bits_truncate(ffi::X509_get_key_usage(
+                self.as_ptr(),
+            )))
+        }
+    }
+
+    /// Returns the purposes permitted by the extended key usage extension, if it exists.
+    ///
+    /// Purposes without a corresponding flag are ignored.
+    #[corresponds(X509_get_extended_key_usage)]
+    #[cfg(ossl110)]
+    pub fn extended_key_usage(&self) -> Option<ExtendedKeyUsageFlags> {
+        unsafe {
+            if ffi::X509_get_extension_flags(self.as_ptr()) & ffi::EXFLAG_XKUSAGE == 0 {
+                return None;
+            }
+            Some(ExtendedKeyUsageFlags::from_bits_truncate(
+                ffi::X509_get_extended_key_usage(self.as_ptr()),
+            ))
+        }
+    }
+
     /// Returns this certificate's subject key id, if it exists.
     #[corresponds(X509_get0_subject_key_id)]
     #[cfg(ossl110)]
@@ -645,6 +899,19 @@ impl X509Ref {
         unsafe { cvt_n(ffi::X509_verify(self.as_ptr(), key.as_ptr())).map(|n| n != 0) }
     }
 
+    /// Checks that `key` is the private key corresponding to the certificate's public key.
+    ///
+    /// On a mismatch, the error stack identifies whether the key is of the wrong type
+    /// (`X509_R_KEY_TYPE_MISMATCH`) or a different key of the same type
+    /// (`X509_R_KEY_VALUES_MISMATCH`).
+    #[corresponds(X509_check_private_key)]
+    pub fn matches_private_key<T>(&self, key: &PKeyRef<T>) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        unsafe { cvt(ffi::X509_check_private_key(self.as_ptr(), key.as_ptr())).map(|_| ()) }
+    }
+
     /// Returns this certificate's serial number.
     #[corresponds(X509_get_serialNumber)]
     pub fn serial_number(&self) -> &Asn1IntegerRef {
@@ -730,6 +997,22 @@ impl X509 {
         X509Builder::new()
     }
 
+    /// Creates a self-signed certificate for `subject`, signed by `key`.
+    ///
+    /// The certificate has a random serial number, and basic constraints, key usage, extended key
+    /// usage, subject alternative name, and subject and authority key identifier extensions, as
+    /// configured by `options`.
+    pub fn self_signed<T>(
+        subject: &X509NameRef,
+        key: &PKeyRef<T>,
+        options: &self_signed::SelfSignedOptions,
+    ) -> Result<X509, ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        self_signed::self_signed(subject, key, options)
+    }
+
     from_pem! {
         /// Deserializes a PEM-encoded X509 structure.
         ///
@@ -768,7 +1051,7 @@ impl X509 {
                         break;
                     }
 
-                    return Err(ErrorStack::get());
+                    return Err(ErrorStack::report());
                 } else {
                     certs.push(X509(r));
                 }
@@ -1008,6 +1291,27 @@ impl X509Extension {
         }
     }
 
+    /// Constructs an extension embedding a list of signed certificate timestamps.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(X509V3_EXT_i2d)]
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+    pub fn new_sct_list(critical: bool, scts: &StackRef<Sct>) -> Result<X509Extension, ErrorStack> {
+        unsafe { X509Extension::new_internal(Nid::CT_PRECERT_SCTS, critical, scts.as_ptr().cast()) }
+    }
+
+    /// Constructs the critical precertificate poison extension, which marks a certificate as a
+    /// precertificate to be submitted to Certificate Transparency logs.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+    pub fn new_ct_precert_poison() -> Result<X509Extension, ErrorStack> {
+        let oid = Asn1Object::from_str("1.3.6.1.4.1.11129.2.4.3")?;
+        // the extension value is an ASN.1 NULL
+        let value = Asn1OctetString::new_from_bytes(&[0x05, 0x00])?;
+        X509Extension::new_from_der(&oid, true, &value)
+    }
+
     pub(crate) unsafe fn new_internal(
         nid: Nid,
         critical: bool,
@@ -1040,6 +1344,13 @@ impl X509ExtensionRef {
         to_der,
         ffi::i2d_X509_EXTENSION
     }
+
+    pub(crate) fn nid(&self) -> Nid {
+        unsafe {
+            let object = ffi::X509_EXTENSION_get_object(self.as_ptr());
+            Nid::from_raw(ffi::OBJ_obj2nid(object))
+        }
+    }
 }
 
 /// A builder used to construct an `X509Name`.
@@ -1198,6 +1509,76 @@ impl X509Name {
         unsafe { cvt_p(ffi::SSL_load_client_CA_file(file.as_ptr())).map(|p| Stack::from_ptr(p)) }
     }
 
+    /// Loads subject names from a directory of PEM-formatted certificates.
+    ///
+    /// Every file in the directory is read, and duplicate names are only included once. This is
+    /// commonly used in conjunction with `SslContextBuilder::set_client_ca_list`.
+    #[corresponds(SSL_add_dir_cert_subjects_to_stack)]
+    pub fn load_client_ca_dir<P: AsRef<Path>>(dir: P) -> Result<Stack<X509Name>, ErrorStack> {
+        let dir = CString::new(dir.as_ref().as_os_str().to_str().unwrap()).unwrap();
+        let list = Stack::new()?;
+        unsafe {
+            cvt(ffi::SSL_add_dir_cert_subjects_to_stack(
+                list.as_ptr(),
+                dir.as_ptr(),
+            ))?;
+        }
+        Ok(list)
+    }
+
+    /// Loads the subject names of a list of PEM-formatted certificates.
+    ///
+    /// This is the in-memory equivalent of [`load_client_ca_file`], and likewise includes
+    /// duplicate names only once.
+    ///
+    /// Requires OpenSSL 1.1.0, LibreSSL 2.7.0, or BoringSSL.
+    ///
+    /// [`load_client_ca_file`]: X509Name::load_client_ca_file
+    #[cfg(any(boringssl, ossl110, libressl270))]
+    pub fn load_client_ca_pem(pem: &[u8]) -> Result<Stack<X509Name>, ErrorStack> {
+        let mut list = Stack::<X509Name>::new()?;
+        for cert in X509::stack_from_pem(pem)? {
+            let name = cert.subject_name();
+            let mut duplicate = false;
+            for existing in &list {
+                if existing.try_cmp(name)? == Ordering::Equal {
+                    duplicate = true;
+                    break;
+                }
+            }
+            if !duplicate {
+                list.push(name.to_owned()?)?;
+            }
+        }
+        Ok(list)
+    }
+
+    /// Serializes a list of names to DER, as a `SEQUENCE OF Name`.
+    pub fn stack_to_der(list: &StackRef<X509Name>) -> Result<Vec<u8>, ErrorStack> {
+        let mut contents = vec![];
+        for name in list {
+            contents.extend_from_slice(&name.to_der()?);
+        }
+
+        let mut der = vec![];
+        der::write_tlv(&mut der, true, ffi::V_ASN1_SEQUENCE, &contents)?;
+        Ok(der)
+    }
+
+    /// Deserializes a list of names from the DER encoding of a `SEQUENCE OF Name`.
+    pub fn stack_from_der(der: &[u8]) -> Result<Stack<X509Name>, ErrorStack> {
+        let mut der = der;
+        let mut contents = der::read_tlv(&mut der, true, ffi::V_ASN1_SEQUENCE)?;
+        der::expect_end(der)?;
+
+        let mut list = Stack::new()?;
+        while !contents.is_empty() {
+            let name = der::read_element(&mut contents, true, ffi::V_ASN1_SEQUENCE)?;
+            list.push(X509Name::from_der(name)?)?;
+        }
+        Ok(list)
+    }
+
     from_der! {
         /// Deserializes a DER-encoded X509 name structure.
         ///
@@ -1243,7 +1624,7 @@ impl X509NameRef {
     pub fn try_cmp(&self, other: &X509NameRef) -> Result<Ordering, ErrorStack> {
         let cmp = unsafe { ffi::X509_NAME_cmp(self.as_ptr(), other.as_ptr()) };
         if cfg!(ossl300) && cmp == -2 {
-            return Err(ErrorStack::get());
+            return Err(ErrorStack::report());
         }
         Ok(cmp.cmp(&0))
     }
@@ -1255,6 +1636,18 @@ impl X509NameRef {
         unsafe { cvt_p(ffi::X509_NAME_dup(self.as_ptr())).map(|n| X509Name::from_ptr(n)) }
     }
 
+    /// Returns the hash of the name, as used in the file names of hashed certificate directories.
+    ///
+    /// This is the value returned by [`X509Ref::subject_name_hash`] for a certificate with this
+    /// subject.
+    #[corresponds(X509_NAME_hash)]
+    pub fn name_hash(&self) -> u32 {
+        #[allow(clippy::unnecessary_cast)]
+        unsafe {
+            ffi::X509_NAME_hash(self.as_ptr()) as u32
+        }
+    }
+
     to_der! {
         /// Serializes the certificate into a DER-encoded X509 name structure.
         ///
@@ -1449,8 +1842,38 @@ impl X509ReqBuilder {
         }
     }
 
+    /// Adds a PKCS#9 `challengePassword` attribute to the request.
+    ///
+    /// The password is encoded as a `PrintableString` where possible, and as a `UTF8String`
+    /// otherwise.
+    #[corresponds(X509_REQ_add1_attr_by_NID)]
+    pub fn set_challenge_password(&mut self, password: &str) -> Result<(), ErrorStack> {
+        let printable = password
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b" '()+,-./:=?".contains(&b));
+        let type_ = if printable {
+            ffi::V_ASN1_PRINTABLESTRING
+        } else {
+            ffi::V_ASN1_UTF8STRING
+        };
+
+        unsafe {
+            cvt(ffi::X509_REQ_add1_attr_by_NID(
+                self.0.as_ptr(),
+                ffi::NID_pkcs9_challengePassword,
+                type_,
+                password.as_ptr(),
+                password.len().try_into().unwrap(),
+            ))
+            .map(|_| ())
+        }
+    }
+
     /// Sign the request using a private key.
     ///
+    /// As with [`X509Builder::sign`], Ed25519 and Ed448 keys must be used with
+    /// [`MessageDigest::null`].
+    ///
     /// This corresponds to [`X509_REQ_sign`].
     ///
     /// [`X509_REQ_sign`]: https://www.openssl.org/docs/manmaster/crypto/X509_REQ_sign.html
@@ -1468,6 +1891,79 @@ impl X509ReqBuilder {
         }
     }
 
+    /// Signs the request with an RSA private key using RSASSA-PSS.
+    ///
+    /// The parameters are the same as for [`X509Builder::sign_pss`].
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[cfg(ossl110)]
+    pub fn sign_pss<T>(
+        &mut self,
+        key: &PKeyRef<T>,
+        digest: MessageDigest,
+        mgf1_digest: MessageDigest,
+        salt_len: u32,
+    ) -> Result<(), ErrorStack>
+    where
+        T: HasPrivate,
+    {
+        let params = RsaPssParameters::new(digest, mgf1_digest, salt_len);
+        let alg = X509Algorithm::from_rsa_pss_parameters(&params)?;
+        self.set_signature_algorithm(&alg)?;
+        let signature = rsa_pss_signature(key, &self.tbs_der()?, digest, mgf1_digest, salt_len)?;
+        self.set_signature(&signature)
+    }
+
+    /// Sets the signature algorithm of the request, without signing it.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(X509_ALGOR_set0)]
+    #[cfg(ossl110)]
+    pub fn set_signature_algorithm(&mut self, alg: &X509AlgorithmRef) -> Result<(), ErrorStack> {
+        unsafe {
+            let mut sig_alg = ptr::null();
+            ffi::X509_REQ_get0_signature(self.0.as_ptr(), ptr::null_mut(), &mut sig_alg);
+            copy_algorithm(sig_alg as *mut _, alg)
+        }
+    }
+
+    /// Returns the DER encoding of the to-be-signed portion of the request.
+    ///
+    /// Unlike certificates, the signature algorithm of a request is not part of the signed data,
+    /// so it may be set before or after calling this.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(i2d_re_X509_REQ_tbs)]
+    #[cfg(ossl110)]
+    pub fn tbs_der(&self) -> Result<Vec<u8>, ErrorStack> {
+        unsafe {
+            let len = cvt(ffi::i2d_re_X509_REQ_tbs(self.0.as_ptr(), ptr::null_mut()))?;
+            let mut buf = vec![0; len as usize];
+            cvt(ffi::i2d_re_X509_REQ_tbs(
+                self.0.as_ptr(),
+                &mut buf.as_mut_ptr(),
+            ))?;
+            Ok(buf)
+        }
+    }
+
+    /// Sets the signature of the request to one produced by an external signer.
+    ///
+    /// The signature must be over the output of [`tbs_der`].
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// [`tbs_der`]: X509ReqBuilder::tbs_der
+    #[corresponds(X509_REQ_get0_signature)]
+    #[cfg(ossl110)]
+    pub fn set_signature(&mut self, signature: &[u8]) -> Result<(), ErrorStack> {
+        unsafe {
+            let mut sig = ptr::null();
+            ffi::X509_REQ_get0_signature(self.0.as_ptr(), &mut sig, ptr::null_mut());
+            copy_signature(sig as *mut _, signature)
+        }
+    }
+
     /// Returns the `X509Req`.
     pub fn build(self) -> X509Req {
         self.0
@@ -1567,6 +2063,20 @@ impl X509ReqRef {
         }
     }
 
+    /// Returns the signature algorithm of the certificate request.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(X509_REQ_get0_signature)]
+    #[cfg(ossl110)]
+    pub fn signature_algorithm(&self) -> &X509AlgorithmRef {
+        unsafe {
+            let mut algor = ptr::null();
+            ffi::X509_REQ_get0_signature(self.as_ptr(), ptr::null_mut(), &mut algor);
+            X509AlgorithmRef::from_const_ptr_opt(algor)
+                .expect("signature algorithm must not be null")
+        }
+    }
+
     /// Returns the public key of the certificate request.
     ///
     /// This corresponds to [`X509_REQ_get_pubkey"]
@@ -1602,6 +2112,30 @@ impl X509ReqRef {
             Ok(Stack::from_ptr(extensions))
         }
     }
+
+    /// Returns the PKCS#9 `challengePassword` attribute of the certificate request, if any.
+    #[corresponds(X509_REQ_get_attr_by_NID)]
+    pub fn challenge_password(&self) -> Option<&str> {
+        unsafe {
+            let loc =
+                ffi::X509_REQ_get_attr_by_NID(self.as_ptr(), ffi::NID_pkcs9_challengePassword, -1);
+            if loc < 0 {
+                return None;
+            }
+            let attr = ffi::X509_REQ_get_attr(self.as_ptr(), loc);
+            let value = ffi::X509_ATTRIBUTE_get0_type(attr, 0);
+            if value.is_null() {
+                return None;
+            }
+            match (*value).type_ {
+                ffi::V_ASN1_PRINTABLESTRING | ffi::V_ASN1_UTF8STRING | ffi::V_ASN1_IA5STRING => {
+                    str::from_utf8(Asn1StringRef::from_ptr((*value).value.asn1_string).as_slice())
+                        .ok()
+                }
+                _ => None,
+            }
+        }
+    }
 }
 
 /// The reason that a certificate was revoked.
@@ -1716,7 +2250,7 @@ impl X509RevokedRef {
             (-1 | -2, _) => Ok(None),
             // A critical value of 0 or 1 suggests success, but a null pointer
             // was returned so something went wrong.
-            (0 | 1, None) => Err(ErrorStack::get()),
+            (0 | 1, None) => Err(ErrorStack::report()),
             (c_int::MIN..=-2 | 2.., _) => panic!("OpenSSL should only return -2, -1, 0, or 1 for an extension's criticality but it returned {}", critical),
         }
     }
@@ -1756,6 +2290,25 @@ foreign_type_and_impl_send_sync! {
     pub struct X509CrlRef;
 }
 
+#[cfg(any(ossl110, libressl270))]
+impl ToOwned for X509CrlRef {
+    type Owned = X509Crl;
+
+    fn to_owned(&self) -> X509Crl {
+        unsafe {
+            ffi::X509_CRL_up_ref(self.as_ptr());
+            X509Crl::from_ptr(self.as_ptr())
+        }
+    }
+}
+
+#[cfg(any(ossl110, libressl270))]
+impl Clone for X509Crl {
+    fn clone(&self) -> X509Crl {
+        X509CrlRef::to_owned(self)
+    }
+}
+
 /// The status of a certificate in a revoction list
 ///
 /// Corresponds to the return value from the [`X509_CRL_get0_by_*`] methods.
@@ -1902,6 +2455,28 @@ impl X509CrlRef {
         }
     }
 
+    /// Returns the hash of the CRL's issuer name.
+    ///
+    /// This is the hash used in the file name of the CRL in hashed certificate directories.
+    #[corresponds(X509_NAME_hash)]
+    pub fn issuer_name_hash(&self) -> u32 {
+        self.issuer_name().name_hash()
+    }
+
+    /// Returns the signature algorithm of the CRL.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(X509_CRL_get0_signature)]
+    #[cfg(ossl110)]
+    pub fn signature_algorithm(&self) -> &X509AlgorithmRef {
+        unsafe {
+            let mut algor = ptr::null();
+            ffi::X509_CRL_get0_signature(self.as_ptr(), ptr::null_mut(), &mut algor);
+            X509AlgorithmRef::from_const_ptr_opt(algor)
+                .expect("signature algorithm must not be null")
+        }
+    }
+
     /// Check if the CRL is signed using the given public key.
     ///
     /// Only the signature is checked: no other checks (such as certificate chain validity)
@@ -2158,6 +2733,44 @@ impl GeneralNameRef {
             Some(slice::from_raw_parts(ptr as *const u8, len as usize))
         }
     }
+
+    /// Returns the type-id and DER-encoded value of this `GeneralName` if it is an `otherName`.
+    #[corresponds(GENERAL_NAME_get0_otherName)]
+    pub fn other_name(&self) -> Option<(&Asn1ObjectRef, Vec<u8>)> {
+        unsafe {
+            let mut oid = ptr::null_mut();
+            let mut value = ptr::null_mut();
+            if ffi::GENERAL_NAME_get0_otherName(self.as_ptr(), &mut oid, &mut value) != 1 {
+                return None;
+            }
+
+            let len = cvt(ffi::i2d_ASN1_TYPE(value, ptr::null_mut())).ok()?;
+            let mut buf = vec![0; len as usize];
+            cvt(ffi::i2d_ASN1_TYPE(value, &mut buf.as_mut_ptr())).ok()?;
+
+            Some((Asn1ObjectRef::from_ptr(oid), buf))
+        }
+    }
+
+    /// Returns the contents of this `GeneralName` if it is an `otherName` containing an
+    /// `id-on-hardwareModuleName`.
+    pub fn hardware_module_name(&self) -> Option<HardwareModuleName> {
+        let (oid, value) = self.other_name()?;
+        if !other_name::is_type_id(oid, HardwareModuleName::type_id()) {
+            return None;
+        }
+        HardwareModuleName::from_der(&value).ok()
+    }
+
+    /// Returns the contents of this `GeneralName` if it is an `otherName` containing an
+    /// `id-on-permanentIdentifier`.
+    pub fn permanent_identifier(&self) -> Option<PermanentIdentifier> {
+        let (oid, value) = self.other_name()?;
+        if !other_name::is_type_id(oid, PermanentIdentifier::type_id()) {
+            return None;
+        }
+        PermanentIdentifier::from_der(&value).ok()
+    }
 }
 
 impl fmt::Debug for GeneralNameRef {
@@ -2265,7 +2878,89 @@ foreign_type_and_impl_send_sync! {
     pub struct X509AlgorithmRef;
 }
 
+impl X509Algorithm {
+    from_der! {
+        /// Deserializes a DER-encoded `AlgorithmIdentifier`.
+        #[corresponds(d2i_X509_ALGOR)]
+        from_der,
+        X509Algorithm,
+        ffi::d2i_X509_ALGOR
+    }
+
+    /// Creates an `AlgorithmIdentifier` from an OID and its DER-encoded parameters.
+    ///
+    /// If `parameters` is `None`, the parameters are absent, as for ECDSA and EdDSA. RSA
+    /// signature algorithms instead use an explicit `NULL`, which is encoded as `&[0x05, 0x00]`.
+    pub fn new(
+        oid: &Asn1ObjectRef,
+        parameters: Option<&[u8]>,
+    ) -> Result<X509Algorithm, ErrorStack> {
+        let mut contents = der::object_to_der(oid)?;
+        if let Some(parameters) = parameters {
+            contents.extend_from_slice(parameters);
+        }
+        let mut buf = vec![];
+        der::write_tlv(&mut buf, true, ffi::V_ASN1_SEQUENCE, &contents)?;
+        X509Algorithm::from_der(&buf)
+    }
+
+    /// Creates an `RSASSA-PSS` `AlgorithmIdentifier`, as defined in [RFC 4055].
+    ///
+    /// `digest` is used both to hash the message and as the MGF1 hash function. The SHA-256,
+    /// SHA-384, and SHA-512 identifiers with a salt as long as the digest match the encodings
+    /// required by the CA/Browser Forum Baseline Requirements.
+    ///
+    /// [RFC 4055]: https://tools.ietf.org/html/rfc4055#section-3.1
+    pub fn rsa_pss(digest: MessageDigest, salt_len: u32) -> Result<X509Algorithm, ErrorStack> {
+        X509Algorithm::from_rsa_pss_parameters(&RsaPssParameters::new(digest, digest, salt_len))
+    }
+
+    /// Creates an `RSASSA-PSS` `AlgorithmIdentifier` from its parameters.
+    ///
+    /// The trailer field is 1, fields with their default values are omitted, and the hash
+    /// functions carry `NULL` parameters.
+    pub fn from_rsa_pss_parameters(
+        parameters: &RsaPssParameters,
+    ) -> Result<X509Algorithm, ErrorStack> {
+        let mut params = vec![];
+        if parameters.digest != Nid::SHA1 {
+            let hash = hash_algorithm_der(parameters.digest)?;
+            der::write_explicit(&mut params, 0, &hash)?;
+        }
+        if parameters.mgf1_digest != Nid::SHA1 {
+            let mut mgf = der::object_to_der(nid_to_object(Nid::MGF1)?)?;
+            mgf.extend_from_slice(&hash_algorithm_der(parameters.mgf1_digest)?);
+            let mut mgf_der = vec![];
+            der::write_tlv(&mut mgf_der, true, ffi::V_ASN1_SEQUENCE, &mgf)?;
+            der::write_explicit(&mut params, 1, &mgf_der)?;
+        }
+        if parameters.salt_len != 20 {
+            let mut salt = parameters.salt_len.to_be_bytes().to_vec();
+            while salt.len() > 1 && salt[0] == 0 {
+                salt.remove(0);
+            }
+            if salt[0] & 0x80 != 0 {
+                salt.insert(0, 0);
+            }
+            let mut salt_der = vec![];
+            der::write_tlv(&mut salt_der, false, ffi::V_ASN1_INTEGER, &salt)?;
+            der::write_explicit(&mut params, 2, &salt_der)?;
+        }
+
+        let mut params_der = vec![];
+        der::write_tlv(&mut params_der, true, ffi::V_ASN1_SEQUENCE, &params)?;
+        X509Algorithm::new(nid_to_object(Nid::RSASSAPSS)?, Some(&params_der))
+    }
+}
+
 impl X509AlgorithmRef {
+    to_der! {
+        /// Serializes the algorithm to a DER-encoded `AlgorithmIdentifier`.
+        #[corresponds(i2d_X509_ALGOR)]
+        to_der,
+        ffi::i2d_X509_ALGOR
+    }
+
     /// Returns the ASN.1 OID of this algorithm.
     pub fn object(&self) -> &Asn1ObjectRef {
         unsafe {
@@ -2274,6 +2969,248 @@ impl X509AlgorithmRef {
             Asn1ObjectRef::from_const_ptr_opt(oid).expect("algorithm oid must not be null")
         }
     }
+
+    /// Returns the DER encoding of the algorithm's parameters, or `None` if they are absent.
+    pub fn parameters(&self) -> Result<Option<Vec<u8>>, ErrorStack> {
+        let buf = self.to_der()?;
+        let mut buf = &buf[..];
+        let mut contents = der::read_tlv(&mut buf, true, ffi::V_ASN1_SEQUENCE)?;
+        der::read_object(&mut contents)?;
+        if contents.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(contents.to_vec()))
+        }
+    }
+
+    /// Returns the parameters of an `RSASSA-PSS` algorithm, or `None` for other algorithms.
+    ///
+    /// Only the MGF1 mask generation function and a trailer field of 1 are supported.
+    pub fn rsa_pss_parameters(&self) -> Result<Option<RsaPssParameters>, ErrorStack> {
+        if self.object().nid() != Nid::RSASSAPSS {
+            return Ok(None);
+        }
+        let params = self.parameters()?.unwrap_or_default();
+        let mut params = &params[..];
+        let mut contents = der::read_tlv(&mut params, true, ffi::V_ASN1_SEQUENCE)?;
+        der::expect_end(params)?;
+
+        let digest = match der::read_explicit(&mut contents, 0)? {
+            Some(mut hash) => {
+                let nid = read_hash_algorithm(&mut hash)?;
+                der::expect_end(hash)?;
+                nid
+            }
+            None => Nid::SHA1,
+        };
+        let mgf1_digest = match der::read_explicit(&mut contents, 1)? {
+            Some(mut mgf) => {
+                let mut mgf_contents = der::read_tlv(&mut mgf, true, ffi::V_ASN1_SEQUENCE)?;
+                der::expect_end(mgf)?;
+                if der::read_object(&mut mgf_contents)?.nid() != Nid::MGF1 {
+                    return Err(der::wrong_tag());
+                }
+                let nid = read_hash_algorithm(&mut mgf_contents)?;
+                der::expect_end(mgf_contents)?;
+                nid
+            }
+            None => Nid::SHA1,
+        };
+        let salt_len = match der::read_explicit(&mut contents, 2)? {
+            Some(mut salt) => {
+                let value = read_small_integer(&mut salt)?;
+                der::expect_end(salt)?;
+                value
+            }
+            None => 20,
+        };
+        if let Some(mut trailer) = der::read_explicit(&mut contents, 3)? {
+            if read_small_integer(&mut trailer)? != 1 {
+                return Err(der::wrong_tag());
+            }
+            der::expect_end(trailer)?;
+        }
+        der::expect_end(contents)?;
+
+        Ok(Some(RsaPssParameters {
+            digest,
+            mgf1_digest,
+            salt_len,
+        }))
+    }
+}
+
+/// The parameters of an `RSASSA-PSS` signature algorithm.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RsaPssParameters {
+    digest: Nid,
+    mgf1_digest: Nid,
+    salt_len: u32,
+}
+
+impl RsaPssParameters {
+    /// Creates `RSASSA-PSS` parameters from the message digest, the MGF1 digest, and the length of
+    /// the salt in bytes.
+    pub fn new(
+        digest: MessageDigest,
+        mgf1_digest: MessageDigest,
+        salt_len: u32,
+    ) -> RsaPssParameters {
+        RsaPssParameters {
+            digest: digest.type_(),
+            mgf1_digest: mgf1_digest.type_(),
+            salt_len,
+        }
+    }
+
+    /// Returns the hash function used to hash the message.
+    pub fn digest(&self) -> Nid {
+        self.digest
+    }
+
+    /// Returns the hash function used by the MGF1 mask generation function.
+    pub fn mgf1_digest(&self) -> Nid {
+        self.mgf1_digest
+    }
+
+    /// Returns the length of the salt, in bytes.
+    pub fn salt_len(&self) -> u32 {
+        self.salt_len
+    }
+}
+
+fn nid_to_object(nid: Nid) -> Result<&'static Asn1ObjectRef, ErrorStack> {
+    unsafe { cvt_p(ffi::OBJ_nid2obj(nid.as_raw())).map(|p| Asn1ObjectRef::from_ptr(p)) }
+}
+
+// Encodes the AlgorithmIdentifier of a hash function with NULL parameters, as is conventional in
+// RSA signature algorithms.
+fn hash_algorithm_der(nid: Nid) -> Result<Vec<u8>, ErrorStack> {
+    let mut contents = der::object_to_der(nid_to_object(nid)?)?;
+    der::write_tlv(&mut contents, false, ffi::V_ASN1_NULL, &[])?;
+    let mut buf = vec![];
+    der::write_tlv(&mut buf, true, ffi::V_ASN1_SEQUENCE, &contents)?;
+    Ok(buf)
+}
+
+// Reads the AlgorithmIdentifier of a hash function, with either absent or NULL parameters.
+fn read_hash_algorithm(der: &mut &[u8]) -> Result<Nid, ErrorStack> {
+    let mut contents = der::read_tlv(der, true, ffi::V_ASN1_SEQUENCE)?;
+    let nid = der::read_object(&mut contents)?.nid();
+    if !contents.is_empty() {
+        der::read_tlv(&mut contents, false, ffi::V_ASN1_NULL)?;
+        der::expect_end(contents)?;
+    }
+    Ok(nid)
+}
+
+fn read_small_integer(der: &mut &[u8]) -> Result<u32, ErrorStack> {
+    let mut contents = der::read_tlv(der, false, ffi::V_ASN1_INTEGER)?;
+    if contents.is_empty() || contents[0] & 0x80 != 0 {
+        return Err(der::wrong_tag());
+    }
+    if contents.len() > 1 && contents[0] == 0 {
+        contents = &contents[1..];
+    }
+    if contents.len() > 4 {
+        return Err(der::wrong_tag());
+    }
+    Ok(contents.iter().fold(0, |acc, &b| (acc << 8) | u32::from(b)))
+}
+
+#[cfg(ossl110)]
+fn rsa_pss_signature<T>(
+    key: &PKeyRef<T>,
+    tbs: &[u8],
+    digest: MessageDigest,
+    mgf1_digest: MessageDigest,
+    salt_len: u32,
+) -> Result<Vec<u8>, ErrorStack>
+where
+    T: HasPrivate,
+{
+    let mut signer = Signer::new(digest, key)?;
+    signer.set_rsa_padding(Padding::PKCS1_PSS)?;
+    signer.set_rsa_mgf1_md(mgf1_digest)?;
+    // an out of range salt length makes signing fail rather than select a special value
+    let salt_len = c_int::try_from(salt_len).unwrap_or(c_int::MAX);
+    signer.set_rsa_pss_saltlen(RsaPssSaltlen::custom(salt_len))?;
+    signer.update(tbs)?;
+    signer.sign_to_vec()
+}
+
+// Replaces the contents of `dst`, which is embedded in a certificate or request, with a copy of
+// `src`.
+#[cfg(ossl110)]
+unsafe fn copy_algorithm(
+    dst: *mut ffi::X509_ALGOR,
+    src: &X509AlgorithmRef,
+) -> Result<(), ErrorStack> {
+    let mut oid = ptr::null();
+    let mut ptype = 0;
+    let mut pval = ptr::null();
+    ffi::X509_ALGOR_get0(&mut oid, &mut ptype, &mut pval, src.as_ptr());
+
+    let oid = cvt_p(ffi::OBJ_dup(oid))?;
+    let pval = match ptype {
+        ffi::V_ASN1_UNDEF | ffi::V_ASN1_NULL => ptr::null_mut(),
+        // the "pointer" holds the boolean value itself
+        ffi::V_ASN1_BOOLEAN => pval as *mut c_void,
+        ffi::V_ASN1_OBJECT => match cvt_p(ffi::OBJ_dup(pval as *const _)) {
+            Ok(p) => p as *mut c_void,
+            Err(e) => {
+                ffi::ASN1_OBJECT_free(oid);
+                return Err(e);
+            }
+        },
+        _ => match cvt_p(ffi::ASN1_STRING_dup(pval as *const _)) {
+            Ok(p) => p as *mut c_void,
+            Err(e) => {
+                ffi::ASN1_OBJECT_free(oid);
+                return Err(e);
+            }
+        },
+    };
+    // X509_ALGOR_set0 only takes ownership of `oid` and `pval` when it succeeds.
+    if ffi::X509_ALGOR_set0(dst, oid, ptype, pval) <= 0 {
+        ffi::ASN1_OBJECT_free(oid);
+        match ptype {
+            ffi::V_ASN1_OBJECT => ffi::ASN1_OBJECT_free(pval as *mut _),
+            ffi::V_ASN1_UNDEF | ffi::V_ASN1_NULL | ffi::V_ASN1_BOOLEAN => {}
+            _ => ffi::ASN1_STRING_free(pval as *mut _),
+        }
+        return Err(ErrorStack::report());
+    }
+    Ok(())
+}
+
+// Replaces the contents of `dst`, which is embedded in a certificate or request, with a BIT
+// STRING containing `signature`.
+#[cfg(ossl110)]
+unsafe fn copy_signature(
+    dst: *mut ffi::ASN1_BIT_STRING,
+    signature: &[u8],
+) -> Result<(), ErrorStack> {
+    // Decoding a BIT STRING records that it has no unused bits, which ASN1_BIT_STRING_set does
+    // not, and which ASN1_STRING_copy preserves.
+    let mut contents = Vec::with_capacity(signature.len() + 1);
+    contents.push(0);
+    contents.extend_from_slice(signature);
+    let mut der = vec![];
+    der::write_tlv(&mut der, false, ffi::V_ASN1_BIT_STRING, &contents)?;
+
+    let len = der.len().try_into().unwrap();
+    let bits = cvt_p(ffi::d2i_ASN1_BIT_STRING(
+        ptr::null_mut(),
+        &mut der.as_ptr(),
+        len,
+    ))?;
+    let bits = crate::asn1::Asn1BitString::from_ptr(bits);
+    cvt(ffi::ASN1_STRING_copy(
+        dst as *mut _,
+        bits.as_ptr() as *const _,
+    ))
+    .map(|_| ())
 }
 
 foreign_type_and_impl_send_sync! {
@@ -2293,6 +3230,16 @@ impl X509ObjectRef {
             X509Ref::from_const_ptr_opt(ptr)
         }
     }
+
+    /// Returns the CRL held by the object, if it holds one.
+    #[corresponds(X509_OBJECT_get0_X509_CRL)]
+    #[cfg(not(boringssl))]
+    pub fn crl(&self) -> Option<&X509CrlRef> {
+        unsafe {
+            let ptr = X509_OBJECT_get0_X509_CRL(self.as_ptr());
+            X509CrlRef::from_const_ptr_opt(ptr)
+        }
+    }
 }
 
 impl Stackable for X509Object {
@@ -2395,6 +3342,21 @@ cfg_if! {
     }
 }
 
+cfg_if! {
+    if #[cfg(any(ossl110, libressl270))] {
+        use ffi::X509_OBJECT_get0_X509_CRL;
+    } else if #[cfg(not(boringssl))] {
+        #[allow(bad_style)]
+        unsafe fn X509_OBJECT_get0_X509_CRL(x: *mut ffi::X509_OBJECT) -> *mut ffi::X509_CRL {
+            if (*x).type_ == ffi::X509_LU_CRL {
+                (*x).data.crl
+            } else {
+                ptr::null_mut()
+            }
+        }
+    }
+}
+
 cfg_if! {
     if #[cfg(any(ossl110, libressl350))] {
         use ffi::X509_OBJECT_free;
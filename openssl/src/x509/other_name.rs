@@ -0,0 +1,240 @@
+//! Typed `otherName` values identifying devices.
+//!
+//! Certificates issued to devices, such as the IEEE 802.1AR device identity certificates used by
+//! BRSKI, commonly carry the identity of the device in an `otherName` subject alternative name
+//! rather than in the subject. This module implements the `id-on-hardwareModuleName` form
+//! defined in [RFC 4108] and the `id-on-permanentIdentifier` form defined in [RFC 4043].
+//!
+//! Values are added to a certificate with [`SubjectAlternativeName::hardware_module_name`] and
+//! [`SubjectAlternativeName::permanent_identifier`], and are read back with
+//! [`GeneralNameRef::hardware_module_name`] and [`GeneralNameRef::permanent_identifier`].
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::asn1::Asn1Object;
+//! use openssl::x509::extension::SubjectAlternativeName;
+//! use openssl::x509::other_name::HardwareModuleName;
+//! use openssl::x509::X509Builder;
+//!
+//! let hw_type = Asn1Object::from_str("1.3.6.1.4.1.6175.10.1").unwrap();
+//! let name = HardwareModuleName::new(hw_type, b"SN-0042");
+//!
+//! let mut builder = X509Builder::new().unwrap();
+//! let san = SubjectAlternativeName::new()
+//!     .hardware_module_name(&name)
+//!     .build(&builder.x509v3_context(None, None))
+//!     .unwrap();
+//! builder.append_extension(san).unwrap();
+//! let cert = builder.build();
+//!
+//! let names = cert.subject_alt_names().unwrap();
+//! let parsed = names[0].hardware_module_name().unwrap();
+//! assert_eq!(parsed.hw_serial_num(), b"SN-0042");
+//! ```
+//!
+//! [RFC 4108]: https://tools.ietf.org/html/rfc4108#section-5
+//! [RFC 4043]: https://tools.ietf.org/html/rfc4043#section-2
+//! [`SubjectAlternativeName::hardware_module_name`]: crate::x509::extension::SubjectAlternativeName::hardware_module_name
+//! [`SubjectAlternativeName::permanent_identifier`]: crate::x509::extension::SubjectAlternativeName::permanent_identifier
+//! [`GeneralNameRef::hardware_module_name`]: crate::x509::GeneralNameRef::hardware_module_name
+//! [`GeneralNameRef::permanent_identifier`]: crate::x509::GeneralNameRef::permanent_identifier
+use foreign_types::ForeignTypeRef;
+use std::fmt;
+use std::str;
+
+use crate::asn1::{Asn1Object, Asn1ObjectRef};
+use crate::error::ErrorStack;
+use crate::x509::der::{expect_end, object_to_der, read_object, read_tlv, write_tlv, wrong_tag};
+
+const HARDWARE_MODULE_NAME: &str = "1.3.6.1.5.5.7.8.4";
+const PERMANENT_IDENTIFIER: &str = "1.3.6.1.5.5.7.8.3";
+
+/// A hardware module name, identifying a hardware module by its type and serial number.
+///
+/// ```text
+/// HardwareModuleName ::= SEQUENCE {
+///     hwType OBJECT IDENTIFIER,
+///     hwSerialNum OCTET STRING }
+/// ```
+#[derive(Clone)]
+pub struct HardwareModuleName {
+    hw_type: Asn1Object,
+    hw_serial_num: Vec<u8>,
+}
+
+impl HardwareModuleName {
+    /// Creates a new `HardwareModuleName` from the OID identifying the type of the module and its
+    /// serial number.
+    pub fn new(hw_type: Asn1Object, hw_serial_num: &[u8]) -> HardwareModuleName {
+        HardwareModuleName {
+            hw_type,
+            hw_serial_num: hw_serial_num.to_vec(),
+        }
+    }
+
+    /// Returns `id-on-hardwareModuleName`, the `otherName` type-id of a hardware module name.
+    pub fn type_id() -> Result<Asn1Object, ErrorStack> {
+        Asn1Object::from_str(HARDWARE_MODULE_NAME)
+    }
+
+    /// Returns the OID identifying the type of the hardware module.
+    pub fn hw_type(&self) -> &Asn1ObjectRef {
+        &self.hw_type
+    }
+
+    /// Returns the serial number of the hardware module.
+    pub fn hw_serial_num(&self) -> &[u8] {
+        &self.hw_serial_num
+    }
+
+    /// Serializes the hardware module name to DER.
+    ///
+    /// This is the `otherName` value, suitable for [`SubjectAlternativeName::other_name2`].
+    ///
+    /// [`SubjectAlternativeName::other_name2`]: crate::x509::extension::SubjectAlternativeName::other_name2
+    pub fn to_der(&self) -> Result<Vec<u8>, ErrorStack> {
+        let mut contents = object_to_der(&self.hw_type)?;
+        write_tlv(
+            &mut contents,
+            false,
+            ffi::V_ASN1_OCTET_STRING,
+            &self.hw_serial_num,
+        )?;
+
+        let mut der = vec![];
+        write_tlv(&mut der, true, ffi::V_ASN1_SEQUENCE, &contents)?;
+        Ok(der)
+    }
+
+    /// Deserializes a DER-encoded hardware module name.
+    pub fn from_der(der: &[u8]) -> Result<HardwareModuleName, ErrorStack> {
+        let mut der = der;
+        let mut contents = read_tlv(&mut der, true, ffi::V_ASN1_SEQUENCE)?;
+        expect_end(der)?;
+
+        let hw_type = read_object(&mut contents)?;
+        let hw_serial_num = read_tlv(&mut contents, false, ffi::V_ASN1_OCTET_STRING)?;
+        expect_end(contents)?;
+
+        Ok(HardwareModuleName::new(hw_type, hw_serial_num))
+    }
+}
+
+impl fmt::Debug for HardwareModuleName {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("HardwareModuleName")
+            .field("hw_type", &self.hw_type())
+            .field("hw_serial_num", &self.hw_serial_num)
+            .finish()
+    }
+}
+
+/// A permanent identifier, identifying an entity independently of the certificates issued to it.
+///
+/// ```text
+/// PermanentIdentifier ::= SEQUENCE {
+///     identifierValue UTF8String OPTIONAL,
+///     assigner OBJECT IDENTIFIER OPTIONAL }
+/// ```
+#[derive(Clone)]
+pub struct PermanentIdentifier {
+    identifier_value: Option<String>,
+    assigner: Option<Asn1Object>,
+}
+
+impl PermanentIdentifier {
+    /// Creates a new `PermanentIdentifier` from the identifier and the OID of the authority which
+    /// assigned it.
+    ///
+    /// If `assigner` is omitted, the identifier was assigned by the issuer of the certificate.
+    pub fn new(
+        identifier_value: Option<&str>,
+        assigner: Option<Asn1Object>,
+    ) -> PermanentIdentifier {
+        PermanentIdentifier {
+            identifier_value: identifier_value.map(str::to_string),
+            assigner,
+        }
+    }
+
+    /// Returns `id-on-permanentIdentifier`, the `otherName` type-id of a permanent identifier.
+    pub fn type_id() -> Result<Asn1Object, ErrorStack> {
+        Asn1Object::from_str(PERMANENT_IDENTIFIER)
+    }
+
+    /// Returns the identifier.
+    pub fn identifier_value(&self) -> Option<&str> {
+        self.identifier_value.as_deref()
+    }
+
+    /// Returns the OID of the authority which assigned the identifier.
+    pub fn assigner(&self) -> Option<&Asn1ObjectRef> {
+        self.assigner.as_deref()
+    }
+
+    /// Serializes the permanent identifier to DER.
+    ///
+    /// This is the `otherName` value, suitable for [`SubjectAlternativeName::other_name2`].
+    ///
+    /// [`SubjectAlternativeName::other_name2`]: crate::x509::extension::SubjectAlternativeName::other_name2
+    pub fn to_der(&self) -> Result<Vec<u8>, ErrorStack> {
+        let mut contents = vec![];
+        if let Some(identifier_value) = &self.identifier_value {
+            write_tlv(
+                &mut contents,
+                false,
+                ffi::V_ASN1_UTF8STRING,
+                identifier_value.as_bytes(),
+            )?;
+        }
+        if let Some(assigner) = &self.assigner {
+            contents.extend_from_slice(&object_to_der(assigner)?);
+        }
+
+        let mut der = vec![];
+        write_tlv(&mut der, true, ffi::V_ASN1_SEQUENCE, &contents)?;
+        Ok(der)
+    }
+
+    /// Deserializes a DER-encoded permanent identifier.
+    pub fn from_der(der: &[u8]) -> Result<PermanentIdentifier, ErrorStack> {
+        let mut der = der;
+        let mut contents = read_tlv(&mut der, true, ffi::V_ASN1_SEQUENCE)?;
+        expect_end(der)?;
+
+        let mut identifier_value = None;
+        if contents.first() == Some(&(ffi::V_ASN1_UTF8STRING as u8)) {
+            let value = read_tlv(&mut contents, false, ffi::V_ASN1_UTF8STRING)?;
+            let value = str::from_utf8(value).map_err(|_| wrong_tag())?;
+            identifier_value = Some(value.to_string());
+        }
+
+        let mut assigner = None;
+        if !contents.is_empty() {
+            assigner = Some(read_object(&mut contents)?);
+        }
+        expect_end(contents)?;
+
+        Ok(PermanentIdentifier {
+            identifier_value,
+            assigner,
+        })
+    }
+}
+
+impl fmt::Debug for PermanentIdentifier {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("PermanentIdentifier")
+            .field("identifier_value", &self.identifier_value)
+            .field("assigner", &self.assigner())
+            .finish()
+    }
+}
+
+pub(crate) fn is_type_id(oid: &Asn1ObjectRef, type_id: Result<Asn1Object, ErrorStack>) -> bool {
+    match type_id {
+        Ok(type_id) => unsafe { ffi::OBJ_cmp(oid.as_ptr(), type_id.as_ptr()) == 0 },
+        Err(_) => false,
+    }
+}
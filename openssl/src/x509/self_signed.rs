@@ -0,0 +1,271 @@
+//! Self-signed certificates.
+//!
+//! [`X509::self_signed`] creates a certificate with the extensions expected of a modern
+//! end-entity or CA certificate in one call, configured by [`SelfSignedOptions`].
+//! [`generate_dev_identity`] creates a key and certificate for a development server on
+//! `localhost`.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::x509::self_signed::generate_dev_identity;
+//!
+//! let (key, cert) = generate_dev_identity().unwrap();
+//! assert!(cert.verify(&key).unwrap());
+//! ```
+//!
+//! [`X509::self_signed`]: crate::x509::X509::self_signed
+use crate::asn1::{Asn1Integer, Asn1Time};
+use crate::bn::BigNum;
+use crate::ec::{EcGroup, EcKey};
+use crate::error::ErrorStack;
+use crate::hash::MessageDigest;
+use crate::nid::Nid;
+use crate::pkey::{HasPrivate, Id, PKey, PKeyRef, Private};
+use crate::x509::csr::default_digest;
+use crate::x509::extension::{
+    AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
+    SubjectKeyIdentifier,
+};
+use crate::x509::{X509Extension, X509Name, X509NameRef, X509};
+
+const DEFAULT_DAYS: u32 = 365;
+
+/// Options for [`X509::self_signed`].
+///
+/// By default, the certificate is an end-entity certificate valid for 365 days from now, for
+/// TLS server authentication. Its subject alternative name is the subject's common name, as a
+/// DNS name, if the subject has one.
+///
+/// [`X509::self_signed`]: crate::x509::X509::self_signed
+pub struct SelfSignedOptions {
+    days: u32,
+    validity: Option<(Asn1Time, Asn1Time)>,
+    serial: Option<BigNum>,
+    subject_alt_name: Option<SubjectAlternativeName>,
+    extended_key_usage: Option<ExtendedKeyUsage>,
+    ca: bool,
+    digest: Option<MessageDigest>,
+    extensions: Vec<X509Extension>,
+}
+
+impl Default for SelfSignedOptions {
+    fn default() -> SelfSignedOptions {
+        SelfSignedOptions::new()
+    }
+}
+
+impl SelfSignedOptions {
+    /// Creates the default options.
+    pub fn new() -> SelfSignedOptions {
+        SelfSignedOptions {
+            days: DEFAULT_DAYS,
+            validity: None,
+            serial: None,
+            subject_alt_name: None,
+            extended_key_usage: None,
+            ca: false,
+            digest: None,
+            extensions: vec![],
+        }
+    }
+
+    /// Sets the number of days the certificate is valid for, starting now.
+    pub fn days(&mut self, days: u32) -> &mut SelfSignedOptions {
+        self.days = days;
+        self
+    }
+
+    /// Sets the subject alternative names of the certificate.
+    pub fn subject_alt_name(
+        &mut self,
+        subject_alt_name: SubjectAlternativeName,
+    ) -> &mut SelfSignedOptions {
+        self.subject_alt_name = Some(subject_alt_name);
+        self
+    }
+
+    /// Sets the extended key usage of the certificate.
+    pub fn extended_key_usage(
+        &mut self,
+        extended_key_usage: ExtendedKeyUsage,
+    ) -> &mut SelfSignedOptions {
+        self.extended_key_usage = Some(extended_key_usage);
+        self
+    }
+
+    /// Makes the certificate a CA certificate, which can sign other certificates and CRLs.
+    ///
+    /// CA certificates have no extended key usage or subject alternative name unless one is set.
+    pub fn ca(&mut self) -> &mut SelfSignedOptions {
+        self.ca = true;
+        self
+    }
+
+    /// Sets the digest used to sign the certificate.
+    ///
+    /// By default, the digest is chosen based on the key, as for [`CsrProfile::digest`].
+    ///
+    /// [`CsrProfile::digest`]: crate::x509::csr::CsrProfile::digest
+    pub fn digest(&mut self, digest: MessageDigest) -> &mut SelfSignedOptions {
+        self.digest = Some(digest);
+        self
+    }
+
+    /// Sets a fixed validity period, in place of the number of days from now.
+    pub(crate) fn validity(
+        &mut self,
+        not_before: Asn1Time,
+        not_after: Asn1Time,
+    ) -> &mut SelfSignedOptions {
+        self.validity = Some((not_before, not_after));
+        self
+    }
+
+    /// Sets a fixed serial number, in place of a random one.
+    pub(crate) fn serial_number(&mut self, serial: BigNum) -> &mut SelfSignedOptions {
+        self.serial = Some(serial);
+        self
+    }
+
+    /// Adds extensions to the certificate, which replace the generated extensions of the same
+    /// types.
+    pub(crate) fn extensions(&mut self, extensions: Vec<X509Extension>) -> &mut SelfSignedOptions {
+        self.extensions = extensions;
+        self
+    }
+}
+
+pub(crate) fn self_signed<T>(
+    subject: &X509NameRef,
+    key: &PKeyRef<T>,
+    options: &SelfSignedOptions,
+) -> Result<X509, ErrorStack>
+where
+    T: HasPrivate,
+{
+    let mut builder = X509::builder()?;
+    builder.set_version(2)?;
+    let serial = match &options.serial {
+        Some(serial) => serial.to_asn1_integer()?,
+        None => Asn1Integer::rand_serial()?,
+    };
+    builder.set_serial_number(&serial)?;
+    builder.set_subject_name(subject)?;
+    builder.set_issuer_name(subject)?;
+    match &options.validity {
+        Some((not_before, not_after)) => {
+            builder.set_not_before(not_before)?;
+            builder.set_not_after(not_after)?;
+        }
+        None => {
+            let not_before = Asn1Time::days_from_now(0)?;
+            builder.set_not_before(&not_before)?;
+            let not_after = Asn1Time::days_from_now(options.days)?;
+            builder.set_not_after(&not_after)?;
+        }
+    }
+    builder.set_pubkey(key)?;
+
+    let replaced = |nid| options.extensions.iter().any(|e| e.nid() == nid);
+
+    if !replaced(Nid::BASIC_CONSTRAINTS) {
+        let mut basic_constraints = BasicConstraints::new();
+        basic_constraints.critical();
+        if options.ca {
+            basic_constraints.ca();
+        }
+        builder.append_extension(basic_constraints.build()?)?;
+    }
+
+    if !replaced(Nid::KEY_USAGE) {
+        let mut key_usage = KeyUsage::new();
+        key_usage.critical().digital_signature();
+        if options.ca {
+            key_usage.key_cert_sign().crl_sign();
+        } else if key.id() == Id::RSA {
+            key_usage.key_encipherment();
+        }
+        builder.append_extension(key_usage.build()?)?;
+    }
+
+    match &options.extended_key_usage {
+        _ if replaced(Nid::EXT_KEY_USAGE) => {}
+        Some(extended_key_usage) => builder.append_extension(extended_key_usage.build()?)?,
+        None if !options.ca => {
+            builder.append_extension(ExtendedKeyUsage::new().server_auth().build()?)?
+        }
+        None => {}
+    }
+
+    let empty_subject = subject.entries().next().is_none();
+    let subject_alt_name = match &options.subject_alt_name {
+        _ if replaced(Nid::SUBJECT_ALT_NAME) => None,
+        Some(subject_alt_name) => Some(subject_alt_name.build_internal(empty_subject)?),
+        None if !options.ca => match subject.entries_by_nid(Nid::COMMONNAME).next() {
+            Some(common_name) => {
+                let common_name = common_name.data().as_utf8()?;
+                Some(
+                    SubjectAlternativeName::new()
+                        .dns(&common_name)
+                        .build_internal(false)?,
+                )
+            }
+            None => None,
+        },
+        None => None,
+    };
+    if let Some(subject_alt_name) = subject_alt_name {
+        builder.append_extension(subject_alt_name)?;
+    }
+
+    for extension in &options.extensions {
+        builder.append_extension2(extension)?;
+    }
+
+    let subject_key_identifier =
+        SubjectKeyIdentifier::new().build(&builder.x509v3_context(None, None))?;
+    builder.append_extension(subject_key_identifier)?;
+    let authority_key_identifier = AuthorityKeyIdentifier::new()
+        .keyid(true)
+        .build(&builder.x509v3_context(None, None))?;
+    builder.append_extension(authority_key_identifier)?;
+
+    let digest = match options.digest {
+        Some(digest) => digest,
+        None => default_digest(key),
+    };
+    builder.sign(key, digest)?;
+
+    Ok(builder.build())
+}
+
+/// Generates a key and a self-signed certificate for a development server.
+///
+/// The key is a new P-256 key. The certificate has the subject `CN=localhost` and the subject
+/// alternative names `localhost`, `127.0.0.1`, and `::1`, and is valid for 30 days for TLS
+/// server and client authentication.
+pub fn generate_dev_identity() -> Result<(PKey<Private>, X509), ErrorStack> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+    let key = PKey::from_ec_key(EcKey::generate(&group)?)?;
+
+    let mut subject = X509Name::builder()?;
+    subject.append_entry_by_nid(Nid::COMMONNAME, "localhost")?;
+    let subject = subject.build();
+
+    let mut subject_alt_name = SubjectAlternativeName::new();
+    subject_alt_name.dns("localhost").ip("127.0.0.1").ip("::1");
+    let mut extended_key_usage = ExtendedKeyUsage::new();
+    extended_key_usage.server_auth().client_auth();
+
+    let cert = X509::self_signed(
+        &subject,
+        &key,
+        SelfSignedOptions::new()
+            .days(30)
+            .subject_alt_name(subject_alt_name)
+            .extended_key_usage(extended_key_usage),
+    )?;
+
+    Ok((key, cert))
+}
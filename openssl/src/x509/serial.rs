@@ -0,0 +1,179 @@
+//! Certificate serial number allocation.
+//!
+//! Serial numbers must be unique per issuer, positive, and no longer than 20 octets, and publicly
+//! trusted CAs must additionally include at least 64 bits of output from a secure random number
+//! generator. [`Asn1Integer::rand_serial`] covers the common case of purely random serial
+//! numbers. [`SerialAllocator`] is for issuers which want increasing serial numbers, for example
+//! to keep an audit trail in issuance order.
+//!
+//! # Examples
+//!
+//! ```
+//! use openssl::bn::BigNum;
+//! use openssl::x509::serial::SerialAllocator;
+//! use openssl::x509::X509;
+//!
+//! let mut allocator = SerialAllocator::new(BigNum::from_u32(1).unwrap(), |next| {
+//!     // write `next` to durable storage before it is handed out
+//!     println!("next serial number counter: {}", next);
+//!     Ok(())
+//! });
+//!
+//! let mut builder = X509::builder().unwrap();
+//! builder.set_serial_number_from(&mut allocator).unwrap();
+//! assert_eq!(allocator.counter().to_string(), "2");
+//! ```
+//!
+//! [`Asn1Integer::rand_serial`]: crate::asn1::Asn1Integer::rand_serial
+use std::error;
+use std::fmt;
+
+use crate::asn1::Asn1Integer;
+use crate::bn::{BigNum, BigNumRef, MsbOption};
+use crate::error::ErrorStack;
+
+/// An error allocating a serial number.
+pub struct SerialError(Inner);
+
+enum Inner {
+    Invalid(&'static str),
+    Persist(Box<dyn error::Error + Send + Sync>),
+    Ssl(ErrorStack),
+}
+
+impl SerialError {
+    /// Returns the underlying OpenSSL error stack, if the error was reported by OpenSSL.
+    pub fn ssl_error(&self) -> Option<&ErrorStack> {
+        match self.0 {
+            Inner::Ssl(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Debug for SerialError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Inner::Invalid(ref e) => fmt.debug_tuple("Invalid").field(e).finish(),
+            Inner::Persist(ref e) => fmt.debug_tuple("Persist").field(e).finish(),
+            Inner::Ssl(ref e) => fmt.debug_tuple("Ssl").field(e).finish(),
+        }
+    }
+}
+
+impl fmt::Display for SerialError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Inner::Invalid(e) => write!(fmt, "invalid serial number: {}", e),
+            Inner::Persist(ref e) => write!(fmt, "error persisting serial number counter: {}", e),
+            Inner::Ssl(ref e) => fmt::Display::fmt(e, fmt),
+        }
+    }
+}
+
+impl error::Error for SerialError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self.0 {
+            Inner::Invalid(_) => None,
+            Inner::Persist(ref e) => Some(&**e),
+            Inner::Ssl(ref e) => Some(e),
+        }
+    }
+}
+
+impl From<ErrorStack> for SerialError {
+    fn from(e: ErrorStack) -> SerialError {
+        SerialError(Inner::Ssl(e))
+    }
+}
+
+type PersistFn = dyn FnMut(&BigNumRef) -> Result<(), Box<dyn error::Error + Send + Sync>> + Send;
+
+/// An allocator of increasing serial numbers.
+///
+/// Each serial number consists of a counter followed by 64 random bits by default, so that
+/// allocated serial numbers are both increasing and unpredictable. The number of random bits can
+/// be changed with [`set_random_bits`]; issuers subject to the CA/Browser Forum Baseline
+/// Requirements must keep at least 64.
+///
+/// Before a serial number is returned, the persistence callback is called with the incremented
+/// counter, and the serial number is only handed out if it succeeds. Storing the value passed to
+/// the callback therefore ensures that a counter is never reused, even if the process exits right
+/// after the allocation.
+///
+/// [`set_random_bits`]: SerialAllocator::set_random_bits
+pub struct SerialAllocator {
+    counter: BigNum,
+    random_bits: i32,
+    persist: Box<PersistFn>,
+}
+
+impl SerialAllocator {
+    /// Creates an allocator whose first serial number is built from the counter `next`.
+    ///
+    /// `next` should be the value most recently passed to `persist`, or 1 for a new issuer.
+    pub fn new<F>(next: BigNum, persist: F) -> SerialAllocator
+    where
+        F: FnMut(&BigNumRef) -> Result<(), Box<dyn error::Error + Send + Sync>> + 'static + Send,
+    {
+        SerialAllocator {
+            counter: next,
+            random_bits: 64,
+            persist: Box::new(persist),
+        }
+    }
+
+    /// Sets the number of random bits appended to the counter.
+    ///
+    /// Defaults to 64. With 0, the serial numbers are the counter values themselves.
+    pub fn set_random_bits(&mut self, bits: u8) {
+        self.random_bits = bits.into();
+    }
+
+    /// Returns the counter the next serial number will be built from.
+    pub fn counter(&self) -> &BigNumRef {
+        &self.counter
+    }
+
+    /// Allocates a serial number.
+    ///
+    /// Fails without consuming a counter value if the persistence callback fails, or if the serial
+    /// number would not fit in 20 octets.
+    pub fn allocate(&mut self) -> Result<Asn1Integer, SerialError> {
+        if self.counter.is_negative() || self.counter.num_bits() == 0 {
+            return Err(SerialError(Inner::Invalid("the counter must be positive")));
+        }
+
+        let mut serial = BigNum::new()?;
+        serial.lshift(&self.counter, self.random_bits)?;
+        if self.random_bits > 0 {
+            let mut random = BigNum::new()?;
+            random.rand(self.random_bits, MsbOption::MAYBE_ZERO, false)?;
+            let shifted = serial;
+            serial = BigNum::new()?;
+            serial.checked_add(&shifted, &random)?;
+        }
+        // the most significant bit of the 20th octet is the sign bit
+        if serial.num_bits() > 159 {
+            return Err(SerialError(Inner::Invalid(
+                "serial number exceeds 20 octets",
+            )));
+        }
+
+        let mut next = self.counter.to_owned()?;
+        next.add_word(1)?;
+        (self.persist)(&next).map_err(|e| SerialError(Inner::Persist(e)))?;
+        self.counter = next;
+
+        Ok(serial.to_asn1_integer()?)
+    }
+}
+
+impl fmt::Debug for SerialAllocator {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("SerialAllocator")
+            .field("counter", &self.counter)
+            .field("random_bits", &self.random_bits)
+            .finish()
+    }
+}
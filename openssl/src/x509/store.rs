@@ -42,22 +42,36 @@
 //! ```
 
 use cfg_if::cfg_if;
+#[cfg(ossl110)]
+use foreign_types::ForeignType;
 use foreign_types::ForeignTypeRef;
 use std::mem;
 
 use crate::error::ErrorStack;
+#[cfg(ossl110)]
+use crate::ssl::SslContextBuilder;
 #[cfg(not(boringssl))]
 use crate::ssl::SslFiletype;
 use crate::stack::StackRef;
 #[cfg(any(ossl102, libressl261))]
 use crate::x509::verify::{X509VerifyFlags, X509VerifyParamRef};
+#[cfg(ossl110)]
+use crate::x509::X509Crl;
 use crate::x509::{X509Object, X509PurposeId, X509};
 use crate::{cvt, cvt_p};
 use openssl_macros::corresponds;
 #[cfg(not(boringssl))]
 use std::ffi::CString;
+#[cfg(ossl110)]
+use std::fs;
 #[cfg(not(boringssl))]
 use std::path::Path;
+#[cfg(ossl110)]
+use std::path::PathBuf;
+#[cfg(ossl110)]
+use std::sync::Mutex;
+#[cfg(ossl110)]
+use std::time::SystemTime;
 
 foreign_type_and_impl_send_sync! {
     type CType = ffi::X509_STORE;
@@ -108,6 +122,33 @@ impl X509StoreBuilderRef {
         unsafe { cvt(ffi::X509_STORE_set_default_paths(self.as_ptr())).map(|_| ()) }
     }
 
+    /// Loads the platform's trusted root certificates.
+    ///
+    /// This calls [`set_default_paths`], and additionally:
+    ///
+    /// * On Windows, adds the certificates in the system `ROOT` certificate store.
+    /// * On macOS, adds the system anchor certificates from the Security framework.
+    /// * On other Unix platforms, if `SSL_CERT_FILE` and `SSL_CERT_DIR` are unset and
+    ///   OpenSSL's built-in default locations do not exist, loads the CA bundle and hashed
+    ///   directory from the first of the common distribution-specific locations which exists.
+    ///
+    /// Platform certificates which cannot be parsed are skipped.
+    ///
+    /// ```no_run
+    /// use openssl::ssl::{SslConnector, SslMethod};
+    ///
+    /// let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    /// builder.cert_store_mut().load_system_roots().unwrap();
+    /// let connector = builder.build();
+    /// ```
+    ///
+    /// [`set_default_paths`]: X509StoreBuilderRef::set_default_paths
+    #[cfg(not(boringssl))]
+    pub fn load_system_roots(&mut self) -> Result<(), ErrorStack> {
+        self.set_default_paths()?;
+        system::load(self)
+    }
+
     /// Adds a lookup method to the store.
     #[corresponds(X509_STORE_add_lookup)]
     pub fn add_lookup<T>(
@@ -138,6 +179,19 @@ impl X509StoreBuilderRef {
     pub fn set_param(&mut self, param: &X509VerifyParamRef) -> Result<(), ErrorStack> {
         unsafe { cvt(ffi::X509_STORE_set1_param(self.as_ptr(), param.as_ptr())).map(|_| ()) }
     }
+
+    /// Returns a mutable reference to the store's chain validation parameters.
+    ///
+    /// Unlike [`set_flags`], this allows flags to be cleared as well as set.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    ///
+    /// [`set_flags`]: X509StoreBuilderRef::set_flags
+    #[corresponds(X509_STORE_get0_param)]
+    #[cfg(ossl110)]
+    pub fn param_mut(&mut self) -> &mut X509VerifyParamRef {
+        unsafe { X509VerifyParamRef::from_ptr_mut(ffi::X509_STORE_get0_param(self.as_ptr())) }
+    }
 }
 
 generic_foreign_type_and_impl_send_sync! {
@@ -264,6 +318,224 @@ impl X509StoreRef {
     pub fn objects(&self) -> &StackRef<X509Object> {
         unsafe { StackRef::from_ptr(X509_STORE_get0_objects(self.as_ptr())) }
     }
+
+    /// Returns a copy of the certificates and CRLs currently in the store.
+    ///
+    /// The store's lock is held while the objects are copied, so the snapshot is consistent even if the store is
+    /// modified concurrently, for example by an [`X509StoreReloader`]. Certificates and CRLs which a
+    /// [`X509Lookup::hash_dir`] lookup has not loaded yet are not included.
+    ///
+    /// Requires OpenSSL 1.1.0 or newer.
+    #[corresponds(X509_STORE_lock)]
+    #[cfg(ossl110)]
+    pub fn snapshot(&self) -> Result<X509StoreSnapshot, ErrorStack> {
+        let mut snapshot = X509StoreSnapshot {
+            certs: vec![],
+            crls: vec![],
+        };
+        unsafe {
+            cvt(ffi::X509_STORE_lock(self.as_ptr()))?;
+            for object in self.objects() {
+                if let Some(cert) = object.x509() {
+                    snapshot.certs.push(cert.to_owned());
+                } else if let Some(crl) = object.crl() {
+                    snapshot.crls.push(crl.to_owned());
+                }
+            }
+            ffi::X509_STORE_unlock(self.as_ptr());
+        }
+        Ok(snapshot)
+    }
+}
+
+/// The certificates and CRLs in an [`X509Store`] at one point in time.
+///
+/// Returned by [`X509StoreRef::snapshot`].
+#[cfg(ossl110)]
+pub struct X509StoreSnapshot {
+    certs: Vec<X509>,
+    crls: Vec<X509Crl>,
+}
+
+#[cfg(ossl110)]
+impl X509StoreSnapshot {
+    /// Returns the certificates, in the store's order.
+    pub fn certificates(&self) -> &[X509] {
+        &self.certs
+    }
+
+    /// Returns the CRLs, in the store's order.
+    pub fn crls(&self) -> &[X509Crl] {
+        &self.crls
+    }
+
+    /// Returns the total number of certificates and CRLs.
+    pub fn len(&self) -> usize {
+        self.certs.len() + self.crls.len()
+    }
+
+    /// Determines if the snapshot contains neither certificates nor CRLs.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Serializes the certificates followed by the CRLs as a PEM bundle.
+    pub fn to_pem(&self) -> Result<Vec<u8>, ErrorStack> {
+        let mut pem = vec![];
+        for cert in &self.certs {
+            pem.extend_from_slice(&cert.to_pem()?);
+        }
+        for crl in &self.crls {
+            pem.extend_from_slice(&crl.to_pem()?);
+        }
+        Ok(pem)
+    }
+}
+
+/// Keeps a certificate store in sync with a CA file and directory.
+///
+/// Long-running servers which trust CA bundles distributed by configuration management need to pick up changes
+/// without being restarted. The reloader owns a single store which is shared by every registered
+/// [`SslContextBuilder`]. When [`reload_if_changed`] notices that the modification time or size of the CA file or
+/// directory has changed, the certificates in the store are replaced while its lock is held, so connections
+/// verifying a peer concurrently see either the old set of certificates or the new one.
+///
+/// The store itself is never swapped out of a context, as a handshake in progress may still be using it.
+///
+/// Requires OpenSSL 1.1.0 or newer.
+///
+/// [`reload_if_changed`]: X509StoreReloader::reload_if_changed
+#[cfg(ossl110)]
+pub struct X509StoreReloader {
+    ca_file: Option<PathBuf>,
+    ca_path: Option<PathBuf>,
+    store: X509Store,
+    stamps: Mutex<[Option<(SystemTime, u64)>; 2]>,
+}
+
+#[cfg(ossl110)]
+impl X509StoreReloader {
+    /// Creates a reloader and loads the certificates in `ca_file`.
+    ///
+    /// Certificates in `ca_path` are looked up by subject name hash, as with [`X509Lookup::hash_dir`], and are
+    /// read again after each reload.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either path is not valid UTF-8.
+    pub fn new(
+        ca_file: Option<&Path>,
+        ca_path: Option<&Path>,
+    ) -> Result<X509StoreReloader, ErrorStack> {
+        let mut store = X509StoreBuilder::new()?;
+        if let Some(ca_path) = ca_path {
+            store
+                .add_lookup(X509Lookup::hash_dir())?
+                .add_dir(ca_path.to_str().unwrap(), SslFiletype::PEM)?;
+        }
+
+        let reloader = X509StoreReloader {
+            ca_file: ca_file.map(Path::to_path_buf),
+            ca_path: ca_path.map(Path::to_path_buf),
+            store: store.build(),
+            stamps: Mutex::new([None, None]),
+        };
+        reloader.reload()?;
+        Ok(reloader)
+    }
+
+    /// Returns the shared certificate store.
+    pub fn store(&self) -> &X509StoreRef {
+        &self.store
+    }
+
+    /// Configures a context to verify peers against the shared certificate store.
+    ///
+    /// This replaces the context's existing certificate store.
+    pub fn register(&self, ctx: &mut SslContextBuilder) -> Result<(), ErrorStack> {
+        unsafe {
+            cvt(ffi::X509_STORE_up_ref(self.store.as_ptr()))?;
+            ctx.set_cert_store(X509Store::from_ptr(self.store.as_ptr()));
+        }
+        Ok(())
+    }
+
+    /// Reloads the certificates if the CA file or directory has changed since the last reload.
+    ///
+    /// Returns `true` if the store was reloaded. If loading fails, the store is left unchanged and the next call
+    /// will try again.
+    pub fn reload_if_changed(&self) -> Result<bool, ErrorStack> {
+        let mut stamps = self.stamps.lock().unwrap();
+        if *stamps == self.current_stamps() {
+            return Ok(false);
+        }
+        self.reload_locked(&mut stamps)?;
+        Ok(true)
+    }
+
+    /// Unconditionally reloads the certificates.
+    pub fn reload(&self) -> Result<(), ErrorStack> {
+        let mut stamps = self.stamps.lock().unwrap();
+        self.reload_locked(&mut stamps)
+    }
+
+    fn current_stamps(&self) -> [Option<(SystemTime, u64)>; 2] {
+        let stamp = |path: &Option<PathBuf>| {
+            let metadata = fs::metadata(path.as_ref()?).ok()?;
+            Some((metadata.modified().ok()?, metadata.len()))
+        };
+        [stamp(&self.ca_file), stamp(&self.ca_path)]
+    }
+
+    fn reload_locked(&self, stamps: &mut [Option<(SystemTime, u64)>; 2]) -> Result<(), ErrorStack> {
+        // read the stamps first so that a change racing with the load is picked up by the next check
+        let current = self.current_stamps();
+
+        let mut fresh = X509StoreBuilder::new()?;
+        if let Some(ca_file) = &self.ca_file {
+            fresh
+                .add_lookup(X509Lookup::file())?
+                .load_cert_file(ca_file, SslFiletype::PEM)?;
+        }
+        let fresh = fresh.build();
+
+        unsafe {
+            let fresh_objects = X509_STORE_get0_objects(fresh.as_ptr()) as *mut ffi::OPENSSL_STACK;
+            let live = self.store.as_ptr();
+            cvt(ffi::X509_STORE_lock(live))?;
+
+            let objects = X509_STORE_get0_objects(live) as *mut ffi::OPENSSL_STACK;
+            loop {
+                let object = ffi::OPENSSL_sk_pop(objects);
+                if object.is_null() {
+                    break;
+                }
+                ffi::X509_OBJECT_free(object as *mut ffi::X509_OBJECT);
+            }
+
+            let mut pushed = true;
+            loop {
+                let object = ffi::OPENSSL_sk_pop(fresh_objects);
+                if object.is_null() {
+                    break;
+                }
+                if ffi::OPENSSL_sk_push(objects, object) <= 0 {
+                    ffi::X509_OBJECT_free(object as *mut ffi::X509_OBJECT);
+                    pushed = false;
+                }
+            }
+            // lookups expect the cache to be sorted
+            ffi::OPENSSL_sk_sort(objects);
+
+            ffi::X509_STORE_unlock(live);
+            if !pushed {
+                return Err(ErrorStack::report());
+            }
+        }
+
+        *stamps = current;
+        Ok(())
+    }
 }
 
 cfg_if! {
@@ -285,3 +557,198 @@ cfg_if! {
         unsafe fn X509_LOOKUP_meth_free(_x: *mut ffi::X509_LOOKUP_METHOD) {}
     }
 }
+
+#[cfg(not(boringssl))]
+mod system {
+    use cfg_if::cfg_if;
+
+    use super::X509StoreBuilderRef;
+    use crate::error::ErrorStack;
+
+    cfg_if! {
+        if #[cfg(windows)] {
+            use libc::c_void;
+            use std::slice;
+
+            use crate::x509::X509;
+
+            const X509_ASN_ENCODING: u32 = 1;
+
+            #[allow(non_snake_case)]
+            #[repr(C)]
+            struct CERT_CONTEXT {
+                dwCertEncodingType: u32,
+                pbCertEncoded: *const u8,
+                cbCertEncoded: u32,
+                pCertInfo: *mut c_void,
+                hCertStore: *mut c_void,
+            }
+
+            #[link(name = "crypt32")]
+            extern "system" {
+                fn CertOpenSystemStoreW(
+                    hProv: usize,
+                    szSubsystemProtocol: *const u16,
+                ) -> *mut c_void;
+                fn CertEnumCertificatesInStore(
+                    hCertStore: *mut c_void,
+                    pPrevCertContext: *const CERT_CONTEXT,
+                ) -> *const CERT_CONTEXT;
+                fn CertCloseStore(hCertStore: *mut c_void, dwFlags: u32) -> i32;
+            }
+
+            pub fn load(store: &mut X509StoreBuilderRef) -> Result<(), ErrorStack> {
+                let name = "ROOT\0".encode_utf16().collect::<Vec<_>>();
+                unsafe {
+                    let system = CertOpenSystemStoreW(0, name.as_ptr());
+                    if system.is_null() {
+                        return Ok(());
+                    }
+
+                    // each call frees the previous context
+                    let mut context = CertEnumCertificatesInStore(system, std::ptr::null());
+                    while !context.is_null() {
+                        let cert = &*context;
+                        if cert.dwCertEncodingType & X509_ASN_ENCODING != 0 {
+                            let der = slice::from_raw_parts(
+                                cert.pbCertEncoded,
+                                cert.cbCertEncoded as usize,
+                            );
+                            add_der(store, der);
+                        }
+                        context = CertEnumCertificatesInStore(system, context);
+                    }
+
+                    CertCloseStore(system, 0);
+                }
+                Ok(())
+            }
+
+            fn add_der(store: &mut X509StoreBuilderRef, der: &[u8]) {
+                if let Ok(cert) = X509::from_der(der) {
+                    // a duplicate of a certificate from the default paths is not an error
+                    let _ = store.add_cert(cert);
+                }
+            }
+        } else if #[cfg(target_os = "macos")] {
+            use libc::c_void;
+            use std::ptr;
+            use std::slice;
+
+            use crate::x509::X509;
+
+            type CFIndex = isize;
+
+            #[link(name = "CoreFoundation", kind = "framework")]
+            extern "C" {
+                fn CFArrayGetCount(array: *const c_void) -> CFIndex;
+                fn CFArrayGetValueAtIndex(array: *const c_void, idx: CFIndex) -> *const c_void;
+                fn CFDataGetLength(data: *const c_void) -> CFIndex;
+                fn CFDataGetBytePtr(data: *const c_void) -> *const u8;
+                fn CFRelease(cf: *const c_void);
+            }
+
+            #[link(name = "Security", kind = "framework")]
+            extern "C" {
+                fn SecTrustCopyAnchorCertificates(anchors: *mut *const c_void) -> i32;
+                fn SecCertificateCopyData(certificate: *const c_void) -> *const c_void;
+            }
+
+            pub fn load(store: &mut X509StoreBuilderRef) -> Result<(), ErrorStack> {
+                unsafe {
+                    let mut anchors = ptr::null();
+                    if SecTrustCopyAnchorCertificates(&mut anchors) != 0 || anchors.is_null() {
+                        return Ok(());
+                    }
+
+                    for i in 0..CFArrayGetCount(anchors) {
+                        let data = SecCertificateCopyData(CFArrayGetValueAtIndex(anchors, i));
+                        if data.is_null() {
+                            continue;
+                        }
+                        let der = slice::from_raw_parts(
+                            CFDataGetBytePtr(data),
+                            CFDataGetLength(data) as usize,
+                        );
+                        if let Ok(cert) = X509::from_der(der) {
+                            // a duplicate of a certificate from the default paths is not an error
+                            let _ = store.add_cert(cert);
+                        }
+                        CFRelease(data);
+                    }
+
+                    CFRelease(anchors);
+                }
+                Ok(())
+            }
+        } else if #[cfg(unix)] {
+            use std::env;
+            use std::ffi::CStr;
+            use std::path::Path;
+
+            use crate::ssl::SslFiletype;
+            use crate::x509::store::X509Lookup;
+
+            // the locations probed by the openssl-probe crate
+            const CERT_FILES: &[&str] = &[
+                "/etc/ssl/certs/ca-certificates.crt",
+                "/etc/pki/tls/certs/ca-bundle.crt",
+                "/etc/ssl/ca-bundle.pem",
+                "/etc/pki/ca-trust/extracted/pem/tls-ca-bundle.pem",
+                "/etc/ssl/cert.pem",
+                "/usr/local/share/certs/ca-root-nss.crt",
+                "/etc/pki/tls/cacert.pem",
+                "/etc/certs/ca-certificates.crt",
+                "/usr/local/etc/openssl/cert.pem",
+                "/opt/local/etc/openssl/cert.pem",
+                "/data/data/com.termux/files/usr/etc/tls/cert.pem",
+            ];
+            const CERT_DIRS: &[&str] = &[
+                "/etc/ssl/certs",
+                "/etc/pki/tls/certs",
+                "/etc/certs",
+                "/usr/local/share/certs",
+                "/system/etc/security/cacerts",
+            ];
+
+            pub fn load(store: &mut X509StoreBuilderRef) -> Result<(), ErrorStack> {
+                let (file_env, file, dir_env, dir) = unsafe {
+                    (
+                        CStr::from_ptr(ffi::X509_get_default_cert_file_env()),
+                        CStr::from_ptr(ffi::X509_get_default_cert_file()),
+                        CStr::from_ptr(ffi::X509_get_default_cert_dir_env()),
+                        CStr::from_ptr(ffi::X509_get_default_cert_dir()),
+                    )
+                };
+
+                if missing(file_env, file) {
+                    if let Some(file) = CERT_FILES.iter().find(|f| Path::new(f).is_file()) {
+                        store
+                            .add_lookup(X509Lookup::file())?
+                            .load_cert_file(file, SslFiletype::PEM)?;
+                    }
+                }
+                if missing(dir_env, dir) {
+                    if let Some(dir) = CERT_DIRS.iter().find(|d| Path::new(d).is_dir()) {
+                        store
+                            .add_lookup(X509Lookup::hash_dir())?
+                            .add_dir(dir, SslFiletype::PEM)?;
+                    }
+                }
+                Ok(())
+            }
+
+            /// Returns true if neither the environment variable nor the default location is usable.
+            fn missing(env_name: &CStr, default: &CStr) -> bool {
+                let env_set = matches!(env_name.to_str(), Ok(name) if env::var_os(name).is_some());
+                let default_exists =
+                    matches!(default.to_str(), Ok(path) if Path::new(path).exists());
+                !env_set && !default_exists
+            }
+        } else {
+            pub fn load(_: &mut X509StoreBuilderRef) -> Result<(), ErrorStack> {
+                Ok(())
+            }
+        }
+    }
+}
@@ -1,19 +1,35 @@
 use std::cmp::Ordering;
+use std::sync::{Arc, Mutex};
 
 use crate::asn1::{Asn1Object, Asn1OctetString, Asn1Time};
 use crate::bn::{BigNum, MsbOption};
+#[cfg(all(ossl320, not(osslconf = "OPENSSL_NO_CMS")))]
+use crate::cms::{CMSOptions, CmsContentInfo};
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+use crate::ct::{LogEntryType, Sct, SctSource};
+use crate::ec::{EcGroup, EcKey};
 use crate::hash::MessageDigest;
 use crate::nid::Nid;
 use crate::pkey::{PKey, Private};
 use crate::rsa::Rsa;
+#[cfg(ossl110)]
+use crate::sign::Signer;
 #[cfg(not(boringssl))]
 use crate::ssl::SslFiletype;
 use crate::stack::Stack;
+#[cfg(ossl110)]
+use crate::x509::ca::Revocation;
+use crate::x509::ca::{CertificateAuthority, IssuanceProfile};
+#[cfg(not(boringssl))]
+use crate::x509::csr::CsrProfile;
 use crate::x509::extension::{
     AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
     SubjectKeyIdentifier,
 };
-#[cfg(not(boringssl))]
+use crate::x509::hash_dir::{self, HashDirBuilder};
+use crate::x509::other_name::{self, HardwareModuleName, PermanentIdentifier};
+use crate::x509::self_signed::{generate_dev_identity, SelfSignedOptions};
+use crate::x509::serial::SerialAllocator;
 use crate::x509::store::X509Lookup;
 use crate::x509::store::X509StoreBuilder;
 #[cfg(any(ossl102, libressl261))]
@@ -23,9 +39,10 @@ use crate::x509::X509PurposeId;
 #[cfg(any(ossl102, libressl261))]
 use crate::x509::X509PurposeRef;
 #[cfg(ossl110)]
-use crate::x509::{CrlReason, X509Builder};
+use crate::x509::{CrlReason, ExtendedKeyUsageFlags, KeyUsageFlags, X509Builder};
 use crate::x509::{
-    CrlStatus, X509Crl, X509Extension, X509Name, X509Req, X509StoreContext, X509VerifyResult, X509,
+    CrlStatus, X509Algorithm, X509Crl, X509Extension, X509Name, X509Req, X509StoreContext,
+    X509VerifyResult, X509,
 };
 
 #[cfg(ossl110)]
@@ -462,6 +479,326 @@ fn x509_req_builder() {
     assert!(req.verify(&pkey).unwrap());
 }
 
+#[test]
+fn x509_req_challenge_password() {
+    let pkey = pkey();
+
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    builder.set_challenge_password("s3cret pass").unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+    let req = X509Req::from_der(&builder.build().to_der().unwrap()).unwrap();
+    assert_eq!(req.challenge_password(), Some("s3cret pass"));
+
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    builder.set_challenge_password("pässword").unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+    let req = X509Req::from_der(&builder.build().to_der().unwrap()).unwrap();
+    assert_eq!(req.challenge_password(), Some("pässword"));
+
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+    assert_eq!(builder.build().challenge_password(), None);
+}
+
+#[test]
+fn csr_profile() {
+    let pkey = pkey();
+
+    let mut san = SubjectAlternativeName::new();
+    san.dns("example.com").dns("www.example.com");
+    let mut key_usage = KeyUsage::new();
+    key_usage.critical().digital_signature().key_encipherment();
+    let mut extended_key_usage = ExtendedKeyUsage::new();
+    extended_key_usage.server_auth().client_auth();
+
+    let req = CsrProfile::new()
+        .subject_alt_name(san)
+        .key_usage(key_usage)
+        .extended_key_usage(extended_key_usage)
+        .challenge_password("enroll-1234")
+        .build(&pkey)
+        .unwrap();
+    let req = X509Req::from_der(&req.to_der().unwrap()).unwrap();
+
+    assert_eq!(req.version(), 0);
+    assert_eq!(req.subject_name().entries().count(), 0);
+    assert!(req.public_key().unwrap().public_eq(&pkey));
+    assert!(req.verify(&pkey).unwrap());
+    assert_eq!(req.challenge_password(), Some("enroll-1234"));
+    assert_eq!(req.extensions().unwrap().len(), 3);
+
+    let text = String::from_utf8(req.to_text().unwrap()).unwrap();
+    assert!(text.contains("X509v3 Subject Alternative Name: critical"));
+    assert!(text.contains("DNS:example.com, DNS:www.example.com"));
+    assert!(text.contains("X509v3 Key Usage: critical"));
+    assert!(text.contains("TLS Web Server Authentication, TLS Web Client Authentication"));
+    assert!(text.contains("sha256WithRSAEncryption"));
+}
+
+#[test]
+fn csr_profile_subject() {
+    let group = EcGroup::from_curve_name(Nid::SECP384R1).unwrap();
+    let pkey = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "device-1")
+        .unwrap();
+    let mut san = SubjectAlternativeName::new();
+    san.dns("device-1.example.com");
+
+    let req = CsrProfile::new()
+        .subject(name.build())
+        .subject_alt_name(san)
+        .build(&pkey)
+        .unwrap();
+
+    assert!(req.verify(&pkey).unwrap());
+    assert_eq!(req.challenge_password(), None);
+    let text = String::from_utf8(req.to_text().unwrap()).unwrap();
+    assert!(text.contains("CN=device-1"));
+    assert!(text.contains("X509v3 Subject Alternative Name: \n"));
+    assert!(text.contains("ecdsa-with-SHA384"));
+}
+
+#[test]
+fn self_signed() {
+    let pkey = pkey();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "example.com")
+        .unwrap();
+    let name = name.build();
+
+    let cert = X509::self_signed(&name, &pkey, SelfSignedOptions::new().days(90)).unwrap();
+
+    assert_eq!(cert.version(), 2);
+    assert!(cert.verify(&pkey).unwrap());
+    assert_eq!(
+        cert.subject_name().to_der().unwrap(),
+        name.to_der().unwrap()
+    );
+    let serial = cert.serial_number().to_bn().unwrap();
+    assert!(serial.num_bits() > 64);
+    let not_after = Asn1Time::days_from_now(90).unwrap();
+    assert!(cert.not_after() <= not_after);
+    assert!(cert.not_after() > Asn1Time::days_from_now(89).unwrap());
+    assert_eq!(
+        cert.subject_alt_names().unwrap()[0].dnsname(),
+        Some("example.com")
+    );
+    assert!(cert.subject_key_id().is_some());
+    assert_eq!(
+        cert.authority_key_id().unwrap().as_slice(),
+        cert.subject_key_id().unwrap().as_slice()
+    );
+
+    let text = String::from_utf8(cert.to_text().unwrap()).unwrap();
+    assert!(text.contains("X509v3 Basic Constraints: critical\n                CA:FALSE"));
+    assert!(text.contains("Digital Signature, Key Encipherment"));
+    assert!(text.contains("TLS Web Server Authentication"));
+    assert!(text.contains("sha256WithRSAEncryption"));
+}
+
+#[test]
+fn self_signed_ca() {
+    let group = EcGroup::from_curve_name(Nid::SECP384R1).unwrap();
+    let pkey = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "Test CA")
+        .unwrap();
+    let name = name.build();
+
+    let cert = X509::self_signed(&name, &pkey, SelfSignedOptions::new().ca()).unwrap();
+
+    assert!(cert.verify(&pkey).unwrap());
+    assert!(cert.subject_alt_names().is_none());
+    let text = String::from_utf8(cert.to_text().unwrap()).unwrap();
+    assert!(text.contains("CA:TRUE"));
+    assert!(text.contains("Digital Signature, Certificate Sign, CRL Sign"));
+    assert!(!text.contains("X509v3 Extended Key Usage"));
+    assert!(text.contains("ecdsa-with-SHA384"));
+}
+
+#[test]
+fn dev_identity() {
+    let (key, cert) = generate_dev_identity().unwrap();
+
+    assert!(cert.verify(&key).unwrap());
+    assert!(cert.public_key().unwrap().public_eq(&key));
+    let names = cert.subject_alt_names().unwrap();
+    assert_eq!(names.len(), 3);
+    assert_eq!(names[0].dnsname(), Some("localhost"));
+    assert_eq!(names[1].ipaddress(), Some(&[127, 0, 0, 1][..]));
+    assert_eq!(names[2].ipaddress().map(|ip| ip.len()), Some(16));
+
+    let mut store = X509StoreBuilder::new().unwrap();
+    store.add_cert(cert.clone()).unwrap();
+    let store = store.build();
+    let mut context = X509StoreContext::new().unwrap();
+    let chain = Stack::new().unwrap();
+    assert!(context
+        .init(&store, &cert, &chain, |c| c.verify_cert())
+        .unwrap());
+}
+
+fn test_ca(days: u32) -> CertificateAuthority {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "Test CA")
+        .unwrap();
+    let cert = X509::self_signed(
+        &name.build(),
+        &key,
+        SelfSignedOptions::new().ca().days(days),
+    )
+    .unwrap();
+    CertificateAuthority::new(cert, key).unwrap()
+}
+
+#[test]
+#[cfg(not(boringssl))]
+fn ca_issue_request() {
+    let mut ca = test_ca(30);
+    let mut allocator = SerialAllocator::new(BigNum::from_u32(1).unwrap(), |_| Ok(()));
+    allocator.set_random_bits(0);
+    ca.set_serial_allocator(allocator);
+
+    let pkey = pkey();
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "www.example.com")
+        .unwrap();
+    let mut san = SubjectAlternativeName::new();
+    san.dns("www.example.com");
+    let req = CsrProfile::new()
+        .subject(name.build())
+        .subject_alt_name(san)
+        .build(&pkey)
+        .unwrap();
+
+    let cert = ca.issue_request(&req, &IssuanceProfile::new()).unwrap();
+
+    assert!(cert
+        .verify(&ca.certificate().public_key().unwrap())
+        .unwrap());
+    assert_eq!(ca.certificate().issued(&cert), X509VerifyResult::OK);
+    assert!(cert.public_key().unwrap().public_eq(&pkey));
+    assert_eq!(cert.serial_number().to_bn().unwrap().to_string(), "1");
+    assert_eq!(cert.not_after(), ca.certificate().not_after());
+    assert_eq!(
+        cert.subject_alt_names().unwrap()[0].dnsname(),
+        Some("www.example.com")
+    );
+    #[cfg(ossl110)]
+    assert_eq!(
+        cert.authority_key_id().unwrap().as_slice(),
+        ca.certificate().subject_key_id().unwrap().as_slice()
+    );
+    let text = String::from_utf8(cert.to_text().unwrap()).unwrap();
+    assert!(text.contains("CA:FALSE"));
+    assert!(text.contains("Digital Signature, Key Encipherment"));
+    assert!(text.contains("ecdsa-with-SHA256"));
+
+    let mut store = X509StoreBuilder::new().unwrap();
+    store.add_cert(ca.certificate().to_owned()).unwrap();
+    let store = store.build();
+    let mut context = X509StoreContext::new().unwrap();
+    let chain = Stack::new().unwrap();
+    assert!(context
+        .init(&store, &cert, &chain, |c| c.verify_cert())
+        .unwrap());
+
+    let cert = ca.issue_request(&req, &IssuanceProfile::new()).unwrap();
+    assert_eq!(cert.serial_number().to_bn().unwrap().to_string(), "2");
+}
+
+#[test]
+fn ca_issue_intermediate() {
+    let mut ca = test_ca(3650);
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    let pkey = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "Intermediate CA")
+        .unwrap();
+
+    let cert = ca
+        .issue(
+            &name.build(),
+            &pkey,
+            IssuanceProfile::new().ca(Some(0)).days(365),
+        )
+        .unwrap();
+
+    assert!(cert.not_after() < ca.certificate().not_after());
+    let text = String::from_utf8(cert.to_text().unwrap()).unwrap();
+    assert!(text.contains("CA:TRUE, pathlen:0"));
+    assert!(text.contains("Digital Signature, Certificate Sign, CRL Sign"));
+}
+
+#[test]
+fn ca_rejects() {
+    let mut ca = test_ca(30);
+
+    assert!(CertificateAuthority::new(ca.certificate().to_owned(), pkey()).is_err());
+
+    let empty = X509Name::builder().unwrap().build();
+    let err = ca
+        .issue(&empty, &pkey(), &IssuanceProfile::new())
+        .unwrap_err();
+    assert!(err.ssl_error().is_none());
+    assert!(err.to_string().contains("subject alternative name"));
+}
+
+#[test]
+#[cfg(ossl110)]
+fn ca_crl() {
+    let mut ca = test_ca(30);
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "revoked.example.com")
+        .unwrap();
+    let name = name.build();
+    let revoked = ca.issue(&name, &pkey(), &IssuanceProfile::new()).unwrap();
+    let valid = ca.issue(&name, &pkey(), &IssuanceProfile::new()).unwrap();
+
+    let mut revocation = Revocation::from_certificate(&revoked).unwrap();
+    revocation.reason(CrlReason::KEY_COMPROMISE);
+    let crl = ca
+        .crl(&BigNum::from_u32(7).unwrap(), &[revocation], 7)
+        .unwrap();
+    let der = crl.to_der().unwrap();
+    let crl = X509Crl::from_der(&der).unwrap();
+
+    assert!(crl.verify(&ca.certificate().public_key().unwrap()).unwrap());
+    assert_eq!(
+        crl.issuer_name().to_der().unwrap(),
+        ca.certificate().subject_name().to_der().unwrap()
+    );
+    assert!(crl.next_update().unwrap() > crl.last_update());
+    assert!(matches!(crl.get_by_cert(&valid), CrlStatus::NotRevoked));
+    let entry = match crl.get_by_cert(&revoked) {
+        CrlStatus::Revoked(entry) => entry,
+        _ => panic!("the certificate should be revoked"),
+    };
+    let (critical, reason_code) = entry.extension::<ReasonCode>().unwrap().unwrap();
+    assert!(!critical);
+    assert_eq!(
+        CrlReason::KEY_COMPROMISE,
+        CrlReason::from_raw(reason_code.get_i64().unwrap() as ffi::c_int)
+    );
+
+    // the cRLNumber extension, containing the INTEGER 7
+    let crl_number = [0x06, 0x03, 0x55, 0x1d, 0x14, 0x04, 0x03, 0x02, 0x01, 0x07];
+    assert!(der.windows(crl_number.len()).any(|w| w == crl_number));
+    let authority_key_identifier = [0x06, 0x03, 0x55, 0x1d, 0x23];
+    assert!(der
+        .windows(authority_key_identifier.len())
+        .any(|w| w == authority_key_identifier));
+}
+
 #[test]
 fn test_stack_from_pem() {
     let certs = include_bytes!("../../test/certs.pem");
@@ -509,6 +846,27 @@ fn signature() {
     assert_eq!(algorithm.object().to_string(), "sha256WithRSAEncryption");
 }
 
+#[test]
+fn matches_private_key() {
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    let key = PKey::private_key_from_pem(include_bytes!("../../test/key.pem")).unwrap();
+    cert.matches_private_key(&key).unwrap();
+
+    let other = pkey();
+    let err = cert.matches_private_key(&other).unwrap_err();
+    assert_eq!(
+        err.errors().last().unwrap().reason_code(),
+        ffi::X509_R_KEY_VALUES_MISMATCH
+    );
+
+    let ec = PKey::private_key_from_pem(include_bytes!("../../test/p256.pem")).unwrap();
+    let err = cert.matches_private_key(&ec).unwrap_err();
+    assert_eq!(
+        err.errors().last().unwrap().reason_code(),
+        ffi::X509_R_KEY_TYPE_MISMATCH
+    );
+}
+
 #[test]
 #[allow(clippy::redundant_clone)]
 fn clone_x509() {
@@ -755,6 +1113,34 @@ fn test_load_subject_der() {
     X509Name::from_der(SUBJECT_DER).unwrap();
 }
 
+#[test]
+#[cfg(any(boringssl, ossl110, libressl270))]
+fn name_stack_der() {
+    let root = include_bytes!("../../test/root-ca.pem");
+    let cert = include_bytes!("../../test/cert.pem");
+    let pem = [&root[..], &cert[..], &root[..]].concat();
+
+    let names = X509Name::load_client_ca_pem(&pem).unwrap();
+    assert_eq!(names.len(), 2);
+    let cert = X509::from_pem(cert).unwrap();
+    assert_eq!(
+        names[1].to_der().unwrap(),
+        cert.subject_name().to_der().unwrap()
+    );
+
+    let der = X509Name::stack_to_der(&names).unwrap();
+    let parsed = X509Name::stack_from_der(&der).unwrap();
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(X509Name::stack_to_der(&parsed).unwrap(), der);
+
+    let empty = Stack::<X509Name>::new().unwrap();
+    assert_eq!(X509Name::stack_to_der(&empty).unwrap(), [0x30, 0x00]);
+    assert!(X509Name::stack_from_der(&[0x30, 0x00]).unwrap().is_empty());
+
+    assert!(X509Name::stack_from_der(&der[..der.len() - 1]).is_err());
+    assert!(X509Name::stack_from_der(&[0x30, 0x02, 0x04, 0x00]).is_err());
+}
+
 #[test]
 fn test_convert_to_text() {
     let cert = include_bytes!("../../test/cert.pem");
@@ -1133,6 +1519,199 @@ fn other_name_as_subject_alternative_name() {
     }
 }
 
+#[cfg(ossl110)]
+#[test]
+fn hardware_module_name_as_subject_alternative_name() {
+    let hw_type = Asn1Object::from_str("1.3.6.1.4.1.6175.10.1").unwrap();
+    let name = HardwareModuleName::new(hw_type, b"SN-0042");
+    let assigner = Asn1Object::from_str("1.3.6.1.4.1.6175.10.2").unwrap();
+    let id = PermanentIdentifier::new(Some("device-1"), Some(assigner));
+
+    let mut builder = X509Builder::new().unwrap();
+    let san = SubjectAlternativeName::new()
+        .hardware_module_name(&name)
+        .permanent_identifier(&id)
+        .dns("example.com")
+        .build(&builder.x509v3_context(None, None))
+        .unwrap();
+    builder.append_extension(san).unwrap();
+    let cert = builder.build();
+    let names = cert.subject_alt_names().unwrap();
+
+    let (oid, value) = names[0].other_name().unwrap();
+    assert!(other_name::is_type_id(
+        oid,
+        Asn1Object::from_str("1.3.6.1.5.5.7.8.4")
+    ));
+    assert_eq!(value, name.to_der().unwrap());
+    let parsed = names[0].hardware_module_name().unwrap();
+    assert_eq!(parsed.hw_type().to_string(), "1.3.6.1.4.1.6175.10.1");
+    assert_eq!(parsed.hw_serial_num(), b"SN-0042");
+    assert!(names[0].permanent_identifier().is_none());
+
+    let parsed = names[1].permanent_identifier().unwrap();
+    assert_eq!(parsed.identifier_value(), Some("device-1"));
+    assert_eq!(
+        parsed.assigner().unwrap().to_string(),
+        "1.3.6.1.4.1.6175.10.2"
+    );
+    assert!(names[1].hardware_module_name().is_none());
+
+    assert!(names[2].other_name().is_none());
+    assert!(names[2].hardware_module_name().is_none());
+}
+
+#[test]
+fn hardware_module_name_der() {
+    let hw_type = Asn1Object::from_str("1.3.6.1.4.1.6175.10.1").unwrap();
+    let name = HardwareModuleName::new(hw_type, b"SN-0042");
+    let der = name.to_der().unwrap();
+    assert_eq!(
+        der,
+        Vec::from_hex("301406092b06010401b01f0a010407534e2d30303432").unwrap()
+    );
+
+    let parsed = HardwareModuleName::from_der(&der).unwrap();
+    assert_eq!(parsed.hw_type().to_string(), "1.3.6.1.4.1.6175.10.1");
+    assert_eq!(parsed.hw_serial_num(), b"SN-0042");
+
+    // wrong outer tag
+    assert!(HardwareModuleName::from_der(&[0x31, 0x00]).is_err());
+    // missing serial number
+    assert!(HardwareModuleName::from_der(&[0x30, 0x03, 0x06, 0x01, 0x2a]).is_err());
+    // trailing data
+    assert!(
+        HardwareModuleName::from_der(&[0x30, 0x05, 0x06, 0x01, 0x2a, 0x04, 0x00, 0x00]).is_err()
+    );
+    // truncated
+    assert!(HardwareModuleName::from_der(&[0x30, 0x07, 0x06, 0x01, 0x2a, 0x04, 0x00]).is_err());
+}
+
+#[test]
+fn permanent_identifier_der() {
+    let id = PermanentIdentifier::new(None, None);
+    let der = id.to_der().unwrap();
+    assert_eq!(der, [0x30, 0x00]);
+    let parsed = PermanentIdentifier::from_der(&der).unwrap();
+    assert_eq!(parsed.identifier_value(), None);
+    assert!(parsed.assigner().is_none());
+
+    let id = PermanentIdentifier::new(Some("device-1"), None);
+    let parsed = PermanentIdentifier::from_der(&id.to_der().unwrap()).unwrap();
+    assert_eq!(parsed.identifier_value(), Some("device-1"));
+    assert!(parsed.assigner().is_none());
+
+    // invalid UTF-8
+    assert!(PermanentIdentifier::from_der(&[0x30, 0x03, 0x0c, 0x01, 0xff]).is_err());
+}
+
+#[cfg(all(ossl110, not(osslconf = "OPENSSL_NO_CT")))]
+#[test]
+fn sct_list() {
+    let pkey = pkey();
+    let sct = Vec::from_hex(
+        "00a4b90990b418581487bb13a2cc67700a3c359804f91bdfb8e377cd0ec80ddc10\
+         0000017b2ce6a5a00000040300083006020101020101",
+    )
+    .unwrap();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "foobar.com")
+        .unwrap();
+    let name = name.build();
+
+    let new_builder = || {
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(365).unwrap())
+            .unwrap();
+        builder.set_pubkey(&pkey).unwrap();
+        builder
+    };
+
+    let mut builder = new_builder();
+    builder
+        .append_extension(X509Extension::new_ct_precert_poison().unwrap())
+        .unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+    let precert = builder.build();
+    let text = String::from_utf8(precert.to_text().unwrap()).unwrap();
+    assert!(text.contains("CT Precertificate Poison: critical"));
+    assert!(precert.scts().is_none());
+
+    let mut scts = Stack::new().unwrap();
+    scts.push(Sct::from_tls(&sct).unwrap()).unwrap();
+    let mut builder = new_builder();
+    builder
+        .append_extension(X509Extension::new_sct_list(false, &scts).unwrap())
+        .unwrap();
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+    let cert = X509::from_der(&builder.build().to_der().unwrap()).unwrap();
+
+    let scts = cert.scts().unwrap();
+    assert_eq!(scts.len(), 1);
+    assert_eq!(scts[0].to_tls().unwrap(), sct);
+    assert_eq!(scts[0].timestamp(), 1628545918368);
+    assert_eq!(scts[0].source(), SctSource::X509V3_EXTENSION);
+    assert_eq!(scts[0].log_entry_type(), LogEntryType::PRECERT);
+}
+
+#[test]
+fn serial_allocator() {
+    let persisted = Arc::new(Mutex::new(vec![]));
+    let mut allocator = SerialAllocator::new(BigNum::from_u32(41).unwrap(), {
+        let persisted = persisted.clone();
+        move |next| {
+            persisted.lock().unwrap().push(next.to_string());
+            Ok(())
+        }
+    });
+
+    let first = allocator.allocate().unwrap().to_bn().unwrap();
+    let second = allocator.allocate().unwrap().to_bn().unwrap();
+    let mut counter = BigNum::new().unwrap();
+    counter.rshift(&first, 64).unwrap();
+    assert_eq!(counter.to_string(), "41");
+    counter.rshift(&second, 64).unwrap();
+    assert_eq!(counter.to_string(), "42");
+    assert!(second > first);
+    assert_eq!(*persisted.lock().unwrap(), ["42", "43"]);
+
+    allocator.set_random_bits(0);
+    let mut builder = X509::builder().unwrap();
+    builder.set_serial_number_from(&mut allocator).unwrap();
+    let cert = builder.build();
+    assert_eq!(cert.serial_number().to_bn().unwrap().to_string(), "43");
+    assert_eq!(allocator.counter().to_string(), "44");
+}
+
+#[test]
+fn serial_allocator_errors() {
+    let mut allocator =
+        SerialAllocator::new(BigNum::from_u32(7).unwrap(), |_| Err("disk full".into()));
+    match allocator.allocate() {
+        Ok(_) => panic!("allocation should fail"),
+        Err(e) => assert!(e.to_string().contains("disk full")),
+    }
+    assert_eq!(allocator.counter().to_string(), "7");
+
+    let mut allocator = SerialAllocator::new(BigNum::new().unwrap(), |_| Ok(()));
+    assert!(allocator.allocate().is_err());
+
+    let mut counter = BigNum::new().unwrap();
+    counter.set_bit(96).unwrap();
+    let mut allocator = SerialAllocator::new(counter, |_| Ok(()));
+    assert!(allocator.allocate().is_err());
+    allocator.set_random_bits(62);
+    allocator.allocate().unwrap();
+}
+
 #[test]
 fn test_dist_point() {
     let cert = include_bytes!("../../test/certv3.pem");
@@ -1159,3 +1738,815 @@ fn test_dist_point_null() {
     let cert = X509::from_pem(cert).unwrap();
     assert!(cert.crl_distribution_points().is_none());
 }
+
+#[cfg(ossl110)]
+fn lint_cert<F>(days: u32, configure: F) -> X509
+where
+    F: FnOnce(&mut X509Builder),
+{
+    let pkey = pkey();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "example.com")
+        .unwrap();
+    let name = name.build();
+
+    let mut builder = X509Builder::new().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(days).unwrap())
+        .unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+
+    let mut serial = BigNum::new().unwrap();
+    serial.rand(128, MsbOption::MAYBE_ZERO, false).unwrap();
+    builder
+        .set_serial_number(&serial.to_asn1_integer().unwrap())
+        .unwrap();
+
+    configure(&mut builder);
+    builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+    builder.build()
+}
+
+#[cfg(ossl110)]
+fn lint_subscriber_extensions(builder: &mut X509Builder) {
+    let context = builder.x509v3_context(None, None);
+    let san = SubjectAlternativeName::new()
+        .dns("example.com")
+        .build(&context)
+        .unwrap();
+    builder.append_extension(san).unwrap();
+    builder
+        .append_extension(
+            KeyUsage::new()
+                .digital_signature()
+                .key_encipherment()
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+    builder
+        .append_extension(
+            ExtendedKeyUsage::new()
+                .server_auth()
+                .client_auth()
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+}
+
+#[cfg(ossl110)]
+#[test]
+fn lint_clean_subscriber() {
+    use crate::x509::lint;
+
+    let cert = lint_cert(90, lint_subscriber_extensions);
+    assert!(!cert.is_ca());
+    assert_eq!(
+        cert.key_usage(),
+        Some(KeyUsageFlags::DIGITAL_SIGNATURE | KeyUsageFlags::KEY_ENCIPHERMENT)
+    );
+    assert_eq!(
+        cert.extended_key_usage(),
+        Some(ExtendedKeyUsageFlags::SERVER_AUTH | ExtendedKeyUsageFlags::CLIENT_AUTH)
+    );
+    assert_eq!(lint::lint(&cert).unwrap(), vec![]);
+}
+
+#[cfg(ossl110)]
+#[test]
+fn lint_violations() {
+    use crate::x509::lint::{self, Lint, Severity};
+
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    assert_eq!(cert.key_usage(), None);
+    assert_eq!(cert.extended_key_usage(), None);
+
+    let findings = lint::lint(&cert).unwrap();
+    let lints = findings.iter().map(|f| f.lint()).collect::<Vec<_>>();
+    assert_eq!(
+        lints,
+        vec![
+            Lint::NOT_V3,
+            Lint::VALIDITY_TOO_LONG,
+            Lint::MISSING_SUBJECT_ALT_NAME,
+            Lint::MISSING_EXTENDED_KEY_USAGE,
+        ]
+    );
+    assert!(findings.iter().all(|f| f.severity() == Severity::Error));
+
+    let cert = lint_cert(90, |builder| {
+        let context = builder.x509v3_context(None, None);
+        let san = SubjectAlternativeName::new()
+            .dns("other.example.com")
+            .build(&context)
+            .unwrap();
+        builder.append_extension(san).unwrap();
+        builder
+            .append_extension(KeyUsage::new().key_cert_sign().build().unwrap())
+            .unwrap();
+        builder
+            .append_extension(
+                ExtendedKeyUsage::new()
+                    .server_auth()
+                    .code_signing()
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+    });
+    let lints = lint::lint(&cert)
+        .unwrap()
+        .iter()
+        .map(|f| f.lint())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lints,
+        vec![
+            Lint::COMMON_NAME_NOT_IN_SAN,
+            Lint::SUBSCRIBER_KEY_USAGE,
+            Lint::EXTENDED_KEY_USAGE,
+        ]
+    );
+}
+
+#[cfg(ossl110)]
+#[test]
+fn lint_ca() {
+    use crate::x509::lint::{self, Lint};
+
+    let cert = lint_cert(3650, |builder| {
+        builder
+            .append_extension(BasicConstraints::new().critical().ca().build().unwrap())
+            .unwrap();
+        builder
+            .append_extension(KeyUsage::new().crl_sign().build().unwrap())
+            .unwrap();
+    });
+    assert!(cert.is_ca());
+
+    let lints = lint::lint(&cert)
+        .unwrap()
+        .iter()
+        .map(|f| f.lint())
+        .collect::<Vec<_>>();
+    assert_eq!(lints, vec![Lint::CA_KEY_USAGE]);
+}
+
+#[test]
+fn bundle_order() {
+    use crate::x509::bundle::CertificateBundle;
+
+    let leaf = X509::from_pem(include_bytes!("../../test/leaf.pem")).unwrap();
+    let intermediate = X509::from_pem(include_bytes!("../../test/intermediate-ca.pem")).unwrap();
+    let root = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+    let unrelated = X509::from_pem(include_bytes!("../../test/crl-ca.crt")).unwrap();
+
+    let mut bundle = CertificateBundle::new(vec![
+        root.clone(),
+        intermediate.clone(),
+        unrelated.clone(),
+        leaf.clone(),
+        intermediate.clone(),
+    ]);
+    assert_eq!(bundle.dedup(), 1);
+    assert!(bundle.order() == vec![unrelated]);
+    assert!(bundle.certs() == &[leaf, intermediate, root][..]);
+
+    let pem = bundle.to_pem().unwrap();
+    let bundle = CertificateBundle::from_pem(&pem).unwrap();
+    assert_eq!(bundle.certs().len(), 3);
+}
+
+#[test]
+fn bundle_missing_issuer() {
+    use crate::x509::bundle::CertificateBundle;
+
+    let leaf = X509::from_pem(include_bytes!("../../test/leaf.pem")).unwrap();
+    let intermediate = X509::from_pem(include_bytes!("../../test/intermediate-ca.pem")).unwrap();
+    let root = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+
+    let empty = X509StoreBuilder::new().unwrap().build();
+    let mut store = X509StoreBuilder::new().unwrap();
+    store.add_cert(root.clone()).unwrap();
+    let store = store.build();
+
+    let mut bundle = CertificateBundle::new(vec![intermediate.clone(), leaf.clone()]);
+    assert!(bundle.order().is_empty());
+    assert!(bundle.missing_issuer(&store).is_none());
+    let missing = bundle.missing_issuer(&empty).unwrap();
+    assert!(missing.certificate() == &intermediate);
+    assert_eq!(
+        missing.issuer_name().to_der().unwrap(),
+        root.subject_name().to_der().unwrap()
+    );
+
+    let bundle = CertificateBundle::new(vec![leaf.clone()]);
+    let missing = bundle.missing_issuer(&store).unwrap();
+    assert!(missing.certificate() == &leaf);
+    // leaf.pem has no authority key identifier extension
+    #[cfg(ossl110)]
+    assert!(missing.authority_key_id().is_none());
+
+    let bundle = CertificateBundle::new(vec![leaf, intermediate, root]);
+    assert!(bundle.missing_issuer(&empty).is_none());
+}
+
+#[test]
+#[cfg(not(boringssl))]
+fn load_system_roots() {
+    let mut builder = X509StoreBuilder::new().unwrap();
+    builder.load_system_roots().unwrap();
+    let store = builder.build();
+    assert!(!store.objects().is_empty());
+}
+
+#[test]
+#[cfg(ossl110)]
+fn store_reloader() {
+    use crate::ssl::{SslContext, SslMethod};
+    use crate::x509::store::X509StoreReloader;
+    use std::fs;
+
+    let root = include_bytes!("../../test/root-ca.pem");
+    let intermediate = include_bytes!("../../test/intermediate-ca.pem");
+
+    let dir = std::env::temp_dir().join(format!("rust-openssl-reloader-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let ca_file = dir.join("ca.pem");
+    fs::write(&ca_file, root).unwrap();
+
+    let reloader = X509StoreReloader::new(Some(&ca_file), None).unwrap();
+    assert_eq!(reloader.store().objects().len(), 1);
+    assert!(!reloader.reload_if_changed().unwrap());
+
+    let mut ctx = SslContext::builder(SslMethod::tls()).unwrap();
+    reloader.register(&mut ctx).unwrap();
+    let ctx = ctx.build();
+
+    fs::write(&ca_file, [&root[..], &intermediate[..]].concat()).unwrap();
+    assert!(reloader.reload_if_changed().unwrap());
+    assert_eq!(ctx.cert_store().objects().len(), 2);
+    assert!(!reloader.reload_if_changed().unwrap());
+
+    fs::write(&ca_file, b"not a certificate").unwrap();
+    assert!(reloader.reload_if_changed().is_err());
+    assert_eq!(ctx.cert_store().objects().len(), 2);
+
+    fs::write(&ca_file, intermediate).unwrap();
+    assert!(reloader.reload_if_changed().unwrap());
+    let objects = ctx.cert_store().objects();
+    assert_eq!(objects.len(), 1);
+    let cert = objects.get(0).unwrap().x509().unwrap();
+    assert!(cert == &*X509::from_pem(intermediate).unwrap());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(all(ossl110, not(boringssl)))]
+fn store_snapshot() {
+    let root = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+    let intermediate = X509::from_pem(include_bytes!("../../test/intermediate-ca.pem")).unwrap();
+    let crl_der = include_bytes!("../../test/test.crl");
+
+    let empty = X509StoreBuilder::new().unwrap().build();
+    assert!(empty.snapshot().unwrap().is_empty());
+
+    let mut builder = X509StoreBuilder::new().unwrap();
+    builder.add_cert(root.clone()).unwrap();
+    builder.add_cert(intermediate.clone()).unwrap();
+    let crl_file =
+        std::env::temp_dir().join(format!("rust-openssl-snapshot-{}.crl", std::process::id()));
+    std::fs::write(&crl_file, crl_der).unwrap();
+    builder
+        .add_lookup(X509Lookup::file())
+        .unwrap()
+        .load_crl_file(&crl_file, SslFiletype::ASN1)
+        .unwrap();
+    std::fs::remove_file(&crl_file).unwrap();
+    let store = builder.build();
+
+    let snapshot = store.snapshot().unwrap();
+    assert_eq!(snapshot.len(), 3);
+    assert_eq!(snapshot.len(), store.objects().len());
+    assert_eq!(snapshot.certificates().len(), 2);
+    assert!(snapshot.certificates().contains(&root));
+    assert!(snapshot.certificates().contains(&intermediate));
+    assert_eq!(snapshot.crls().len(), 1);
+    assert_eq!(snapshot.crls()[0].to_der().unwrap(), &crl_der[..]);
+
+    // the snapshot keeps its objects alive after the store is gone
+    drop(store);
+    let pem = snapshot.to_pem().unwrap();
+    let certs = X509::stack_from_pem(&pem).unwrap();
+    assert_eq!(certs, snapshot.certificates());
+    let pem = String::from_utf8(pem).unwrap();
+    let crl = X509Crl::from_pem(&pem.as_bytes()[pem.find("-----BEGIN X509 CRL-----").unwrap()..])
+        .unwrap();
+    assert_eq!(crl.to_der().unwrap(), &crl_der[..]);
+}
+
+#[test]
+fn name_hash() {
+    let cert = X509::from_pem(include_bytes!("../../test/cert.pem")).unwrap();
+    assert_eq!(cert.subject_name().name_hash(), cert.subject_name_hash());
+    assert_eq!(cert.issuer_name().name_hash(), cert.issuer_name_hash());
+
+    let crl = X509Crl::from_der(include_bytes!("../../test/test.crl")).unwrap();
+    let ca = X509::from_pem(include_bytes!("../../test/crl-ca.crt")).unwrap();
+    assert_eq!(crl.issuer_name_hash(), ca.subject_name_hash());
+    assert_eq!(
+        hash_dir::crl_file_name(&crl, 1),
+        format!("{:08x}.r1", ca.subject_name_hash())
+    );
+}
+
+#[test]
+#[cfg(all(ossl110, not(boringssl)))]
+fn hash_dir_lookup() {
+    use std::fs;
+
+    const TEST_T_2024: time_t = 1704067200;
+
+    let root = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+    let intermediate = X509::from_pem(include_bytes!("../../test/intermediate-ca.pem")).unwrap();
+    let leaf = X509::from_pem(include_bytes!("../../test/leaf.pem")).unwrap();
+    let crl = X509Crl::from_der(include_bytes!("../../test/test.crl")).unwrap();
+
+    let dir = std::env::temp_dir().join(format!("rust-openssl-hash-dir-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("00000000.0"), b"stale").unwrap();
+    fs::write(dir.join("README"), b"not hashed").unwrap();
+
+    let mut builder = HashDirBuilder::new();
+    builder
+        .add_cert(root.clone())
+        .add_cert(intermediate.clone())
+        .add_cert(root.clone())
+        .add_crl(crl);
+    builder.write(&dir).unwrap();
+
+    let mut names = fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    names.sort();
+    let mut expected = vec![
+        hash_dir::cert_file_name(&root, 0),
+        hash_dir::cert_file_name(&intermediate, 0),
+        format!(
+            "{:08x}.r0",
+            X509Crl::from_der(include_bytes!("../../test/test.crl"))
+                .unwrap()
+                .issuer_name_hash()
+        ),
+        "README".to_string(),
+    ];
+    expected.sort();
+    assert_eq!(names, expected);
+
+    let mut store = X509StoreBuilder::new().unwrap();
+    let lookup = store.add_lookup(X509Lookup::hash_dir()).unwrap();
+    lookup
+        .add_dir(dir.to_str().unwrap(), SslFiletype::PEM)
+        .unwrap();
+    store.param_mut().set_time(TEST_T_2024);
+    let store = store.build();
+    let mut context = X509StoreContext::new().unwrap();
+    let chain = Stack::new().unwrap();
+    assert!(context
+        .init(&store, &leaf, &chain, |c| c.verify_cert())
+        .unwrap());
+
+    fs::remove_file(dir.join(hash_dir::cert_file_name(&root, 0))).unwrap();
+    fs::write(dir.join("root.pem"), root.to_pem().unwrap()).unwrap();
+    hash_dir::rehash(&dir).unwrap();
+    assert_eq!(
+        fs::read(dir.join(hash_dir::cert_file_name(&root, 0))).unwrap(),
+        root.to_pem().unwrap()
+    );
+    assert!(!dir
+        .join(hash_dir::cert_file_name(&intermediate, 0))
+        .exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(ossl110)]
+fn verify_partial_chain() {
+    const TEST_T_2024: time_t = 1704067200;
+
+    let leaf = X509::from_pem(include_bytes!("../../test/leaf.pem")).unwrap();
+    let intermediate = X509::from_pem(include_bytes!("../../test/intermediate-ca.pem")).unwrap();
+    let chain = Stack::new().unwrap();
+
+    let verify = |partial_chain: bool| {
+        let mut store_bldr = X509StoreBuilder::new().unwrap();
+        store_bldr.add_cert(intermediate.clone()).unwrap();
+        store_bldr
+            .set_flags(X509VerifyFlags::PARTIAL_CHAIN | X509VerifyFlags::NO_ALT_CHAINS)
+            .unwrap();
+        let param = store_bldr.param_mut();
+        param.set_time(TEST_T_2024);
+        if !partial_chain {
+            param.clear_flags(X509VerifyFlags::PARTIAL_CHAIN).unwrap();
+        }
+        let store = store_bldr.build();
+
+        let mut context = X509StoreContext::new().unwrap();
+        context
+            .init(&store, &leaf, &chain, |c| c.verify_cert())
+            .unwrap()
+    };
+
+    assert!(verify(true));
+    assert!(!verify(false));
+}
+
+#[test]
+fn verify_at() {
+    // Jan 1st, 2024 and Jan 1st, 2030
+    const TEST_T_2024: time_t = 1704067200;
+    const TEST_T_2030: time_t = 1893456000;
+
+    let leaf = X509::from_pem(include_bytes!("../../test/leaf.pem")).unwrap();
+    let intermediate = X509::from_pem(include_bytes!("../../test/intermediate-ca.pem")).unwrap();
+    let root = X509::from_pem(include_bytes!("../../test/root-ca.pem")).unwrap();
+
+    let mut chain = Stack::new().unwrap();
+    chain.push(intermediate).unwrap();
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(root).unwrap();
+    let store = store_bldr.build();
+
+    let result = X509StoreContext::verify_at(&leaf, &chain, &store, TEST_T_2024).unwrap();
+    assert_eq!(result, X509VerifyResult::OK);
+
+    let result = X509StoreContext::verify_at(&leaf, &chain, &store, TEST_T_2030).unwrap();
+    assert_eq!(result.error_string(), "certificate has expired");
+}
+
+#[test]
+#[cfg(any(ossl102, libressl261))]
+fn store_context_param_mut() {
+    const TEST_T_2024: time_t = 1704067200;
+
+    let leaf = X509::from_pem(include_bytes!("../../test/leaf.pem")).unwrap();
+    let intermediate = X509::from_pem(include_bytes!("../../test/intermediate-ca.pem")).unwrap();
+    let chain = Stack::new().unwrap();
+
+    let mut store_bldr = X509StoreBuilder::new().unwrap();
+    store_bldr.add_cert(intermediate).unwrap();
+    let store = store_bldr.build();
+
+    let mut context = X509StoreContext::new().unwrap();
+    let verified = context
+        .init(&store, &leaf, &chain, |c| {
+            c.set_time(TEST_T_2024);
+            c.param_mut()
+                .set_flags(X509VerifyFlags::PARTIAL_CHAIN)
+                .unwrap();
+            c.verify_cert()
+        })
+        .unwrap();
+    assert!(verified);
+}
+
+#[test]
+#[cfg(ossl110)]
+fn external_signature() {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    let pkey = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+    // ecdsa-with-SHA256, without parameters
+    let alg = X509Algorithm::from_der(&Vec::from_hex("300a06082a8648ce3d040302").unwrap()).unwrap();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "external signer")
+        .unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(365).unwrap())
+        .unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    builder.set_signature_algorithm(&alg).unwrap();
+
+    let tbs = builder.tbs_der().unwrap();
+    let mut signer = Signer::new(MessageDigest::sha256(), &pkey).unwrap();
+    signer.update(&tbs).unwrap();
+    let signature = signer.sign_to_vec().unwrap();
+    builder.set_signature(&signature).unwrap();
+    let cert = builder.build();
+
+    assert_eq!(
+        cert.signature_algorithm().to_der().unwrap(),
+        alg.to_der().unwrap()
+    );
+    assert_eq!(cert.signature().as_slice(), &signature[..]);
+    assert!(cert.verify(&pkey).unwrap());
+    let cert = X509::from_der(&cert.to_der().unwrap()).unwrap();
+    assert!(cert.verify(&pkey).unwrap());
+}
+
+#[test]
+#[cfg(ossl110)]
+fn external_signature_req() {
+    let pkey = pkey();
+    // sha256WithRSAEncryption, with NULL parameters
+    let alg =
+        X509Algorithm::from_der(&Vec::from_hex("300d06092a864886f70d01010b0500").unwrap()).unwrap();
+
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_version(0).unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    builder.set_signature_algorithm(&alg).unwrap();
+
+    let tbs = builder.tbs_der().unwrap();
+    let mut signer = Signer::new(MessageDigest::sha256(), &pkey).unwrap();
+    signer.update(&tbs).unwrap();
+    let signature = signer.sign_to_vec().unwrap();
+    builder.set_signature(&signature).unwrap();
+    let req = builder.build();
+
+    assert!(req.verify(&pkey).unwrap());
+    let req = X509Req::from_der(&req.to_der().unwrap()).unwrap();
+    assert!(req.verify(&pkey).unwrap());
+
+    // a signature over anything else is rejected
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_version(0).unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    builder.set_signature_algorithm(&alg).unwrap();
+    let mut signer = Signer::new(MessageDigest::sha256(), &pkey).unwrap();
+    signer.update(b"something else").unwrap();
+    let signature = signer.sign_to_vec().unwrap();
+    builder.set_signature(&signature).unwrap();
+    assert!(!builder.build().verify(&pkey).unwrap());
+}
+
+#[test]
+fn algorithm_identifier() {
+    let oid = Asn1Object::from_str("1.2.840.10045.4.3.2").unwrap();
+    let alg = X509Algorithm::new(&oid, None).unwrap();
+    assert_eq!(
+        hex::encode(alg.to_der().unwrap()),
+        "300a06082a8648ce3d040302"
+    );
+    assert_eq!(alg.parameters().unwrap(), None);
+    assert!(alg.rsa_pss_parameters().unwrap().is_none());
+
+    let oid = Asn1Object::from_str("1.2.840.113549.1.1.11").unwrap();
+    let alg = X509Algorithm::new(&oid, Some(&[0x05, 0x00])).unwrap();
+    assert_eq!(
+        hex::encode(alg.to_der().unwrap()),
+        "300d06092a864886f70d01010b0500"
+    );
+    assert_eq!(alg.parameters().unwrap().unwrap(), [0x05, 0x00]);
+
+    let cert = include_bytes!("../../test/cert.pem");
+    let cert = X509::from_pem(cert).unwrap();
+    assert_eq!(
+        cert.signature_algorithm().object().nid(),
+        Nid::SHA256WITHRSAENCRYPTION
+    );
+    assert_eq!(
+        cert.signature_algorithm().parameters().unwrap().unwrap(),
+        [0x05, 0x00]
+    );
+
+    #[cfg(ossl110)]
+    {
+        let crl = include_bytes!("../../test/test.crl");
+        let crl = X509Crl::from_der(crl).unwrap();
+        assert_eq!(
+            crl.signature_algorithm().object().nid(),
+            Nid::SHA256WITHRSAENCRYPTION
+        );
+    }
+
+    assert!(X509Algorithm::new(&oid, Some(&[0x05])).is_err());
+}
+
+#[test]
+fn algorithm_identifier_rsa_pss() {
+    // the encoding required by the CA/Browser Forum Baseline Requirements
+    let alg = X509Algorithm::rsa_pss(MessageDigest::sha256(), 32).unwrap();
+    assert_eq!(
+        hex::encode(alg.to_der().unwrap()),
+        "304106092a864886f70d01010a3034a00f300d06096086480165030402010500a11c301a06092a864886f70d010108300d06096086480165030402010500a203020120"
+    );
+    let params = alg.rsa_pss_parameters().unwrap().unwrap();
+    assert_eq!(params.digest(), Nid::SHA256);
+    assert_eq!(params.mgf1_digest(), Nid::SHA256);
+    assert_eq!(params.salt_len(), 32);
+
+    // all fields take their default values
+    let alg = X509Algorithm::rsa_pss(MessageDigest::sha1(), 20).unwrap();
+    assert_eq!(
+        hex::encode(alg.to_der().unwrap()),
+        "300d06092a864886f70d01010a3000"
+    );
+    let params = alg.rsa_pss_parameters().unwrap().unwrap();
+    assert_eq!(params.digest(), Nid::SHA1);
+    assert_eq!(params.mgf1_digest(), Nid::SHA1);
+    assert_eq!(params.salt_len(), 20);
+}
+
+#[test]
+#[cfg(ossl110)]
+fn rsa_pss_external_signature() {
+    use crate::rsa::Padding;
+    use crate::sign::RsaPssSaltlen;
+
+    let pkey = pkey();
+    let alg = X509Algorithm::rsa_pss(MessageDigest::sha384(), 48).unwrap();
+
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_version(0).unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    builder.set_signature_algorithm(&alg).unwrap();
+
+    let tbs = builder.tbs_der().unwrap();
+    let mut signer = Signer::new(MessageDigest::sha384(), &pkey).unwrap();
+    signer.set_rsa_padding(Padding::PKCS1_PSS).unwrap();
+    signer.set_rsa_mgf1_md(MessageDigest::sha384()).unwrap();
+    signer
+        .set_rsa_pss_saltlen(RsaPssSaltlen::custom(48))
+        .unwrap();
+    signer.update(&tbs).unwrap();
+    let signature = signer.sign_to_vec().unwrap();
+    builder.set_signature(&signature).unwrap();
+
+    let req = X509Req::from_der(&builder.build().to_der().unwrap()).unwrap();
+    assert!(req.verify(&pkey).unwrap());
+    let params = req
+        .signature_algorithm()
+        .rsa_pss_parameters()
+        .unwrap()
+        .unwrap();
+    assert_eq!(params.digest(), Nid::SHA384);
+    assert_eq!(params.salt_len(), 48);
+}
+
+#[test]
+#[cfg(ossl110)]
+fn sign_pss() {
+    let pkey = pkey();
+
+    let mut name = X509Name::builder().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "pss").unwrap();
+    let name = name.build();
+
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(365).unwrap())
+        .unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    builder
+        .sign_pss(&pkey, MessageDigest::sha256(), MessageDigest::sha256(), 32)
+        .unwrap();
+    let cert = X509::from_der(&builder.build().to_der().unwrap()).unwrap();
+
+    assert!(cert.verify(&pkey).unwrap());
+    assert_eq!(
+        cert.signature_algorithm().to_der().unwrap(),
+        X509Algorithm::rsa_pss(MessageDigest::sha256(), 32)
+            .unwrap()
+            .to_der()
+            .unwrap()
+    );
+
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_version(0).unwrap();
+    builder.set_pubkey(&pkey).unwrap();
+    builder
+        .sign_pss(&pkey, MessageDigest::sha512(), MessageDigest::sha1(), 0)
+        .unwrap();
+    let req = X509Req::from_der(&builder.build().to_der().unwrap()).unwrap();
+
+    assert!(req.verify(&pkey).unwrap());
+    let params = req
+        .signature_algorithm()
+        .rsa_pss_parameters()
+        .unwrap()
+        .unwrap();
+    assert_eq!(params.digest(), Nid::SHA512);
+    assert_eq!(params.mgf1_digest(), Nid::SHA1);
+    assert_eq!(params.salt_len(), 0);
+
+    // PSS is only defined for RSA keys
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    let ec_key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_pubkey(&ec_key).unwrap();
+    assert!(builder
+        .sign_pss(
+            &ec_key,
+            MessageDigest::sha256(),
+            MessageDigest::sha256(),
+            32
+        )
+        .is_err());
+}
+
+#[test]
+#[cfg(ossl111)]
+fn eddsa_signing() {
+    for key in [
+        PKey::generate_ed25519().unwrap(),
+        PKey::generate_ed448().unwrap(),
+    ] {
+        let digest = key.default_digest().unwrap().message_digest().unwrap();
+
+        let mut name = X509Name::builder().unwrap();
+        name.append_entry_by_nid(Nid::COMMONNAME, "eddsa").unwrap();
+        let name = name.build();
+
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(365).unwrap())
+            .unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder.sign(&key, digest).unwrap();
+        let cert = X509::from_der(&builder.build().to_der().unwrap()).unwrap();
+        assert!(cert.verify(&key).unwrap());
+        assert_eq!(
+            cert.signature_algorithm().object().nid().as_raw(),
+            key.id().as_raw()
+        );
+        // EdDSA algorithm identifiers have absent parameters
+        assert_eq!(cert.signature_algorithm().parameters().unwrap(), None);
+
+        let mut builder = X509Req::builder().unwrap();
+        builder.set_version(0).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder.sign(&key, digest).unwrap();
+        let req = X509Req::from_der(&builder.build().to_der().unwrap()).unwrap();
+        assert!(req.verify(&key).unwrap());
+
+        let req = CsrProfile::new().build(&key).unwrap();
+        assert!(req.verify(&key).unwrap());
+
+        // RFC 8419 pairs Ed25519 with SHA-512 and Ed448 with SHAKE256 in CMS
+        #[cfg(all(ossl320, not(osslconf = "OPENSSL_NO_CMS")))]
+        {
+            let md = if key.id() == crate::pkey::Id::ED25519 {
+                MessageDigest::sha512()
+            } else {
+                MessageDigest::shake_256()
+            };
+            let mut cms = CmsContentInfo::sign_with_digest(
+                &cert,
+                &key,
+                None,
+                b"eddsa",
+                md,
+                CMSOptions::BINARY,
+            )
+            .unwrap();
+            let mut content = vec![];
+            cms.verify(
+                None,
+                None,
+                None,
+                Some(&mut content),
+                CMSOptions::NO_SIGNER_CERT_VERIFY,
+            )
+            .unwrap();
+            assert_eq!(content, b"eddsa");
+        }
+
+        // a digest cannot be combined with a key which signs messages directly
+        let mut builder = X509Req::builder().unwrap();
+        builder.set_pubkey(&key).unwrap();
+        assert!(builder.sign(&key, MessageDigest::sha256()).is_err());
+    }
+}
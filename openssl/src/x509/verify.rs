@@ -44,6 +44,9 @@ bitflags! {
         const EXTENDED_CRL_SUPPORT = ffi::X509_V_FLAG_EXTENDED_CRL_SUPPORT;
         const USE_DELTAS = ffi::X509_V_FLAG_USE_DELTAS;
         const CHECK_SS_SIGNATURE = ffi::X509_V_FLAG_CHECK_SS_SIGNATURE;
+        /// Looks for issuers in the trusted store before the untrusted chain.
+        ///
+        /// This is always enabled in OpenSSL 1.1.0 and newer.
         #[cfg(ossl102)]
         const TRUSTED_FIRST = ffi::X509_V_FLAG_TRUSTED_FIRST;
         #[cfg(ossl102)]
@@ -52,8 +55,12 @@ bitflags! {
         const SUITEB_192_LOS = ffi::X509_V_FLAG_SUITEB_128_LOS;
         #[cfg(ossl102)]
         const SUITEB_128_LOS = ffi::X509_V_FLAG_SUITEB_192_LOS;
+        /// Accepts a chain ending in any trusted certificate, not just a self-signed root.
+        ///
+        /// This allows an intermediate certificate to be used as a trust anchor.
         #[cfg(ossl102)]
         const PARTIAL_CHAIN = ffi::X509_V_FLAG_PARTIAL_CHAIN;
+        /// Fails verification rather than looking for an alternative chain when the first one is not trusted.
         #[cfg(ossl110)]
         const NO_ALT_CHAINS = ffi::X509_V_FLAG_NO_ALT_CHAINS;
         #[cfg(ossl110)]
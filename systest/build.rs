@@ -77,10 +77,17 @@ fn main() {
         cfg.header("openssl/cms.h");
         if version >= 0x10100000 {
             cfg.header("openssl/kdf.h");
+            cfg.header("openssl/ct.h");
         }
 
         if version >= 0x30000000 {
             cfg.header("openssl/provider.h");
+            cfg.header("openssl/store.h");
+            cfg.header("openssl/ui.h");
+        }
+
+        if version >= 0x30200000 {
+            cfg.header("openssl/hpke.h");
         }
     }
 